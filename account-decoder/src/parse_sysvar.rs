@@ -12,7 +12,10 @@ use solana_sdk::{
     slot_hashes::SlotHashes,
     slot_history::{self, SlotHistory},
     stake_history::{StakeHistory, StakeHistoryEntry},
-    sysvar::{self, fees::Fees, recent_blockhashes::RecentBlockhashes, rewards::Rewards},
+    sysvar::{
+        self, fees::Fees, recent_blockhashes::RecentBlockhashes, rewards::Rewards,
+        vote_group::VoteGroup,
+    },
 };
 
 pub fn parse_sysvar(data: &[u8], pubkey: &Pubkey) -> Result<SysvarAccountType, ParseAccountError> {
@@ -77,6 +80,20 @@ pub fn parse_sysvar(data: &[u8], pubkey: &Pubkey) -> Result<SysvarAccountType, P
                     .collect();
                 SysvarAccountType::StakeHistory(stake_history)
             })
+        } else if pubkey == &sysvar::vote_group::id() {
+            deserialize::<VoteGroup>(data).ok().map(|vote_group| {
+                SysvarAccountType::VoteGroup(UiVoteGroup {
+                    slot: vote_group.slot,
+                    seed: vote_group.seed.to_string(),
+                    members: vote_group
+                        .members
+                        .iter()
+                        .map(|member| member.to_string())
+                        .collect(),
+                    rotation_window_slots: vote_group.rotation_window_slots,
+                    window_start: vote_group.window_start,
+                })
+            })
         } else {
             None
         }
@@ -98,6 +115,7 @@ pub enum SysvarAccountType {
     SlotHashes(Vec<UiSlotHashEntry>),
     SlotHistory(UiSlotHistory),
     StakeHistory(Vec<UiStakeHistoryEntry>),
+    VoteGroup(UiVoteGroup),
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Default)]
@@ -210,6 +228,16 @@ pub struct UiStakeHistoryEntry {
     pub stake_history: StakeHistoryEntry,
 }
 
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UiVoteGroup {
+    pub slot: Slot,
+    pub seed: StringAmount,
+    pub members: Vec<String>,
+    pub rotation_window_slots: u64,
+    pub window_start: Slot,
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -325,5 +353,25 @@ mod test {
 
         let bad_data = vec![0; 4];
         assert!(parse_sysvar(&bad_data, &sysvar::stake_history::id()).is_err());
+
+        let member = solana_sdk::pubkey::new_rand();
+        let vote_group = VoteGroup {
+            slot: 3,
+            seed: u64::MAX,
+            members: vec![member],
+            rotation_window_slots: 4,
+            window_start: 0,
+        };
+        let vote_group_sysvar = create_account_for_test(&vote_group);
+        assert_eq!(
+            parse_sysvar(&vote_group_sysvar.data, &sysvar::vote_group::id()).unwrap(),
+            SysvarAccountType::VoteGroup(UiVoteGroup {
+                slot: 3,
+                seed: u64::MAX.to_string(),
+                members: vec![member.to_string()],
+                rotation_window_slots: 4,
+                window_start: 0,
+            }),
+        );
     }
 }
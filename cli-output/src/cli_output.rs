@@ -16,7 +16,7 @@ use {
     solana_clap_utils::keypair::SignOnly,
     solana_client::rpc_response::{
         RpcAccountBalance, RpcContactInfo, RpcInflationGovernor, RpcInflationRate, RpcKeyedAccount,
-        RpcSupply, RpcVoteAccountInfo,
+        RpcSupply, RpcVoteAccountInfo, RpcVoteGroupWindow,
     },
     solana_sdk::{
         clock::{Epoch, Slot, UnixTimestamp},
@@ -1492,6 +1492,262 @@ pub struct CliLeaderScheduleEntry {
     pub leader: String,
 }
 
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliVoteGroup {
+    pub slot: Slot,
+    pub seed: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub distance: Option<u32>,
+    pub algorithm: String,
+    pub members: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vote_account: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_member: Option<bool>,
+}
+
+impl QuietDisplay for CliVoteGroup {}
+impl VerboseDisplay for CliVoteGroup {}
+
+impl fmt::Display for CliVoteGroup {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln_name_value(f, "Slot:", &self.slot.to_string())?;
+        writeln_name_value(f, "Seed:", &self.seed.to_string())?;
+        writeln_name_value(f, "Algorithm:", &self.algorithm)?;
+        if let Some(distance) = self.distance {
+            writeln_name_value(f, "Distance:", &distance.to_string())?;
+        }
+        if let (Some(vote_account), Some(is_member)) = (&self.vote_account, self.is_member) {
+            let membership = if is_member {
+                style("member").green().to_string()
+            } else {
+                style("not a member").yellow().to_string()
+            };
+            writeln_name_value(
+                f,
+                "Vote Account:",
+                &format!("{} ({})", vote_account, membership),
+            )?;
+        }
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{}",
+            style(format!("Members ({}):", self.members.len())).bold()
+        )?;
+        for (i, member) in self.members.iter().enumerate() {
+            if self.vote_account.as_ref() == Some(member) {
+                writeln!(f, "{}", style(format!("* {:<4} {}", i, member)).bold())?;
+            } else {
+                writeln!(f, "  {:<4} {}", i, member)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliVoteGroupSimulation {
+    pub epochs: u64,
+    pub slots: u64,
+    pub group_size: usize,
+    pub algorithm: String,
+    /// how often every voter would be selected if groups were perfectly fair
+    pub expected_frequency: f64,
+    pub min_selections: u64,
+    pub max_selections: u64,
+    /// standard deviation of the selection counts over their mean
+    pub coefficient_of_variation: f64,
+    pub voters: Vec<CliVoteGroupSimulationVoter>,
+}
+
+impl QuietDisplay for CliVoteGroupSimulation {}
+impl VerboseDisplay for CliVoteGroupSimulation {
+    fn write_str(&self, w: &mut dyn std::fmt::Write) -> std::fmt::Result {
+        write!(w, "{}", self)?;
+        writeln!(w)?;
+        writeln!(
+            w,
+            "{}",
+            style(format!(
+                "  {:<44}  {:>10}  {:>10}  {:>12}",
+                "Vote Account", "Selections", "Frequency", "Longest Gap"
+            ))
+            .bold()
+        )?;
+        for voter in &self.voters {
+            writeln!(
+                w,
+                "  {:<44}  {:>10}  {:>9.4}%  {:>12}",
+                voter.vote_account,
+                voter.selections,
+                voter.frequency * 100.,
+                voter.longest_gap
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for CliVoteGroupSimulation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln_name_value(f, "Epochs:", &self.epochs.to_string())?;
+        writeln_name_value(f, "Simulated slots:", &self.slots.to_string())?;
+        writeln_name_value(f, "Voters:", &self.voters.len().to_string())?;
+        writeln_name_value(f, "Group size:", &self.group_size.to_string())?;
+        writeln_name_value(f, "Algorithm:", &self.algorithm)?;
+        writeln_name_value(
+            f,
+            "Expected frequency:",
+            &format!("{:.4}%", self.expected_frequency * 100.),
+        )?;
+        writeln_name_value(
+            f,
+            "Selections:",
+            &format!("min {}, max {}", self.min_selections, self.max_selections),
+        )?;
+        writeln_name_value(
+            f,
+            "Skew (coefficient of variation):",
+            &format!("{:.4}", self.coefficient_of_variation),
+        )?;
+        if let Some(voter) = self.voters.iter().max_by_key(|voter| voter.longest_gap) {
+            writeln_name_value(
+                f,
+                "Longest gap:",
+                &format!("{} slots ({})", voter.longest_gap, voter.vote_account),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliVoteGroupSimulationVoter {
+    pub vote_account: String,
+    pub selections: u64,
+    pub frequency: f64,
+    /// most consecutive simulated slots the voter was left out of the group
+    pub longest_gap: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliVoteGroupSchedule {
+    pub epoch: Epoch,
+    pub windows: Vec<RpcVoteGroupWindow>,
+}
+
+impl CliVoteGroupSchedule {
+    /// one `first_slot,last_slot,seed,members` row per window, members are space separated
+    pub fn to_csv(&self) -> String {
+        let mut csv = "first_slot,last_slot,seed,members\n".to_string();
+        for window in &self.windows {
+            csv.push_str(&format!(
+                "{},{},{},{}\n",
+                window.first_slot,
+                window.last_slot,
+                window.seed,
+                window.members.join(" ")
+            ));
+        }
+        csv
+    }
+}
+
+impl QuietDisplay for CliVoteGroupSchedule {}
+impl VerboseDisplay for CliVoteGroupSchedule {}
+
+impl fmt::Display for CliVoteGroupSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln_name_value(f, "Epoch:", &self.epoch.to_string())?;
+        writeln_name_value(f, "Windows:", &self.windows.len().to_string())?;
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{}",
+            style(format!("  {:<23}  {:<20}  Members", "Slots", "Seed")).bold()
+        )?;
+        for window in &self.windows {
+            writeln!(
+                f,
+                "  {:<23}  {:<20}  {}",
+                format!("{}-{}", window.first_slot, window.last_slot),
+                window.seed,
+                window.members.join(" ")
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliVoteGroupVerification {
+    pub signature: String,
+    /// the slot the transaction landed in
+    pub slot: Slot,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub err: Option<String>,
+    pub votes: Vec<CliVoteGroupVerifiedVote>,
+}
+
+impl QuietDisplay for CliVoteGroupVerification {}
+impl VerboseDisplay for CliVoteGroupVerification {}
+
+impl fmt::Display for CliVoteGroupVerification {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln_name_value(f, "Signature:", &self.signature)?;
+        writeln_name_value(f, "Landed in slot:", &self.slot.to_string())?;
+        if let Some(err) = &self.err {
+            writeln_name_value(f, "Transaction error:", err)?;
+        }
+        for vote in &self.votes {
+            writeln!(f)?;
+            writeln_name_value(f, "Vote Account:", &vote.vote_account)?;
+            writeln_name_value(f, "Voted slot:", &vote.voted_slot.to_string())?;
+            writeln_name_value(f, "Group seed:", &vote.seed.to_string())?;
+            let membership = if vote.is_member {
+                style("member of the voted slot's group")
+                    .green()
+                    .to_string()
+            } else {
+                style("not a member of the voted slot's group")
+                    .red()
+                    .to_string()
+            };
+            writeln_name_value(f, "Verdict:", &membership)?;
+            writeln!(
+                f,
+                "{}",
+                style(format!("Members ({}):", vote.members.len())).bold()
+            )?;
+            for (i, member) in vote.members.iter().enumerate() {
+                if *member == vote.vote_account {
+                    writeln!(f, "{}", style(format!("* {:<4} {}", i, member)).bold())?;
+                } else {
+                    writeln!(f, "  {:<4} {}", i, member)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliVoteGroupVerifiedVote {
+    pub vote_account: String,
+    /// the slot group membership is checked against, the first slot of the vote
+    pub voted_slot: Slot,
+    pub seed: u64,
+    pub members: Vec<String>,
+    pub is_member: bool,
+}
+
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CliInflation {
@@ -2559,4 +2815,29 @@ mod tests {
             "verbose"
         );
     }
+
+    #[test]
+    fn test_vote_group_schedule_csv() {
+        let schedule = CliVoteGroupSchedule {
+            epoch: 3,
+            windows: vec![
+                RpcVoteGroupWindow {
+                    first_slot: 96,
+                    last_slot: 99,
+                    seed: 42,
+                    members: vec!["a".to_string(), "b".to_string()],
+                },
+                RpcVoteGroupWindow {
+                    first_slot: 100,
+                    last_slot: 103,
+                    seed: 7,
+                    members: vec!["b".to_string()],
+                },
+            ],
+        };
+        assert_eq!(
+            schedule.to_csv(),
+            "first_slot,last_slot,seed,members\n96,99,42,a b\n100,103,7,b\n"
+        );
+    }
 }
@@ -1,6 +1,6 @@
 use crate::{
     cluster_query::*, feature::*, inflation::*, memo::*, nonce::*, program::*, spend_utils::*,
-    stake::*, validator_info::*, vote::*,
+    stake::*, validator_info::*, vote::*, vote_group::*,
 };
 use clap::{value_t_or_exit, App, AppSettings, Arg, ArgMatches, SubCommand};
 use log::*;
@@ -354,6 +354,7 @@ pub enum CliCommand {
         withdraw_authority: SignerIndex,
         memo: Option<String>,
     },
+    VoteGroup(VoteGroupCliCommand),
     // Wallet Commands
     Address,
     Airdrop {
@@ -776,6 +777,9 @@ pub fn parse_command(
         ("withdraw-from-vote-account", Some(matches)) => {
             parse_withdraw_from_vote_account(matches, default_signer, wallet_manager)
         }
+        ("vote-group", Some(matches)) => {
+            parse_vote_group_subcommand(matches, default_signer, wallet_manager)
+        }
         // Wallet Commands
         ("address", Some(matches)) => Ok(CliCommandInfo {
             command: CliCommand::Address,
@@ -1868,6 +1872,9 @@ pub fn process_command(config: &CliConfig) -> ProcessResult {
             *withdraw_authority,
             memo.as_ref(),
         ),
+        CliCommand::VoteGroup(vote_group_subcommand) => {
+            process_vote_group_subcommand(&rpc_client, config, vote_group_subcommand)
+        }
 
         // Wallet Commands
 
@@ -2271,6 +2278,7 @@ pub fn app<'ab, 'v>(name: &str, about: &'ab str, version: &'v str) -> App<'ab, '
         )
         .validator_info_subcommands()
         .vote_subcommands()
+        .vote_group_subcommands()
 }
 
 #[cfg(test)]
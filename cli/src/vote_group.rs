@@ -0,0 +1,804 @@
+use crate::cli::{CliCommand, CliCommandInfo, CliConfig, CliError, ProcessResult};
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use solana_clap_utils::{
+    input_parsers::{pubkey_of_signer, value_of},
+    input_validators::{is_parsable, is_slot, is_valid_pubkey},
+    keypair::*,
+};
+use solana_cli_output::{
+    CliVoteGroup, CliVoteGroupSchedule, CliVoteGroupSimulation, CliVoteGroupSimulationVoter,
+    CliVoteGroupVerification, CliVoteGroupVerifiedVote,
+};
+use solana_client::{
+    rpc_client::RpcClient,
+    rpc_config::{RpcConfirmedTransactionConfig, RpcVoteGroupScheduleConfig},
+};
+use solana_remote_wallet::remote_wallet::RemoteWalletManager;
+use solana_sdk::{
+    clock::{Epoch, Slot},
+    commitment_config::CommitmentConfig,
+    epoch_schedule::EpochSchedule,
+    hash::hashv,
+    program_utils::limited_deserialize,
+    pubkey::Pubkey,
+    signature::Signature,
+    transaction::Transaction,
+    vote_group_gen::{seed_for_slot, ExclusionList, SelectionAlgorithm, VoteGroupGenerator},
+};
+use solana_transaction_status::UiTransactionEncoding;
+use solana_vote_program::vote_instruction::VoteInstruction;
+use std::{collections::HashMap, sync::Arc};
+
+#[derive(Debug, PartialEq)]
+pub enum VoteGroupCliCommand {
+    Show {
+        slot: Option<Slot>,
+        vote_account_pubkey: Option<Pubkey>,
+    },
+    Simulate {
+        epochs: u64,
+        group_size: Option<usize>,
+        algorithm: Option<SelectionAlgorithm>,
+    },
+    Verify {
+        signature: Signature,
+    },
+    Schedule {
+        epoch: Option<Epoch>,
+        csv: bool,
+    },
+}
+
+pub trait VoteGroupSubCommands {
+    fn vote_group_subcommands(self) -> Self;
+}
+
+impl VoteGroupSubCommands for App<'_, '_> {
+    fn vote_group_subcommands(self) -> Self {
+        self.subcommand(
+            SubCommand::with_name("vote-group")
+                .about("Vote group commands")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    SubCommand::with_name("show")
+                        .about("Show the vote group for a slot")
+                        .arg(pubkey!(
+                            Arg::with_name("vote_account_pubkey")
+                                .index(1)
+                                .value_name("VOTE_ACCOUNT_ADDRESS"),
+                            "Vote account to look for in the group [default: the vote account of the configured keypair]. "
+                        ))
+                        .arg(
+                            Arg::with_name("slot")
+                                .long("slot")
+                                .takes_value(true)
+                                .value_name("SLOT")
+                                .validator(is_slot)
+                                .help("Show the vote group for this slot [default: current slot]"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("simulate")
+                        .about("Simulate vote group selection over the cluster's current voters")
+                        .arg(
+                            Arg::with_name("epochs")
+                                .long("epochs")
+                                .takes_value(true)
+                                .value_name("EPOCHS")
+                                .default_value("1")
+                                .validator(is_parsable::<u64>)
+                                .help("Number of epochs of slots to simulate"),
+                        )
+                        .arg(
+                            Arg::with_name("group_size")
+                                .long("group-size")
+                                .takes_value(true)
+                                .value_name("SIZE")
+                                .validator(is_parsable::<usize>)
+                                .help("Group size to simulate [default: size of the current group]"),
+                        )
+                        .arg(
+                            Arg::with_name("algorithm")
+                                .long("algorithm")
+                                .takes_value(true)
+                                .value_name("ALGORITHM")
+                                .possible_values(&["v1", "v2", "v3"])
+                                .help("Selection algorithm to simulate [default: algorithm of the current group]"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("verify")
+                        .about("Check that the votes of a transaction came from members of the voted slot's group")
+                        .arg(
+                            Arg::with_name("signature")
+                                .index(1)
+                                .value_name("TRANSACTION_SIGNATURE")
+                                .takes_value(true)
+                                .required(true)
+                                .help("Signature of the vote transaction to verify"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("schedule")
+                        .about("Show the vote group of every rotation window of an epoch")
+                        .arg(
+                            Arg::with_name("epoch")
+                                .long("epoch")
+                                .takes_value(true)
+                                .value_name("EPOCH")
+                                .validator(is_parsable::<Epoch>)
+                                .help("Epoch to show the schedule for [default: current epoch]"),
+                        )
+                        .arg(
+                            Arg::with_name("csv")
+                                .long("csv")
+                                .takes_value(false)
+                                .help("Write the schedule as CSV, one row per window, in place of --output"),
+                        ),
+                ),
+        )
+    }
+}
+
+pub fn parse_vote_group_subcommand(
+    matches: &ArgMatches<'_>,
+    default_signer: &DefaultSigner,
+    wallet_manager: &mut Option<Arc<RemoteWalletManager>>,
+) -> Result<CliCommandInfo, CliError> {
+    let response = match matches.subcommand() {
+        ("show", Some(matches)) => {
+            let slot = value_of(matches, "slot");
+            let vote_account_pubkey =
+                pubkey_of_signer(matches, "vote_account_pubkey", wallet_manager)?;
+            // the configured keypair is only used to find a vote account to highlight, so a
+            // missing one is not an error here
+            let signers = if vote_account_pubkey.is_some() {
+                vec![]
+            } else {
+                default_signer
+                    .signer_from_path(matches, wallet_manager)
+                    .map(|signer| vec![signer])
+                    .unwrap_or_default()
+            };
+            CliCommandInfo {
+                command: CliCommand::VoteGroup(VoteGroupCliCommand::Show {
+                    slot,
+                    vote_account_pubkey,
+                }),
+                signers,
+            }
+        }
+        ("simulate", Some(matches)) => {
+            let epochs = value_of(matches, "epochs").unwrap();
+            let group_size = value_of(matches, "group_size");
+            let algorithm = value_of(matches, "algorithm");
+            CliCommandInfo {
+                command: CliCommand::VoteGroup(VoteGroupCliCommand::Simulate {
+                    epochs,
+                    group_size,
+                    algorithm,
+                }),
+                signers: vec![],
+            }
+        }
+        ("schedule", Some(matches)) => CliCommandInfo {
+            command: CliCommand::VoteGroup(VoteGroupCliCommand::Schedule {
+                epoch: value_of(matches, "epoch"),
+                csv: matches.is_present("csv"),
+            }),
+            signers: vec![],
+        },
+        ("verify", Some(matches)) => match matches.value_of("signature").unwrap().parse() {
+            Ok(signature) => CliCommandInfo {
+                command: CliCommand::VoteGroup(VoteGroupCliCommand::Verify { signature }),
+                signers: vec![],
+            },
+            _ => return Err(CliError::BadParameter("Invalid signature".to_string())),
+        },
+        _ => unreachable!(),
+    };
+    Ok(response)
+}
+
+pub fn process_vote_group_subcommand(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    vote_group_subcommand: &VoteGroupCliCommand,
+) -> ProcessResult {
+    match vote_group_subcommand {
+        VoteGroupCliCommand::Show {
+            slot,
+            vote_account_pubkey,
+        } => process_show(rpc_client, config, *slot, vote_account_pubkey.as_ref()),
+        VoteGroupCliCommand::Simulate {
+            epochs,
+            group_size,
+            algorithm,
+        } => process_simulate(rpc_client, config, *epochs, *group_size, *algorithm),
+        VoteGroupCliCommand::Verify { signature } => process_verify(rpc_client, config, signature),
+        VoteGroupCliCommand::Schedule { epoch, csv } => {
+            process_schedule(rpc_client, config, *epoch, *csv)
+        }
+    }
+}
+
+// the configured keypair is usually a node identity rather than a vote account, so look up the
+// vote account it votes with
+fn vote_account_for_identity(rpc_client: &RpcClient, identity: &Pubkey) -> Pubkey {
+    let node_pubkey = identity.to_string();
+    rpc_client
+        .get_vote_accounts()
+        .ok()
+        .and_then(|vote_accounts| {
+            vote_accounts
+                .current
+                .into_iter()
+                .chain(vote_accounts.delinquent.into_iter())
+                .find(|vote_account| vote_account.node_pubkey == node_pubkey)
+        })
+        .and_then(|vote_account| vote_account.vote_pubkey.parse().ok())
+        .unwrap_or(*identity)
+}
+
+fn process_show(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    slot: Option<Slot>,
+    vote_account_pubkey: Option<&Pubkey>,
+) -> ProcessResult {
+    let slot = match slot {
+        Some(slot) => slot,
+        None => rpc_client.get_slot()?,
+    };
+    let vote_group = rpc_client.get_vote_group_for_slot(slot)?.ok_or_else(|| {
+        CliError::RpcRequestError(format!("Vote group not available for slot {}", slot))
+    })?;
+
+    let vote_account = match vote_account_pubkey {
+        Some(vote_account_pubkey) => Some(*vote_account_pubkey),
+        None => config
+            .pubkey()
+            .ok()
+            .map(|identity| vote_account_for_identity(rpc_client, &identity)),
+    }
+    .map(|vote_account| vote_account.to_string());
+    let is_member = vote_account
+        .as_ref()
+        .map(|vote_account| vote_group.members.contains(vote_account));
+
+    let cli_vote_group = CliVoteGroup {
+        slot: vote_group.slot,
+        seed: vote_group.seed,
+        distance: vote_group.distance,
+        algorithm: vote_group.algorithm,
+        members: vote_group.members,
+        vote_account,
+        is_member,
+    };
+    Ok(config.output_format.formatted_string(&cli_vote_group))
+}
+
+/// the (vote account, voted slot) of every vote instruction in `transaction`, the voted slot is
+/// the one the runtime checks group membership against
+pub fn votes_in_transaction(transaction: &Transaction) -> Vec<(Pubkey, Slot)> {
+    let message = &transaction.message;
+    message
+        .instructions
+        .iter()
+        .filter(|instruction| {
+            message
+                .account_keys
+                .get(instruction.program_id_index as usize)
+                == Some(&solana_vote_program::id())
+        })
+        .filter_map(|instruction| {
+            let vote = match limited_deserialize::<VoteInstruction>(&instruction.data) {
+                Ok(VoteInstruction::Vote(vote)) | Ok(VoteInstruction::VoteSwitch(vote, _)) => vote,
+                _ => return None,
+            };
+            let vote_account = instruction
+                .accounts
+                .first()
+                .and_then(|index| message.account_keys.get(*index as usize))?;
+            Some((*vote_account, *vote.slots.first()?))
+        })
+        .collect()
+}
+
+fn process_verify(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    signature: &Signature,
+) -> ProcessResult {
+    let confirmed_transaction = rpc_client.get_confirmed_transaction_with_config(
+        signature,
+        RpcConfirmedTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: Some(CommitmentConfig::confirmed()),
+        },
+    )?;
+    let transaction = confirmed_transaction
+        .transaction
+        .transaction
+        .decode()
+        .ok_or_else(|| {
+            CliError::RpcRequestError(format!("Unable to decode transaction {}", signature))
+        })?;
+    let votes = votes_in_transaction(&transaction);
+    if votes.is_empty() {
+        return Err(
+            CliError::BadParameter(format!("Transaction {} holds no votes", signature)).into(),
+        );
+    }
+
+    let votes = votes
+        .into_iter()
+        .map(|(vote_account, voted_slot)| {
+            let vote_group = rpc_client
+                .get_vote_group_for_slot(voted_slot)?
+                .ok_or_else(|| {
+                    CliError::RpcRequestError(format!(
+                        "Vote group not available for slot {}",
+                        voted_slot
+                    ))
+                })?;
+            let vote_account = vote_account.to_string();
+            Ok(CliVoteGroupVerifiedVote {
+                is_member: vote_group.members.contains(&vote_account),
+                vote_account,
+                voted_slot,
+                seed: vote_group.seed,
+                members: vote_group.members,
+            })
+        })
+        .collect::<Result<Vec<_>, Box<dyn std::error::Error>>>()?;
+
+    let verification = CliVoteGroupVerification {
+        signature: signature.to_string(),
+        slot: confirmed_transaction.slot,
+        err: confirmed_transaction
+            .transaction
+            .meta
+            .and_then(|meta| meta.err)
+            .map(|err| err.to_string()),
+        votes,
+    };
+    Ok(config.output_format.formatted_string(&verification))
+}
+
+fn process_schedule(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    epoch: Option<Epoch>,
+    csv: bool,
+) -> ProcessResult {
+    let epoch = match epoch {
+        Some(epoch) => epoch,
+        None => rpc_client.get_epoch_info()?.epoch,
+    };
+    let epoch_schedule = rpc_client.get_epoch_schedule()?;
+    let last_slot = epoch_schedule.get_last_slot_in_epoch(epoch);
+
+    // the node answers a page of windows at a time, walk them to the end of the epoch
+    let mut windows = vec![];
+    let mut slot = epoch_schedule.get_first_slot_in_epoch(epoch);
+    while slot <= last_slot {
+        let page = rpc_client.get_vote_group_schedule(
+            slot,
+            RpcVoteGroupScheduleConfig {
+                limit: None,
+                commitment: Some(config.commitment),
+            },
+        )?;
+        let next_slot = match page.last() {
+            Some(window) => window.last_slot + 1,
+            None => break,
+        };
+        windows.extend(page);
+        slot = next_slot;
+    }
+    if windows.is_empty() {
+        return Err(CliError::RpcRequestError(format!(
+            "Vote group schedule not available for epoch {}",
+            epoch
+        ))
+        .into());
+    }
+
+    let schedule = CliVoteGroupSchedule { epoch, windows };
+    if csv {
+        Ok(schedule.to_csv())
+    } else {
+        Ok(config.output_format.formatted_string(&schedule))
+    }
+}
+
+fn process_simulate(
+    rpc_client: &RpcClient,
+    config: &CliConfig,
+    epochs: u64,
+    group_size: Option<usize>,
+    algorithm: Option<SelectionAlgorithm>,
+) -> ProcessResult {
+    let vote_accounts = rpc_client.get_vote_accounts()?;
+    let voters: HashMap<Pubkey, Pubkey> = vote_accounts
+        .current
+        .iter()
+        .chain(vote_accounts.delinquent.iter())
+        .filter_map(|vote_account| {
+            Some((
+                vote_account.vote_pubkey.parse().ok()?,
+                vote_account.node_pubkey.parse().ok()?,
+            ))
+        })
+        .collect();
+
+    // anything not given on the command line is taken from the group the cluster uses right now
+    let current_group = if group_size.is_none() || algorithm.is_none() {
+        rpc_client.get_vote_group_for_slot(rpc_client.get_slot()?)?
+    } else {
+        None
+    };
+    let group_size = group_size
+        .or_else(|| current_group.as_ref().map(|group| group.members.len()))
+        .ok_or_else(|| {
+            CliError::BadParameter(
+                "Current vote group not available, specify --group-size".to_string(),
+            )
+        })?;
+    let algorithm = algorithm
+        .or_else(|| {
+            current_group
+                .as_ref()
+                .and_then(|group| group.algorithm.parse().ok())
+        })
+        .unwrap_or_default();
+
+    let generator =
+        VoteGroupGenerator::new_with_exclusions(&voters, group_size, &ExclusionList::legacy())
+            .with_algorithm(algorithm);
+    if generator.voters().is_empty() {
+        return Err(CliError::RpcRequestError("No voters to simulate".to_string()).into());
+    }
+
+    let epoch_schedule = rpc_client.get_epoch_schedule()?;
+    let first_epoch = rpc_client.get_epoch_info()?.epoch;
+    let simulation = simulate_vote_groups(&generator, &epoch_schedule, first_epoch, epochs);
+    Ok(config.output_format.formatted_string(&simulation))
+}
+
+/// walks the groups of `epochs` epochs worth of slots starting at `first_epoch`, the bank hash
+/// seeding each epoch is made up so the run is repeatable
+pub fn simulate_vote_groups(
+    generator: &VoteGroupGenerator,
+    epoch_schedule: &EpochSchedule,
+    first_epoch: Epoch,
+    epochs: u64,
+) -> CliVoteGroupSimulation {
+    let voters = generator.voters();
+    let mut selections = vec![0u64; voters.len()];
+    let mut longest_gaps = vec![0u64; voters.len()];
+    let mut last_selected: Vec<Option<u64>> = vec![None; voters.len()];
+
+    let mut slots = 0;
+    for epoch in first_epoch..first_epoch + epochs {
+        let epoch_bank_hash = hashv(&[b"vote-group-simulate", &epoch.to_le_bytes()]);
+        let first_slot = epoch_schedule.get_first_slot_in_epoch(epoch);
+        for slot in first_slot..first_slot + epoch_schedule.get_slots_in_epoch(epoch) {
+            let seed = seed_for_slot(slot, epoch, &epoch_bank_hash);
+            for member in generator.group_for_seed(seed) {
+                let i = match voters.binary_search(&member) {
+                    Ok(i) => i,
+                    Err(_) => continue,
+                };
+                // v1 groups may walk over the same voter twice, it still only votes once
+                if last_selected[i] == Some(slots) {
+                    continue;
+                }
+                let gap = last_selected[i].map_or(slots, |last| slots - last - 1);
+                longest_gaps[i] = longest_gaps[i].max(gap);
+                last_selected[i] = Some(slots);
+                selections[i] += 1;
+            }
+            slots += 1;
+        }
+    }
+    for (longest_gap, last) in longest_gaps.iter_mut().zip(last_selected.iter()) {
+        let gap = last.map_or(slots, |last| slots - last - 1);
+        *longest_gap = (*longest_gap).max(gap);
+    }
+
+    let mean = selections.iter().sum::<u64>() as f64 / voters.len() as f64;
+    let variance = selections
+        .iter()
+        .map(|count| (*count as f64 - mean).powi(2))
+        .sum::<f64>()
+        / voters.len() as f64;
+    let coefficient_of_variation = if mean > 0. {
+        variance.sqrt() / mean
+    } else {
+        0.
+    };
+
+    let mut cli_voters: Vec<_> = voters
+        .iter()
+        .zip(selections.iter().zip(longest_gaps.iter()))
+        .map(
+            |(vote_account, (selections, longest_gap))| CliVoteGroupSimulationVoter {
+                vote_account: vote_account.to_string(),
+                selections: *selections,
+                frequency: if slots > 0 {
+                    *selections as f64 / slots as f64
+                } else {
+                    0.
+                },
+                longest_gap: *longest_gap,
+            },
+        )
+        .collect();
+    cli_voters.sort_by(|a, b| a.selections.cmp(&b.selections));
+
+    CliVoteGroupSimulation {
+        epochs,
+        slots,
+        group_size: generator.group_size(),
+        algorithm: format!("{:?}", generator.algorithm()).to_lowercase(),
+        expected_frequency: generator.group_size().min(voters.len()) as f64 / voters.len() as f64,
+        min_selections: selections.iter().copied().min().unwrap_or(0),
+        max_selections: selections.iter().copied().max().unwrap_or(0),
+        coefficient_of_variation,
+        voters: cli_voters,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{app, parse_command};
+    use solana_sdk::{
+        hash::Hash,
+        signature::{read_keypair_file, write_keypair, Keypair, Signer},
+        system_instruction,
+    };
+    use solana_vote_program::{vote_instruction, vote_state::Vote};
+    use tempfile::NamedTempFile;
+
+    fn make_tmp_file() -> (String, NamedTempFile) {
+        let tmp_file = NamedTempFile::new().unwrap();
+        (String::from(tmp_file.path().to_str().unwrap()), tmp_file)
+    }
+
+    #[test]
+    fn test_parse_vote_group_show() {
+        let test_commands = app("test", "desc", "version");
+        let default_keypair = Keypair::new();
+        let (default_keypair_file, mut tmp_file) = make_tmp_file();
+        write_keypair(&default_keypair, tmp_file.as_file_mut()).unwrap();
+        let default_signer = DefaultSigner::new("", &default_keypair_file);
+
+        let test_show = test_commands
+            .clone()
+            .get_matches_from(vec!["test", "vote-group", "show"]);
+        assert_eq!(
+            parse_command(&test_show, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::VoteGroup(VoteGroupCliCommand::Show {
+                    slot: None,
+                    vote_account_pubkey: None,
+                }),
+                signers: vec![read_keypair_file(&default_keypair_file).unwrap().into()],
+            }
+        );
+
+        let vote_account_pubkey = solana_sdk::pubkey::new_rand();
+        let test_show = test_commands.clone().get_matches_from(vec![
+            "test",
+            "vote-group",
+            "show",
+            &vote_account_pubkey.to_string(),
+            "--slot",
+            "42",
+        ]);
+        assert_eq!(
+            parse_command(&test_show, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::VoteGroup(VoteGroupCliCommand::Show {
+                    slot: Some(42),
+                    vote_account_pubkey: Some(vote_account_pubkey),
+                }),
+                signers: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_vote_group_simulate() {
+        let test_commands = app("test", "desc", "version");
+        let default_keypair = Keypair::new();
+        let (default_keypair_file, mut tmp_file) = make_tmp_file();
+        write_keypair(&default_keypair, tmp_file.as_file_mut()).unwrap();
+        let default_signer = DefaultSigner::new("", &default_keypair_file);
+
+        let test_simulate =
+            test_commands
+                .clone()
+                .get_matches_from(vec!["test", "vote-group", "simulate"]);
+        assert_eq!(
+            parse_command(&test_simulate, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::VoteGroup(VoteGroupCliCommand::Simulate {
+                    epochs: 1,
+                    group_size: None,
+                    algorithm: None,
+                }),
+                signers: vec![],
+            }
+        );
+
+        let test_simulate = test_commands.clone().get_matches_from(vec![
+            "test",
+            "vote-group",
+            "simulate",
+            "--epochs",
+            "3",
+            "--group-size",
+            "7",
+            "--algorithm",
+            "v2",
+        ]);
+        assert_eq!(
+            parse_command(&test_simulate, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::VoteGroup(VoteGroupCliCommand::Simulate {
+                    epochs: 3,
+                    group_size: Some(7),
+                    algorithm: Some(SelectionAlgorithm::V2),
+                }),
+                signers: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_vote_group_verify() {
+        let test_commands = app("test", "desc", "version");
+        let default_keypair = Keypair::new();
+        let (default_keypair_file, mut tmp_file) = make_tmp_file();
+        write_keypair(&default_keypair, tmp_file.as_file_mut()).unwrap();
+        let default_signer = DefaultSigner::new("", &default_keypair_file);
+
+        let signature = Signature::new(&[1; 64]);
+        let test_verify = test_commands.clone().get_matches_from(vec![
+            "test",
+            "vote-group",
+            "verify",
+            &signature.to_string(),
+        ]);
+        assert_eq!(
+            parse_command(&test_verify, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::VoteGroup(VoteGroupCliCommand::Verify { signature }),
+                signers: vec![],
+            }
+        );
+
+        let test_verify = test_commands.clone().get_matches_from(vec![
+            "test",
+            "vote-group",
+            "verify",
+            "deadbeef",
+        ]);
+        assert!(parse_command(&test_verify, &default_signer, &mut None).is_err());
+    }
+
+    #[test]
+    fn test_parse_vote_group_schedule() {
+        let test_commands = app("test", "desc", "version");
+        let default_keypair = Keypair::new();
+        let (default_keypair_file, mut tmp_file) = make_tmp_file();
+        write_keypair(&default_keypair, tmp_file.as_file_mut()).unwrap();
+        let default_signer = DefaultSigner::new("", &default_keypair_file);
+
+        let test_schedule =
+            test_commands
+                .clone()
+                .get_matches_from(vec!["test", "vote-group", "schedule"]);
+        assert_eq!(
+            parse_command(&test_schedule, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::VoteGroup(VoteGroupCliCommand::Schedule {
+                    epoch: None,
+                    csv: false,
+                }),
+                signers: vec![],
+            }
+        );
+
+        let test_schedule = test_commands.clone().get_matches_from(vec![
+            "test",
+            "vote-group",
+            "schedule",
+            "--epoch",
+            "12",
+            "--csv",
+        ]);
+        assert_eq!(
+            parse_command(&test_schedule, &default_signer, &mut None).unwrap(),
+            CliCommandInfo {
+                command: CliCommand::VoteGroup(VoteGroupCliCommand::Schedule {
+                    epoch: Some(12),
+                    csv: true,
+                }),
+                signers: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn test_votes_in_transaction() {
+        let vote_pubkey = Pubkey::new_unique();
+        let other_vote_pubkey = Pubkey::new_unique();
+        let authorized_voter = Keypair::new();
+        let instructions = vec![
+            vote_instruction::vote(
+                &vote_pubkey,
+                &authorized_voter.pubkey(),
+                Vote::new(vec![7, 8], Hash::default()),
+            ),
+            system_instruction::transfer(&authorized_voter.pubkey(), &Pubkey::new_unique(), 1),
+            vote_instruction::vote_switch(
+                &other_vote_pubkey,
+                &authorized_voter.pubkey(),
+                Vote::new(vec![9], Hash::default()),
+                Hash::default(),
+            ),
+        ];
+        let transaction =
+            Transaction::new_with_payer(&instructions, Some(&authorized_voter.pubkey()));
+        assert_eq!(
+            votes_in_transaction(&transaction),
+            vec![(vote_pubkey, 7), (other_vote_pubkey, 9)]
+        );
+
+        let transaction = Transaction::new_with_payer(
+            &[system_instruction::transfer(
+                &authorized_voter.pubkey(),
+                &vote_pubkey,
+                1,
+            )],
+            Some(&authorized_voter.pubkey()),
+        );
+        assert!(votes_in_transaction(&transaction).is_empty());
+    }
+
+    #[test]
+    fn test_simulate_vote_groups() {
+        let voters: HashMap<Pubkey, Pubkey> = (0..20)
+            .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+            .collect();
+        let generator =
+            VoteGroupGenerator::new_with_exclusions(&voters, 5, &ExclusionList::new(vec![]))
+                .with_algorithm(SelectionAlgorithm::V2);
+        let epoch_schedule = EpochSchedule::custom(64, 64, false);
+
+        let simulation = simulate_vote_groups(&generator, &epoch_schedule, 0, 4);
+        assert_eq!(simulation.slots, 4 * 64);
+        assert_eq!(simulation.voters.len(), 20);
+        assert_eq!(simulation.algorithm, "v2");
+        assert!((simulation.expected_frequency - 0.25).abs() < f64::EPSILON);
+        // v2 groups never repeat a member so every slot selects exactly group size voters
+        let total: u64 = simulation.voters.iter().map(|voter| voter.selections).sum();
+        assert_eq!(total, 4 * 64 * 5);
+        for voter in &simulation.voters {
+            assert!(voter.longest_gap < simulation.slots);
+            assert!(voter.selections >= simulation.min_selections);
+            assert!(voter.selections <= simulation.max_selections);
+        }
+        // runs are repeatable
+        let again = simulate_vote_groups(&generator, &epoch_schedule, 0, 4);
+        assert_eq!(
+            serde_json::to_string(&simulation).unwrap(),
+            serde_json::to_string(&again).unwrap()
+        );
+    }
+}
@@ -4,7 +4,8 @@ use {
             RpcSignatureSubscribeConfig, RpcTransactionLogsConfig, RpcTransactionLogsFilter,
         },
         rpc_response::{
-            Response as RpcResponse, RpcLogsResponse, RpcSignatureResult, SlotInfo, SlotUpdate,
+            Response as RpcResponse, RpcLogsResponse, RpcSignatureResult, RpcVoteGroup, SlotInfo,
+            SlotUpdate,
         },
     },
     log::*,
@@ -157,6 +158,10 @@ pub type LogsSubscription = (
     Receiver<RpcResponse<RpcLogsResponse>>,
 );
 pub type SlotsSubscription = (PubsubClientSubscription<SlotInfo>, Receiver<SlotInfo>);
+pub type VoteGroupSubscription = (
+    PubsubClientSubscription<RpcVoteGroup>,
+    Receiver<RpcVoteGroup>,
+);
 pub type SignatureSubscription = (
     PubsubClientSubscription<RpcResponse<RpcSignatureResult>>,
     Receiver<RpcResponse<RpcSignatureResult>>,
@@ -276,6 +281,58 @@ impl PubsubClient {
         Ok((result, receiver))
     }
 
+    pub fn vote_group_subscribe(url: &str) -> Result<VoteGroupSubscription, PubsubClientError> {
+        let url = Url::parse(url)?;
+        let (socket, _response) = connect(url)?;
+        let (sender, receiver) = channel::<RpcVoteGroup>();
+
+        let socket = Arc::new(RwLock::new(socket));
+        let socket_clone = socket.clone();
+        let exit = Arc::new(AtomicBool::new(false));
+        let exit_clone = exit.clone();
+        let subscription_id = PubsubClientSubscription::<RpcVoteGroup>::send_subscribe(
+            &socket_clone,
+            json!({
+                "jsonrpc":"2.0","id":1,"method":"voteGroupSubscribe","params":[]
+            })
+            .to_string(),
+        )?;
+
+        let t_cleanup = std::thread::spawn(move || {
+            loop {
+                if exit_clone.load(Ordering::Relaxed) {
+                    break;
+                }
+                match PubsubClientSubscription::read_message(&socket_clone) {
+                    Ok(message) => match sender.send(message) {
+                        Ok(_) => (),
+                        Err(err) => {
+                            info!("receive error: {:?}", err);
+                            break;
+                        }
+                    },
+                    Err(err) => {
+                        info!("receive error: {:?}", err);
+                        break;
+                    }
+                }
+            }
+
+            info!("websocket - exited receive loop");
+        });
+
+        let result = PubsubClientSubscription {
+            message_type: PhantomData,
+            operation: "voteGroup",
+            socket,
+            subscription_id,
+            t_cleanup: Some(t_cleanup),
+            exit,
+        };
+
+        Ok((result, receiver))
+    }
+
     pub fn signature_subscribe(
         url: &str,
         signature: &Signature,
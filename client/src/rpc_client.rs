@@ -800,6 +800,93 @@ impl RpcClient {
         self.send(RpcRequest::GetLeaderSchedule, json!([slot, config]))
     }
 
+    pub fn get_current_vote_group(&self) -> ClientResult<Option<RpcCurrentVoteGroup>> {
+        self.get_current_vote_group_with_commitment(self.commitment())
+    }
+
+    pub fn get_current_vote_group_with_commitment(
+        &self,
+        commitment_config: CommitmentConfig,
+    ) -> ClientResult<Option<RpcCurrentVoteGroup>> {
+        self.send(
+            RpcRequest::GetCurrentVoteGroup,
+            json!([self.maybe_map_commitment(commitment_config)?]),
+        )
+    }
+
+    pub fn get_vote_group_for_slot(&self, slot: Slot) -> ClientResult<Option<RpcVoteGroup>> {
+        self.get_vote_group_for_slot_with_commitment(slot, self.commitment())
+    }
+
+    pub fn get_vote_group_for_slot_with_commitment(
+        &self,
+        slot: Slot,
+        commitment_config: CommitmentConfig,
+    ) -> ClientResult<Option<RpcVoteGroup>> {
+        self.send(
+            RpcRequest::GetVoteGroupForSlot,
+            json!([slot, self.maybe_map_commitment(commitment_config)?]),
+        )
+    }
+
+    pub fn get_vote_group_history(
+        &self,
+        start_slot: Slot,
+        config: RpcVoteGroupHistoryConfig,
+    ) -> ClientResult<Vec<RpcVoteGroupHistoryEntry>> {
+        self.send(RpcRequest::GetVoteGroupHistory, json!([start_slot, config]))
+    }
+
+    pub fn get_vote_group_schedule(
+        &self,
+        start_slot: Slot,
+        config: RpcVoteGroupScheduleConfig,
+    ) -> ClientResult<Vec<RpcVoteGroupWindow>> {
+        self.send(RpcRequest::GetVoteGroupSchedule, json!([start_slot, config]))
+    }
+
+    pub fn get_vote_group_stake_weight(
+        &self,
+        slot: Slot,
+    ) -> ClientResult<Option<RpcVoteGroupStakeWeight>> {
+        self.get_vote_group_stake_weight_with_commitment(slot, self.commitment())
+    }
+
+    pub fn get_vote_group_stake_weight_with_commitment(
+        &self,
+        slot: Slot,
+        commitment_config: CommitmentConfig,
+    ) -> ClientResult<Option<RpcVoteGroupStakeWeight>> {
+        self.send(
+            RpcRequest::GetVoteGroupStakeWeight,
+            json!([slot, self.maybe_map_commitment(commitment_config)?]),
+        )
+    }
+
+    pub fn is_voter_in_group(
+        &self,
+        vote_pubkey: &Pubkey,
+        slot: Slot,
+    ) -> ClientResult<Option<bool>> {
+        self.is_voter_in_group_with_commitment(vote_pubkey, slot, self.commitment())
+    }
+
+    pub fn is_voter_in_group_with_commitment(
+        &self,
+        vote_pubkey: &Pubkey,
+        slot: Slot,
+        commitment_config: CommitmentConfig,
+    ) -> ClientResult<Option<bool>> {
+        self.send(
+            RpcRequest::IsVoterInGroup,
+            json!([
+                vote_pubkey.to_string(),
+                slot,
+                self.maybe_map_commitment(commitment_config)?
+            ]),
+        )
+    }
+
     pub fn get_epoch_schedule(&self) -> ClientResult<EpochSchedule> {
         self.send(RpcRequest::GetEpochSchedule, Value::Null)
     }
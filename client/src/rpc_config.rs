@@ -85,6 +85,21 @@ pub struct RpcGetVoteAccountsConfig {
     pub delinquent_slot_distance: Option<u64>,
 }
 
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcVoteGroupHistoryConfig {
+    pub end_slot: Option<Slot>, // highest rooted slot if `None`
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcVoteGroupScheduleConfig {
+    pub limit: Option<usize>,
+    #[serde(flatten)]
+    pub commitment: Option<CommitmentConfig>,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum RpcLeaderScheduleConfigWrapper {
@@ -27,6 +27,7 @@ pub enum RpcRequest {
 
     GetConfirmedSignaturesForAddress2,
     GetConfirmedTransaction,
+    GetCurrentVoteGroup,
     GetEpochInfo,
     GetEpochSchedule,
     GetFeeCalculatorForBlockhash,
@@ -70,6 +71,11 @@ pub enum RpcRequest {
     GetTransactionCount,
     GetVersion,
     GetVoteAccounts,
+    GetVoteGroupForSlot,
+    GetVoteGroupHistory,
+    GetVoteGroupSchedule,
+    GetVoteGroupStakeWeight,
+    IsVoterInGroup,
     MinimumLedgerSlot,
     RegisterNode,
     RequestAirdrop,
@@ -95,6 +101,7 @@ impl fmt::Display for RpcRequest {
             RpcRequest::GetConfirmedSignaturesForAddress => "getConfirmedSignaturesForAddress",
             RpcRequest::GetConfirmedSignaturesForAddress2 => "getConfirmedSignaturesForAddress2",
             RpcRequest::GetConfirmedTransaction => "getConfirmedTransaction",
+            RpcRequest::GetCurrentVoteGroup => "getCurrentVoteGroup",
             RpcRequest::GetEpochInfo => "getEpochInfo",
             RpcRequest::GetEpochSchedule => "getEpochSchedule",
             RpcRequest::GetFeeCalculatorForBlockhash => "getFeeCalculatorForBlockhash",
@@ -135,6 +142,11 @@ impl fmt::Display for RpcRequest {
             RpcRequest::GetTransactionCount => "getTransactionCount",
             RpcRequest::GetVersion => "getVersion",
             RpcRequest::GetVoteAccounts => "getVoteAccounts",
+            RpcRequest::GetVoteGroupForSlot => "getVoteGroupForSlot",
+            RpcRequest::GetVoteGroupHistory => "getVoteGroupHistory",
+            RpcRequest::GetVoteGroupSchedule => "getVoteGroupSchedule",
+            RpcRequest::GetVoteGroupStakeWeight => "getVoteGroupStakeWeight",
+            RpcRequest::IsVoterInGroup => "isVoterInGroup",
             RpcRequest::MinimumLedgerSlot => "minimumLedgerSlot",
             RpcRequest::RegisterNode => "registerNode",
             RpcRequest::RequestAirdrop => "requestAirdrop",
@@ -151,6 +163,8 @@ pub const MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS: usize = 256;
 pub const MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS_SLOT_RANGE: u64 = 10_000;
 pub const MAX_GET_CONFIRMED_BLOCKS_RANGE: u64 = 500_000;
 pub const MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT: usize = 1_000;
+pub const MAX_GET_VOTE_GROUP_HISTORY_LIMIT: usize = 1_000;
+pub const MAX_GET_VOTE_GROUP_SCHEDULE_LIMIT: usize = 10_000;
 pub const MAX_MULTIPLE_ACCOUNTS: usize = 100;
 pub const NUM_LARGEST_ACCOUNTS: usize = 20;
 pub const MAX_GET_PROGRAM_ACCOUNT_FILTERS: usize = 4;
@@ -362,6 +362,88 @@ pub enum StakeActivationState {
     Inactive,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcVoteGroup {
+    pub slot: Slot,
+    pub seed: u64,
+    /// ring distance the group was walked with, absent when members are drawn by stake
+    pub distance: Option<u32>,
+    pub algorithm: String,
+    /// base58 vote account pubkeys in selection order
+    pub members: Vec<String>,
+    /// slots the group lasts before the next one is selected
+    pub rotation_window_slots: u64,
+    /// the first slot of the window, the group is seeded by it
+    pub window_start: Slot,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcVoteGroupWindow {
+    pub first_slot: Slot,
+    /// the last slot of the window, windows are cut short at the end of the epoch
+    pub last_slot: Slot,
+    pub seed: u64,
+    /// base58 vote account pubkeys in selection order
+    pub members: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcVoteGroupStakeWeight {
+    pub slot: Slot,
+    /// stake of the group's members, a member picked more than once counts once
+    pub group_stake: u64,
+    /// stake of every vote account in the slot's epoch
+    pub total_stake: u64,
+    /// the share of the cluster's stake the group covers
+    pub cluster_stake_fraction: f64,
+    /// the group's members in selection order
+    pub members: Vec<RpcVoteGroupMemberStake>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcVoteGroupMemberStake {
+    /// base58 vote account pubkey
+    pub vote_pubkey: String,
+    pub stake: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcCurrentVoteGroup {
+    pub slot: Slot,
+    pub seed: u64,
+    /// the group's members in selection order, a member picked more than once is listed once
+    pub members: Vec<RpcVoteGroupMember>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcVoteGroupMember {
+    /// base58 vote account pubkey
+    pub vote_pubkey: String,
+    /// base58 identity of the member's node
+    pub node_pubkey: String,
+    pub activated_stake: u64,
+    pub commission: u8,
+    /// the latest slot the member voted on, 0 if it hasn't voted
+    pub last_vote: Slot,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcVoteGroupHistoryEntry {
+    pub slot: Slot,
+    pub seed: u64,
+    /// base58 vote account pubkeys in selection order
+    pub members: Vec<String>,
+    /// the members that voted on the slot
+    pub voted: Vec<String>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct RpcStakeActivation {
@@ -21,7 +21,7 @@ use crate::{
     crds_gossip_pull::{CrdsFilter, ProcessPullStats, CRDS_GOSSIP_PULL_CRDS_TIMEOUT_MS},
     crds_value::{
         self, CrdsData, CrdsValue, CrdsValueLabel, EpochSlotsIndex, LowestSlot, NodeInstance,
-        SnapshotHash, Version, Vote, MAX_WALLCLOCK,
+        SnapshotHash, Version, Vote, VoteGroupClaim, MAX_WALLCLOCK,
     },
     data_budget::DataBudget,
     epoch_slots::EpochSlots,
@@ -225,6 +225,7 @@ pub struct ClusterInfo {
     contact_debug_interval: u64, // milliseconds, 0 = disabled
     contact_save_interval: u64,  // milliseconds, 0 = disabled
     instance: NodeInstance,
+    vote_group_claim: Mutex<Option<VoteGroupClaim>>, // last claim pushed for this node
     contact_info_path: PathBuf,
 }
 
@@ -326,7 +327,7 @@ pub fn make_accounts_hashes_message(
 pub(crate) type Ping = ping_pong::Ping<[u8; GOSSIP_PING_TOKEN_SIZE]>;
 
 // TODO These messages should go through the gpu pipeline for spam filtering
-#[frozen_abi(digest = "CH5BWuhAyvUiUQYgu2Lcwu7eoiW6bQitvtLS1yFsdmrE")]
+#[frozen_abi(digest = "FxPPt83Z9mUi2YDr2vnMF6kQbQLKzHeBS5GApkRydj4X")]
 #[derive(Serialize, Deserialize, Debug, AbiEnumVisitor, AbiExample)]
 #[allow(clippy::large_enum_variant)]
 enum Protocol {
@@ -451,7 +452,9 @@ fn retain_staked(values: &mut Vec<CrdsValue>, stakes: &HashMap<Pubkey, u64>) {
             CrdsData::LowestSlot(_, _)
             | CrdsData::AccountsHashes(_)
             | CrdsData::LegacyVersion(_)
-            | CrdsData::DuplicateShred(_, _) => {
+            | CrdsData::DuplicateShred(_, _)
+            // Only staked vote accounts are ever picked into a vote group.
+            | CrdsData::VoteGroupClaim(_) => {
                 let stake = stakes.get(&value.pubkey()).copied();
                 stake.unwrap_or_default() >= MIN_STAKE_FOR_GOSSIP
             }
@@ -483,6 +486,7 @@ impl ClusterInfo {
             local_message_pending_push_queue: Mutex::default(),
             contact_debug_interval: DEFAULT_CONTACT_DEBUG_INTERVAL_MILLIS,
             instance: NodeInstance::new(&mut thread_rng(), id, timestamp()),
+            vote_group_claim: Mutex::default(),
             contact_info_path: PathBuf::default(),
             contact_save_interval: 0, // disabled
         };
@@ -520,6 +524,7 @@ impl ClusterInfo {
             ),
             contact_debug_interval: self.contact_debug_interval,
             instance: NodeInstance::new(&mut thread_rng(), *new_id, timestamp()),
+            vote_group_claim: Mutex::default(),
             contact_info_path: PathBuf::default(),
             contact_save_interval: 0, // disabled
         }
@@ -975,6 +980,59 @@ impl ClusterInfo {
         GossipWriteLock::new(self.gossip.write().unwrap(), label, counter)
     }
 
+    /// Announces that `vote_account` is in the vote group for `slot`. Consecutive claims
+    /// accumulate into one crds value until a slot no longer fits in its window.
+    pub fn push_vote_group_claim(&self, vote_account: Pubkey, slot: Slot) {
+        let mut last_claim = self.vote_group_claim.lock().unwrap();
+        let now = timestamp();
+        let claim = match last_claim.take() {
+            Some(mut claim) if claim.vote_account == vote_account => {
+                if claim.contains(slot) {
+                    *last_claim = Some(claim);
+                    return;
+                }
+                // the update has to win over the previous claim in crds
+                let wallclock = now.max(claim.wallclock + 1);
+                if claim.add(slot) {
+                    claim.wallclock = wallclock;
+                    claim
+                } else {
+                    VoteGroupClaim::new(self.id(), vote_account, slot, wallclock)
+                }
+            }
+            _ => VoteGroupClaim::new(self.id(), vote_account, slot, now),
+        };
+        let entry = CrdsValue::new_signed(CrdsData::VoteGroupClaim(claim.clone()), &self.keypair);
+        *last_claim = Some(claim);
+        self.push_message(entry);
+    }
+
+    pub fn get_vote_group_claim(&self, pubkey: &Pubkey) -> Option<VoteGroupClaim> {
+        self.gossip
+            .read()
+            .unwrap()
+            .crds
+            .get(&CrdsValueLabel::VoteGroupClaim(*pubkey))
+            .and_then(|entry| entry.value.vote_group_claim())
+            .cloned()
+    }
+
+    /// Every node's latest vote group claim, keyed by the node's identity.
+    pub fn get_vote_group_claims(&self) -> HashMap<Pubkey, VoteGroupClaim> {
+        let gossip = self.gossip.read().unwrap();
+        gossip
+            .crds
+            .get_nodes_contact_info()
+            .filter_map(|node| {
+                gossip
+                    .crds
+                    .get(&CrdsValueLabel::VoteGroupClaim(node.id))
+                    .and_then(|entry| entry.value.vote_group_claim())
+            })
+            .map(|claim| (claim.from, claim.clone()))
+            .collect()
+    }
+
     pub(crate) fn push_message(&self, message: CrdsValue) {
         self.local_message_pending_push_queue
             .lock()
@@ -3771,6 +3829,47 @@ mod tests {
         assert!(slots.is_empty());
     }
 
+    #[test]
+    fn test_push_vote_group_claim() {
+        let keys = Keypair::new();
+        let contact_info = ContactInfo::new_localhost(&keys.pubkey(), 0);
+        let cluster_info = ClusterInfo::new_with_invalid_keypair(contact_info);
+        let vote_account = Pubkey::new_unique();
+        assert_eq!(cluster_info.get_vote_group_claim(&keys.pubkey()), None);
+        // the node's own contact info is already queued
+        let queued = cluster_info
+            .local_message_pending_push_queue
+            .lock()
+            .unwrap()
+            .len();
+
+        cluster_info.push_vote_group_claim(vote_account, 10);
+        cluster_info.push_vote_group_claim(vote_account, 12);
+        // already claimed, nothing new to push
+        cluster_info.push_vote_group_claim(vote_account, 12);
+        assert_eq!(
+            cluster_info
+                .local_message_pending_push_queue
+                .lock()
+                .unwrap()
+                .len(),
+            queued + 2
+        );
+        cluster_info.flush_push_queue();
+        let claim = cluster_info.get_vote_group_claim(&keys.pubkey()).unwrap();
+        assert_eq!(claim.vote_account, vote_account);
+        assert_eq!(claim.slots().collect::<Vec<_>>(), vec![10, 12]);
+
+        // a slot past the window starts a new claim
+        let far = 10 + crds_value::MAX_VOTE_GROUP_CLAIM_SLOTS;
+        cluster_info.push_vote_group_claim(vote_account, far);
+        cluster_info.flush_push_queue();
+        let claims = cluster_info.get_vote_group_claims();
+        assert_eq!(claims.len(), 1);
+        assert_eq!(claims[&keys.pubkey()].first_slot, far);
+        assert_eq!(claims[&keys.pubkey()].slots().count(), 1);
+    }
+
     #[test]
     fn test_append_entrypoint_to_pulls() {
         let thread_pool = ThreadPoolBuilder::new().build().unwrap();
@@ -598,6 +598,22 @@ impl ClusterInfoVoteListener {
                     .map(|(stake, _)| *stake)
                     .unwrap_or_default();
                 let total_stake = epoch_stakes.total_stake();
+                // once votes are limited to the slot's group, the group's stake confirms it
+                let (stake, total_stake, thresholds) = if root_bank.is_limited_voting_enforced() {
+                    let members = root_bank
+                        .vote_group_stakes(last_vote_slot, last_vote_hash)
+                        .unwrap_or_default();
+                    (
+                        members.get(vote_pubkey).copied().unwrap_or_default(),
+                        members.values().sum(),
+                        [
+                            DUPLICATE_THRESHOLD,
+                            root_bank.vote_group_quorum_fraction().as_f64(),
+                        ],
+                    )
+                } else {
+                    (stake, total_stake, THRESHOLDS_TO_CHECK)
+                };
 
                 // Fast track processing of the last slot in a vote transactions
                 // so that notifications for optimistic confirmation can be sent
@@ -609,6 +625,7 @@ impl ClusterInfoVoteListener {
                     *vote_pubkey,
                     stake,
                     total_stake,
+                    &thresholds,
                 );
 
                 if is_gossip_vote && is_new && stake > 0 {
@@ -625,6 +642,14 @@ impl ClusterInfoVoteListener {
                     }
                 }
                 if reached_threshold_results[1] {
+                    if root_bank.is_limited_voting_enforced() {
+                        Self::report_vote_group_quorum(
+                            vote_tracker,
+                            last_vote_slot,
+                            &last_vote_hash,
+                            total_stake,
+                        );
+                    }
                     new_optimistic_confirmed_slots.push((last_vote_slot, last_vote_hash));
                     // Notify subscribers about new optimistic confirmation
                     if let Some(sender) = bank_notification_sender {
@@ -802,6 +827,7 @@ impl ClusterInfoVoteListener {
         pubkey: Pubkey,
         stake: u64,
         total_epoch_stake: u64,
+        thresholds_to_check: &[f64],
     ) -> (Vec<bool>, bool) {
         let slot_tracker = vote_tracker.get_or_insert_slot_tracker(slot);
         // Insert vote and check for optimistic confirmation
@@ -809,7 +835,33 @@ impl ClusterInfoVoteListener {
 
         w_slot_tracker
             .get_or_insert_optimistic_votes_tracker(hash)
-            .add_vote_pubkey(pubkey, stake, total_epoch_stake, &THRESHOLDS_TO_CHECK)
+            .add_vote_pubkey(pubkey, stake, total_epoch_stake, thresholds_to_check)
+    }
+
+    // how long the group took from its first vote on the slot to a quorum
+    fn report_vote_group_quorum(
+        vote_tracker: &VoteTracker,
+        slot: Slot,
+        hash: &Hash,
+        group_stake: u64,
+    ) {
+        let first_vote = vote_tracker
+            .get_slot_vote_tracker(slot)
+            .and_then(|slot_tracker| {
+                slot_tracker
+                    .read()
+                    .unwrap()
+                    .optimistic_votes_tracker(hash)
+                    .and_then(|tracker| tracker.first_vote())
+            });
+        if let Some(first_vote) = first_vote {
+            datapoint_info!(
+                "vote_group_quorum",
+                ("slot", slot, i64),
+                ("latency_us", first_vote.elapsed().as_micros(), i64),
+                ("group_stake", group_stake, i64),
+            );
+        }
     }
 
     fn sum_stake(sum: &mut u64, epoch_stakes: Option<&EpochStakes>, pubkey: &Pubkey) {
@@ -1,4 +1,7 @@
-use crate::{consensus::Stake, rpc_subscriptions::RpcSubscriptions};
+use crate::{
+    consensus::{GroupStakes, Stake},
+    rpc_subscriptions::RpcSubscriptions,
+};
 use solana_measure::measure::Measure;
 use solana_metrics::datapoint_info;
 use solana_runtime::{
@@ -45,6 +48,46 @@ fn get_highest_confirmed_root(mut rooted_stake: Vec<(Slot, u64)>, total_stake: u
     0
 }
 
+// the highest slot rooted by more than `threshold` of its group, out of block commitment
+// already scaled to each slot's group
+fn get_highest_confirmed_group_root(
+    block_commitment: &HashMap<Slot, BlockCommitment>,
+    total_stake: Stake,
+    threshold: f64,
+) -> Slot {
+    block_commitment
+        .iter()
+        .filter(|(_, commitment)| {
+            (commitment.get_rooted_stake() as f64 / total_stake as f64) > threshold
+        })
+        .map(|(slot, _)| *slot)
+        .max()
+        .unwrap_or(0)
+}
+
+// the group members' commitment of each slot, scaled so that measured against `total_stake`
+// it reads as the share of the slot's group. a slot whose group is unknown counted every voter
+// and is already a share of `total_stake`
+fn scale_to_group_stakes(
+    block_commitment: &mut HashMap<Slot, BlockCommitment>,
+    group_stakes: &HashMap<Slot, GroupStakes>,
+    total_stake: Stake,
+) {
+    for (slot, commitment) in block_commitment.iter_mut() {
+        let group_stake: Stake = match group_stakes.get(slot) {
+            Some(members) => members.values().sum(),
+            None => continue,
+        };
+        for stake in commitment.commitment.iter_mut() {
+            *stake = if group_stake == 0 {
+                0
+            } else {
+                (*stake as u128 * total_stake as u128 / group_stake as u128) as Stake
+            };
+        }
+    }
+}
+
 pub struct AggregateCommitmentService {
     t_commitment: JoinHandle<()>,
 }
@@ -138,11 +181,36 @@ impl AggregateCommitmentService {
         aggregation_data: CommitmentAggregationData,
         ancestors: Vec<u64>,
     ) -> CommitmentSlots {
-        let (block_commitment, rooted_stake) =
-            Self::aggregate_commitment(&ancestors, &aggregation_data.bank);
+        // once votes are limited to each slot's group, a slot's commitment is its group's and
+        // the group's quorum fraction confirms it
+        let bank = &aggregation_data.bank;
+        let (group_stakes, confirmation_threshold) = if bank.is_limited_voting_enforced() {
+            (
+                Some(Self::group_stakes(&ancestors, bank)),
+                bank.vote_group_quorum_fraction().as_f64(),
+            )
+        } else {
+            (None, VOTE_THRESHOLD_SIZE)
+        };
+        let (mut block_commitment, rooted_stake) =
+            Self::aggregate_commitment_with_groups(&ancestors, bank, group_stakes.as_ref());
+        if let Some(group_stakes) = &group_stakes {
+            scale_to_group_stakes(
+                &mut block_commitment,
+                group_stakes,
+                aggregation_data.total_stake,
+            );
+        }
 
-        let highest_confirmed_root =
-            get_highest_confirmed_root(rooted_stake, aggregation_data.total_stake);
+        let highest_confirmed_root = if group_stakes.is_some() {
+            get_highest_confirmed_group_root(
+                &block_commitment,
+                aggregation_data.total_stake,
+                confirmation_threshold,
+            )
+        } else {
+            get_highest_confirmed_root(rooted_stake, aggregation_data.total_stake)
+        };
 
         let mut new_block_commitment = BlockCommitmentCache::new(
             block_commitment,
@@ -154,6 +222,7 @@ impl AggregateCommitmentService {
                 highest_confirmed_root,
             },
         );
+        new_block_commitment.set_confirmation_threshold(confirmation_threshold);
         let highest_confirmed_slot = new_block_commitment.calculate_highest_confirmed_slot();
         new_block_commitment.set_highest_confirmed_slot(highest_confirmed_slot);
 
@@ -172,6 +241,24 @@ impl AggregateCommitmentService {
     pub fn aggregate_commitment(
         ancestors: &[Slot],
         bank: &Bank,
+    ) -> (HashMap<Slot, BlockCommitment>, Vec<(Slot, u64)>) {
+        Self::aggregate_commitment_with_groups(ancestors, bank, None)
+    }
+
+    // the group of each ancestor, a slot whose group is unknown is left out
+    fn group_stakes(ancestors: &[Slot], bank: &Bank) -> HashMap<Slot, GroupStakes> {
+        ancestors
+            .iter()
+            .filter_map(|slot| Some((*slot, bank.vote_group_stakes_for_slot(*slot)?)))
+            .collect()
+    }
+
+    // with `group_stakes` a vote account's stake only counts toward the slots whose group it
+    // is in, or whose group is unknown. rooted stake still counts every vote account
+    fn aggregate_commitment_with_groups(
+        ancestors: &[Slot],
+        bank: &Bank,
+        group_stakes: Option<&HashMap<Slot, GroupStakes>>,
     ) -> (HashMap<Slot, BlockCommitment>, Vec<(Slot, u64)>) {
         assert!(!ancestors.is_empty());
 
@@ -182,18 +269,47 @@ impl AggregateCommitmentService {
 
         let mut commitment = HashMap::new();
         let mut rooted_stake: Vec<(Slot, u64)> = Vec::new();
-        for (_, (lamports, account)) in bank.vote_accounts().into_iter() {
+        for (vote_pubkey, (lamports, account)) in bank.vote_accounts().into_iter() {
             if lamports == 0 {
                 continue;
             }
             if let Ok(vote_state) = account.vote_state().as_ref() {
+                let group_stakes = match group_stakes {
+                    None => {
+                        Self::aggregate_commitment_for_vote_account(
+                            &mut commitment,
+                            &mut rooted_stake,
+                            vote_state,
+                            ancestors,
+                            lamports,
+                        );
+                        continue;
+                    }
+                    Some(group_stakes) => group_stakes,
+                };
+                let mut account_commitment = HashMap::new();
                 Self::aggregate_commitment_for_vote_account(
-                    &mut commitment,
+                    &mut account_commitment,
                     &mut rooted_stake,
                     vote_state,
                     ancestors,
                     lamports,
                 );
+                for (slot, account_commitment) in account_commitment {
+                    let is_member = group_stakes
+                        .get(&slot)
+                        .map_or(true, |members| members.contains_key(&vote_pubkey));
+                    if is_member {
+                        let block_commitment = commitment.entry(slot).or_default();
+                        for (stake, account_stake) in block_commitment
+                            .commitment
+                            .iter_mut()
+                            .zip(account_commitment.commitment.iter())
+                        {
+                            *stake += account_stake;
+                        }
+                    }
+                }
             }
         }
 
@@ -269,6 +385,59 @@ mod tests {
         assert_eq!(get_highest_confirmed_root(rooted_stake, 10), 1);
     }
 
+    #[test]
+    fn test_get_highest_confirmed_group_root() {
+        let rooted = |stake| {
+            let mut commitment = BlockCommitment::default();
+            commitment.increase_rooted_stake(stake);
+            commitment
+        };
+        let block_commitment = vec![(1, rooted(80)), (2, rooted(70)), (3, rooted(60))]
+            .into_iter()
+            .collect();
+        assert_eq!(
+            get_highest_confirmed_group_root(&HashMap::new(), 100, 0.5),
+            0
+        );
+        assert_eq!(
+            get_highest_confirmed_group_root(&block_commitment, 100, VOTE_THRESHOLD_SIZE),
+            2
+        );
+        assert_eq!(
+            get_highest_confirmed_group_root(&block_commitment, 100, 0.75),
+            1
+        );
+        assert_eq!(
+            get_highest_confirmed_group_root(&block_commitment, 100, 0.8),
+            0
+        );
+    }
+
+    #[test]
+    fn test_scale_to_group_stakes() {
+        let mut block_commitment = HashMap::new();
+        let mut commitment = BlockCommitment::default();
+        commitment.increase_confirmation_stake(1, 10);
+        commitment.increase_rooted_stake(5);
+        block_commitment.insert(1, commitment.clone());
+        block_commitment.insert(2, commitment);
+        let group: GroupStakes = vec![(Pubkey::new_unique(), 20), (Pubkey::new_unique(), 20)]
+            .into_iter()
+            .collect();
+        let group_stakes = vec![(1, group)].into_iter().collect();
+
+        scale_to_group_stakes(&mut block_commitment, &group_stakes, 100);
+        let mut expected = BlockCommitment::default();
+        expected.increase_confirmation_stake(1, 25);
+        expected.increase_rooted_stake(12);
+        assert_eq!(block_commitment[&1], expected);
+        // without a known group the slot already is a share of the total stake
+        let mut expected = BlockCommitment::default();
+        expected.increase_confirmation_stake(1, 10);
+        expected.increase_rooted_stake(5);
+        assert_eq!(block_commitment[&2], expected);
+    }
+
     #[test]
     fn test_aggregate_commitment_for_vote_account_1() {
         let ancestors = vec![3, 4, 5, 7, 9, 11];
@@ -482,6 +651,124 @@ mod tests {
         assert_eq!(get_highest_confirmed_root(rooted_stake, 100), 1)
     }
 
+    #[test]
+    fn test_update_commitment_cache_vote_groups() {
+        let validator_vote_keypairs: Vec<_> =
+            (0..20).map(|_| ValidatorVoteKeypairs::new_rand()).collect();
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config_with_vote_accounts(
+            1_000_000_000,
+            &validator_vote_keypairs,
+            vec![100; validator_vote_keypairs.len()],
+        );
+        let bank = Arc::new(Bank::new(&genesis_config));
+        assert!(bank.is_limited_voting_enforced());
+        let total_stake = bank.total_epoch_stake();
+        let members = bank.vote_group_stakes_for_slot(0).unwrap();
+        assert!(members.len() < validator_vote_keypairs.len());
+
+        let vote = |vote_pubkey: &Pubkey| {
+            let (_stake, vote_account) = bank.get_vote_account(vote_pubkey).unwrap();
+            let mut vote_state = vote_account.vote_state().as_ref().unwrap().clone();
+            vote_state.process_slot_vote_unchecked(0);
+            let mut vote_account = bank.get_account(vote_pubkey).unwrap();
+            VoteState::to(
+                &VoteStateVersions::new_current(vote_state),
+                &mut vote_account,
+            )
+            .unwrap();
+            bank.store_account(vote_pubkey, &vote_account);
+        };
+        let update = || {
+            let block_commitment_cache = RwLock::new(BlockCommitmentCache::default());
+            AggregateCommitmentService::update_commitment_cache(
+                &block_commitment_cache,
+                CommitmentAggregationData::new(bank.clone(), 0, total_stake),
+                vec![0],
+            );
+            block_commitment_cache.into_inner().unwrap()
+        };
+
+        // the votes from outside the slot's group don't count toward it
+        for keypairs in &validator_vote_keypairs {
+            if !members.contains_key(&keypairs.vote_keypair.pubkey()) {
+                vote(&keypairs.vote_keypair.pubkey());
+            }
+        }
+        let cache = update();
+        assert_eq!(
+            cache.confirmation_threshold(),
+            bank.vote_group_quorum_fraction().as_f64()
+        );
+        assert!(cache.get_block_commitment(0).is_none());
+
+        // the whole group voted, which reads as all of the stake
+        for vote_pubkey in members.keys() {
+            vote(vote_pubkey);
+        }
+        let cache = update();
+        let mut expected = BlockCommitment::default();
+        expected.increase_confirmation_stake(1, total_stake);
+        assert_eq!(cache.get_block_commitment(0), Some(&expected));
+        assert_eq!(cache.highest_confirmed_slot(), 0);
+    }
+
+    #[test]
+    fn test_highest_confirmed_group_root_advance() {
+        let validator_vote_keypairs: Vec<_> =
+            (0..20).map(|_| ValidatorVoteKeypairs::new_rand()).collect();
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config_with_vote_accounts(
+            1_000_000_000,
+            &validator_vote_keypairs,
+            vec![100; validator_vote_keypairs.len()],
+        );
+        let bank = Arc::new(Bank::new(&genesis_config));
+        assert!(bank.is_limited_voting_enforced());
+        let total_stake = bank.total_epoch_stake();
+        let mut members: Vec<_> = bank
+            .vote_group_stakes_for_slot(2)
+            .unwrap()
+            .keys()
+            .copied()
+            .collect();
+        members.sort();
+
+        let root = |vote_pubkeys: &[Pubkey]| {
+            for vote_pubkey in vote_pubkeys {
+                let (_stake, vote_account) = bank.get_vote_account(vote_pubkey).unwrap();
+                let mut vote_state = vote_account.vote_state().as_ref().unwrap().clone();
+                vote_state.root_slot = Some(2);
+                let mut vote_account = bank.get_account(vote_pubkey).unwrap();
+                VoteState::to(
+                    &VoteStateVersions::new_current(vote_state),
+                    &mut vote_account,
+                )
+                .unwrap();
+                bank.store_account(vote_pubkey, &vote_account);
+            }
+            let block_commitment_cache = RwLock::new(BlockCommitmentCache::default());
+            AggregateCommitmentService::update_commitment_cache(
+                &block_commitment_cache,
+                CommitmentAggregationData::new(bank.clone(), 0, total_stake),
+                vec![0, 1, 2],
+            );
+            let highest_confirmed_root = block_commitment_cache
+                .read()
+                .unwrap()
+                .highest_confirmed_root();
+            highest_confirmed_root
+        };
+
+        // the group's stake alone doesn't reach the threshold of the total stake
+        assert!(
+            (members.len() as f64 / validator_vote_keypairs.len() as f64) < VOTE_THRESHOLD_SIZE
+        );
+
+        // a single member isn't the quorum of the slot's group
+        assert!(root(&members[..1]) < 2);
+        // the whole group finalizes the slot
+        assert_eq!(root(&members), 2);
+    }
+
     #[test]
     fn test_highest_confirmed_root_advance() {
         fn get_vote_account_root_slot(vote_pubkey: Pubkey, bank: &Arc<Bank>) -> Slot {
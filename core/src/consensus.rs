@@ -89,11 +89,16 @@ pub type Result<T> = std::result::Result<T, TowerError>;
 
 pub type Stake = u64;
 pub type VotedStakes = HashMap<Slot, Stake>;
+/// the members of a slot's vote group with their stake
+pub type GroupStakes = HashMap<Pubkey, Stake>;
 pub type PubkeyVotes = Vec<(Pubkey, Slot)>;
 
 pub(crate) struct ComputedBankState {
     pub voted_stakes: VotedStakes,
     pub total_stake: Stake,
+    // the stake of each slot's vote group, a slot missing here is measured against
+    // `total_stake`
+    pub group_stakes: VotedStakes,
     pub bank_weight: u128,
     // Tree of intervals of lockouts of the form [slot, slot + slot.lockout],
     // keyed by end of the range
@@ -231,10 +236,23 @@ impl Tower {
         ancestors: &HashMap<Slot, HashSet<Slot>>,
         get_frozen_hash: impl Fn(Slot) -> Option<Hash>,
         latest_validator_votes_for_frozen_banks: &mut LatestValidatorVotesForFrozenBanks,
+        vote_groups: Option<&dyn Fn(Slot) -> Option<GroupStakes>>,
     ) -> ComputedBankState
     where
         F: IntoIterator<Item = (Pubkey, (u64, ArcVoteAccount))>,
     {
+        // with `vote_groups` only the members of a slot's group count toward it. a slot whose
+        // group can't be told has nothing to filter its votes by, every voter counts toward it
+        // and it is measured against the total stake like before limited voting
+        let mut slot_groups: HashMap<Slot, Option<GroupStakes>> = HashMap::new();
+        let mut is_group_member = |slot: Slot, key: &Pubkey| match vote_groups {
+            None => true,
+            Some(vote_groups) => slot_groups
+                .entry(slot)
+                .or_insert_with(|| vote_groups(slot))
+                .as_ref()
+                .map_or(true, |members| members.contains_key(key)),
+        };
         let mut vote_slots = HashSet::new();
         let mut voted_stakes = HashMap::new();
         let mut total_stake = 0;
@@ -290,7 +308,10 @@ impl Tower {
             let start_root = vote_state.root_slot;
 
             // Add the last vote to update the `heaviest_subtree_fork_choice`
-            if let Some(last_landed_voted_slot) = vote_state.last_voted_slot() {
+            if let Some(last_landed_voted_slot) = vote_state
+                .last_voted_slot()
+                .filter(|slot| is_group_member(*slot, &key))
+            {
                 latest_validator_votes_for_frozen_banks.check_add_vote(
                     key,
                     last_landed_voted_slot,
@@ -346,6 +367,7 @@ impl Tower {
                     vote.slot,
                     voted_stake,
                     ancestors,
+                    |slot| is_group_member(slot, &key),
                 );
             }
             total_stake += voted_stake;
@@ -354,9 +376,14 @@ impl Tower {
         // TODO: populate_ancestor_voted_stakes only adds zeros. Comment why
         // that is necessary (if so).
         Self::populate_ancestor_voted_stakes(&mut voted_stakes, vote_slots, ancestors);
+        let group_stakes = slot_groups
+            .into_iter()
+            .filter_map(|(slot, members)| Some((slot, members?.values().sum())))
+            .collect();
         ComputedBankState {
             voted_stakes,
             total_stake,
+            group_stakes,
             bank_weight,
             lockout_intervals,
             my_latest_landed_vote,
@@ -367,14 +394,21 @@ impl Tower {
         &self,
         slot: Slot,
         voted_stakes: &VotedStakes,
+        group_stakes: &VotedStakes,
         total_stake: Stake,
     ) -> bool {
+        let slot_stake = Self::slot_stake(slot, group_stakes, total_stake);
         voted_stakes
             .get(&slot)
-            .map(|stake| (*stake as f64 / total_stake as f64) > self.threshold_size)
+            .map(|stake| (*stake as f64 / slot_stake as f64) > self.threshold_size)
             .unwrap_or(false)
     }
 
+    /// the stake `slot`'s voted stake is measured against, its group's when the slot has one
+    pub(crate) fn slot_stake(slot: Slot, group_stakes: &VotedStakes, total_stake: Stake) -> Stake {
+        group_stakes.get(&slot).copied().unwrap_or(total_stake)
+    }
+
     pub fn tower_slots(&self) -> Vec<Slot> {
         self.lockouts.tower()
     }
@@ -460,6 +494,59 @@ impl Tower {
         }
     }
 
+    /// a validator outside the group of the slot it would vote on sends no vote, so nothing
+    /// enters its lockouts and it can't root through them. its root follows `cluster_root`
+    /// instead, lockouts at or below it are dropped. the root only moves when no vote in the
+    /// tower is still locked out against it. Returns the new root if it moved
+    pub fn record_cluster_root(
+        &mut self,
+        cluster_root: Slot,
+        ancestors: &HashMap<Slot, HashSet<Slot>>,
+    ) -> Option<Slot> {
+        if cluster_root <= self.root() {
+            return None;
+        }
+        if let Some(lockout) = self.lockout_conflicting_with_root(cluster_root, ancestors) {
+            warn!(
+                "{} can't follow cluster root {}, its vote on {} is locked out against it",
+                self.node_pubkey, cluster_root, lockout.slot
+            );
+            inc_new_counter_info!("tower-cluster-root-locked-out", 1);
+            return None;
+        }
+        trace!("{} follows cluster root {}", self.node_pubkey, cluster_root);
+        self.lockouts
+            .votes
+            .retain(|lockout| lockout.slot > cluster_root);
+        self.lockouts.root_slot = Some(cluster_root);
+        datapoint_info!("tower-cluster-root", ("root", cluster_root, i64));
+        Some(cluster_root)
+    }
+
+    /// the first vote `cluster_root` would break: a vote below it on another fork whose
+    /// lockout hasn't expired by it, or a vote above it that doesn't descend from it. a root
+    /// missing from `ancestors` isn't known to be on any vote's fork
+    fn lockout_conflicting_with_root(
+        &self,
+        cluster_root: Slot,
+        ancestors: &HashMap<Slot, HashSet<Slot>>,
+    ) -> Option<&Lockout> {
+        let root_ancestors = ancestors.get(&cluster_root);
+        self.lockouts
+            .votes
+            .iter()
+            .find(|lockout| match lockout.slot.cmp(&cluster_root) {
+                Ordering::Less => {
+                    !lockout.is_expired(cluster_root)
+                        && !root_ancestors.map_or(false, |slots| slots.contains(&lockout.slot))
+                }
+                Ordering::Equal => false,
+                Ordering::Greater => !ancestors
+                    .get(&lockout.slot)
+                    .map_or(false, |slots| slots.contains(&cluster_root)),
+            })
+    }
+
     #[cfg(test)]
     pub fn record_vote(&mut self, slot: Slot, hash: Hash) -> Option<Slot> {
         self.record_bank_vote_and_update_lockouts(slot, hash, self.last_voted_slot())
@@ -804,6 +891,7 @@ impl Tower {
         &self,
         slot: Slot,
         voted_stakes: &VotedStakes,
+        group_stakes: &VotedStakes,
         total_stake: Stake,
     ) -> bool {
         let mut lockouts = self.lockouts.clone();
@@ -811,6 +899,7 @@ impl Tower {
         let vote = lockouts.nth_recent_vote(self.threshold_depth);
         if let Some(vote) = vote {
             if let Some(fork_stake) = voted_stakes.get(&vote.slot) {
+                let total_stake = Self::slot_stake(vote.slot, group_stakes, total_stake);
                 let lockout = *fork_stake as f64 / total_stake as f64;
                 trace!(
                     "fork_stake slot: {}, vote slot: {}, lockout: {} fork_stake: {} total_stake: {}",
@@ -860,13 +949,15 @@ impl Tower {
         voted_slot: Slot,
         voted_stake: u64,
         ancestors: &HashMap<Slot, HashSet<Slot>>,
+        mut counts_toward: impl FnMut(Slot) -> bool,
     ) {
         // If there's no ancestors, that means this slot must be from
         // before the current root, so ignore this slot
         if let Some(vote_slot_ancestors) = ancestors.get(&voted_slot) {
-            *voted_stakes.entry(voted_slot).or_default() += voted_stake;
-            for slot in vote_slot_ancestors {
-                *voted_stakes.entry(*slot).or_default() += voted_stake;
+            for slot in std::iter::once(&voted_slot).chain(vote_slot_ancestors) {
+                if counts_toward(*slot) {
+                    *voted_stakes.entry(*slot).or_default() += voted_stake;
+                }
             }
         }
     }
@@ -2189,6 +2280,7 @@ pub mod test {
             &ancestors,
             |_| Some(Hash::default()),
             &mut latest_validator_votes_for_frozen_banks,
+            None,
         );
         assert_eq!(voted_stakes[&0], 2);
         assert_eq!(total_stake, 2);
@@ -2201,6 +2293,100 @@ pub mod test {
         assert_eq!(bank_weight, 12)
     }
 
+    #[test]
+    fn test_collect_vote_lockouts_group_stakes() {
+        //three accounts voting for slot 0, only the first two are in its group
+        let accounts = gen_stakes(&[(1, &[0]), (1, &[0]), (2, &[0])]);
+        let group: GroupStakes = accounts
+            .iter()
+            .take(2)
+            .map(|(pubkey, (stake, _))| (*pubkey, *stake))
+            .collect();
+        let vote_groups = |slot: Slot| if slot == 0 { Some(group.clone()) } else { None };
+
+        let ancestors = vec![(1, vec![0].into_iter().collect()), (0, HashSet::new())]
+            .into_iter()
+            .collect();
+        let mut latest_validator_votes_for_frozen_banks =
+            LatestValidatorVotesForFrozenBanks::default();
+        let ComputedBankState {
+            voted_stakes,
+            total_stake,
+            group_stakes,
+            ..
+        } = Tower::collect_vote_lockouts(
+            &Pubkey::default(),
+            1,
+            accounts.clone().into_iter(),
+            &ancestors,
+            |_| Some(Hash::default()),
+            &mut latest_validator_votes_for_frozen_banks,
+            Some(&vote_groups),
+        );
+        // the whole group voted, half of the total stake
+        assert_eq!(total_stake, 4);
+        assert_eq!(voted_stakes[&0], 2);
+        assert_eq!(group_stakes[&0], 2);
+        let tower = Tower::new_for_tests(0, 0.67);
+        assert!(tower.is_slot_confirmed(0, &voted_stakes, &group_stakes, total_stake));
+        assert!(!tower.is_slot_confirmed(0, &voted_stakes, &HashMap::new(), total_stake));
+
+        // only the group's votes reach fork choice
+        let mut new_votes = latest_validator_votes_for_frozen_banks.take_votes_dirty_set(0);
+        new_votes.sort();
+        let mut expected_votes: Vec<(Pubkey, SlotHashKey)> = group
+            .keys()
+            .map(|pubkey| (*pubkey, (0, Hash::default())))
+            .collect();
+        expected_votes.sort();
+        assert_eq!(new_votes, expected_votes);
+    }
+
+    #[test]
+    fn test_collect_vote_lockouts_unknown_group() {
+        //two accounts voting for slot 0, its group can't be told
+        let accounts = gen_stakes(&[(1, &[0]), (3, &[0])]);
+        let vote_groups = |_: Slot| None;
+
+        let ancestors = vec![(1, vec![0].into_iter().collect()), (0, HashSet::new())]
+            .into_iter()
+            .collect();
+        let ComputedBankState {
+            voted_stakes,
+            total_stake,
+            group_stakes,
+            ..
+        } = Tower::collect_vote_lockouts(
+            &Pubkey::default(),
+            1,
+            accounts.into_iter(),
+            &ancestors,
+            |_| Some(Hash::default()),
+            &mut LatestValidatorVotesForFrozenBanks::default(),
+            Some(&vote_groups),
+        );
+        // every vote counts and the slot is measured against the total stake
+        assert_eq!(total_stake, 4);
+        assert_eq!(voted_stakes[&0], 4);
+        assert!(group_stakes.is_empty());
+        let tower = Tower::new_for_tests(0, 0.67);
+        assert!(tower.is_slot_confirmed(0, &voted_stakes, &group_stakes, total_stake));
+    }
+
+    #[test]
+    fn test_check_vote_threshold_group_stake() {
+        let mut tower = Tower::new_for_tests(1, 0.67);
+        tower.record_vote(0, Hash::default());
+        // the group of slot 0 holds 2 of the 4 staked and all of it voted
+        let stakes = vec![(0, 2)].into_iter().collect();
+        let group_stakes = vec![(0, 2)].into_iter().collect();
+        assert!(tower.check_vote_stake_threshold(1, &stakes, &group_stakes, 4));
+        assert!(!tower.check_vote_stake_threshold(1, &stakes, &HashMap::new(), 4));
+        // a group that didn't vote in full stays below the threshold
+        let group_stakes = vec![(0, 3)].into_iter().collect();
+        assert!(!tower.check_vote_stake_threshold(1, &stakes, &group_stakes, 4));
+    }
+
     #[test]
     fn test_collect_vote_lockouts_root() {
         let votes: Vec<u64> = (0..MAX_LOCKOUT_HISTORY as u64).collect();
@@ -2249,6 +2435,7 @@ pub mod test {
             &ancestors,
             |_| Some(Hash::default()),
             &mut latest_validator_votes_for_frozen_banks,
+            None,
         );
         for i in 0..MAX_LOCKOUT_HISTORY {
             assert_eq!(voted_stakes[&(i as u64)], 2);
@@ -2265,7 +2452,7 @@ pub mod test {
     fn test_check_vote_threshold_without_votes() {
         let tower = Tower::new_for_tests(1, 0.67);
         let stakes = vec![(0, 1)].into_iter().collect();
-        assert!(tower.check_vote_stake_threshold(0, &stakes, 2));
+        assert!(tower.check_vote_stake_threshold(0, &stakes, &HashMap::new(), 2));
     }
 
     #[test]
@@ -2277,28 +2464,33 @@ pub mod test {
             stakes.insert(i, 1);
             tower.record_vote(i, Hash::default());
         }
-        assert!(!tower.check_vote_stake_threshold(MAX_LOCKOUT_HISTORY as u64 + 1, &stakes, 2,));
+        assert!(!tower.check_vote_stake_threshold(
+            MAX_LOCKOUT_HISTORY as u64 + 1,
+            &stakes,
+            &HashMap::new(),
+            2,
+        ));
     }
 
     #[test]
     fn test_is_slot_confirmed_not_enough_stake_failure() {
         let tower = Tower::new_for_tests(1, 0.67);
         let stakes = vec![(0, 1)].into_iter().collect();
-        assert!(!tower.is_slot_confirmed(0, &stakes, 2));
+        assert!(!tower.is_slot_confirmed(0, &stakes, &HashMap::new(), 2));
     }
 
     #[test]
     fn test_is_slot_confirmed_unknown_slot() {
         let tower = Tower::new_for_tests(1, 0.67);
         let stakes = HashMap::new();
-        assert!(!tower.is_slot_confirmed(0, &stakes, 2));
+        assert!(!tower.is_slot_confirmed(0, &stakes, &HashMap::new(), 2));
     }
 
     #[test]
     fn test_is_slot_confirmed_pass() {
         let tower = Tower::new_for_tests(1, 0.67);
         let stakes = vec![(0, 2)].into_iter().collect();
-        assert!(tower.is_slot_confirmed(0, &stakes, 2));
+        assert!(tower.is_slot_confirmed(0, &stakes, &HashMap::new(), 2));
     }
 
     #[test]
@@ -2329,6 +2521,73 @@ pub mod test {
         assert!(tower.is_locked_out(2, &ancestors));
     }
 
+    #[test]
+    fn test_record_cluster_root() {
+        let mut tower = Tower::new_for_tests(0, 0.67);
+        for slot in 1..4 {
+            tower.record_vote(slot, Hash::default());
+        }
+        // 0 - 1 - 2 - 3 - 4
+        let ancestors: HashMap<Slot, HashSet<Slot>> =
+            (0..5).map(|slot| (slot, (0..slot).collect())).collect();
+        assert_eq!(tower.record_cluster_root(0, &ancestors), None);
+        assert_eq!(tower.record_cluster_root(2, &ancestors), Some(2));
+        assert_eq!(tower.root(), 2);
+        assert!(!tower.has_voted(2));
+        assert!(tower.has_voted(3));
+        assert_eq!(tower.record_cluster_root(2, &ancestors), None);
+
+        // the tower keeps voting from the cluster's root
+        assert_eq!(tower.record_vote(4, Hash::default()), None);
+        assert_eq!(tower.last_voted_slot(), Some(4));
+    }
+
+    #[test]
+    fn test_record_cluster_root_locked_out() {
+        // 0 - 1 - 2 - 3 - 6
+        //      \
+        //       4 - 5
+        let mut ancestors: HashMap<Slot, HashSet<Slot>> = HashMap::new();
+        for (slot, slot_ancestors) in &[
+            (0, vec![]),
+            (1, vec![0]),
+            (2, vec![0, 1]),
+            (3, vec![0, 1, 2]),
+            (4, vec![0, 1]),
+            (5, vec![0, 1, 4]),
+            (6, vec![0, 1, 2, 3]),
+        ] {
+            ancestors.insert(*slot, slot_ancestors.iter().copied().collect());
+        }
+
+        // a vote below the root on another fork is still locked out
+        let mut tower = Tower::new_for_tests(0, 0.67);
+        tower.record_vote(4, Hash::default());
+        assert_eq!(tower.record_cluster_root(6, &ancestors), None);
+        assert_eq!(tower.root(), 0);
+        assert!(tower.has_voted(4));
+        // until its lockout runs out
+        let expiration = tower.lockouts.votes[0].expiration_slot();
+        assert!(expiration < 8);
+        ancestors.insert(8, (0..4).chain(6..8).collect());
+        assert_eq!(tower.record_cluster_root(8, &ancestors), Some(8));
+        assert!(!tower.has_voted(4));
+
+        // a vote above the root that doesn't descend from it
+        let mut tower = Tower::new_for_tests(0, 0.67);
+        tower.record_vote(5, Hash::default());
+        assert_eq!(tower.record_cluster_root(3, &ancestors), None);
+        assert_eq!(tower.root(), 0);
+        // a root on the vote's own fork is followed, the vote stays
+        assert_eq!(tower.record_cluster_root(4, &ancestors), Some(4));
+        assert!(tower.has_voted(5));
+
+        // a root that isn't known can't be checked against the votes below it
+        let mut tower = Tower::new_for_tests(0, 0.67);
+        tower.record_vote(1, Hash::default());
+        assert_eq!(tower.record_cluster_root(2, &HashMap::new()), None);
+    }
+
     #[test]
     fn test_check_already_voted() {
         let mut tower = Tower::new_for_tests(0, 0.67);
@@ -2412,14 +2671,14 @@ pub mod test {
         let mut tower = Tower::new_for_tests(1, 0.67);
         let stakes = vec![(0, 1)].into_iter().collect();
         tower.record_vote(0, Hash::default());
-        assert!(!tower.check_vote_stake_threshold(1, &stakes, 2));
+        assert!(!tower.check_vote_stake_threshold(1, &stakes, &HashMap::new(), 2));
     }
     #[test]
     fn test_check_vote_threshold_above_threshold() {
         let mut tower = Tower::new_for_tests(1, 0.67);
         let stakes = vec![(0, 2)].into_iter().collect();
         tower.record_vote(0, Hash::default());
-        assert!(tower.check_vote_stake_threshold(1, &stakes, 2));
+        assert!(tower.check_vote_stake_threshold(1, &stakes, &HashMap::new(), 2));
     }
 
     #[test]
@@ -2429,7 +2688,7 @@ pub mod test {
         tower.record_vote(0, Hash::default());
         tower.record_vote(1, Hash::default());
         tower.record_vote(2, Hash::default());
-        assert!(tower.check_vote_stake_threshold(6, &stakes, 2));
+        assert!(tower.check_vote_stake_threshold(6, &stakes, &HashMap::new(), 2));
     }
 
     #[test]
@@ -2437,7 +2696,7 @@ pub mod test {
         let mut tower = Tower::new_for_tests(1, 0.67);
         let stakes = HashMap::new();
         tower.record_vote(0, Hash::default());
-        assert!(!tower.check_vote_stake_threshold(1, &stakes, 2));
+        assert!(!tower.check_vote_stake_threshold(1, &stakes, &HashMap::new(), 2));
     }
 
     #[test]
@@ -2448,7 +2707,7 @@ pub mod test {
         tower.record_vote(0, Hash::default());
         tower.record_vote(1, Hash::default());
         tower.record_vote(2, Hash::default());
-        assert!(tower.check_vote_stake_threshold(6, &stakes, 2,));
+        assert!(tower.check_vote_stake_threshold(6, &stakes, &HashMap::new(), 2,));
     }
 
     #[test]
@@ -2460,7 +2719,13 @@ pub mod test {
         });
         let set: HashSet<u64> = vec![0u64, 1u64].into_iter().collect();
         let ancestors: HashMap<u64, HashSet<u64>> = [(2u64, set)].iter().cloned().collect();
-        Tower::update_ancestor_voted_stakes(&mut voted_stakes, 2, account.lamports, &ancestors);
+        Tower::update_ancestor_voted_stakes(
+            &mut voted_stakes,
+            2,
+            account.lamports,
+            &ancestors,
+            |_| true,
+        );
         assert_eq!(voted_stakes[&0], 1);
         assert_eq!(voted_stakes[&1], 1);
         assert_eq!(voted_stakes[&2], 1);
@@ -2565,8 +2830,14 @@ pub mod test {
             &ancestors,
             |_| None,
             &mut LatestValidatorVotesForFrozenBanks::default(),
+            None,
         );
-        assert!(tower.check_vote_stake_threshold(vote_to_evaluate, &voted_stakes, total_stake,));
+        assert!(tower.check_vote_stake_threshold(
+            vote_to_evaluate,
+            &voted_stakes,
+            &HashMap::new(),
+            total_stake,
+        ));
 
         // CASE 2: Now we want to evaluate a vote for slot VOTE_THRESHOLD_DEPTH + 1. This slot
         // will expire the vote in one of the vote accounts, so we should have insufficient
@@ -2583,8 +2854,14 @@ pub mod test {
             &ancestors,
             |_| None,
             &mut LatestValidatorVotesForFrozenBanks::default(),
+            None,
         );
-        assert!(!tower.check_vote_stake_threshold(vote_to_evaluate, &voted_stakes, total_stake,));
+        assert!(!tower.check_vote_stake_threshold(
+            vote_to_evaluate,
+            &voted_stakes,
+            &HashMap::new(),
+            total_stake,
+        ));
     }
 
     fn vote_and_check_recent(num_votes: usize) {
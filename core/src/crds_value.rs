@@ -36,6 +36,9 @@ pub const MAX_VOTES: VoteIndex = 32;
 pub type EpochSlotsIndex = u8;
 pub const MAX_EPOCH_SLOTS: EpochSlotsIndex = 255;
 
+/// how many slots past its first slot a single vote group claim can cover
+pub const MAX_VOTE_GROUP_CLAIM_SLOTS: u64 = 1024;
+
 /// CrdsValue that is replicated across the cluster
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, AbiExample)]
 pub struct CrdsValue {
@@ -89,6 +92,9 @@ pub enum CrdsData {
     Version(Version),
     NodeInstance(NodeInstance),
     DuplicateShred(DuplicateShredIndex, DuplicateShred),
+    /// Nodes without this variant fail to deserialize any push or pull message carrying
+    /// it, claims are only pushed once feature_set::vote_group_gossip_claims is active
+    VoteGroupClaim(VoteGroupClaim),
 }
 
 impl Sanitize for CrdsData {
@@ -125,6 +131,7 @@ impl Sanitize for CrdsData {
                     shred.sanitize()
                 }
             }
+            CrdsData::VoteGroupClaim(claim) => claim.sanitize(),
         }
     }
 }
@@ -138,7 +145,7 @@ pub(crate) fn new_rand_timestamp<R: Rng>(rng: &mut R) -> u64 {
 impl CrdsData {
     /// New random CrdsData for tests and benchmarks.
     fn new_rand<R: Rng>(rng: &mut R, pubkey: Option<Pubkey>) -> CrdsData {
-        let kind = rng.gen_range(0, 7);
+        let kind = rng.gen_range(0, 8);
         // TODO: Implement other kinds of CrdsData here.
         // TODO: Assign ranges to each arm proportional to their frequency in
         // the mainnet crds table.
@@ -149,6 +156,7 @@ impl CrdsData {
             3 => CrdsData::AccountsHashes(SnapshotHash::new_rand(rng, pubkey)),
             4 => CrdsData::Version(Version::new_rand(rng, pubkey)),
             5 => CrdsData::Vote(rng.gen_range(0, MAX_VOTES), Vote::new_rand(rng, pubkey)),
+            6 => CrdsData::VoteGroupClaim(VoteGroupClaim::new_rand(rng, pubkey)),
             _ => CrdsData::EpochSlots(
                 rng.gen_range(0, MAX_EPOCH_SLOTS),
                 EpochSlots::new_rand(rng, pubkey),
@@ -454,6 +462,91 @@ impl Sanitize for NodeInstance {
     }
 }
 
+/// A node announcing the slots it believes its vote account is in the vote group for.
+/// Peers can give its vote packets for those slots priority, and monitoring can pick out
+/// nodes that claim a place in a group but never vote.
+#[derive(Clone, Debug, PartialEq, AbiExample, Deserialize, Serialize)]
+pub struct VoteGroupClaim {
+    pub from: Pubkey,
+    pub vote_account: Pubkey,
+    pub first_slot: Slot,
+    slots: Vec<u8>, // bit i is set when first_slot + i is claimed
+    pub wallclock: u64,
+}
+
+impl VoteGroupClaim {
+    pub fn new(from: Pubkey, vote_account: Pubkey, slot: Slot, now: u64) -> Self {
+        Self {
+            from,
+            vote_account,
+            first_slot: slot,
+            slots: vec![1],
+            wallclock: now,
+        }
+    }
+
+    /// New random VoteGroupClaim for tests and benchmarks.
+    fn new_rand<R: Rng>(rng: &mut R, pubkey: Option<Pubkey>) -> Self {
+        let first_slot = rng.gen_range(0, MAX_SLOT - MAX_VOTE_GROUP_CLAIM_SLOTS);
+        let mut claim = Self::new(
+            pubkey.unwrap_or_else(pubkey::new_rand),
+            pubkey::new_rand(),
+            first_slot,
+            new_rand_timestamp(rng),
+        );
+        for _ in 0..rng.gen_range(0, 16) {
+            claim.add(first_slot + rng.gen_range(0, MAX_VOTE_GROUP_CLAIM_SLOTS));
+        }
+        claim
+    }
+
+    /// Claims `slot` as well, false if it falls outside of the slots this claim can cover.
+    pub fn add(&mut self, slot: Slot) -> bool {
+        if slot < self.first_slot || slot - self.first_slot >= MAX_VOTE_GROUP_CLAIM_SLOTS {
+            return false;
+        }
+        let offset = (slot - self.first_slot) as usize;
+        if self.slots.len() <= offset / 8 {
+            self.slots.resize(offset / 8 + 1, 0);
+        }
+        self.slots[offset / 8] |= 1 << (offset % 8);
+        true
+    }
+
+    pub fn contains(&self, slot: Slot) -> bool {
+        if slot < self.first_slot {
+            return false;
+        }
+        let offset = slot - self.first_slot;
+        match self.slots.get((offset / 8) as usize) {
+            Some(byte) => byte & (1 << (offset % 8)) != 0,
+            None => false,
+        }
+    }
+
+    /// The claimed slots in ascending order.
+    pub fn slots(&self) -> impl Iterator<Item = Slot> + '_ {
+        (0..self.slots.len() as u64 * 8)
+            .filter(move |offset| self.slots[(offset / 8) as usize] & (1 << (offset % 8)) != 0)
+            .map(move |offset| self.first_slot + offset)
+    }
+
+    pub fn last_slot(&self) -> Slot {
+        self.slots().last().unwrap_or(self.first_slot)
+    }
+}
+
+impl Sanitize for VoteGroupClaim {
+    fn sanitize(&self) -> Result<(), SanitizeError> {
+        sanitize_wallclock(self.wallclock)?;
+        if self.first_slot >= MAX_SLOT || self.slots.len() as u64 > MAX_VOTE_GROUP_CLAIM_SLOTS / 8 {
+            return Err(SanitizeError::ValueOutOfBounds);
+        }
+        self.from.sanitize()?;
+        self.vote_account.sanitize()
+    }
+}
+
 /// Type of the replicated value
 /// These are labels for values in a record that is associated with `Pubkey`
 #[derive(PartialEq, Hash, Eq, Clone, Debug)]
@@ -468,6 +561,7 @@ pub enum CrdsValueLabel {
     Version(Pubkey),
     NodeInstance(Pubkey),
     DuplicateShred(DuplicateShredIndex, Pubkey),
+    VoteGroupClaim(Pubkey),
 }
 
 impl fmt::Display for CrdsValueLabel {
@@ -483,6 +577,7 @@ impl fmt::Display for CrdsValueLabel {
             CrdsValueLabel::Version(_) => write!(f, "Version({})", self.pubkey()),
             CrdsValueLabel::NodeInstance(pk) => write!(f, "NodeInstance({})", pk),
             CrdsValueLabel::DuplicateShred(ix, pk) => write!(f, "DuplicateShred({}, {})", ix, pk),
+            CrdsValueLabel::VoteGroupClaim(pk) => write!(f, "VoteGroupClaim({})", pk),
         }
     }
 }
@@ -500,6 +595,7 @@ impl CrdsValueLabel {
             CrdsValueLabel::Version(p) => *p,
             CrdsValueLabel::NodeInstance(p) => *p,
             CrdsValueLabel::DuplicateShred(_, p) => *p,
+            CrdsValueLabel::VoteGroupClaim(p) => *p,
         }
     }
 }
@@ -548,6 +644,7 @@ impl CrdsValue {
             CrdsData::Version(version) => version.wallclock,
             CrdsData::NodeInstance(node) => node.wallclock,
             CrdsData::DuplicateShred(_, shred) => shred.wallclock,
+            CrdsData::VoteGroupClaim(claim) => claim.wallclock,
         }
     }
     pub fn pubkey(&self) -> Pubkey {
@@ -562,6 +659,7 @@ impl CrdsValue {
             CrdsData::Version(version) => version.from,
             CrdsData::NodeInstance(node) => node.from,
             CrdsData::DuplicateShred(_, shred) => shred.from,
+            CrdsData::VoteGroupClaim(claim) => claim.from,
         }
     }
     pub fn label(&self) -> CrdsValueLabel {
@@ -576,6 +674,7 @@ impl CrdsValue {
             CrdsData::Version(_) => CrdsValueLabel::Version(self.pubkey()),
             CrdsData::NodeInstance(node) => CrdsValueLabel::NodeInstance(node.from),
             CrdsData::DuplicateShred(ix, shred) => CrdsValueLabel::DuplicateShred(*ix, shred.from),
+            CrdsData::VoteGroupClaim(claim) => CrdsValueLabel::VoteGroupClaim(claim.from),
         }
     }
     pub fn contact_info(&self) -> Option<&ContactInfo> {
@@ -635,6 +734,13 @@ impl CrdsValue {
         }
     }
 
+    pub fn vote_group_claim(&self) -> Option<&VoteGroupClaim> {
+        match &self.data {
+            CrdsData::VoteGroupClaim(claim) => Some(claim),
+            _ => None,
+        }
+    }
+
     /// Returns the size (in bytes) of a CrdsValue
     pub fn size(&self) -> u64 {
         serialized_size(&self).expect("unable to serialize contact info")
@@ -1057,4 +1163,35 @@ mod test {
         assert!(node.should_force_push(&pubkey));
         assert!(!node.should_force_push(&Pubkey::new_unique()));
     }
+
+    #[test]
+    fn test_vote_group_claim() {
+        let from = Pubkey::new_unique();
+        let vote_account = Pubkey::new_unique();
+        let mut claim = VoteGroupClaim::new(from, vote_account, 100, timestamp());
+        assert!(claim.contains(100));
+        assert!(claim.add(103));
+        assert!(claim.add(100 + MAX_VOTE_GROUP_CLAIM_SLOTS - 1));
+        assert!(!claim.add(100 + MAX_VOTE_GROUP_CLAIM_SLOTS));
+        assert!(!claim.add(99));
+        assert!(!claim.contains(101));
+        assert!(!claim.contains(99));
+        assert!(!claim.contains(100 + MAX_VOTE_GROUP_CLAIM_SLOTS));
+        assert_eq!(
+            claim.slots().collect::<Vec<_>>(),
+            vec![100, 103, 100 + MAX_VOTE_GROUP_CLAIM_SLOTS - 1]
+        );
+        assert_eq!(claim.last_slot(), 100 + MAX_VOTE_GROUP_CLAIM_SLOTS - 1);
+
+        let value = CrdsValue::new_signed(CrdsData::VoteGroupClaim(claim.clone()), &Keypair::new());
+        assert_eq!(value.sanitize(), Ok(()));
+        assert_eq!(value.label(), CrdsValueLabel::VoteGroupClaim(from));
+        assert_eq!(value.pubkey(), from);
+        assert_eq!(value.vote_group_claim(), Some(&claim));
+
+        let mut oversized = claim;
+        oversized.slots.push(0);
+        let value = CrdsValue::new_unsigned(CrdsData::VoteGroupClaim(oversized));
+        assert_eq!(value.sanitize(), Err(SanitizeError::ValueOutOfBounds));
+    }
 }
@@ -255,6 +255,7 @@ pub(crate) struct ForkStats {
     pub(crate) vote_threshold: bool,
     pub(crate) is_locked_out: bool,
     pub(crate) voted_stakes: VotedStakes,
+    pub(crate) group_stakes: VotedStakes,
     pub(crate) is_supermajority_confirmed: bool,
     pub(crate) computed: bool,
     pub(crate) lockout_intervals: LockoutIntervals,
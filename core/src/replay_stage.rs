@@ -13,6 +13,7 @@ use crate::{
     consensus::{
         ComputedBankState, Stake, SwitchForkDecision, Tower, VotedStakes, SWITCH_FORK_THRESHOLD,
     },
+    crds::Cursor,
     fork_choice::{ForkChoice, SelectVoteAndResetForkResult},
     heaviest_subtree_fork_choice::HeaviestSubtreeForkChoice,
     latest_validator_votes_for_frozen_banks::LatestValidatorVotesForFrozenBanks,
@@ -30,6 +31,7 @@ use solana_client::rpc_response::SlotUpdate;
 use solana_ledger::{
     block_error::BlockError,
     blockstore::Blockstore,
+    blockstore_meta::VoteGroupRecord,
     blockstore_processor::{self, BlockstoreProcessorError, TransactionStatusSender},
     entry::VerifyRecyclers,
     leader_schedule_cache::LeaderScheduleCache,
@@ -38,10 +40,12 @@ use solana_measure::{measure::Measure, thread_mem_usage};
 use solana_metrics::inc_new_counter_info;
 use solana_runtime::{
     accounts_background_service::AbsRequestSender, bank::Bank, bank_forks::BankForks,
-    commitment::BlockCommitmentCache, vote_sender_types::ReplayVoteSender,
+    commitment::BlockCommitmentCache, shared_vote_group_generator::SharedVoteGroupGenerator,
+    vote_sender_types::ReplayVoteSender,
 };
 use solana_sdk::{
     clock::{Slot, MAX_PROCESSING_AGE, NUM_CONSECUTIVE_LEADER_SLOTS},
+    feature_set,
     genesis_config::ClusterType,
     hash::Hash,
     pubkey::Pubkey,
@@ -51,9 +55,10 @@ use solana_sdk::{
     transaction::Transaction,
     instruction::VoterGroup,
 };
-use solana_vote_program::vote_state::Vote;
+use solana_vote_program::{vote_state::Vote, vote_transaction};
 use std::{
     collections::{BTreeMap, HashMap, HashSet},
+    net::SocketAddr,
     result,
     sync::{
         atomic::{AtomicBool, Ordering},
@@ -117,6 +122,7 @@ pub struct ReplayStageConfig {
     pub exit: Arc<AtomicBool>,
     pub subscriptions: Arc<RpcSubscriptions>,
     pub leader_schedule_cache: Arc<LeaderScheduleCache>,
+    pub vote_group_generator: Arc<SharedVoteGroupGenerator>,
     pub latest_root_senders: Vec<Sender<Slot>>,
     pub accounts_background_request_sender: AbsRequestSender,
     pub block_commitment_cache: Arc<RwLock<BlockCommitmentCache>>,
@@ -314,6 +320,7 @@ impl ReplayStage {
             exit,
             subscriptions,
             leader_schedule_cache,
+            vote_group_generator,
             latest_root_senders,
             accounts_background_request_sender,
             block_commitment_cache,
@@ -380,6 +387,9 @@ impl ReplayStage {
                         &subscriptions,
                         &mut progress,
                     );
+                    // the first bank of a new epoch swaps in the epoch's vote group generator
+                    let working_bank = bank_forks.read().unwrap().working_bank();
+                    vote_group_generator.set_working_bank(&working_bank);
                     generate_new_bank_forks_time.stop();
                     Self::report_memory(&allocated, "generate_new_bank_forks", start);
 
@@ -506,6 +516,7 @@ impl ReplayStage {
                         let confirmed_forks = Self::confirm_forks(
                             &tower,
                             &fork_stats.voted_stakes,
+                            &fork_stats.group_stakes,
                             fork_stats.total_stake,
                             &progress,
                             &bank_forks,
@@ -594,6 +605,7 @@ impl ReplayStage {
                             &cluster_info,
                             &blockstore,
                             &leader_schedule_cache,
+                            &vote_group_generator,
                             &lockouts_sender,
                             &accounts_background_request_sender,
                             &latest_root_senders,
@@ -607,6 +619,7 @@ impl ReplayStage {
                             &mut voted_signatures,
                             &mut has_new_vote_been_rooted,
                             &mut replay_timing,
+                            &ancestors,
                         );
                     };
                     voting_time.stop();
@@ -1335,6 +1348,108 @@ impl ReplayStage {
         );
     }
 
+    // Archive the seed and members of each newly rooted slot's vote group, so historical
+    // membership can be answered after the epoch's voter set is gone from account state.
+    // Participation is read from the vote states of `voted_bank`, the bank just voted on,
+    // and summed up per epoch as well
+    fn record_vote_groups(blockstore: &Blockstore, voted_bank: &Bank, rooted_banks: &[Arc<Bank>]) {
+        let vote_states: HashMap<Pubkey, (Vec<Slot>, Option<Slot>)> = voted_bank
+            .vote_accounts()
+            .into_iter()
+            .filter_map(|(vote_pubkey, (_, vote_account))| {
+                let vote_state = vote_account.vote_state();
+                let vote_state = vote_state.as_ref().ok()?;
+                let slots = vote_state
+                    .votes
+                    .iter()
+                    .map(|lockout| lockout.slot)
+                    .collect();
+                Some((vote_pubkey, (slots, vote_state.root_slot)))
+            })
+            .collect();
+        for bank in rooted_banks {
+            let slot = bank.slot();
+            // ledger-tool verify re-derives the seeds of rooted slots and compares them
+            if let Some((window_start, record)) = blockstore_processor::vote_group_seed_record(bank)
+            {
+                if let Err(err) = blockstore.cache_vote_group_seed(window_start, &record) {
+                    error!(
+                        "Unable to record vote group seed for slot {}: {:?}",
+                        slot, err
+                    );
+                }
+            }
+            if let Some((generator, seed)) = bank.vote_group_for_slot(slot) {
+                let members = generator.group_for_seed(seed);
+                let voted = members
+                    .iter()
+                    .filter(|member| {
+                        vote_states.get(member).map_or(false, |(slots, root_slot)| {
+                            slots.contains(&slot) || root_slot.map_or(false, |root| root >= slot)
+                        })
+                    })
+                    .cloned()
+                    .collect::<Vec<_>>();
+                datapoint_info!(
+                    "vote_group_size",
+                    ("slot", slot, i64),
+                    ("members", members.len(), i64),
+                    ("voted", voted.len(), i64),
+                );
+                let record = VoteGroupRecord {
+                    seed,
+                    members,
+                    voted,
+                };
+                if let Err(err) = blockstore.cache_vote_group(slot, &record) {
+                    error!("Unable to record vote group for slot {}: {:?}", slot, err);
+                }
+                if let Err(err) =
+                    blockstore.record_vote_group_participation(bank.epoch(), slot, &record)
+                {
+                    error!(
+                        "Unable to record vote group participation for slot {}: {:?}",
+                        slot, err
+                    );
+                }
+            }
+        }
+        let cache_stats = voted_bank.vote_group_generator().take_cache_stats();
+        datapoint_info!(
+            "vote_group_cache",
+            ("hits", cache_stats.hits, i64),
+            ("misses", cache_stats.misses, i64),
+            ("hit_ratio", cache_stats.hit_ratio(), f64),
+        );
+    }
+
+    /// whether this validator votes on the bank at `slot` with `hash`, once limited voting is
+    /// enforced in `bank` only the members of that bank's group do. a vote's pair is its last
+    /// slot and its hash, the same pair the vote program checks
+    fn is_in_vote_group(bank: &Bank, slot: Slot, hash: Hash, vote_account_pubkey: &Pubkey) -> bool {
+        !bank.is_limited_voting_enforced() || bank.in_group(slot, hash, *vote_account_pubkey)
+    }
+
+    /// moves the tower's root up to the cluster's highest confirmed root when it is on
+    /// `bank`'s fork and none of the tower's votes is locked out against it. Returns the new
+    /// root if it moved
+    fn follow_cluster_root(
+        bank: &Bank,
+        tower: &mut Tower,
+        block_commitment_cache: &RwLock<BlockCommitmentCache>,
+        ancestors: &HashMap<Slot, HashSet<Slot>>,
+    ) -> Option<Slot> {
+        let cluster_root = block_commitment_cache
+            .read()
+            .unwrap()
+            .highest_confirmed_root();
+        if bank.ancestors.contains_key(&cluster_root) {
+            tower.record_cluster_root(cluster_root, ancestors)
+        } else {
+            None
+        }
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn handle_votable_bank(
         bank: &Arc<Bank>,
@@ -1348,6 +1463,7 @@ impl ReplayStage {
         cluster_info: &Arc<ClusterInfo>,
         blockstore: &Arc<Blockstore>,
         leader_schedule_cache: &Arc<LeaderScheduleCache>,
+        vote_group_generator: &SharedVoteGroupGenerator,
         lockouts_sender: &Sender<CommitmentAggregationData>,
         accounts_background_request_sender: &AbsRequestSender,
         latest_root_senders: &[Sender<Slot>],
@@ -1361,12 +1477,21 @@ impl ReplayStage {
         vote_signatures: &mut Vec<Signature>,
         has_new_vote_been_rooted: &mut bool,
         replay_timing: &mut ReplayTiming,
+        ancestors: &HashMap<Slot, HashSet<Slot>>,
     ) {
         if bank.is_empty() {
             inc_new_counter_info!("replay_stage-voted_empty_bank", 1);
         }
         trace!("handle votable bank {}", bank.slot());
-        let new_root = tower.record_bank_vote(bank, vote_account_pubkey);
+        let in_group = Self::is_in_vote_group(bank, bank.slot(), bank.hash(), vote_account_pubkey);
+        let new_root = if in_group {
+            tower.record_bank_vote(bank, vote_account_pubkey)
+        } else {
+            // no vote is sent outside the slot's group, keep the slot out of the tower so its
+            // lockouts don't drift from the votes the cluster saw and block the next group
+            inc_new_counter_info!("replay_stage-out_of_group_bank", 1);
+            Self::follow_cluster_root(bank, tower, block_commitment_cache, ancestors)
+        };
 
         if let Err(err) = tower.save(&cluster_info.keypair) {
             error!("Unable to save tower: {:?}", err);
@@ -1389,9 +1514,11 @@ impl ReplayStage {
             // get shreds for repair on gossip before we update leader schedule, otherwise they may
             // get dropped.
             leader_schedule_cache.set_root(rooted_banks.last().unwrap());
+            vote_group_generator.set_root(rooted_banks.last().unwrap());
             blockstore
                 .set_roots(&rooted_slots)
                 .expect("Ledger set roots failed");
+            Self::record_vote_groups(blockstore, bank, &rooted_banks);
             let highest_confirmed_root = Some(
                 block_commitment_cache
                     .read()
@@ -1435,6 +1562,9 @@ impl ReplayStage {
         update_commitment_cache_time.stop();
         replay_timing.update_commitment_cache_us += update_commitment_cache_time.as_us();
 
+        if !in_group {
+            return;
+        }
         Self::push_vote(
             cluster_info,
             bank,
@@ -1466,7 +1596,11 @@ impl ReplayStage {
         log::trace!("authorized_voter_pubkey_string {}", vote_account_pubkey.to_string());
         log::trace!("vote_hash: {}", vote.hash);
   
-        let in_group = bank.in_group(vote.slots[0],vote.hash,*vote_account_pubkey);
+        // every voter keeps voting through the epoch limited voting activates in, so both
+        // voter sets can be compared
+        let in_group = vote.slots.last().map_or(false, |slot| {
+            Self::is_in_vote_group(bank, *slot, vote.hash, vote_account_pubkey)
+        });
 
         if in_group {
             warn!(
@@ -1652,18 +1786,76 @@ impl ReplayStage {
         if let Some(vote_tx) = vote_tx {
             tower.refresh_last_vote_tx_blockhash(vote_tx.message.recent_blockhash);
             let mut send_time = Measure::start("send_vote");
-            let _ = cluster_info.send_vote(
-                &vote_tx,
-                crate::banking_stage::next_leader_tpu(cluster_info, poh_recorder),
-            );
+            let tpu = crate::banking_stage::next_leader_tpu(cluster_info, poh_recorder);
+            let _ = cluster_info.send_vote(&vote_tx, tpu);
             send_time.stop();
             let mut push_time = Measure::start("push_vote");
             cluster_info.push_vote(&tower.tower_slots(), vote_tx);
+            // a vote only gets generated when the vote account is in the group of the voted
+            // bank, the last slot of the vote
+            if let Some(slot) = tower.last_vote().slots.last() {
+                // nodes that predate the claims can't deserialize a gossip message carrying
+                // one, they only go out once the cluster has upgraded
+                if bank
+                    .feature_set
+                    .is_active(&feature_set::vote_group_gossip_claims::id())
+                {
+                    cluster_info.push_vote_group_claim(*vote_account_pubkey, *slot);
+                }
+                Self::relay_group_votes(
+                    cluster_info,
+                    bank,
+                    vote_account_pubkey,
+                    *slot,
+                    tower.last_vote().hash,
+                    tpu,
+                );
+            }
             push_time.stop();
             replay_timing.vote_push_us += push_time.as_us();
         }
     }
 
+    /// the group leader of a slot relays the votes the other members of the group pushed to
+    /// gossip straight to the next leader, so they don't all have to wait on gossip to get
+    /// there. every other member leaves it at its own vote
+    fn relay_group_votes(
+        cluster_info: &ClusterInfo,
+        bank: &Bank,
+        vote_account_pubkey: &Pubkey,
+        slot: Slot,
+        hash: Hash,
+        tpu: Option<SocketAddr>,
+    ) {
+        // every voter sends its own votes until limited voting is enforced
+        if !bank.is_limited_voting_enforced()
+            || bank.vote_group_leader(slot, hash) != Some(*vote_account_pubkey)
+        {
+            return;
+        }
+        let (_, votes) = cluster_info.get_votes(&mut Cursor::default());
+        let mut relayed = 0;
+        for vote_tx in votes {
+            let is_member_vote = match vote_transaction::parse_vote_transaction(&vote_tx) {
+                Some((vote_account, vote, _)) => {
+                    vote_account != *vote_account_pubkey
+                        && vote.slots.last() == Some(&slot)
+                        && vote.hash == hash
+                        && bank.in_group(slot, hash, vote_account)
+                }
+                None => false,
+            };
+            if is_member_vote && cluster_info.send_vote(&vote_tx, tpu).is_ok() {
+                relayed += 1;
+            }
+        }
+        datapoint_info!(
+            "replay_stage-vote_group_relay",
+            ("slot", slot, i64),
+            ("relayed", relayed, i64),
+        );
+    }
+
     fn update_commitment_cache(
         bank: Arc<Bank>,
         root: Slot,
@@ -1854,6 +2046,11 @@ impl ReplayStage {
                     unfrozen_gossip_verified_vote_hashes.remove_slot_hash(bank.slot(), &bank_hash)
                 {
                     for pubkey in new_frozen_voters {
+                        if bank.is_group_weighted_fork_choice_active()
+                            && !bank.in_group(bank.slot(), bank_hash, pubkey)
+                        {
+                            continue;
+                        }
                         latest_validator_votes_for_frozen_banks.check_add_vote(
                             pubkey,
                             bank.slot(),
@@ -1902,6 +2099,11 @@ impl ReplayStage {
                     .expect("All frozen banks must exist in the Progress map")
                     .computed;
                 if !is_computed {
+                    let vote_groups = |slot| {
+                        progress
+                            .get_hash(slot)
+                            .and_then(|hash| bank.vote_group_stakes(slot, hash))
+                    };
                     let computed_bank_state = Tower::collect_vote_lockouts(
                         my_vote_pubkey,
                         bank_slot,
@@ -1909,6 +2111,11 @@ impl ReplayStage {
                         &ancestors,
                         |slot| progress.get_hash(slot),
                         latest_validator_votes_for_frozen_banks,
+                        if bank.is_group_weighted_fork_choice_active() {
+                            Some(&vote_groups)
+                        } else {
+                            None
+                        },
                     );
                     // Notify any listeners of the votes found in this newly computed
                     // bank
@@ -1920,6 +2127,7 @@ impl ReplayStage {
                     let ComputedBankState {
                         voted_stakes,
                         total_stake,
+                        group_stakes,
                         lockout_intervals,
                         my_latest_landed_vote,
                         ..
@@ -1929,6 +2137,7 @@ impl ReplayStage {
                         .expect("All frozen banks must exist in the Progress map");
                     stats.total_stake = total_stake;
                     stats.voted_stakes = voted_stakes;
+                    stats.group_stakes = group_stakes;
                     stats.lockout_intervals = lockout_intervals;
                     stats.block_height = bank.block_height();
                     stats.bank_hash = Some(bank.hash());
@@ -1964,8 +2173,12 @@ impl ReplayStage {
                 .get_fork_stats_mut(bank_slot)
                 .expect("All frozen banks must exist in the Progress map");
 
-            stats.vote_threshold =
-                tower.check_vote_stake_threshold(bank_slot, &stats.voted_stakes, stats.total_stake);
+            stats.vote_threshold = tower.check_vote_stake_threshold(
+                bank_slot,
+                &stats.voted_stakes,
+                &stats.group_stakes,
+                stats.total_stake,
+            );
             stats.is_locked_out = tower.is_locked_out(bank_slot, &ancestors);
             stats.has_voted = tower.has_voted(bank_slot);
             stats.is_recent = tower.is_recent(bank_slot);
@@ -2380,6 +2593,7 @@ impl ReplayStage {
     fn confirm_forks(
         tower: &Tower,
         voted_stakes: &VotedStakes,
+        group_stakes: &VotedStakes,
         total_stake: Stake,
         progress: &ProgressMap,
         bank_forks: &RwLock<BankForks>,
@@ -2394,7 +2608,9 @@ impl ReplayStage {
                     .expect("bank in progress must exist in BankForks")
                     .clone();
                 let duration = prog.replay_stats.started.elapsed().as_millis();
-                if bank.is_frozen() && tower.is_slot_confirmed(*slot, voted_stakes, total_stake) {
+                if bank.is_frozen()
+                    && tower.is_slot_confirmed(*slot, voted_stakes, group_stakes, total_stake)
+                {
                     info!("validator fork confirmed {} {}ms", *slot, duration);
                     datapoint_info!("validator-confirmation", ("duration_ms", duration, i64));
                     confirmed_forks.push(*slot);
@@ -2571,7 +2787,7 @@ pub(crate) mod tests {
     use crate::{
         cluster_info::Node,
         consensus::test::{initialize_state, VoteSimulator},
-        consensus::Tower,
+        consensus::{Tower, VOTE_THRESHOLD_DEPTH},
         crds::Cursor,
         optimistically_confirmed_bank_tracker::OptimisticallyConfirmedBank,
         progress_map::ValidatorStakeInfo,
@@ -2593,7 +2809,7 @@ pub(crate) mod tests {
     };
     use solana_runtime::{
         accounts_background_service::AbsRequestSender,
-        commitment::BlockCommitment,
+        commitment::{BlockCommitment, VOTE_THRESHOLD_SIZE},
         genesis_utils::{self, GenesisConfigInfo, ValidatorVoteKeypairs},
     };
     use solana_sdk::{
@@ -2663,12 +2879,17 @@ pub(crate) mod tests {
                 .iter()
                 .map(|v| (v.node_keypair.pubkey(), v.vote_keypair.pubkey()))
                 .collect();
-        let GenesisConfigInfo { genesis_config, .. } =
-            genesis_utils::create_genesis_config_with_vote_accounts(
-                10_000,
-                &validator_authorized_voter_keypairs,
-                vec![100; validator_authorized_voter_keypairs.len()],
-            );
+        let GenesisConfigInfo {
+            mut genesis_config, ..
+        } = genesis_utils::create_genesis_config_with_vote_accounts(
+            10_000,
+            &validator_authorized_voter_keypairs,
+            vec![100; validator_authorized_voter_keypairs.len()],
+        );
+        // every validator votes on every slot, the vote group checks have their own tests
+        genesis_config
+            .accounts
+            .remove(&feature_set::limited_voting::id());
 
         let bank0 = Bank::new(&genesis_config);
 
@@ -3586,6 +3807,7 @@ pub(crate) mod tests {
             let confirmed_forks = ReplayStage::confirm_forks(
                 &tower,
                 &fork_progress.fork_stats.voted_stakes,
+                &fork_progress.fork_stats.group_stakes,
                 fork_progress.fork_stats.total_stake,
                 &progress,
                 &bank_forks,
@@ -3635,6 +3857,7 @@ pub(crate) mod tests {
             let confirmed_forks = ReplayStage::confirm_forks(
                 &tower,
                 &fork_progress.fork_stats.voted_stakes,
+                &fork_progress.fork_stats.group_stakes,
                 fork_progress.fork_stats.total_stake,
                 &progress,
                 &bank_forks,
@@ -5107,6 +5330,60 @@ pub(crate) mod tests {
         )
     }
 
+    #[test]
+    fn test_follow_cluster_root() {
+        let vote_simulator = setup_forks();
+        let bank_forks = vote_simulator.bank_forks.read().unwrap();
+        let ancestors = bank_forks.ancestors();
+        let bank4 = bank_forks.get(4).unwrap().clone();
+        let bank6 = bank_forks.get(6).unwrap().clone();
+        let mut block_commitment_cache = BlockCommitmentCache::default();
+        block_commitment_cache.set_highest_confirmed_root(3);
+        let block_commitment_cache = RwLock::new(block_commitment_cache);
+
+        // the cluster root is on another fork than the bank
+        let mut tower = Tower::new_for_tests(VOTE_THRESHOLD_DEPTH, VOTE_THRESHOLD_SIZE);
+        assert_eq!(
+            ReplayStage::follow_cluster_root(
+                &bank4,
+                &mut tower,
+                &block_commitment_cache,
+                &ancestors
+            ),
+            None
+        );
+        assert_eq!(tower.root(), 0);
+
+        // the tower's vote for slot 2 is still locked out at slot 3 on the other fork
+        tower.record_vote(2, bank_forks.get(2).unwrap().hash());
+        assert_eq!(
+            ReplayStage::follow_cluster_root(
+                &bank6,
+                &mut tower,
+                &block_commitment_cache,
+                &ancestors
+            ),
+            None
+        );
+        assert_eq!(tower.root(), 0);
+        assert!(tower.has_voted(2));
+
+        // a vote for an ancestor of the cluster root follows it
+        let mut tower = Tower::new_for_tests(VOTE_THRESHOLD_DEPTH, VOTE_THRESHOLD_SIZE);
+        tower.record_vote(1, bank_forks.get(1).unwrap().hash());
+        assert_eq!(
+            ReplayStage::follow_cluster_root(
+                &bank6,
+                &mut tower,
+                &block_commitment_cache,
+                &ancestors
+            ),
+            Some(3)
+        );
+        assert_eq!(tower.root(), 3);
+        assert!(!tower.has_voted(1));
+    }
+
     fn setup_forks() -> VoteSimulator {
         /*
             Build fork structure:
@@ -31,7 +31,8 @@ use solana_client::{
         TokenAccountsFilter, DELINQUENT_VALIDATOR_SLOT_DISTANCE, MAX_GET_CONFIRMED_BLOCKS_RANGE,
         MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS2_LIMIT,
         MAX_GET_CONFIRMED_SIGNATURES_FOR_ADDRESS_SLOT_RANGE, MAX_GET_PROGRAM_ACCOUNT_FILTERS,
-        MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS, MAX_GET_SLOT_LEADERS, MAX_MULTIPLE_ACCOUNTS,
+        MAX_GET_SIGNATURE_STATUSES_QUERY_ITEMS, MAX_GET_SLOT_LEADERS,
+        MAX_GET_VOTE_GROUP_HISTORY_LIMIT, MAX_GET_VOTE_GROUP_SCHEDULE_LIMIT, MAX_MULTIPLE_ACCOUNTS,
         NUM_LARGEST_ACCOUNTS,
     },
     rpc_response::Response as RpcResponse,
@@ -51,6 +52,7 @@ use solana_runtime::{
     bank_forks::{BankForks, SnapshotConfig},
     commitment::{BlockCommitmentArray, BlockCommitmentCache, CommitmentSlots},
     inline_spl_token_v2_0::{SPL_TOKEN_ACCOUNT_MINT_OFFSET, SPL_TOKEN_ACCOUNT_OWNER_OFFSET},
+    shared_vote_group_generator::SharedVoteGroupGenerator,
     snapshot_utils::get_highest_snapshot_archive_path,
 };
 use solana_sdk::{
@@ -160,6 +162,7 @@ pub struct JsonRpcRequestProcessor {
     largest_accounts_cache: Arc<RwLock<LargestAccountsCache>>,
     max_slots: Arc<MaxSlots>,
     leader_schedule_cache: Arc<LeaderScheduleCache>,
+    vote_group_generator: Arc<SharedVoteGroupGenerator>,
     max_complete_transaction_status_slot: Arc<AtomicU64>,
 }
 impl Metadata for JsonRpcRequestProcessor {}
@@ -246,6 +249,7 @@ impl JsonRpcRequestProcessor {
         largest_accounts_cache: Arc<RwLock<LargestAccountsCache>>,
         max_slots: Arc<MaxSlots>,
         leader_schedule_cache: Arc<LeaderScheduleCache>,
+        vote_group_generator: Arc<SharedVoteGroupGenerator>,
         max_complete_transaction_status_slot: Arc<AtomicU64>,
     ) -> (Self, Receiver<TransactionInfo>) {
         let (sender, receiver) = channel();
@@ -267,6 +271,7 @@ impl JsonRpcRequestProcessor {
                 largest_accounts_cache,
                 max_slots,
                 leader_schedule_cache,
+                vote_group_generator,
                 max_complete_transaction_status_slot,
             },
             receiver,
@@ -310,6 +315,7 @@ impl JsonRpcRequestProcessor {
             largest_accounts_cache: Arc::new(RwLock::new(LargestAccountsCache::new(30))),
             max_slots: Arc::new(MaxSlots::default()),
             leader_schedule_cache: Arc::new(LeaderScheduleCache::new_from_bank(bank)),
+            vote_group_generator: Arc::new(SharedVoteGroupGenerator::new_from_bank(bank)),
             max_complete_transaction_status_slot: Arc::new(AtomicU64::default()),
         }
     }
@@ -1553,6 +1559,114 @@ impl JsonRpcRequestProcessor {
         })
     }
 
+    pub fn get_current_vote_group(
+        &self,
+        commitment: Option<CommitmentConfig>,
+    ) -> Result<Option<RpcCurrentVoteGroup>> {
+        let bank = self.bank(commitment);
+        Ok(get_rpc_current_vote_group(&bank))
+    }
+
+    pub fn get_vote_group_for_slot(
+        &self,
+        slot: Slot,
+        commitment: Option<CommitmentConfig>,
+    ) -> Result<Option<RpcVoteGroup>> {
+        let bank = self.bank(commitment);
+        Ok(get_rpc_vote_group(&bank, slot))
+    }
+
+    pub fn get_vote_group_history(
+        &self,
+        start_slot: Slot,
+        config: Option<RpcVoteGroupHistoryConfig>,
+    ) -> Result<Vec<RpcVoteGroupHistoryEntry>> {
+        let config = config.unwrap_or_default();
+        let limit = config.limit.unwrap_or(MAX_GET_VOTE_GROUP_HISTORY_LIMIT);
+        if limit == 0 || limit > MAX_GET_VOTE_GROUP_HISTORY_LIMIT {
+            return Err(Error::invalid_params(format!(
+                "Invalid limit; max {}",
+                MAX_GET_VOTE_GROUP_HISTORY_LIMIT
+            )));
+        }
+
+        // Groups are archived as slots are rooted, so only finalized slots can be returned
+        let highest_confirmed_root = self
+            .block_commitment_cache
+            .read()
+            .unwrap()
+            .highest_confirmed_root();
+        let end_slot = min(
+            config.end_slot.unwrap_or(highest_confirmed_root),
+            highest_confirmed_root,
+        );
+        if end_slot < start_slot {
+            return Ok(vec![]);
+        }
+
+        let lowest_blockstore_slot = self.blockstore.lowest_slot();
+        let history = self
+            .blockstore
+            .rooted_slot_iterator(max(start_slot, lowest_blockstore_slot))
+            .map_err(|_| Error::internal_error())?
+            .take_while(|&slot| slot <= end_slot)
+            .filter_map(|slot| {
+                let record = self.blockstore.get_vote_group(slot).ok()??;
+                Some(RpcVoteGroupHistoryEntry {
+                    slot,
+                    seed: record.seed,
+                    members: record.members.iter().map(|key| key.to_string()).collect(),
+                    voted: record.voted.iter().map(|key| key.to_string()).collect(),
+                })
+            })
+            .take(limit)
+            .collect();
+        Ok(history)
+    }
+
+    pub fn get_vote_group_schedule(
+        &self,
+        start_slot: Slot,
+        config: Option<RpcVoteGroupScheduleConfig>,
+    ) -> Result<Vec<RpcVoteGroupWindow>> {
+        let config = config.unwrap_or_default();
+        let limit = config.limit.unwrap_or(MAX_GET_VOTE_GROUP_SCHEDULE_LIMIT);
+        if limit == 0 || limit > MAX_GET_VOTE_GROUP_SCHEDULE_LIMIT {
+            return Err(Error::invalid_params(format!(
+                "Invalid limit; max {}",
+                MAX_GET_VOTE_GROUP_SCHEDULE_LIMIT
+            )));
+        }
+        let bank = self.bank(config.commitment);
+        Ok(get_rpc_vote_group_schedule(&bank, start_slot, limit))
+    }
+
+    pub fn get_vote_group_stake_weight(
+        &self,
+        slot: Slot,
+        commitment: Option<CommitmentConfig>,
+    ) -> Result<Option<RpcVoteGroupStakeWeight>> {
+        let bank = self.bank(commitment);
+        Ok(get_rpc_vote_group_stake_weight(&bank, slot))
+    }
+
+    pub fn is_voter_in_group(
+        &self,
+        vote_pubkey: &Pubkey,
+        slot: Slot,
+        commitment: Option<CommitmentConfig>,
+    ) -> Result<Option<bool>> {
+        // slots of the working epoch, and of the previous one right after the boundary, are
+        // answered by the shared generator without the banks
+        if let Some(in_group) = self.vote_group_generator.in_group_at(slot, vote_pubkey) {
+            return Ok(Some(in_group));
+        }
+        let bank = self.bank(commitment);
+        Ok(bank
+            .vote_group_for_slot(slot)
+            .and_then(|(generator, seed)| generator.try_in_group_for_seed(seed, *vote_pubkey).ok()))
+    }
+
     pub fn get_token_account_balance(
         &self,
         pubkey: &Pubkey,
@@ -2048,6 +2162,125 @@ fn get_spl_token_mint_filter(program_id: &Pubkey, filters: &[RpcFilterType]) ->
     }
 }
 
+pub(crate) fn get_rpc_vote_group(bank: &Bank, slot: Slot) -> Option<RpcVoteGroup> {
+    let (generator, seed) = bank.vote_group_for_slot(slot)?;
+    let members = generator.try_group_for_seed(seed).ok()?;
+    let (window_start, rotation_window_slots) = bank.vote_group_window(slot)?;
+    Some(RpcVoteGroup {
+        slot,
+        seed,
+        distance: generator.distance_for_seed(seed),
+        algorithm: format!("{:?}", generator.algorithm()).to_lowercase(),
+        members: members.iter().map(|key| key.to_string()).collect(),
+        rotation_window_slots,
+        window_start,
+    })
+}
+
+/// the groups of up to `limit` rotation windows from `start_slot` to the end of its epoch, stops
+/// early at the first slot whose group the bank can't tell
+fn get_rpc_vote_group_schedule(
+    bank: &Bank,
+    start_slot: Slot,
+    limit: usize,
+) -> Vec<RpcVoteGroupWindow> {
+    let epoch_schedule = bank.epoch_schedule();
+    let last_slot_in_epoch =
+        epoch_schedule.get_last_slot_in_epoch(epoch_schedule.get_epoch(start_slot));
+    let mut windows = vec![];
+    let mut slot = start_slot;
+    while slot <= last_slot_in_epoch && windows.len() < limit {
+        let vote_group = match get_rpc_vote_group(bank, slot) {
+            Some(vote_group) => vote_group,
+            None => break,
+        };
+        let last_slot = (vote_group.window_start + vote_group.rotation_window_slots)
+            .saturating_sub(1)
+            .min(last_slot_in_epoch);
+        windows.push(RpcVoteGroupWindow {
+            first_slot: slot,
+            last_slot,
+            seed: vote_group.seed,
+            members: vote_group.members,
+        });
+        slot = last_slot + 1;
+    }
+    windows
+}
+
+/// the group of the bank's slot, each member joined with its vote account so callers don't
+/// have to look them up one by one
+fn get_rpc_current_vote_group(bank: &Bank) -> Option<RpcCurrentVoteGroup> {
+    let slot = bank.slot();
+    let (generator, seed) = bank.vote_group_for_slot(slot)?;
+    let default_vote_state = VoteState::default();
+    let mut members: Vec<Pubkey> = Vec::new();
+    for member in generator.try_group_for_seed(seed).ok()? {
+        if !members.contains(&member) {
+            members.push(member);
+        }
+    }
+    let to_member =
+        |member: &Pubkey, activated_stake: u64, vote_state: &VoteState| RpcVoteGroupMember {
+            vote_pubkey: member.to_string(),
+            node_pubkey: vote_state.node_pubkey.to_string(),
+            activated_stake,
+            commission: vote_state.commission,
+            last_vote: vote_state.votes.iter().last().map_or(0, |vote| vote.slot),
+        };
+    let members = members
+        .iter()
+        .map(|member| match bank.get_vote_account(member) {
+            Some((activated_stake, account)) => {
+                let vote_state = account.vote_state();
+                let vote_state = vote_state.as_ref().unwrap_or(&default_vote_state);
+                to_member(member, activated_stake, vote_state)
+            }
+            // closed since the epoch's stakes were taken
+            None => to_member(member, 0, &default_vote_state),
+        })
+        .collect();
+    Some(RpcCurrentVoteGroup {
+        slot,
+        seed,
+        members,
+    })
+}
+
+pub(crate) fn get_rpc_vote_group_stake_weight(
+    bank: &Bank,
+    slot: Slot,
+) -> Option<RpcVoteGroupStakeWeight> {
+    let (generator, seed) = bank.vote_group_for_slot(slot)?;
+    let epoch_stakes = bank.epoch_stakes(bank.epoch_schedule().get_epoch(slot))?;
+    let mut members: Vec<Pubkey> = Vec::new();
+    for member in generator.try_group_for_seed(seed).ok()? {
+        if !members.contains(&member) {
+            members.push(member);
+        }
+    }
+    let members: Vec<RpcVoteGroupMemberStake> = members
+        .iter()
+        .map(|member| RpcVoteGroupMemberStake {
+            vote_pubkey: member.to_string(),
+            stake: epoch_stakes.vote_account_stake(member),
+        })
+        .collect();
+    let group_stake = members.iter().map(|member| member.stake).sum();
+    let total_stake = epoch_stakes.total_stake();
+    Some(RpcVoteGroupStakeWeight {
+        slot,
+        group_stake,
+        total_stake,
+        cluster_stake_fraction: if total_stake == 0 {
+            0.0
+        } else {
+            group_stake as f64 / total_stake as f64
+        },
+        members,
+    })
+}
+
 pub(crate) fn get_parsed_token_account(
     bank: Arc<Bank>,
     pubkey: &Pubkey,
@@ -2686,6 +2919,54 @@ pub mod rpc_full {
             config: Option<RpcBlockProductionConfig>,
         ) -> Result<RpcResponse<RpcBlockProduction>>;
 
+        #[rpc(meta, name = "getCurrentVoteGroup")]
+        fn get_current_vote_group(
+            &self,
+            meta: Self::Metadata,
+            commitment: Option<CommitmentConfig>,
+        ) -> Result<Option<RpcCurrentVoteGroup>>;
+
+        #[rpc(meta, name = "getVoteGroupForSlot")]
+        fn get_vote_group_for_slot(
+            &self,
+            meta: Self::Metadata,
+            slot: Slot,
+            commitment: Option<CommitmentConfig>,
+        ) -> Result<Option<RpcVoteGroup>>;
+
+        #[rpc(meta, name = "getVoteGroupHistory")]
+        fn get_vote_group_history(
+            &self,
+            meta: Self::Metadata,
+            start_slot: Slot,
+            config: Option<RpcVoteGroupHistoryConfig>,
+        ) -> Result<Vec<RpcVoteGroupHistoryEntry>>;
+
+        #[rpc(meta, name = "getVoteGroupSchedule")]
+        fn get_vote_group_schedule(
+            &self,
+            meta: Self::Metadata,
+            start_slot: Slot,
+            config: Option<RpcVoteGroupScheduleConfig>,
+        ) -> Result<Vec<RpcVoteGroupWindow>>;
+
+        #[rpc(meta, name = "getVoteGroupStakeWeight")]
+        fn get_vote_group_stake_weight(
+            &self,
+            meta: Self::Metadata,
+            slot: Slot,
+            commitment: Option<CommitmentConfig>,
+        ) -> Result<Option<RpcVoteGroupStakeWeight>>;
+
+        #[rpc(meta, name = "isVoterInGroup")]
+        fn is_voter_in_group(
+            &self,
+            meta: Self::Metadata,
+            vote_pubkey_str: String,
+            slot: Slot,
+            commitment: Option<CommitmentConfig>,
+        ) -> Result<Option<bool>>;
+
         // SPL Token-specific RPC endpoints
         // See https://github.com/solana-labs/solana-program-library/releases/tag/token-v2.0.0 for
         // program details
@@ -3563,6 +3844,79 @@ pub mod rpc_full {
             meta.get_stake_activation(&pubkey, config)
         }
 
+        fn get_current_vote_group(
+            &self,
+            meta: Self::Metadata,
+            commitment: Option<CommitmentConfig>,
+        ) -> Result<Option<RpcCurrentVoteGroup>> {
+            debug!("get_current_vote_group rpc request received");
+            meta.get_current_vote_group(commitment)
+        }
+
+        fn get_vote_group_for_slot(
+            &self,
+            meta: Self::Metadata,
+            slot: Slot,
+            commitment: Option<CommitmentConfig>,
+        ) -> Result<Option<RpcVoteGroup>> {
+            debug!("get_vote_group_for_slot rpc request received: {:?}", slot);
+            meta.get_vote_group_for_slot(slot, commitment)
+        }
+
+        fn get_vote_group_history(
+            &self,
+            meta: Self::Metadata,
+            start_slot: Slot,
+            config: Option<RpcVoteGroupHistoryConfig>,
+        ) -> Result<Vec<RpcVoteGroupHistoryEntry>> {
+            debug!(
+                "get_vote_group_history rpc request received: {}-{:?}",
+                start_slot, config
+            );
+            meta.get_vote_group_history(start_slot, config)
+        }
+
+        fn get_vote_group_schedule(
+            &self,
+            meta: Self::Metadata,
+            start_slot: Slot,
+            config: Option<RpcVoteGroupScheduleConfig>,
+        ) -> Result<Vec<RpcVoteGroupWindow>> {
+            debug!(
+                "get_vote_group_schedule rpc request received: {}-{:?}",
+                start_slot, config
+            );
+            meta.get_vote_group_schedule(start_slot, config)
+        }
+
+        fn get_vote_group_stake_weight(
+            &self,
+            meta: Self::Metadata,
+            slot: Slot,
+            commitment: Option<CommitmentConfig>,
+        ) -> Result<Option<RpcVoteGroupStakeWeight>> {
+            debug!(
+                "get_vote_group_stake_weight rpc request received: {:?}",
+                slot
+            );
+            meta.get_vote_group_stake_weight(slot, commitment)
+        }
+
+        fn is_voter_in_group(
+            &self,
+            meta: Self::Metadata,
+            vote_pubkey_str: String,
+            slot: Slot,
+            commitment: Option<CommitmentConfig>,
+        ) -> Result<Option<bool>> {
+            debug!(
+                "is_voter_in_group rpc request received: {:?} {:?}",
+                vote_pubkey_str, slot
+            );
+            let vote_pubkey = verify_pubkey(&vote_pubkey_str)?;
+            meta.is_voter_in_group(&vote_pubkey, slot, commitment)
+        }
+
         fn get_inflation_reward(
             &self,
             meta: Self::Metadata,
@@ -3751,7 +4105,7 @@ pub mod tests {
     use jsonrpc_core_client::transports::local;
     use solana_client::rpc_filter::{Memcmp, MemcmpEncodedBytes};
     use solana_ledger::{
-        blockstore_meta::PerfSample,
+        blockstore_meta::{PerfSample, VoteGroupRecord},
         blockstore_processor::fill_blockstore_slot_with_ticks,
         genesis_utils::{create_genesis_config, GenesisConfigInfo},
     };
@@ -3761,6 +4115,7 @@ pub mod tests {
     use solana_sdk::{
         account::Account,
         clock::MAX_RECENT_BLOCKHASHES,
+        feature_set,
         fee_calculator::DEFAULT_BURN_PERCENT,
         hash::{hash, Hash},
         instruction::InstructionError,
@@ -3952,6 +4307,7 @@ pub mod tests {
             Arc::new(RwLock::new(LargestAccountsCache::new(30))),
             max_slots,
             Arc::new(LeaderScheduleCache::new_from_bank(&bank)),
+            Arc::new(SharedVoteGroupGenerator::new_from_bank(&bank)),
             max_complete_transaction_status_slot,
         );
         SendTransactionService::new(tpu_address, &bank_forks, None, receiver, 1000, 1);
@@ -5620,6 +5976,9 @@ pub mod tests {
             Arc::new(RwLock::new(LargestAccountsCache::new(30))),
             Arc::new(MaxSlots::default()),
             Arc::new(LeaderScheduleCache::default()),
+            Arc::new(SharedVoteGroupGenerator::new_from_bank(
+                &bank_forks.read().unwrap().root_bank(),
+            )),
             Arc::new(AtomicU64::default()),
         );
         SendTransactionService::new(tpu_address, &bank_forks, None, receiver, 1000, 1);
@@ -5785,6 +6144,10 @@ pub mod tests {
         genesis_config.rent.exemption_threshold = 2.0;
         genesis_config.epoch_schedule =
             EpochSchedule::custom(TEST_SLOTS_PER_EPOCH, TEST_SLOTS_PER_EPOCH, false);
+        // the tests vote with every validator on every slot, groups are still computed
+        genesis_config
+            .accounts
+            .remove(&feature_set::limited_voting::id());
 
         let bank = Bank::new(&genesis_config);
         (
@@ -5897,6 +6260,9 @@ pub mod tests {
             Arc::new(RwLock::new(LargestAccountsCache::new(30))),
             Arc::new(MaxSlots::default()),
             Arc::new(LeaderScheduleCache::default()),
+            Arc::new(SharedVoteGroupGenerator::new_from_bank(
+                &bank_forks.read().unwrap().root_bank(),
+            )),
             Arc::new(AtomicU64::default()),
         );
         SendTransactionService::new(tpu_address, &bank_forks, None, receiver, 1000, 1);
@@ -7323,6 +7689,9 @@ pub mod tests {
             Arc::new(RwLock::new(LargestAccountsCache::new(30))),
             Arc::new(MaxSlots::default()),
             Arc::new(LeaderScheduleCache::default()),
+            Arc::new(SharedVoteGroupGenerator::new_from_bank(
+                &bank_forks.read().unwrap().root_bank(),
+            )),
             Arc::new(AtomicU64::default()),
         );
 
@@ -7465,4 +7834,321 @@ pub mod tests {
             expect58
         );
     }
+
+    #[test]
+    fn test_rpc_get_vote_group_for_slot() {
+        let bob_pubkey = solana_sdk::pubkey::new_rand();
+        let RpcHandler {
+            io,
+            meta,
+            leader_vote_keypair,
+            ..
+        } = start_rpc_handler_with_tx(&bob_pubkey);
+
+        let bank = meta.bank(None);
+        let (generator, seed) = bank.vote_group_for_slot(bank.slot()).unwrap();
+        let expected = RpcVoteGroup {
+            slot: bank.slot(),
+            seed,
+            distance: generator.distance_for_seed(seed),
+            algorithm: format!("{:?}", generator.algorithm()).to_lowercase(),
+            members: vec![leader_vote_keypair.pubkey().to_string()],
+            rotation_window_slots: 1,
+            window_start: bank.slot(),
+        };
+
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"getVoteGroupForSlot","params":[{}]}}"#,
+            bank.slot()
+        );
+        let res = io.handle_request_sync(&req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        let vote_group: RpcVoteGroup = serde_json::from_value(result["result"].clone()).unwrap();
+        assert_eq!(vote_group, expected);
+
+        // no epoch stakes that far out
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"getVoteGroupForSlot","params":[{}]}}"#,
+            bank.slot() + 100 * TEST_SLOTS_PER_EPOCH
+        );
+        let res = io.handle_request_sync(&req, meta);
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(result["result"], Value::Null);
+    }
+
+    #[test]
+    fn test_rpc_get_vote_group_schedule() {
+        let bob_pubkey = solana_sdk::pubkey::new_rand();
+        let RpcHandler {
+            io,
+            meta,
+            leader_vote_keypair,
+            ..
+        } = start_rpc_handler_with_tx(&bob_pubkey);
+
+        let bank = meta.bank(None);
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"getVoteGroupSchedule","params":[{}, {{"limit": 4}}]}}"#,
+            bank.slot()
+        );
+        let res = io.handle_request_sync(&req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        let windows: Vec<RpcVoteGroupWindow> =
+            serde_json::from_value(result["result"].clone()).unwrap();
+        assert_eq!(windows.len(), 4);
+        for (slot, window) in (bank.slot()..).zip(windows) {
+            let (_generator, seed) = bank.vote_group_for_slot(slot).unwrap();
+            assert_eq!(
+                window,
+                RpcVoteGroupWindow {
+                    first_slot: slot,
+                    last_slot: slot,
+                    seed,
+                    members: vec![leader_vote_keypair.pubkey().to_string()],
+                }
+            );
+        }
+
+        // the schedule ends with the epoch
+        let last_slot = bank.epoch_schedule().get_last_slot_in_epoch(bank.epoch());
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"getVoteGroupSchedule","params":[{}]}}"#,
+            last_slot
+        );
+        let res = io.handle_request_sync(&req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        let windows: Vec<RpcVoteGroupWindow> =
+            serde_json::from_value(result["result"].clone()).unwrap();
+        assert_eq!(windows.len(), 1);
+        assert_eq!(windows[0].last_slot, last_slot);
+
+        // no epoch stakes that far out
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"getVoteGroupSchedule","params":[{}]}}"#,
+            bank.slot() + 100 * TEST_SLOTS_PER_EPOCH
+        );
+        let res = io.handle_request_sync(&req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(result["result"], json!([]));
+
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"getVoteGroupSchedule","params":[{}, {{"limit": {}}}]}}"#,
+            bank.slot(),
+            MAX_GET_VOTE_GROUP_SCHEDULE_LIMIT + 1
+        );
+        let res = io.handle_request_sync(&req, meta);
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(
+            result["error"]["message"],
+            format!("Invalid limit; max {}", MAX_GET_VOTE_GROUP_SCHEDULE_LIMIT)
+        );
+    }
+
+    #[test]
+    fn test_rpc_get_current_vote_group() {
+        let bob_pubkey = solana_sdk::pubkey::new_rand();
+        let RpcHandler {
+            io,
+            meta,
+            leader_pubkey,
+            leader_vote_keypair,
+            ..
+        } = start_rpc_handler_with_tx(&bob_pubkey);
+
+        let bank = meta.bank(None);
+        let (_generator, seed) = bank.vote_group_for_slot(bank.slot()).unwrap();
+        let (activated_stake, vote_account) = bank
+            .get_vote_account(&leader_vote_keypair.pubkey())
+            .unwrap();
+        let vote_state = vote_account.vote_state().as_ref().unwrap().clone();
+        let expected = RpcCurrentVoteGroup {
+            slot: bank.slot(),
+            seed,
+            members: vec![RpcVoteGroupMember {
+                vote_pubkey: leader_vote_keypair.pubkey().to_string(),
+                node_pubkey: leader_pubkey.to_string(),
+                activated_stake,
+                commission: vote_state.commission,
+                last_vote: vote_state.votes.iter().last().map_or(0, |vote| vote.slot),
+            }],
+        };
+
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"getCurrentVoteGroup"}"#;
+        let res = io.handle_request_sync(req, meta);
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        let current_vote_group: RpcCurrentVoteGroup =
+            serde_json::from_value(result["result"].clone()).unwrap();
+        assert_eq!(current_vote_group, expected);
+    }
+
+    #[test]
+    fn test_rpc_get_vote_group_stake_weight() {
+        let bob_pubkey = solana_sdk::pubkey::new_rand();
+        let RpcHandler {
+            io,
+            meta,
+            leader_vote_keypair,
+            ..
+        } = start_rpc_handler_with_tx(&bob_pubkey);
+
+        let bank = meta.bank(None);
+        let epoch_stakes = bank.epoch_stakes(bank.epoch()).unwrap();
+        let stake = epoch_stakes.vote_account_stake(&leader_vote_keypair.pubkey());
+        let total_stake = epoch_stakes.total_stake();
+        let expected = RpcVoteGroupStakeWeight {
+            slot: bank.slot(),
+            group_stake: stake,
+            total_stake,
+            cluster_stake_fraction: stake as f64 / total_stake as f64,
+            members: vec![RpcVoteGroupMemberStake {
+                vote_pubkey: leader_vote_keypair.pubkey().to_string(),
+                stake,
+            }],
+        };
+
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"getVoteGroupStakeWeight","params":[{}]}}"#,
+            bank.slot()
+        );
+        let res = io.handle_request_sync(&req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        let stake_weight: RpcVoteGroupStakeWeight =
+            serde_json::from_value(result["result"].clone()).unwrap();
+        assert_eq!(stake_weight, expected);
+
+        // no epoch stakes that far out
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"getVoteGroupStakeWeight","params":[{}]}}"#,
+            bank.slot() + 100 * TEST_SLOTS_PER_EPOCH
+        );
+        let res = io.handle_request_sync(&req, meta);
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(result["result"], Value::Null);
+    }
+
+    #[test]
+    fn test_rpc_get_vote_group_history() {
+        let bob_pubkey = solana_sdk::pubkey::new_rand();
+        let roots = vec![0, 1, 3, 4, 8];
+        let RpcHandler {
+            io,
+            meta,
+            block_commitment_cache,
+            ..
+        } = start_rpc_handler_with_tx_and_blockstore(&bob_pubkey, roots.clone());
+        block_commitment_cache
+            .write()
+            .unwrap()
+            .set_highest_confirmed_root(4);
+
+        let member = solana_sdk::pubkey::new_rand();
+        let absent = solana_sdk::pubkey::new_rand();
+        for slot in &roots {
+            let record = VoteGroupRecord {
+                seed: *slot,
+                members: vec![member, absent],
+                voted: vec![member],
+            };
+            meta.blockstore.cache_vote_group(*slot, &record).unwrap();
+        }
+        let entry = |slot: Slot| RpcVoteGroupHistoryEntry {
+            slot,
+            seed: slot,
+            members: vec![member.to_string(), absent.to_string()],
+            voted: vec![member.to_string()],
+        };
+
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"getVoteGroupHistory","params":[1]}"#;
+        let res = io.handle_request_sync(&req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        let history: Vec<RpcVoteGroupHistoryEntry> =
+            serde_json::from_value(result["result"].clone()).unwrap();
+        assert_eq!(history, vec![entry(1), entry(3), entry(4)]);
+
+        let req = r#"{"jsonrpc":"2.0","id":1,"method":"getVoteGroupHistory","params":[1,{"endSlot":8,"limit":2}]}"#;
+        let res = io.handle_request_sync(&req, meta.clone());
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        let history: Vec<RpcVoteGroupHistoryEntry> =
+            serde_json::from_value(result["result"].clone()).unwrap();
+        assert_eq!(history, vec![entry(1), entry(3)]);
+
+        let req = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"getVoteGroupHistory","params":[0,{{"limit":{}}}]}}"#,
+            MAX_GET_VOTE_GROUP_HISTORY_LIMIT + 1
+        );
+        let res = io.handle_request_sync(&req, meta);
+        let result: Value = serde_json::from_str(&res.expect("actual response"))
+            .expect("actual response deserialization");
+        assert_eq!(
+            result["error"]["message"],
+            format!("Invalid limit; max {}", MAX_GET_VOTE_GROUP_HISTORY_LIMIT)
+        );
+    }
+
+    #[test]
+    fn test_rpc_is_voter_in_group() {
+        let bob_pubkey = solana_sdk::pubkey::new_rand();
+        let RpcHandler {
+            io,
+            meta,
+            leader_vote_keypair,
+            ..
+        } = start_rpc_handler_with_tx(&bob_pubkey);
+        let slot = meta.bank(None).slot();
+
+        let is_voter_in_group = |vote_pubkey: String, slot: Slot| {
+            let req = format!(
+                r#"{{"jsonrpc":"2.0","id":1,"method":"isVoterInGroup","params":["{}", {}]}}"#,
+                vote_pubkey, slot
+            );
+            let res = io.handle_request_sync(&req, meta.clone());
+            let result: Value = serde_json::from_str(&res.expect("actual response"))
+                .expect("actual response deserialization");
+            result
+        };
+
+        let result = is_voter_in_group(leader_vote_keypair.pubkey().to_string(), slot);
+        assert_eq!(result["result"], json!(true));
+        let result = is_voter_in_group(bob_pubkey.to_string(), slot);
+        assert_eq!(result["result"], json!(false));
+        let result = is_voter_in_group(
+            leader_vote_keypair.pubkey().to_string(),
+            slot + 100 * TEST_SLOTS_PER_EPOCH,
+        );
+        assert_eq!(result["result"], Value::Null);
+        let result = is_voter_in_group("not a pubkey".to_string(), slot);
+        assert!(result["error"].is_object());
+
+        // once replay roots the next epoch, the generator it shares answers for its slots
+        let bank = meta.bank(None);
+        let next_epoch_slot = bank
+            .epoch_schedule()
+            .get_first_slot_in_epoch(bank.epoch() + 1);
+        let next_epoch_bank = Bank::new_from_parent(&bank, &Pubkey::default(), next_epoch_slot);
+        assert!(meta.vote_group_generator.set_root(&next_epoch_bank));
+        let (generator, seed) = next_epoch_bank
+            .vote_group_for_slot(next_epoch_slot)
+            .unwrap();
+        for vote_pubkey in &[leader_vote_keypair.pubkey(), bob_pubkey] {
+            let in_group = generator.try_in_group_for_seed(seed, *vote_pubkey).unwrap();
+            assert_eq!(
+                meta.vote_group_generator
+                    .in_group_at(next_epoch_slot, vote_pubkey),
+                Some(in_group)
+            );
+            let result = is_voter_in_group(vote_pubkey.to_string(), next_epoch_slot);
+            assert_eq!(result["result"], json!(in_group));
+        }
+    }
 }
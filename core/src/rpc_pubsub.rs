@@ -11,8 +11,8 @@ use solana_client::{
         RpcTransactionLogsConfig, RpcTransactionLogsFilter,
     },
     rpc_response::{
-        Response as RpcResponse, RpcKeyedAccount, RpcLogsResponse, RpcSignatureResult, SlotInfo,
-        SlotUpdate,
+        Response as RpcResponse, RpcKeyedAccount, RpcLogsResponse, RpcSignatureResult,
+        RpcVoteGroup, SlotInfo, SlotUpdate,
     },
 };
 #[cfg(test)]
@@ -187,6 +187,26 @@ pub trait RpcSolPubSub {
         name = "rootUnsubscribe"
     )]
     fn root_unsubscribe(&self, meta: Option<Self::Metadata>, id: SubscriptionId) -> Result<bool>;
+
+    // Get notification when the vote group of newly processed slots changes
+    #[pubsub(
+        subscription = "voteGroupNotification",
+        subscribe,
+        name = "voteGroupSubscribe"
+    )]
+    fn vote_group_subscribe(&self, meta: Self::Metadata, subscriber: Subscriber<RpcVoteGroup>);
+
+    // Unsubscribe from vote group notification subscription.
+    #[pubsub(
+        subscription = "voteGroupNotification",
+        unsubscribe,
+        name = "voteGroupUnsubscribe"
+    )]
+    fn vote_group_unsubscribe(
+        &self,
+        meta: Option<Self::Metadata>,
+        id: SubscriptionId,
+    ) -> Result<bool>;
 }
 
 pub struct RpcSolPubSubImpl {
@@ -549,6 +569,36 @@ impl RpcSolPubSub for RpcSolPubSubImpl {
             })
         }
     }
+
+    fn vote_group_subscribe(&self, _meta: Self::Metadata, subscriber: Subscriber<RpcVoteGroup>) {
+        info!("vote_group_subscribe");
+        if let Err(err) = self.check_subscription_count() {
+            subscriber.reject(err).unwrap_or_default();
+            return;
+        }
+        let id = self.uid.fetch_add(1, atomic::Ordering::Relaxed);
+        let sub_id = SubscriptionId::Number(id as u64);
+        info!("vote_group_subscribe: id={:?}", sub_id);
+        self.subscriptions
+            .add_vote_group_subscription(sub_id, subscriber);
+    }
+
+    fn vote_group_unsubscribe(
+        &self,
+        _meta: Option<Self::Metadata>,
+        id: SubscriptionId,
+    ) -> Result<bool> {
+        info!("vote_group_unsubscribe");
+        if self.subscriptions.remove_vote_group_subscription(&id) {
+            Ok(true)
+        } else {
+            Err(Error {
+                code: ErrorCode::InvalidParams,
+                message: "Invalid Request: Subscription id does not exist".into(),
+                data: None,
+            })
+        }
+    }
 }
 
 #[cfg(test)]
@@ -1330,4 +1380,42 @@ mod tests {
             .vote_unsubscribe(Some(session), SubscriptionId::Number(0))
             .is_ok());
     }
+
+    #[test]
+    #[serial]
+    fn test_vote_group_subscribe() {
+        let validator_voting_keypairs: Vec<_> =
+            (0..3).map(|_| ValidatorVoteKeypairs::new_rand()).collect();
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config_with_vote_accounts(
+            10_000,
+            &validator_voting_keypairs,
+            vec![100; validator_voting_keypairs.len()],
+        );
+        let bank = Bank::new(&genesis_config);
+        let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
+        let expected_res =
+            crate::rpc::get_rpc_vote_group(&bank_forks.read().unwrap().get(0).unwrap(), 0).unwrap();
+        let rpc = RpcSolPubSubImpl::default_with_bank_forks(bank_forks);
+        let session = create_session();
+        let (subscriber, _id_receiver, receiver) = Subscriber::new_test("voteGroupNotification");
+        rpc.vote_group_subscribe(session, subscriber);
+
+        rpc.subscriptions
+            .notify_subscribers(CommitmentSlots::new_from_slot(0));
+        let (response, _) = robust_poll_or_panic(receiver);
+        let expected_res_str =
+            serde_json::to_string(&serde_json::to_value(expected_res).unwrap()).unwrap();
+        let expected = format!(
+            r#"{{"jsonrpc":"2.0","method":"voteGroupNotification","params":{{"result":{},"subscription":0}}}}"#,
+            expected_res_str
+        );
+        assert_eq!(expected, response);
+
+        assert!(rpc
+            .vote_group_unsubscribe(None, SubscriptionId::Number(0))
+            .unwrap());
+        assert!(rpc
+            .vote_group_unsubscribe(None, SubscriptionId::Number(0))
+            .is_err());
+    }
 }
@@ -23,6 +23,7 @@ use solana_metrics::inc_new_counter_info;
 use solana_runtime::{
     bank_forks::{BankForks, SnapshotConfig},
     commitment::BlockCommitmentCache,
+    shared_vote_group_generator::SharedVoteGroupGenerator,
     snapshot_utils,
 };
 use solana_sdk::{hash::Hash, native_token::lamports_to_sol, pubkey::Pubkey};
@@ -276,6 +277,7 @@ impl JsonRpcService {
         send_transaction_leader_forward_count: u64,
         max_slots: Arc<MaxSlots>,
         leader_schedule_cache: Arc<LeaderScheduleCache>,
+        vote_group_generator: Arc<SharedVoteGroupGenerator>,
         current_transaction_status_slot: Arc<AtomicU64>,
     ) -> Self {
         info!("rpc bound to {:?}", rpc_addr);
@@ -357,6 +359,7 @@ impl JsonRpcService {
             largest_accounts_cache,
             max_slots,
             leader_schedule_cache,
+            vote_group_generator,
             current_transaction_status_slot,
         );
 
@@ -498,6 +501,7 @@ mod tests {
             ip_addr,
             solana_net_utils::find_available_port_in_range(ip_addr, (10000, 65535)).unwrap(),
         );
+        let vote_group_generator = Arc::new(SharedVoteGroupGenerator::new_from_bank(&bank));
         let bank_forks = Arc::new(RwLock::new(BankForks::new(bank)));
         let ledger_path = get_tmp_ledger_path!();
         let blockstore = Arc::new(Blockstore::open(&ledger_path).unwrap());
@@ -523,6 +527,7 @@ mod tests {
             1,
             Arc::new(MaxSlots::default()),
             Arc::new(LeaderScheduleCache::default()),
+            vote_group_generator,
             Arc::new(AtomicU64::default()),
         );
         let thread = rpc_service.thread_hdl.thread();
@@ -2,7 +2,7 @@
 
 use crate::{
     optimistically_confirmed_bank_tracker::OptimisticallyConfirmedBank,
-    rpc::{get_parsed_token_account, get_parsed_token_accounts},
+    rpc::{get_parsed_token_account, get_parsed_token_accounts, get_rpc_vote_group},
 };
 use core::hash::Hash;
 use jsonrpc_pubsub::{
@@ -16,7 +16,8 @@ use solana_client::{
     rpc_filter::RpcFilterType,
     rpc_response::{
         ProcessedSignatureResult, ReceivedSignatureResult, Response, RpcKeyedAccount,
-        RpcLogsResponse, RpcResponseContext, RpcSignatureResult, SlotInfo, SlotUpdate,
+        RpcLogsResponse, RpcResponseContext, RpcSignatureResult, RpcVoteGroup, SlotInfo,
+        SlotUpdate,
     },
 };
 use solana_measure::measure::Measure;
@@ -150,6 +151,7 @@ type RpcSlotSubscriptions = RwLock<HashMap<SubscriptionId, Sink<SlotInfo>>>;
 type RpcSlotUpdateSubscriptions = RwLock<HashMap<SubscriptionId, Sink<Arc<SlotUpdate>>>>;
 type RpcVoteSubscriptions = RwLock<HashMap<SubscriptionId, Sink<RpcVote>>>;
 type RpcRootSubscriptions = RwLock<HashMap<SubscriptionId, Sink<Slot>>>;
+type RpcVoteGroupSubscriptions = RwLock<HashMap<SubscriptionId, Sink<RpcVoteGroup>>>;
 
 fn add_subscription<K, S, T>(
     subscriptions: &mut HashMap<K, HashMap<SubscriptionId, SubscriptionData<S, T>>>,
@@ -396,6 +398,7 @@ struct Subscriptions {
     slots_updates_subscriptions: Arc<RpcSlotUpdateSubscriptions>,
     vote_subscriptions: Arc<RpcVoteSubscriptions>,
     root_subscriptions: Arc<RpcRootSubscriptions>,
+    vote_group_subscriptions: Arc<RpcVoteGroupSubscriptions>,
 }
 
 impl Subscriptions {
@@ -412,6 +415,7 @@ impl Subscriptions {
         total += self.slot_subscriptions.read().unwrap().len();
         total += self.vote_subscriptions.read().unwrap().len();
         total += self.root_subscriptions.read().unwrap().len();
+        total += self.vote_group_subscriptions.read().unwrap().len();
         total
     }
 }
@@ -475,6 +479,7 @@ impl RpcSubscriptions {
         let slots_updates_subscriptions = Arc::new(RpcSlotUpdateSubscriptions::default());
         let vote_subscriptions = Arc::new(RpcVoteSubscriptions::default());
         let root_subscriptions = Arc::new(RpcRootSubscriptions::default());
+        let vote_group_subscriptions = Arc::new(RpcVoteGroupSubscriptions::default());
         let notification_sender = Arc::new(Mutex::new(notification_sender));
 
         let _bank_forks = bank_forks.clone();
@@ -493,6 +498,7 @@ impl RpcSubscriptions {
             slots_updates_subscriptions,
             vote_subscriptions,
             root_subscriptions,
+            vote_group_subscriptions,
         };
         let _subscriptions = subscriptions.clone();
 
@@ -1001,6 +1007,21 @@ impl RpcSubscriptions {
         });
     }
 
+    pub fn add_vote_group_subscription(
+        &self,
+        sub_id: SubscriptionId,
+        subscriber: Subscriber<RpcVoteGroup>,
+    ) {
+        let sink = subscriber.assign_id(sub_id.clone()).unwrap();
+        let mut subscriptions = self.subscriptions.vote_group_subscriptions.write().unwrap();
+        subscriptions.insert(sub_id, sink);
+    }
+
+    pub fn remove_vote_group_subscription(&self, id: &SubscriptionId) -> bool {
+        let mut subscriptions = self.subscriptions.vote_group_subscriptions.write().unwrap();
+        subscriptions.remove(id).is_some()
+    }
+
     fn enqueue_notification(&self, notification_entry: NotificationEntry) {
         match self
             .notification_sender
@@ -1025,6 +1046,8 @@ impl RpcSubscriptions {
         subscriptions: Subscriptions,
         bank_forks: Arc<RwLock<BankForks>>,
     ) {
+        // members of the group last pushed to vote group subscribers
+        let mut last_vote_group_members: Option<Vec<String>> = None;
         loop {
             if exit.load(Ordering::Relaxed) {
                 break;
@@ -1103,7 +1126,14 @@ impl RpcSubscriptions {
                             &commitment_slots,
                             &notifier,
                             "bank",
-                        )
+                        );
+                        Self::process_vote_group_notification(
+                            commitment_slots.slot,
+                            &notifier,
+                            &subscriptions.vote_group_subscriptions,
+                            &bank_forks,
+                            &mut last_vote_group_members,
+                        );
                     }
                     NotificationEntry::Gossip(slot) => {
                         Self::process_gossip_notification(
@@ -1137,6 +1167,39 @@ impl RpcSubscriptions {
         }
     }
 
+    /// pushes the group of the newly processed `slot` when its members differ from the last
+    /// group pushed, consecutive slots with the same members only notify once
+    fn process_vote_group_notification(
+        slot: Slot,
+        notifier: &RpcNotifier,
+        subscriptions: &RpcVoteGroupSubscriptions,
+        bank_forks: &Arc<RwLock<BankForks>>,
+        last_members: &mut Option<Vec<String>>,
+    ) {
+        let subscriptions = subscriptions.read().unwrap();
+        if subscriptions.is_empty() {
+            return;
+        }
+        let bank = bank_forks.read().unwrap().get(slot).cloned();
+        let vote_group = match bank.and_then(|bank| get_rpc_vote_group(&bank, slot)) {
+            Some(vote_group) => vote_group,
+            None => return,
+        };
+        if last_members.as_ref() == Some(&vote_group.members) {
+            return;
+        }
+        debug!(
+            "vote group notify: {:?}, num_subscriptions: {:?}",
+            slot,
+            subscriptions.len()
+        );
+        *last_members = Some(vote_group.members.clone());
+        for (_, sink) in subscriptions.iter() {
+            inc_new_counter_info!("rpc-subscription-notify-vote-group", 1);
+            notifier.notify(vote_group.clone(), sink);
+        }
+    }
+
     fn process_gossip_notification(
         slot: Slot,
         notifier: &RpcNotifier,
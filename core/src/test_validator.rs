@@ -12,6 +12,7 @@ use {
         bank_forks::{ArchiveFormat, SnapshotConfig, SnapshotVersion},
         genesis_utils::create_genesis_config_with_leader_ex,
         hardened_unpack::MAX_GENESIS_ARCHIVE_UNPACKED_SIZE,
+        shared_vote_group_generator::SharedVoteGroupGenerator,
     },
     solana_sdk::{
         account::{Account, AccountSharedData},
@@ -24,6 +25,7 @@ use {
         pubkey::Pubkey,
         rent::Rent,
         signature::{read_keypair_file, write_keypair_file, Keypair, Signer},
+        vote_group_gen::VoteGroupOverrides,
     },
     std::{
         collections::HashMap,
@@ -82,7 +84,9 @@ pub struct TestValidatorGenesis {
     pub validator_exit: Arc<RwLock<ValidatorExit>>,
     pub start_progress: Arc<RwLock<ValidatorStartProgress>>,
     pub authorized_voter_keypairs: Arc<RwLock<Vec<Arc<Keypair>>>>,
+    pub vote_group_generator: Arc<RwLock<Option<Arc<SharedVoteGroupGenerator>>>>,
     pub max_ledger_shreds: Option<u64>,
+    vote_group_overrides: Option<VoteGroupOverrides>,
 }
 
 impl TestValidatorGenesis {
@@ -136,6 +140,11 @@ impl TestValidatorGenesis {
         self
     }
 
+    pub fn vote_group_overrides(&mut self, vote_group_overrides: VoteGroupOverrides) -> &mut Self {
+        self.vote_group_overrides = Some(vote_group_overrides);
+        self
+    }
+
     pub fn gossip_host(&mut self, gossip_host: IpAddr) -> &mut Self {
         self.node_config.gossip_addr.set_ip(gossip_host);
         self
@@ -497,9 +506,11 @@ impl TestValidator {
             warp_slot: config.warp_slot,
             bpf_jit: !config.no_bpf_jit,
             validator_exit: config.validator_exit.clone(),
+            vote_group_generator: config.vote_group_generator.clone(),
             rocksdb_compaction_interval: Some(100), // Compact every 100 slots
             max_ledger_shreds: config.max_ledger_shreds,
             no_wait_for_vote_to_start_leader: true,
+            vote_group_overrides: config.vote_group_overrides.clone(),
             ..ValidatorConfig::default()
         };
 
@@ -106,7 +106,7 @@ impl TransactionStatusService {
                                 bank.get_fee_calculator(&transaction.message().recent_blockhash)
                             })
                             .expect("FeeCalculator must exist");
-                        let fee = fee_calculator.calculate_fee(transaction.message());
+                        let fee = bank.transaction_fee(&fee_calculator, transaction, &status);
                         let (writable_keys, readonly_keys) = transaction
                             .message
                             .get_account_keys_by_lock_type(bank.demote_sysvar_write_locks());
@@ -39,6 +39,7 @@ use solana_runtime::{
     },
     bank_forks::{BankForks, SnapshotConfig},
     commitment::BlockCommitmentCache,
+    shared_vote_group_generator::SharedVoteGroupGenerator,
     vote_sender_types::ReplayVoteSender,
 };
 use solana_sdk::{
@@ -110,6 +111,7 @@ impl Tvu {
         poh_recorder: &Arc<Mutex<PohRecorder>>,
         tower: Tower,
         leader_schedule_cache: &Arc<LeaderScheduleCache>,
+        vote_group_generator: &Arc<SharedVoteGroupGenerator>,
         exit: &Arc<AtomicBool>,
         completed_slots_receivers: [CompletedSlotsReceiver; 2],
         block_commitment_cache: Arc<RwLock<BlockCommitmentCache>>,
@@ -255,6 +257,7 @@ impl Tvu {
             exit: exit.clone(),
             subscriptions: subscriptions.clone(),
             leader_schedule_cache: leader_schedule_cache.clone(),
+            vote_group_generator: vote_group_generator.clone(),
             latest_root_senders: vec![ledger_cleanup_slot_sender],
             accounts_background_request_sender,
             block_commitment_cache,
@@ -378,6 +381,7 @@ pub mod tests {
             create_test_recorder(&bank, &blockstore, None);
         let vote_keypair = Keypair::new();
         let leader_schedule_cache = Arc::new(LeaderScheduleCache::new_from_bank(&bank));
+        let vote_group_generator = Arc::new(SharedVoteGroupGenerator::new_from_bank(&bank));
         let block_commitment_cache = Arc::new(RwLock::new(BlockCommitmentCache::default()));
         let (retransmit_slots_sender, _retransmit_slots_receiver) = unbounded();
         let (_gossip_verified_vote_hash_sender, gossip_verified_vote_hash_receiver) = unbounded();
@@ -411,6 +415,7 @@ pub mod tests {
             &poh_recorder,
             tower,
             &leader_schedule_cache,
+            &vote_group_generator,
             &exit,
             completed_slots_receivers,
             block_commitment_cache,
@@ -51,6 +51,7 @@ use solana_runtime::{
     bank_forks::{BankForks, SnapshotConfig},
     commitment::BlockCommitmentCache,
     hardened_unpack::{open_genesis_config, MAX_GENESIS_ARCHIVE_UNPACKED_SIZE},
+    shared_vote_group_generator::SharedVoteGroupGenerator,
 };
 use solana_sdk::{
     clock::Slot,
@@ -61,6 +62,7 @@ use solana_sdk::{
     shred_version::compute_shred_version,
     signature::{Keypair, Signer},
     timing::timestamp,
+    vote_group_gen::VoteGroupOverrides,
 };
 use solana_vote_program::vote_state::VoteState;
 use std::time::Instant;
@@ -133,6 +135,9 @@ pub struct ValidatorConfig {
     pub tpu_coalesce_ms: u64,
     pub validator_exit: Arc<RwLock<ValidatorExit>>,
     pub no_wait_for_vote_to_start_leader: bool,
+    pub vote_group_overrides: Option<VoteGroupOverrides>,
+    /// set once the validator has built its shared vote group generator, for the admin rpc
+    pub vote_group_generator: Arc<RwLock<Option<Arc<SharedVoteGroupGenerator>>>>,
 }
 
 impl Default for ValidatorConfig {
@@ -189,6 +194,8 @@ impl Default for ValidatorConfig {
             tpu_coalesce_ms: DEFAULT_TPU_COALESCE_MS,
             validator_exit: Arc::new(RwLock::new(ValidatorExit::default())),
             no_wait_for_vote_to_start_leader: true,
+            vote_group_overrides: None,
+            vote_group_generator: Arc::default(),
         }
     }
 }
@@ -411,6 +418,10 @@ impl Validator {
         *start_progress.write().unwrap() = ValidatorStartProgress::StartingServices;
 
         let leader_schedule_cache = Arc::new(leader_schedule_cache);
+        let vote_group_generator = Arc::new(SharedVoteGroupGenerator::new_from_bank(
+            &bank_forks.root_bank(),
+        ));
+        *config.vote_group_generator.write().unwrap() = Some(vote_group_generator.clone());
         let bank = bank_forks.working_bank();
         if let Some(ref shrink_paths) = config.account_shrink_paths {
             bank.set_shrink_paths(shrink_paths.clone());
@@ -553,6 +564,7 @@ impl Validator {
                     config.send_transaction_leader_forward_count,
                     max_slots.clone(),
                     leader_schedule_cache.clone(),
+                    vote_group_generator.clone(),
                     max_complete_transaction_status_slot,
                 )),
                 if config.rpc_config.minimal_api {
@@ -710,6 +722,7 @@ impl Validator {
             &poh_recorder,
             tower,
             &leader_schedule_cache,
+            &vote_group_generator,
             &exit,
             completed_slots_receivers,
             block_commitment_cache,
@@ -1116,6 +1129,7 @@ fn new_banks_from_ledger(
         debug_keys: config.debug_keys.clone(),
         account_indexes: config.account_indexes.clone(),
         accounts_db_caching_enabled: config.accounts_db_caching_enabled,
+        vote_group_overrides: config.vote_group_overrides.clone(),
         ..blockstore_processor::ProcessOptions::default()
     };
 
@@ -1,10 +1,12 @@
 use solana_sdk::pubkey::Pubkey;
-use std::collections::HashSet;
+use std::{collections::HashSet, time::Instant};
 
 #[derive(Default)]
 pub struct VoteStakeTracker {
     voted: HashSet<Pubkey>,
     stake: u64,
+    // when the first vote was tracked, to time how long a threshold takes to reach
+    first_vote: Option<Instant>,
 }
 
 impl VoteStakeTracker {
@@ -21,6 +23,7 @@ impl VoteStakeTracker {
     ) -> (Vec<bool>, bool) {
         let is_new = !self.voted.contains(&vote_pubkey);
         if is_new {
+            self.first_vote.get_or_insert_with(Instant::now);
             self.voted.insert(vote_pubkey);
             let old_stake = self.stake;
             let new_stake = self.stake + stake;
@@ -45,6 +48,10 @@ impl VoteStakeTracker {
     pub fn stake(&self) -> u64 {
         self.stake
     }
+
+    pub fn first_vote(&self) -> Option<Instant> {
+        self.first_vote
+    }
 }
 
 #[cfg(test)]
@@ -99,4 +106,23 @@ mod test {
             assert!(is_new);
         }
     }
+
+    #[test]
+    fn test_first_vote() {
+        let mut vote_stake_tracker = VoteStakeTracker::default();
+        assert_eq!(vote_stake_tracker.first_vote(), None);
+        let pubkey = solana_sdk::pubkey::new_rand();
+        vote_stake_tracker.add_vote_pubkey(pubkey, 1, 10, &[VOTE_THRESHOLD_SIZE]);
+        let first_vote = vote_stake_tracker.first_vote().unwrap();
+
+        // the quorum latency is timed from the first vote, later votes leave it be
+        vote_stake_tracker.add_vote_pubkey(pubkey, 1, 10, &[VOTE_THRESHOLD_SIZE]);
+        vote_stake_tracker.add_vote_pubkey(
+            solana_sdk::pubkey::new_rand(),
+            1,
+            10,
+            &[VOTE_THRESHOLD_SIZE],
+        );
+        assert_eq!(vote_stake_tracker.first_vote(), Some(first_vote));
+    }
 }
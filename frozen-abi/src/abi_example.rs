@@ -334,6 +334,13 @@ impl<T: AbiExample> AbiExample for std::sync::Arc<T> {
     }
 }
 
+impl<T: AbiExample> AbiExample for std::sync::Arc<[T]> {
+    fn example() -> Self {
+        info!("AbiExample for (Arc<[T]>): {}", type_name::<Self>());
+        std::sync::Arc::new([T::example()])
+    }
+}
+
 impl<T: AbiExample> AbiExample for std::rc::Rc<T> {
     fn example() -> Self {
         info!("AbiExample for (Rc<T>): {}", type_name::<Self>());
@@ -196,6 +196,50 @@ fn main() -> Result<(), Box<dyn error::Error>> {
                 .default_value(&default_faucet_pubkey)
                 .help("Path to file containing the faucet's pubkey"),
         )
+        .arg(
+            Arg::with_name("vote_group_config_authority")
+                .long("vote-group-config-authority")
+                .value_name("PUBKEY")
+                .takes_value(true)
+                .validator(is_pubkey_or_keypair)
+                .help(
+                    "Path to file containing the pubkey allowed to update the vote group config \
+                     [default: no vote group config account, groups use the default size]",
+                ),
+        )
+        .arg(
+            Arg::with_name("vote_group_exclusions_authority")
+                .long("vote-group-exclusions-authority")
+                .value_name("PUBKEY")
+                .takes_value(true)
+                .validator(is_pubkey_or_keypair)
+                .help(
+                    "Path to file containing the pubkey allowed to update the vote group \
+                     exclusion registry [default: no registry, groups leave out the legacy \
+                     never voter]",
+                ),
+        )
+        .arg(
+            Arg::with_name("vote_group_admin_signer")
+                .long("vote-group-admin-signer")
+                .value_name("PUBKEY")
+                .takes_value(true)
+                .multiple(true)
+                .validator(is_pubkey_or_keypair)
+                .help(
+                    "Path to file containing a pubkey of the vote group admin authority, \
+                     repeat for every signer [default: no vote group admin params account]",
+                ),
+        )
+        .arg(
+            Arg::with_name("vote_group_admin_threshold")
+                .long("vote-group-admin-threshold")
+                .value_name("NUMBER")
+                .takes_value(true)
+                .requires("vote_group_admin_signer")
+                .default_value("1")
+                .help("How many of the vote group admin signers have to sign a change"),
+        )
         .arg(
             Arg::with_name("bootstrap_stake_authorized_pubkey")
                 .long("bootstrap-stake-authorized-pubkey")
@@ -438,6 +482,25 @@ fn main() -> Result<(), Box<dyn error::Error>> {
         pubkey_of(&matches, "bootstrap_stake_authorized_pubkey");
     let faucet_lamports = value_t!(matches, "faucet_lamports", u64).unwrap_or(0);
     let faucet_pubkey = pubkey_of(&matches, "faucet_pubkey");
+    let vote_group_config_authority = pubkey_of(&matches, "vote_group_config_authority");
+    let vote_group_exclusions_authority = pubkey_of(&matches, "vote_group_exclusions_authority");
+    let vote_group_admin_authority =
+        pubkeys_of(&matches, "vote_group_admin_signer").map(|signers| {
+            solana_vote_program::vote_group_admin::MultisigAuthority::new(
+                signers,
+                value_t_or_exit!(matches, "vote_group_admin_threshold", u8),
+            )
+        });
+    if let Some(authority) = &vote_group_admin_authority {
+        if !authority.is_valid() {
+            eprintln!(
+                "Error: the vote group admin threshold must be between 1 and the number of \
+                 distinct signers, at most {}",
+                solana_vote_program::vote_group_admin::MAX_ADMIN_SIGNERS
+            );
+            process::exit(1);
+        }
+    }
 
     let ticks_per_slot = value_t_or_exit!(matches, "ticks_per_slot", u64);
 
@@ -576,6 +639,21 @@ fn main() -> Result<(), Box<dyn error::Error>> {
     }
 
     solana_stake_program::add_genesis_accounts(&mut genesis_config);
+    if let Some(authority) = vote_group_config_authority {
+        solana_vote_program::vote_group_config::add_genesis_account(
+            &mut genesis_config,
+            &authority,
+        );
+    }
+    if let Some(authority) = vote_group_exclusions_authority {
+        solana_vote_program::vote_group_exclusions::add_genesis_account(
+            &mut genesis_config,
+            &authority,
+        );
+    }
+    if let Some(authority) = vote_group_admin_authority {
+        solana_vote_program::vote_group_admin::add_genesis_account(&mut genesis_config, authority);
+    }
     if genesis_config.cluster_type == ClusterType::Development {
         solana_runtime::genesis_utils::activate_all_features(&mut genesis_config);
     }
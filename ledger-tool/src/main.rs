@@ -62,6 +62,7 @@ use std::{
 
 mod bigtable;
 use bigtable::*;
+mod vote_groups;
 
 #[derive(PartialEq)]
 enum LedgerOutputMethod {
@@ -1057,6 +1058,12 @@ fn main() {
                     .takes_value(false)
                     .help("Skip ledger PoH verification"),
             )
+            .arg(
+                Arg::with_name("skip_vote_group_verify")
+                    .long("skip-vote-group-verify")
+                    .takes_value(false)
+                    .help("Skip checking rooted slots against the recorded vote group seeds"),
+            )
             .arg(
                 Arg::with_name("print_accounts_stats")
                     .long("print-accounts-stats")
@@ -1083,6 +1090,15 @@ fn main() {
                     .takes_value(true)
                     .help("Output file"),
             )
+        ).subcommand(
+            SubCommand::with_name("vote-groups")
+            .about("Rebuild the vote groups of rooted slots and audit the votes that landed in them")
+            .arg(&no_snapshot_arg)
+            .arg(&account_paths_arg)
+            .arg(&hard_forks_arg)
+            .arg(&max_genesis_archive_unpacked_size_arg)
+            .arg(&starting_slot_arg)
+            .arg(&ending_slot_arg)
         ).subcommand(
             SubCommand::with_name("create-snapshot")
             .about("Create a new ledger snapshot")
@@ -1749,6 +1765,7 @@ fn main() {
                 bpf_jit: !matches.is_present("no_bpf_jit"),
                 accounts_db_caching_enabled: !arg_matches.is_present("no_accounts_db_caching"),
                 allow_dead_slots: arg_matches.is_present("allow_dead_slots"),
+                verify_vote_groups: !arg_matches.is_present("skip_vote_group_verify"),
                 ..ProcessOptions::default()
             };
             let print_accounts_stats = arg_matches.is_present("print_accounts_stats");
@@ -1825,6 +1842,50 @@ fn main() {
                 }
             }
         }
+        ("vote-groups", Some(arg_matches)) => {
+            let starting_slot = value_t_or_exit!(arg_matches, "starting_slot", Slot);
+            let ending_slot = value_t!(arg_matches, "ending_slot", Slot).ok();
+            let process_options = ProcessOptions {
+                dev_halt_at_slot: ending_slot,
+                new_hard_forks: hardforks_of(arg_matches, "hard_forks"),
+                poh_verify: false,
+                ..ProcessOptions::default()
+            };
+
+            let blockstore = open_blockstore(
+                &ledger_path,
+                AccessType::TryPrimaryThenSecondary,
+                wal_recovery_mode,
+            );
+            match load_bank_forks(
+                arg_matches,
+                &open_genesis_config_by(&ledger_path, arg_matches),
+                &blockstore,
+                process_options,
+                snapshot_archive_path,
+            ) {
+                Ok((bank_forks, _leader_schedule_cache, _snapshot_hash)) => {
+                    let bank = bank_forks.working_bank();
+                    let ending_slot = ending_slot.unwrap_or_else(|| bank.slot());
+                    match vote_groups::audit_vote_groups(
+                        &bank,
+                        &blockstore,
+                        starting_slot,
+                        ending_slot,
+                    ) {
+                        Ok(audit) => vote_groups::print_vote_group_audit(&audit),
+                        Err(err) => {
+                            eprintln!("{}", err);
+                            exit(1);
+                        }
+                    }
+                }
+                Err(err) => {
+                    eprintln!("Failed to load ledger: {:?}", err);
+                    exit(1);
+                }
+            }
+        }
         ("create-snapshot", Some(arg_matches)) => {
             let output_directory = value_t!(arg_matches, "output_directory", PathBuf)
                 .unwrap_or_else(|_| ledger_path.clone());
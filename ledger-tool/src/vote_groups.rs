@@ -0,0 +1,199 @@
+/// The `vote-groups` subcommand
+use solana_ledger::blockstore::Blockstore;
+use solana_runtime::bank::Bank;
+use solana_sdk::{clock::Slot, pubkey::Pubkey, signature::Signature, transaction::Transaction};
+use solana_vote_program::vote_transaction;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemberAudit {
+    // rooted slots of the range the vote account was picked for
+    pub selected: u64,
+    // the ones it landed a vote on
+    pub voted: u64,
+    // landed votes for slots of the range it wasn't picked for
+    pub out_of_group: u64,
+}
+
+impl MemberAudit {
+    pub fn participation(&self) -> Option<f64> {
+        if self.selected == 0 {
+            None
+        } else {
+            Some(self.voted as f64 / self.selected as f64)
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OutOfGroupVote {
+    pub vote_pubkey: Pubkey,
+    pub voted_slot: Slot,
+    pub landed_slot: Slot,
+    pub signature: Signature,
+}
+
+#[derive(Debug, Default)]
+pub struct VoteGroupAudit {
+    pub slots: u64,
+    // rooted slots whose group could neither be rebuilt nor was recorded
+    pub unknown_groups: Vec<Slot>,
+    // vote transactions whose status neither the bank nor the blockstore remembers
+    pub unknown_status: u64,
+    pub members: BTreeMap<Pubkey, MemberAudit>,
+    pub out_of_group_votes: Vec<OutOfGroupVote>,
+}
+
+// the group is rebuilt from `bank` when it can answer for `slot`. hash seeded groups of
+// older slots need the seed replay recorded, and groups of epochs the bank no longer keeps
+// stakes for are only known from the members replay recorded at the root
+fn vote_group(bank: &Bank, blockstore: &Blockstore, slot: Slot) -> Option<Vec<Pubkey>> {
+    if let Some((generator, seed)) = bank.vote_group_for_slot(slot) {
+        return generator.try_group_for_seed(seed).ok();
+    }
+    let epoch_stakes = bank.epoch_stakes(bank.epoch_schedule().get_epoch(slot));
+    if let (Some(epoch_stakes), Some((window_start, _))) =
+        (epoch_stakes, bank.vote_group_window(slot))
+    {
+        if let Ok(Some(record)) = blockstore.get_vote_group_seed(window_start) {
+            return epoch_stakes
+                .get_group_genr()
+                .try_group_for_seed(record.seed)
+                .ok();
+        }
+    }
+    blockstore
+        .get_vote_group(slot)
+        .ok()
+        .flatten()
+        .map(|record| record.members)
+}
+
+// the bank only remembers the status of recent transactions, older ones are found in the
+// blockstore if the validator kept transaction history
+fn vote_landed(bank: &Bank, blockstore: &Blockstore, tx: &Transaction) -> Option<bool> {
+    let signature = tx.signatures.get(0)?;
+    if let Some(status) = bank.get_signature_status(signature) {
+        return Some(status.is_ok());
+    }
+    blockstore
+        .get_rooted_transaction_status(*signature)
+        .ok()
+        .flatten()
+        .map(|(_, meta)| meta.status.is_ok())
+}
+
+/// rebuilds the group of every rooted slot in `starting_slot..=ending_slot` and checks the
+/// votes landed in those slots against them
+pub fn audit_vote_groups(
+    bank: &Bank,
+    blockstore: &Blockstore,
+    starting_slot: Slot,
+    ending_slot: Slot,
+) -> Result<VoteGroupAudit, String> {
+    let rooted_slots: Vec<Slot> = blockstore
+        .rooted_slot_iterator(starting_slot)
+        .map_err(|err| format!("Failed to iterate rooted slots: {:?}", err))?
+        .take_while(|slot| *slot <= ending_slot)
+        .collect();
+
+    let mut audit = VoteGroupAudit::default();
+    let mut groups: HashMap<Slot, HashSet<Pubkey>> = HashMap::new();
+    for slot in &rooted_slots {
+        audit.slots += 1;
+        match vote_group(bank, blockstore, *slot) {
+            Some(members) => {
+                let members: HashSet<Pubkey> = members.into_iter().collect();
+                for member in &members {
+                    audit.members.entry(*member).or_default().selected += 1;
+                }
+                groups.insert(*slot, members);
+            }
+            None => audit.unknown_groups.push(*slot),
+        }
+    }
+
+    let mut votes = HashSet::new();
+    for landed_slot in &rooted_slots {
+        let entries = blockstore
+            .get_slot_entries(*landed_slot, 0)
+            .map_err(|err| format!("Failed to load entries for slot {}: {:?}", landed_slot, err))?;
+        for tx in entries.iter().flat_map(|entry| entry.transactions.iter()) {
+            let (vote_pubkey, vote, _) = match vote_transaction::parse_vote_transaction(tx) {
+                Some(parsed) => parsed,
+                None => continue,
+            };
+            match vote_landed(bank, blockstore, tx) {
+                Some(true) => (),
+                Some(false) => continue,
+                None => {
+                    audit.unknown_status += 1;
+                    continue;
+                }
+            }
+            for voted_slot in vote.slots {
+                let group = match groups.get(&voted_slot) {
+                    Some(group) => group,
+                    None => continue,
+                };
+                // towers repeat slots they already voted on
+                if !votes.insert((voted_slot, vote_pubkey)) {
+                    continue;
+                }
+                let member = audit.members.entry(vote_pubkey).or_default();
+                if group.contains(&vote_pubkey) {
+                    member.voted += 1;
+                } else {
+                    member.out_of_group += 1;
+                    audit.out_of_group_votes.push(OutOfGroupVote {
+                        vote_pubkey,
+                        voted_slot,
+                        landed_slot: *landed_slot,
+                        signature: tx.signatures[0],
+                    });
+                }
+            }
+        }
+    }
+    Ok(audit)
+}
+
+pub fn print_vote_group_audit(audit: &VoteGroupAudit) {
+    println!(
+        "{} rooted slots, {} without a known vote group",
+        audit.slots,
+        audit.unknown_groups.len()
+    );
+    if audit.unknown_status > 0 {
+        println!(
+            "{} vote transactions skipped, their status is unknown",
+            audit.unknown_status
+        );
+    }
+    println!(
+        "{:<44}  {:>10}  {:>10}  {:>13}  {:>12}",
+        "Vote Account", "Selected", "Voted", "Participation", "Out of Group"
+    );
+    for (vote_pubkey, member) in &audit.members {
+        println!(
+            "{:<44}  {:>10}  {:>10}  {:>13}  {:>12}",
+            vote_pubkey.to_string(),
+            member.selected,
+            member.voted,
+            member
+                .participation()
+                .map(|participation| format!("{:.2}%", participation * 100.0))
+                .unwrap_or_else(|| "-".to_string()),
+            member.out_of_group,
+        );
+    }
+    if !audit.out_of_group_votes.is_empty() {
+        println!("Out of group votes:");
+        for vote in &audit.out_of_group_votes {
+            println!(
+                "  {} voted on slot {} in slot {}: {}",
+                vote.vote_pubkey, vote.voted_slot, vote.landed_slot, vote.signature
+            );
+        }
+    }
+}
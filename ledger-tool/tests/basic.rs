@@ -46,4 +46,8 @@ fn nominal() {
     let output = run_ledger_tool(&["-l", &ledger_path, "print", "-vvv"]);
     assert!(output.status.success());
     assert_eq!(count_newlines(&output.stdout), ticks + meta_lines);
+
+    // Audit the vote groups of the rooted slots
+    let output = run_ledger_tool(&["-l", &ledger_path, "vote-groups"]);
+    assert!(output.status.success());
 }
@@ -60,7 +60,7 @@ pub fn load(
                     process::exit(1);
                 }
 
-                let deserialized_bank = snapshot_utils::bank_from_archive(
+                let mut deserialized_bank = snapshot_utils::bank_from_archive(
                     &account_paths,
                     &process_options.frozen_accounts,
                     &snapshot_config.snapshot_path,
@@ -73,6 +73,8 @@ pub fn load(
                     process_options.accounts_db_caching_enabled,
                 )
                 .expect("Load from snapshot failed");
+                deserialized_bank
+                    .set_vote_group_overrides(process_options.vote_group_overrides.clone());
                 if let Some(shrink_paths) = shrink_paths {
                     deserialized_bank.set_shrink_paths(shrink_paths);
                 }
@@ -27,7 +27,7 @@ use solana_metrics::{datapoint_debug, datapoint_error};
 use solana_rayon_threadlimit::get_thread_count;
 use solana_runtime::hardened_unpack::{unpack_genesis_archive, MAX_GENESIS_ARCHIVE_UNPACKED_SIZE};
 use solana_sdk::{
-    clock::{Slot, UnixTimestamp, DEFAULT_TICKS_PER_SECOND, MS_PER_TICK},
+    clock::{Epoch, Slot, UnixTimestamp, DEFAULT_TICKS_PER_SECOND, MS_PER_TICK},
     genesis_config::GenesisConfig,
     hash::Hash,
     pubkey::Pubkey,
@@ -145,6 +145,9 @@ pub struct Blockstore {
     blocktime_cf: LedgerColumn<cf::Blocktime>,
     perf_samples_cf: LedgerColumn<cf::PerfSamples>,
     block_height_cf: LedgerColumn<cf::BlockHeight>,
+    vote_groups_cf: LedgerColumn<cf::VoteGroups>,
+    vote_group_seeds_cf: LedgerColumn<cf::VoteGroupSeeds>,
+    vote_group_participation_cf: LedgerColumn<cf::VoteGroupParticipation>,
     program_costs_cf: LedgerColumn<cf::ProgramCosts>,
     bank_hash_cf: LedgerColumn<cf::BankHash>,
     last_root: Arc<RwLock<Slot>>,
@@ -318,6 +321,9 @@ impl Blockstore {
         let blocktime_cf = db.column();
         let perf_samples_cf = db.column();
         let block_height_cf = db.column();
+        let vote_groups_cf = db.column();
+        let vote_group_seeds_cf = db.column();
+        let vote_group_participation_cf = db.column();
         let program_costs_cf = db.column();
         let bank_hash_cf = db.column();
 
@@ -368,6 +374,9 @@ impl Blockstore {
             blocktime_cf,
             perf_samples_cf,
             block_height_cf,
+            vote_groups_cf,
+            vote_group_seeds_cf,
+            vote_group_participation_cf,
             program_costs_cf,
             bank_hash_cf,
             new_shreds_signals: vec![],
@@ -1757,6 +1766,72 @@ impl Blockstore {
         self.block_height_cf.put(slot, &block_height)
     }
 
+    /// the vote group replay recorded when `slot` was rooted, old groups can be looked up here
+    /// without re-deriving their epoch's voter set from pruned account state
+    pub fn get_vote_group(&self, slot: Slot) -> Result<Option<VoteGroupRecord>> {
+        datapoint_info!(
+            "blockstore-rpc-api",
+            ("method", "get_vote_group".to_string(), String)
+        );
+        let lowest_cleanup_slot = self.lowest_cleanup_slot.read().unwrap();
+        // lowest_cleanup_slot is the last slot that was not cleaned up by
+        // LedgerCleanupService
+        if *lowest_cleanup_slot > 0 && *lowest_cleanup_slot >= slot {
+            return Err(BlockstoreError::SlotCleanedUp);
+        }
+        self.vote_groups_cf.get(slot)
+    }
+
+    pub fn cache_vote_group(&self, slot: Slot, vote_group: &VoteGroupRecord) -> Result<()> {
+        self.vote_groups_cf.put(slot, vote_group)
+    }
+
+    /// the seed replay derived the vote group of the rotation window starting at
+    /// `window_start` from
+    pub fn get_vote_group_seed(&self, window_start: Slot) -> Result<Option<VoteGroupSeedRecord>> {
+        self.vote_group_seeds_cf.get(window_start)
+    }
+
+    pub fn cache_vote_group_seed(
+        &self,
+        window_start: Slot,
+        seed: &VoteGroupSeedRecord,
+    ) -> Result<()> {
+        self.vote_group_seeds_cf.put(window_start, seed)
+    }
+
+    /// how the members of `epoch`'s vote groups took part in the slots rooted so far. unlike
+    /// the vote groups themselves the summaries are never cleaned up
+    pub fn get_vote_group_participation(
+        &self,
+        epoch: Epoch,
+    ) -> Result<Option<VoteGroupParticipation>> {
+        datapoint_info!(
+            "blockstore-rpc-api",
+            ("method", "get_vote_group_participation".to_string(), String)
+        );
+        self.vote_group_participation_cf.get(epoch)
+    }
+
+    /// adds the vote group of the rooted `slot` of `epoch` to the epoch's participation
+    /// summary, a slot that was already counted is left out
+    pub fn record_vote_group_participation(
+        &self,
+        epoch: Epoch,
+        slot: Slot,
+        vote_group: &VoteGroupRecord,
+    ) -> Result<()> {
+        let mut participation = self
+            .vote_group_participation_cf
+            .get(epoch)?
+            .unwrap_or_default();
+        if participation.add_slot(slot, vote_group) {
+            self.vote_group_participation_cf
+                .put(epoch, &participation)?;
+        }
+        Ok(())
+    }
+
     pub fn get_first_available_block(&self) -> Result<Slot> {
         let mut root_iterator = self.rooted_slot_iterator(self.lowest_slot())?;
         Ok(root_iterator.next().unwrap_or_default())
@@ -3747,7 +3822,7 @@ fn adjust_ulimit_nofile(_enforce_ulimit_nofile: bool) -> Result<()> {
 fn adjust_ulimit_nofile(enforce_ulimit_nofile: bool) -> Result<()> {
     // Rocks DB likes to have many open files.  The default open file descriptor limit is
     // usually not enough
-    let desired_nofile = 500000;
+    let desired_nofile = 20000;
 
     fn get_nofile() -> libc::rlimit {
         let mut nofile = libc::rlimit {
@@ -3808,6 +3883,7 @@ pub mod tests {
         pubkey::Pubkey,
         signature::Signature,
         transaction::TransactionError,
+        vote_group_gen::SelectionAlgorithm,
     };
     use solana_storage_proto::convert::generated;
     use solana_transaction_status::{InnerInstructions, Reward, Rewards, TransactionTokenBalance};
@@ -7778,6 +7854,104 @@ pub mod tests {
         Blockstore::destroy(&blockstore_path).expect("Expected successful database destruction");
     }
 
+    #[test]
+    fn test_cache_get_vote_group() {
+        let blockstore_path = get_tmp_ledger_path!();
+        {
+            let blockstore = Blockstore::open(&blockstore_path).unwrap();
+            let record = VoteGroupRecord {
+                seed: 42,
+                members: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+                voted: vec![],
+            };
+            blockstore.cache_vote_group(5, &record).unwrap();
+            assert_eq!(blockstore.get_vote_group(5).unwrap(), Some(record));
+            assert_eq!(blockstore.get_vote_group(6).unwrap(), None);
+
+            blockstore.run_purge(0, 5, PurgeType::PrimaryIndex).unwrap();
+            *blockstore.lowest_cleanup_slot.write().unwrap() = 5;
+            assert_matches!(
+                blockstore.get_vote_group(5),
+                Err(BlockstoreError::SlotCleanedUp)
+            );
+            assert_eq!(blockstore.vote_groups_cf.get(5).unwrap(), None);
+        }
+        Blockstore::destroy(&blockstore_path).expect("Expected successful database destruction");
+    }
+
+    #[test]
+    fn test_cache_get_vote_group_seed() {
+        let blockstore_path = get_tmp_ledger_path!();
+        {
+            let blockstore = Blockstore::open(&blockstore_path).unwrap();
+            let record = VoteGroupSeedRecord {
+                seed: 42,
+                algorithm: SelectionAlgorithm::V3,
+                ordering_version: 1,
+            };
+            blockstore.cache_vote_group_seed(8, &record).unwrap();
+            assert_eq!(blockstore.get_vote_group_seed(8).unwrap(), Some(record));
+            assert_eq!(blockstore.get_vote_group_seed(9).unwrap(), None);
+
+            blockstore.run_purge(0, 8, PurgeType::PrimaryIndex).unwrap();
+            assert_eq!(blockstore.get_vote_group_seed(8).unwrap(), None);
+        }
+        Blockstore::destroy(&blockstore_path).expect("Expected successful database destruction");
+    }
+
+    #[test]
+    fn test_record_vote_group_participation() {
+        let blockstore_path = get_tmp_ledger_path!();
+        {
+            let blockstore = Blockstore::open(&blockstore_path).unwrap();
+            let (present, absent, outsider) = (
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+            );
+            let record = VoteGroupRecord {
+                seed: 42,
+                members: vec![present, absent],
+                voted: vec![present],
+            };
+            assert_eq!(blockstore.get_vote_group_participation(2).unwrap(), None);
+            blockstore
+                .record_vote_group_participation(2, 5, &record)
+                .unwrap();
+            blockstore
+                .record_vote_group_participation(2, 7, &record)
+                .unwrap();
+            // replaying a rooted slot doesn't count it again
+            blockstore
+                .record_vote_group_participation(2, 7, &record)
+                .unwrap();
+
+            let participation = blockstore.get_vote_group_participation(2).unwrap().unwrap();
+            assert_eq!(participation.slots, 2);
+            assert_eq!(participation.last_slot, Some(7));
+            assert_eq!(
+                participation.members[&present],
+                MemberParticipation {
+                    selected: 2,
+                    voted: 2
+                }
+            );
+            assert_eq!(participation.members[&absent].absent(), 2);
+            assert_eq!(participation.participation_rate(&present), Some(1.0));
+            assert_eq!(participation.participation_rate(&absent), Some(0.0));
+            assert_eq!(participation.participation_rate(&outsider), None);
+            assert_eq!(blockstore.get_vote_group_participation(3).unwrap(), None);
+
+            // purging the epoch's slots keeps the summary
+            blockstore.run_purge(0, 7, PurgeType::PrimaryIndex).unwrap();
+            assert_eq!(
+                blockstore.get_vote_group_participation(2).unwrap(),
+                Some(participation)
+            );
+        }
+        Blockstore::destroy(&blockstore_path).expect("Expected successful database destruction");
+    }
+
     #[test]
     fn test_lowest_slot() {
         let blockstore_path = get_tmp_ledger_path!();
@@ -16,7 +16,7 @@ use serde::de::DeserializeOwned;
 use serde::Serialize;
 use solana_runtime::hardened_unpack::UnpackError;
 use solana_sdk::{
-    clock::{Slot, UnixTimestamp},
+    clock::{Epoch, Slot, UnixTimestamp},
     pubkey::Pubkey,
     signature::Signature,
 };
@@ -73,6 +73,12 @@ const BLOCKTIME_CF: &str = "blocktime";
 const PERF_SAMPLES_CF: &str = "perf_samples";
 /// Column family for BlockHeight
 const BLOCK_HEIGHT_CF: &str = "block_height";
+/// Column family for VoteGroups
+const VOTE_GROUPS_CF: &str = "vote_groups";
+/// Column family for VoteGroupSeeds
+const VOTE_GROUP_SEEDS_CF: &str = "vote_group_seeds";
+/// Column family for VoteGroupParticipation
+const VOTE_GROUP_PARTICIPATION_CF: &str = "vote_group_participation";
 /// Column family for ProgramCosts
 const PROGRAM_COSTS_CF: &str = "program_costs";
 
@@ -183,6 +189,18 @@ pub mod columns {
     /// The block height column
     pub struct BlockHeight;
 
+    #[derive(Debug)]
+    /// The vote groups column
+    pub struct VoteGroups;
+
+    #[derive(Debug)]
+    /// The vote group seeds column, keyed by the first slot of the rotation window
+    pub struct VoteGroupSeeds;
+
+    #[derive(Debug)]
+    /// The vote group participation column, keyed by epoch
+    pub struct VoteGroupParticipation;
+
     #[derive(Debug)]
     // The program costs column
     pub struct ProgramCosts;
@@ -352,6 +370,18 @@ impl Rocks {
             BlockHeight::NAME,
             get_cf_options::<BlockHeight>(&access_type, &oldest_slot),
         );
+        let vote_groups_cf_descriptor = ColumnFamilyDescriptor::new(
+            VoteGroups::NAME,
+            get_cf_options::<VoteGroups>(&access_type, &oldest_slot),
+        );
+        let vote_group_seeds_cf_descriptor = ColumnFamilyDescriptor::new(
+            VoteGroupSeeds::NAME,
+            get_cf_options::<VoteGroupSeeds>(&access_type, &oldest_slot),
+        );
+        let vote_group_participation_cf_descriptor = ColumnFamilyDescriptor::new(
+            VoteGroupParticipation::NAME,
+            get_cf_options::<VoteGroupParticipation>(&access_type, &oldest_slot),
+        );
         let program_costs_cf_descriptor = ColumnFamilyDescriptor::new(
             ProgramCosts::NAME,
             get_cf_options::<ProgramCosts>(&access_type, &oldest_slot),
@@ -380,6 +410,12 @@ impl Rocks {
             (Blocktime::NAME, blocktime_cf_descriptor),
             (PerfSamples::NAME, perf_samples_cf_descriptor),
             (BlockHeight::NAME, block_height_cf_descriptor),
+            (VoteGroups::NAME, vote_groups_cf_descriptor),
+            (VoteGroupSeeds::NAME, vote_group_seeds_cf_descriptor),
+            (
+                VoteGroupParticipation::NAME,
+                vote_group_participation_cf_descriptor,
+            ),
             (ProgramCosts::NAME, program_costs_cf_descriptor),
         ];
         let cf_names: Vec<_> = cfs.iter().map(|c| c.0).collect();
@@ -499,6 +535,9 @@ impl Rocks {
             Blocktime::NAME,
             PerfSamples::NAME,
             BlockHeight::NAME,
+            VoteGroups::NAME,
+            VoteGroupSeeds::NAME,
+            VoteGroupParticipation::NAME,
             ProgramCosts::NAME,
         ]
     }
@@ -774,6 +813,55 @@ impl TypedColumn for columns::BlockHeight {
     type Type = u64;
 }
 
+impl SlotColumn for columns::VoteGroups {}
+impl ColumnName for columns::VoteGroups {
+    const NAME: &'static str = VOTE_GROUPS_CF;
+}
+impl TypedColumn for columns::VoteGroups {
+    type Type = blockstore_meta::VoteGroupRecord;
+}
+
+impl SlotColumn for columns::VoteGroupSeeds {}
+impl ColumnName for columns::VoteGroupSeeds {
+    const NAME: &'static str = VOTE_GROUP_SEEDS_CF;
+}
+impl TypedColumn for columns::VoteGroupSeeds {
+    type Type = blockstore_meta::VoteGroupSeedRecord;
+}
+
+impl Column for columns::VoteGroupParticipation {
+    type Index = Epoch;
+
+    fn key(epoch: Epoch) -> Vec<u8> {
+        let mut key = vec![0; 8];
+        BigEndian::write_u64(&mut key[..], epoch);
+        key
+    }
+
+    fn index(key: &[u8]) -> Epoch {
+        BigEndian::read_u64(&key[..8])
+    }
+
+    fn primary_index(epoch: Epoch) -> u64 {
+        epoch
+    }
+
+    fn slot(_index: Self::Index) -> Slot {
+        unimplemented!()
+    }
+
+    #[allow(clippy::wrong_self_convention)]
+    fn as_index(epoch: u64) -> Epoch {
+        epoch
+    }
+}
+impl ColumnName for columns::VoteGroupParticipation {
+    const NAME: &'static str = VOTE_GROUP_PARTICIPATION_CF;
+}
+impl TypedColumn for columns::VoteGroupParticipation {
+    type Type = blockstore_meta::VoteGroupParticipation;
+}
+
 impl ColumnName for columns::ProgramCosts {
     const NAME: &'static str = PROGRAM_COSTS_CF;
 }
@@ -1319,8 +1407,10 @@ fn get_cf_options<C: 'static + Column + ColumnName>(
 
     // TransactionStatusIndex must be excluded from LedgerCleanupService's rocksdb
     // compactions....
+    // VoteGroupParticipation is keyed by epoch, not slot, and kept for the life of the ledger
     if matches!(access_type, AccessType::PrimaryOnly)
         && C::NAME != columns::TransactionStatusIndex::NAME
+        && C::NAME != columns::VoteGroupParticipation::NAME
     {
         options.set_compaction_filter_factory(PurgedSlotFilterFactory::<C> {
             oldest_slot: oldest_slot.clone(),
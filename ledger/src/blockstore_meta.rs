@@ -1,7 +1,10 @@
 use crate::erasure::ErasureConfig;
 use serde::{Deserialize, Serialize};
-use solana_sdk::{clock::Slot, hash::Hash};
-use std::{collections::BTreeSet, ops::RangeBounds};
+use solana_sdk::{clock::Slot, hash::Hash, pubkey::Pubkey, vote_group_gen::SelectionAlgorithm};
+use std::{
+    collections::{BTreeSet, HashMap},
+    ops::RangeBounds,
+};
 
 #[derive(Clone, Debug, Default, Deserialize, Serialize, Eq, PartialEq)]
 // The Meta column family
@@ -276,6 +279,72 @@ pub struct PerfSample {
     pub sample_period_secs: u16,
 }
 
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+/// The seed and members of a rooted slot's vote group
+pub struct VoteGroupRecord {
+    pub seed: u64,
+    pub members: Vec<Pubkey>,
+    // Members whose vote state had confirmed the slot, or rooted past it, when it was rooted
+    pub voted: Vec<Pubkey>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, Eq, PartialEq)]
+/// The seed a rotation window's vote group was derived from and how it was walked
+pub struct VoteGroupSeedRecord {
+    pub seed: u64,
+    pub algorithm: SelectionAlgorithm,
+    pub ordering_version: u8,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, Eq, PartialEq)]
+/// How often a vote account was picked for a rooted slot's vote group and voted on it
+pub struct MemberParticipation {
+    pub selected: u64,
+    pub voted: u64,
+}
+
+impl MemberParticipation {
+    pub fn absent(&self) -> u64 {
+        self.selected.saturating_sub(self.voted)
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+/// The VoteGroupRecords of an epoch's rooted slots summed up per member
+pub struct VoteGroupParticipation {
+    // Rooted slots counted so far
+    pub slots: u64,
+    // The last of them, rooted slots only ever grow so this keeps a slot from counting twice
+    pub last_slot: Option<Slot>,
+    pub members: HashMap<Pubkey, MemberParticipation>,
+}
+
+impl VoteGroupParticipation {
+    /// Counts `slot`'s group, false when it is not past the last slot counted
+    pub fn add_slot(&mut self, slot: Slot, record: &VoteGroupRecord) -> bool {
+        if self.last_slot.map_or(false, |last_slot| slot <= last_slot) {
+            return false;
+        }
+        self.slots += 1;
+        self.last_slot = Some(slot);
+        for member in &record.members {
+            self.members.entry(*member).or_default().selected += 1;
+        }
+        for member in &record.voted {
+            self.members.entry(*member).or_default().voted += 1;
+        }
+        true
+    }
+
+    /// The share of `member`'s groups it voted in, None when it was never picked
+    pub fn participation_rate(&self, member: &Pubkey) -> Option<f64> {
+        self.members
+            .get(member)
+            .filter(|participation| participation.selected > 0)
+            .map(|participation| participation.voted as f64 / participation.selected as f64)
+    }
+}
+
 #[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
 pub struct ProgramCost {
     pub cost: u64,
@@ -2,7 +2,7 @@ use crate::{
     block_error::BlockError,
     blockstore::Blockstore,
     blockstore_db::BlockstoreError,
-    blockstore_meta::SlotMeta,
+    blockstore_meta::{SlotMeta, VoteGroupSeedRecord},
     entry::{create_ticks, Entry, EntrySlice, EntryType, EntryVerificationStatus, VerifyRecyclers},
     leader_schedule_cache::LeaderScheduleCache,
 };
@@ -32,14 +32,17 @@ use solana_sdk::{
     clock::{Slot, MAX_PROCESSING_AGE},
     genesis_config::GenesisConfig,
     hash::Hash,
+    instruction::VoterGroup,
     pubkey::Pubkey,
     signature::{Keypair, Signature},
     timing,
     transaction::{Result, Transaction, TransactionError},
+    vote_group_gen::VoteGroupOverrides,
 };
 use solana_transaction_status::token_balances::{
     collect_token_balances, TransactionTokenBalancesSet,
 };
+use solana_vote_program::vote_transaction;
 
 use std::{
     cell::RefCell,
@@ -352,6 +355,12 @@ pub enum BlockstoreProcessorError {
 
     #[error("root bank with mismatched capitalization at {0}")]
     RootBankWithMismatchedCapitalization(Slot),
+
+    #[error("vote group seed of root {0} differs from the recorded one")]
+    VoteGroupSeedMismatch(Slot),
+
+    #[error("root {0} holds a vote from {1} outside of its vote group")]
+    OutOfGroupVote(Slot, Pubkey),
 }
 
 /// Callback for accessing bank state while processing the blockstore
@@ -371,6 +380,8 @@ pub struct ProcessOptions {
     pub account_indexes: AccountSecondaryIndexes,
     pub accounts_db_caching_enabled: bool,
     pub allow_dead_slots: bool,
+    pub vote_group_overrides: Option<VoteGroupOverrides>,
+    pub verify_vote_groups: bool,
 }
 
 pub fn process_blockstore(
@@ -390,7 +401,7 @@ pub fn process_blockstore(
     }
 
     // Setup bank for slot 0
-    let bank0 = Bank::new_with_paths(
+    let mut bank0 = Bank::new_with_paths(
         &genesis_config,
         account_paths,
         &opts.frozen_accounts,
@@ -399,6 +410,7 @@ pub fn process_blockstore(
         opts.account_indexes.clone(),
         opts.accounts_db_caching_enabled,
     );
+    bank0.set_vote_group_overrides(opts.vote_group_overrides.clone());
     let bank0 = Arc::new(bank0);
     info!("processing ledger for slot 0...");
     let recyclers = VerifyRecyclers::default();
@@ -968,6 +980,10 @@ fn load_frozen_forks(
             assert!(bank.is_frozen());
             all_banks.insert(bank.slot(), bank.clone());
 
+            if opts.verify_vote_groups && blockstore.is_root(slot) {
+                verify_vote_groups(blockstore, &bank)?;
+            }
+
             // If we've reached the last known root in blockstore, start looking
             // for newer cluster confirmed roots
             let new_root_bank = {
@@ -1228,6 +1244,63 @@ impl TransactionStatusSender {
 
 pub type CacheBlockMetaSender = Sender<Arc<Bank>>;
 
+/// the first slot of the rotation window `bank`'s slot belongs to and the seed its vote
+/// group was derived from
+pub fn vote_group_seed_record(bank: &Bank) -> Option<(Slot, VoteGroupSeedRecord)> {
+    let (window_start, _) = bank.vote_group_window(bank.slot())?;
+    let (generator, seed) = bank.vote_group_for_slot(bank.slot())?;
+    Some((
+        window_start,
+        VoteGroupSeedRecord {
+            seed,
+            algorithm: generator.algorithm(),
+            ordering_version: generator.ordering_version(),
+        },
+    ))
+}
+
+// re-derives the vote group of the rooted `bank` and compares it with the seed recorded when
+// the slot was first replayed, then checks that every vote it landed came from a member of
+// the voted slot's group. before limited voting is enforced out of group votes still land
+fn verify_vote_groups(
+    blockstore: &Blockstore,
+    bank: &Bank,
+) -> result::Result<(), BlockstoreProcessorError> {
+    let slot = bank.slot();
+    if let Some((window_start, record)) = vote_group_seed_record(bank) {
+        if let Some(recorded) = blockstore.get_vote_group_seed(window_start)? {
+            if recorded != record {
+                warn!(
+                    "vote group seed of slot {} is {:?}, {:?} was recorded",
+                    slot, record, recorded
+                );
+                return Err(BlockstoreProcessorError::VoteGroupSeedMismatch(slot));
+            }
+        }
+    }
+    if !bank.is_limited_voting_enforced() {
+        return Ok(());
+    }
+    let entries = blockstore.get_slot_entries(slot, 0)?;
+    for tx in entries.iter().flat_map(|entry| entry.transactions.iter()) {
+        if let Some((vote_pubkey, vote, _)) = vote_transaction::parse_vote_transaction(tx) {
+            let landed = tx
+                .signatures
+                .get(0)
+                .and_then(|signature| bank.get_signature_status(signature))
+                .map_or(false, |status| status.is_ok());
+            if landed
+                && vote.slots.first().map_or(false, |voted| {
+                    !bank.in_group(*voted, vote.hash, vote_pubkey)
+                })
+            {
+                return Err(BlockstoreProcessorError::OutOfGroupVote(slot, vote_pubkey));
+            }
+        }
+    }
+    Ok(())
+}
+
 pub fn cache_block_meta(bank: &Arc<Bank>, cache_block_meta_sender: Option<&CacheBlockMetaSender>) {
     if let Some(cache_block_meta_sender) = cache_block_meta_sender {
         cache_block_meta_sender
@@ -1628,6 +1701,54 @@ pub mod tests {
         assert_eq!(bank_forks.root(), 4);
     }
 
+    #[test]
+    fn test_process_blockstore_verify_vote_groups() {
+        solana_logger::setup();
+
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config(10_000);
+        let ticks_per_slot = genesis_config.ticks_per_slot;
+        let (ledger_path, blockhash) = create_new_tmp_ledger!(&genesis_config);
+        let blockstore =
+            Blockstore::open(&ledger_path).expect("Expected to successfully open database ledger");
+        let last_entry_hash =
+            fill_blockstore_slot_with_ticks(&blockstore, ticks_per_slot, 1, 0, blockhash);
+        fill_blockstore_slot_with_ticks(&blockstore, ticks_per_slot, 2, 1, last_entry_hash);
+        blockstore.set_roots(&[0, 1, 2]).unwrap();
+
+        let opts = ProcessOptions {
+            verify_vote_groups: true,
+            ..ProcessOptions::default()
+        };
+        // nothing was recorded for the ledger yet
+        let (bank_forks, _leader_schedule) =
+            process_blockstore(&genesis_config, &blockstore, Vec::new(), opts.clone(), None)
+                .unwrap();
+        let (window_start, record) = vote_group_seed_record(&bank_forks[2]).unwrap();
+
+        blockstore
+            .cache_vote_group_seed(window_start, &record)
+            .unwrap();
+        assert!(
+            process_blockstore(&genesis_config, &blockstore, Vec::new(), opts.clone(), None)
+                .is_ok()
+        );
+
+        // a replay that derived another seed diverged from the one that recorded it
+        blockstore
+            .cache_vote_group_seed(
+                window_start,
+                &VoteGroupSeedRecord {
+                    seed: record.seed.wrapping_add(1),
+                    ..record
+                },
+            )
+            .unwrap();
+        assert_matches!(
+            process_blockstore(&genesis_config, &blockstore, Vec::new(), opts, None),
+            Err(BlockstoreProcessorError::VoteGroupSeedMismatch(_))
+        );
+    }
+
     #[test]
     fn test_process_blockstore_with_two_forks() {
         solana_logger::setup();
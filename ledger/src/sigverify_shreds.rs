@@ -246,7 +246,7 @@ pub fn verify_shreds_gpu(
     out.set_pinnable();
     elems.push(
         perf_libs::Elems {
-            #![allow(clippy::cast_ptr_alignment)]
+            // hack
             elems: pubkeys.as_ptr() as *const solana_sdk::packet::Packet,
             num: num_packets as u32,
         },
@@ -385,7 +385,7 @@ pub fn sign_shreds_gpu(
     signatures_out.resize(total_sigs * sig_size, 0);
     elems.push(
         perf_libs::Elems {
-            #![allow(clippy::cast_ptr_alignment)]
+            // hack
             elems: pinned_keypair.as_ptr() as *const solana_sdk::packet::Packet,
             num: num_keypair_packets as u32,
         },
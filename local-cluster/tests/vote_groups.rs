@@ -0,0 +1,156 @@
+#![allow(clippy::integer_arithmetic)]
+use log::*;
+use serial_test::serial;
+use solana_client::rpc_client::RpcClient;
+use solana_core::validator::ValidatorConfig;
+use solana_local_cluster::{
+    local_cluster::{ClusterConfig, LocalCluster},
+    validator_configs::*,
+};
+use solana_sdk::{
+    clock::{Epoch, Slot},
+    commitment_config::CommitmentConfig,
+    epoch_schedule::{EpochSchedule, MINIMUM_SLOTS_PER_EPOCH},
+    pubkey::Pubkey,
+    signature::Signer,
+    vote_group_gen::VoteGroupOverrides,
+};
+use solana_vote_program::vote_state::VoteState;
+use std::{
+    collections::HashSet,
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+const RUST_LOG_FILTER: &str =
+    "error,solana_core::replay_stage=warn,solana_local_cluster=info,vote_groups=info";
+
+const NUM_NODES: usize = 15;
+const VOTE_GROUP_SIZE: usize = 5;
+const SLOTS_PER_EPOCH: u64 = MINIMUM_SLOTS_PER_EPOCH as u64;
+
+// every node is staked in genesis, so the groups of the genesis epochs already draw from
+// all of them
+fn new_vote_group_cluster() -> LocalCluster {
+    let validator_config = ValidatorConfig {
+        vote_group_overrides: Some(VoteGroupOverrides {
+            group_size: Some(VOTE_GROUP_SIZE),
+            ..VoteGroupOverrides::default()
+        }),
+        ..ValidatorConfig::default()
+    };
+    let mut config = ClusterConfig {
+        cluster_lamports: 100_000,
+        node_stakes: vec![100; NUM_NODES],
+        validator_configs: make_identical_validator_configs(&validator_config, NUM_NODES),
+        slots_per_epoch: SLOTS_PER_EPOCH,
+        stakers_slot_offset: SLOTS_PER_EPOCH,
+        skip_warmup_slots: true,
+        ..ClusterConfig::default()
+    };
+    LocalCluster::new(&mut config)
+}
+
+fn vote_pubkeys(cluster: &LocalCluster) -> Vec<Pubkey> {
+    cluster
+        .validators
+        .values()
+        .map(|validator| validator.info.voting_keypair.pubkey())
+        .collect()
+}
+
+fn vote_group_members(client: &RpcClient, slot: Slot) -> Vec<String> {
+    let group = client
+        .get_vote_group_for_slot(slot)
+        .unwrap()
+        .unwrap_or_else(|| panic!("no vote group for slot {}", slot));
+    assert_eq!(group.members.len(), VOTE_GROUP_SIZE, "slot {}", slot);
+    group.members
+}
+
+fn wait_for_root_in_epoch(client: &RpcClient, epoch_schedule: &EpochSchedule, epoch: Epoch) {
+    let loop_start = Instant::now();
+    let loop_timeout = Duration::from_secs(180);
+    loop {
+        let root = client
+            .get_slot_with_commitment(CommitmentConfig::finalized())
+            .unwrap_or(0);
+        if epoch_schedule.get_epoch(root) >= epoch {
+            info!("rooted slot {} of epoch {}", root, epoch);
+            return;
+        }
+        assert!(
+            loop_start.elapsed() < loop_timeout,
+            "no root in epoch {} after {:?}, last root {}",
+            epoch,
+            loop_timeout,
+            root
+        );
+        sleep(Duration::from_secs(1));
+    }
+}
+
+#[test]
+#[serial]
+fn test_vote_groups_reach_roots() {
+    solana_logger::setup_with_default(RUST_LOG_FILTER);
+    error!("test_vote_groups_reach_roots");
+    let cluster = new_vote_group_cluster();
+    cluster.check_for_new_roots(16, &"test_vote_groups_reach_roots");
+}
+
+#[test]
+#[serial]
+fn test_vote_groups_only_members_votes_land() {
+    solana_logger::setup_with_default(RUST_LOG_FILTER);
+    error!("test_vote_groups_only_members_votes_land");
+    let cluster = new_vote_group_cluster();
+    cluster.check_for_new_roots(16, &"test_vote_groups_only_members_votes_land");
+
+    let client = RpcClient::new_socket(cluster.entry_point_info.rpc);
+    let mut num_votes = 0;
+    for vote_pubkey in vote_pubkeys(&cluster) {
+        let account = client.get_account(&vote_pubkey).unwrap();
+        let vote_state = VoteState::deserialize(&account.data).unwrap();
+        for lockout in vote_state.votes.iter() {
+            assert!(
+                vote_group_members(&client, lockout.slot).contains(&vote_pubkey.to_string()),
+                "{} voted on slot {} outside of its group",
+                vote_pubkey,
+                lockout.slot
+            );
+            num_votes += 1;
+        }
+    }
+    // the roots above need votes, an empty tower everywhere would prove nothing
+    assert!(num_votes > 0);
+}
+
+#[test]
+#[serial]
+fn test_vote_groups_rotate_across_epochs() {
+    solana_logger::setup_with_default(RUST_LOG_FILTER);
+    error!("test_vote_groups_rotate_across_epochs");
+    let cluster = new_vote_group_cluster();
+    let client = RpcClient::new_socket(cluster.entry_point_info.rpc);
+    let epoch_schedule = client.get_epoch_schedule().unwrap();
+    wait_for_root_in_epoch(&client, &epoch_schedule, 2);
+
+    let mut seeds = HashSet::new();
+    let mut members = HashSet::new();
+    for epoch in 0..=2 {
+        let first_slot = epoch_schedule.get_first_slot_in_epoch(epoch);
+        let group = client.get_vote_group_for_slot(first_slot).unwrap().unwrap();
+        assert!(seeds.insert(group.seed), "epoch {} repeats a seed", epoch);
+        for slot in first_slot..=epoch_schedule.get_last_slot_in_epoch(epoch) {
+            members.extend(vote_group_members(&client, slot));
+        }
+    }
+    // a group that never rotates would keep the same five members
+    assert!(members.len() > VOTE_GROUP_SIZE);
+    let vote_pubkeys: HashSet<_> = vote_pubkeys(&cluster)
+        .iter()
+        .map(|vote_pubkey| vote_pubkey.to_string())
+        .collect();
+    assert!(members.is_subset(&vote_pubkeys));
+}
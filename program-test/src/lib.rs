@@ -189,7 +189,7 @@ fn get_sysvar<T: Default + Sysvar + Sized + serde::de::DeserializeOwned>(
     let invoke_context = get_invoke_context();
 
     let sysvar_data = match invoke_context.get_sysvar_data(id).ok_or_else(|| {
-        ic_msg!(invoke_context, "Unable to get Sysvar {}", id);
+        solana_sdk::ic_msg!(invoke_context, "Unable to get Sysvar {}", id);
         UNSUPPORTED_SYSVAR
     }) {
         Ok(sysvar_data) => sysvar_data,
@@ -743,9 +743,9 @@ impl ProgramTest {
         let mut bank = Bank::new(&genesis_config);
 
         for loader in &[
-            solana_bpf_loader_deprecated_program!(),
-            solana_bpf_loader_program!(),
-            solana_bpf_loader_upgradeable_program!(),
+            ("solana_bpf_loader_deprecated_program".to_string(), solana_sdk::bpf_loader_deprecated::id(), solana_bpf_loader_program::process_instruction as solana_sdk::process_instruction::ProcessInstructionWithContext),
+            ("solana_bpf_loader_program".to_string(), solana_sdk::bpf_loader::id(), solana_bpf_loader_program::process_instruction),
+            ("solana_bpf_loader_upgradeable_program".to_string(), solana_sdk::bpf_loader_upgradeable::id(), solana_bpf_loader_program::process_instruction),
         ] {
             bank.add_builtin(&loader.0, loader.1, loader.2);
         }
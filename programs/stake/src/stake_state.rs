@@ -560,6 +560,14 @@ pub struct PointValue {
     pub points: u128, // over these points
 }
 
+/// the credits a vote account is owed for `epoch` when they follow its vote group
+///  participation instead of the credits counted in its vote state
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ParticipationCredits {
+    pub epoch: Epoch,
+    pub credits: u64,
+}
+
 impl Stake {
     pub fn stake(
         &self,
@@ -574,6 +582,7 @@ impl Stake {
         &mut self,
         point_value: &PointValue,
         vote_state: &VoteState,
+        participation: Option<&ParticipationCredits>,
         stake_history: Option<&StakeHistory>,
         inflation_point_calc_tracer: &mut Option<impl FnMut(&InflationPointCalculationEvent)>,
         fix_stake_deactivate: bool,
@@ -587,6 +596,7 @@ impl Stake {
         self.calculate_rewards(
             point_value,
             vote_state,
+            participation,
             stake_history,
             inflation_point_calc_tracer,
             fix_stake_deactivate,
@@ -607,12 +617,14 @@ impl Stake {
     pub fn calculate_points(
         &self,
         vote_state: &VoteState,
+        participation: Option<&ParticipationCredits>,
         stake_history: Option<&StakeHistory>,
         inflation_point_calc_tracer: &mut Option<impl FnMut(&InflationPointCalculationEvent)>,
         fix_stake_deactivate: bool,
     ) -> u128 {
         self.calculate_points_and_credits(
             vote_state,
+            participation,
             stake_history,
             inflation_point_calc_tracer,
             fix_stake_deactivate,
@@ -622,10 +634,12 @@ impl Stake {
 
     /// for a given stake and vote_state, calculate how many
     ///   points were earned (credits * stake) and new value
-    ///   for credits_observed were the points paid
+    ///   for credits_observed were the points paid, the credits of `participation`'s
+    ///   epoch are swapped for its participation credits
     fn calculate_points_and_credits(
         &self,
         new_vote_state: &VoteState,
+        participation: Option<&ParticipationCredits>,
         stake_history: Option<&StakeHistory>,
         inflation_point_calc_tracer: &mut Option<impl FnMut(&InflationPointCalculationEvent)>,
         fix_stake_deactivate: bool,
@@ -670,7 +684,17 @@ impl Stake {
                 //  or was activated after this epoch
                 0
             };
-            let earned_credits = u128::from(earned_credits);
+            let earned_credits = match participation {
+                Some(participation)
+                    if participation.epoch == epoch
+                        && final_epoch_credits > initial_epoch_credits =>
+                {
+                    // same share of the epoch's participation credits as of its vote credits
+                    u128::from(participation.credits) * u128::from(earned_credits)
+                        / u128::from(final_epoch_credits - initial_epoch_credits)
+                }
+                _ => u128::from(earned_credits),
+            };
 
             // don't want to assume anything about order of the iterator...
             new_credits_observed = new_credits_observed.max(final_epoch_credits);
@@ -702,12 +726,14 @@ impl Stake {
         &self,
         point_value: &PointValue,
         vote_state: &VoteState,
+        participation: Option<&ParticipationCredits>,
         stake_history: Option<&StakeHistory>,
         inflation_point_calc_tracer: &mut Option<impl FnMut(&InflationPointCalculationEvent)>,
         fix_stake_deactivate: bool,
     ) -> Option<(u64, u64, u64)> {
         let (points, credits_observed) = self.calculate_points_and_credits(
             vote_state,
+            participation,
             stake_history,
             inflation_point_calc_tracer,
             fix_stake_deactivate,
@@ -1524,6 +1550,7 @@ pub fn redeem_rewards(
     stake_account: &mut AccountSharedData,
     vote_account: &mut AccountSharedData,
     vote_state: &VoteState,
+    participation: Option<&ParticipationCredits>,
     point_value: &PointValue,
     stake_history: Option<&StakeHistory>,
     inflation_point_calc_tracer: &mut Option<impl FnMut(&InflationPointCalculationEvent)>,
@@ -1549,6 +1576,7 @@ pub fn redeem_rewards(
         if let Some((stakers_reward, voters_reward)) = stake.redeem_rewards(
             point_value,
             vote_state,
+            participation,
             stake_history,
             inflation_point_calc_tracer,
             fix_stake_deactivate,
@@ -1571,6 +1599,7 @@ pub fn redeem_rewards(
 pub fn calculate_points(
     stake_account: &AccountSharedData,
     vote_account: &AccountSharedData,
+    participation: Option<&ParticipationCredits>,
     stake_history: Option<&StakeHistory>,
     fix_stake_deactivate: bool,
 ) -> Result<u128, InstructionError> {
@@ -1580,6 +1609,7 @@ pub fn calculate_points(
 
         Ok(stake.calculate_points(
             &vote_state,
+            participation,
             stake_history,
             &mut null_tracer(),
             fix_stake_deactivate,
@@ -3901,6 +3931,7 @@ mod tests {
                 },
                 &vote_state,
                 None,
+                None,
                 &mut null_tracer(),
                 true,
             )
@@ -3920,6 +3951,7 @@ mod tests {
                 },
                 &vote_state,
                 None,
+                None,
                 &mut null_tracer(),
                 true,
             )
@@ -3956,6 +3988,7 @@ mod tests {
                 },
                 &vote_state,
                 None,
+                None,
                 &mut null_tracer(),
                 true,
             )
@@ -3971,7 +4004,7 @@ mod tests {
         // no overflow on points
         assert_eq!(
             u128::from(stake.delegation.stake) * epoch_slots,
-            stake.calculate_points(&vote_state, None, &mut null_tracer(), true)
+            stake.calculate_points(&vote_state, None, None, &mut null_tracer(), true)
         );
     }
 
@@ -3998,6 +4031,7 @@ mod tests {
                 },
                 &vote_state,
                 None,
+                None,
                 &mut null_tracer(),
                 true,
             )
@@ -4017,6 +4051,7 @@ mod tests {
                 },
                 &vote_state,
                 None,
+                None,
                 &mut null_tracer(),
                 true,
             )
@@ -4033,6 +4068,7 @@ mod tests {
                 },
                 &vote_state,
                 None,
+                None,
                 &mut null_tracer(),
                 true,
             )
@@ -4052,6 +4088,7 @@ mod tests {
                 },
                 &vote_state,
                 None,
+                None,
                 &mut null_tracer(),
                 true,
             )
@@ -4069,6 +4106,7 @@ mod tests {
                 },
                 &vote_state,
                 None,
+                None,
                 &mut null_tracer(),
                 true,
             )
@@ -4092,6 +4130,7 @@ mod tests {
                 },
                 &vote_state,
                 None,
+                None,
                 &mut null_tracer(),
                 true,
             )
@@ -4109,6 +4148,7 @@ mod tests {
                 },
                 &vote_state,
                 None,
+                None,
                 &mut null_tracer(),
                 true,
             )
@@ -4123,6 +4163,7 @@ mod tests {
                 },
                 &vote_state,
                 None,
+                None,
                 &mut null_tracer(),
                 true,
             )
@@ -4140,6 +4181,7 @@ mod tests {
                 },
                 &vote_state,
                 None,
+                None,
                 &mut null_tracer(),
                 true,
             )
@@ -4157,6 +4199,7 @@ mod tests {
                 },
                 &vote_state,
                 None,
+                None,
                 &mut null_tracer(),
                 true,
             )
@@ -4165,11 +4208,86 @@ mod tests {
         // assert the previous behavior is preserved where fix_stake_deactivate=false
         assert_eq!(
             (0, 0),
-            stake.calculate_points_and_credits(&vote_state, None, &mut null_tracer(), false)
+            stake.calculate_points_and_credits(&vote_state, None, None, &mut null_tracer(), false)
         );
         assert_eq!(
             (0, 4),
-            stake.calculate_points_and_credits(&vote_state, None, &mut null_tracer(), true)
+            stake.calculate_points_and_credits(&vote_state, None, None, &mut null_tracer(), true)
+        );
+    }
+
+    #[test]
+    fn test_stake_state_calculate_points_with_participation() {
+        let mut vote_state = VoteState::default();
+        let mut stake = Stake::new(
+            1,
+            &Pubkey::default(),
+            &vote_state,
+            std::u64::MAX,
+            &Config::default(),
+        );
+
+        // 2 credits at epoch 0, 4 at epoch 1
+        vote_state.increment_credits(0);
+        vote_state.increment_credits(0);
+        for _ in 0..4 {
+            vote_state.increment_credits(1);
+        }
+        let participation = ParticipationCredits {
+            epoch: 1,
+            credits: 10,
+        };
+
+        // only epoch 1's credits are swapped
+        assert_eq!(
+            2 + 10,
+            stake.calculate_points(
+                &vote_state,
+                Some(&participation),
+                None,
+                &mut null_tracer(),
+                true
+            )
+        );
+        // a stake that saw half of epoch 1 earns half of its participation credits
+        stake.credits_observed = 4;
+        assert_eq!(
+            (5, 6),
+            stake.calculate_points_and_credits(
+                &vote_state,
+                Some(&participation),
+                None,
+                &mut null_tracer(),
+                true
+            )
+        );
+        // no participation credits for another epoch
+        assert_eq!(
+            (2, 6),
+            stake.calculate_points_and_credits(
+                &vote_state,
+                Some(&ParticipationCredits {
+                    epoch: 0,
+                    credits: 10,
+                }),
+                None,
+                &mut null_tracer(),
+                true
+            )
+        );
+        assert_eq!(
+            Some((5, 0, 6)),
+            stake.calculate_rewards(
+                &PointValue {
+                    rewards: 5,
+                    points: 5
+                },
+                &vote_state,
+                Some(&participation),
+                None,
+                &mut null_tracer(),
+                true,
+            )
         );
     }
 
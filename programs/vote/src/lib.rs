@@ -2,6 +2,10 @@
 #![allow(clippy::integer_arithmetic)]
 
 pub mod authorized_voters;
+pub mod vote_group_admin;
+pub mod vote_group_certificate;
+pub mod vote_group_config;
+pub mod vote_group_exclusions;
 pub mod vote_instruction;
 pub mod vote_state;
 pub mod vote_transaction;
@@ -0,0 +1,622 @@
+//! vote group admin program
+//!  lets a multisig authority change the vote group parameters with instructions. a
+//!  change is staged in the params account and only becomes the params the bank reads
+//!  once the next epoch starts, so every epoch sees one consistent set
+use crate::vote_group_exclusions::MAX_EXCLUDED_VOTERS;
+use log::*;
+use num_derive::{FromPrimitive, ToPrimitive};
+use serde_derive::{Deserialize, Serialize};
+use solana_sdk::{
+    account::{Account, AccountSharedData, ReadableAccount, WritableAccount},
+    clock::Epoch,
+    decode_error::DecodeError,
+    genesis_config::GenesisConfig,
+    instruction::{AccountMeta, Instruction, InstructionError},
+    keyed_account::{from_keyed_account, get_signers, next_keyed_account, KeyedAccount},
+    process_instruction::InvokeContext,
+    program_utils::limited_deserialize,
+    pubkey::Pubkey,
+    sysvar::{self, clock::Clock},
+    vote_group_gen::{ExclusionList, QuorumFraction, SelectionAlgorithm},
+};
+use std::collections::HashSet;
+use thiserror::Error;
+
+// vote group admin program ID
+pub use solana_sdk::vote::group_admin::program::{check_id, id};
+// the account the params live in
+pub use solana_sdk::vote::group_admin::params;
+
+/// most keys an authority can be made of
+pub const MAX_ADMIN_SIGNERS: usize = 11;
+
+#[derive(Error, Debug, Clone, PartialEq, FromPrimitive, ToPrimitive)]
+pub enum VoteGroupAdminError {
+    #[error("fewer of the authority's signers signed than its threshold")]
+    NotEnoughSigners,
+
+    #[error("group size must be at least one")]
+    ZeroGroupSize,
+
+    #[error("quorum fraction must be at least one half and below one")]
+    InvalidQuorumFraction,
+
+    #[error("the exclusion list is full")]
+    TooManyExclusions,
+}
+
+impl<E> DecodeError<E> for VoteGroupAdminError {
+    fn type_of() -> &'static str {
+        "VoteGroupAdminError"
+    }
+}
+
+/// `threshold` of `signers` have to sign every change
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq, Clone)]
+pub struct MultisigAuthority {
+    pub signers: Vec<Pubkey>,
+    pub threshold: u8,
+}
+
+impl MultisigAuthority {
+    pub fn new(signers: Vec<Pubkey>, threshold: u8) -> Self {
+        Self { signers, threshold }
+    }
+
+    /// distinct signers, no more than MAX_ADMIN_SIGNERS, and a threshold they can meet
+    pub fn is_valid(&self) -> bool {
+        let distinct: HashSet<&Pubkey> = self.signers.iter().collect();
+        distinct.len() == self.signers.len()
+            && self.signers.len() <= MAX_ADMIN_SIGNERS
+            && self.threshold > 0
+            && self.threshold as usize <= self.signers.len()
+    }
+
+    pub fn is_authorized(&self, signers: &HashSet<Pubkey>) -> bool {
+        self.threshold > 0
+            && self
+                .signers
+                .iter()
+                .filter(|signer| signers.contains(signer))
+                .count()
+                >= self.threshold as usize
+    }
+}
+
+/// what the admin decided, fields left at None keep what the vote group config and the
+/// features decide
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct GroupParams {
+    pub group_size: Option<u64>,
+    pub algorithm: Option<SelectionAlgorithm>,
+    pub quorum_fraction: Option<QuorumFraction>,
+    /// replaces the exclusion registry
+    pub exclusions: ExclusionList,
+}
+
+impl Default for GroupParams {
+    fn default() -> Self {
+        Self {
+            group_size: None,
+            algorithm: None,
+            quorum_fraction: None,
+            exclusions: ExclusionList::legacy(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Clone)]
+pub struct AdminState {
+    pub authority: MultisigAuthority,
+    /// the params of the epochs before effective_epoch
+    pub current: GroupParams,
+    /// the params with every change made so far, in effect from effective_epoch on
+    pub staged: GroupParams,
+    pub effective_epoch: Epoch,
+}
+
+impl AdminState {
+    pub fn new(authority: MultisigAuthority) -> Self {
+        Self {
+            authority,
+            ..Self::default()
+        }
+    }
+
+    pub fn from<T: ReadableAccount>(account: &T) -> Option<Self> {
+        bincode::deserialize(account.data()).ok()
+    }
+
+    /// room for a full authority and two full exclusion lists
+    pub fn size_of() -> usize {
+        let params = GroupParams {
+            group_size: Some(0),
+            algorithm: Some(SelectionAlgorithm::default()),
+            quorum_fraction: Some(QuorumFraction::default()),
+            exclusions: ExclusionList::default(),
+        };
+        let state = Self {
+            authority: MultisigAuthority::new(vec![Pubkey::default(); MAX_ADMIN_SIGNERS], 0),
+            current: params.clone(),
+            staged: params,
+            effective_epoch: 0,
+        };
+        bincode::serialized_size(&state).unwrap() as usize
+            + 2 * MAX_EXCLUDED_VOTERS * std::mem::size_of::<Pubkey>()
+    }
+
+    /// the params the bank uses during `epoch`
+    pub fn params_at(&self, epoch: Epoch) -> &GroupParams {
+        if epoch >= self.effective_epoch {
+            &self.staged
+        } else {
+            &self.current
+        }
+    }
+
+    /// the params a change made during `epoch` goes into, they take effect with the next
+    /// epoch
+    fn stage(&mut self, epoch: Epoch) -> &mut GroupParams {
+        if epoch >= self.effective_epoch {
+            self.current = self.staged.clone();
+        }
+        self.effective_epoch = epoch + 1;
+        &mut self.staged
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub enum VoteGroupAdminInstruction {
+    /// Set how many voters are selected for each slot
+    ///
+    /// # Account references
+    ///   0. [WRITE] Params account
+    ///   1. [] Clock sysvar
+    ///   2..2+M. [SIGNER] M of the authority's signers
+    SetGroupSize(u64),
+
+    /// Set the walk groups are picked with
+    ///
+    /// # Account references
+    ///   0. [WRITE] Params account
+    ///   1. [] Clock sysvar
+    ///   2..2+M. [SIGNER] M of the authority's signers
+    SetAlgorithmVersion(SelectionAlgorithm),
+
+    /// Set the share of a group whose votes make a quorum
+    ///
+    /// # Account references
+    ///   0. [WRITE] Params account
+    ///   1. [] Clock sysvar
+    ///   2..2+M. [SIGNER] M of the authority's signers
+    SetQuorumFraction(QuorumFraction),
+
+    /// Leave a vote account out of the groups or take it back in
+    ///
+    /// # Account references
+    ///   0. [WRITE] Params account
+    ///   1. [] Clock sysvar
+    ///   2..2+M. [SIGNER] M of the authority's signers
+    SetExclusion {
+        vote_account: Pubkey,
+        excluded: bool,
+    },
+}
+
+fn admin_instruction(signers: &[Pubkey], instruction: &VoteGroupAdminInstruction) -> Instruction {
+    let mut account_metas = vec![
+        AccountMeta::new(params::id(), false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+    ];
+    for signer in signers {
+        account_metas.push(AccountMeta::new_readonly(*signer, true));
+    }
+    Instruction::new_with_bincode(id(), instruction, account_metas)
+}
+
+pub fn set_group_size(signers: &[Pubkey], group_size: u64) -> Instruction {
+    admin_instruction(
+        signers,
+        &VoteGroupAdminInstruction::SetGroupSize(group_size),
+    )
+}
+
+pub fn set_algorithm_version(signers: &[Pubkey], algorithm: SelectionAlgorithm) -> Instruction {
+    admin_instruction(
+        signers,
+        &VoteGroupAdminInstruction::SetAlgorithmVersion(algorithm),
+    )
+}
+
+pub fn set_quorum_fraction(signers: &[Pubkey], quorum_fraction: QuorumFraction) -> Instruction {
+    admin_instruction(
+        signers,
+        &VoteGroupAdminInstruction::SetQuorumFraction(quorum_fraction),
+    )
+}
+
+pub fn set_exclusion(signers: &[Pubkey], vote_account: &Pubkey, excluded: bool) -> Instruction {
+    admin_instruction(
+        signers,
+        &VoteGroupAdminInstruction::SetExclusion {
+            vote_account: *vote_account,
+            excluded,
+        },
+    )
+}
+
+/// the params account, sized for AdminState::size_of whatever `state` holds
+pub fn create_account(lamports: u64, state: &AdminState) -> AccountSharedData {
+    let mut data = bincode::serialize(state).unwrap();
+    data.resize(AdminState::size_of().max(data.len()), 0);
+    AccountSharedData::from(Account {
+        lamports,
+        data,
+        owner: id(),
+        ..Account::default()
+    })
+}
+
+/// the params account starts out with nothing set but the legacy exclusions
+pub fn add_genesis_account(
+    genesis_config: &mut GenesisConfig,
+    authority: MultisigAuthority,
+) -> u64 {
+    let mut account = create_account(0, &AdminState::new(authority));
+    let lamports = genesis_config.rent.minimum_balance(account.data().len());
+
+    account.lamports = lamports.max(1);
+
+    genesis_config.add_account(params::id(), account);
+
+    lamports
+}
+
+pub fn process_instruction(
+    _program_id: &Pubkey,
+    keyed_accounts: &[KeyedAccount],
+    data: &[u8],
+    _invoke_context: &mut dyn InvokeContext,
+) -> Result<(), InstructionError> {
+    trace!("process_instruction: {:?}", data);
+    trace!("keyed_accounts: {:?}", keyed_accounts);
+
+    let signers: HashSet<Pubkey> = get_signers(keyed_accounts);
+
+    let keyed_accounts = &mut keyed_accounts.iter();
+    let me = next_keyed_account(keyed_accounts)?;
+    if me.owner()? != id() {
+        return Err(InstructionError::InvalidAccountOwner);
+    }
+    if !params::check_id(me.unsigned_key()) {
+        return Err(InstructionError::InvalidArgument);
+    }
+    let clock = from_keyed_account::<Clock>(next_keyed_account(keyed_accounts)?)?;
+
+    let instruction: VoteGroupAdminInstruction = limited_deserialize(data)?;
+    let mut state: AdminState = bincode::deserialize(me.try_account_ref()?.data())
+        .map_err(|_| InstructionError::InvalidAccountData)?;
+    if !state.authority.is_authorized(&signers) {
+        return Err(VoteGroupAdminError::NotEnoughSigners.into());
+    }
+
+    let params = state.stage(clock.epoch);
+    match instruction {
+        VoteGroupAdminInstruction::SetGroupSize(group_size) => {
+            if group_size == 0 {
+                return Err(VoteGroupAdminError::ZeroGroupSize.into());
+            }
+            params.group_size = Some(group_size);
+        }
+        VoteGroupAdminInstruction::SetAlgorithmVersion(algorithm) => {
+            params.algorithm = Some(algorithm);
+        }
+        VoteGroupAdminInstruction::SetQuorumFraction(quorum_fraction) => {
+            if !quorum_fraction.is_valid() {
+                return Err(VoteGroupAdminError::InvalidQuorumFraction.into());
+            }
+            params.quorum_fraction = Some(quorum_fraction);
+        }
+        VoteGroupAdminInstruction::SetExclusion {
+            vote_account,
+            excluded,
+        } => {
+            if excluded {
+                if params.exclusions.len() >= MAX_EXCLUDED_VOTERS
+                    && !params.exclusions.contains(&vote_account)
+                {
+                    return Err(VoteGroupAdminError::TooManyExclusions.into());
+                }
+                params.exclusions.add(vote_account);
+            } else {
+                params.exclusions.remove(&vote_account);
+            }
+        }
+    }
+
+    let serialized =
+        bincode::serialize(&state).map_err(|_| InstructionError::InvalidAccountData)?;
+    let mut account = me.try_account_ref_mut()?;
+    if account.data().len() < serialized.len() {
+        return Err(InstructionError::AccountDataTooSmall);
+    }
+    account.data_as_mut_slice()[..serialized.len()].copy_from_slice(&serialized);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::{
+        account, keyed_account::create_keyed_is_signer_accounts,
+        process_instruction::MockInvokeContext,
+    };
+    use std::cell::RefCell;
+
+    struct Setup {
+        signers: Vec<Pubkey>,
+        params_account: RefCell<AccountSharedData>,
+    }
+
+    impl Setup {
+        /// 2 of 3 signers
+        fn new() -> Self {
+            let signers: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+            let authority = MultisigAuthority::new(signers.clone(), 2);
+            assert!(authority.is_valid());
+            Self {
+                signers,
+                params_account: RefCell::new(create_account(1, &AdminState::new(authority))),
+            }
+        }
+
+        fn state(&self) -> AdminState {
+            AdminState::from(&*self.params_account.borrow()).unwrap()
+        }
+
+        fn process(&self, instruction: &Instruction, epoch: Epoch) -> Result<(), InstructionError> {
+            let clock_account =
+                RefCell::new(account::create_account_shared_data_for_test(&Clock {
+                    epoch,
+                    ..Clock::default()
+                }));
+            let signer_accounts: Vec<RefCell<AccountSharedData>> = instruction.accounts[2..]
+                .iter()
+                .map(|_| RefCell::new(AccountSharedData::default()))
+                .collect();
+            let mut accounts = vec![
+                (&instruction.accounts[0].pubkey, false, &self.params_account),
+                (&instruction.accounts[1].pubkey, false, &clock_account),
+            ];
+            for (meta, account) in instruction.accounts[2..].iter().zip(&signer_accounts) {
+                accounts.push((&meta.pubkey, meta.is_signer, account));
+            }
+            let keyed_accounts = create_keyed_is_signer_accounts(&accounts);
+            process_instruction(
+                &id(),
+                &keyed_accounts,
+                &instruction.data,
+                &mut MockInvokeContext::default(),
+            )
+        }
+    }
+
+    #[test]
+    fn test_multisig_authority() {
+        let keys: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        let authority = MultisigAuthority::new(keys.clone(), 2);
+        assert!(authority.is_valid());
+        assert!(!authority.is_authorized(&vec![keys[0]].into_iter().collect()));
+        assert!(!authority.is_authorized(&vec![keys[0], keys[0]].into_iter().collect()));
+        assert!(authority.is_authorized(&vec![keys[0], keys[2]].into_iter().collect()));
+        assert!(
+            !authority.is_authorized(&vec![keys[0], Pubkey::new_unique()].into_iter().collect())
+        );
+
+        assert!(!MultisigAuthority::new(keys.clone(), 0).is_valid());
+        assert!(!MultisigAuthority::new(keys.clone(), 4).is_valid());
+        assert!(!MultisigAuthority::new(vec![keys[0], keys[0]], 1).is_valid());
+        assert!(!MultisigAuthority::new(
+            (0..=MAX_ADMIN_SIGNERS)
+                .map(|_| Pubkey::new_unique())
+                .collect(),
+            1
+        )
+        .is_valid());
+        assert!(!MultisigAuthority::default().is_authorized(&HashSet::new()));
+    }
+
+    #[test]
+    fn test_set_group_size() {
+        let setup = Setup::new();
+        let signers = &setup.signers;
+        assert_eq!(setup.process(&set_group_size(&signers[..2], 21), 5), Ok(()));
+        let state = setup.state();
+        // the change waits for the next epoch
+        assert_eq!(state.effective_epoch, 6);
+        assert_eq!(state.params_at(5).group_size, None);
+        assert_eq!(state.params_at(6).group_size, Some(21));
+
+        // later changes in the same epoch pile onto the staged params
+        assert_eq!(setup.process(&set_group_size(&signers[1..], 30), 5), Ok(()));
+        let state = setup.state();
+        assert_eq!(state.params_at(5).group_size, None);
+        assert_eq!(state.params_at(6).group_size, Some(30));
+
+        // a change in a later epoch starts from what is in effect by then
+        assert_eq!(setup.process(&set_group_size(&signers[..2], 40), 9), Ok(()));
+        let state = setup.state();
+        assert_eq!(state.effective_epoch, 10);
+        assert_eq!(state.params_at(9).group_size, Some(30));
+        assert_eq!(state.params_at(10).group_size, Some(40));
+
+        assert_eq!(
+            setup.process(&set_group_size(&signers[..2], 0), 9),
+            Err(VoteGroupAdminError::ZeroGroupSize.into())
+        );
+    }
+
+    #[test]
+    fn test_set_algorithm_version() {
+        let setup = Setup::new();
+        assert_eq!(
+            setup.process(
+                &set_algorithm_version(&setup.signers, SelectionAlgorithm::V3),
+                0
+            ),
+            Ok(())
+        );
+        let state = setup.state();
+        assert_eq!(state.params_at(0).algorithm, None);
+        assert_eq!(state.params_at(1).algorithm, Some(SelectionAlgorithm::V3));
+    }
+
+    #[test]
+    fn test_set_quorum_fraction() {
+        let setup = Setup::new();
+        let three_quarters = QuorumFraction {
+            numerator: 3,
+            denominator: 4,
+        };
+        assert_eq!(
+            setup.process(&set_quorum_fraction(&setup.signers[..2], three_quarters), 2),
+            Ok(())
+        );
+        assert_eq!(
+            setup.state().params_at(3).quorum_fraction,
+            Some(three_quarters)
+        );
+
+        for (numerator, denominator) in &[(1, 3), (4, 4), (1, 0)] {
+            let fraction = QuorumFraction {
+                numerator: *numerator,
+                denominator: *denominator,
+            };
+            assert_eq!(
+                setup.process(&set_quorum_fraction(&setup.signers[..2], fraction), 2),
+                Err(VoteGroupAdminError::InvalidQuorumFraction.into())
+            );
+        }
+        assert_eq!(
+            setup.state().params_at(3).quorum_fraction,
+            Some(three_quarters)
+        );
+    }
+
+    #[test]
+    fn test_set_exclusion() {
+        let setup = Setup::new();
+        let vote_account = Pubkey::new_unique();
+        let signers = &setup.signers[..2];
+        assert_eq!(
+            setup.process(&set_exclusion(signers, &vote_account, true), 0),
+            Ok(())
+        );
+        let state = setup.state();
+        assert!(!state.params_at(0).exclusions.contains(&vote_account));
+        assert!(state.params_at(1).exclusions.contains(&vote_account));
+        // the legacy list stays unless it is taken out
+        assert_eq!(state.params_at(1).exclusions.len(), 2);
+
+        assert_eq!(
+            setup.process(&set_exclusion(signers, &vote_account, false), 1),
+            Ok(())
+        );
+        let state = setup.state();
+        assert!(state.params_at(1).exclusions.contains(&vote_account));
+        assert!(!state.params_at(2).exclusions.contains(&vote_account));
+
+        // the list fills up at MAX_EXCLUDED_VOTERS
+        let remaining = MAX_EXCLUDED_VOTERS - state.params_at(2).exclusions.len();
+        for _ in 0..remaining {
+            assert_eq!(
+                setup.process(&set_exclusion(signers, &Pubkey::new_unique(), true), 2),
+                Ok(())
+            );
+        }
+        assert_eq!(
+            setup.process(&set_exclusion(signers, &vote_account, true), 2),
+            Err(VoteGroupAdminError::TooManyExclusions.into())
+        );
+        let excluded = *setup.state().params_at(3).exclusions.iter().next().unwrap();
+        assert_eq!(
+            setup.process(&set_exclusion(signers, &excluded, true), 2),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_authorization() {
+        let setup = Setup::new();
+        let before = setup.state();
+        assert_eq!(
+            setup.process(&set_group_size(&setup.signers[..1], 21), 0),
+            Err(VoteGroupAdminError::NotEnoughSigners.into())
+        );
+        let outsider = Pubkey::new_unique();
+        assert_eq!(
+            setup.process(&set_group_size(&[setup.signers[0], outsider], 21), 0),
+            Err(VoteGroupAdminError::NotEnoughSigners.into())
+        );
+
+        // signer keys listed without signing don't count
+        let mut instruction = set_group_size(&setup.signers[..2], 21);
+        instruction.accounts[3].is_signer = false;
+        assert_eq!(
+            setup.process(&instruction, 0),
+            Err(VoteGroupAdminError::NotEnoughSigners.into())
+        );
+        assert_eq!(setup.state(), before);
+    }
+
+    #[test]
+    fn test_bad_accounts() {
+        let setup = Setup::new();
+        let instruction = set_group_size(&setup.signers, 21);
+
+        // only the params account, owned by the program
+        let mut wrong_key = instruction.clone();
+        wrong_key.accounts[0].pubkey = Pubkey::new_unique();
+        assert_eq!(
+            setup.process(&wrong_key, 0),
+            Err(InstructionError::InvalidArgument)
+        );
+        setup
+            .params_account
+            .borrow_mut()
+            .set_owner(Pubkey::new_unique());
+        assert_eq!(
+            setup.process(&instruction, 0),
+            Err(InstructionError::InvalidAccountOwner)
+        );
+        setup.params_account.borrow_mut().set_owner(id());
+
+        let mut wrong_clock = instruction.clone();
+        wrong_clock.accounts[1].pubkey = Pubkey::new_unique();
+        assert_eq!(
+            setup.process(&wrong_clock, 0),
+            Err(InstructionError::InvalidArgument)
+        );
+
+        let mut garbage = instruction;
+        garbage.data = vec![9; 4];
+        assert_eq!(
+            setup.process(&garbage, 0),
+            Err(InstructionError::InvalidInstructionData)
+        );
+    }
+
+    #[test]
+    fn test_genesis_account() {
+        let mut genesis_config = GenesisConfig::default();
+        let authority = MultisigAuthority::new(vec![Pubkey::new_unique()], 1);
+        let lamports = add_genesis_account(&mut genesis_config, authority.clone());
+        let account = &genesis_config.accounts[&params::id()];
+        assert_eq!(account.lamports, lamports);
+        assert_eq!(account.owner, id());
+        assert_eq!(account.data.len(), AdminState::size_of());
+        let state = AdminState::from(account).unwrap();
+        assert_eq!(state, AdminState::new(authority));
+        assert_eq!(state.params_at(0), &GroupParams::default());
+    }
+}
@@ -0,0 +1,319 @@
+//! certificates for the votes of a slot's group
+//!  every member of the group signs the same short message naming the slot and the hash it
+//!  voted on, so the group's votes fold into one certificate holding a signature per member
+//!  instead of a vote transaction per member. with the small fixed size groups that is a
+//!  few kilobytes for the whole group and one signature check per member, no transactions
+use serde_derive::{Deserialize, Serialize};
+use solana_sdk::{
+    clock::Slot,
+    hash::{hashv, Hash},
+    pubkey::Pubkey,
+    signature::{Signature, Signer},
+    vote_group_gen::VoteGroupGenerator,
+};
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+/// domain separation for the message group members sign, it can never double as a
+/// transaction message or any other signed payload
+pub const VOTE_GROUP_CERTIFICATE_DOMAIN: &[u8] = b"safecoin-vote-group-certificate";
+
+/// the message every member of `slot`'s group signs for a vote on `hash`
+pub fn certificate_message(slot: Slot, hash: &Hash) -> Hash {
+    hashv(&[
+        VOTE_GROUP_CERTIFICATE_DOMAIN,
+        &slot.to_le_bytes(),
+        hash.as_ref(),
+    ])
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum CertificateError {
+    #[error("vote account {0} is not in the group for the slot")]
+    NotInGroup(Pubkey),
+
+    #[error("the signature of vote account {0} does not verify")]
+    BadSignature(Pubkey),
+
+    #[error("vote account {0} is not signed for by its authorized voter")]
+    WrongAuthorizedVoter(Pubkey),
+
+    #[error("the signatures are not ordered by vote account or repeat one")]
+    Unordered,
+}
+
+/// one member's vote, signed by the vote account's authorized voter
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GroupVoteSignature {
+    pub vote_account: Pubkey,
+    pub authorized_voter: Pubkey,
+    pub signature: Signature,
+}
+
+impl GroupVoteSignature {
+    pub fn new<T: Signer + ?Sized>(
+        vote_account: Pubkey,
+        authorized_voter: &T,
+        slot: Slot,
+        hash: &Hash,
+    ) -> Self {
+        Self {
+            vote_account,
+            authorized_voter: authorized_voter.pubkey(),
+            signature: authorized_voter.sign_message(certificate_message(slot, hash).as_ref()),
+        }
+    }
+
+    pub fn verify(&self, slot: Slot, hash: &Hash) -> bool {
+        self.signature.verify(
+            self.authorized_voter.as_ref(),
+            certificate_message(slot, hash).as_ref(),
+        )
+    }
+}
+
+/// the votes of a slot's group on one hash
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct VoteGroupCertificate {
+    pub slot: Slot,
+    pub hash: Hash,
+    /// one per member that voted, ordered by vote account
+    pub signatures: Vec<GroupVoteSignature>,
+}
+
+impl VoteGroupCertificate {
+    pub fn vote_accounts(&self) -> Vec<Pubkey> {
+        self.signatures
+            .iter()
+            .map(|signature| signature.vote_account)
+            .collect()
+    }
+
+    /// checks that every signer is in the group `generator` picks for `seed`, is signed for
+    /// by the authorized voter `authorized_voter` returns for it and signed this slot and hash
+    pub fn verify<F>(
+        &self,
+        generator: &VoteGroupGenerator,
+        seed: u64,
+        authorized_voter: F,
+    ) -> Result<(), CertificateError>
+    where
+        F: Fn(&Pubkey) -> Option<Pubkey>,
+    {
+        if self
+            .signatures
+            .windows(2)
+            .any(|pair| pair[0].vote_account >= pair[1].vote_account)
+        {
+            return Err(CertificateError::Unordered);
+        }
+        for signature in &self.signatures {
+            if !generator
+                .try_in_group_for_seed(seed, signature.vote_account)
+                .unwrap_or(false)
+            {
+                return Err(CertificateError::NotInGroup(signature.vote_account));
+            }
+            if authorized_voter(&signature.vote_account) != Some(signature.authorized_voter) {
+                return Err(CertificateError::WrongAuthorizedVoter(
+                    signature.vote_account,
+                ));
+            }
+            if !signature.verify(self.slot, &self.hash) {
+                return Err(CertificateError::BadSignature(signature.vote_account));
+            }
+        }
+        Ok(())
+    }
+
+    /// whether the signers make up a quorum of the group, see VoteGroupGenerator::has_quorum
+    pub fn has_quorum(&self, generator: &VoteGroupGenerator, seed: u64) -> bool {
+        generator.has_quorum(&self.vote_accounts(), seed)
+    }
+}
+
+/// collects the signatures of a group's members for one slot and hash as they come in
+pub struct VoteAggregator<'a> {
+    generator: &'a VoteGroupGenerator,
+    seed: u64,
+    slot: Slot,
+    hash: Hash,
+    signatures: BTreeMap<Pubkey, GroupVoteSignature>,
+}
+
+impl<'a> VoteAggregator<'a> {
+    pub fn new(generator: &'a VoteGroupGenerator, seed: u64, slot: Slot, hash: Hash) -> Self {
+        Self {
+            generator,
+            seed,
+            slot,
+            hash,
+            signatures: BTreeMap::new(),
+        }
+    }
+
+    /// adds a member's signature, Ok(false) when the member's vote is already in. whether the
+    /// authorized voter is the vote account's is left to whoever verifies the certificate
+    pub fn add(&mut self, signature: GroupVoteSignature) -> Result<bool, CertificateError> {
+        if !self
+            .generator
+            .try_in_group_for_seed(self.seed, signature.vote_account)
+            .unwrap_or(false)
+        {
+            return Err(CertificateError::NotInGroup(signature.vote_account));
+        }
+        if !signature.verify(self.slot, &self.hash) {
+            return Err(CertificateError::BadSignature(signature.vote_account));
+        }
+        if self.signatures.contains_key(&signature.vote_account) {
+            return Ok(false);
+        }
+        self.signatures.insert(signature.vote_account, signature);
+        Ok(true)
+    }
+
+    pub fn len(&self) -> usize {
+        self.signatures.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.signatures.is_empty()
+    }
+
+    pub fn has_quorum(&self) -> bool {
+        let votes: Vec<Pubkey> = self.signatures.keys().copied().collect();
+        self.generator.has_quorum(&votes, self.seed)
+    }
+
+    /// every signature collected so far
+    pub fn certificate(&self) -> VoteGroupCertificate {
+        VoteGroupCertificate {
+            slot: self.slot,
+            hash: self.hash,
+            signatures: self.signatures.values().copied().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::signature::Keypair;
+    use std::collections::HashMap;
+
+    fn setup(voters: usize, group_size: usize) -> (VoteGroupGenerator, HashMap<Pubkey, Keypair>) {
+        let keypairs: HashMap<Pubkey, Keypair> = (0..voters)
+            .map(|_| (solana_sdk::pubkey::new_rand(), Keypair::new()))
+            .collect();
+        let map: HashMap<Pubkey, Pubkey> = keypairs.keys().map(|key| (*key, *key)).collect();
+        (VoteGroupGenerator::new(&map, group_size), keypairs)
+    }
+
+    #[test]
+    fn test_vote_aggregator() {
+        let (generator, keypairs) = setup(20, 7);
+        let (seed, slot, hash) = (3, 42, Hash::new_unique());
+        let group = generator.group_for_seed(seed);
+        let mut aggregator = VoteAggregator::new(&generator, seed, slot, hash);
+        assert!(aggregator.is_empty());
+
+        for (i, member) in group.iter().enumerate() {
+            let signature = GroupVoteSignature::new(*member, &keypairs[member], slot, &hash);
+            assert_eq!(aggregator.add(signature), Ok(true));
+            assert_eq!(aggregator.add(signature), Ok(false));
+            assert_eq!(aggregator.len(), i + 1);
+        }
+        assert!(aggregator.has_quorum());
+
+        let outsider = keypairs.keys().find(|key| !group.contains(key)).unwrap();
+        assert_eq!(
+            aggregator.add(GroupVoteSignature::new(
+                *outsider,
+                &keypairs[outsider],
+                slot,
+                &hash
+            )),
+            Err(CertificateError::NotInGroup(*outsider))
+        );
+        // a signature over another hash doesn't count for this one
+        assert_eq!(
+            aggregator.add(GroupVoteSignature::new(
+                group[0],
+                &keypairs[&group[0]],
+                slot,
+                &Hash::new_unique()
+            )),
+            Err(CertificateError::BadSignature(group[0]))
+        );
+
+        let certificate = aggregator.certificate();
+        assert_eq!(certificate.signatures.len(), group.len());
+        let authorized_voter = |key: &Pubkey| keypairs.get(key).map(|keypair| keypair.pubkey());
+        assert_eq!(
+            certificate.verify(&generator, seed, authorized_voter),
+            Ok(())
+        );
+        assert!(certificate.has_quorum(&generator, seed));
+
+        // a signature and two keys per member on top of the slot and hash
+        let bytes = bincode::serialize(&certificate).unwrap();
+        assert_eq!(bytes.len(), 48 + 128 * group.len());
+        assert_eq!(
+            bincode::deserialize::<VoteGroupCertificate>(&bytes).unwrap(),
+            certificate
+        );
+    }
+
+    #[test]
+    fn test_vote_group_certificate_verify() {
+        let (generator, keypairs) = setup(10, 5);
+        let (seed, slot, hash) = (8, 7, Hash::new_unique());
+        let group = generator.group_for_seed(seed);
+        let mut aggregator = VoteAggregator::new(&generator, seed, slot, hash);
+        for member in &group {
+            aggregator
+                .add(GroupVoteSignature::new(
+                    *member,
+                    &keypairs[member],
+                    slot,
+                    &hash,
+                ))
+                .unwrap();
+        }
+        let certificate = aggregator.certificate();
+        let authorized_voter = |key: &Pubkey| keypairs.get(key).map(|keypair| keypair.pubkey());
+
+        let mut wrong_slot = certificate.clone();
+        wrong_slot.slot += 1;
+        assert!(matches!(
+            wrong_slot.verify(&generator, seed, authorized_voter),
+            Err(CertificateError::BadSignature(_))
+        ));
+
+        let mut unordered = certificate.clone();
+        unordered.signatures.reverse();
+        assert_eq!(
+            unordered.verify(&generator, seed, authorized_voter),
+            Err(CertificateError::Unordered)
+        );
+
+        let first = certificate.signatures[0].vote_account;
+        assert_eq!(
+            certificate.verify(&generator, seed, |_| Some(Pubkey::default())),
+            Err(CertificateError::WrongAuthorizedVoter(first))
+        );
+
+        let mut stolen = certificate.clone();
+        let imposter = Keypair::new();
+        stolen.signatures[0] = GroupVoteSignature::new(first, &imposter, slot, &hash);
+        assert_eq!(
+            stolen.verify(&generator, seed, authorized_voter),
+            Err(CertificateError::WrongAuthorizedVoter(first))
+        );
+
+        let mut partial = certificate;
+        partial.signatures.truncate(1);
+        assert_eq!(partial.verify(&generator, seed, authorized_voter), Ok(()));
+        assert!(!partial.has_quorum(&generator, seed));
+    }
+}
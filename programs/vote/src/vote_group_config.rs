@@ -0,0 +1,244 @@
+//! config for vote groups
+//!  carries the vote group parameters the cluster can tune without a release,
+//!  the bank samples it whenever it captures the stakes of a new epoch
+use bincode::{deserialize, serialized_size};
+use serde_derive::{Deserialize, Serialize};
+use solana_config_program::{
+    config_instruction, create_config_account, get_config_data, ConfigState,
+};
+use solana_sdk::{
+    account::{AccountSharedData, ReadableAccount},
+    genesis_config::GenesisConfig,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    vote_group_gen::{
+        DynamicGroupSize, DEFAULT_OUT_OF_GROUP_VOTE_THRESHOLD, DEFAULT_ROTATION_WINDOW_SLOTS,
+        OPTIMAL_VOTE_GROUP_SIZE,
+    },
+};
+
+// vote group config ID
+pub use solana_sdk::vote::group_config::{check_id, id};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
+pub struct Config {
+    /// how many voters are selected for each slot, clamped to the number of voters
+    pub group_size: u64,
+    /// out of group votes in an epoch before a vote account is left out of later groups
+    pub out_of_group_vote_threshold: u64,
+    /// size the groups as this share of the epoch's eligible voters instead, 0 keeps
+    /// group_size
+    pub group_size_percent: u8,
+    /// bounds of a percentage based group size
+    pub min_group_size: u64,
+    pub max_group_size: u64,
+    /// slots a group lasts before the next one is selected, 0 and 1 select one every slot
+    pub rotation_window_slots: u64,
+}
+
+/// the config accounts were created with before groups could last more than a slot
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Clone, Copy)]
+struct PercentConfig {
+    group_size: u64,
+    out_of_group_vote_threshold: u64,
+    group_size_percent: u8,
+    min_group_size: u64,
+    max_group_size: u64,
+}
+
+impl From<PercentConfig> for Config {
+    fn from(percent: PercentConfig) -> Self {
+        Self {
+            group_size: percent.group_size,
+            out_of_group_vote_threshold: percent.out_of_group_vote_threshold,
+            group_size_percent: percent.group_size_percent,
+            min_group_size: percent.min_group_size,
+            max_group_size: percent.max_group_size,
+            ..Self::default()
+        }
+    }
+}
+
+/// the config accounts were created with before groups could be sized by percentage, their
+/// data is too short to hold the new fields
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Clone, Copy)]
+struct LegacyConfig {
+    group_size: u64,
+    out_of_group_vote_threshold: u64,
+}
+
+impl From<LegacyConfig> for Config {
+    fn from(legacy: LegacyConfig) -> Self {
+        Self {
+            group_size: legacy.group_size,
+            out_of_group_vote_threshold: legacy.out_of_group_vote_threshold,
+            ..Self::default()
+        }
+    }
+}
+
+impl Config {
+    pub fn from<T: ReadableAccount>(account: &T) -> Option<Self> {
+        let data = get_config_data(&account.data()).ok()?;
+        deserialize(data)
+            .ok()
+            .or_else(|| deserialize::<PercentConfig>(data).ok().map(Into::into))
+            .or_else(|| deserialize::<LegacyConfig>(data).ok().map(Into::into))
+    }
+
+    /// the percentage based size when one is configured
+    pub fn dynamic_group_size(&self) -> Option<DynamicGroupSize> {
+        if self.group_size_percent == 0 {
+            return None;
+        }
+        Some(DynamicGroupSize {
+            percent: self.group_size_percent,
+            min: self.min_group_size as usize,
+            max: self.max_group_size as usize,
+        })
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            group_size: OPTIMAL_VOTE_GROUP_SIZE as u64,
+            out_of_group_vote_threshold: DEFAULT_OUT_OF_GROUP_VOTE_THRESHOLD,
+            group_size_percent: 0,
+            min_group_size: OPTIMAL_VOTE_GROUP_SIZE as u64,
+            max_group_size: OPTIMAL_VOTE_GROUP_SIZE as u64,
+            rotation_window_slots: DEFAULT_ROTATION_WINDOW_SLOTS,
+        }
+    }
+}
+
+impl ConfigState for Config {
+    fn max_space() -> u64 {
+        serialized_size(&Config::default()).unwrap()
+    }
+}
+
+/// the account is only writable by `authority`, without one the defaults stay fixed
+pub fn add_genesis_account(genesis_config: &mut GenesisConfig, authority: &Pubkey) -> u64 {
+    let mut account = create_account(0, authority, &Config::default());
+    let lamports = genesis_config.rent.minimum_balance(account.data().len());
+
+    account.lamports = lamports.max(1);
+
+    genesis_config.add_account(id(), account);
+
+    lamports
+}
+
+pub fn create_account(lamports: u64, authority: &Pubkey, config: &Config) -> AccountSharedData {
+    create_config_account(vec![(*authority, true)], config, lamports)
+}
+
+/// stores a new config, takes effect for the first epoch whose stakes are captured afterwards
+pub fn update(authority: &Pubkey, config: &Config) -> Instruction {
+    config_instruction::store(&id(), false, vec![(*authority, true)], config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() {
+        let authority = Pubkey::new_unique();
+        let config = Config {
+            group_size: 21,
+            out_of_group_vote_threshold: 3,
+            ..Config::default()
+        };
+        let account = create_account(1, &authority, &config);
+        assert_eq!(Config::from(&account), Some(config));
+        assert_eq!(Config::from(&AccountSharedData::default()), None);
+        assert_eq!(config.dynamic_group_size(), None);
+
+        let instruction = update(&authority, &config);
+        assert_eq!(instruction.accounts[0].pubkey, id());
+        assert!(!instruction.accounts[0].is_signer);
+        assert_eq!(instruction.accounts[1].pubkey, authority);
+        assert!(instruction.accounts[1].is_signer);
+    }
+
+    impl ConfigState for LegacyConfig {
+        fn max_space() -> u64 {
+            serialized_size(&LegacyConfig::from(&Config::default())).unwrap()
+        }
+    }
+
+    impl ConfigState for PercentConfig {
+        fn max_space() -> u64 {
+            serialized_size(&PercentConfig::default()).unwrap()
+        }
+    }
+
+    impl From<&Config> for LegacyConfig {
+        fn from(config: &Config) -> Self {
+            Self {
+                group_size: config.group_size,
+                out_of_group_vote_threshold: config.out_of_group_vote_threshold,
+            }
+        }
+    }
+
+    #[test]
+    fn test_legacy_config() {
+        let legacy = LegacyConfig {
+            group_size: 21,
+            out_of_group_vote_threshold: 3,
+        };
+        let account = create_config_account(vec![(Pubkey::new_unique(), true)], &legacy, 1);
+        assert_eq!(
+            Config::from(&account),
+            Some(Config {
+                group_size: 21,
+                out_of_group_vote_threshold: 3,
+                ..Config::default()
+            })
+        );
+    }
+
+    #[test]
+    fn test_percent_config() {
+        let percent = PercentConfig {
+            group_size: 21,
+            out_of_group_vote_threshold: 3,
+            group_size_percent: 5,
+            min_group_size: 11,
+            max_group_size: 200,
+        };
+        let account = create_config_account(vec![(Pubkey::new_unique(), true)], &percent, 1);
+        assert_eq!(
+            Config::from(&account),
+            Some(Config {
+                group_size: 21,
+                out_of_group_vote_threshold: 3,
+                group_size_percent: 5,
+                min_group_size: 11,
+                max_group_size: 200,
+                rotation_window_slots: DEFAULT_ROTATION_WINDOW_SLOTS,
+            })
+        );
+    }
+
+    #[test]
+    fn test_dynamic_group_size() {
+        let config = Config {
+            group_size_percent: 5,
+            min_group_size: 11,
+            max_group_size: 200,
+            ..Config::default()
+        };
+        assert_eq!(
+            config.dynamic_group_size(),
+            Some(DynamicGroupSize {
+                percent: 5,
+                min: 11,
+                max: 200,
+            })
+        );
+    }
+}
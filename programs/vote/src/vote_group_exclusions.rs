@@ -0,0 +1,105 @@
+//! registry of the vote accounts that are never picked for a vote group
+//!  a config account holding the exclusion list, so leaving a voter out of the groups is a
+//!  store by the registry's authority instead of a release. the bank reads it whenever it
+//!  captures the stakes of a new epoch, without the account it keeps ExclusionList::legacy()
+use bincode::serialized_size;
+use serde_derive::{Deserialize, Serialize};
+use solana_config_program::{
+    config_instruction, create_config_account, get_config_data, ConfigState,
+};
+use solana_sdk::{
+    account::{AccountSharedData, ReadableAccount},
+    genesis_config::GenesisConfig,
+    instruction::Instruction,
+    pubkey::Pubkey,
+    vote_group_gen::ExclusionList,
+};
+
+// vote group exclusions ID
+pub use solana_sdk::vote::group_exclusions::{check_id, id};
+
+/// how many vote accounts the registry account has room for
+pub const MAX_EXCLUDED_VOTERS: usize = 128;
+
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Clone)]
+pub struct Registry {
+    pub excluded: ExclusionList,
+}
+
+impl Registry {
+    pub fn from<T: ReadableAccount>(account: &T) -> Option<Self> {
+        let data = get_config_data(&account.data()).ok()?;
+        bincode::deserialize(data).ok()
+    }
+}
+
+impl ConfigState for Registry {
+    fn max_space() -> u64 {
+        serialized_size(&Registry::default()).unwrap()
+            + (MAX_EXCLUDED_VOTERS * std::mem::size_of::<Pubkey>()) as u64
+    }
+}
+
+/// seeds the registry with the legacy list, the account is only writable by `authority`
+pub fn add_genesis_account(genesis_config: &mut GenesisConfig, authority: &Pubkey) -> u64 {
+    let mut account = create_account(
+        0,
+        authority,
+        &Registry {
+            excluded: ExclusionList::legacy(),
+        },
+    );
+    let lamports = genesis_config.rent.minimum_balance(account.data().len());
+
+    account.lamports = lamports.max(1);
+
+    genesis_config.add_account(id(), account);
+
+    lamports
+}
+
+/// the account is sized for MAX_EXCLUDED_VOTERS whatever `registry` holds, later stores
+/// can only grow the list that far
+pub fn create_account(lamports: u64, authority: &Pubkey, registry: &Registry) -> AccountSharedData {
+    let mut account = create_config_account(vec![(*authority, true)], registry, lamports);
+    let keys_len = account.data().len() - serialized_size(registry).unwrap() as usize;
+    let space = keys_len + Registry::max_space() as usize;
+    let mut data = account.data().to_vec();
+    data.resize(space, 0);
+    account.set_data(data);
+    account
+}
+
+/// stores a new list, takes effect for the first epoch whose stakes are captured afterwards
+pub fn update(authority: &Pubkey, registry: &Registry) -> Instruction {
+    config_instruction::store(&id(), false, vec![(*authority, true)], registry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() {
+        let authority = Pubkey::new_unique();
+        let registry = Registry {
+            excluded: ExclusionList::new(vec![Pubkey::new_unique(), Pubkey::new_unique()]),
+        };
+        let account = create_account(1, &authority, &registry);
+        assert_eq!(Registry::from(&account), Some(registry.clone()));
+        assert_eq!(Registry::from(&AccountSharedData::default()), None);
+
+        // a full list still fits the account
+        let full = Registry {
+            excluded: ExclusionList::new((0..MAX_EXCLUDED_VOTERS).map(|_| Pubkey::new_unique())),
+        };
+        let full_len = create_account(1, &authority, &full).data().len();
+        assert_eq!(account.data().len(), full_len);
+
+        let instruction = update(&authority, &registry);
+        assert_eq!(instruction.accounts[0].pubkey, id());
+        assert!(!instruction.accounts[0].is_signer);
+        assert_eq!(instruction.accounts[1].pubkey, authority);
+        assert!(instruction.accounts[1].is_signer);
+    }
+}
@@ -10,16 +10,19 @@ use num_derive::{FromPrimitive, ToPrimitive};
 use serde_derive::{Deserialize, Serialize};
 use solana_metrics::inc_new_counter_info;
 use solana_sdk::{
+    clock::Slot,
     decode_error::DecodeError,
     feature_set,
     hash::Hash,
-    instruction::{AccountMeta, Instruction, InstructionError},
+    instruction::{AccountMeta, Instruction, InstructionError, VoterGroup},
     keyed_account::{from_keyed_account, get_signers, next_keyed_account, KeyedAccount},
     process_instruction::InvokeContext,
     program_utils::limited_deserialize,
     pubkey::Pubkey,
     system_instruction,
     sysvar::{self, clock::Clock, slot_hashes::SlotHashes},
+    vote_group_gen::{MembershipCommitment, MembershipReveal},
+    vrf::VrfProof,
 };
 use std::collections::HashSet;
 use thiserror::Error;
@@ -44,6 +47,15 @@ pub enum VoteError {
 
     #[error("authorized voter has already been changed this epoch")]
     TooSoonToReauthorize,
+
+    #[error("vote account is not in the vote group for the voted slot")]
+    NotInVoteGroup,
+
+    #[error("membership can only be committed to for the next epoch")]
+    MembershipCommitmentNotNextEpoch,
+
+    #[error("no room for the membership commitment in the vote account")]
+    MembershipCommitmentNoRoom,
 }
 
 impl<E> DecodeError<E> for VoteError {
@@ -123,6 +135,32 @@ pub enum VoteInstruction {
     ///   2. [SIGNER] Vote or withdraw authority
     ///   3. [SIGNER] New vote or withdraw authority
     AuthorizeChecked(VoteAuthorize),
+
+    /// A Vote instruction carrying the voter's VRF sortition proof for the voted slot
+    ///
+    /// # Account references
+    ///   0. [WRITE] Vote account to vote with
+    ///   1. [] Slot hashes sysvar
+    ///   2. [] Clock sysvar
+    ///   3. [SIGNER] Vote authority
+    VoteWithVrfProof(Vote, VrfProof),
+
+    /// Publish the vote account's commit-reveal membership commitment for the next epoch
+    ///
+    /// # Account references
+    ///   0. [WRITE] Vote account to commit for
+    ///   1. [] Clock sysvar
+    ///   2. [SIGNER] Vote authority
+    CommitGroupMembership(MembershipCommitment),
+
+    /// A Vote instruction revealing the voter's committed membership secret for the voted slot
+    ///
+    /// # Account references
+    ///   0. [WRITE] Vote account to vote with
+    ///   1. [] Slot hashes sysvar
+    ///   2. [] Clock sysvar
+    ///   3. [SIGNER] Vote authority
+    VoteWithMembershipReveal(Vote, MembershipReveal),
 }
 
 fn initialize_account(vote_pubkey: &Pubkey, vote_init: &VoteInit) -> Instruction {
@@ -260,6 +298,64 @@ pub fn vote(vote_pubkey: &Pubkey, authorized_voter_pubkey: &Pubkey, vote: Vote)
     Instruction::new_with_bincode(id(), &VoteInstruction::Vote(vote), account_metas)
 }
 
+pub fn vote_with_vrf_proof(
+    vote_pubkey: &Pubkey,
+    authorized_voter_pubkey: &Pubkey,
+    vote: Vote,
+    proof: VrfProof,
+) -> Instruction {
+    let account_metas = vec![
+        AccountMeta::new(*vote_pubkey, false),
+        AccountMeta::new_readonly(sysvar::slot_hashes::id(), false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(*authorized_voter_pubkey, true),
+    ];
+
+    Instruction::new_with_bincode(
+        id(),
+        &VoteInstruction::VoteWithVrfProof(vote, proof),
+        account_metas,
+    )
+}
+
+pub fn commit_group_membership(
+    vote_pubkey: &Pubkey,
+    authorized_voter_pubkey: &Pubkey,
+    commitment: MembershipCommitment,
+) -> Instruction {
+    let account_metas = vec![
+        AccountMeta::new(*vote_pubkey, false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(*authorized_voter_pubkey, true),
+    ];
+
+    Instruction::new_with_bincode(
+        id(),
+        &VoteInstruction::CommitGroupMembership(commitment),
+        account_metas,
+    )
+}
+
+pub fn vote_with_membership_reveal(
+    vote_pubkey: &Pubkey,
+    authorized_voter_pubkey: &Pubkey,
+    vote: Vote,
+    reveal: MembershipReveal,
+) -> Instruction {
+    let account_metas = vec![
+        AccountMeta::new(*vote_pubkey, false),
+        AccountMeta::new_readonly(sysvar::slot_hashes::id(), false),
+        AccountMeta::new_readonly(sysvar::clock::id(), false),
+        AccountMeta::new_readonly(*authorized_voter_pubkey, true),
+    ];
+
+    Instruction::new_with_bincode(
+        id(),
+        &VoteInstruction::VoteWithMembershipReveal(vote, reveal),
+        account_metas,
+    )
+}
+
 pub fn vote_switch(
     vote_pubkey: &Pubkey,
     authorized_voter_pubkey: &Pubkey,
@@ -307,6 +403,76 @@ fn verify_rent_exemption(
     }
 }
 
+/// the groups votes are checked against. every voter is accepted until limited voting is
+/// active, and in the epoch it activates in the outcome of the check is only recorded
+struct LimitedVoterGroup<'a> {
+    group: &'a dyn VoterGroup,
+    limited_voting: bool,
+    transition: bool,
+}
+
+impl<'a> LimitedVoterGroup<'a> {
+    fn new(invoke_context: &'a dyn InvokeContext) -> Self {
+        let group = invoke_context.voter_group();
+        let limited_voting = invoke_context.is_feature_active(&feature_set::limited_voting::id());
+        Self {
+            group,
+            limited_voting,
+            transition: limited_voting && group.accepts_out_of_group_votes(),
+        }
+    }
+
+    /// whether the outcome of the group check counts, credits and penalties wait for it
+    fn is_enforced(&self) -> bool {
+        self.limited_voting && !self.transition
+    }
+
+    fn check(
+        &self,
+        slot: Slot,
+        hash: Hash,
+        voter: Pubkey,
+        in_group: impl FnOnce() -> bool,
+    ) -> bool {
+        if !self.limited_voting {
+            return true;
+        }
+        let in_group = in_group();
+        if self.transition {
+            self.group
+                .record_transition_vote(slot, hash, voter, in_group);
+            return true;
+        }
+        in_group
+    }
+}
+
+impl VoterGroup for LimitedVoterGroup<'_> {
+    fn in_group(&self, slot: Slot, hash: Hash, voter: Pubkey) -> bool {
+        self.check(slot, hash, voter, || self.group.in_group(slot, hash, voter))
+    }
+
+    fn in_group_with_proof(&self, slot: Slot, hash: Hash, voter: Pubkey, proof: &VrfProof) -> bool {
+        self.check(slot, hash, voter, || {
+            self.group.in_group_with_proof(slot, hash, voter, proof)
+        })
+    }
+
+    fn in_group_with_reveal(
+        &self,
+        slot: Slot,
+        hash: Hash,
+        voter: Pubkey,
+        commitments: &[MembershipCommitment],
+        reveal: &MembershipReveal,
+    ) -> bool {
+        self.check(slot, hash, voter, || {
+            self.group
+                .in_group_with_reveal(slot, hash, voter, commitments, reveal)
+        })
+    }
+}
+
 pub fn process_instruction(
     _program_id: &Pubkey,
     keyed_accounts: &[KeyedAccount],
@@ -355,15 +521,71 @@ pub fn process_instruction(
         }
         VoteInstruction::Vote(vote) | VoteInstruction::VoteSwitch(vote, _) => {
             inc_new_counter_info!("vote-native", 1);
+            let voter_group = LimitedVoterGroup::new(invoke_context);
+            let enforce_vote_group = invoke_context
+                .is_feature_active(&feature_set::enforce_vote_group_membership::id());
+            let vote_group_credits = voter_group.is_enforced()
+                && invoke_context.is_feature_active(&feature_set::vote_group_credits::id());
+            let vote_group_penalties = voter_group.is_enforced()
+                && invoke_context.is_feature_active(&feature_set::vote_group_penalties::id());
             vote_state::process_vote(
                 me,
                 &from_keyed_account::<SlotHashes>(next_keyed_account(keyed_accounts)?)?,
                 &from_keyed_account::<Clock>(next_keyed_account(keyed_accounts)?)?,
                 &vote,
                 &signers,
-                invoke_context.voter_group(),
+                &voter_group,
+                enforce_vote_group,
+                vote_group_credits,
+                vote_group_penalties,
             )
         }
+        VoteInstruction::VoteWithVrfProof(vote, proof) => {
+            inc_new_counter_info!("vote-native", 1);
+            let enforce_vote_group = invoke_context
+                .is_feature_active(&feature_set::enforce_vote_group_membership::id());
+            vote_state::process_vote_with_vrf_proof(
+                me,
+                &from_keyed_account::<SlotHashes>(next_keyed_account(keyed_accounts)?)?,
+                &from_keyed_account::<Clock>(next_keyed_account(keyed_accounts)?)?,
+                &vote,
+                &signers,
+                &LimitedVoterGroup::new(invoke_context),
+                &proof,
+                enforce_vote_group,
+            )
+        }
+        VoteInstruction::CommitGroupMembership(commitment) => {
+            if invoke_context.is_feature_active(&feature_set::commit_reveal_vote_groups::id()) {
+                vote_state::commit_group_membership(
+                    me,
+                    commitment,
+                    &signers,
+                    &from_keyed_account::<Clock>(next_keyed_account(keyed_accounts)?)?,
+                )
+            } else {
+                Err(InstructionError::InvalidInstructionData)
+            }
+        }
+        VoteInstruction::VoteWithMembershipReveal(vote, reveal) => {
+            if invoke_context.is_feature_active(&feature_set::commit_reveal_vote_groups::id()) {
+                inc_new_counter_info!("vote-native", 1);
+                let enforce_vote_group = invoke_context
+                    .is_feature_active(&feature_set::enforce_vote_group_membership::id());
+                vote_state::process_vote_with_membership_reveal(
+                    me,
+                    &from_keyed_account::<SlotHashes>(next_keyed_account(keyed_accounts)?)?,
+                    &from_keyed_account::<Clock>(next_keyed_account(keyed_accounts)?)?,
+                    &vote,
+                    &signers,
+                    &LimitedVoterGroup::new(invoke_context),
+                    &reveal,
+                    enforce_vote_group,
+                )
+            } else {
+                Err(InstructionError::InvalidInstructionData)
+            }
+        }
         VoteInstruction::Withdraw(lamports) => {
             let to = next_keyed_account(keyed_accounts)?;
             vote_state::withdraw(me, lamports, to, &signers)
@@ -511,6 +733,15 @@ mod tests {
             )),
             Err(InstructionError::InvalidAccountData),
         );
+        assert_eq!(
+            process_instruction(&vote_with_vrf_proof(
+                &Pubkey::default(),
+                &Pubkey::default(),
+                Vote::default(),
+                VrfProof::default(),
+            )),
+            Err(InstructionError::InvalidAccountData),
+        );
         assert_eq!(
             process_instruction(&authorize(
                 &Pubkey::default(),
@@ -18,12 +18,16 @@ use solana_sdk::{
     slot_hashes::SlotHash,
     sysvar::clock::Clock,
     instruction::VoterGroup,
+    vote_group_gen::{MembershipCommitment, MembershipReveal},
+    vrf::VrfProof,
 };
 use std::boxed::Box;
 use std::cmp::Ordering;
 use std::collections::{HashSet, VecDeque};
 
 mod vote_state_0_23_5;
+mod vote_state_1_6_19;
+mod vote_state_penalty;
 pub mod vote_state_versions;
 pub use vote_state_versions::*;
 
@@ -34,6 +38,15 @@ pub const INITIAL_LOCKOUT: usize = 2;
 // Maximum number of credits history to keep around
 pub const MAX_EPOCH_CREDITS_HISTORY: usize = 64;
 
+// Credits earned when a vote is rooted, depending on whether the voter was in the slot's group
+pub const IN_GROUP_VOTE_CREDITS: u64 = 1;
+pub const OUT_OF_GROUP_VOTE_CREDITS: u64 = 0;
+
+// Epochs between crossing the out of group vote threshold and the leader schedule epoch
+// whose vote groups leave the account out. Stakes for leader schedule epoch N + 1 are
+// captured at the start of epoch N, so the first groups that can drop it are N + 2's
+pub const VOTE_GROUP_PENALTY_EPOCH_OFFSET: Epoch = 2;
+
 // Offset of VoteState::prior_voters, for determining initialization status without deserialization
 const DEFAULT_PRIOR_VOTERS_OFFSET: usize = 82;
 
@@ -159,7 +172,7 @@ impl<I> CircBuf<I> {
     }
 }
 
-#[frozen_abi(digest = "331ZmXrmsUcwbKhzR3C1UEU6uNwZr48ExE54JDKGWA4w")]
+#[frozen_abi(digest = "DFD4ntaYDupPwqyjmJxSGaSNRy6AZTrr7s59KyEvcQBF")]
 #[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq, Clone, AbiExample)]
 pub struct VoteState {
     /// the node that votes in this account
@@ -191,6 +204,15 @@ pub struct VoteState {
 
     /// most recent timestamp submitted with a vote
     pub last_timestamp: BlockTimestamp,
+
+    /// votes for slots the account wasn't in the group for, as (Epoch, count)
+    pub out_of_group_votes: (Epoch, u64),
+
+    /// the epoch the out of group votes last crossed the threshold in
+    pub vote_group_penalty_epoch: Option<Epoch>,
+
+    /// commit-reveal group membership for the current epoch and the next, oldest first
+    pub membership_commitments: Vec<MembershipCommitment>,
 }
 
 impl VoteState {
@@ -222,7 +244,10 @@ impl VoteState {
 
     pub fn size_of() -> usize {
         // Upper limit on the size of the Vote State. Equal to
-        // size_of(VoteState) when votes.len() is MAX_LOCKOUT_HISTORY
+        // size_of(VoteState) when votes.len() is MAX_LOCKOUT_HISTORY.
+        // Accounts only carry the vote group penalty fields once they've been
+        // penalized, and membership commitments once they've committed. commitments
+        // only stay while a full tower still fits, see fits_full_tower
         let vote_state = VoteStateVersions::new_current(Self::get_max_sized_vote_state());
         serialized_size(&vote_state).unwrap() as usize
     }
@@ -349,20 +374,39 @@ impl VoteState {
         vote: &Vote,
         slot_hashes: &[SlotHash],
         epoch: Epoch,
+    ) -> Result<(), VoteError> {
+        self.process_vote_with_credits(vote, slot_hashes, epoch, &|_| 1)
+    }
+
+    /// same as process_vote, but a vote that gets rooted earns `credits_for(rooted slot)`
+    pub fn process_vote_with_credits(
+        &mut self,
+        vote: &Vote,
+        slot_hashes: &[SlotHash],
+        epoch: Epoch,
+        credits_for: &dyn Fn(Slot) -> u64,
     ) -> Result<(), VoteError> {
         if vote.slots.is_empty() {
             return Err(VoteError::EmptySlots);
         }
-
-
-
         self.check_slots_are_valid(vote, slot_hashes)?;
 
-        vote.slots.iter().for_each(|s| self.process_slot(*s, epoch));
+        vote.slots
+            .iter()
+            .for_each(|s| self.process_slot_with_credits(*s, epoch, credits_for));
         Ok(())
     }
 
     pub fn process_slot(&mut self, slot: Slot, epoch: Epoch) {
+        self.process_slot_with_credits(slot, epoch, &|_| 1)
+    }
+
+    fn process_slot_with_credits(
+        &mut self,
+        slot: Slot,
+        epoch: Epoch,
+        credits_for: &dyn Fn(Slot) -> u64,
+    ) {
         // Ignore votes for slots earlier than we already have votes for
         if self
             .last_voted_slot()
@@ -380,7 +424,7 @@ impl VoteState {
             let vote = self.votes.pop_front().unwrap();
             self.root_slot = Some(vote.slot);
 
-            self.increment_credits(epoch);
+            self.increment_credits_by(epoch, credits_for(vote.slot));
         }
         self.votes.push_back(vote);
         self.double_lockouts();
@@ -388,6 +432,10 @@ impl VoteState {
 
     /// increment credits, record credits for last epoch if new epoch
     pub fn increment_credits(&mut self, epoch: Epoch) {
+        self.increment_credits_by(epoch, 1)
+    }
+
+    pub fn increment_credits_by(&mut self, epoch: Epoch, credits: u64) {
         // increment credits, record by epoch
 
         // never seen a credit
@@ -411,7 +459,7 @@ impl VoteState {
             }
         }
 
-        self.epoch_credits.last_mut().unwrap().1 += 1;
+        self.epoch_credits.last_mut().unwrap().1 += credits;
     }
 
     /// "unchecked" functions used by tests and Tower
@@ -581,6 +629,98 @@ impl VoteState {
         Ok(())
     }
 
+    pub fn has_vote_group_penalty_state(&self) -> bool {
+        self.out_of_group_votes != (0, 0) || self.vote_group_penalty_epoch.is_some()
+    }
+
+    /// counts `count` more out of group votes in `epoch`, returns true when that crosses
+    /// `threshold` and the account gets penalized. the penalty fields take room of their own,
+    /// membership commitments make way for them and an account that still has no room for
+    /// them, one with every authorized voter slot taken, isn't tracked
+    pub fn record_out_of_group_votes(&mut self, epoch: Epoch, count: u64, threshold: u64) -> bool {
+        let previous = (self.out_of_group_votes, self.vote_group_penalty_epoch);
+        if self.out_of_group_votes.0 != epoch {
+            self.out_of_group_votes = (epoch, 0);
+        }
+        let before = self.out_of_group_votes.1;
+        self.out_of_group_votes.1 = before.saturating_add(count);
+        let penalized = before < threshold && self.out_of_group_votes.1 >= threshold;
+        if penalized {
+            self.vote_group_penalty_epoch = Some(epoch);
+        }
+        if !self.fits_full_tower() {
+            let commitments = self.membership_commitments.clone();
+            self.evict_membership_commitments();
+            if !self.fits_full_tower() {
+                self.out_of_group_votes = previous.0;
+                self.vote_group_penalty_epoch = previous.1;
+                self.membership_commitments = commitments;
+                return false;
+            }
+        }
+        penalized
+    }
+
+    /// whether the groups of `leader_schedule_epoch` leave this account out
+    pub fn is_excluded_from_vote_groups(&self, leader_schedule_epoch: Epoch) -> bool {
+        self.vote_group_penalty_epoch.map_or(false, |epoch| {
+            epoch.saturating_add(VOTE_GROUP_PENALTY_EPOCH_OFFSET) == leader_schedule_epoch
+        })
+    }
+
+    /// the commitment the account made for `epoch`'s groups
+    pub fn membership_commitment(&self, epoch: Epoch) -> Option<&MembershipCommitment> {
+        self.membership_commitments
+            .iter()
+            .find(|commitment| commitment.epoch == epoch)
+    }
+
+    /// keeps `commitment` for its epoch, which has to be the one after `current_epoch` so it
+    /// is fixed before any of the epoch's bank hashes exist. older commitments are dropped
+    pub fn commit_group_membership(
+        &mut self,
+        commitment: MembershipCommitment,
+        current_epoch: Epoch,
+    ) -> Result<(), VoteError> {
+        if commitment.epoch != current_epoch.saturating_add(1) {
+            return Err(VoteError::MembershipCommitmentNotNextEpoch);
+        }
+        let previous = self.membership_commitments.clone();
+        self.membership_commitments
+            .retain(|committed| committed.epoch == current_epoch);
+        self.membership_commitments.push(commitment);
+        // the current epoch's commitment makes way for the next one when both don't fit
+        while self.membership_commitments.len() > 1 && !self.fits_full_tower() {
+            self.membership_commitments.remove(0);
+        }
+        if !self.fits_full_tower() {
+            self.membership_commitments = previous;
+            return Err(VoteError::MembershipCommitmentNoRoom);
+        }
+        Ok(())
+    }
+
+    /// drops membership commitments, oldest first, until a full tower fits again. a new
+    /// authorized voter takes the room they had
+    fn evict_membership_commitments(&mut self) {
+        while !self.membership_commitments.is_empty() && !self.fits_full_tower() {
+            self.membership_commitments.remove(0);
+        }
+    }
+
+    /// whether the state still fits the account once its tower and credit history are full,
+    /// otherwise the votes that fill them would fail with AccountDataTooSmall
+    fn fits_full_tower(&self) -> bool {
+        let full = VoteState {
+            votes: VecDeque::from(vec![Lockout::default(); MAX_LOCKOUT_HISTORY]),
+            root_slot: Some(std::u64::MAX),
+            epoch_credits: vec![(0, 0, 0); MAX_EPOCH_CREDITS_HISTORY],
+            ..self.clone()
+        };
+        serialized_size(&VoteStateVersions::new_current(full))
+            .map_or(false, |size| size as usize <= Self::size_of())
+    }
+
     pub fn is_uninitialized_no_deser(data: &[u8]) -> bool {
         const VERSION_OFFSET: usize = 4;
         data.len() != VoteState::size_of()
@@ -611,6 +751,12 @@ pub fn authorize<S: std::hash::BuildHasher>(
                 clock.leader_schedule_epoch + 1,
                 |epoch_authorized_voter| verify_authorized_signer(&epoch_authorized_voter, signers),
             )?;
+            vote_state.evict_membership_commitments();
+            // a penalized account can't take a new authorized voter the penalty fields leave
+            // no room for, the votes that fill its tower would fail later on
+            if !vote_state.fits_full_tower() {
+                return Err(InstructionError::AccountDataTooSmall);
+            }
         }
         VoteAuthorize::Withdrawer => {
             verify_authorized_signer(&vote_state.authorized_withdrawer, signers)?;
@@ -728,7 +874,122 @@ pub fn process_vote<S: std::hash::BuildHasher>(
     vote: &Vote,
     signers: &HashSet<Pubkey, S>,
     group: &dyn VoterGroup,
+    enforce_vote_group: bool,
+    vote_group_credits: bool,
+    vote_group_penalties: bool,
 ) -> Result<(), InstructionError> {
+    process_vote_in_group(
+        vote_account,
+        slot_hashes,
+        clock,
+        vote,
+        signers,
+        enforce_vote_group,
+        if vote_group_credits {
+            Some(group)
+        } else {
+            None
+        },
+        if vote_group_penalties {
+            Some(group)
+        } else {
+            None
+        },
+        |slot, hash, voter, _| group.in_group(slot, hash, voter),
+    )
+}
+
+/// publishes the vote account's membership commitment for the next epoch
+pub fn commit_group_membership<S: std::hash::BuildHasher>(
+    vote_account: &KeyedAccount,
+    commitment: MembershipCommitment,
+    signers: &HashSet<Pubkey, S>,
+    clock: &Clock,
+) -> Result<(), InstructionError> {
+    let mut vote_state: VoteState =
+        State::<VoteStateVersions>::state(vote_account)?.convert_to_current();
+    let authorized_voter = vote_state.get_and_update_authorized_voter(clock.epoch)?;
+    verify_authorized_signer(&authorized_voter, signers)?;
+    vote_state.commit_group_membership(commitment, clock.epoch)?;
+    vote_account.set_state(&VoteStateVersions::new_current(vote_state))
+}
+
+/// same as process_vote but membership comes from the voter's VRF sortition proof
+pub fn process_vote_with_vrf_proof<S: std::hash::BuildHasher>(
+    vote_account: &KeyedAccount,
+    slot_hashes: &[SlotHash],
+    clock: &Clock,
+    vote: &Vote,
+    signers: &HashSet<Pubkey, S>,
+    group: &dyn VoterGroup,
+    proof: &VrfProof,
+    enforce_vote_group: bool,
+) -> Result<(), InstructionError> {
+    process_vote_in_group(
+        vote_account,
+        slot_hashes,
+        clock,
+        vote,
+        signers,
+        enforce_vote_group,
+        // the proof only covers the voted slot, rooted slots can't be checked
+        None,
+        // and neither can the other slots of the vote
+        None,
+        |slot, hash, voter, _| group.in_group_with_proof(slot, hash, voter, proof),
+    )
+}
+
+/// same as process_vote but membership comes from the secret the voter reveals for the voted
+/// slot's window, checked against the commitment in its vote state
+pub fn process_vote_with_membership_reveal<S: std::hash::BuildHasher>(
+    vote_account: &KeyedAccount,
+    slot_hashes: &[SlotHash],
+    clock: &Clock,
+    vote: &Vote,
+    signers: &HashSet<Pubkey, S>,
+    group: &dyn VoterGroup,
+    reveal: &MembershipReveal,
+    enforce_vote_group: bool,
+) -> Result<(), InstructionError> {
+    process_vote_in_group(
+        vote_account,
+        slot_hashes,
+        clock,
+        vote,
+        signers,
+        enforce_vote_group,
+        // the reveal only covers the voted slot, rooted slots can't be checked
+        None,
+        // and neither can the other slots of the vote
+        None,
+        |slot, hash, voter, vote_state| {
+            group.in_group_with_reveal(
+                slot,
+                hash,
+                voter,
+                &vote_state.membership_commitments,
+                reveal,
+            )
+        },
+    )
+}
+
+fn process_vote_in_group<S, F>(
+    vote_account: &KeyedAccount,
+    slot_hashes: &[SlotHash],
+    clock: &Clock,
+    vote: &Vote,
+    signers: &HashSet<Pubkey, S>,
+    enforce_vote_group: bool,
+    credits_group: Option<&dyn VoterGroup>,
+    penalty_group: Option<&dyn VoterGroup>,
+    in_group: F,
+) -> Result<(), InstructionError>
+where
+    S: std::hash::BuildHasher,
+    F: FnOnce(Slot, Hash, Pubkey, &VoteState) -> bool,
+{
     let versioned = State::<VoteStateVersions>::state(vote_account)?;
 
     if versioned.is_uninitialized() {
@@ -742,11 +1003,58 @@ pub fn process_vote<S: std::hash::BuildHasher>(
     log::trace!("last_hashy: {}", slot_hashes[0].1);
     log::trace!("last_hashzy: {}", slot_hashes[0].0);
     log::trace!("P: {}", authorized_voter.to_string().to_lowercase().find("x").unwrap_or(2) % 10);
-    let hash = slot_hashes[0].1;
-    if !group.in_group(vote.slots[0],hash,authorized_voter) {
-        return Err(InstructionError::UninitializedAccount);
+    let vote_pubkey = *vote_account.unsigned_key();
+    let last_voted_slot = vote_state.last_voted_slot();
+    // a slot that fell out of slot hashes can't be checked, it keeps the full credit
+    let credits_for = |slot: Slot| match credits_group {
+        Some(group) => match slot_hashes.iter().find(|(s, _)| *s == slot) {
+            Some((_, hash)) if !group.in_group(slot, *hash, vote_pubkey) => {
+                OUT_OF_GROUP_VOTE_CREDITS
+            }
+            _ => IN_GROUP_VOTE_CREDITS,
+        },
+        None => IN_GROUP_VOTE_CREDITS,
+    };
+    vote_state.process_vote_with_credits(vote, slot_hashes, clock.epoch, &credits_for)?;
+    if enforce_vote_group {
+        // the vote is validated against slot hashes first, so vote.hash is the hash of the last
+        // slot, the voted bank. its group is the one the sender checked before voting
+        let voted_slot = *vote.slots.last().ok_or(VoteError::EmptySlots)?;
+        if !in_group(voted_slot, vote.hash, vote_pubkey, &vote_state) {
+            inc_new_counter_info!("vote_group_votes_rejected", 1);
+            return Err(VoteError::NotInVoteGroup.into());
+        }
+        inc_new_counter_info!("vote_group_votes_accepted", 1);
+    }
+    if let Some(group) = penalty_group {
+        // only slots this vote added count, the voted slot itself passed the check above
+        let out_of_group = vote
+            .slots
+            .iter()
+            .filter(|slot| last_voted_slot.map_or(true, |last| **slot > last))
+            .filter(|slot| {
+                slot_hashes
+                    .iter()
+                    .find(|(s, _)| s == *slot)
+                    .map_or(false, |(_, hash)| {
+                        !group.in_group(**slot, *hash, vote_pubkey)
+                    })
+            })
+            .count() as u64;
+        inc_new_counter_info!("vote_group_out_of_group_slots", out_of_group as usize);
+        if out_of_group > 0
+            && vote_state.record_out_of_group_votes(
+                clock.epoch,
+                out_of_group,
+                group.out_of_group_vote_threshold(),
+            )
+        {
+            info!(
+                "vote account {} crossed the out of group vote threshold in epoch {}",
+                vote_pubkey, clock.epoch
+            );
+        }
     }
-    vote_state.process_vote(vote, slot_hashes, clock.epoch)?;
     if let Some(timestamp) = vote.timestamp {
         vote.slots
             .iter()
@@ -800,6 +1108,7 @@ mod tests {
         account::AccountSharedData,
         account_utils::StateMut,
         hash::hash,
+        instruction::AnyVoter,
         keyed_account::{get_signers, next_keyed_account},
     };
     use std::cell::RefCell;
@@ -949,6 +1258,10 @@ mod tests {
             },
             &vote.clone(),
             &signers,
+            &AnyVoter,
+            true,
+            false,
+            false,
         )?;
         StateMut::<VoteStateVersions>::state(&*vote_account.borrow())
             .map(|versioned| versioned.convert_to_current())
@@ -1157,6 +1470,10 @@ mod tests {
             },
             &vote,
             &signers,
+            &AnyVoter,
+            false,
+            false,
+            false,
         );
         assert_eq!(res, Err(InstructionError::MissingRequiredSignature));
 
@@ -1173,6 +1490,10 @@ mod tests {
             },
             &vote,
             &signers,
+            &AnyVoter,
+            false,
+            false,
+            false,
         );
         assert_eq!(res, Ok(()));
 
@@ -1297,6 +1618,10 @@ mod tests {
             },
             &vote,
             &signers,
+            &AnyVoter,
+            false,
+            false,
+            false,
         );
         assert_eq!(res, Err(InstructionError::MissingRequiredSignature));
 
@@ -1318,6 +1643,10 @@ mod tests {
             },
             &vote,
             &signers,
+            &AnyVoter,
+            false,
+            false,
+            false,
         );
         assert_eq!(res, Ok(()));
     }
@@ -2115,4 +2444,589 @@ mod tests {
         VoteState::serialize(&account_state, &mut vote_account_data).unwrap();
         assert!(!VoteState::is_uninitialized_no_deser(&vote_account_data));
     }
+
+    struct OnlyVoter {
+        voter: Pubkey,
+        hash: Hash,
+    }
+
+    struct CountingVoter {
+        voter: OnlyVoter,
+        threshold: u64,
+    }
+
+    impl VoterGroup for CountingVoter {
+        fn in_group(&self, slot: Slot, hash: Hash, voter: Pubkey) -> bool {
+            self.voter.in_group(slot, hash, voter)
+        }
+        fn in_group_with_proof(
+            &self,
+            slot: Slot,
+            hash: Hash,
+            voter: Pubkey,
+            proof: &VrfProof,
+        ) -> bool {
+            self.voter.in_group_with_proof(slot, hash, voter, proof)
+        }
+        fn in_group_with_reveal(
+            &self,
+            slot: Slot,
+            hash: Hash,
+            voter: Pubkey,
+            commitments: &[MembershipCommitment],
+            reveal: &MembershipReveal,
+        ) -> bool {
+            self.voter
+                .in_group_with_reveal(slot, hash, voter, commitments, reveal)
+        }
+        fn out_of_group_vote_threshold(&self) -> u64 {
+            self.threshold
+        }
+    }
+
+    impl VoterGroup for OnlyVoter {
+        fn in_group(&self, _: Slot, hash: Hash, voter: Pubkey) -> bool {
+            voter == self.voter && hash == self.hash
+        }
+        fn in_group_with_proof(&self, slot: Slot, hash: Hash, voter: Pubkey, _: &VrfProof) -> bool {
+            self.in_group(slot, hash, voter)
+        }
+        // the reveal itself is the bank's to check, only a commitment has to be there
+        fn in_group_with_reveal(
+            &self,
+            slot: Slot,
+            hash: Hash,
+            voter: Pubkey,
+            commitments: &[MembershipCommitment],
+            _: &MembershipReveal,
+        ) -> bool {
+            self.in_group(slot, hash, voter) && !commitments.is_empty()
+        }
+    }
+
+    #[test]
+    fn test_process_vote_enforce_vote_group() {
+        let (vote_pubkey, vote_account) = create_test_account();
+        let vote = Vote::new(vec![1], Hash::new_unique());
+        let slot_hashes = &[(*vote.slots.last().unwrap(), vote.hash)];
+        let keyed_accounts = &[KeyedAccount::new(&vote_pubkey, true, &vote_account)];
+        let signers: HashSet<Pubkey> = get_signers(keyed_accounts);
+        let clock = Clock::default();
+
+        let outsider = OnlyVoter {
+            voter: solana_sdk::pubkey::new_rand(),
+            hash: vote.hash,
+        };
+        assert_eq!(
+            process_vote(
+                &keyed_accounts[0],
+                slot_hashes,
+                &clock,
+                &vote,
+                &signers,
+                &outsider,
+                true,
+                false,
+                false
+            ),
+            Err(VoteError::NotInVoteGroup.into())
+        );
+        // nothing was recorded
+        let vote_state: VoteState = StateMut::<VoteStateVersions>::state(&*vote_account.borrow())
+            .unwrap()
+            .convert_to_current();
+        assert!(vote_state.votes.is_empty());
+
+        let member = OnlyVoter {
+            voter: vote_pubkey,
+            hash: vote.hash,
+        };
+        assert_eq!(
+            process_vote(
+                &keyed_accounts[0],
+                slot_hashes,
+                &clock,
+                &vote,
+                &signers,
+                &member,
+                true,
+                false,
+                false
+            ),
+            Ok(())
+        );
+        let vote_state: VoteState = StateMut::<VoteStateVersions>::state(&*vote_account.borrow())
+            .unwrap()
+            .convert_to_current();
+        assert_eq!(vote_state.last_voted_slot(), Some(1));
+
+        // without enforcement the group isn't checked at all
+        let next_vote = Vote::new(vec![2], Hash::new_unique());
+        let next_slot_hashes = &[(2, next_vote.hash), (1, vote.hash)];
+        assert_eq!(
+            process_vote(
+                &keyed_accounts[0],
+                next_slot_hashes,
+                &clock,
+                &next_vote,
+                &signers,
+                &outsider,
+                false,
+                false,
+                false
+            ),
+            Ok(())
+        );
+        let vote_state: VoteState = StateMut::<VoteStateVersions>::state(&*vote_account.borrow())
+            .unwrap()
+            .convert_to_current();
+        assert_eq!(vote_state.last_voted_slot(), Some(2));
+    }
+
+    #[test]
+    fn test_process_vote_with_credits() {
+        let mut vote_state = VoteState::default();
+        let mut in_group_state = VoteState::default();
+        for slot in 0..(MAX_LOCKOUT_HISTORY + 10) as Slot {
+            let vote = Vote::new(vec![slot], Hash::default());
+            let slot_hashes = &[(slot, Hash::default())];
+            vote_state.process_vote(&vote, slot_hashes, 0).unwrap();
+            in_group_state
+                .process_vote_with_credits(&vote, slot_hashes, 0, &|slot| {
+                    if slot % 2 == 0 {
+                        OUT_OF_GROUP_VOTE_CREDITS
+                    } else {
+                        IN_GROUP_VOTE_CREDITS
+                    }
+                })
+                .unwrap();
+        }
+        // slots 0 through 9 got rooted
+        assert_eq!(vote_state.root_slot, Some(9));
+        assert_eq!(in_group_state.root_slot, Some(9));
+        assert_eq!(vote_state.credits(), 10);
+        assert_eq!(in_group_state.credits(), 5);
+        assert_eq!(vote_state.votes, in_group_state.votes);
+    }
+
+    #[test]
+    fn test_record_out_of_group_votes() {
+        let mut vote_state = VoteState::default();
+        assert!(!vote_state.has_vote_group_penalty_state());
+        assert!(!vote_state.record_out_of_group_votes(1, 2, 3));
+        assert!(vote_state.record_out_of_group_votes(1, 1, 3));
+        // only crossing the threshold counts
+        assert!(!vote_state.record_out_of_group_votes(1, 1, 3));
+        assert_eq!(vote_state.out_of_group_votes, (1, 4));
+        assert_eq!(vote_state.vote_group_penalty_epoch, Some(1));
+        // a new epoch starts the count over but the penalty stays until it's replaced
+        assert!(!vote_state.record_out_of_group_votes(2, 2, 3));
+        assert_eq!(vote_state.out_of_group_votes, (2, 2));
+        assert_eq!(vote_state.vote_group_penalty_epoch, Some(1));
+        // a zero threshold never penalizes
+        assert!(!VoteState::default().record_out_of_group_votes(0, 10, 0));
+
+        assert!(!vote_state.is_excluded_from_vote_groups(2));
+        assert!(vote_state.is_excluded_from_vote_groups(1 + VOTE_GROUP_PENALTY_EPOCH_OFFSET));
+        assert!(!vote_state.is_excluded_from_vote_groups(2 + VOTE_GROUP_PENALTY_EPOCH_OFFSET));
+        assert!(!VoteState::default().is_excluded_from_vote_groups(VOTE_GROUP_PENALTY_EPOCH_OFFSET));
+    }
+
+    #[test]
+    fn test_vote_state_penalty_layout() {
+        let vote_state = VoteState::get_max_sized_vote_state();
+        let versioned = VoteStateVersions::new_current(vote_state.clone());
+        assert!(matches!(versioned, VoteStateVersions::V1_6_19(_)));
+        // accounts without penalty state keep the size they always had
+        let legacy = vote_state_1_6_19::VoteState1_6_19::from(vote_state.clone());
+        assert_eq!(
+            serialized_size(&versioned).unwrap(),
+            serialized_size(&legacy).unwrap() + 4
+        );
+        assert_eq!(
+            VoteState::size_of(),
+            serialized_size(&versioned).unwrap() as usize
+        );
+        assert_eq!(versioned.convert_to_current(), vote_state);
+
+        let mut vote_state = VoteState::new(
+            &VoteInit {
+                node_pubkey: solana_sdk::pubkey::new_rand(),
+                authorized_voter: solana_sdk::pubkey::new_rand(),
+                authorized_withdrawer: solana_sdk::pubkey::new_rand(),
+                commission: 0,
+            },
+            &Clock::default(),
+        );
+        vote_state.record_out_of_group_votes(0, 1, 1);
+        let versioned = VoteStateVersions::new_current(vote_state.clone());
+        assert!(matches!(versioned, VoteStateVersions::Penalty(_)));
+        let mut data = vec![0; VoteState::size_of()];
+        VoteState::serialize(&versioned, &mut data).unwrap();
+        assert_eq!(VoteState::deserialize(&data).unwrap(), vote_state);
+        assert!(!VoteState::is_uninitialized_no_deser(&data));
+
+        // committing moves the account to the current layout
+        vote_state
+            .commit_group_membership(
+                MembershipCommitment {
+                    epoch: 1,
+                    root: Hash::new_unique(),
+                },
+                0,
+            )
+            .unwrap();
+        let versioned = VoteStateVersions::new_current(vote_state.clone());
+        assert!(matches!(versioned, VoteStateVersions::Current(_)));
+        VoteState::serialize(&versioned, &mut data).unwrap();
+        assert_eq!(VoteState::deserialize(&data).unwrap(), vote_state);
+    }
+
+    #[test]
+    fn test_vote_state_commitments_fit() {
+        let fits = |vote_state: &VoteState| {
+            let mut data = vec![0; VoteState::size_of()];
+            VoteState::serialize(
+                &VoteStateVersions::new_current(vote_state.clone()),
+                &mut data,
+            )
+            .is_ok()
+                && VoteState::deserialize(&data).unwrap() == *vote_state
+        };
+        let commitment = |epoch| MembershipCommitment {
+            epoch,
+            root: Hash::new_unique(),
+        };
+        // a long running penalized account keeps as many commitments as fit next to its
+        // authorized voters, the current epoch's makes way for the next one first
+        for (voters, kept) in &[(1, 2), (2, 1), (3, 0), (4, 0)] {
+            let mut vote_state = VoteState::get_max_sized_vote_state();
+            let mut authorized_voters = AuthorizedVoters::new(1, Pubkey::new_unique());
+            for epoch in 2..=*voters {
+                authorized_voters.insert(epoch, Pubkey::new_unique());
+            }
+            vote_state.authorized_voters = authorized_voters;
+            // with every authorized voter slot taken there's no room for a penalty either
+            if *voters < 4 {
+                vote_state.record_out_of_group_votes(1, 1, 1);
+            }
+            for epoch in 2..4 {
+                let result = vote_state.commit_group_membership(commitment(epoch), epoch - 1);
+                if *kept == 0 {
+                    assert_eq!(result, Err(VoteError::MembershipCommitmentNoRoom));
+                } else {
+                    result.unwrap();
+                    assert!(vote_state.membership_commitment(epoch).is_some());
+                }
+            }
+            assert_eq!(vote_state.membership_commitments.len(), *kept, "{}", voters);
+            assert!(fits(&vote_state), "{} voters", voters);
+        }
+
+        // a new authorized voter takes the room of the oldest commitment
+        let (vote_pubkey, vote_account) = create_test_account();
+        let mut vote_state: VoteState =
+            StateMut::<VoteStateVersions>::state(&*vote_account.borrow())
+                .unwrap()
+                .convert_to_current();
+        vote_state
+            .commit_group_membership(commitment(1), 0)
+            .unwrap();
+        let kept = commitment(2);
+        vote_state.commit_group_membership(kept, 1).unwrap();
+        vote_account
+            .borrow_mut()
+            .set_state(&VoteStateVersions::new_current(vote_state))
+            .unwrap();
+        let keyed_accounts = &[KeyedAccount::new(&vote_pubkey, true, &vote_account)];
+        let signers: HashSet<Pubkey> = get_signers(keyed_accounts);
+        let clock = Clock {
+            epoch: 1,
+            leader_schedule_epoch: 2,
+            ..Clock::default()
+        };
+        authorize(
+            &keyed_accounts[0],
+            &Pubkey::new_unique(),
+            VoteAuthorize::Voter,
+            &signers,
+            &clock,
+        )
+        .unwrap();
+        let mut vote_state: VoteState =
+            StateMut::<VoteStateVersions>::state(&*vote_account.borrow())
+                .unwrap()
+                .convert_to_current();
+        assert_eq!(vote_state.membership_commitments, vec![kept]);
+        vote_state.votes = VecDeque::from(vec![Lockout::default(); MAX_LOCKOUT_HISTORY]);
+        vote_state.root_slot = Some(std::u64::MAX);
+        vote_state.epoch_credits = vec![(0, 0, 0); MAX_EPOCH_CREDITS_HISTORY];
+        assert!(fits(&vote_state));
+    }
+
+    #[test]
+    fn test_commit_group_membership() {
+        let mut vote_state = VoteState::default();
+        let commitment = |epoch| MembershipCommitment {
+            epoch,
+            root: Hash::new_unique(),
+        };
+        assert_eq!(
+            vote_state.commit_group_membership(commitment(3), 3),
+            Err(VoteError::MembershipCommitmentNotNextEpoch)
+        );
+        assert_eq!(
+            vote_state.commit_group_membership(commitment(5), 3),
+            Err(VoteError::MembershipCommitmentNotNextEpoch)
+        );
+        let first = commitment(4);
+        vote_state.commit_group_membership(first, 3).unwrap();
+        assert_eq!(vote_state.membership_commitment(4), Some(&first));
+        // a new commitment for the same epoch replaces the last
+        let second = commitment(4);
+        vote_state.commit_group_membership(second, 3).unwrap();
+        assert_eq!(vote_state.membership_commitments, vec![second]);
+        // the current epoch's is kept while the next one is committed to
+        let next = commitment(5);
+        vote_state.commit_group_membership(next, 4).unwrap();
+        assert_eq!(vote_state.membership_commitments, vec![second, next]);
+        vote_state
+            .commit_group_membership(commitment(6), 5)
+            .unwrap();
+        assert_eq!(vote_state.membership_commitment(4), None);
+        assert_eq!(vote_state.membership_commitment(5), Some(&next));
+        assert_eq!(vote_state.membership_commitments.len(), 2);
+    }
+
+    #[test]
+    fn test_process_vote_with_membership_reveal() {
+        let (vote_pubkey, vote_account) = create_test_account();
+        let vote = Vote::new(vec![1], Hash::new_unique());
+        let slot_hashes = &[(*vote.slots.last().unwrap(), vote.hash)];
+        let keyed_accounts = &[KeyedAccount::new(&vote_pubkey, true, &vote_account)];
+        let signers: HashSet<Pubkey> = get_signers(keyed_accounts);
+        let clock = Clock::default();
+        let member = OnlyVoter {
+            voter: vote_pubkey,
+            hash: vote.hash,
+        };
+        let reveal = MembershipReveal::default();
+        let process = |group: &dyn VoterGroup| {
+            process_vote_with_membership_reveal(
+                &keyed_accounts[0],
+                slot_hashes,
+                &clock,
+                &vote,
+                &signers,
+                group,
+                &reveal,
+                true,
+            )
+        };
+
+        // nothing to reveal against before the account committed
+        assert_eq!(process(&member), Err(VoteError::NotInVoteGroup.into()));
+        let commitment = MembershipCommitment {
+            epoch: 1,
+            root: Hash::new_unique(),
+        };
+        assert_eq!(
+            commit_group_membership(
+                &keyed_accounts[0],
+                commitment,
+                &HashSet::<Pubkey>::new(),
+                &clock
+            ),
+            Err(InstructionError::MissingRequiredSignature)
+        );
+        commit_group_membership(&keyed_accounts[0], commitment, &signers, &clock).unwrap();
+        let outsider = OnlyVoter {
+            voter: solana_sdk::pubkey::new_rand(),
+            hash: vote.hash,
+        };
+        assert_eq!(process(&outsider), Err(VoteError::NotInVoteGroup.into()));
+        assert_eq!(process(&member), Ok(()));
+        let vote_state: VoteState = StateMut::<VoteStateVersions>::state(&*vote_account.borrow())
+            .unwrap()
+            .convert_to_current();
+        assert_eq!(vote_state.membership_commitment(1), Some(&commitment));
+        assert_eq!(vote_state.last_voted_slot(), Some(1));
+    }
+
+    #[test]
+    fn test_process_vote_penalties() {
+        let (vote_pubkey, vote_account) = create_test_account();
+        let keyed_accounts = &[KeyedAccount::new(&vote_pubkey, true, &vote_account)];
+        let signers: HashSet<Pubkey> = get_signers(keyed_accounts);
+        let clock = Clock::default();
+        let hash = Hash::new_unique();
+        let group = CountingVoter {
+            voter: OnlyVoter {
+                voter: vote_pubkey,
+                hash,
+            },
+            threshold: 3,
+        };
+
+        // only the slots with `hash` are in group
+        let mut slot_hashes = vec![(3, hash), (2, Hash::new_unique()), (1, Hash::new_unique())];
+        let vote = Vote::new(vec![1, 2, 3], hash);
+        assert_eq!(
+            process_vote(
+                &keyed_accounts[0],
+                &slot_hashes,
+                &clock,
+                &vote,
+                &signers,
+                &group,
+                true,
+                false,
+                true
+            ),
+            Ok(())
+        );
+        let vote_state = VoteState::from(&*vote_account.borrow()).unwrap();
+        assert_eq!(vote_state.out_of_group_votes, (0, 2));
+        assert_eq!(vote_state.vote_group_penalty_epoch, None);
+
+        // slots voted before aren't counted again
+        slot_hashes.insert(0, (5, hash));
+        slot_hashes.insert(1, (4, Hash::new_unique()));
+        let vote = Vote::new(vec![2, 3, 4, 5], hash);
+        assert_eq!(
+            process_vote(
+                &keyed_accounts[0],
+                &slot_hashes,
+                &clock,
+                &vote,
+                &signers,
+                &group,
+                true,
+                false,
+                true
+            ),
+            Ok(())
+        );
+        let vote_state = VoteState::from(&*vote_account.borrow()).unwrap();
+        assert_eq!(vote_state.out_of_group_votes, (0, 3));
+        assert_eq!(vote_state.vote_group_penalty_epoch, Some(0));
+
+        // nothing is counted without the feature
+        let (vote_pubkey, vote_account) = create_test_account();
+        let keyed_accounts = &[KeyedAccount::new(&vote_pubkey, true, &vote_account)];
+        let signers: HashSet<Pubkey> = get_signers(keyed_accounts);
+        let group = CountingVoter {
+            voter: OnlyVoter {
+                voter: vote_pubkey,
+                hash,
+            },
+            threshold: 1,
+        };
+        let vote = Vote::new(vec![1, 2, 3], hash);
+        assert_eq!(
+            process_vote(
+                &keyed_accounts[0],
+                &slot_hashes,
+                &clock,
+                &vote,
+                &signers,
+                &group,
+                true,
+                false,
+                false
+            ),
+            Ok(())
+        );
+        let vote_state = VoteState::from(&*vote_account.borrow()).unwrap();
+        assert!(!vote_state.has_vote_group_penalty_state());
+    }
+
+    #[test]
+    fn test_process_vote_penalties_max_sized_account() {
+        let (vote_pubkey, vote_account) = create_test_account();
+        let mut vote_state = VoteState::from(&*vote_account.borrow()).unwrap();
+        for epoch in 1..=MAX_LEADER_SCHEDULE_EPOCH_OFFSET {
+            vote_state
+                .authorized_voters
+                .insert(epoch, solana_sdk::pubkey::new_rand());
+        }
+        vote_account
+            .borrow_mut()
+            .set_state(&VoteStateVersions::new_current(vote_state))
+            .unwrap();
+        let keyed_accounts = &[KeyedAccount::new(&vote_pubkey, true, &vote_account)];
+        let signers: HashSet<Pubkey> = get_signers(keyed_accounts);
+        let clock = Clock::default();
+        let hash = Hash::new_unique();
+        let group = CountingVoter {
+            voter: OnlyVoter {
+                voter: vote_pubkey,
+                hash,
+            },
+            threshold: 1,
+        };
+        let process = |slot_hashes: &[SlotHash], vote: &Vote| {
+            process_vote(
+                &keyed_accounts[0],
+                slot_hashes,
+                &clock,
+                vote,
+                &signers,
+                &group,
+                true,
+                false,
+                true,
+            )
+        };
+
+        // every authorized voter slot is taken, the penalty has no room and isn't recorded
+        let vote = Vote::new(vec![1, 2], hash);
+        assert_eq!(
+            process(&[(2, hash), (1, Hash::new_unique())], &vote),
+            Ok(())
+        );
+        let vote_state = VoteState::from(&*vote_account.borrow()).unwrap();
+        assert!(!vote_state.has_vote_group_penalty_state());
+
+        // and the votes that fill the tower still fit the account
+        for slot in 3..(3 + MAX_LOCKOUT_HISTORY as Slot) {
+            let vote = Vote::new(vec![slot], hash);
+            assert_eq!(process(&[(slot, hash)], &vote), Ok(()));
+        }
+        let vote_state = VoteState::from(&*vote_account.borrow()).unwrap();
+        assert_eq!(vote_state.votes.len(), MAX_LOCKOUT_HISTORY);
+
+        // a penalized account can't take a voter the penalty leaves no room for
+        let (vote_pubkey, vote_account) = create_test_account();
+        let mut vote_state = VoteState::from(&*vote_account.borrow()).unwrap();
+        for epoch in 1..MAX_LEADER_SCHEDULE_EPOCH_OFFSET {
+            vote_state
+                .authorized_voters
+                .insert(epoch, solana_sdk::pubkey::new_rand());
+        }
+        assert!(vote_state.record_out_of_group_votes(0, 1, 1));
+        vote_account
+            .borrow_mut()
+            .set_state(&VoteStateVersions::new_current(vote_state.clone()))
+            .unwrap();
+        let keyed_accounts = &[KeyedAccount::new(&vote_pubkey, true, &vote_account)];
+        let signers: HashSet<Pubkey> = get_signers(keyed_accounts);
+        assert_eq!(
+            authorize(
+                &keyed_accounts[0],
+                &solana_sdk::pubkey::new_rand(),
+                VoteAuthorize::Voter,
+                &signers,
+                &Clock {
+                    leader_schedule_epoch: MAX_LEADER_SCHEDULE_EPOCH_OFFSET - 1,
+                    ..Clock::default()
+                },
+            ),
+            Err(InstructionError::AccountDataTooSmall)
+        );
+        assert_eq!(
+            VoteState::from(&*vote_account.borrow()).unwrap(),
+            vote_state
+        );
+    }
 }
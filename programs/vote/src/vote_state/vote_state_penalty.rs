@@ -0,0 +1,77 @@
+use super::*;
+
+// the layout before membership commitments were added, accounts that carry vote group
+// penalty state but never committed keep being written in it
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq, Clone, AbiExample)]
+pub struct VoteStatePenalty {
+    /// the node that votes in this account
+    pub node_pubkey: Pubkey,
+
+    /// the signer for withdrawals
+    pub authorized_withdrawer: Pubkey,
+    /// percentage (0-100) that represents what part of a rewards
+    ///  payout should be given to this VoteAccount
+    pub commission: u8,
+
+    pub votes: VecDeque<Lockout>,
+
+    pub root_slot: Option<Slot>,
+
+    /// the signer for vote transactions
+    pub authorized_voters: AuthorizedVoters,
+
+    /// history of prior authorized voters and the epochs for which
+    /// they were set, the bottom end of the range is inclusive,
+    /// the top of the range is exclusive
+    pub prior_voters: CircBuf<(Pubkey, Epoch, Epoch)>,
+
+    /// history of how many credits earned by the end of each epoch
+    ///  each tuple is (Epoch, credits, prev_credits)
+    pub epoch_credits: Vec<(Epoch, u64, u64)>,
+
+    /// most recent timestamp submitted with a vote
+    pub last_timestamp: BlockTimestamp,
+
+    /// votes for slots the account wasn't in the group for, as (Epoch, count)
+    pub out_of_group_votes: (Epoch, u64),
+
+    /// the epoch the out of group votes last crossed the threshold in
+    pub vote_group_penalty_epoch: Option<Epoch>,
+}
+
+impl From<VoteStatePenalty> for VoteState {
+    fn from(state: VoteStatePenalty) -> Self {
+        VoteState {
+            node_pubkey: state.node_pubkey,
+            authorized_withdrawer: state.authorized_withdrawer,
+            commission: state.commission,
+            votes: state.votes,
+            root_slot: state.root_slot,
+            authorized_voters: state.authorized_voters,
+            prior_voters: state.prior_voters,
+            epoch_credits: state.epoch_credits,
+            last_timestamp: state.last_timestamp,
+            out_of_group_votes: state.out_of_group_votes,
+            vote_group_penalty_epoch: state.vote_group_penalty_epoch,
+            ..VoteState::default()
+        }
+    }
+}
+
+impl From<VoteState> for VoteStatePenalty {
+    fn from(state: VoteState) -> Self {
+        VoteStatePenalty {
+            node_pubkey: state.node_pubkey,
+            authorized_withdrawer: state.authorized_withdrawer,
+            commission: state.commission,
+            votes: state.votes,
+            root_slot: state.root_slot,
+            authorized_voters: state.authorized_voters,
+            prior_voters: state.prior_voters,
+            epoch_credits: state.epoch_credits,
+            last_timestamp: state.last_timestamp,
+            out_of_group_votes: state.out_of_group_votes,
+            vote_group_penalty_epoch: state.vote_group_penalty_epoch,
+        }
+    }
+}
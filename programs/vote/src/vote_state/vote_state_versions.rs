@@ -1,15 +1,29 @@
 use super::*;
-use crate::vote_state::vote_state_0_23_5::VoteState0_23_5;
+use crate::vote_state::{
+    vote_state_0_23_5::VoteState0_23_5, vote_state_1_6_19::VoteState1_6_19,
+    vote_state_penalty::VoteStatePenalty,
+};
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+#[frozen_abi(digest = "3udP9kwdfKBsMVLig6U1WqfHUHe2kRD7azWUNuH2a9nj")]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone, AbiExample, AbiEnumVisitor)]
 pub enum VoteStateVersions {
     V0_23_5(Box<VoteState0_23_5>),
+    V1_6_19(Box<VoteState1_6_19>),
+    Penalty(Box<VoteStatePenalty>),
     Current(Box<VoteState>),
 }
 
 impl VoteStateVersions {
+    /// the 1.6.19 layout is kept until the account carries vote group penalty state, and the
+    /// penalty layout until it commits to its group membership
     pub fn new_current(vote_state: VoteState) -> Self {
-        Self::Current(Box::new(vote_state))
+        if !vote_state.membership_commitments.is_empty() {
+            Self::Current(Box::new(vote_state))
+        } else if vote_state.has_vote_group_penalty_state() {
+            Self::Penalty(Box::new(vote_state.into()))
+        } else {
+            Self::V1_6_19(Box::new(vote_state.into()))
+        }
     }
 
     pub fn convert_to_current(self) -> VoteState {
@@ -46,8 +60,16 @@ impl VoteStateVersions {
 
                     /// most recent timestamp submitted with a vote
                     last_timestamp: state.last_timestamp.clone(),
+
+                    out_of_group_votes: (0, 0),
+
+                    vote_group_penalty_epoch: None,
+
+                    membership_commitments: Vec::new(),
                 }
             }
+            VoteStateVersions::V1_6_19(state) => (*state).into(),
+            VoteStateVersions::Penalty(state) => (*state).into(),
             VoteStateVersions::Current(state) => *state,
         }
     }
@@ -58,6 +80,10 @@ impl VoteStateVersions {
                 vote_state.authorized_voter == Pubkey::default()
             }
 
+            VoteStateVersions::V1_6_19(vote_state) => vote_state.authorized_voters.is_empty(),
+
+            VoteStateVersions::Penalty(vote_state) => vote_state.authorized_voters.is_empty(),
+
             VoteStateVersions::Current(vote_state) => vote_state.authorized_voters.is_empty(),
         }
     }
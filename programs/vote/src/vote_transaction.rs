@@ -41,6 +41,10 @@ pub fn parse_vote_transaction(tx: &Transaction) -> Option<(Pubkey, Vote, Option<
                             VoteInstruction::VoteSwitch(vote, hash) => {
                                 Some((*key, vote, Some(hash)))
                             }
+                            VoteInstruction::VoteWithVrfProof(vote, _) => Some((*key, vote, None)),
+                            VoteInstruction::VoteWithMembershipReveal(vote, _) => {
+                                Some((*key, vote, None))
+                            }
                             _ => None,
                         })
                     })
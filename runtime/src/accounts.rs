@@ -105,6 +105,8 @@ pub struct LoadedTransaction {
     pub loaders: TransactionLoaders,
     pub rent: TransactionRent,
     pub rent_debits: RentDebits,
+    /// whether the fee is waived when the transaction succeeds, decided once at load
+    pub fee_exempt: bool,
 }
 
 pub type TransactionLoadResult = (Result<LoadedTransaction>, Option<NonceRollbackFull>);
@@ -188,6 +190,7 @@ impl Accounts {
         ancestors: &Ancestors,
         tx: &Transaction,
         fee: u64,
+        fee_exempt: bool,
         error_counters: &mut ErrorCounters,
         rent_collector: &RentCollector,
         feature_set: &FeatureSet,
@@ -295,7 +298,11 @@ impl Accounts {
                         error_counters.insufficient_funds += 1;
                         Err(TransactionError::InsufficientFundsForFee)
                     } else {
-                        accounts[payer_index].lamports -= fee;
+                        // an exempt transaction still has to cover the fee, it is charged
+                        // after execution when the transaction fails
+                        if !fee_exempt {
+                            accounts[payer_index].lamports -= fee;
+                        }
 
                         let message = tx.message();
                         let loaders = message
@@ -320,6 +327,7 @@ impl Accounts {
                             loaders,
                             rent: tx_rent,
                             rent_debits,
+                            fee_exempt,
                         })
                     }
                 }
@@ -407,6 +415,7 @@ impl Accounts {
         error_counters: &mut ErrorCounters,
         rent_collector: &RentCollector,
         feature_set: &FeatureSet,
+        is_fee_exempt: impl Fn(&Transaction) -> bool,
     ) -> Vec<TransactionLoadResult> {
         txs.zip(lock_results)
             .map(|etx| match etx {
@@ -429,6 +438,7 @@ impl Accounts {
                         ancestors,
                         tx,
                         fee,
+                        is_fee_exempt(tx),
                         error_counters,
                         rent_collector,
                         feature_set,
@@ -1123,6 +1133,7 @@ mod tests {
             error_counters,
             rent_collector,
             &FeatureSet::all_enabled(),
+            |_| false,
         )
     }
 
@@ -1969,6 +1980,7 @@ mod tests {
                 loaders: transaction_loaders0,
                 rent: transaction_rent0,
                 rent_debits: RentDebits::default(),
+                fee_exempt: false,
             }),
             None,
         );
@@ -1983,6 +1995,7 @@ mod tests {
                 loaders: transaction_loaders1,
                 rent: transaction_rent1,
                 rent_debits: RentDebits::default(),
+                fee_exempt: false,
             }),
             None,
         );
@@ -2083,6 +2096,7 @@ mod tests {
             &mut error_counters,
             &rent_collector,
             &FeatureSet::all_enabled(),
+            |_| false,
         )
     }
 
@@ -2375,6 +2389,7 @@ mod tests {
                 loaders: transaction_loaders,
                 rent: transaction_rent,
                 rent_debits: RentDebits::default(),
+                fee_exempt: false,
             }),
             nonce_rollback,
         );
@@ -2492,6 +2507,7 @@ mod tests {
                 loaders: transaction_loaders,
                 rent: transaction_rent,
                 rent_debits: RentDebits::default(),
+                fee_exempt: false,
             }),
             nonce_rollback,
         );
@@ -0,0 +1,26 @@
+use {
+    solana_sdk::{clock::Slot, pubkey::Pubkey},
+    std::{
+        fmt,
+        sync::{Arc, RwLock},
+    },
+};
+
+/// hooks for external indexers that follow the bank as it's built, every bank hands them on
+/// to its children
+pub trait AccountsUpdateNotifierInterface: fmt::Debug {
+    /// the vote group rotated at `slot`, `members` are the vote accounts `seed` picked
+    fn notify_vote_group_change(&self, slot: Slot, members: &[Pubkey], seed: u64);
+}
+
+pub type AccountsUpdateNotifier = Arc<RwLock<dyn AccountsUpdateNotifierInterface + Sync + Send>>;
+
+#[derive(Clone, Debug, Default)]
+pub struct OptionalAccountsUpdateNotifier(pub Option<AccountsUpdateNotifier>);
+
+#[cfg(RUSTC_WITH_SPECIALIZATION)]
+impl solana_frozen_abi::abi_example::AbiExample for OptionalAccountsUpdateNotifier {
+    fn example() -> Self {
+        Self(None)
+    }
+}
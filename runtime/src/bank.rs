@@ -9,9 +9,12 @@ use crate::{
     },
     accounts_db::{ErrorCounters, SnapshotStorages},
     accounts_index::{AccountSecondaryIndexes, Ancestors, IndexKey},
+    accounts_update_notifier_interface::{AccountsUpdateNotifier, OptionalAccountsUpdateNotifier},
     blockhash_queue::BlockhashQueue,
     builtins::{self, ActivationType},
-    epoch_stakes::{EpochStakes, NodeVoteAccounts},
+    epoch_stakes::{
+        EpochAssignments, EpochAssignmentsCache, EpochStakes, NodeVoteAccounts, VoteActivity,
+    },
     hashed_transaction::{HashedTransaction, HashedTransactionSlice},
     inline_spl_token_v2_0,
     instruction_recorder::InstructionRecorder,
@@ -27,6 +30,7 @@ use crate::{
     system_instruction_processor::{get_system_account_kind, SystemAccountKind},
     transaction_batch::TransactionBatch,
     vote_account::ArcVoteAccount,
+    vote_group_transition::VoteGroupTransition,
 };
 use byteorder::{ByteOrder, LittleEndian};
 use itertools::Itertools;
@@ -72,11 +76,20 @@ use solana_sdk::{
     sysvar::{self},
     timing::years_as_slots,
     transaction::{self, Result, Transaction, TransactionError},
+    vote_group_gen::{
+        AbsenceThreshold, BlockhashSeed, DelinquencyThreshold, ExclusionList, MembershipCommitment,
+        MembershipReveal, MinimumStake, QuorumFraction, SeedDerivation, SeedSource,
+        SelectionAlgorithm, SelectionMode, VoteGroupGenerator, VoteGroupOverrides, VoteGroupParams,
+        DEFAULT_OUT_OF_GROUP_VOTE_THRESHOLD, DEFAULT_ROTATION_WINDOW_SLOTS,
+        OPTIMAL_VOTE_GROUP_SIZE, VOTE_GROUP_LOG_TARGET,
+    },
+    vrf::VrfProof,
 };
 use solana_stake_program::stake_state::{
-    self, Delegation, InflationPointCalculationEvent, PointValue,
+    self, Delegation, InflationPointCalculationEvent, ParticipationCredits, PointValue,
 };
 use solana_vote_program::{
+    vote_group_admin, vote_group_config, vote_group_exclusions,
     vote_instruction::VoteInstruction,
     vote_state::{VoteState, VoteStateVersions},
 };
@@ -874,6 +887,9 @@ pub struct Bank {
 
     bpf_compute_budget: Option<BpfComputeBudget>,
 
+    /// Node local vote group parameters, only honoured on development clusters
+    vote_group_overrides: Option<VoteGroupOverrides>,
+
     /// Builtin programs activated dynamically by feature
     #[allow(clippy::rc_buffer)]
     feature_builtins: Arc<Vec<(Builtin, Pubkey, ActivationType)>>,
@@ -912,6 +928,14 @@ pub struct Bank {
     pub drop_callback: RwLock<OptionalDropCallback>,
 
     pub freeze_started: AtomicBool,
+
+    /// votes recorded in the epoch limited voting activates in, shared along the fork
+    vote_group_transition: Arc<VoteGroupTransition>,
+
+    /// the slots each epoch's groups assigned, walked once and shared by all banks
+    epoch_assignments_cache: Arc<EpochAssignmentsCache>,
+
+    pub accounts_update_notifier: RwLock<OptionalAccountsUpdateNotifier>,
 }
 
 impl Default for BlockhashQueue {
@@ -1093,6 +1117,7 @@ impl Bank {
             signature_count: AtomicU64::new(0),
             message_processor: parent.message_processor.clone(),
             bpf_compute_budget: parent.bpf_compute_budget,
+            vote_group_overrides: parent.vote_group_overrides.clone(),
             feature_builtins: parent.feature_builtins.clone(),
             hard_forks: parent.hard_forks.clone(),
             last_vote_sync: AtomicU64::new(parent.last_vote_sync.load(Relaxed)),
@@ -1116,6 +1141,11 @@ impl Bank {
                     .as_ref()
                     .map(|drop_callback| drop_callback.clone_box()),
             )),
+            vote_group_transition: parent.vote_group_transition.clone(),
+            epoch_assignments_cache: parent.epoch_assignments_cache.clone(),
+            accounts_update_notifier: RwLock::new(
+                parent.accounts_update_notifier.read().unwrap().clone(),
+            ),
             freeze_started: AtomicBool::new(false),
         };
 
@@ -1145,9 +1175,10 @@ impl Bank {
         *new.stakes.write().unwrap() = cloned;
 
         let leader_schedule_epoch = epoch_schedule.get_leader_schedule_epoch(slot);
-        new.update_epoch_stakes(leader_schedule_epoch);
+        let previous_epoch_assignments = new.update_epoch_stakes(leader_schedule_epoch);
         new.update_slot_hashes();
-        new.update_rewards(parent_epoch, reward_calc_tracer);
+        new.update_vote_group();
+        new.update_rewards(parent_epoch, previous_epoch_assignments, reward_calc_tracer);
         new.update_stake_history(Some(parent_epoch));
         new.update_clock(Some(parent_epoch));
         new.update_fees();
@@ -1170,6 +1201,10 @@ impl Bank {
         *self.drop_callback.write().unwrap() = OptionalDropCallback(callback);
     }
 
+    pub fn set_accounts_update_notifier(&self, notifier: Option<AccountsUpdateNotifier>) {
+        *self.accounts_update_notifier.write().unwrap() = OptionalAccountsUpdateNotifier(notifier);
+    }
+
     /// Like `new_from_parent` but additionally:
     /// * Doesn't assume that the parent is anywhere near `slot`, parent could be millions of slots
     /// in the past
@@ -1244,10 +1279,18 @@ impl Bank {
             epoch_schedule: fields.epoch_schedule,
             inflation: Arc::new(RwLock::new(fields.inflation)),
             stakes: RwLock::new(fields.stakes),
-            epoch_stakes: fields.epoch_stakes,
+            epoch_stakes: fields
+                .epoch_stakes
+                .into_iter()
+                .map(|(epoch, mut epoch_stakes)| {
+                    epoch_stakes.restore_authorized_voters();
+                    (epoch, epoch_stakes)
+                })
+                .collect(),
             is_delta: AtomicBool::new(fields.is_delta),
             message_processor: new(),
             bpf_compute_budget: None,
+            vote_group_overrides: None,
             feature_builtins: new(),
             last_vote_sync: new(),
             rewards: new(),
@@ -1265,6 +1308,9 @@ impl Bank {
             feature_set: new(),
             drop_callback: RwLock::new(OptionalDropCallback(None)),
             freeze_started: AtomicBool::new(fields.hash != Hash::default()),
+            vote_group_transition: new(),
+            epoch_assignments_cache: new(),
+            accounts_update_notifier: new(),
         };
         bank.finish_init(genesis_config, additional_builtins);
 
@@ -1523,11 +1569,225 @@ impl Bank {
         });
     }
 
+    /// publishes the group for this slot, or for the parent slot while group seeds
+    /// still come from the voted bank hash since this slot's hash isn't known yet
+    fn update_vote_group(&self) {
+        if !self
+            .feature_set
+            .is_active(&feature_set::vote_group_sysvar::id())
+        {
+            return;
+        }
+        let (slot, hash) = if self
+            .feature_set
+            .is_active(&feature_set::vote_group_slot_seeds::id())
+        {
+            (self.slot, Hash::default())
+        } else {
+            (self.parent_slot, self.parent_hash)
+        };
+        let (stakes, seed) = match self.vote_group_seed(slot, hash) {
+            Some(found) => found,
+            None => return,
+        };
+        let generator = stakes.get_group_genr();
+        let members = if generator.voters().is_empty() {
+            vec![]
+        } else {
+            generator.group_for_seed(seed)
+        };
+        let (window_start, rotation_window_slots) = self
+            .vote_group_window(slot)
+            .unwrap_or((slot, DEFAULT_ROTATION_WINDOW_SLOTS));
+        // the group only changes at the start of its window
+        if window_start == slot {
+            if let Some(notifier) = &self.accounts_update_notifier.read().unwrap().0 {
+                notifier
+                    .read()
+                    .unwrap()
+                    .notify_vote_group_change(slot, &members, seed);
+            }
+        }
+        let vote_group = sysvar::vote_group::VoteGroup {
+            slot,
+            seed,
+            members,
+            rotation_window_slots,
+            window_start,
+        };
+        self.update_sysvar_account(&sysvar::vote_group::id(), |account| {
+            create_account(
+                &vote_group,
+                self.inherit_specially_retained_account_fields(account),
+            )
+        });
+    }
+
     pub fn get_slot_history(&self) -> SlotHistory {
         from_account(&self.get_account(&sysvar::slot_history::id()).unwrap()).unwrap()
     }
 
-    fn update_epoch_stakes(&mut self, leader_schedule_epoch: Epoch) {
+    /// how the vote group generator for a new epoch should be built, the active
+    /// features are sampled when the epoch stakes are captured so every node
+    /// switches at the same epoch boundary. the group size comes from the vote group
+    /// config account when the cluster has one, node local overrides win over both
+    fn vote_group_params(&self) -> VoteGroupParams {
+        let selection_mode = if self
+            .feature_set
+            .is_active(&feature_set::stake_weighted_vote_groups::id())
+        {
+            SelectionMode::StakeWeighted
+        } else {
+            SelectionMode::Uniform
+        };
+        let algorithm = if self
+            .feature_set
+            .is_active(&feature_set::vote_group_selection_v3::id())
+        {
+            SelectionAlgorithm::V3
+        } else if self
+            .feature_set
+            .is_active(&feature_set::vote_group_selection_v2::id())
+        {
+            SelectionAlgorithm::V2
+        } else {
+            SelectionAlgorithm::V1
+        };
+        let config = self.vote_group_config();
+        let admin_params = self
+            .vote_group_admin_state()
+            .map(|state| state.params_at(self.epoch()).clone());
+        let mut params = VoteGroupParams {
+            selection_mode,
+            algorithm: admin_params
+                .as_ref()
+                .and_then(|admin_params| admin_params.algorithm)
+                .unwrap_or(algorithm),
+            distinct_members: self
+                .feature_set
+                .is_active(&feature_set::vote_group_distinct_members::id()),
+            dedup_identities: self
+                .feature_set
+                .is_active(&feature_set::vote_group_identity_dedup::id()),
+            group_size: admin_params
+                .as_ref()
+                .and_then(|admin_params| admin_params.group_size)
+                .or_else(|| config.map(|config| config.group_size))
+                .map(|group_size| group_size as usize)
+                .filter(|group_size| *group_size > 0)
+                .unwrap_or(OPTIMAL_VOTE_GROUP_SIZE),
+            dynamic_group_size: config
+                .filter(|_| {
+                    admin_params
+                        .as_ref()
+                        .map_or(true, |admin_params| admin_params.group_size.is_none())
+                })
+                .and_then(|config| config.dynamic_group_size()),
+            exclude_penalized: self
+                .feature_set
+                .is_active(&feature_set::vote_group_penalties::id()),
+            minimum_stake: MinimumStake::default(),
+            delinquency: if self
+                .feature_set
+                .is_active(&feature_set::vote_group_delinquency::id())
+            {
+                Some(DelinquencyThreshold::default())
+            } else {
+                None
+            },
+            // credits only tell assigned slots apart once out of group votes earn none
+            absence: if self
+                .feature_set
+                .is_active(&feature_set::vote_group_absence::id())
+                && self
+                    .feature_set
+                    .is_active(&feature_set::vote_group_credits::id())
+            {
+                Some(AbsenceThreshold::default())
+            } else {
+                None
+            },
+            rotation_window_slots: config
+                .filter(|_| {
+                    self.feature_set
+                        .is_active(&feature_set::vote_group_rotation_window::id())
+                })
+                .map_or(DEFAULT_ROTATION_WINDOW_SLOTS, |config| {
+                    config.rotation_window_slots.max(1)
+                }),
+            exclusions: match admin_params {
+                Some(admin_params) => admin_params.exclusions,
+                None => self
+                    .vote_group_exclusions()
+                    .map(|registry| registry.excluded)
+                    .unwrap_or_else(ExclusionList::legacy),
+            },
+            preset_members: None,
+        };
+        if let Some(overrides) = &self.vote_group_overrides {
+            overrides.apply(&mut params);
+        }
+        params
+    }
+
+    fn vote_group_config(&self) -> Option<vote_group_config::Config> {
+        self.get_account(&vote_group_config::id())
+            .and_then(|account| vote_group_config::Config::from(&account))
+    }
+
+    fn vote_group_exclusions(&self) -> Option<vote_group_exclusions::Registry> {
+        self.get_account(&vote_group_exclusions::id())
+            .and_then(|account| vote_group_exclusions::Registry::from(&account))
+    }
+
+    /// the params the vote group admin program set, they override the config account and the
+    /// exclusion registry
+    fn vote_group_admin_state(&self) -> Option<vote_group_admin::AdminState> {
+        self.get_account(&vote_group_admin::params::id())
+            .filter(|account| account.owner() == &vote_group_admin::id())
+            .and_then(|account| vote_group_admin::AdminState::from(&account))
+    }
+
+    /// the share of a group whose votes make a quorum during this bank's epoch
+    pub fn vote_group_quorum_fraction(&self) -> QuorumFraction {
+        self.vote_group_admin_state()
+            .and_then(|state| state.params_at(self.epoch()).quorum_fraction)
+            .unwrap_or_default()
+    }
+
+    /// the slots the groups of the epoch before this one assigned, only slot seeds let every
+    /// slot's group be walked again
+    fn previous_epoch_assignments(&self) -> Option<Arc<EpochAssignments>> {
+        self.epoch_assignments(self.epoch().checked_sub(1)?)
+    }
+
+    /// walking every slot of an epoch is slow, the first bank to ask does it for all of them
+    fn epoch_assignments(&self, epoch: Epoch) -> Option<Arc<EpochAssignments>> {
+        if !self
+            .feature_set
+            .is_active(&feature_set::vote_group_slot_seeds::id())
+        {
+            return None;
+        }
+        let epoch_stakes = self.epoch_stakes.get(&epoch)?;
+        Some(
+            self.epoch_assignments_cache
+                .get_or_insert_with(epoch, epoch_stakes, || {
+                    EpochAssignments::new(
+                        epoch_stakes,
+                        epoch,
+                        self.epoch_schedule.get_first_slot_in_epoch(epoch),
+                        self.epoch_schedule.get_last_slot_in_epoch(epoch),
+                    )
+                }),
+        )
+    }
+
+    /// returns the previous epoch's assignments when the new epoch stakes needed them
+    fn update_epoch_stakes(
+        &mut self,
+        leader_schedule_epoch: Epoch,
+    ) -> Option<Arc<EpochAssignments>> {
         // update epoch_stakes cache
         //  if my parent didn't populate for this staker's epoch, we've
         //  crossed a boundary
@@ -1536,8 +1796,25 @@ impl Bank {
                 epoch >= leader_schedule_epoch.saturating_sub(MAX_LEADER_SCHEDULE_STAKES)
             });
 
-            let new_epoch_stakes =
-                EpochStakes::new(&self.stakes.read().unwrap(), leader_schedule_epoch);
+            let previous_voters = self
+                .epoch_stakes
+                .get(&leader_schedule_epoch.saturating_sub(1))
+                .map(|epoch_stakes| epoch_stakes.get_group_genr().voters());
+            let params = self.vote_group_params();
+            let assignments = params
+                .absence
+                .and_then(|_| self.previous_epoch_assignments());
+            let new_epoch_stakes = EpochStakes::new_with_vote_activity(
+                &self.stakes.read().unwrap(),
+                leader_schedule_epoch,
+                &params,
+                self.parent_hash,
+                Some(VoteActivity {
+                    slot: self.slot,
+                    previous_voters,
+                    assignments: assignments.as_deref(),
+                }),
+            );
             {
                 let vote_stakes: HashMap<_, _> = self
                     .stakes
@@ -1556,7 +1833,9 @@ impl Bank {
             }
             self.epoch_stakes
                 .insert(leader_schedule_epoch, new_epoch_stakes);
+            return assignments;
         }
+        None
     }
 
     fn update_fees(&self) {
@@ -1683,6 +1962,7 @@ impl Bank {
     fn update_rewards(
         &mut self,
         prev_epoch: Epoch,
+        prev_epoch_assignments: Option<Arc<EpochAssignments>>,
         reward_calc_tracer: &mut Option<impl FnMut(&RewardCalculationEvent)>,
     ) {
         if prev_epoch == self.epoch() {
@@ -1709,6 +1989,7 @@ impl Bank {
 
         let validator_point_value = self.pay_validator_rewards(
             prev_epoch,
+            prev_epoch_assignments,
             validator_rewards,
             reward_calc_tracer,
             self.stake_program_v2_enabled(),
@@ -1842,11 +2123,53 @@ impl Bank {
         accounts
     }
 
+    /// the participation credits of every delegated vote account that was assigned any slot
+    ///  of `rewarded_epoch`, empty unless rewards follow vote group participation.
+    ///  `assignments` are reused when the new epoch stakes already walked `rewarded_epoch`
+    fn participation_credits(
+        &self,
+        rewarded_epoch: Epoch,
+        assignments: Option<Arc<EpochAssignments>>,
+        stake_delegation_accounts: &HashMap<
+            Pubkey,
+            (Vec<(Pubkey, AccountSharedData)>, AccountSharedData),
+        >,
+    ) -> HashMap<Pubkey, ParticipationCredits> {
+        if !self
+            .feature_set
+            .is_active(&feature_set::vote_group_participation_rewards::id())
+        {
+            return HashMap::new();
+        }
+        let assignments = match assignments
+            .filter(|assignments| assignments.epoch == rewarded_epoch)
+            .or_else(|| self.epoch_assignments(rewarded_epoch))
+        {
+            Some(assignments) => assignments,
+            None => return HashMap::new(),
+        };
+        // a vote account that voted on every slot it was assigned is owed as many credits as
+        //  the epoch has slots
+        let full_credits = self.epoch_schedule.get_slots_in_epoch(rewarded_epoch);
+        stake_delegation_accounts
+            .iter()
+            .filter_map(|(vote_pubkey, (_stake_group, vote_account))| {
+                let vote_state = StateMut::<VoteStateVersions>::state(vote_account)
+                    .ok()?
+                    .convert_to_current();
+                assignments
+                    .participation_credits(vote_pubkey, vote_state.epoch_credits(), full_credits)
+                    .map(|credits| (*vote_pubkey, credits))
+            })
+            .collect()
+    }
+
     /// iterate over all stakes, redeem vote credits for each stake we can
     ///   successfully load and parse, return the lamport value of one point
     fn pay_validator_rewards(
         &mut self,
         rewarded_epoch: Epoch,
+        rewarded_epoch_assignments: Option<Arc<EpochAssignments>>,
         rewards: u64,
         reward_calc_tracer: &mut Option<impl FnMut(&RewardCalculationEvent)>,
         fix_stake_deactivate: bool,
@@ -1854,18 +2177,26 @@ impl Bank {
         let stake_history = self.stakes.read().unwrap().history().clone();
 
         let mut stake_delegation_accounts = self.stake_delegation_accounts(reward_calc_tracer);
+        let participation = self.participation_credits(
+            rewarded_epoch,
+            rewarded_epoch_assignments,
+            &stake_delegation_accounts,
+        );
 
         let points: u128 = stake_delegation_accounts
             .iter()
-            .flat_map(|(_vote_pubkey, (stake_group, vote_account))| {
+            .flat_map(|(vote_pubkey, (stake_group, vote_account))| {
                 stake_group
                     .iter()
-                    .map(move |(_stake_pubkey, stake_account)| (stake_account, vote_account))
+                    .map(move |(_stake_pubkey, stake_account)| {
+                        (vote_pubkey, stake_account, vote_account)
+                    })
             })
-            .map(|(stake_account, vote_account)| {
+            .map(|(vote_pubkey, stake_account, vote_account)| {
                 stake_state::calculate_points(
                     &stake_account,
                     &vote_account,
+                    participation.get(vote_pubkey),
                     Some(&stake_history),
                     fix_stake_deactivate,
                 )
@@ -1910,6 +2241,7 @@ impl Bank {
                     stake_account,
                     vote_account,
                     &vote_state,
+                    participation.get(vote_pubkey),
                     &point_value,
                     Some(&stake_history),
                     &mut reward_calc_tracer.as_mut(),
@@ -2990,6 +3322,7 @@ impl Bank {
         check_time.stop();
 
         let mut load_time = Measure::start("accounts_load");
+        let fee_exemption_slot_hashes = self.fee_exemption_slot_hashes();
         let mut loaded_accounts = self.rc.accounts.load_accounts(
             &self.ancestors,
             hashed_txs.as_transactions_iter(),
@@ -2998,6 +3331,7 @@ impl Bank {
             &mut error_counters,
             &self.rent_collector,
             &self.feature_set,
+            |tx| self.is_fee_exempt_vote(tx, fee_exemption_slot_hashes.as_ref()),
         );
         load_time.stop();
 
@@ -3203,17 +3537,101 @@ impl Bank {
         )
     }
 
+    /// the fee `tx` pays at `fee_calculator`'s rate after executing with `result`, an exempt
+    /// vote only goes free when it succeeds
+    pub fn transaction_fee(
+        &self,
+        fee_calculator: &FeeCalculator,
+        tx: &Transaction,
+        result: &Result<()>,
+    ) -> u64 {
+        let fee_exempt = self.is_fee_exempt_vote(tx, self.fee_exemption_slot_hashes().as_ref());
+        Self::fee_after_execution(fee_calculator, tx, result, fee_exempt)
+    }
+
+    fn fee_after_execution(
+        fee_calculator: &FeeCalculator,
+        tx: &Transaction,
+        result: &Result<()>,
+        fee_exempt: bool,
+    ) -> u64 {
+        if result.is_ok() && fee_exempt {
+            0
+        } else {
+            fee_calculator.calculate_fee(tx.message())
+        }
+    }
+
+    /// the slot hashes exempt votes are checked against, None until vote_group_fee_exemption
+    /// is active. read once for a whole batch, the sysvar is too big to read for every vote
+    fn fee_exemption_slot_hashes(&self) -> Option<SlotHashes> {
+        if !self
+            .feature_set
+            .is_active(&feature_set::vote_group_fee_exemption::id())
+        {
+            return None;
+        }
+        self.get_account(&sysvar::slot_hashes::id())
+            .and_then(|account| from_account(&account))
+    }
+
+    /// whether `tx` is a vote from a member of the voted slot's group, those pay no fee once
+    /// vote_group_fee_exemption is active and `slot_hashes` are known. only a transaction
+    /// holding nothing but the vote is exempt, its hash has to be the voted bank's and the
+    /// vote account has to pass the same check the vote program makes. the payer still has
+    /// to cover the fee, a vote that fails to execute, for one without its authorized voter's
+    /// signature, pays it in full
+    fn is_fee_exempt_vote(&self, tx: &Transaction, slot_hashes: Option<&SlotHashes>) -> bool {
+        let slot_hashes = match slot_hashes {
+            Some(slot_hashes) => slot_hashes,
+            None => return false,
+        };
+        let message = tx.message();
+        if message.instructions.len() != 1 {
+            return false;
+        }
+        let instruction = &message.instructions[0];
+        if message
+            .account_keys
+            .get(instruction.program_id_index as usize)
+            != Some(&solana_vote_program::id())
+        {
+            return false;
+        }
+        let vote = match limited_deserialize::<VoteInstruction>(&instruction.data) {
+            Ok(VoteInstruction::Vote(vote)) | Ok(VoteInstruction::VoteSwitch(vote, _)) => vote,
+            _ => return false,
+        };
+        let vote_pubkey = match instruction
+            .accounts
+            .first()
+            .and_then(|index| message.account_keys.get(*index as usize))
+        {
+            Some(vote_pubkey) => *vote_pubkey,
+            None => return false,
+        };
+        let last_slot = match vote.slots.last() {
+            Some(last_slot) => *last_slot,
+            None => return false,
+        };
+        slot_hashes.get(&last_slot) == Some(&vote.hash)
+            && self.in_group(last_slot, vote.hash, vote_pubkey)
+    }
+
+    /// `fee_exempt` is what loading decided for every transaction
     fn filter_program_errors_and_collect_fee<'a>(
         &self,
         txs: impl Iterator<Item = &'a Transaction>,
         executed: &[TransactionExecutionResult],
+        fee_exempt: &[bool],
     ) -> Vec<Result<()>> {
         let hash_queue = self.blockhash_queue.read().unwrap();
         let mut fees = 0;
 
         let results = txs
             .zip(executed)
-            .map(|(tx, (res, nonce_rollback))| {
+            .zip(fee_exempt)
+            .map(|((tx, (res, nonce_rollback)), fee_exempt)| {
                 let (fee_calculator, is_durable_nonce) = nonce_rollback
                     .as_ref()
                     .map(|nonce_rollback| nonce_rollback.fee_calculator())
@@ -3228,7 +3646,7 @@ impl Bank {
                     });
                 let fee_calculator = fee_calculator.ok_or(TransactionError::BlockhashNotFound)?;
 
-                let fee = fee_calculator.calculate_fee(tx.message());
+                let fee = Self::fee_after_execution(&fee_calculator, tx, res, *fee_exempt);
 
                 let message = tx.message();
                 match *res {
@@ -3324,8 +3742,19 @@ impl Bank {
         );
         timings.store_us += write_time.as_us();
         self.update_transaction_statuses(hashed_txs, &executed);
-        let fee_collection_results =
-            self.filter_program_errors_and_collect_fee(hashed_txs.as_transactions_iter(), executed);
+        let fee_exempt: Vec<_> = loaded_accounts
+            .iter()
+            .map(|(loaded_transaction, _nonce_rollback)| {
+                loaded_transaction
+                    .as_ref()
+                    .map_or(false, |loaded_transaction| loaded_transaction.fee_exempt)
+            })
+            .collect();
+        let fee_collection_results = self.filter_program_errors_and_collect_fee(
+            hashed_txs.as_transactions_iter(),
+            executed,
+            &fee_exempt,
+        );
 
         TransactionResults {
             fee_collection_results,
@@ -4157,6 +4586,34 @@ impl Bank {
         self.bpf_compute_budget = bpf_compute_budget;
     }
 
+    /// every node has to build the same groups, so the overrides are ignored outside of
+    /// development clusters. a genesis bank also rebuilds the groups of the epochs it
+    /// captured at genesis, later epochs pick the overrides up as their stakes are captured
+    pub fn set_vote_group_overrides(&mut self, vote_group_overrides: Option<VoteGroupOverrides>) {
+        if vote_group_overrides.is_some() && self.cluster_type() != ClusterType::Development {
+            warn!(
+                "ignoring vote group overrides on a {:?} cluster",
+                self.cluster_type()
+            );
+            return;
+        }
+        self.vote_group_overrides = vote_group_overrides;
+        if self.slot == 0 {
+            let mut params = VoteGroupParams::default();
+            if let Some(overrides) = &self.vote_group_overrides {
+                overrides.apply(&mut params);
+            }
+            for (epoch, epoch_stakes) in self.epoch_stakes.iter_mut() {
+                *epoch_stakes = EpochStakes::new_with_vote_group_params(
+                    epoch_stakes.stakes(),
+                    *epoch,
+                    &params,
+                    *epoch_stakes.vote_group_anchor(),
+                );
+            }
+        }
+    }
+
     pub fn hard_forks(&self) -> Arc<RwLock<HardForks>> {
         self.hard_forks.clone()
     }
@@ -4366,6 +4823,25 @@ impl Bank {
             hash = extend_and_hash(&hash, &buf)
         }
 
+        if self
+            .feature_set
+            .is_active(&feature_set::voter_set_root_in_bank_hash::id())
+        {
+            if let Some(epoch_stakes) = self.epoch_stakes(self.epoch()) {
+                let voter_set_root = epoch_stakes.get_group_genr().voter_set_root();
+                hash = extend_and_hash(&hash, voter_set_root.as_ref());
+            }
+        }
+
+        if self
+            .feature_set
+            .is_active(&feature_set::vote_group_in_bank_hash::id())
+        {
+            if let Some(commitment) = self.vote_group_commitment() {
+                hash = extend_and_hash(&hash, commitment.as_ref());
+            }
+        }
+
         info!(
             "bank frozen: {} hash: {} accounts_delta: {} signature_count: {} last_blockhash: {} capitalization: {}",
             self.slot(),
@@ -4673,6 +5149,16 @@ impl Bank {
         &self.epoch_stakes
     }
 
+    /// the vote group generator of the bank's own epoch, shared with every other bank of
+    /// the epoch and only rebuilt when the next epoch's stakes are captured
+    pub fn vote_group_generator(&self) -> Arc<VoteGroupGenerator> {
+        self.epoch_stakes
+            .get(&self.epoch)
+            .expect("Epoch stakes for bank's own epoch must exist")
+            .vote_group_generator()
+            .clone()
+    }
+
     pub fn epoch_staked_nodes(&self, epoch: Epoch) -> Option<HashMap<Pubkey, u64>> {
         Some(self.epoch_stakes.get(&epoch)?.stakes().staked_nodes())
     }
@@ -5095,76 +5581,365 @@ impl Bank {
     }
 }
 
-impl VoterGroup for Bank {
-        
-    /// determine if a voter is in the group for a given slot
-    fn in_group(&self, slot : Slot, hash: Hash, voter: Pubkey) -> bool {
+impl Bank {
+    /// how a voted hash is turned into a group seed while groups aren't seeded from slots
+    fn vote_group_seed_derivation(&self) -> SeedDerivation {
+        if self
+            .feature_set
+            .is_active(&feature_set::vote_group_seed_derivation_v2::id())
+        {
+            SeedDerivation::V2
+        } else {
+            SeedDerivation::V1
+        }
+    }
+
+    /// the epoch stakes holding the generator for `slot` and the seed of its group
+    pub(crate) fn vote_group_seed(&self, slot: Slot, hash: Hash) -> Option<(&EpochStakes, u64)> {
         let epoch = self.epoch_schedule.get_epoch(slot);
-        match self.epoch_stakes.get(&epoch){
-            None => panic!("No epoch"),
-            Some(stakes) =>{
-                let vgr = stakes.get_group_genr();
-                return vgr.in_group_for_hash(hash, voter) 
+        let stakes = match self.epoch_stakes.get(&epoch) {
+            None => {
+                warn!("no epoch stakes for epoch {} (slot {})", epoch, slot);
+                return None;
             }
+            Some(stakes) => stakes,
+        };
+        if let Some(seed) = self.vote_group_preset_seed() {
+            return Some((stakes, seed));
         }
+        let seed = if self
+            .feature_set
+            .is_active(&feature_set::vote_group_slot_seeds::id())
+        {
+            stakes
+                .windowed_seed_source(epoch, self.epoch_schedule.get_first_slot_in_epoch(epoch))
+                .seed_for(slot)
+        } else {
+            BlockhashSeed::new(hash)
+                .with_derivation(self.vote_group_seed_derivation())
+                .seed_for(slot)
+        };
+        Some((stakes, seed))
     }
-}
 
-impl Drop for Bank {
-    fn drop(&mut self) {
-        if !self.skip_drop.load(Relaxed) {
-            if let Some(drop_callback) = self.drop_callback.read().unwrap().0.as_ref() {
-                drop_callback.callback(self);
-            } else {
-                // Default case
-                // 1. Tests
-                // 2. At startup when replaying blockstore and there's no
-                // AccountsBackgroundService to perform cleanups yet.
-                self.rc.accounts.purge_slot(self.slot());
-            }
+    /// the seed every group is pinned to by the node local overrides
+    pub fn vote_group_preset_seed(&self) -> Option<u64> {
+        self.vote_group_overrides
+            .as_ref()
+            .and_then(|overrides| overrides.seed)
+    }
+
+    /// commitment to the group of this bank's slot, see VoteGroupGenerator::group_commitment.
+    /// groups seeded from the voted hash aren't known before the bank hash, so there is none
+    /// without slot seeds
+    fn vote_group_commitment(&self) -> Option<Hash> {
+        if !self
+            .feature_set
+            .is_active(&feature_set::vote_group_slot_seeds::id())
+        {
+            return None;
         }
+        let (stakes, seed) = self.vote_group_seed(self.slot(), Hash::default())?;
+        stakes.get_group_genr().group_commitment(seed).ok()
     }
-}
 
-pub fn goto_end_of_slot(bank: &mut Bank) {
-    let mut tick_hash = bank.last_blockhash();
-    loop {
-        tick_hash = hashv(&[&tick_hash.as_ref(), &[42]]);
-        bank.register_tick(&tick_hash);
-        if tick_hash == bank.last_blockhash() {
-            bank.freeze();
-            return;
+    /// the first slot of `slot`'s rotation window and how many slots the window lasts, groups
+    /// seeded from voted hashes never last longer than their slot
+    pub fn vote_group_window(&self, slot: Slot) -> Option<(Slot, u64)> {
+        if !self
+            .feature_set
+            .is_active(&feature_set::vote_group_slot_seeds::id())
+        {
+            return Some((slot, DEFAULT_ROTATION_WINDOW_SLOTS));
         }
+        let epoch = self.epoch_schedule.get_epoch(slot);
+        let source = self
+            .epoch_stakes
+            .get(&epoch)?
+            .windowed_seed_source(epoch, self.epoch_schedule.get_first_slot_in_epoch(epoch));
+        Some((source.window_start(slot), source.window_slots()))
     }
-}
 
-fn is_simple_vote_transaction(transaction: &Transaction) -> bool {
-    if transaction.message.instructions.len() == 1 {
-        let instruction = &transaction.message.instructions[0];
-        let program_pubkey =
-            transaction.message.account_keys[instruction.program_id_index as usize];
-        if program_pubkey == solana_vote_program::id() {
-            if let Ok(vote_instruction) = limited_deserialize::<VoteInstruction>(&instruction.data)
-            {
-                return matches!(
-                    vote_instruction,
-                    VoteInstruction::Vote(_) | VoteInstruction::VoteSwitch(_, _)
-                );
-            }
+    /// how many rotation windows `epoch` has, a membership commitment for it covers one
+    /// secret each
+    pub fn membership_windows(&self, epoch: Epoch) -> Option<u32> {
+        let window_slots = if self
+            .feature_set
+            .is_active(&feature_set::vote_group_slot_seeds::id())
+        {
+            self.epoch_stakes.get(&epoch)?.rotation_window_slots()
+        } else {
+            DEFAULT_ROTATION_WINDOW_SLOTS
+        };
+        let slots_in_epoch = self.epoch_schedule.get_slots_in_epoch(epoch);
+        Some(((slots_in_epoch + window_slots - 1) / window_slots) as u32)
+    }
+
+    /// the rotation window of its epoch a membership reveal for `slot` has to open
+    pub fn membership_window(&self, slot: Slot) -> Option<u32> {
+        let epoch = self.epoch_schedule.get_epoch(slot);
+        let (window_start, window_slots) = self.vote_group_window(slot)?;
+        Some(
+            ((window_start - self.epoch_schedule.get_first_slot_in_epoch(epoch)) / window_slots)
+                as u32,
+        )
+    }
+
+    /// the generator and seed of `slot`'s group as this bank sees it. without slot seeds
+    /// the slot's own bank hash stands in for the voted hash, so only this bank's slot and
+    /// the slots in its slot hashes can be answered
+    pub fn vote_group_for_slot(&self, slot: Slot) -> Option<(&VoteGroupGenerator, u64)> {
+        let hash = self.vote_group_hash(slot)?;
+        self.vote_group_seed(slot, hash)
+            .map(|(stakes, seed)| (stakes.get_group_genr(), seed))
+    }
+
+    /// same as vote_group_stakes for the slots vote_group_for_slot can answer
+    pub fn vote_group_stakes_for_slot(&self, slot: Slot) -> Option<HashMap<Pubkey, u64>> {
+        self.vote_group_stakes(slot, self.vote_group_hash(slot)?)
+    }
+
+    fn vote_group_hash(&self, slot: Slot) -> Option<Hash> {
+        if self
+            .feature_set
+            .is_active(&feature_set::vote_group_slot_seeds::id())
+        {
+            Some(Hash::default())
+        } else if slot == self.slot() {
+            Some(self.hash())
+        } else {
+            let slot_hashes: SlotHashes =
+                from_account(&self.get_account(&sysvar::slot_hashes::id())?)?;
+            slot_hashes.get(&slot).copied()
         }
     }
-    false
-}
 
-#[cfg(test)]
-pub(crate) mod tests {
-    use super::*;
-    use crate::{
-        accounts_db::SHRINK_RATIO,
-        accounts_index::{
-            AccountIndex, AccountMap, AccountSecondaryIndexes, Ancestors, ITER_BATCH_SIZE,
-        },
-        genesis_utils::{
+    /// same as in_group for a whole batch of voters, the group is walked once
+    /// and the voters are checked in parallel
+    pub fn in_group_batch(&self, slot: Slot, hash: Hash, voters: &[Pubkey]) -> Vec<bool> {
+        let in_group = match self.vote_group_seed(slot, hash) {
+            Some((stakes, seed)) if !stakes.get_group_genr().voters().is_empty() => stakes
+                .get_group_genr()
+                .par_in_group_for_seed_batch(seed, voters),
+            _ => vec![false; voters.len()],
+        };
+        inc_new_counter_info!("vote_group_membership_checks", voters.len());
+        inc_new_counter_info!(
+            "vote_group_membership_not_in_group",
+            in_group.iter().filter(|in_group| !**in_group).count()
+        );
+        in_group
+    }
+
+    /// whether votes are limited to the voted slot's group, before the feature every voter
+    /// votes on every slot
+    pub fn is_limited_voting_active(&self) -> bool {
+        self.feature_set
+            .is_active(&feature_set::limited_voting::id())
+    }
+
+    /// whether this bank is in the epoch limited voting activated in, votes from outside the
+    /// group are still accepted and only recorded. a feature active since genesis has
+    /// nothing to transition from
+    pub fn is_limited_voting_transition(&self) -> bool {
+        self.feature_set
+            .activated_slot(&feature_set::limited_voting::id())
+            .map_or(false, |slot| {
+                slot > 0 && self.epoch_schedule.get_epoch(slot) == self.epoch()
+            })
+    }
+
+    /// whether votes from outside the group are rejected
+    pub fn is_limited_voting_enforced(&self) -> bool {
+        self.is_limited_voting_active() && !self.is_limited_voting_transition()
+    }
+
+    /// whether fork choice only counts the votes of each slot's group, weighed against the
+    /// group's stake
+    pub fn is_group_weighted_fork_choice_active(&self) -> bool {
+        self.is_limited_voting_enforced()
+            && self
+                .feature_set
+                .is_active(&feature_set::group_weighted_fork_choice::id())
+    }
+
+    /// the members of `slot`'s group with their stake in the slot's epoch, a member picked
+    /// more than once is listed once
+    pub fn vote_group_stakes(&self, slot: Slot, hash: Hash) -> Option<HashMap<Pubkey, u64>> {
+        let (stakes, seed) = self.vote_group_seed(slot, hash)?;
+        Some(
+            stakes
+                .get_group_genr()
+                .group_for_seed(seed)
+                .into_iter()
+                .map(|member| (member, stakes.vote_account_stake(&member)))
+                .collect(),
+        )
+    }
+
+    pub fn vote_group_transition(&self) -> &VoteGroupTransition {
+        &self.vote_group_transition
+    }
+
+    /// the member of `slot`'s group that relays the other members' votes to the next
+    /// leader, every node that agrees on the slot's seed agrees on it
+    pub fn vote_group_leader(&self, slot: Slot, hash: Hash) -> Option<Pubkey> {
+        let (stakes, seed) = self.vote_group_seed(slot, hash)?;
+        stakes.get_group_genr().group_leader_for_seed(seed)
+    }
+}
+
+impl VoterGroup for Bank {
+        
+    /// determine if a voter is in the group for a given slot
+    fn in_group(&self, slot : Slot, hash: Hash, voter: Pubkey) -> bool {
+        let (stakes, seed) = match self.vote_group_seed(slot, hash) {
+            Some(found) => found,
+            None => return false,
+        };
+        let in_group = stakes
+            .get_group_genr()
+            .try_in_group_for_seed(seed, voter)
+            .unwrap_or_else(|err| {
+                warn!("unable to check vote group for slot {}: {}", slot, err);
+                false
+            });
+        trace!(
+            target: VOTE_GROUP_LOG_TARGET,
+            "vote group check slot={} seed={} voter={} in_group={}",
+            slot,
+            seed,
+            voter,
+            in_group
+        );
+        inc_new_counter_info!("vote_group_membership_checks", 1);
+        if !in_group {
+            inc_new_counter_info!("vote_group_membership_not_in_group", 1);
+        }
+        in_group
+    }
+
+    /// determine if a voter's VRF proof puts it in the group for a given slot
+    fn in_group_with_proof(&self, slot: Slot, hash: Hash, voter: Pubkey, proof: &VrfProof) -> bool {
+        let epoch = self.epoch_schedule.get_epoch(slot);
+        match self.epoch_stakes.get(&epoch) {
+            None => {
+                warn!("no epoch stakes for epoch {} (slot {})", epoch, slot);
+                false
+            }
+            Some(stakes) => stakes
+                .get_group_genr()
+                .in_vrf_group_for_hash(hash, voter, proof),
+        }
+    }
+
+    /// determine if the secret a voter reveals puts it in the group for a given slot
+    fn in_group_with_reveal(
+        &self,
+        slot: Slot,
+        hash: Hash,
+        voter: Pubkey,
+        commitments: &[MembershipCommitment],
+        reveal: &MembershipReveal,
+    ) -> bool {
+        let epoch = self.epoch_schedule.get_epoch(slot);
+        let commitment = match commitments
+            .iter()
+            .find(|commitment| commitment.epoch == epoch)
+        {
+            Some(commitment) => commitment,
+            None => return false,
+        };
+        match (
+            self.epoch_stakes.get(&epoch),
+            self.membership_window(slot),
+            self.membership_windows(epoch),
+        ) {
+            (Some(stakes), Some(window), Some(windows)) => stakes
+                .get_group_genr()
+                .in_committed_group_for_hash(hash, voter, commitment, (window, windows), reveal),
+            _ => {
+                warn!("no epoch stakes for epoch {} (slot {})", epoch, slot);
+                false
+            }
+        }
+    }
+
+    fn out_of_group_vote_threshold(&self) -> u64 {
+        self.vote_group_config()
+            .map(|config| config.out_of_group_vote_threshold)
+            .unwrap_or(DEFAULT_OUT_OF_GROUP_VOTE_THRESHOLD)
+    }
+
+    fn accepts_out_of_group_votes(&self) -> bool {
+        self.is_limited_voting_transition()
+    }
+
+    fn record_transition_vote(&self, slot: Slot, hash: Hash, voter: Pubkey, in_group: bool) {
+        self.vote_group_transition
+            .record(slot, hash, voter, in_group);
+    }
+}
+
+impl Drop for Bank {
+    fn drop(&mut self) {
+        if !self.skip_drop.load(Relaxed) {
+            if let Some(drop_callback) = self.drop_callback.read().unwrap().0.as_ref() {
+                drop_callback.callback(self);
+            } else {
+                // Default case
+                // 1. Tests
+                // 2. At startup when replaying blockstore and there's no
+                // AccountsBackgroundService to perform cleanups yet.
+                self.rc.accounts.purge_slot(self.slot());
+            }
+        }
+    }
+}
+
+pub fn goto_end_of_slot(bank: &mut Bank) {
+    let mut tick_hash = bank.last_blockhash();
+    loop {
+        tick_hash = hashv(&[&tick_hash.as_ref(), &[42]]);
+        bank.register_tick(&tick_hash);
+        if tick_hash == bank.last_blockhash() {
+            bank.freeze();
+            return;
+        }
+    }
+}
+
+fn is_simple_vote_transaction(transaction: &Transaction) -> bool {
+    if transaction.message.instructions.len() == 1 {
+        let instruction = &transaction.message.instructions[0];
+        let program_pubkey =
+            transaction.message.account_keys[instruction.program_id_index as usize];
+        if program_pubkey == solana_vote_program::id() {
+            if let Ok(vote_instruction) = limited_deserialize::<VoteInstruction>(&instruction.data)
+            {
+                return matches!(
+                    vote_instruction,
+                    VoteInstruction::Vote(_)
+                        | VoteInstruction::VoteSwitch(_, _)
+                        | VoteInstruction::VoteWithVrfProof(_, _)
+                        | VoteInstruction::VoteWithMembershipReveal(_, _)
+                );
+            }
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::{
+        accounts_db::SHRINK_RATIO,
+        accounts_index::{
+            AccountIndex, AccountMap, AccountSecondaryIndexes, Ancestors, ITER_BATCH_SIZE,
+        },
+        accounts_update_notifier_interface::AccountsUpdateNotifierInterface,
+        genesis_utils::{
             activate_all_features, bootstrap_validator_stake_lamports,
             create_genesis_config_with_leader, create_genesis_config_with_vote_accounts,
             GenesisConfigInfo, ValidatorVoteKeypairs,
@@ -5197,12 +5972,12 @@ pub(crate) mod tests {
         stake_state::{self, Authorized, Delegation, Lockup, Stake},
     };
     use solana_vote_program::{
-        vote_instruction,
+        vote_instruction::{self, VoteError},
         vote_state::{
             self, BlockTimestamp, Vote, VoteInit, VoteState, VoteStateVersions, MAX_LOCKOUT_HISTORY,
         },
     };
-    use std::{result, thread::Builder, time::Duration};
+    use std::{result, sync::Mutex, thread::Builder, time::Duration};
 
     #[test]
     fn test_nonce_rollback_info() {
@@ -7124,7 +7899,7 @@ pub(crate) mod tests {
                     .map(move |(_stake_pubkey, stake_account)| (stake_account, vote_account))
             })
             .map(|(stake_account, vote_account)| {
-                stake_state::calculate_points(&stake_account, &vote_account, None, true)
+                stake_state::calculate_points(&stake_account, &vote_account, None, None, true)
                     .unwrap_or(0)
             })
             .sum();
@@ -7796,7 +8571,8 @@ pub(crate) mod tests {
         ];
         let initial_balance = bank.get_balance(&leader);
 
-        let results = bank.filter_program_errors_and_collect_fee([tx1, tx2].iter(), &results);
+        let results =
+            bank.filter_program_errors_and_collect_fee([tx1, tx2].iter(), &results, &[false; 2]);
         bank.freeze();
         assert_eq!(
             bank.get_balance(&leader),
@@ -12648,4 +13424,1020 @@ pub(crate) mod tests {
         rent_debits.push(&Pubkey::default(), i64::MAX as u64, 0);
         assert_eq!(rent_debits.0.len(), 2);
     }
+
+    #[test]
+    fn test_in_group_batch() {
+        let validator_keypairs: Vec<_> =
+            (0..20).map(|_| ValidatorVoteKeypairs::new_rand()).collect();
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config_with_vote_accounts(
+            1_000_000_000,
+            &validator_keypairs,
+            vec![10_000; 20],
+        );
+        let bank = Bank::new(&genesis_config);
+        let mut voters: Vec<Pubkey> = validator_keypairs
+            .iter()
+            .map(|keypairs| keypairs.vote_keypair.pubkey())
+            .collect();
+        voters.push(Pubkey::new_unique());
+        for slot in 0..10 {
+            let hash = Hash::new_unique();
+            let expected: Vec<bool> = voters
+                .iter()
+                .map(|voter| bank.in_group(slot, hash, *voter))
+                .collect();
+            assert!(expected.iter().any(|found| *found));
+            assert_eq!(bank.in_group_batch(slot, hash, &voters), expected);
+        }
+
+        // no epoch stakes that far out
+        let far_slot = bank.epoch_schedule().get_first_slot_in_epoch(100);
+        assert_eq!(
+            bank.in_group_batch(far_slot, Hash::new_unique(), &voters),
+            vec![false; voters.len()]
+        );
+    }
+
+    #[test]
+    fn test_vote_group_config_group_size() {
+        let validator_keypairs: Vec<_> =
+            (0..10).map(|_| ValidatorVoteKeypairs::new_rand()).collect();
+        let GenesisConfigInfo {
+            mut genesis_config, ..
+        } = create_genesis_config_with_vote_accounts(
+            1_000_000_000,
+            &validator_keypairs,
+            vec![10_000; 10],
+        );
+        let authority = Pubkey::new_unique();
+        genesis_config.add_account(
+            vote_group_config::id(),
+            vote_group_config::create_account(
+                1_000_000,
+                &authority,
+                &vote_group_config::Config {
+                    group_size: 3,
+                    ..vote_group_config::Config::default()
+                },
+            ),
+        );
+        let bank = Arc::new(Bank::new(&genesis_config));
+        // epochs set up at genesis keep the default size
+        let genesis_epoch = bank.get_leader_schedule_epoch(0);
+        assert_eq!(
+            bank.epoch_stakes(genesis_epoch)
+                .unwrap()
+                .get_group_genr()
+                .group_size(),
+            OPTIMAL_VOTE_GROUP_SIZE
+        );
+
+        // the config is picked up once the next epoch's stakes are captured
+        let slot = bank.epoch_schedule().get_first_slot_in_epoch(1);
+        let bank = Bank::new_from_parent(&bank, &Pubkey::default(), slot);
+        let epoch = bank.get_leader_schedule_epoch(slot);
+        assert!(epoch > genesis_epoch);
+        assert_eq!(
+            bank.epoch_stakes(epoch).unwrap().get_group_genr().group_size(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_vote_group_config_dynamic_group_size() {
+        let validator_keypairs: Vec<_> =
+            (0..10).map(|_| ValidatorVoteKeypairs::new_rand()).collect();
+        let GenesisConfigInfo {
+            mut genesis_config, ..
+        } = create_genesis_config_with_vote_accounts(
+            1_000_000_000,
+            &validator_keypairs,
+            vec![10_000; 10],
+        );
+        genesis_config.add_account(
+            vote_group_config::id(),
+            vote_group_config::create_account(
+                1_000_000,
+                &Pubkey::new_unique(),
+                &vote_group_config::Config {
+                    group_size_percent: 30,
+                    min_group_size: 2,
+                    max_group_size: 8,
+                    ..vote_group_config::Config::default()
+                },
+            ),
+        );
+        let bank = Arc::new(Bank::new(&genesis_config));
+        let slot = bank.epoch_schedule().get_first_slot_in_epoch(1);
+        let bank = Bank::new_from_parent(&bank, &Pubkey::default(), slot);
+        let epoch = bank.get_leader_schedule_epoch(slot);
+        // 30% of the 10 voters
+        assert_eq!(
+            bank.epoch_stakes(epoch).unwrap().get_group_genr().group_size(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_vote_group_rotation_window() {
+        let validator_keypairs: Vec<_> =
+            (0..10).map(|_| ValidatorVoteKeypairs::new_rand()).collect();
+        let GenesisConfigInfo {
+            mut genesis_config, ..
+        } = create_genesis_config_with_vote_accounts(
+            1_000_000_000,
+            &validator_keypairs,
+            vec![10_000; 10],
+        );
+        genesis_config.add_account(
+            vote_group_config::id(),
+            vote_group_config::create_account(
+                1_000_000,
+                &Pubkey::new_unique(),
+                &vote_group_config::Config {
+                    rotation_window_slots: 4,
+                    ..vote_group_config::Config::default()
+                },
+            ),
+        );
+        let bank = Arc::new(Bank::new(&genesis_config));
+        let slot = bank.epoch_schedule().get_first_slot_in_epoch(1);
+        let bank = Bank::new_from_parent(&bank, &Pubkey::default(), slot);
+        let epoch = bank.get_leader_schedule_epoch(slot);
+        assert_eq!(bank.epoch_stakes(epoch).unwrap().rotation_window_slots(), 4);
+
+        // the windows start over with every epoch
+        let first_slot = bank.epoch_schedule().get_first_slot_in_epoch(epoch);
+        assert_eq!(
+            bank.vote_group_window(first_slot + 5),
+            Some((first_slot + 4, 4))
+        );
+        let seed = |slot| bank.vote_group_seed(slot, Hash::default()).unwrap().1;
+        for slot in first_slot + 5..first_slot + 8 {
+            assert_eq!(seed(slot), seed(first_slot + 4));
+        }
+        assert_ne!(seed(first_slot + 8), seed(first_slot + 4));
+
+        // without the feature every slot still gets its own group
+        genesis_config
+            .accounts
+            .remove(&feature_set::vote_group_rotation_window::id());
+        let bank = Arc::new(Bank::new(&genesis_config));
+        let bank = Bank::new_from_parent(&bank, &Pubkey::default(), slot);
+        assert_eq!(bank.epoch_stakes(epoch).unwrap().rotation_window_slots(), 1);
+        assert_eq!(
+            bank.vote_group_window(first_slot + 5),
+            Some((first_slot + 5, 1))
+        );
+    }
+
+    #[test]
+    fn test_vote_group_exclusions() {
+        let validator_keypairs: Vec<_> =
+            (0..10).map(|_| ValidatorVoteKeypairs::new_rand()).collect();
+        let GenesisConfigInfo {
+            mut genesis_config, ..
+        } = create_genesis_config_with_vote_accounts(
+            1_000_000_000,
+            &validator_keypairs,
+            vec![10_000; 10],
+        );
+        // none of the validators ever votes, keep them from counting as delinquent
+        genesis_config
+            .accounts
+            .remove(&feature_set::vote_group_delinquency::id());
+        let excluded = validator_keypairs[0].vote_keypair.pubkey();
+        genesis_config.add_account(
+            vote_group_exclusions::id(),
+            vote_group_exclusions::create_account(
+                1_000_000,
+                &Pubkey::new_unique(),
+                &vote_group_exclusions::Registry {
+                    excluded: ExclusionList::new(vec![excluded]),
+                },
+            ),
+        );
+        let bank = Arc::new(Bank::new(&genesis_config));
+        let genesis_epoch = bank.get_leader_schedule_epoch(0);
+        assert!(bank
+            .epoch_stakes(genesis_epoch)
+            .unwrap()
+            .get_group_genr()
+            .voters()
+            .contains(&excluded));
+
+        // the registry is picked up once the next epoch's stakes are captured
+        let slot = bank.epoch_schedule().get_first_slot_in_epoch(1);
+        let bank = Bank::new_from_parent(&bank, &Pubkey::default(), slot);
+        let epoch = bank.get_leader_schedule_epoch(slot);
+        let generator = bank.epoch_stakes(epoch).unwrap().get_group_genr().clone();
+        assert_eq!(generator.voters().len(), 9);
+        assert!(!generator.voters().contains(&excluded));
+    }
+
+    #[test]
+    fn test_limited_voting_activation() {
+        let validator_keypairs: Vec<_> =
+            (0..20).map(|_| ValidatorVoteKeypairs::new_rand()).collect();
+        let GenesisConfigInfo {
+            mut genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config_with_vote_accounts(
+            1_000_000_000,
+            &validator_keypairs,
+            vec![10_000; 20],
+        );
+        genesis_config
+            .accounts
+            .remove(&feature_set::limited_voting::id());
+        let vote = |bank: &Bank, keypairs: &ValidatorVoteKeypairs| {
+            let tx = Transaction::new_signed_with_payer(
+                &[vote_instruction::vote(
+                    &keypairs.vote_keypair.pubkey(),
+                    &keypairs.vote_keypair.pubkey(),
+                    Vote::new(vec![bank.parent_slot()], bank.parent_hash()),
+                )],
+                Some(&mint_keypair.pubkey()),
+                &[&mint_keypair, &keypairs.vote_keypair],
+                bank.last_blockhash(),
+            );
+            bank.process_transaction(&tx)
+        };
+        let in_group = |bank: &Bank, keypairs: &ValidatorVoteKeypairs| {
+            bank.in_group(
+                bank.parent_slot(),
+                bank.parent_hash(),
+                keypairs.vote_keypair.pubkey(),
+            )
+        };
+
+        // before activation every voter is accepted
+        let bank0 = Arc::new(Bank::new(&genesis_config));
+        let bank = Bank::new_from_parent(&bank0, &Pubkey::default(), 1);
+        assert!(!bank.is_limited_voting_active());
+        let outsider = validator_keypairs
+            .iter()
+            .find(|keypairs| !in_group(&bank, keypairs))
+            .unwrap();
+        assert_eq!(vote(&bank, outsider), Ok(()));
+
+        // the feature activates with the first slot of the next epoch
+        bank.store_account(
+            &feature_set::limited_voting::id(),
+            &feature::create_account(&Feature { activated_at: None }, 42),
+        );
+        let last_slot = bank.epoch_schedule().get_last_slot_in_epoch(0);
+        let bank = Arc::new(Bank::new_from_parent(
+            &Arc::new(bank),
+            &Pubkey::default(),
+            last_slot,
+        ));
+        assert!(!bank.is_limited_voting_active());
+        let bank = Bank::new_from_parent(&bank, &Pubkey::default(), last_slot + 1);
+        assert!(bank.is_limited_voting_active());
+
+        // through the activation epoch outsiders are still accepted, both outcomes are recorded
+        assert!(bank.is_limited_voting_transition());
+        assert!(!bank.is_limited_voting_enforced());
+        let outsider = validator_keypairs
+            .iter()
+            .find(|keypairs| !in_group(&bank, keypairs))
+            .unwrap();
+        let member = validator_keypairs
+            .iter()
+            .find(|keypairs| in_group(&bank, keypairs))
+            .unwrap();
+        assert_eq!(vote(&bank, outsider), Ok(()));
+        assert_eq!(vote(&bank, member), Ok(()));
+        let slot_votes = bank
+            .vote_group_transition()
+            .slot_votes(bank.parent_slot(), bank.parent_hash())
+            .unwrap();
+        assert_eq!(slot_votes.all.len(), 2);
+        assert!(slot_votes.all.contains(&outsider.vote_keypair.pubkey()));
+        assert_eq!(
+            slot_votes.in_group.into_iter().collect::<Vec<_>>(),
+            vec![member.vote_keypair.pubkey()]
+        );
+        bank.vote_group_transition()
+            .report(bank.parent_slot(), &bank);
+        let summary = bank.vote_group_transition().summary();
+        assert_eq!(summary.reported_slots, 1);
+        assert_eq!(summary.confirmed_slots, 0);
+        assert!(summary.is_clean());
+        assert_eq!(
+            bank.vote_group_transition()
+                .slot_votes(bank.parent_slot(), bank.parent_hash()),
+            None
+        );
+
+        // from the next epoch on outsiders are rejected
+        let last_slot = bank.epoch_schedule().get_last_slot_in_epoch(1);
+        let bank = Arc::new(Bank::new_from_parent(
+            &Arc::new(bank),
+            &Pubkey::default(),
+            last_slot,
+        ));
+        let bank = Bank::new_from_parent(&bank, &Pubkey::default(), last_slot + 1);
+        assert!(bank.is_limited_voting_enforced());
+        let outsider = validator_keypairs
+            .iter()
+            .find(|keypairs| !in_group(&bank, keypairs))
+            .unwrap();
+        assert_eq!(
+            vote(&bank, outsider),
+            Err(TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(VoteError::NotInVoteGroup as u32)
+            ))
+        );
+        let member = validator_keypairs
+            .iter()
+            .find(|keypairs| in_group(&bank, keypairs))
+            .unwrap();
+        assert_eq!(vote(&bank, member), Ok(()));
+    }
+
+    #[test]
+    fn test_vote_group_commit_reveal() {
+        use solana_sdk::vote_group_gen::commit_reveal::MembershipSecrets;
+
+        let validator_keypairs: Vec<_> =
+            (0..20).map(|_| ValidatorVoteKeypairs::new_rand()).collect();
+        let GenesisConfigInfo {
+            genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config_with_vote_accounts(
+            1_000_000_000,
+            &validator_keypairs,
+            vec![10_000; 20],
+        );
+        let bank0 = Arc::new(Bank::new(&genesis_config));
+        let bank = Bank::new_from_parent(&bank0, &Pubkey::default(), 1);
+        let process = |bank: &Bank, instruction: Instruction, keypairs: &ValidatorVoteKeypairs| {
+            let tx = Transaction::new_signed_with_payer(
+                &[instruction],
+                Some(&mint_keypair.pubkey()),
+                &[&mint_keypair, &keypairs.vote_keypair],
+                bank.last_blockhash(),
+            );
+            bank.process_transaction(&tx)
+        };
+
+        // every voter commits an epoch ahead
+        let windows = bank.membership_windows(1).unwrap();
+        assert_eq!(windows as u64, bank.epoch_schedule().get_slots_in_epoch(1));
+        let secrets: Vec<_> = validator_keypairs
+            .iter()
+            .map(|keypairs| {
+                let vote_pubkey = keypairs.vote_keypair.pubkey();
+                MembershipSecrets::new(vote_pubkey.as_ref(), &vote_pubkey, 1, windows)
+            })
+            .collect();
+        for (keypairs, secrets) in validator_keypairs.iter().zip(&secrets) {
+            let vote_pubkey = keypairs.vote_keypair.pubkey();
+            let late = MembershipSecrets::new(&[0], &vote_pubkey, 0, windows).commitment();
+            assert_eq!(
+                process(
+                    &bank,
+                    vote_instruction::commit_group_membership(&vote_pubkey, &vote_pubkey, late),
+                    keypairs
+                ),
+                Err(TransactionError::InstructionError(
+                    0,
+                    InstructionError::Custom(VoteError::MembershipCommitmentNotNextEpoch as u32)
+                ))
+            );
+            assert_eq!(
+                process(
+                    &bank,
+                    vote_instruction::commit_group_membership(
+                        &vote_pubkey,
+                        &vote_pubkey,
+                        secrets.commitment()
+                    ),
+                    keypairs
+                ),
+                Ok(())
+            );
+            let vote_state = VoteState::from(&bank.get_account(&vote_pubkey).unwrap()).unwrap();
+            assert_eq!(
+                vote_state.membership_commitment(1),
+                Some(&secrets.commitment())
+            );
+        }
+
+        // in the committed epoch the voted slot's secret is revealed with the vote
+        let first_slot = bank.epoch_schedule().get_first_slot_in_epoch(1);
+        let bank = Arc::new(Bank::new_from_parent(
+            &Arc::new(bank),
+            &Pubkey::default(),
+            first_slot,
+        ));
+        let bank = Bank::new_from_parent(&bank, &Pubkey::default(), first_slot + 1);
+        let window = bank.membership_window(bank.parent_slot()).unwrap();
+        assert_eq!(window, 0);
+        let generator = bank.epoch_stakes(1).unwrap().get_group_genr().clone();
+        let selected = |secrets: &MembershipSecrets| {
+            generator.vrf_output_selected(
+                secrets
+                    .output(window, &bank.parent_hash())
+                    .unwrap()
+                    .as_ref(),
+            )
+        };
+        let vote = |keypairs: &ValidatorVoteKeypairs, reveal: MembershipReveal| {
+            let vote_pubkey = keypairs.vote_keypair.pubkey();
+            process(
+                &bank,
+                vote_instruction::vote_with_membership_reveal(
+                    &vote_pubkey,
+                    &vote_pubkey,
+                    Vote::new(vec![bank.parent_slot()], bank.parent_hash()),
+                    reveal,
+                ),
+                keypairs,
+            )
+        };
+        let not_in_group = Err(TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(VoteError::NotInVoteGroup as u32),
+        ));
+
+        let (outsider, outsider_secrets) = validator_keypairs
+            .iter()
+            .zip(&secrets)
+            .find(|(_, secrets)| !selected(secrets))
+            .unwrap();
+        assert_eq!(
+            vote(outsider, outsider_secrets.reveal(window).unwrap()),
+            not_in_group
+        );
+        let (member, member_secrets) = validator_keypairs
+            .iter()
+            .zip(&secrets)
+            .find(|(_, secrets)| selected(secrets))
+            .unwrap();
+        // another window's secret doesn't open this one
+        assert_eq!(
+            vote(member, member_secrets.reveal(window + 1).unwrap()),
+            not_in_group
+        );
+        assert_eq!(vote(member, member_secrets.reveal(window).unwrap()), Ok(()));
+    }
+
+    #[test]
+    fn test_vote_group_fee_exemption() {
+        let validator_keypairs: Vec<_> =
+            (0..20).map(|_| ValidatorVoteKeypairs::new_rand()).collect();
+        let GenesisConfigInfo {
+            mut genesis_config,
+            mint_keypair,
+            ..
+        } = create_genesis_config_with_vote_accounts(
+            1_000_000_000,
+            &validator_keypairs,
+            vec![10_000; 20],
+        );
+        genesis_config.fee_rate_governor = FeeRateGovernor::new(4, 0);
+        let fee_paid = |bank: &Bank, tx: &Transaction| {
+            let balance = bank.get_balance(&mint_keypair.pubkey());
+            let _ = bank.process_transaction(tx);
+            balance - bank.get_balance(&mint_keypair.pubkey())
+        };
+        let vote_fee = |bank: &Bank, keypairs: &ValidatorVoteKeypairs| {
+            let tx = Transaction::new_signed_with_payer(
+                &[vote_instruction::vote(
+                    &keypairs.vote_keypair.pubkey(),
+                    &keypairs.vote_keypair.pubkey(),
+                    Vote::new(vec![bank.parent_slot()], bank.parent_hash()),
+                )],
+                Some(&mint_keypair.pubkey()),
+                &[&mint_keypair, &keypairs.vote_keypair],
+                bank.last_blockhash(),
+            );
+            fee_paid(bank, &tx)
+        };
+        let in_group = |bank: &Bank, keypairs: &ValidatorVoteKeypairs| {
+            bank.in_group(
+                bank.parent_slot(),
+                bank.parent_hash(),
+                keypairs.vote_keypair.pubkey(),
+            )
+        };
+
+        let bank0 = Arc::new(Bank::new(&genesis_config));
+        let bank = Bank::new_from_parent(&bank0, &Pubkey::default(), 1);
+        let member = validator_keypairs
+            .iter()
+            .find(|keypairs| in_group(&bank, keypairs))
+            .unwrap();
+        let outsider = validator_keypairs
+            .iter()
+            .find(|keypairs| !in_group(&bank, keypairs))
+            .unwrap();
+        assert_eq!(vote_fee(&bank, member), 0);
+        assert_eq!(bank.collector_fees.load(Relaxed), 0);
+        assert_eq!(vote_fee(&bank, outsider), 8);
+        assert_eq!(bank.collector_fees.load(Relaxed), 8);
+
+        // a vote that fails pays, here the member votes for the same slot again
+        let mut vote = Vote::new(vec![bank.parent_slot()], bank.parent_hash());
+        vote.timestamp = Some(1);
+        let tx = Transaction::new_signed_with_payer(
+            &[vote_instruction::vote(
+                &member.vote_keypair.pubkey(),
+                &member.vote_keypair.pubkey(),
+                vote,
+            )],
+            Some(&mint_keypair.pubkey()),
+            &[&mint_keypair, &member.vote_keypair],
+            bank.last_blockhash(),
+        );
+        assert_eq!(fee_paid(&bank, &tx), 8);
+        assert_eq!(bank.collector_fees.load(Relaxed), 16);
+
+        // so does a vote for a member's account its authorized voter didn't sign
+        let other_member = validator_keypairs
+            .iter()
+            .find(|keypairs| {
+                keypairs.vote_keypair.pubkey() != member.vote_keypair.pubkey()
+                    && in_group(&bank, keypairs)
+            })
+            .unwrap();
+        let mut unsigned = vote_instruction::vote(
+            &other_member.vote_keypair.pubkey(),
+            &other_member.vote_keypair.pubkey(),
+            Vote::new(vec![bank.parent_slot()], bank.parent_hash()),
+        );
+        unsigned
+            .accounts
+            .iter_mut()
+            .for_each(|meta| meta.is_signer = false);
+        let tx = Transaction::new_signed_with_payer(
+            &[unsigned],
+            Some(&mint_keypair.pubkey()),
+            &[&mint_keypair],
+            bank.last_blockhash(),
+        );
+        assert_eq!(
+            bank.process_transaction(&tx),
+            Err(TransactionError::InstructionError(
+                0,
+                InstructionError::MissingRequiredSignature
+            ))
+        );
+        // one signature to pay for
+        assert_eq!(bank.collector_fees.load(Relaxed), 20);
+
+        // and a vote whose hash isn't the voted bank's is never exempt
+        let tx = Transaction::new_signed_with_payer(
+            &[vote_instruction::vote(
+                &other_member.vote_keypair.pubkey(),
+                &other_member.vote_keypair.pubkey(),
+                Vote::new(vec![bank.parent_slot()], Hash::new_unique()),
+            )],
+            Some(&mint_keypair.pubkey()),
+            &[&mint_keypair, &other_member.vote_keypair],
+            bank.last_blockhash(),
+        );
+        let fee_calculator = bank.get_fee_calculator(&bank.last_blockhash()).unwrap();
+        assert_eq!(bank.transaction_fee(&fee_calculator, &tx, &Ok(())), 8);
+        assert_eq!(fee_paid(&bank, &tx), 8);
+
+        // anything bundled with the vote pays in full
+        let tx = Transaction::new_signed_with_payer(
+            &[
+                vote_instruction::vote(
+                    &member.vote_keypair.pubkey(),
+                    &member.vote_keypair.pubkey(),
+                    Vote::new(vec![bank.parent_slot()], bank.parent_hash()),
+                ),
+                system_instruction::transfer(&mint_keypair.pubkey(), &Pubkey::new_unique(), 1),
+            ],
+            Some(&mint_keypair.pubkey()),
+            &[&mint_keypair, &member.vote_keypair],
+            bank.last_blockhash(),
+        );
+        assert_eq!(bank.transaction_fee(&fee_calculator, &tx, &Ok(())), 8);
+
+        // without the feature members pay too
+        genesis_config
+            .accounts
+            .remove(&feature_set::vote_group_fee_exemption::id());
+        let bank0 = Arc::new(Bank::new(&genesis_config));
+        let bank = Bank::new_from_parent(&bank0, &Pubkey::default(), 1);
+        let member = validator_keypairs
+            .iter()
+            .find(|keypairs| in_group(&bank, keypairs))
+            .unwrap();
+        assert_eq!(vote_fee(&bank, member), 8);
+    }
+
+    #[test]
+    fn test_voter_set_root_in_bank_hash() {
+        let validator_keypairs: Vec<_> =
+            (0..3).map(|_| ValidatorVoteKeypairs::new_rand()).collect();
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config_with_vote_accounts(
+            1_000_000_000,
+            &validator_keypairs,
+            vec![10_000; 3],
+        );
+        let mut bank = Bank::new(&genesis_config);
+        // the group commitment is hashed in after the root
+        bank.deactivate_feature(&feature_set::vote_group_in_bank_hash::id());
+        let hash = bank.hash_internal_state();
+        bank.deactivate_feature(&feature_set::voter_set_root_in_bank_hash::id());
+        let hash_without_root = bank.hash_internal_state();
+        let voter_set_root = bank
+            .epoch_stakes(bank.epoch())
+            .unwrap()
+            .get_group_genr()
+            .voter_set_root();
+        assert_ne!(hash, hash_without_root);
+        assert_eq!(
+            hash,
+            extend_and_hash(&hash_without_root, voter_set_root.as_ref())
+        );
+    }
+
+    #[test]
+    fn test_vote_group_in_bank_hash() {
+        let validator_keypairs: Vec<_> =
+            (0..3).map(|_| ValidatorVoteKeypairs::new_rand()).collect();
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config_with_vote_accounts(
+            1_000_000_000,
+            &validator_keypairs,
+            vec![10_000; 3],
+        );
+        let mut bank = Bank::new(&genesis_config);
+        let hash = bank.hash_internal_state();
+        let commitment = bank.vote_group_commitment().unwrap();
+        bank.deactivate_feature(&feature_set::vote_group_in_bank_hash::id());
+        let hash_without_group = bank.hash_internal_state();
+        assert_eq!(
+            hash,
+            extend_and_hash(&hash_without_group, commitment.as_ref())
+        );
+
+        // a group seeded from the bank's own hash can't be committed to
+        bank.deactivate_feature(&feature_set::vote_group_slot_seeds::id());
+        assert_eq!(bank.vote_group_commitment(), None);
+    }
+
+    #[test]
+    fn test_previous_epoch_assignments() {
+        let validator_keypairs: Vec<_> =
+            (0..5).map(|_| ValidatorVoteKeypairs::new_rand()).collect();
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config_with_vote_accounts(
+            1_000_000_000,
+            &validator_keypairs,
+            vec![10_000; 5],
+        );
+        let bank0 = Arc::new(Bank::new(&genesis_config));
+        assert_eq!(bank0.previous_epoch_assignments(), None);
+        assert!(bank0.vote_group_params().absence.is_some());
+
+        let first_slot = bank0.epoch_schedule().get_first_slot_in_epoch(1);
+        let mut bank = Bank::new_from_parent(&bank0, &Pubkey::default(), first_slot);
+        let assignments = bank.previous_epoch_assignments().unwrap();
+        assert_eq!(assignments.epoch, 0);
+        let generator = bank.epoch_stakes(0).unwrap().get_group_genr();
+        assert_eq!(
+            assignments.assigned_slots.values().sum::<u64>(),
+            first_slot * generator.group_size() as u64
+        );
+        // the epoch was walked once, for the new epoch stakes, rewards and other forks reuse it
+        assert!(Arc::ptr_eq(
+            &assignments,
+            &bank.epoch_assignments(0).unwrap()
+        ));
+        let fork = Bank::new_from_parent(&bank0, &Pubkey::default(), first_slot + 1);
+        assert!(Arc::ptr_eq(
+            &assignments,
+            &fork.previous_epoch_assignments().unwrap()
+        ));
+
+        bank.deactivate_feature(&feature_set::vote_group_slot_seeds::id());
+        assert_eq!(bank.previous_epoch_assignments(), None);
+        bank.deactivate_feature(&feature_set::vote_group_credits::id());
+        assert_eq!(bank.vote_group_params().absence, None);
+    }
+
+    #[test]
+    fn test_vote_group_participation_rewards() {
+        let validator_keypairs: Vec<_> =
+            (0..4).map(|_| ValidatorVoteKeypairs::new_rand()).collect();
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config_with_vote_accounts(
+            1_000_000_000,
+            &validator_keypairs,
+            vec![10_000; 4],
+        );
+        let bank0 = Arc::new(Bank::new(&genesis_config));
+        let first_slot = bank0.epoch_schedule().get_first_slot_in_epoch(1);
+        let full_credits = bank0.get_slots_in_epoch(0);
+
+        // pays 1_000_000 lamports for epoch 0 and returns what each validator earned, one
+        //  voted on all of its assigned slots, one on half, one on none and one voted twice
+        //  as often as it was assigned
+        let pay = |slot: Slot, participation_rewards: bool| {
+            let mut bank = Bank::new_from_parent(&bank0, &Pubkey::default(), slot);
+            if !participation_rewards {
+                bank.deactivate_feature(&feature_set::vote_group_participation_rewards::id());
+            }
+            let assignments = bank.epoch_assignments(0).unwrap();
+            let assigned: Vec<_> = validator_keypairs
+                .iter()
+                .map(|keypairs| assignments.assigned_slots[&keypairs.vote_keypair.pubkey()])
+                .collect();
+            let pre_balances: Vec<_> = validator_keypairs
+                .iter()
+                .zip([assigned[0], assigned[1] / 2, 0, assigned[3] * 2].iter())
+                .map(|(keypairs, credits)| {
+                    let vote_pubkey = keypairs.vote_keypair.pubkey();
+                    let mut vote_account = bank.get_account(&vote_pubkey).unwrap();
+                    let mut vote_state = VoteState::from(&vote_account).unwrap();
+                    vote_state.increment_credits_by(0, *credits);
+                    VoteState::to(
+                        &VoteStateVersions::new_current(vote_state),
+                        &mut vote_account,
+                    )
+                    .unwrap();
+                    bank.store_account(&vote_pubkey, &vote_account);
+                    vote_account.lamports + bank.get_balance(&keypairs.stake_keypair.pubkey())
+                })
+                .collect();
+            bank.pay_validator_rewards(0, None, 1_000_000, &mut null_tracer(), true);
+            let earned: Vec<_> = validator_keypairs
+                .iter()
+                .zip(pre_balances)
+                .map(|(keypairs, pre_balance)| {
+                    bank.get_balance(&keypairs.vote_keypair.pubkey())
+                        + bank.get_balance(&keypairs.stake_keypair.pubkey())
+                        - pre_balance
+                })
+                .collect();
+            assert!(earned.iter().sum::<u64>() <= 1_000_000);
+            (assigned, earned)
+        };
+
+        let (assigned, earned) = pay(first_slot, true);
+        assert!(earned[0] > 0);
+        assert_eq!(earned[3], earned[0]);
+        assert_eq!(earned[2], 0);
+        let half_credits = full_credits * (assigned[1] / 2) / assigned[1];
+        assert!((earned[1] as i64 - (earned[0] * half_credits / full_credits) as i64).abs() <= 1);
+
+        // credits alone pay per vote
+        let (assigned, earned) = pay(first_slot + 1, false);
+        assert_eq!(earned[2], 0);
+        assert!(
+            (earned[3] as i64 * assigned[0] as i64 - earned[0] as i64 * 2 * assigned[3] as i64)
+                .abs()
+                <= (assigned[0] + 2 * assigned[3]) as i64
+        );
+    }
+
+    #[test]
+    fn test_vote_group_admin_params() {
+        let validator_keypairs: Vec<_> =
+            (0..10).map(|_| ValidatorVoteKeypairs::new_rand()).collect();
+        let GenesisConfigInfo {
+            mut genesis_config, ..
+        } = create_genesis_config_with_vote_accounts(
+            1_000_000_000,
+            &validator_keypairs,
+            vec![10_000; 10],
+        );
+        genesis_config.add_account(
+            vote_group_config::id(),
+            vote_group_config::create_account(
+                1_000_000,
+                &Pubkey::new_unique(),
+                &vote_group_config::Config {
+                    group_size: 5,
+                    ..vote_group_config::Config::default()
+                },
+            ),
+        );
+        let three_quarters = QuorumFraction {
+            numerator: 3,
+            denominator: 4,
+        };
+        let mut state = vote_group_admin::AdminState::new(
+            vote_group_admin::MultisigAuthority::new(vec![Pubkey::new_unique()], 1),
+        );
+        state.staged.group_size = Some(3);
+        state.staged.quorum_fraction = Some(three_quarters);
+        state.effective_epoch = 1;
+        genesis_config.add_account(
+            vote_group_admin::params::id(),
+            vote_group_admin::create_account(1_000_000, &state),
+        );
+        let bank = Arc::new(Bank::new(&genesis_config));
+        assert_eq!(bank.vote_group_quorum_fraction(), QuorumFraction::default());
+
+        // the staged params win over the config account from the next epoch on
+        let slot = bank.epoch_schedule().get_first_slot_in_epoch(1);
+        let bank = Bank::new_from_parent(&bank, &Pubkey::default(), slot);
+        let epoch = bank.get_leader_schedule_epoch(slot);
+        assert_eq!(
+            bank.epoch_stakes(epoch)
+                .unwrap()
+                .get_group_genr()
+                .group_size(),
+            3
+        );
+        assert_eq!(bank.vote_group_quorum_fraction(), three_quarters);
+    }
+
+    #[test]
+    fn test_vote_group_generator_shared_within_epoch() {
+        let validator_keypairs: Vec<_> =
+            (0..10).map(|_| ValidatorVoteKeypairs::new_rand()).collect();
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config_with_vote_accounts(
+            1_000_000_000,
+            &validator_keypairs,
+            vec![10_000; 10],
+        );
+        let bank0 = Arc::new(Bank::new(&genesis_config));
+        let bank1 = Bank::new_from_parent(&bank0, &Pubkey::default(), 1);
+        assert!(Arc::ptr_eq(
+            &bank0.vote_group_generator(),
+            &bank1.vote_group_generator()
+        ));
+
+        let slot = bank0.epoch_schedule().get_first_slot_in_epoch(1);
+        let bank2 = Bank::new_from_parent(&bank0, &Pubkey::default(), slot);
+        assert_eq!(bank2.epoch(), 1);
+        assert!(Arc::ptr_eq(
+            &bank2.vote_group_generator(),
+            bank0.epoch_stakes(1).unwrap().vote_group_generator()
+        ));
+    }
+
+    #[test]
+    fn test_vote_group_preset() {
+        let validator_keypairs: Vec<_> =
+            (0..10).map(|_| ValidatorVoteKeypairs::new_rand()).collect();
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config_with_vote_accounts(
+            1_000_000_000,
+            &validator_keypairs,
+            vec![10_000; 10],
+        );
+        // members don't have to hold a vote account
+        let mut members: Vec<_> = validator_keypairs[..2]
+            .iter()
+            .map(|keypairs| keypairs.vote_keypair.pubkey())
+            .collect();
+        members.push(solana_sdk::pubkey::new_rand());
+        let mut bank = Bank::new(&genesis_config);
+        bank.set_vote_group_overrides(Some(VoteGroupOverrides {
+            members: Some(members.clone()),
+            seed: Some(42),
+            ..VoteGroupOverrides::default()
+        }));
+        assert_eq!(bank.vote_group_preset_seed(), Some(42));
+
+        let bank = Arc::new(bank);
+        let slot = bank.epoch_schedule().get_first_slot_in_epoch(1) + 3;
+        let bank = Bank::new_from_parent(&bank, &Pubkey::default(), slot);
+        let (generator, seed) = bank.vote_group_for_slot(slot).unwrap();
+        assert_eq!(seed, 42);
+        let mut group = generator.try_group_for_seed(seed).unwrap();
+        group.sort();
+        members.sort();
+        assert_eq!(group, members);
+        assert_eq!(
+            bank.vote_group_for_slot(slot).unwrap().1,
+            bank.vote_group_for_slot(slot - 1).unwrap().1
+        );
+    }
+
+    #[test]
+    fn test_vote_group_overrides() {
+        let validator_keypairs: Vec<_> =
+            (0..10).map(|_| ValidatorVoteKeypairs::new_rand()).collect();
+        let GenesisConfigInfo {
+            mut genesis_config, ..
+        } = create_genesis_config_with_vote_accounts(
+            1_000_000_000,
+            &validator_keypairs,
+            vec![10_000; 10],
+        );
+        let overrides = VoteGroupOverrides {
+            group_size: Some(4),
+            algorithm: Some(SelectionAlgorithm::V2),
+            ..VoteGroupOverrides::default()
+        };
+
+        // the genesis epochs are rebuilt and later epochs inherit the overrides
+        let mut bank = Bank::new(&genesis_config);
+        bank.set_vote_group_overrides(Some(overrides.clone()));
+        let genesis_epoch = bank.get_leader_schedule_epoch(0);
+        let generator = bank.epoch_stakes(genesis_epoch).unwrap().get_group_genr();
+        assert_eq!(generator.group_size(), 4);
+        assert_eq!(generator.algorithm(), SelectionAlgorithm::V2);
+        let bank = Arc::new(bank);
+        let slot = bank.epoch_schedule().get_first_slot_in_epoch(1);
+        let bank = Bank::new_from_parent(&bank, &Pubkey::default(), slot);
+        let epoch = bank.get_leader_schedule_epoch(slot);
+        assert!(epoch > genesis_epoch);
+        let generator = bank.epoch_stakes(epoch).unwrap().get_group_genr();
+        assert_eq!(generator.group_size(), 4);
+        assert_eq!(generator.algorithm(), SelectionAlgorithm::V2);
+
+        // every node has to agree on the groups of a real cluster
+        genesis_config.cluster_type = ClusterType::MainnetBeta;
+        let mut bank = Bank::new(&genesis_config);
+        bank.set_vote_group_overrides(Some(overrides));
+        assert_eq!(
+            bank.epoch_stakes(genesis_epoch)
+                .unwrap()
+                .get_group_genr()
+                .group_size(),
+            OPTIMAL_VOTE_GROUP_SIZE
+        );
+    }
+
+    #[test]
+    fn test_vote_group_sysvar() {
+        let validator_keypairs: Vec<_> =
+            (0..20).map(|_| ValidatorVoteKeypairs::new_rand()).collect();
+        let GenesisConfigInfo {
+            mut genesis_config, ..
+        } = create_genesis_config_with_vote_accounts(
+            1_000_000_000,
+            &validator_keypairs,
+            vec![10_000; 20],
+        );
+        let bank0 = Arc::new(Bank::new(&genesis_config));
+        let bank = Bank::new_from_parent(&bank0, &Pubkey::default(), 1);
+        assert!(bank
+            .feature_set
+            .is_active(&feature_set::vote_group_sysvar::id()));
+        let vote_group: sysvar::vote_group::VoteGroup =
+            from_account(&bank.get_account(&sysvar::vote_group::id()).unwrap()).unwrap();
+        let (slot, hash) = if bank
+            .feature_set
+            .is_active(&feature_set::vote_group_slot_seeds::id())
+        {
+            (bank.slot(), Hash::default())
+        } else {
+            (bank.parent_slot(), bank.parent_hash())
+        };
+        assert_eq!(vote_group.slot, slot);
+        assert_eq!(vote_group.rotation_window_slots, 1);
+        assert_eq!(vote_group.window_start, slot);
+        assert!(!vote_group.members.is_empty());
+        for keypairs in &validator_keypairs {
+            let key = keypairs.vote_keypair.pubkey();
+            assert_eq!(bank.in_group(slot, hash, key), vote_group.contains(&key));
+        }
+
+        // nothing is published until the feature is active
+        genesis_config
+            .accounts
+            .remove(&feature_set::vote_group_sysvar::id());
+        let bank0 = Arc::new(Bank::new(&genesis_config));
+        let bank = Bank::new_from_parent(&bank0, &Pubkey::default(), 1);
+        assert!(bank.get_account(&sysvar::vote_group::id()).is_none());
+    }
+
+    #[test]
+    fn test_vote_group_change_notifier() {
+        #[derive(Debug, Default)]
+        struct VoteGroupRecorder(Mutex<Vec<(Slot, Vec<Pubkey>, u64)>>);
+        impl AccountsUpdateNotifierInterface for VoteGroupRecorder {
+            fn notify_vote_group_change(&self, slot: Slot, members: &[Pubkey], seed: u64) {
+                self.0.lock().unwrap().push((slot, members.to_vec(), seed));
+            }
+        }
+
+        let validator_keypairs: Vec<_> =
+            (0..20).map(|_| ValidatorVoteKeypairs::new_rand()).collect();
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config_with_vote_accounts(
+            1_000_000_000,
+            &validator_keypairs,
+            vec![10_000; 20],
+        );
+        let recorder = Arc::new(RwLock::new(VoteGroupRecorder::default()));
+        let bank0 = Arc::new(Bank::new(&genesis_config));
+        bank0.set_accounts_update_notifier(Some(recorder.clone()));
+
+        // children hand the notifier on, every bank reports the group it publishes
+        let bank1 = Arc::new(Bank::new_from_parent(&bank0, &Pubkey::default(), 1));
+        let bank2 = Bank::new_from_parent(&bank1, &Pubkey::default(), 2);
+        let published: Vec<_> = [bank1.as_ref(), &bank2]
+            .iter()
+            .map(|bank| {
+                let vote_group: sysvar::vote_group::VoteGroup =
+                    from_account(&bank.get_account(&sysvar::vote_group::id()).unwrap()).unwrap();
+                (vote_group.slot, vote_group.members, vote_group.seed)
+            })
+            .collect();
+        assert_eq!(*recorder.read().unwrap().0.lock().unwrap(), published);
+    }
 }
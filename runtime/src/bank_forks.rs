@@ -225,6 +225,9 @@ impl BankForks {
                     .node_id_to_vote_accounts()
             );
         }
+        if root_bank.is_limited_voting_transition() {
+            root_bank.vote_group_transition().report(root, root_bank);
+        }
         let root_tx_count = root_bank
             .parents()
             .last()
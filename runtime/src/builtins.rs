@@ -3,6 +3,7 @@ use crate::{
     system_instruction_processor,
 };
 use solana_sdk::{
+    feature_set,
     instruction::InstructionError,
     keyed_account::KeyedAccount,
     process_instruction::{stable_log, InvokeContext, ProcessInstructionWithContext},
@@ -92,7 +93,15 @@ pub enum ActivationType {
 /// normal child Bank creation.
 /// https://github.com/solana-labs/solana/blob/84b139cc94b5be7c9e0c18c2ad91743231b85a0d/runtime/src/bank.rs#L1723
 fn feature_builtins() -> Vec<(Builtin, Pubkey, ActivationType)> {
-    vec![]
+    vec![(
+        Builtin::new(
+            "vote_group_admin_program",
+            solana_vote_program::vote_group_admin::id(),
+            with_program_logging!(solana_vote_program::vote_group_admin::process_instruction),
+        ),
+        feature_set::vote_group_admin_program::id(),
+        ActivationType::NewProgram,
+    )]
 }
 
 pub(crate) fn get() -> Builtins {
@@ -36,7 +36,6 @@ impl BlockCommitment {
 }
 
 /// A node's view of cluster commitment as per a particular bank
-#[derive(Default)]
 pub struct BlockCommitmentCache {
     /// Map of all commitment levels of current ancestor slots, aggregated from the vote account
     /// data in the bank
@@ -46,6 +45,20 @@ pub struct BlockCommitmentCache {
     commitment_slots: CommitmentSlots,
     /// Total stake active during the bank's epoch
     total_stake: u64,
+    /// Share of the stake that confirms a slot, the vote group quorum fraction once votes are
+    /// limited to each slot's group
+    confirmation_threshold: f64,
+}
+
+impl Default for BlockCommitmentCache {
+    fn default() -> Self {
+        Self {
+            block_commitment: HashMap::default(),
+            commitment_slots: CommitmentSlots::default(),
+            total_stake: 0,
+            confirmation_threshold: VOTE_THRESHOLD_SIZE,
+        }
+    }
 }
 
 impl std::fmt::Debug for BlockCommitmentCache {
@@ -72,6 +85,7 @@ impl BlockCommitmentCache {
             block_commitment,
             commitment_slots,
             total_stake,
+            confirmation_threshold: VOTE_THRESHOLD_SIZE,
         }
     }
 
@@ -83,6 +97,10 @@ impl BlockCommitmentCache {
         self.total_stake
     }
 
+    pub fn confirmation_threshold(&self) -> f64 {
+        self.confirmation_threshold
+    }
+
     pub fn slot(&self) -> Slot {
         self.commitment_slots.slot
     }
@@ -139,7 +157,7 @@ impl BlockCommitmentCache {
     }
 
     pub fn get_confirmation_count(&self, slot: Slot) -> Option<usize> {
-        self.get_lockout_count(slot, VOTE_THRESHOLD_SIZE)
+        self.get_lockout_count(slot, self.confirmation_threshold)
     }
 
     // Returns the lowest level at which at least `minimum_stake_percentage` of the total epoch
@@ -180,9 +198,14 @@ impl BlockCommitmentCache {
                 highest_confirmed_slot: root,
                 highest_confirmed_root: root,
             },
+            ..Self::default()
         }
     }
 
+    pub fn set_confirmation_threshold(&mut self, threshold: f64) {
+        self.confirmation_threshold = threshold;
+    }
+
     pub fn set_highest_confirmed_slot(&mut self, slot: Slot) {
         self.commitment_slots.highest_confirmed_slot = slot;
     }
@@ -258,7 +281,7 @@ mod tests {
         block_commitment.entry(0).or_insert(cache0);
         block_commitment.entry(1).or_insert(cache1);
         block_commitment.entry(2).or_insert(cache2);
-        let block_commitment_cache = BlockCommitmentCache {
+        let mut block_commitment_cache = BlockCommitmentCache {
             block_commitment,
             total_stake: 50,
             ..BlockCommitmentCache::default()
@@ -268,6 +291,11 @@ mod tests {
         assert_eq!(block_commitment_cache.get_confirmation_count(1), Some(1));
         assert_eq!(block_commitment_cache.get_confirmation_count(2), Some(0),);
         assert_eq!(block_commitment_cache.get_confirmation_count(3), None,);
+
+        // a higher threshold needs the shallower votes too
+        block_commitment_cache.set_confirmation_threshold(0.85);
+        assert_eq!(block_commitment_cache.get_confirmation_count(0), Some(1));
+        assert_eq!(block_commitment_cache.get_confirmation_count(1), Some(1));
     }
 
     #[test]
@@ -1,9 +1,22 @@
 use crate::{stakes::Stakes, vote_account::ArcVoteAccount};
 use serde::{Deserialize, Serialize};
-use solana_sdk::{clock::Epoch, pubkey::Pubkey};
-use solana_sdk::vote_group_gen::{VoteGroupGenerator,OPTIMAL_VOTE_GROUP_SIZE};
+use solana_sdk::{
+    clock::{Epoch, Slot},
+    hash::Hash,
+    pubkey::Pubkey,
+};
+use solana_sdk::vote_group_gen::{
+    AbsenceThreshold, EpochAnchoredSeed, ExclusionList, LegacyVoteGroupGenerator, MinimumStake,
+    SelectionMode, VoteGroupError, VoteGroupGenerator, VoteGroupParams, WindowedSeed,
+    DEFAULT_ROTATION_WINDOW_SLOTS,
+};
+use solana_stake_program::stake_state::ParticipationCredits;
 
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    sync::{Arc, RwLock},
+};
 
 pub type NodeIdToVoteAccounts = HashMap<Pubkey, NodeVoteAccounts>;
 pub type EpochAuthorizedVoters = HashMap<Pubkey, Pubkey>;
@@ -20,32 +33,307 @@ pub struct EpochStakes {
     total_stake: u64,
     node_id_to_vote_accounts: Arc<NodeIdToVoteAccounts>,
     epoch_authorized_voters: Arc<EpochAuthorizedVoters>,
-    vote_group_gen: VoteGroupGenerator,
+    vote_group_gen: Arc<VoteGroupGenerator>,
+    vote_group_anchor: Hash,
+    rotation_window_slots: u64,
+}
+
+/// what the bank knew about recent voting when it captured an epoch's stakes
+#[derive(Clone, Copy, Debug)]
+pub struct VoteActivity<'a> {
+    /// the slot the stakes were captured at, last votes are measured against it
+    pub slot: Slot,
+    /// the voters of the previous epoch's generator, an authorized voter missing from it
+    /// has to meet the reentry distance to be picked
+    pub previous_voters: Option<&'a [Pubkey]>,
+    /// the slots the previous epoch's groups assigned, absent voters are only left out when
+    /// they are known
+    pub assignments: Option<&'a EpochAssignments>,
+}
+
+/// how many slots an epoch's groups assigned every vote account. the credits its vote state
+/// earned in the epoch count the ones it voted on, with vote group credits only votes for
+/// slots it was assigned earn any
+#[derive(Clone, Debug, Default, PartialEq, Eq, AbiExample)]
+pub struct EpochAssignments {
+    pub epoch: Epoch,
+    pub assigned_slots: HashMap<Pubkey, u64>,
+}
+
+impl EpochAssignments {
+    /// walks the group of every slot of `epoch`, `first_slot..=last_slot`
+    pub fn new(
+        epoch_stakes: &EpochStakes,
+        epoch: Epoch,
+        first_slot: Slot,
+        last_slot: Slot,
+    ) -> Self {
+        Self {
+            epoch,
+            assigned_slots: epoch_stakes.get_group_genr().assigned_slots(
+                &epoch_stakes.windowed_seed_source(epoch, first_slot),
+                first_slot..=last_slot,
+            ),
+        }
+    }
+
+    fn voted(&self, epoch_credits: &[(Epoch, u64, u64)]) -> u64 {
+        epoch_credits
+            .iter()
+            .find(|(epoch, _, _)| *epoch == self.epoch)
+            .map_or(0, |(_, credits, prev_credits)| {
+                credits.saturating_sub(*prev_credits)
+            })
+    }
+
+    fn is_absent(
+        &self,
+        key: &Pubkey,
+        epoch_credits: &[(Epoch, u64, u64)],
+        threshold: &AbsenceThreshold,
+    ) -> bool {
+        let assigned = self.assigned_slots.get(key).copied().unwrap_or(0);
+        threshold.is_absent(assigned, self.voted(epoch_credits))
+    }
+
+    /// the credits `key` is owed for the epoch when rewards follow participation: the share
+    /// of its assigned slots it voted on, out of `full_credits`. None if it was assigned none
+    pub fn participation_credits(
+        &self,
+        key: &Pubkey,
+        epoch_credits: &[(Epoch, u64, u64)],
+        full_credits: u64,
+    ) -> Option<ParticipationCredits> {
+        let assigned = self.assigned_slots.get(key).copied().filter(|n| *n > 0)?;
+        let voted = self.voted(epoch_credits).min(assigned);
+        Some(ParticipationCredits {
+            epoch: self.epoch,
+            credits: (u128::from(full_credits) * u128::from(voted) / u128::from(assigned)) as u64,
+        })
+    }
+}
+
+/// the assignments of the epochs walked so far, shared by every bank so an epoch's groups
+/// are walked once. they are keyed by the anchor too, forks that captured the epoch's stakes
+/// at different banks don't share them
+#[derive(AbiExample, Debug, Default)]
+pub struct EpochAssignmentsCache {
+    assignments: RwLock<HashMap<(Epoch, Hash), Arc<EpochAssignments>>>,
+}
+
+impl EpochAssignmentsCache {
+    /// the assignments of `epoch_stakes`, walked by `walk` the first time they're asked for.
+    /// epochs before the one before `epoch` are dropped then
+    pub fn get_or_insert_with<F>(
+        &self,
+        epoch: Epoch,
+        epoch_stakes: &EpochStakes,
+        walk: F,
+    ) -> Arc<EpochAssignments>
+    where
+        F: FnOnce() -> EpochAssignments,
+    {
+        let key = (epoch, *epoch_stakes.vote_group_anchor());
+        if let Some(assignments) = self.assignments.read().unwrap().get(&key) {
+            return assignments.clone();
+        }
+        let walked = Arc::new(walk());
+        let mut assignments = self.assignments.write().unwrap();
+        assignments.retain(|(cached_epoch, _), _| *cached_epoch + 1 >= epoch);
+        assignments.entry(key).or_insert(walked).clone()
+    }
 }
 
 impl EpochStakes {
     pub fn new(stakes: &Stakes, leader_schedule_epoch: Epoch) -> Self {
+        Self::new_with_vote_group_params(
+            stakes,
+            leader_schedule_epoch,
+            &VoteGroupParams::default(),
+            Hash::default(),
+        )
+    }
+
+    /// `vote_group_anchor` is the bank hash the epoch's per slot group seeds are derived from,
+    /// it is captured here, an epoch ahead, so nobody can steer it once the epoch is near
+    pub fn new_with_vote_group_params(
+        stakes: &Stakes,
+        leader_schedule_epoch: Epoch,
+        params: &VoteGroupParams,
+        vote_group_anchor: Hash,
+    ) -> Self {
+        Self::new_with_vote_activity(
+            stakes,
+            leader_schedule_epoch,
+            params,
+            vote_group_anchor,
+            None,
+        )
+    }
+
+    /// same as new_with_vote_group_params, delinquent voters are only left out when the
+    /// `vote_activity` at capture is known
+    pub fn new_with_vote_activity(
+        stakes: &Stakes,
+        leader_schedule_epoch: Epoch,
+        params: &VoteGroupParams,
+        vote_group_anchor: Hash,
+        vote_activity: Option<VoteActivity>,
+    ) -> Self {
         let epoch_vote_accounts = Stakes::vote_accounts(stakes);
         let (total_stake, node_id_to_vote_accounts, epoch_authorized_voters) =
-            Self::parse_epoch_vote_accounts(&epoch_vote_accounts, leader_schedule_epoch);  
-            let group_size = 
-            if epoch_authorized_voters.len() < OPTIMAL_VOTE_GROUP_SIZE 
-                { epoch_authorized_voters.len()} 
-            else 
-                { OPTIMAL_VOTE_GROUP_SIZE }; 
-            let gen : VoteGroupGenerator = VoteGroupGenerator::new(&epoch_authorized_voters,group_size) ;
-            Self {
+            Self::parse_epoch_vote_accounts(&epoch_vote_accounts, leader_schedule_epoch);
+        // penalized, dust and delinquent accounts only leave the groups, they keep voting and
+        // stay in epoch_authorized_voters
+        let mut exclusions = params.exclusions.clone();
+        let mut num_excluded = 0;
+        if params.exclude_penalized {
+            for (key, (_, account)) in epoch_vote_accounts.iter() {
+                if epoch_authorized_voters.contains_key(key)
+                    && account.vote_state().as_ref().map_or(false, |vote_state| {
+                        vote_state.is_excluded_from_vote_groups(leader_schedule_epoch)
+                    })
+                    && exclusions.add(*key)
+                {
+                    num_excluded += 1;
+                }
+            }
+        }
+        if params.minimum_stake != MinimumStake::None {
+            for (key, (stake, _)) in epoch_vote_accounts.iter() {
+                if epoch_authorized_voters.contains_key(key)
+                    && !params.minimum_stake.is_met(*stake, total_stake)
+                    && exclusions.add(*key)
+                {
+                    num_excluded += 1;
+                }
+            }
+        }
+        if let (Some(threshold), Some(vote_activity)) = (params.delinquency, vote_activity) {
+            let delinquent: Vec<Pubkey> = epoch_vote_accounts
+                .iter()
+                .filter(|(key, (_, account))| {
+                    epoch_authorized_voters.contains_key(key)
+                        && !exclusions.contains(key)
+                        && account.vote_state().as_ref().map_or(false, |vote_state| {
+                            let was_delinquent = vote_activity
+                                .previous_voters
+                                .map_or(false, |voters| voters.binary_search(key).is_err());
+                            threshold.is_delinquent(
+                                vote_state.last_voted_slot(),
+                                vote_activity.slot,
+                                was_delinquent,
+                            )
+                        })
+                })
+                .map(|(key, _)| *key)
+                .collect();
+            // after a cluster wide stall every last vote is old, leaving everyone out would
+            // leave nobody to vote the cluster back up
+            if delinquent.len() < epoch_authorized_voters.len() - num_excluded {
+                for key in delinquent {
+                    exclusions.add(key);
+                    num_excluded += 1;
+                }
+            }
+        }
+        if let (Some(threshold), Some(assignments)) = (
+            params.absence,
+            vote_activity.and_then(|vote_activity| vote_activity.assignments),
+        ) {
+            let absent: Vec<Pubkey> = epoch_vote_accounts
+                .iter()
+                .filter(|(key, (_, account))| {
+                    epoch_authorized_voters.contains_key(key)
+                        && !exclusions.contains(key)
+                        && account.vote_state().as_ref().map_or(false, |vote_state| {
+                            assignments.is_absent(key, vote_state.epoch_credits(), &threshold)
+                        })
+                })
+                .map(|(key, _)| *key)
+                .collect();
+            // same as for delinquency, the groups never run out of voters
+            let eligible = epoch_authorized_voters
+                .keys()
+                .filter(|key| !exclusions.contains(key))
+                .count();
+            if absent.len() < eligible {
+                for key in absent {
+                    exclusions.add(key);
+                    num_excluded += 1;
+                }
+            }
+        }
+        // after the other exclusions, so a left out account doesn't shadow its live siblings
+        if params.dedup_identities {
+            num_excluded += add_identity_duplicates(
+                &mut exclusions,
+                &node_id_to_vote_accounts,
+                &epoch_vote_accounts,
+            );
+        }
+        let num_voters = epoch_authorized_voters.len() - num_excluded;
+        let gen = build_vote_group_gen(
+            &epoch_authorized_voters,
+            &epoch_vote_accounts,
+            params,
+            &exclusions,
+            num_voters,
+        );
+        Self {
             stakes: Arc::new(stakes.clone()),
             total_stake,
             node_id_to_vote_accounts: Arc::new(node_id_to_vote_accounts),
             epoch_authorized_voters: Arc::new(epoch_authorized_voters),
-            vote_group_gen: gen,
-       }
+            vote_group_gen: Arc::new(gen),
+            vote_group_anchor,
+            rotation_window_slots: params.rotation_window_slots.max(1),
+        }
     }
+
     pub fn get_group_genr(&self) -> &VoteGroupGenerator {
         &self.vote_group_gen
     }
 
+    /// the generator is built once per epoch, every bank of the epoch shares it and the
+    /// groups it has already cached
+    pub fn vote_group_generator(&self) -> &Arc<VoteGroupGenerator> {
+        &self.vote_group_gen
+    }
+
+    /// snapshots leave the generator's authorized voters out, they are the epoch's
+    pub(crate) fn restore_authorized_voters(&mut self) {
+        Arc::make_mut(&mut self.vote_group_gen)
+            .set_authorized_voters(&self.epoch_authorized_voters);
+    }
+
+    pub fn vote_group_anchor(&self) -> &Hash {
+        &self.vote_group_anchor
+    }
+
+    /// where the group seeds of `epoch`'s slots come from
+    pub fn seed_source(&self, epoch: Epoch) -> EpochAnchoredSeed {
+        EpochAnchoredSeed::new(epoch, self.vote_group_anchor)
+    }
+
+    /// slots every group of the epoch lasts
+    pub fn rotation_window_slots(&self) -> u64 {
+        self.rotation_window_slots
+    }
+
+    /// seed_source with every group kept for the epoch's rotation window, `first_slot` is the
+    /// first slot of `epoch`
+    pub fn windowed_seed_source(
+        &self,
+        epoch: Epoch,
+        first_slot: Slot,
+    ) -> WindowedSeed<EpochAnchoredSeed> {
+        WindowedSeed::new(
+            self.seed_source(epoch),
+            first_slot,
+            self.rotation_window_slots,
+        )
+    }
 
     pub fn stakes(&self) -> &Stakes {
         &self.stakes
@@ -127,11 +415,246 @@ impl EpochStakes {
     }
 }
 
+pub trait VoteGroupGeneratorExt {
+    /// the generator of `params` over the voters and stake of `epoch_stakes`. the exclusions
+    /// that need the epoch's vote activity, penalties, delinquency and absence, are left to
+    /// EpochStakes::new_with_vote_activity
+    fn from_epoch_stakes(epoch_stakes: &EpochStakes, params: &VoteGroupParams) -> Self;
+}
+
+impl VoteGroupGeneratorExt for VoteGroupGenerator {
+    fn from_epoch_stakes(epoch_stakes: &EpochStakes, params: &VoteGroupParams) -> Self {
+        let epoch_vote_accounts = epoch_stakes.stakes().vote_accounts();
+        let epoch_authorized_voters = epoch_stakes.epoch_authorized_voters();
+        let total_stake = epoch_stakes.total_stake();
+        let mut exclusions = params.exclusions.clone();
+        if params.minimum_stake != MinimumStake::None {
+            for (key, (stake, _)) in epoch_vote_accounts.iter() {
+                if epoch_authorized_voters.contains_key(key)
+                    && !params.minimum_stake.is_met(*stake, total_stake)
+                {
+                    exclusions.add(*key);
+                }
+            }
+        }
+        if params.dedup_identities {
+            add_identity_duplicates(
+                &mut exclusions,
+                epoch_stakes.node_id_to_vote_accounts(),
+                epoch_vote_accounts,
+            );
+        }
+        let num_voters = epoch_authorized_voters
+            .keys()
+            .filter(|key| !exclusions.contains(key))
+            .count();
+        build_vote_group_gen(
+            epoch_authorized_voters,
+            epoch_vote_accounts,
+            params,
+            &exclusions,
+            num_voters,
+        )
+    }
+}
+
+// the groups of `params` drawn from the epoch's voters, `num_voters` of them are eligible
+fn build_vote_group_gen(
+    epoch_authorized_voters: &EpochAuthorizedVoters,
+    epoch_vote_accounts: &HashMap<Pubkey, (u64, ArcVoteAccount)>,
+    params: &VoteGroupParams,
+    exclusions: &ExclusionList,
+    num_voters: usize,
+) -> VoteGroupGenerator {
+    let group_size = match params.dynamic_group_size {
+        Some(dynamic_group_size) => dynamic_group_size.size_for(num_voters),
+        None => params.group_size.min(num_voters),
+    };
+    if let Some(members) = &params.preset_members {
+        // every member is in every group, the seed only shuffles them
+        let voters: EpochAuthorizedVoters = members
+            .iter()
+            .map(|key| (*key, *epoch_authorized_voters.get(key).unwrap_or(key)))
+            .collect();
+        VoteGroupGenerator::new(&voters, voters.len())
+            .with_algorithm(params.algorithm)
+            .with_distinct_members(true)
+    } else {
+        match params.selection_mode {
+            SelectionMode::Uniform => VoteGroupGenerator::new_with_exclusions(
+                epoch_authorized_voters,
+                group_size,
+                exclusions,
+            ),
+            SelectionMode::StakeWeighted | SelectionMode::Binomial => {
+                let vote_stakes: HashMap<Pubkey, u64> = epoch_vote_accounts
+                    .iter()
+                    .map(|(key, (stake, _))| (*key, *stake))
+                    .collect();
+                if params.selection_mode == SelectionMode::Binomial {
+                    VoteGroupGenerator::new_binomial(
+                        epoch_authorized_voters,
+                        &vote_stakes,
+                        group_size,
+                        exclusions,
+                    )
+                } else {
+                    VoteGroupGenerator::new_stake_weighted(
+                        epoch_authorized_voters,
+                        &vote_stakes,
+                        group_size,
+                        exclusions,
+                    )
+                }
+            }
+            SelectionMode::Overlapping {
+                min_overlap_percent,
+            } => VoteGroupGenerator::new_with_exclusions(
+                epoch_authorized_voters,
+                group_size,
+                exclusions,
+            )
+            .with_min_overlap(min_overlap_percent),
+        }
+        .with_algorithm(params.algorithm)
+        .with_distinct_members(params.distinct_members)
+    }
+}
+
+// leaves all but the most staked vote account of each node out of the groups
+fn add_identity_duplicates(
+    exclusions: &mut ExclusionList,
+    node_id_to_vote_accounts: &NodeIdToVoteAccounts,
+    epoch_vote_accounts: &HashMap<Pubkey, (u64, ArcVoteAccount)>,
+) -> usize {
+    let identities: HashMap<Pubkey, Pubkey> = node_id_to_vote_accounts
+        .iter()
+        .flat_map(|(node_pubkey, node_vote_accounts)| {
+            node_vote_accounts
+                .vote_accounts
+                .iter()
+                .map(move |key| (*key, *node_pubkey))
+        })
+        .collect();
+    exclusions.add_identity_duplicates(&identities, |key| {
+        epoch_vote_accounts.get(key).map_or(0, |(stake, _)| *stake)
+    })
+}
+
+/// epoch stakes as they are laid out in snapshots older than 1.3.0, the generator only has
+/// its voters, distances and group size and there is no vote group anchor. epochs read back
+/// from them are anchored to the default hash
+#[derive(Clone, Debug, Serialize, Deserialize, AbiExample, PartialEq)]
+pub(crate) struct LegacyEpochStakes {
+    stakes: Arc<Stakes>,
+    total_stake: u64,
+    node_id_to_vote_accounts: Arc<NodeIdToVoteAccounts>,
+    epoch_authorized_voters: Arc<EpochAuthorizedVoters>,
+    vote_group_gen: LegacyVoteGroupGenerator,
+}
+
+impl From<LegacyEpochStakes> for EpochStakes {
+    fn from(legacy: LegacyEpochStakes) -> Self {
+        Self {
+            stakes: legacy.stakes,
+            total_stake: legacy.total_stake,
+            node_id_to_vote_accounts: legacy.node_id_to_vote_accounts,
+            epoch_authorized_voters: legacy.epoch_authorized_voters,
+            vote_group_gen: Arc::new(legacy.vote_group_gen.into()),
+            vote_group_anchor: Hash::default(),
+            rotation_window_slots: DEFAULT_ROTATION_WINDOW_SLOTS,
+        }
+    }
+}
+
+impl TryFrom<&EpochStakes> for LegacyEpochStakes {
+    type Error = VoteGroupError;
+
+    /// refuses epoch stakes that wouldn't read back the same, an anchor, a rotation window
+    /// or generator settings other than the ones legacy epochs are read back with
+    fn try_from(epoch_stakes: &EpochStakes) -> Result<Self, Self::Error> {
+        if epoch_stakes.vote_group_anchor != Hash::default() {
+            return Err(VoteGroupError::LegacyLayout(format!(
+                "the vote group anchor {}",
+                epoch_stakes.vote_group_anchor
+            )));
+        }
+        check_default_rotation_window(epoch_stakes)?;
+        Ok(Self {
+            stakes: epoch_stakes.stakes.clone(),
+            total_stake: epoch_stakes.total_stake,
+            node_id_to_vote_accounts: epoch_stakes.node_id_to_vote_accounts.clone(),
+            epoch_authorized_voters: epoch_stakes.epoch_authorized_voters.clone(),
+            vote_group_gen: LegacyVoteGroupGenerator::try_from(
+                epoch_stakes.vote_group_gen.as_ref(),
+            )?,
+        })
+    }
+}
+
+fn check_default_rotation_window(epoch_stakes: &EpochStakes) -> Result<(), VoteGroupError> {
+    if epoch_stakes.rotation_window_slots != DEFAULT_ROTATION_WINDOW_SLOTS {
+        return Err(VoteGroupError::LegacyLayout(format!(
+            "a rotation window of {} slots",
+            epoch_stakes.rotation_window_slots
+        )));
+    }
+    Ok(())
+}
+
+/// epoch stakes as they are laid out in 1.3.0 snapshots, every group lasted a single slot
+#[derive(Clone, Debug, Serialize, Deserialize, AbiExample, PartialEq)]
+pub(crate) struct EpochStakesV1_3_0 {
+    stakes: Arc<Stakes>,
+    total_stake: u64,
+    node_id_to_vote_accounts: Arc<NodeIdToVoteAccounts>,
+    epoch_authorized_voters: Arc<EpochAuthorizedVoters>,
+    vote_group_gen: Arc<VoteGroupGenerator>,
+    vote_group_anchor: Hash,
+}
+
+impl From<EpochStakesV1_3_0> for EpochStakes {
+    fn from(previous: EpochStakesV1_3_0) -> Self {
+        Self {
+            stakes: previous.stakes,
+            total_stake: previous.total_stake,
+            node_id_to_vote_accounts: previous.node_id_to_vote_accounts,
+            epoch_authorized_voters: previous.epoch_authorized_voters,
+            vote_group_gen: previous.vote_group_gen,
+            vote_group_anchor: previous.vote_group_anchor,
+            rotation_window_slots: DEFAULT_ROTATION_WINDOW_SLOTS,
+        }
+    }
+}
+
+impl TryFrom<&EpochStakes> for EpochStakesV1_3_0 {
+    type Error = VoteGroupError;
+
+    /// refuses epoch stakes rotating groups over other than the default window
+    fn try_from(epoch_stakes: &EpochStakes) -> Result<Self, Self::Error> {
+        check_default_rotation_window(epoch_stakes)?;
+        Ok(Self {
+            stakes: epoch_stakes.stakes.clone(),
+            total_stake: epoch_stakes.total_stake,
+            node_id_to_vote_accounts: epoch_stakes.node_id_to_vote_accounts.clone(),
+            epoch_authorized_voters: epoch_stakes.epoch_authorized_voters.clone(),
+            vote_group_gen: epoch_stakes.vote_group_gen.clone(),
+            vote_group_anchor: epoch_stakes.vote_group_anchor,
+        })
+    }
+}
+
 #[cfg(test)]
 pub(crate) mod tests {
     use super::*;
+    use crate::stakes::tests::{create_stake_account, create_staked_node_accounts};
     use solana_sdk::account::AccountSharedData;
-    use solana_vote_program::vote_state::create_account_with_authorized;
+    use solana_sdk::vote_group_gen::{
+        AbsenceThreshold, DelinquencyThreshold, DynamicGroupSize, ExclusionList, SelectionAlgorithm,
+    };
+    use solana_vote_program::vote_state::{
+        self, create_account_with_authorized, VoteState, VoteStateVersions,
+    };
     use std::iter;
 
     struct VoteAccountInfo {
@@ -233,4 +756,550 @@ pub(crate) mod tests {
             vote_accounts_map.len() as u64 * num_vote_accounts_per_node as u64 * 100
         );
     }
+
+    #[test]
+    fn test_vote_group_params() {
+        let params = VoteGroupParams {
+            selection_mode: SelectionMode::StakeWeighted,
+            algorithm: SelectionAlgorithm::V2,
+            distinct_members: true,
+            dedup_identities: false,
+            group_size: 21,
+            dynamic_group_size: None,
+            exclude_penalized: false,
+            minimum_stake: MinimumStake::None,
+            delinquency: None,
+            absence: None,
+            rotation_window_slots: 4,
+            exclusions: ExclusionList::default(),
+            preset_members: None,
+        };
+        let anchor = Hash::new_unique();
+        let epoch_stakes =
+            EpochStakes::new_with_vote_group_params(&Stakes::default(), 0, &params, anchor);
+        assert_eq!(epoch_stakes.vote_group_anchor(), &anchor);
+        assert_eq!(epoch_stakes.seed_source(4), EpochAnchoredSeed::new(4, anchor));
+        assert_eq!(epoch_stakes.rotation_window_slots(), 4);
+        assert_eq!(
+            epoch_stakes.windowed_seed_source(4, 128),
+            WindowedSeed::new(EpochAnchoredSeed::new(4, anchor), 128, 4)
+        );
+        assert_eq!(
+            EpochStakes::new(&Stakes::default(), 0).rotation_window_slots(),
+            DEFAULT_ROTATION_WINDOW_SLOTS
+        );
+        let gen = epoch_stakes.get_group_genr();
+        assert_eq!(gen.selection_mode(), SelectionMode::StakeWeighted);
+        assert_eq!(gen.algorithm(), SelectionAlgorithm::V2);
+        assert!(gen.distinct_members());
+
+        let binomial = VoteGroupParams {
+            selection_mode: SelectionMode::Binomial,
+            ..params.clone()
+        };
+        let epoch_stakes =
+            EpochStakes::new_with_vote_group_params(&Stakes::default(), 0, &binomial, anchor);
+        assert_eq!(
+            epoch_stakes.get_group_genr().selection_mode(),
+            SelectionMode::Binomial
+        );
+
+        let gen = EpochStakes::new(&Stakes::default(), 0)
+            .get_group_genr()
+            .clone();
+        assert_eq!(gen.selection_mode(), SelectionMode::Uniform);
+        assert_eq!(gen.algorithm(), SelectionAlgorithm::V1);
+        assert!(!gen.distinct_members());
+    }
+
+    #[test]
+    fn test_exclude_penalized_vote_accounts() {
+        let mut stakes = Stakes::default();
+        let mut vote_pubkeys = vec![];
+        for _ in 0..3 {
+            let ((vote_pubkey, vote_account), (stake_pubkey, stake_account)) =
+                create_staked_node_accounts(10);
+            stakes.store(&vote_pubkey, &vote_account, true, true);
+            stakes.store(&stake_pubkey, &stake_account, true, true);
+            vote_pubkeys.push((vote_pubkey, vote_account));
+        }
+        let (penalized, mut vote_account) = vote_pubkeys.pop().unwrap();
+        let mut vote_state = VoteState::from(&vote_account).unwrap();
+        vote_state.vote_group_penalty_epoch = Some(3);
+        VoteState::to(
+            &VoteStateVersions::new_current(vote_state),
+            &mut vote_account,
+        )
+        .unwrap();
+        stakes.store(&penalized, &vote_account, true, true);
+
+        let params = VoteGroupParams {
+            exclude_penalized: true,
+            ..VoteGroupParams::default()
+        };
+        let epoch_stakes =
+            EpochStakes::new_with_vote_group_params(&stakes, 5, &params, Hash::default());
+        let gen = epoch_stakes.get_group_genr();
+        assert!(!gen.voters().contains(&penalized));
+        assert_eq!(gen.group_size(), 2);
+        // still an authorized voter, it just isn't picked
+        assert!(epoch_stakes
+            .epoch_authorized_voters()
+            .contains_key(&penalized));
+
+        for (leader_schedule_epoch, params) in vec![
+            (4, params.clone()),
+            (6, params),
+            (5, VoteGroupParams::default()),
+        ] {
+            let epoch_stakes = EpochStakes::new_with_vote_group_params(
+                &stakes,
+                leader_schedule_epoch,
+                &params,
+                Hash::default(),
+            );
+            assert!(epoch_stakes.get_group_genr().voters().contains(&penalized));
+        }
+    }
+    #[test]
+    fn test_exclude_below_minimum_stake() {
+        let mut stakes = Stakes::default();
+        let mut vote_pubkeys = vec![];
+        for stake in &[10, 10, 10, 1] {
+            let ((vote_pubkey, vote_account), (stake_pubkey, stake_account)) =
+                create_staked_node_accounts(*stake);
+            stakes.store(&vote_pubkey, &vote_account, true, true);
+            stakes.store(&stake_pubkey, &stake_account, true, true);
+            vote_pubkeys.push(vote_pubkey);
+        }
+        let dust = vote_pubkeys.pop().unwrap();
+
+        for minimum_stake in vec![
+            MinimumStake::Lamports(10),
+            MinimumStake::FractionOfTotal {
+                numerator: 1,
+                denominator: 10,
+            },
+        ] {
+            let params = VoteGroupParams {
+                minimum_stake,
+                ..VoteGroupParams::default()
+            };
+            let epoch_stakes =
+                EpochStakes::new_with_vote_group_params(&stakes, 0, &params, Hash::default());
+            let gen = epoch_stakes.get_group_genr();
+            assert_eq!(gen.voters().len(), 3);
+            assert!(!gen.voters().contains(&dust));
+            assert_eq!(gen.group_size(), 3);
+            assert!(epoch_stakes.epoch_authorized_voters().contains_key(&dust));
+        }
+
+        let epoch_stakes = EpochStakes::new(&stakes, 0);
+        assert!(epoch_stakes.get_group_genr().voters().contains(&dust));
+    }
+    #[test]
+    fn test_exclude_delinquent_voters() {
+        let mut stakes = Stakes::default();
+        let mut vote_pubkeys = vec![];
+        for last_voted_slot in &[Some(1_000), Some(995), Some(950), None] {
+            let ((vote_pubkey, mut vote_account), (stake_pubkey, stake_account)) =
+                create_staked_node_accounts(10);
+            if let Some(slot) = last_voted_slot {
+                let mut vote_state = VoteState::from(&vote_account).unwrap();
+                vote_state.process_slot_vote_unchecked(*slot);
+                VoteState::to(
+                    &VoteStateVersions::new_current(vote_state),
+                    &mut vote_account,
+                )
+                .unwrap();
+            }
+            stakes.store(&vote_pubkey, &vote_account, true, true);
+            stakes.store(&stake_pubkey, &stake_account, true, true);
+            vote_pubkeys.push(vote_pubkey);
+        }
+        let params = VoteGroupParams {
+            delinquency: Some(DelinquencyThreshold {
+                max_slot_distance: 20,
+                reentry_slot_distance: 2,
+            }),
+            ..VoteGroupParams::default()
+        };
+        let new_epoch_stakes = |previous_voters: Option<&[Pubkey]>| {
+            EpochStakes::new_with_vote_activity(
+                &stakes,
+                0,
+                &params,
+                Hash::default(),
+                Some(VoteActivity {
+                    slot: 1_001,
+                    previous_voters,
+                    assignments: None,
+                }),
+            )
+        };
+
+        let epoch_stakes = new_epoch_stakes(None);
+        let mut live = vote_pubkeys[..2].to_vec();
+        live.sort();
+        assert_eq!(epoch_stakes.get_group_genr().voters(), &live[..]);
+        assert_eq!(epoch_stakes.get_group_genr().group_size(), 2);
+        assert_eq!(epoch_stakes.epoch_authorized_voters().len(), 4);
+
+        // left out last epoch, voting again but not recently enough to come back yet
+        let epoch_stakes = new_epoch_stakes(Some(&vote_pubkeys[..1]));
+        assert_eq!(epoch_stakes.get_group_genr().voters(), &vote_pubkeys[..1]);
+
+        // nothing to check against without the vote activity
+        let epoch_stakes =
+            EpochStakes::new_with_vote_group_params(&stakes, 0, &params, Hash::default());
+        assert_eq!(epoch_stakes.get_group_genr().voters().len(), 4);
+    }
+
+    #[test]
+    fn test_all_delinquent_keeps_voters() {
+        let mut stakes = Stakes::default();
+        for _ in 0..3 {
+            let ((vote_pubkey, vote_account), (stake_pubkey, stake_account)) =
+                create_staked_node_accounts(10);
+            stakes.store(&vote_pubkey, &vote_account, true, true);
+            stakes.store(&stake_pubkey, &stake_account, true, true);
+        }
+        let params = VoteGroupParams {
+            delinquency: Some(DelinquencyThreshold::default()),
+            ..VoteGroupParams::default()
+        };
+        let epoch_stakes = EpochStakes::new_with_vote_activity(
+            &stakes,
+            0,
+            &params,
+            Hash::default(),
+            Some(VoteActivity {
+                slot: 1_000_000,
+                previous_voters: None,
+                assignments: None,
+            }),
+        );
+        assert_eq!(epoch_stakes.get_group_genr().voters().len(), 3);
+    }
+
+    #[test]
+    fn test_exclude_absent_voters() {
+        let mut stakes = Stakes::default();
+        let mut vote_pubkeys = vec![];
+        for credits in &[40, 35, 10, 0] {
+            let ((vote_pubkey, mut vote_account), (stake_pubkey, stake_account)) =
+                create_staked_node_accounts(10);
+            let mut vote_state = VoteState::from(&vote_account).unwrap();
+            vote_state.increment_credits_by(1, 10);
+            vote_state.increment_credits_by(2, *credits);
+            VoteState::to(
+                &VoteStateVersions::new_current(vote_state),
+                &mut vote_account,
+            )
+            .unwrap();
+            stakes.store(&vote_pubkey, &vote_account, true, true);
+            stakes.store(&stake_pubkey, &stake_account, true, true);
+            vote_pubkeys.push(vote_pubkey);
+        }
+        let params = VoteGroupParams {
+            absence: Some(AbsenceThreshold::default()),
+            ..VoteGroupParams::default()
+        };
+        let mut assignments = EpochAssignments {
+            epoch: 2,
+            assigned_slots: vote_pubkeys.iter().map(|key| (*key, 40)).collect(),
+        };
+        // never assigned enough slots to judge
+        assignments.assigned_slots.insert(vote_pubkeys[3], 10);
+        let new_epoch_stakes = |assignments: &EpochAssignments| {
+            EpochStakes::new_with_vote_activity(
+                &stakes,
+                0,
+                &params,
+                Hash::default(),
+                Some(VoteActivity {
+                    slot: 1_000,
+                    previous_voters: None,
+                    assignments: Some(assignments),
+                }),
+            )
+        };
+
+        let epoch_stakes = new_epoch_stakes(&assignments);
+        let gen = epoch_stakes.get_group_genr();
+        assert!(!gen.voters().contains(&vote_pubkeys[2]));
+        assert_eq!(gen.voters().len(), 3);
+        assert_eq!(gen.group_size(), 3);
+        assert_eq!(epoch_stakes.epoch_authorized_voters().len(), 4);
+
+        // only the credits of the assigned epoch count
+        assignments.epoch = 1;
+        let epoch_stakes = new_epoch_stakes(&assignments);
+        assert_eq!(epoch_stakes.get_group_genr().voters().len(), 1);
+
+        // everybody absent leaves everybody in
+        assignments.epoch = 3;
+        assignments.assigned_slots.insert(vote_pubkeys[3], 40);
+        let epoch_stakes = new_epoch_stakes(&assignments);
+        assert_eq!(epoch_stakes.get_group_genr().voters().len(), 4);
+    }
+
+    #[test]
+    fn test_participation_credits() {
+        let (member, idle, outsider) = (
+            solana_sdk::pubkey::new_rand(),
+            solana_sdk::pubkey::new_rand(),
+            solana_sdk::pubkey::new_rand(),
+        );
+        let assignments = EpochAssignments {
+            epoch: 2,
+            assigned_slots: vec![(member, 40), (idle, 40), (outsider, 0)]
+                .into_iter()
+                .collect(),
+        };
+        let epoch_credits = [(1, 100, 0), (2, 130, 100)];
+        assert_eq!(
+            assignments.participation_credits(&member, &epoch_credits, 1_000),
+            Some(ParticipationCredits {
+                epoch: 2,
+                credits: 750,
+            })
+        );
+        // votes beyond the assigned slots earn nothing more
+        assert_eq!(
+            assignments
+                .participation_credits(&member, &[(2, 200, 100)], 1_000)
+                .unwrap()
+                .credits,
+            1_000
+        );
+        assert_eq!(
+            assignments
+                .participation_credits(&idle, &epoch_credits[..1], 1_000)
+                .unwrap()
+                .credits,
+            0
+        );
+        assert_eq!(
+            assignments.participation_credits(&outsider, &epoch_credits, 1_000),
+            None
+        );
+        assert_eq!(
+            assignments.participation_credits(
+                &solana_sdk::pubkey::new_rand(),
+                &epoch_credits,
+                1_000
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_restore_authorized_voters() {
+        let mut stakes = Stakes::default();
+        for _ in 0..3 {
+            let ((vote_pubkey, vote_account), (stake_pubkey, stake_account)) =
+                create_staked_node_accounts(10);
+            stakes.store(&vote_pubkey, &vote_account, true, true);
+            stakes.store(&stake_pubkey, &stake_account, true, true);
+        }
+        let epoch_stakes = EpochStakes::new(&stakes, 0);
+        let check = |epoch_stakes: &EpochStakes| {
+            epoch_stakes
+                .epoch_authorized_voters()
+                .iter()
+                .all(|(vote_account, voter)| {
+                    epoch_stakes
+                        .get_group_genr()
+                        .authorized_voter_for(vote_account)
+                        == Some(*voter)
+                })
+        };
+        assert!(check(&epoch_stakes));
+
+        let mut read_back: EpochStakes =
+            bincode::deserialize(&bincode::serialize(&epoch_stakes).unwrap()).unwrap();
+        assert!(!check(&read_back));
+        read_back.restore_authorized_voters();
+        assert!(check(&read_back));
+    }
+
+    #[test]
+    fn test_dedup_identities() {
+        let mut stakes = Stakes::default();
+        let operator = solana_sdk::pubkey::new_rand();
+        let mut operator_accounts = vec![];
+        for stake in &[10, 30, 20] {
+            let vote_pubkey = solana_sdk::pubkey::new_rand();
+            let vote_account = vote_state::create_account(&vote_pubkey, &operator, 0, 1);
+            let (stake_pubkey, stake_account) = create_stake_account(*stake, &vote_pubkey);
+            stakes.store(&vote_pubkey, &vote_account, true, true);
+            stakes.store(&stake_pubkey, &stake_account, true, true);
+            operator_accounts.push(vote_pubkey);
+        }
+        let ((solo, vote_account), (stake_pubkey, stake_account)) = create_staked_node_accounts(1);
+        stakes.store(&solo, &vote_account, true, true);
+        stakes.store(&stake_pubkey, &stake_account, true, true);
+
+        let params = VoteGroupParams {
+            dedup_identities: true,
+            ..VoteGroupParams::default()
+        };
+        let epoch_stakes =
+            EpochStakes::new_with_vote_group_params(&stakes, 0, &params, Hash::default());
+        let gen = epoch_stakes.get_group_genr();
+        let mut expected = vec![operator_accounts[1], solo];
+        expected.sort();
+        assert_eq!(gen.voters(), &expected[..]);
+        assert_eq!(gen.group_size(), 2);
+        // the others keep voting, they just aren't picked
+        assert_eq!(epoch_stakes.epoch_authorized_voters().len(), 4);
+
+        let epoch_stakes = EpochStakes::new(&stakes, 0);
+        assert_eq!(epoch_stakes.get_group_genr().voters().len(), 4);
+    }
+
+    #[test]
+    fn test_dynamic_group_size() {
+        let mut stakes = Stakes::default();
+        for _ in 0..40 {
+            let ((vote_pubkey, vote_account), (stake_pubkey, stake_account)) =
+                create_staked_node_accounts(10);
+            stakes.store(&vote_pubkey, &vote_account, true, true);
+            stakes.store(&stake_pubkey, &stake_account, true, true);
+        }
+        let group_size = |percent, min, max| {
+            let params = VoteGroupParams {
+                dynamic_group_size: Some(DynamicGroupSize { percent, min, max }),
+                ..VoteGroupParams::default()
+            };
+            EpochStakes::new_with_vote_group_params(&stakes, 0, &params, Hash::default())
+                .get_group_genr()
+                .group_size()
+        };
+        assert_eq!(group_size(10, 1, 100), 4);
+        assert_eq!(group_size(10, 5, 100), 5);
+        assert_eq!(group_size(50, 1, 15), 15);
+        assert_eq!(group_size(50, 50, 100), 40);
+    }
+
+    #[test]
+    fn test_from_epoch_stakes() {
+        let mut stakes = Stakes::default();
+        let mut vote_pubkeys = vec![];
+        for stake in &[10, 10, 10, 10, 1] {
+            let ((vote_pubkey, vote_account), (stake_pubkey, stake_account)) =
+                create_staked_node_accounts(*stake);
+            stakes.store(&vote_pubkey, &vote_account, true, true);
+            stakes.store(&stake_pubkey, &stake_account, true, true);
+            vote_pubkeys.push(vote_pubkey);
+        }
+        let dust = vote_pubkeys.pop().unwrap();
+
+        for params in vec![
+            VoteGroupParams::default(),
+            VoteGroupParams {
+                selection_mode: SelectionMode::StakeWeighted,
+                algorithm: SelectionAlgorithm::V2,
+                group_size: 3,
+                minimum_stake: MinimumStake::Lamports(10),
+                ..VoteGroupParams::default()
+            },
+            VoteGroupParams {
+                preset_members: Some(vote_pubkeys[..2].to_vec()),
+                ..VoteGroupParams::default()
+            },
+        ] {
+            let epoch_stakes =
+                EpochStakes::new_with_vote_group_params(&stakes, 0, &params, Hash::default());
+            assert_eq!(
+                &VoteGroupGenerator::from_epoch_stakes(&epoch_stakes, &params),
+                epoch_stakes.get_group_genr()
+            );
+        }
+
+        let params = VoteGroupParams {
+            minimum_stake: MinimumStake::Lamports(10),
+            ..VoteGroupParams::default()
+        };
+        let gen = VoteGroupGenerator::from_epoch_stakes(&EpochStakes::new(&stakes, 0), &params);
+        assert_eq!(gen.voters().len(), 4);
+        assert!(!gen.voters().contains(&dust));
+    }
+
+    #[test]
+    fn test_legacy_epoch_stakes_layout() {
+        let mut stakes = Stakes::default();
+        for stake in &[10, 20, 30] {
+            let ((vote_pubkey, vote_account), (stake_pubkey, stake_account)) =
+                create_staked_node_accounts(*stake);
+            stakes.store(&vote_pubkey, &vote_account, true, true);
+            stakes.store(&stake_pubkey, &stake_account, true, true);
+        }
+        let epoch_stakes = EpochStakes::new_with_vote_group_params(
+            &stakes,
+            0,
+            &VoteGroupParams::default(),
+            Hash::default(),
+        );
+
+        // epoch stakes the way snapshots older than 1.3.0 were written
+        let generator = LegacyVoteGroupGenerator::try_from(epoch_stakes.get_group_genr()).unwrap();
+        let bytes = bincode::serialize(&(
+            epoch_stakes.stakes.clone(),
+            epoch_stakes.total_stake(),
+            epoch_stakes.node_id_to_vote_accounts().clone(),
+            epoch_stakes.epoch_authorized_voters().clone(),
+            (
+                generator.possible_voters.clone(),
+                generator.all_distance.clone(),
+                generator.group_size,
+            ),
+        ))
+        .unwrap();
+        assert_eq!(
+            bincode::serialize(&LegacyEpochStakes::try_from(&epoch_stakes).unwrap()).unwrap(),
+            bytes
+        );
+
+        let restored: EpochStakes = bincode::deserialize::<LegacyEpochStakes>(&bytes)
+            .unwrap()
+            .into();
+        assert_eq!(restored.vote_group_anchor(), &Hash::default());
+        assert_eq!(restored.total_stake(), epoch_stakes.total_stake());
+        assert_eq!(
+            restored.get_group_genr().voters(),
+            epoch_stakes.get_group_genr().voters()
+        );
+
+        // state the older layouts can't hold is refused rather than dropped
+        let anchored = EpochStakes::new_with_vote_group_params(
+            &stakes,
+            0,
+            &VoteGroupParams::default(),
+            Hash::new_unique(),
+        );
+        assert!(matches!(
+            LegacyEpochStakes::try_from(&anchored),
+            Err(VoteGroupError::LegacyLayout(_))
+        ));
+        assert!(EpochStakesV1_3_0::try_from(&anchored).is_ok());
+        let windowed = EpochStakes::new_with_vote_group_params(
+            &stakes,
+            0,
+            &VoteGroupParams {
+                rotation_window_slots: DEFAULT_ROTATION_WINDOW_SLOTS + 1,
+                ..VoteGroupParams::default()
+            },
+            Hash::default(),
+        );
+        assert!(matches!(
+            LegacyEpochStakes::try_from(&windowed),
+            Err(VoteGroupError::LegacyLayout(_))
+        ));
+        assert!(matches!(
+            EpochStakesV1_3_0::try_from(&windowed),
+            Err(VoteGroupError::LegacyLayout(_))
+        ));
+    }
 }
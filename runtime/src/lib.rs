@@ -6,6 +6,7 @@ pub mod accounts_cache;
 pub mod accounts_db;
 pub mod accounts_hash;
 pub mod accounts_index;
+pub mod accounts_update_notifier_interface;
 pub mod append_vec;
 pub mod bank;
 pub mod bank_client;
@@ -29,6 +30,7 @@ mod native_loader;
 pub mod rent_collector;
 pub mod secondary_index;
 pub mod serde_snapshot;
+pub mod shared_vote_group_generator;
 pub mod snapshot_package;
 pub mod snapshot_utils;
 pub mod stake_weighted_timestamp;
@@ -37,6 +39,8 @@ pub mod status_cache;
 mod system_instruction_processor;
 pub mod transaction_batch;
 pub mod vote_account;
+pub mod vote_group_schedule_cache;
+pub mod vote_group_transition;
 pub mod vote_sender_types;
 
 #[macro_use]
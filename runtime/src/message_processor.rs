@@ -1225,7 +1225,7 @@ mod tests {
     use super::*;
     use solana_sdk::{
         account::Account,
-        instruction::{AccountMeta, Instruction, InstructionError},
+        instruction::{AccountMeta, AnyVoter, Instruction, InstructionError},
         message::Message,
         native_loader::create_loadable_account_for_test,
     };
@@ -1267,6 +1267,7 @@ mod tests {
             Arc::new(FeatureSet::all_enabled()),
             Arc::new(Accounts::default()),
             &ancestors,
+            &AnyVoter,
         );
 
         // Check call depth increases and has a limit
@@ -1865,6 +1866,7 @@ mod tests {
             &mut ExecuteDetailsTimings::default(),
             Arc::new(Accounts::default()),
             &ancestors,
+            &AnyVoter,
         );
         assert_eq!(result, Ok(()));
         assert_eq!(accounts[0].borrow().lamports, 100);
@@ -1893,6 +1895,7 @@ mod tests {
             &mut ExecuteDetailsTimings::default(),
             Arc::new(Accounts::default()),
             &ancestors,
+            &AnyVoter,
         );
         assert_eq!(
             result,
@@ -1925,6 +1928,7 @@ mod tests {
             &mut ExecuteDetailsTimings::default(),
             Arc::new(Accounts::default()),
             &ancestors,
+            &AnyVoter,
         );
         assert_eq!(
             result,
@@ -2044,6 +2048,7 @@ mod tests {
             &mut ExecuteDetailsTimings::default(),
             Arc::new(Accounts::default()),
             &ancestors,
+            &AnyVoter,
         );
         assert_eq!(
             result,
@@ -2076,6 +2081,7 @@ mod tests {
             &mut ExecuteDetailsTimings::default(),
             Arc::new(Accounts::default()),
             &ancestors,
+            &AnyVoter,
         );
         assert_eq!(result, Ok(()));
 
@@ -2106,6 +2112,7 @@ mod tests {
             &mut ExecuteDetailsTimings::default(),
             Arc::new(Accounts::default()),
             &ancestors,
+            &AnyVoter,
         );
         assert_eq!(result, Ok(()));
         assert_eq!(accounts[0].borrow().lamports, 80);
@@ -2198,6 +2205,7 @@ mod tests {
             Arc::new(FeatureSet::all_enabled()),
             Arc::new(Accounts::default()),
             &ancestors,
+            &AnyVoter,
         );
         let metas = vec![
             AccountMeta::new(owned_key, false),
@@ -43,10 +43,12 @@ use solana_frozen_abi::abi_example::IgnoreAsHelper;
 
 mod common;
 mod future;
+mod legacy;
 mod tests;
 mod utils;
 
 use future::Context as TypeContextFuture;
+use legacy::{ContextV1_2_0 as TypeContextLegacy, ContextV1_3_0 as TypeContextPrevious};
 #[allow(unused_imports)]
 use utils::{serialize_iter_as_map, serialize_iter_as_seq, serialize_iter_as_tuple};
 
@@ -58,6 +60,8 @@ pub(crate) use crate::accounts_db::{SnapshotStorage, SnapshotStorages};
 
 #[derive(Copy, Clone, Eq, PartialEq)]
 pub(crate) enum SerdeStyle {
+    Older,
+    Previous,
     Newer,
 }
 
@@ -153,6 +157,8 @@ where
         }};
     }
     match serde_style {
+        SerdeStyle::Older => INTO!(TypeContextLegacy),
+        SerdeStyle::Previous => INTO!(TypeContextPrevious),
         SerdeStyle::Newer => INTO!(TypeContextFuture),
     }
     .map_err(|err| {
@@ -183,6 +189,8 @@ where
         };
     }
     match serde_style {
+        SerdeStyle::Older => INTO!(TypeContextLegacy),
+        SerdeStyle::Previous => INTO!(TypeContextPrevious),
         SerdeStyle::Newer => INTO!(TypeContextFuture),
     }
     .map_err(|err| {
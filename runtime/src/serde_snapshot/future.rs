@@ -43,7 +43,7 @@ use std::sync::RwLock;
 // because it's handled by SerializableVersionedBank.
 // So, sync fields with it!
 #[derive(Clone, Deserialize)]
-pub(crate) struct DeserializableVersionedBank {
+pub(crate) struct DeserializableVersionedBank<E = EpochStakes> {
     pub(crate) blockhash_queue: BlockhashQueue,
     pub(crate) ancestors: Ancestors,
     pub(crate) hash: Hash,
@@ -74,13 +74,13 @@ pub(crate) struct DeserializableVersionedBank {
     pub(crate) inflation: Inflation,
     pub(crate) stakes: Stakes,
     pub(crate) unused_accounts: UnusedAccounts,
-    pub(crate) epoch_stakes: HashMap<Epoch, EpochStakes>,
+    pub(crate) epoch_stakes: HashMap<Epoch, E>,
     pub(crate) is_delta: bool,
     pub(crate) message_processor: MessageProcessor,
 }
 
-impl From<DeserializableVersionedBank> for BankFieldsToDeserialize {
-    fn from(dvb: DeserializableVersionedBank) -> Self {
+impl<E: Into<EpochStakes>> From<DeserializableVersionedBank<E>> for BankFieldsToDeserialize {
+    fn from(dvb: DeserializableVersionedBank<E>) -> Self {
         BankFieldsToDeserialize {
             blockhash_queue: dvb.blockhash_queue,
             ancestors: dvb.ancestors,
@@ -111,7 +111,11 @@ impl From<DeserializableVersionedBank> for BankFieldsToDeserialize {
             epoch_schedule: dvb.epoch_schedule,
             inflation: dvb.inflation,
             stakes: dvb.stakes,
-            epoch_stakes: dvb.epoch_stakes,
+            epoch_stakes: dvb
+                .epoch_stakes
+                .into_iter()
+                .map(|(epoch, stakes)| (epoch, stakes.into()))
+                .collect(),
             is_delta: dvb.is_delta,
         }
     }
@@ -120,7 +124,7 @@ impl From<DeserializableVersionedBank> for BankFieldsToDeserialize {
 // Serializable version of Bank, not Deserializable to avoid cloning by using refs.
 // Sync fields with DeserializableVersionedBank!
 #[derive(Serialize)]
-pub(crate) struct SerializableVersionedBank<'a> {
+pub(crate) struct SerializableVersionedBank<'a, E = &'a HashMap<Epoch, EpochStakes>> {
     pub(crate) blockhash_queue: &'a RwLock<BlockhashQueue>,
     pub(crate) ancestors: &'a Ancestors,
     pub(crate) hash: Hash,
@@ -151,13 +155,21 @@ pub(crate) struct SerializableVersionedBank<'a> {
     pub(crate) inflation: Inflation,
     pub(crate) stakes: &'a RwLock<Stakes>,
     pub(crate) unused_accounts: UnusedAccounts,
-    pub(crate) epoch_stakes: &'a HashMap<Epoch, EpochStakes>,
+    pub(crate) epoch_stakes: E,
     pub(crate) is_delta: bool,
     pub(crate) message_processor: MessageProcessor,
 }
 
 impl<'a> From<crate::bank::BankFieldsToSerialize<'a>> for SerializableVersionedBank<'a> {
     fn from(rhs: crate::bank::BankFieldsToSerialize<'a>) -> Self {
+        let epoch_stakes = rhs.epoch_stakes;
+        Self::new(rhs, epoch_stakes)
+    }
+}
+
+impl<'a, E> SerializableVersionedBank<'a, E> {
+    // the older snapshot layouts only differ in how epoch_stakes is written
+    pub(crate) fn new(rhs: crate::bank::BankFieldsToSerialize<'a>, epoch_stakes: E) -> Self {
         fn new<T: Default>() -> T {
             T::default()
         }
@@ -192,7 +204,7 @@ impl<'a> From<crate::bank::BankFieldsToSerialize<'a>> for SerializableVersionedB
             inflation: rhs.inflation,
             stakes: rhs.stakes,
             unused_accounts: new(),
-            epoch_stakes: rhs.epoch_stakes,
+            epoch_stakes,
             is_delta: rhs.is_delta,
             message_processor: new(),
         }
@@ -200,7 +212,7 @@ impl<'a> From<crate::bank::BankFieldsToSerialize<'a>> for SerializableVersionedB
 }
 
 #[cfg(RUSTC_WITH_SPECIALIZATION)]
-impl<'a> IgnoreAsHelper for SerializableVersionedBank<'a> {}
+impl<'a, E> IgnoreAsHelper for SerializableVersionedBank<'a, E> {}
 
 pub(super) struct Context {}
 impl<'a> TypeContext<'a> for Context {
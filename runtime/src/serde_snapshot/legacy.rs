@@ -0,0 +1,103 @@
+use super::future::{
+    Context as FutureContext, DeserializableVersionedBank, SerializableAccountStorageEntry,
+    SerializableVersionedBank,
+};
+use {
+    super::*,
+    crate::epoch_stakes::{EpochStakesV1_3_0, LegacyEpochStakes},
+    solana_sdk::vote_group_gen::VoteGroupError,
+    std::convert::TryFrom,
+};
+
+type AccountsDbFields = super::AccountsDbFields<SerializableAccountStorageEntry>;
+
+// Snapshots older than 1.4.0 only differ from future.rs in how each epoch's stakes are laid
+// out. 1.2.0 only holds the voters, distances and group size of the vote group generator,
+// 1.3.0 has no rotation window
+pub(super) struct Context<E> {
+    phantom: std::marker::PhantomData<E>,
+}
+
+pub(super) type ContextV1_2_0 = Context<LegacyEpochStakes>;
+pub(super) type ContextV1_3_0 = Context<EpochStakesV1_3_0>;
+
+impl<'a, E> TypeContext<'a> for Context<E>
+where
+    E: Serialize
+        + DeserializeOwned
+        + Into<EpochStakes>
+        + for<'b> TryFrom<&'b EpochStakes, Error = VoteGroupError>,
+{
+    type SerializableAccountStorageEntry = SerializableAccountStorageEntry;
+
+    fn serialize_bank_and_storage<S: serde::ser::Serializer>(
+        serializer: S,
+        serializable_bank: &SerializableBankAndStorage<'a, Self>,
+    ) -> std::result::Result<S::Ok, S::Error>
+    where
+        Self: std::marker::Sized,
+    {
+        let bank_fields = serializable_bank.bank.get_fields_to_serialize();
+        // an older layout that can't hold an epoch's stakes must not drop what it can't hold
+        let epoch_stakes = bank_fields
+            .epoch_stakes
+            .iter()
+            .map(|(epoch, stakes)| {
+                E::try_from(stakes)
+                    .map(|stakes| (*epoch, stakes))
+                    .map_err(|err| {
+                        serde::ser::Error::custom(format!("epoch {} stakes: {}", epoch, err))
+                    })
+            })
+            .collect::<std::result::Result<HashMap<Epoch, E>, S::Error>>()?;
+        (
+            SerializableVersionedBank::new(bank_fields, epoch_stakes),
+            SerializableAccountsDb::<'a, Self> {
+                accounts_db: &*serializable_bank.bank.rc.accounts.accounts_db,
+                slot: serializable_bank.bank.rc.slot,
+                account_storage_entries: serializable_bank.snapshot_storages,
+                phantom: std::marker::PhantomData::default(),
+            },
+        )
+            .serialize(serializer)
+    }
+
+    fn serialize_accounts_db_fields<S: serde::ser::Serializer>(
+        serializer: S,
+        serializable_db: &SerializableAccountsDb<'a, Self>,
+    ) -> std::result::Result<S::Ok, S::Error>
+    where
+        Self: std::marker::Sized,
+    {
+        FutureContext::serialize_accounts_db_fields(
+            serializer,
+            &SerializableAccountsDb::<'a, FutureContext> {
+                accounts_db: serializable_db.accounts_db,
+                slot: serializable_db.slot,
+                account_storage_entries: serializable_db.account_storage_entries,
+                phantom: std::marker::PhantomData::default(),
+            },
+        )
+    }
+
+    fn deserialize_bank_fields<R>(
+        mut stream: &mut BufReader<R>,
+    ) -> Result<(BankFieldsToDeserialize, AccountsDbFields), Error>
+    where
+        R: Read,
+    {
+        let bank_fields =
+            deserialize_from::<_, DeserializableVersionedBank<E>>(&mut stream)?.into();
+        let accounts_db_fields = Self::deserialize_accounts_db_fields(stream)?;
+        Ok((bank_fields, accounts_db_fields))
+    }
+
+    fn deserialize_accounts_db_fields<R>(
+        stream: &mut BufReader<R>,
+    ) -> Result<AccountsDbFields, Error>
+    where
+        R: Read,
+    {
+        deserialize_from(stream)
+    }
+}
@@ -87,6 +87,16 @@ where
     R: Read,
 {
     match serde_style {
+        SerdeStyle::Older => context_accountsdb_from_stream::<TypeContextLegacy, R>(
+            stream,
+            account_paths,
+            unpacked_append_vec_map,
+        ),
+        SerdeStyle::Previous => context_accountsdb_from_stream::<TypeContextPrevious, R>(
+            stream,
+            account_paths,
+            unpacked_append_vec_map,
+        ),
         SerdeStyle::Newer => context_accountsdb_from_stream::<TypeContextFuture, R>(
             stream,
             account_paths,
@@ -107,6 +117,24 @@ where
     W: Write,
 {
     match serde_style {
+        SerdeStyle::Older => serialize_into(
+            stream,
+            &SerializableAccountsDb::<TypeContextLegacy> {
+                accounts_db,
+                slot,
+                account_storage_entries,
+                phantom: std::marker::PhantomData::default(),
+            },
+        ),
+        SerdeStyle::Previous => serialize_into(
+            stream,
+            &SerializableAccountsDb::<TypeContextPrevious> {
+                accounts_db,
+                slot,
+                account_storage_entries,
+                phantom: std::marker::PhantomData::default(),
+            },
+        ),
         SerdeStyle::Newer => serialize_into(
             stream,
             &SerializableAccountsDb::<TypeContextFuture> {
@@ -272,23 +300,66 @@ pub(crate) fn reconstruct_accounts_db_via_serialization(
     accounts_db
 }
 
+#[test]
+fn test_accounts_serialize_older() {
+    test_accounts_serialize_style(SerdeStyle::Older)
+}
+
+#[test]
+fn test_accounts_serialize_previous() {
+    test_accounts_serialize_style(SerdeStyle::Previous)
+}
+
 #[test]
 fn test_accounts_serialize_newer() {
     test_accounts_serialize_style(SerdeStyle::Newer)
 }
 
+#[test]
+fn test_bank_serialize_older() {
+    test_bank_serialize_style(SerdeStyle::Older)
+}
+
+#[test]
+fn test_bank_serialize_previous() {
+    test_bank_serialize_style(SerdeStyle::Previous)
+}
+
 #[test]
 fn test_bank_serialize_newer() {
     test_bank_serialize_style(SerdeStyle::Newer)
 }
 
+#[test]
+fn test_bank_serialize_older_refuses_anchored_epoch_stakes() {
+    let (genesis_config, _) = create_genesis_config(500);
+    let bank0 = Arc::new(Bank::new(&genesis_config));
+    // epoch stakes captured after genesis are anchored to the capturing bank's parent hash,
+    // which 1.2.0 snapshots have no room for
+    let slot = bank0.epoch_schedule().get_first_slot_in_epoch(1);
+    let bank = Bank::new_from_parent(&bank0, &Pubkey::default(), slot);
+    bank.freeze();
+    bank.squash();
+    bank.force_flush_accounts_cache();
+
+    let snapshot_storages = bank.get_snapshot_storages();
+    let to_stream = |serde_style| {
+        let mut buf = vec![];
+        let mut writer = std::io::BufWriter::new(Cursor::new(&mut buf));
+        crate::serde_snapshot::bank_to_stream(serde_style, &mut writer, &bank, &snapshot_storages)
+    };
+    assert!(to_stream(SerdeStyle::Older).is_err());
+    assert!(to_stream(SerdeStyle::Previous).is_ok());
+    assert!(to_stream(SerdeStyle::Newer).is_ok());
+}
+
 #[cfg(all(test, RUSTC_WITH_SPECIALIZATION))]
 mod test_bank_serialize {
     use super::*;
 
     // These some what long test harness is required to freeze the ABI of
     // Bank's serialization due to versioned nature
-    #[frozen_abi(digest = "DuRGntVwLGNAv5KooafUSpxk67BPAx2yC7Z8A9c8wr2G")]
+    #[frozen_abi(digest = "BuqDV9NheiwbYLWYzLyExBdFVgRbBsNmdhFoujPqTpiY")]
     #[derive(Serialize, AbiExample)]
     pub struct BankAbiTestWrapperFuture {
         #[serde(serialize_with = "wrapper_future")]
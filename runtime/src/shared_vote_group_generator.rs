@@ -0,0 +1,419 @@
+//! The vote group generator of the working epoch, shared by the validator's services
+//!
+//! The validator builds one from its root bank at startup and hands the same Arc to every
+//! service that checks groups, instead of each of them building and refreshing its own copy
+//! out of the banks it happens to hold. Replay swaps in the next epoch's generator as soon as
+//! it creates the epoch's first bank, the next epoch's stakes are fixed an epoch ahead so no
+//! root is needed to agree on them. The previous epoch's generator is kept for a short window
+//! after the boundary so votes still in flight on its slots are answered. Readers load the
+//! generators without a lock and always get them together with the epochs and seeds they
+//! belong to.
+use crate::{bank::Bank, epoch_stakes::EpochStakes};
+use arc_swap::ArcSwap;
+use log::*;
+use solana_sdk::{
+    clock::{Epoch, Slot},
+    epoch_schedule::EpochSchedule,
+    feature_set,
+    pubkey::Pubkey,
+    vote_group_gen::{EpochAnchoredSeed, SeedSource, VoteGroupGenerator, WindowedSeed},
+};
+use solana_vote_program::vote_state::MAX_LOCKOUT_HISTORY;
+use std::{ops::RangeInclusive, sync::Arc};
+
+/// slots after an epoch boundary the previous epoch's groups are still answered for, a vote
+/// in flight is for one of the last slots of the voter's tower
+pub const DUAL_ACCEPT_WINDOW_SLOTS: Slot = MAX_LOCKOUT_HISTORY as Slot;
+
+/// one epoch's generator with the slots and seeds it answers for
+#[derive(Debug)]
+pub struct EpochVoteGroupGenerator {
+    pub epoch: Epoch,
+    pub slots: RangeInclusive<Slot>,
+    pub generator: Arc<VoteGroupGenerator>,
+    /// None while groups are seeded from voted hashes, only a bank knows those
+    pub seed_source: Option<WindowedSeed<EpochAnchoredSeed>>,
+    /// the seed every group is pinned to by the node local overrides
+    pub preset_seed: Option<u64>,
+}
+
+impl EpochVoteGroupGenerator {
+    pub fn new(
+        epoch: Epoch,
+        epoch_schedule: &EpochSchedule,
+        epoch_stakes: &EpochStakes,
+        slot_seeds: bool,
+    ) -> Self {
+        let first_slot = epoch_schedule.get_first_slot_in_epoch(epoch);
+        Self {
+            epoch,
+            slots: first_slot..=epoch_schedule.get_last_slot_in_epoch(epoch),
+            generator: epoch_stakes.vote_group_generator().clone(),
+            seed_source: if slot_seeds {
+                Some(epoch_stakes.windowed_seed_source(epoch, first_slot))
+            } else {
+                None
+            },
+            preset_seed: None,
+        }
+    }
+
+    pub fn new_from_bank(bank: &Bank) -> Self {
+        let epoch = bank.epoch();
+        Self {
+            preset_seed: bank.vote_group_preset_seed(),
+            ..Self::new(
+                epoch,
+                bank.epoch_schedule(),
+                bank.epoch_stakes(epoch)
+                    .expect("Epoch stakes for bank's own epoch must exist"),
+                bank.feature_set
+                    .is_active(&feature_set::vote_group_slot_seeds::id()),
+            )
+        }
+    }
+
+    /// the seed of `slot`'s group, None for slots of other epochs or without slot seeds
+    /// unless it is pinned
+    pub fn seed_at(&self, slot: Slot) -> Option<u64> {
+        if !self.slots.contains(&slot) {
+            return None;
+        }
+        if self.preset_seed.is_some() {
+            return self.preset_seed;
+        }
+        self.seed_source
+            .as_ref()
+            .map(|source| source.seed_for(slot))
+    }
+
+    pub fn group_at(&self, slot: Slot) -> Option<Vec<Pubkey>> {
+        self.generator.try_group_for_seed(self.seed_at(slot)?).ok()
+    }
+
+    pub fn in_group_at(&self, slot: Slot, key: &Pubkey) -> Option<bool> {
+        self.generator
+            .try_in_group_for_seed(self.seed_at(slot)?, *key)
+            .ok()
+    }
+}
+
+/// the generators readers see at once, swapped together
+#[derive(Debug)]
+pub struct VoteGroupEpochs {
+    pub current: Arc<EpochVoteGroupGenerator>,
+    /// the epoch before `current`, until the dual accept window after the boundary closes
+    pub previous: Option<Arc<EpochVoteGroupGenerator>>,
+}
+
+impl VoteGroupEpochs {
+    /// the generator that answers for `slot`, None for slots of any other epoch
+    pub fn for_slot(&self, slot: Slot) -> Option<&EpochVoteGroupGenerator> {
+        if self.current.slots.contains(&slot) {
+            return Some(&self.current);
+        }
+        self.previous
+            .as_deref()
+            .filter(|previous| previous.slots.contains(&slot))
+    }
+
+    pub fn group_at(&self, slot: Slot) -> Option<Vec<Pubkey>> {
+        self.for_slot(slot)?.group_at(slot)
+    }
+
+    pub fn in_group_at(&self, slot: Slot, key: &Pubkey) -> Option<bool> {
+        self.for_slot(slot)?.in_group_at(slot, key)
+    }
+
+    fn is_dual_accept_closed(&self, slot: Slot) -> bool {
+        self.previous.is_some() && slot >= self.current.slots.start() + DUAL_ACCEPT_WINDOW_SLOTS
+    }
+}
+
+#[derive(Debug)]
+pub struct SharedVoteGroupGenerator {
+    epochs: ArcSwap<VoteGroupEpochs>,
+}
+
+impl SharedVoteGroupGenerator {
+    pub fn new_from_bank(root_bank: &Bank) -> Self {
+        Self {
+            epochs: ArcSwap::from_pointee(VoteGroupEpochs {
+                current: Arc::new(EpochVoteGroupGenerator::new_from_bank(root_bank)),
+                previous: None,
+            }),
+        }
+    }
+
+    pub fn load_epochs(&self) -> Arc<VoteGroupEpochs> {
+        self.epochs.load_full()
+    }
+
+    pub fn load(&self) -> Arc<EpochVoteGroupGenerator> {
+        self.epochs.load().current.clone()
+    }
+
+    pub fn epoch(&self) -> Epoch {
+        self.epochs.load().current.epoch
+    }
+
+    pub fn generator(&self) -> Arc<VoteGroupGenerator> {
+        self.epochs.load().current.generator.clone()
+    }
+
+    pub fn group_at(&self, slot: Slot) -> Option<Vec<Pubkey>> {
+        self.epochs.load().group_at(slot)
+    }
+
+    pub fn in_group_at(&self, slot: Slot, key: &Pubkey) -> Option<bool> {
+        self.epochs.load().in_group_at(slot, key)
+    }
+
+    /// called by replay with each new working bank. the epoch's first bank swaps in the
+    /// epoch's generator, the previous one stays until the dual accept window closes.
+    /// returns whether the generator was swapped
+    pub fn set_working_bank(&self, bank: &Bank) -> bool {
+        self.advance(bank, false)
+    }
+
+    /// catches up when the root enters an epoch no working bank was seen in, and replaces
+    /// the current epoch's generator if a minority fork crossed the boundary first with
+    /// other stakes than the root's. a root from an older epoch never swaps back
+    pub fn set_root(&self, root_bank: &Bank) -> bool {
+        self.advance(root_bank, true)
+    }
+
+    fn advance(&self, bank: &Bank, is_root: bool) -> bool {
+        let epoch = bank.epoch();
+        let slot = bank.slot();
+        let loaded = self.epochs.load_full();
+        let replaces = |current: &EpochVoteGroupGenerator| {
+            current.epoch < epoch
+                || (is_root
+                    && current.epoch == epoch
+                    && bank.epoch_stakes(epoch).map_or(false, |epoch_stakes| {
+                        !Arc::ptr_eq(epoch_stakes.vote_group_generator(), &current.generator)
+                    }))
+        };
+        if !replaces(&loaded.current) && !loaded.is_dual_accept_closed(slot) {
+            return false;
+        }
+        let next = if replaces(&loaded.current) {
+            Some(Arc::new(EpochVoteGroupGenerator::new_from_bank(bank)))
+        } else {
+            None
+        };
+        let previous = self.epochs.rcu(|epochs| match &next {
+            Some(next) if replaces(&epochs.current) => {
+                let previous = if epochs.current.epoch == epoch {
+                    epochs.previous.clone()
+                } else if epochs.current.epoch + 1 == epoch
+                    && slot < next.slots.start() + DUAL_ACCEPT_WINDOW_SLOTS
+                {
+                    Some(epochs.current.clone())
+                } else {
+                    None
+                };
+                Arc::new(VoteGroupEpochs {
+                    current: next.clone(),
+                    previous,
+                })
+            }
+            _ if epochs.is_dual_accept_closed(slot) => Arc::new(VoteGroupEpochs {
+                current: epochs.current.clone(),
+                previous: None,
+            }),
+            _ => epochs.clone(),
+        });
+        match next {
+            Some(next) if replaces(&previous.current) => {
+                info!(
+                    "vote group generator swapped to epoch {} at slot {} ({} voters)",
+                    epoch,
+                    slot,
+                    next.generator.voters().len()
+                );
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::genesis_utils::{
+        create_genesis_config_with_vote_accounts, GenesisConfigInfo, ValidatorVoteKeypairs,
+    };
+    use solana_sdk::signature::Signer;
+
+    #[test]
+    fn test_shared_vote_group_generator() {
+        let validator_keypairs: Vec<_> =
+            (0..10).map(|_| ValidatorVoteKeypairs::new_rand()).collect();
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config_with_vote_accounts(
+            1_000_000_000,
+            &validator_keypairs,
+            vec![10_000; 10],
+        );
+        let bank0 = Arc::new(Bank::new(&genesis_config));
+        let shared = SharedVoteGroupGenerator::new_from_bank(&bank0);
+        assert_eq!(shared.epoch(), 0);
+        assert!(Arc::ptr_eq(
+            &shared.generator(),
+            bank0.epoch_stakes(0).unwrap().vote_group_generator()
+        ));
+
+        // the same groups the bank picks for the epoch's slots, none for the next epoch
+        let current = shared.load();
+        let first_slot_in_epoch1 = bank0.epoch_schedule().get_first_slot_in_epoch(1);
+        for slot in 0..10 {
+            let (generator, seed) = bank0.vote_group_for_slot(slot).unwrap();
+            let expected = generator.group_for_seed(seed);
+            assert_eq!(current.group_at(slot), Some(expected.clone()));
+            for keypairs in &validator_keypairs {
+                let key = keypairs.vote_keypair.pubkey();
+                assert_eq!(
+                    current.in_group_at(slot, &key),
+                    Some(expected.contains(&key))
+                );
+            }
+        }
+        assert_eq!(current.group_at(first_slot_in_epoch1), None);
+
+        // a root in the same epoch keeps the generator
+        let bank1 = Arc::new(Bank::new_from_parent(&bank0, &Pubkey::default(), 1));
+        assert!(!shared.set_root(&bank1));
+        assert!(Arc::ptr_eq(&shared.load(), &current));
+
+        // the first root of the next epoch swaps it, loads taken before keep the old one
+        let bank2 = Bank::new_from_parent(&bank1, &Pubkey::default(), first_slot_in_epoch1);
+        assert!(shared.set_root(&bank2));
+        assert_eq!(shared.epoch(), 1);
+        assert_eq!(current.epoch, 0);
+        assert!(shared.load().group_at(first_slot_in_epoch1).is_some());
+        assert_eq!(shared.load().group_at(0), None);
+
+        // never back to an older epoch
+        assert!(!shared.set_root(&bank1));
+        assert_eq!(shared.epoch(), 1);
+    }
+
+    #[test]
+    fn test_shared_vote_group_generator_epoch_boundary() {
+        let validator_keypairs: Vec<_> =
+            (0..10).map(|_| ValidatorVoteKeypairs::new_rand()).collect();
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config_with_vote_accounts(
+            1_000_000_000,
+            &validator_keypairs,
+            vec![10_000; 10],
+        );
+        let bank0 = Arc::new(Bank::new(&genesis_config));
+        let shared = SharedVoteGroupGenerator::new_from_bank(&bank0);
+        let first_slot_in_epoch1 = bank0.epoch_schedule().get_first_slot_in_epoch(1);
+        let last_slot_in_epoch0 = first_slot_in_epoch1 - 1;
+        let expected_in_group = |bank: &Bank, slot: Slot, key: &Pubkey| {
+            let (generator, seed) = bank.vote_group_for_slot(slot).unwrap();
+            generator.try_in_group_for_seed(seed, *key).unwrap()
+        };
+
+        // the last slot of the epoch keeps the generator
+        let last_bank = Arc::new(Bank::new_from_parent(
+            &bank0,
+            &Pubkey::default(),
+            last_slot_in_epoch0,
+        ));
+        assert!(!shared.set_working_bank(&last_bank));
+        let before = shared.load_epochs();
+        assert_eq!(before.current.epoch, 0);
+        assert!(before.previous.is_none());
+
+        // the boundary slot swaps without waiting for a root in the epoch
+        let first_bank = Arc::new(Bank::new_from_parent(
+            &last_bank,
+            &Pubkey::default(),
+            first_slot_in_epoch1,
+        ));
+        assert!(shared.set_working_bank(&first_bank));
+        assert!(!shared.set_working_bank(&first_bank));
+        assert_eq!(shared.epoch(), 1);
+        assert!(Arc::ptr_eq(
+            &shared.generator(),
+            first_bank.epoch_stakes(1).unwrap().vote_group_generator()
+        ));
+        // loads taken before the swap keep answering for the old epoch only
+        assert_eq!(before.group_at(first_slot_in_epoch1), None);
+        assert!(before.group_at(last_slot_in_epoch0).is_some());
+
+        // votes in flight on the previous epoch's slots are answered by its generator, the
+        // new epoch's by the new one
+        let epochs = shared.load_epochs();
+        assert!(Arc::ptr_eq(
+            epochs.previous.as_ref().unwrap(),
+            &before.current
+        ));
+        for keypairs in &validator_keypairs {
+            let key = keypairs.vote_keypair.pubkey();
+            assert_eq!(
+                shared.in_group_at(last_slot_in_epoch0, &key),
+                Some(expected_in_group(&last_bank, last_slot_in_epoch0, &key))
+            );
+            assert_eq!(
+                shared.in_group_at(first_slot_in_epoch1, &key),
+                Some(expected_in_group(&first_bank, first_slot_in_epoch1, &key))
+            );
+        }
+
+        // rooting the boundary bank changes nothing
+        assert!(!shared.set_root(&first_bank));
+        assert!(Arc::ptr_eq(&shared.load_epochs(), &epochs));
+
+        // the previous epoch is answered until the window closes
+        let in_window = Bank::new_from_parent(
+            &first_bank,
+            &Pubkey::default(),
+            first_slot_in_epoch1 + DUAL_ACCEPT_WINDOW_SLOTS - 1,
+        );
+        assert!(!shared.set_working_bank(&in_window));
+        assert!(shared.group_at(last_slot_in_epoch0).is_some());
+        let past_window = Bank::new_from_parent(
+            &first_bank,
+            &Pubkey::default(),
+            first_slot_in_epoch1 + DUAL_ACCEPT_WINDOW_SLOTS,
+        );
+        assert!(!shared.set_working_bank(&past_window));
+        assert!(shared.load_epochs().previous.is_none());
+        assert_eq!(shared.group_at(last_slot_in_epoch0), None);
+        assert!(shared.group_at(first_slot_in_epoch1).is_some());
+        assert_eq!(shared.epoch(), 1);
+
+        // a root that skips past the window never opens it
+        let shared = SharedVoteGroupGenerator::new_from_bank(&bank0);
+        assert!(shared.set_root(&past_window));
+        assert_eq!(shared.epoch(), 1);
+        assert!(shared.load_epochs().previous.is_none());
+    }
+
+    #[test]
+    fn test_shared_vote_group_generator_hash_seeds() {
+        let validator_keypairs: Vec<_> =
+            (0..3).map(|_| ValidatorVoteKeypairs::new_rand()).collect();
+        let GenesisConfigInfo {
+            mut genesis_config, ..
+        } = create_genesis_config_with_vote_accounts(
+            1_000_000_000,
+            &validator_keypairs,
+            vec![10_000; 3],
+        );
+        genesis_config
+            .accounts
+            .remove(&feature_set::vote_group_slot_seeds::id());
+        let bank = Bank::new(&genesis_config);
+        let shared = SharedVoteGroupGenerator::new_from_bank(&bank);
+        // the generator is shared but only a bank can seed the groups
+        assert!(!shared.generator().voters().is_empty());
+        assert_eq!(shared.load().seed_at(0), None);
+        assert_eq!(shared.load().group_at(0), None);
+    }
+}
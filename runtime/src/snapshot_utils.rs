@@ -42,12 +42,16 @@ pub const SNAPSHOT_STATUS_CACHE_FILE_NAME: &str = "status_cache";
 pub const MAX_SNAPSHOTS: usize = 8; // Save some snapshots but not too many
 const MAX_SNAPSHOT_DATA_FILE_SIZE: u64 = 32 * 1024 * 1024 * 1024; // 32 GiB
 const VERSION_STRING_V1_2_0: &str = "1.2.0";
-const DEFAULT_SNAPSHOT_VERSION: SnapshotVersion = SnapshotVersion::V1_2_0;
+const VERSION_STRING_V1_3_0: &str = "1.3.0";
+const VERSION_STRING_V1_4_0: &str = "1.4.0";
+const DEFAULT_SNAPSHOT_VERSION: SnapshotVersion = SnapshotVersion::V1_4_0;
 const TMP_SNAPSHOT_PREFIX: &str = "tmp-snapshot-";
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 pub enum SnapshotVersion {
     V1_2_0,
+    V1_3_0,
+    V1_4_0,
 }
 
 impl Default for SnapshotVersion {
@@ -66,6 +70,8 @@ impl From<SnapshotVersion> for &'static str {
     fn from(snapshot_version: SnapshotVersion) -> &'static str {
         match snapshot_version {
             SnapshotVersion::V1_2_0 => VERSION_STRING_V1_2_0,
+            SnapshotVersion::V1_3_0 => VERSION_STRING_V1_3_0,
+            SnapshotVersion::V1_4_0 => VERSION_STRING_V1_4_0,
         }
     }
 }
@@ -85,6 +91,8 @@ impl FromStr for SnapshotVersion {
         };
         match version_string {
             VERSION_STRING_V1_2_0 => Ok(SnapshotVersion::V1_2_0),
+            VERSION_STRING_V1_3_0 => Ok(SnapshotVersion::V1_3_0),
+            VERSION_STRING_V1_4_0 => Ok(SnapshotVersion::V1_4_0),
             _ => Err("unsupported snapshot version"),
         }
     }
@@ -520,7 +528,9 @@ pub fn add_snapshot<P: AsRef<Path>>(
     let mut bank_serialize = Measure::start("bank-serialize-ms");
     let bank_snapshot_serializer = move |stream: &mut BufWriter<File>| -> Result<()> {
         let serde_style = match snapshot_version {
-            SnapshotVersion::V1_2_0 => SerdeStyle::Newer,
+            SnapshotVersion::V1_2_0 => SerdeStyle::Older,
+            SnapshotVersion::V1_3_0 => SerdeStyle::Previous,
+            SnapshotVersion::V1_4_0 => SerdeStyle::Newer,
         };
         bank_to_stream(serde_style, stream.by_ref(), bank, snapshot_storages)?;
         Ok(())
@@ -795,21 +805,24 @@ fn rebuild_bank_from_snapshots(
         "Loading bank from {}",
         &root_paths.snapshot_file_path.display()
     );
+    let serde_style = match snapshot_version_enum {
+        SnapshotVersion::V1_2_0 => SerdeStyle::Older,
+        SnapshotVersion::V1_3_0 => SerdeStyle::Previous,
+        SnapshotVersion::V1_4_0 => SerdeStyle::Newer,
+    };
     let bank = deserialize_snapshot_data_file(&root_paths.snapshot_file_path, |mut stream| {
-        Ok(match snapshot_version_enum {
-            SnapshotVersion::V1_2_0 => bank_from_stream(
-                SerdeStyle::Newer,
-                &mut stream,
-                account_paths,
-                unpacked_append_vec_map,
-                genesis_config,
-                frozen_account_pubkeys,
-                debug_keys,
-                additional_builtins,
-                account_indexes,
-                accounts_db_caching_enabled,
-            ),
-        }?)
+        Ok(bank_from_stream(
+            serde_style,
+            &mut stream,
+            account_paths,
+            unpacked_append_vec_map,
+            genesis_config,
+            frozen_account_pubkeys,
+            debug_keys,
+            additional_builtins,
+            account_indexes,
+            accounts_db_caching_enabled,
+        )?)
     })?;
 
     let status_cache_path = unpacked_snapshots_dir.join(SNAPSHOT_STATUS_CACHE_FILE_NAME);
@@ -0,0 +1,165 @@
+use crate::bank::Bank;
+use log::*;
+use solana_sdk::{
+    clock::{Epoch, Slot},
+    epoch_schedule::EpochSchedule,
+    pubkey::Pubkey,
+    vote_group_gen::VoteGroupSchedule,
+};
+use std::{
+    collections::{hash_map::Entry, HashMap, VecDeque},
+    sync::{Arc, RwLock},
+};
+
+type CachedSchedules = (HashMap<Epoch, Arc<VoteGroupSchedule>>, VecDeque<Epoch>);
+// a schedule holds every group of an epoch, keep the previous, current and next epoch
+const MAX_SCHEDULES: usize = 3;
+
+#[derive(Default)]
+pub struct VoteGroupScheduleCache {
+    // Map from an epoch to the vote group schedule for that epoch
+    cached_schedules: RwLock<CachedSchedules>,
+    epoch_schedule: EpochSchedule,
+    max_schedules: usize,
+}
+
+impl VoteGroupScheduleCache {
+    pub fn new_from_bank(bank: &Bank) -> Self {
+        let cache = Self {
+            cached_schedules: RwLock::new((HashMap::new(), VecDeque::new())),
+            epoch_schedule: *bank.epoch_schedule(),
+            max_schedules: MAX_SCHEDULES,
+        };
+        cache.set_root(bank);
+        cache
+    }
+
+    /// computes the root's epoch and prefetches the next one so the boundary never stalls votes
+    pub fn set_root(&self, root_bank: &Bank) {
+        let epoch = self.epoch_schedule.get_epoch(root_bank.slot());
+        self.get_epoch_schedule_else_compute(epoch, root_bank);
+        self.get_epoch_schedule_else_compute(epoch + 1, root_bank);
+    }
+
+    pub fn group_at(&self, slot: Slot, bank: Option<&Bank>) -> Option<Vec<Pubkey>> {
+        self.get_schedule_for_slot(slot, bank)?.group_at(slot)
+    }
+
+    pub fn in_group_at(&self, slot: Slot, key: &Pubkey, bank: Option<&Bank>) -> Option<bool> {
+        self.get_schedule_for_slot(slot, bank)?
+            .in_group_at(slot, key)
+    }
+
+    fn get_schedule_for_slot(
+        &self,
+        slot: Slot,
+        bank: Option<&Bank>,
+    ) -> Option<Arc<VoteGroupSchedule>> {
+        let epoch = self.epoch_schedule.get_epoch(slot);
+        match bank {
+            Some(bank) => self.get_epoch_schedule_else_compute(epoch, bank),
+            None => self.cached_schedules.read().unwrap().0.get(&epoch).cloned(),
+        }
+    }
+
+    pub fn get_epoch_schedule_else_compute(
+        &self,
+        epoch: Epoch,
+        bank: &Bank,
+    ) -> Option<Arc<VoteGroupSchedule>> {
+        if let Some(schedule) = self.cached_schedules.read().unwrap().0.get(&epoch) {
+            return Some(schedule.clone());
+        }
+        self.compute_epoch_schedule(epoch, bank)
+    }
+
+    fn compute_epoch_schedule(&self, epoch: Epoch, bank: &Bank) -> Option<Arc<VoteGroupSchedule>> {
+        let epoch_stakes = bank.epoch_stakes(epoch)?;
+        let schedule = Arc::new(VoteGroupSchedule::new(
+            epoch_stakes.get_group_genr(),
+            epoch,
+            self.epoch_schedule.get_first_slot_in_epoch(epoch),
+            self.epoch_schedule.get_slots_in_epoch(epoch),
+            epoch_stakes.vote_group_anchor(),
+        ));
+        let mut cached_schedules = self.cached_schedules.write().unwrap();
+        let (ref mut schedules, ref mut order) = *cached_schedules;
+        // Check to see if schedule exists in case somebody already inserted in the time we were
+        // waiting for the write lock
+        let entry = schedules.entry(epoch);
+        if let Entry::Vacant(v) = entry {
+            debug!("computed vote group schedule for epoch {}", epoch);
+            v.insert(schedule.clone());
+            order.push_back(epoch);
+            Self::retain_latest(schedules, order, self.max_schedules);
+        }
+        schedules.get(&epoch).cloned()
+    }
+
+    fn retain_latest(
+        schedules: &mut HashMap<Epoch, Arc<VoteGroupSchedule>>,
+        order: &mut VecDeque<Epoch>,
+        max_schedules: usize,
+    ) {
+        while schedules.len() > max_schedules {
+            let epoch = order.pop_front().unwrap();
+            schedules.remove(&epoch);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::genesis_utils::{
+        create_genesis_config_with_vote_accounts, GenesisConfigInfo, ValidatorVoteKeypairs,
+    };
+    use solana_sdk::{signature::Signer, vote_group_gen::seed_for_slot};
+
+    #[test]
+    fn test_vote_group_schedule_cache() {
+        let validator_keypairs: Vec<_> =
+            (0..10).map(|_| ValidatorVoteKeypairs::new_rand()).collect();
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config_with_vote_accounts(
+            1_000_000_000,
+            &validator_keypairs,
+            vec![10_000; 10],
+        );
+        let bank = Bank::new(&genesis_config);
+        let cache = VoteGroupScheduleCache::new_from_bank(&bank);
+
+        // the root's epoch and the next one are ready without a bank
+        assert!(cache.group_at(0, None).is_some());
+        let next_epoch_slot = bank.epoch_schedule().get_first_slot_in_epoch(1);
+        assert!(cache.group_at(next_epoch_slot, None).is_some());
+
+        let epoch_stakes = bank.epoch_stakes(0).unwrap();
+        let generator = epoch_stakes.get_group_genr();
+        for slot in 0..10 {
+            let expected =
+                generator.group_for_seed(seed_for_slot(slot, 0, epoch_stakes.vote_group_anchor()));
+            assert_eq!(cache.group_at(slot, Some(&bank)), Some(expected.clone()));
+            for keypairs in &validator_keypairs {
+                let key = keypairs.vote_keypair.pubkey();
+                assert_eq!(
+                    cache.in_group_at(slot, &key, None),
+                    Some(expected.contains(&key))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_retain_latest() {
+        let mut schedules = HashMap::new();
+        let mut order = VecDeque::new();
+        for epoch in 0..5 {
+            schedules.insert(epoch, Arc::new(VoteGroupSchedule::default()));
+            order.push_back(epoch);
+        }
+        VoteGroupScheduleCache::retain_latest(&mut schedules, &mut order, MAX_SCHEDULES);
+        assert_eq!(schedules.len(), MAX_SCHEDULES);
+        assert_eq!(order, vec![2, 3, 4]);
+        assert!(!schedules.contains_key(&1));
+    }
+}
@@ -0,0 +1,169 @@
+//! vote group transition
+//!  in the epoch limited voting activates in the vote program still accepts votes from
+//!  outside the voted slot's group and only records the outcome of the group check here.
+//!  each rooted slot is then checked twice, once for the full voter set and once for the
+//!  slot's group, so the cluster can see the groups would have confirmed every slot before
+//!  they're enforced in the next epoch
+use crate::{bank::Bank, commitment::VOTE_THRESHOLD_SIZE};
+use log::*;
+use solana_metrics::datapoint_info;
+use solana_sdk::{clock::Slot, hash::Hash, pubkey::Pubkey};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::RwLock,
+};
+
+/// the voters recorded for one voted slot and hash
+#[derive(AbiExample, Clone, Debug, Default, PartialEq, Eq)]
+pub struct SlotVotes {
+    pub all: HashSet<Pubkey>,
+    pub in_group: HashSet<Pubkey>,
+}
+
+/// the rooted slots so far and which of them each voter set confirmed
+#[derive(AbiExample, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TransitionSummary {
+    pub reported_slots: u64,
+    pub confirmed_slots: u64,
+    pub group_confirmed_slots: u64,
+    /// confirmed by the full set but not by the slot's group
+    pub missed_slots: u64,
+}
+
+impl TransitionSummary {
+    /// whether the groups confirmed every slot the full set did
+    pub fn is_clean(&self) -> bool {
+        self.missed_slots == 0
+    }
+}
+
+/// shared by every bank of a fork, votes are recorded as banks replay them and reported
+/// once their slot is rooted
+#[derive(AbiExample, Debug, Default)]
+pub struct VoteGroupTransition {
+    votes: RwLock<HashMap<(Slot, Hash), SlotVotes>>,
+    summary: RwLock<TransitionSummary>,
+}
+
+impl VoteGroupTransition {
+    pub fn record(&self, slot: Slot, hash: Hash, voter: Pubkey, in_group: bool) {
+        let mut votes = self.votes.write().unwrap();
+        let slot_votes = votes.entry((slot, hash)).or_default();
+        slot_votes.all.insert(voter);
+        if in_group {
+            slot_votes.in_group.insert(voter);
+        }
+    }
+
+    pub fn slot_votes(&self, slot: Slot, hash: Hash) -> Option<SlotVotes> {
+        self.votes.read().unwrap().get(&(slot, hash)).cloned()
+    }
+
+    pub fn summary(&self) -> TransitionSummary {
+        *self.summary.read().unwrap()
+    }
+
+    /// checks the slots up to `root` against both voter sets and forgets their votes
+    pub fn report(&self, root: Slot, root_bank: &Bank) {
+        let rooted: Vec<((Slot, Hash), SlotVotes)> = {
+            let mut votes = self.votes.write().unwrap();
+            let keys: Vec<(Slot, Hash)> = votes
+                .keys()
+                .filter(|(slot, _)| *slot <= root)
+                .copied()
+                .collect();
+            keys.into_iter()
+                .filter_map(|key| votes.remove_entry(&key))
+                .collect()
+        };
+        if rooted.is_empty() {
+            return;
+        }
+        let fraction = root_bank.vote_group_quorum_fraction();
+        let mut summary = self.summary.write().unwrap();
+        for ((slot, hash), slot_votes) in rooted {
+            let epoch = root_bank.epoch_schedule().get_epoch(slot);
+            let epoch_stakes = match root_bank.epoch_stakes(epoch) {
+                Some(epoch_stakes) => epoch_stakes,
+                None => continue,
+            };
+            let vote_accounts = epoch_stakes.stakes().vote_accounts();
+            let stake_of = |voters: &HashSet<Pubkey>| -> u64 {
+                voters
+                    .iter()
+                    .filter_map(|voter| vote_accounts.get(voter))
+                    .map(|(stake, _)| *stake)
+                    .sum()
+            };
+            let total_stake = epoch_stakes.total_stake();
+            let voted_stake = stake_of(&slot_votes.all);
+            let group_stake = stake_of(&slot_votes.in_group);
+            let confirmed =
+                total_stake > 0 && voted_stake as f64 / total_stake as f64 > VOTE_THRESHOLD_SIZE;
+            let in_group: Vec<Pubkey> = slot_votes.in_group.iter().copied().collect();
+            let group_confirmed = root_bank
+                .vote_group_seed(slot, hash)
+                .map(|(stakes, seed)| {
+                    stakes
+                        .get_group_genr()
+                        .has_quorum_with_fraction(&in_group, seed, fraction)
+                })
+                .unwrap_or(false);
+
+            summary.reported_slots += 1;
+            if confirmed {
+                summary.confirmed_slots += 1;
+            }
+            if group_confirmed {
+                summary.group_confirmed_slots += 1;
+            }
+            if confirmed && !group_confirmed {
+                summary.missed_slots += 1;
+                warn!(
+                    "vote group for slot {} {} missed a slot the full voter set confirmed",
+                    slot, hash
+                );
+            }
+            datapoint_info!(
+                "vote_group_transition",
+                ("slot", slot, i64),
+                ("voters", slot_votes.all.len(), i64),
+                ("group_voters", slot_votes.in_group.len(), i64),
+                ("voted_stake", voted_stake, i64),
+                ("group_voted_stake", group_stake, i64),
+                ("total_stake", total_stake, i64),
+                ("confirmed", confirmed, bool),
+                ("group_confirmed", group_confirmed, bool),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record() {
+        let transition = VoteGroupTransition::default();
+        let (member, outsider, hash) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Hash::new_unique(),
+        );
+        transition.record(3, hash, member, true);
+        transition.record(3, hash, outsider, false);
+        // replaying the vote in another bank of the fork doesn't count it twice
+        transition.record(3, hash, member, true);
+
+        let slot_votes = transition.slot_votes(3, hash).unwrap();
+        assert_eq!(slot_votes.all.len(), 2);
+        assert_eq!(
+            slot_votes.in_group.into_iter().collect::<Vec<_>>(),
+            vec![member]
+        );
+        assert_eq!(transition.slot_votes(3, Hash::new_unique()), None);
+        assert_eq!(transition.slot_votes(4, hash), None);
+        assert!(transition.summary().is_clean());
+    }
+}
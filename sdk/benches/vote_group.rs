@@ -0,0 +1,140 @@
+#![feature(test)]
+
+extern crate test;
+use solana_sdk::{pubkey::Pubkey, vote_group_gen::VoteGroupGenerator};
+use std::collections::HashMap;
+use test::Bencher;
+
+const GROUP_SIZE: usize = 32;
+const BATCH_SIZE: usize = 1_000;
+
+fn generator(num_voters: usize) -> (VoteGroupGenerator, Vec<Pubkey>) {
+    let voters: HashMap<Pubkey, Pubkey> = (0..num_voters)
+        .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+        .collect();
+    let generator = VoteGroupGenerator::new(&voters, GROUP_SIZE);
+    let keys = generator.voters().to_vec();
+    (generator, keys)
+}
+
+// the same seed every time, only the first check walks the group
+fn bench_in_group_cached(b: &mut Bencher, num_voters: usize) {
+    let (generator, keys) = generator(num_voters);
+    let mut i = 0;
+    b.iter(|| {
+        i = (i + 1) % keys.len();
+        generator.try_in_group_for_seed(42, keys[i]).unwrap()
+    });
+}
+
+// a new seed every time, every check walks the group
+fn bench_in_group_uncached(b: &mut Bencher, num_voters: usize) {
+    let (generator, keys) = generator(num_voters);
+    let mut seed = 0;
+    b.iter(|| {
+        seed += 1;
+        generator
+            .try_in_group_for_seed(seed, keys[seed as usize % keys.len()])
+            .unwrap()
+    });
+}
+
+fn bench_in_group_batch(b: &mut Bencher, num_voters: usize) {
+    let (generator, keys) = generator(num_voters);
+    let batch: Vec<_> = keys.iter().cycle().take(BATCH_SIZE).copied().collect();
+    let mut seed = 0;
+    b.iter(|| {
+        seed += 1;
+        generator.in_group_for_seed_batch(seed, &batch)
+    });
+}
+
+fn bench_par_in_group_batch(b: &mut Bencher, num_voters: usize) {
+    let (generator, keys) = generator(num_voters);
+    let batch: Vec<_> = keys.iter().cycle().take(BATCH_SIZE).copied().collect();
+    let mut seed = 0;
+    b.iter(|| {
+        seed += 1;
+        generator.par_in_group_for_seed_batch(seed, &batch)
+    });
+}
+
+#[bench]
+fn bench_in_group_cached_100(b: &mut Bencher) {
+    bench_in_group_cached(b, 100);
+}
+
+#[bench]
+fn bench_in_group_cached_1k(b: &mut Bencher) {
+    bench_in_group_cached(b, 1_000);
+}
+
+#[bench]
+fn bench_in_group_cached_10k(b: &mut Bencher) {
+    bench_in_group_cached(b, 10_000);
+}
+
+#[bench]
+fn bench_in_group_cached_100k(b: &mut Bencher) {
+    bench_in_group_cached(b, 100_000);
+}
+
+#[bench]
+fn bench_in_group_uncached_100(b: &mut Bencher) {
+    bench_in_group_uncached(b, 100);
+}
+
+#[bench]
+fn bench_in_group_uncached_1k(b: &mut Bencher) {
+    bench_in_group_uncached(b, 1_000);
+}
+
+#[bench]
+fn bench_in_group_uncached_10k(b: &mut Bencher) {
+    bench_in_group_uncached(b, 10_000);
+}
+
+#[bench]
+fn bench_in_group_uncached_100k(b: &mut Bencher) {
+    bench_in_group_uncached(b, 100_000);
+}
+
+#[bench]
+fn bench_in_group_batch_100(b: &mut Bencher) {
+    bench_in_group_batch(b, 100);
+}
+
+#[bench]
+fn bench_in_group_batch_1k(b: &mut Bencher) {
+    bench_in_group_batch(b, 1_000);
+}
+
+#[bench]
+fn bench_in_group_batch_10k(b: &mut Bencher) {
+    bench_in_group_batch(b, 10_000);
+}
+
+#[bench]
+fn bench_in_group_batch_100k(b: &mut Bencher) {
+    bench_in_group_batch(b, 100_000);
+}
+
+#[bench]
+fn bench_par_in_group_batch_100(b: &mut Bencher) {
+    bench_par_in_group_batch(b, 100);
+}
+
+#[bench]
+fn bench_par_in_group_batch_1k(b: &mut Bencher) {
+    bench_par_in_group_batch(b, 1_000);
+}
+
+#[bench]
+fn bench_par_in_group_batch_10k(b: &mut Bencher) {
+    bench_par_in_group_batch(b, 10_000);
+}
+
+#[bench]
+fn bench_par_in_group_batch_100k(b: &mut Bencher) {
+    bench_par_in_group_batch(b, 100_000);
+}
@@ -0,0 +1,97 @@
+#![feature(test)]
+
+extern crate test;
+use solana_sdk::{
+    pubkey::Pubkey,
+    vote_group_gen::{SelectionAlgorithm, VoteGroupGenerator},
+};
+use std::collections::HashMap;
+use test::Bencher;
+
+const NUM_VOTERS: usize = 100_000;
+
+fn voters(num_voters: usize) -> HashMap<Pubkey, Pubkey> {
+    (0..num_voters)
+        .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+        .collect()
+}
+
+// groups of a share of the voters, what a dynamic group size picks on a large cluster
+fn generator(group_size: usize, algorithm: SelectionAlgorithm) -> VoteGroupGenerator {
+    VoteGroupGenerator::new(&voters(NUM_VOTERS), group_size)
+        .with_algorithm(algorithm)
+        .with_distinct_members(true)
+}
+
+// a new seed every time, every check walks the group
+fn bench_in_group_uncached(b: &mut Bencher, group_size: usize, algorithm: SelectionAlgorithm) {
+    let generator = generator(group_size, algorithm);
+    let keys = generator.voters().to_vec();
+    let mut seed = 0;
+    b.iter(|| {
+        seed += 1;
+        generator
+            .try_in_group_for_seed(seed, keys[seed as usize % keys.len()])
+            .unwrap()
+    });
+}
+
+#[bench]
+fn bench_new_100k(b: &mut Bencher) {
+    let map = voters(NUM_VOTERS);
+    b.iter(|| VoteGroupGenerator::new(&map, 32));
+}
+
+#[bench]
+fn bench_new_250k(b: &mut Bencher) {
+    let map = voters(250_000);
+    b.iter(|| VoteGroupGenerator::new(&map, 32));
+}
+
+#[bench]
+fn bench_in_group_uncached_100k_1_percent_v1(b: &mut Bencher) {
+    bench_in_group_uncached(b, NUM_VOTERS / 100, SelectionAlgorithm::V1);
+}
+
+#[bench]
+fn bench_in_group_uncached_100k_1_percent_v2(b: &mut Bencher) {
+    bench_in_group_uncached(b, NUM_VOTERS / 100, SelectionAlgorithm::V2);
+}
+
+#[bench]
+fn bench_in_group_uncached_100k_1_percent_v3(b: &mut Bencher) {
+    bench_in_group_uncached(b, NUM_VOTERS / 100, SelectionAlgorithm::V3);
+}
+
+#[bench]
+fn bench_in_group_uncached_100k_10_percent_v2(b: &mut Bencher) {
+    bench_in_group_uncached(b, NUM_VOTERS / 10, SelectionAlgorithm::V2);
+}
+
+#[bench]
+fn bench_in_group_uncached_100k_10_percent_v3(b: &mut Bencher) {
+    bench_in_group_uncached(b, NUM_VOTERS / 10, SelectionAlgorithm::V3);
+}
+
+// the group is cached, a position is a map lookup rather than a walk
+#[bench]
+fn bench_position_in_group_100k_10_percent(b: &mut Bencher) {
+    let generator = generator(NUM_VOTERS / 10, SelectionAlgorithm::V2);
+    let group = generator.group_for_seed(42);
+    let mut i = 0;
+    b.iter(|| {
+        i = (i + 1) % group.len();
+        generator.position_in_group(42, &group[i])
+    });
+}
+
+#[bench]
+fn bench_index_of_100k(b: &mut Bencher) {
+    let generator = generator(32, SelectionAlgorithm::V2);
+    let keys = generator.voters().to_vec();
+    let mut i = 0;
+    b.iter(|| {
+        i = (i + 1) % keys.len();
+        generator.index_of(&keys[i])
+    });
+}
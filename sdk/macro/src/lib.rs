@@ -110,6 +110,10 @@ impl Parse for RespanInput {
                     respan_using: ident.span(),
                 })
             }
+            TokenTree::Ident(ident) => Ok(RespanInput {
+                to_respan,
+                respan_using: ident.span(),
+            }),
             val => Err(syn::Error::new_spanned(
                 val,
                 "expected None-delimited group",
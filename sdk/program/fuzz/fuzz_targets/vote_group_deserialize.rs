@@ -0,0 +1,41 @@
+//! Feeds arbitrary bytes to the generator's deserializer, a generator that passes
+//! `check_state` must then be safe to walk groups with
+
+#![no_main]
+use bincode::Options;
+use libfuzzer_sys::fuzz_target;
+use solana_program::{pubkey::Pubkey, vote_group_gen::VoteGroupGenerator};
+
+// bounds the vectors a hostile length prefix can ask for
+const MAX_DESERIALIZE_BYTES: u64 = 64 * 1024;
+// walking a group is linear in its size, anything bigger only finds timeouts
+const MAX_GROUP_SIZE: usize = 4096;
+
+fuzz_target!(|data: &[u8]| {
+    let generator: VoteGroupGenerator = match bincode::options()
+        .with_limit(MAX_DESERIALIZE_BYTES)
+        .with_fixint_encoding()
+        .allow_trailing_bytes()
+        .deserialize(data)
+    {
+        Ok(generator) => generator,
+        Err(_) => return,
+    };
+    if generator.check_state().is_err() || generator.group_size() > MAX_GROUP_SIZE {
+        return;
+    }
+    for seed in &[0, 1, u64::MAX, data.len() as u64] {
+        for member in generator.group_for_seed(*seed) {
+            assert_eq!(generator.try_in_group_for_seed(*seed, member), Ok(true));
+        }
+        assert!(
+            !generator
+                .try_in_group_for_seed(*seed, Pubkey::new_from_array([0xff; 32]))
+                .unwrap_or(false)
+                || generator
+                    .voters()
+                    .contains(&Pubkey::new_from_array([0xff; 32]))
+        );
+        let _ = generator.distance_for_seed(*seed);
+    }
+});
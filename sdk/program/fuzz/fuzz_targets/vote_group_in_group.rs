@@ -0,0 +1,66 @@
+//! Builds generators out of adversarial voter sets and checks that walking their groups
+//! never panics and that membership agrees with the group that was walked
+
+#![no_main]
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use solana_program::{
+    hash::Hash,
+    pubkey::Pubkey,
+    vote_group_gen::{seed_from_bytes, ExclusionList, SelectionAlgorithm, VoteGroupGenerator},
+};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    voters: Vec<([u8; 32], u32)>, // stakes stay well below an overflowing total, like real ones
+    group_size: u16,
+    v2: bool,
+    distinct_members: bool,
+    stake_weighted: bool,
+    hash: [u8; 32],
+    test_key: [u8; 32],
+}
+
+fuzz_target!(|input: Input| {
+    let map: HashMap<Pubkey, Pubkey> = input
+        .voters
+        .iter()
+        .map(|(key, _)| (Pubkey::new_from_array(*key), Pubkey::default()))
+        .collect();
+    let algorithm = if input.v2 {
+        SelectionAlgorithm::V2
+    } else {
+        SelectionAlgorithm::V1
+    };
+    let size = input.group_size as usize;
+    let generator = if input.stake_weighted {
+        let stakes: HashMap<Pubkey, u64> = input
+            .voters
+            .iter()
+            .map(|(key, stake)| (Pubkey::new_from_array(*key), *stake as u64))
+            .collect();
+        VoteGroupGenerator::new_stake_weighted(&map, &stakes, size, &ExclusionList::default())
+    } else {
+        VoteGroupGenerator::new(&map, size)
+    }
+    .with_algorithm(algorithm)
+    .with_distinct_members(input.distinct_members);
+    assert!(generator.check_state().is_ok());
+
+    let hash = Hash::new_from_array(input.hash);
+    let group = generator.group_for_hash(hash);
+    let voters: HashSet<&Pubkey> = generator.voters().iter().collect();
+    for member in &group {
+        assert!(voters.contains(member));
+        assert!(generator.in_group_for_hash(hash, *member));
+    }
+    let test_key = Pubkey::new_from_array(input.test_key);
+    assert_eq!(
+        generator.in_group_for_hash(hash, test_key),
+        group.contains(&test_key)
+    );
+    let seed = seed_from_bytes(&input.hash).unwrap();
+    assert_eq!(generator.group_for_seed(seed), group);
+    let _ = generator.distance_for_seed(seed);
+});
@@ -0,0 +1,14 @@
+//! Folds arbitrary bytes into seeds, every length must either fold or be rejected, and
+//! hashing the same bytes into a seed must never fail
+
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use solana_program::vote_group_gen::{seed_for_material, seed_from_bytes, VoteGroupError};
+
+fuzz_target!(|data: &[u8]| {
+    match seed_from_bytes(data) {
+        Ok(_) => assert!(!data.is_empty() && data.len() % 8 == 0),
+        Err(err) => assert_eq!(err, VoteGroupError::BadSeedMaterial(data.len())),
+    }
+    assert_eq!(seed_for_material(data), seed_for_material(data));
+});
@@ -1,7 +1,10 @@
 //! Defines a composable Instruction type and a memory-efficient CompiledInstruction.
 
 use crate::sanitize::Sanitize;
-use crate::{pubkey::Pubkey, short_vec,clock::Slot,hash::Hash};
+use crate::{pubkey::Pubkey, short_vec,clock::Slot,hash::Hash,vrf::VrfProof};
+use crate::vote_group_gen::{
+    MembershipCommitment, MembershipReveal, DEFAULT_OUT_OF_GROUP_VOTE_THRESHOLD,
+};
 use bincode::serialize;
 use borsh::BorshSerialize;
 use serde::Serialize;
@@ -9,6 +12,28 @@ use thiserror::Error;
 
 pub trait VoterGroup {
     fn in_group(&self,slot: Slot,hash : Hash, test_key: Pubkey) -> bool ;
+    /// membership proven by the voter's VRF sortition proof rather than the ring walk
+    fn in_group_with_proof(&self, slot: Slot, hash: Hash, test_key: Pubkey, proof: &VrfProof) -> bool;
+    /// membership proven by revealing the secret the voter committed to for the slot's epoch
+    fn in_group_with_reveal(
+        &self,
+        slot: Slot,
+        hash: Hash,
+        test_key: Pubkey,
+        commitments: &[MembershipCommitment],
+        reveal: &MembershipReveal,
+    ) -> bool;
+    /// out of group votes in an epoch before a vote account is penalized
+    fn out_of_group_vote_threshold(&self) -> u64 {
+        DEFAULT_OUT_OF_GROUP_VOTE_THRESHOLD
+    }
+    /// whether votes from outside the group are still accepted, only in the epoch limited
+    /// voting activates in
+    fn accepts_out_of_group_votes(&self) -> bool {
+        false
+    }
+    /// every vote accepted while out of group votes are, with the outcome of its group check
+    fn record_transition_vote(&self, _slot: Slot, _hash: Hash, _voter: Pubkey, _in_group: bool) {}
 }
 #[derive(Debug, Clone)]
 pub struct MockVoterGrp {
@@ -19,6 +44,19 @@ impl VoterGroup for MockVoterGrp {
     fn in_group(&self,_: Slot,_ : Hash, _: Pubkey) -> bool {
         true
     }
+    fn in_group_with_proof(&self, _: Slot, _: Hash, _: Pubkey, _: &VrfProof) -> bool {
+        true
+    }
+    fn in_group_with_reveal(
+        &self,
+        _: Slot,
+        _: Hash,
+        _: Pubkey,
+        _: &[MembershipCommitment],
+        _: &MembershipReveal,
+    ) -> bool {
+        true
+    }
 }
 impl Default for MockVoterGrp {
     fn default() -> Self {
@@ -33,6 +71,30 @@ impl MockVoterGrp {
     }
 }
 
+/// every voter is in every group, what votes are checked against until limited voting is
+/// active
+#[derive(Debug, Default, Clone, Copy)]
+pub struct AnyVoter;
+
+impl VoterGroup for AnyVoter {
+    fn in_group(&self, _: Slot, _: Hash, _: Pubkey) -> bool {
+        true
+    }
+    fn in_group_with_proof(&self, _: Slot, _: Hash, _: Pubkey, _: &VrfProof) -> bool {
+        true
+    }
+    fn in_group_with_reveal(
+        &self,
+        _: Slot,
+        _: Hash,
+        _: Pubkey,
+        _: &[MembershipCommitment],
+        _: &MembershipReveal,
+    ) -> bool {
+        true
+    }
+}
+
 /// Reasons the runtime might have rejected an instruction.
 #[derive(
     Serialize, Deserialize, Debug, Error, PartialEq, Eq, Clone, AbiExample, AbiEnumVisitor,
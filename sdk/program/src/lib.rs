@@ -45,6 +45,7 @@ pub mod slot_history;
 pub mod stake_history;
 pub mod system_instruction;
 pub mod vote_group_gen;
+pub mod vrf;
 
 pub mod system_program;
 pub mod sysvar;
@@ -56,6 +57,24 @@ pub mod config {
 }
 
 pub mod vote {
+    pub mod group_config {
+        crate::declare_id!("VoteGroupConfig1111111111111111111111111111");
+    }
+
+    pub mod group_exclusions {
+        crate::declare_id!("VoteGroupNeverVoters11111111111111111111111");
+    }
+
+    pub mod group_admin {
+        pub mod program {
+            crate::declare_id!("VoteGroupAdmin11111111111111111111111111111");
+        }
+
+        pub mod params {
+            crate::declare_id!("VoteGroupParams1111111111111111111111111111");
+        }
+    }
+
     pub mod program {
         crate::declare_id!("Vote111111111111111111111111111111111111111");
     }
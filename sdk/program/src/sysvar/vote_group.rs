@@ -0,0 +1,55 @@
+//! named accounts for synthesized data accounts for bank state, etc.
+//!
+//! this account carries the vote group of the most recent slot whose group is known,
+//! so programs can read it instead of redoing the selection
+//!
+use crate::{clock::Slot, pubkey::Pubkey, sysvar::Sysvar};
+
+crate::declare_sysvar_id!("SysvarVoteGroup1111111111111111111111111111", VoteGroup);
+
+#[repr(C)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct VoteGroup {
+    /// the slot votes must be for to be judged against this group
+    pub slot: Slot,
+    /// the seed the group was selected with
+    pub seed: u64,
+    /// group members in selection order
+    pub members: Vec<Pubkey>,
+    /// slots the group lasts before the next one is selected
+    pub rotation_window_slots: u64,
+    /// the first slot of the window, the group is seeded by it
+    pub window_start: Slot,
+}
+
+impl VoteGroup {
+    pub fn contains(&self, key: &Pubkey) -> bool {
+        self.members.contains(key)
+    }
+}
+
+impl Sysvar for VoteGroup {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vote_group_round_trip() {
+        let member = Pubkey::new_unique();
+        let vote_group = VoteGroup {
+            slot: 42,
+            seed: 7,
+            members: vec![member, Pubkey::new_unique()],
+            rotation_window_slots: 4,
+            window_start: 40,
+        };
+        let data = bincode::serialize(&vote_group).unwrap();
+        assert_eq!(
+            bincode::deserialize::<VoteGroup>(&data).unwrap(),
+            vote_group
+        );
+        assert!(vote_group.contains(&member));
+        assert!(!vote_group.contains(&Pubkey::new_unique()));
+    }
+}
@@ -0,0 +1,169 @@
+//! Walker/Vose alias table used to pick voters in proportion to their stake
+//!
+//! Built entirely with integer math so every node constructs the exact same table.
+
+use super::selection_core::{AliasColumns, Taken};
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    Serialize,
+    Deserialize,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    AbiExample,
+    PartialEq,
+)]
+pub struct AliasTable {
+    weights: Vec<u64>,
+    /// how much of each column belongs to its own index, out of `total`
+    prob: Vec<u64>,
+    /// the index that owns the rest of the column
+    alias: Vec<u32>,
+    total: u64,
+}
+
+impl AliasTable {
+    pub fn new(weights: &[u64]) -> Self {
+        let n = weights.len();
+        let total: u64 = weights.iter().sum();
+        if n == 0 || total == 0 {
+            return Self::default();
+        }
+        // scale every weight by n so the average column is exactly `total`
+        let mut scaled: Vec<u128> = weights.iter().map(|w| *w as u128 * n as u128).collect();
+        let mut prob = vec![total; n];
+        let mut alias: Vec<u32> = (0..n as u32).collect();
+        let (mut small, mut large): (Vec<usize>, Vec<usize>) =
+            (0..n).partition(|i| scaled[*i] < total as u128);
+        while let (Some(l), Some(g)) = (small.pop(), large.pop()) {
+            prob[l] = scaled[l] as u64;
+            alias[l] = g as u32;
+            scaled[g] = scaled[g] + scaled[l] - total as u128;
+            if scaled[g] < total as u128 {
+                small.push(g);
+            } else {
+                large.push(g);
+            }
+        }
+        Self {
+            weights: weights.to_vec(),
+            prob,
+            alias,
+            total,
+        }
+    }
+
+    pub fn weights(&self) -> &[u64] {
+        &self.weights
+    }
+
+    /// sum of the weights
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    pub fn len(&self) -> usize {
+        self.prob.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.prob.is_empty()
+    }
+
+    /// true when the table covers exactly `len` voters and every column points inside it,
+    /// anything else would index out of bounds while sampling
+    pub fn is_consistent(&self, len: usize) -> bool {
+        self.weights.len() == len
+            && self.prob.len() == len
+            && self.alias.len() == len
+            && self.total > 0
+            && self.total == self.weights.iter().fold(0u64, |a, w| a.saturating_add(*w))
+            && self.alias.iter().all(|a| (*a as usize) < len)
+    }
+
+    /// the table as borrowed slices, for the allocation free sampling in selection_core
+    pub fn columns(&self) -> AliasColumns<'_> {
+        AliasColumns {
+            weights: &self.weights,
+            prob: &self.prob,
+            alias: &self.alias,
+            total: self.total,
+        }
+    }
+
+    /// see AliasColumns::sample_distinct
+    pub fn sample_distinct(&self, count: usize, seed: u64, excluded: &[usize]) -> Vec<usize> {
+        let mut indexes = vec![0; count.min(self.len())];
+        let mut words = vec![0; Taken::words_for(self.len(), indexes.len())];
+        let len = self.columns().sample_distinct(
+            seed,
+            excluded,
+            &mut Taken::new(&mut words),
+            &mut indexes,
+        );
+        indexes.truncate(len);
+        indexes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alias_table_empty() {
+        assert!(AliasTable::new(&[]).is_empty());
+        assert!(AliasTable::new(&[0, 0]).is_empty());
+    }
+
+    #[test]
+    fn test_alias_table_exact_distribution() {
+        // every (column, coin) pair is walked once, so the counts are exact
+        let weights = [1, 2, 3, 6];
+        let table = AliasTable::new(&weights);
+        let mut counts = [0u64; 4];
+        for column in 0..weights.len() as u64 {
+            for coin in 0..table.total {
+                counts[table.columns().sample(column, coin)] += 1;
+            }
+        }
+        let n = weights.len() as u64;
+        for (count, weight) in counts.iter().zip(weights.iter()) {
+            assert_eq!(*count, weight * n);
+        }
+    }
+
+    #[test]
+    fn test_alias_table_sample_distinct() {
+        let table = AliasTable::new(&[5, 0, 5, 1_000_000, 3, 7]);
+        for seed in 0..50 {
+            let picked = table.sample_distinct(3, seed, &[2]);
+            assert_eq!(picked.len(), 3);
+            assert!(!picked.contains(&1) && !picked.contains(&2));
+            let mut sorted = picked.clone();
+            sorted.sort_unstable();
+            sorted.dedup();
+            assert_eq!(sorted.len(), 3);
+            assert_eq!(picked, table.sample_distinct(3, seed, &[2]));
+        }
+        // only four indexes can be picked, the huge weight starves the draws
+        let mut all = table.sample_distinct(10, 9, &[2]);
+        all.sort_unstable();
+        assert_eq!(all, vec![0, 3, 4, 5]);
+        assert!(AliasTable::default().sample_distinct(3, 9, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_alias_table_zero_weight_never_sampled() {
+        let table = AliasTable::new(&[5, 0, 5]);
+        for column in 0..3 {
+            for coin in 0..10 {
+                assert_ne!(table.columns().sample(column, coin), 1);
+            }
+        }
+    }
+}
@@ -0,0 +1,78 @@
+//! The authorized voter of every vote account a generator can select
+//!
+//! Groups are made of vote accounts but vote transactions are signed by the authorized voter,
+//! so the generator keeps the values of the map it was built from. They come from the
+//! epoch's vote accounts and are never serialized, whoever reads a generator back puts them
+//! back with VoteGroupGenerator::set_authorized_voters. The map is also kept the other way
+//! round so the vote accounts a key signs for are found without going through every entry.
+
+use crate::pubkey::Pubkey;
+use std::{cmp::Ordering, collections::HashMap, sync::Arc};
+
+#[derive(Clone, Debug, Default)]
+pub struct AuthorizedVoters {
+    voters: Arc<HashMap<Pubkey, Pubkey>>,
+    // (authorized voter, vote account) pairs, sorted
+    vote_accounts: Arc<Vec<(Pubkey, Pubkey)>>,
+}
+
+impl AuthorizedVoters {
+    /// keeps the entries of `map` for the vote accounts in `possible_voters`
+    pub fn new(map: &HashMap<Pubkey, Pubkey>, possible_voters: &[Pubkey]) -> Self {
+        let voters: HashMap<Pubkey, Pubkey> = possible_voters
+            .iter()
+            .filter_map(|key| map.get(key).map(|voter| (*key, *voter)))
+            .collect();
+        let mut vote_accounts: Vec<(Pubkey, Pubkey)> =
+            voters.iter().map(|(key, voter)| (*voter, *key)).collect();
+        vote_accounts.sort_unstable();
+        Self {
+            voters: Arc::new(voters),
+            vote_accounts: Arc::new(vote_accounts),
+        }
+    }
+
+    pub fn get(&self, vote_account: &Pubkey) -> Option<Pubkey> {
+        self.voters.get(vote_account).copied()
+    }
+
+    /// the vote accounts `voter` signs for, in canonical order
+    pub fn vote_accounts<'a>(&'a self, voter: &'a Pubkey) -> impl Iterator<Item = Pubkey> + 'a {
+        // never equal, so the search ends on the first pair of `voter`
+        let first = self
+            .vote_accounts
+            .binary_search_by(|(key, _)| key.cmp(voter).then(Ordering::Greater))
+            .unwrap_err();
+        self.vote_accounts[first..]
+            .iter()
+            .take_while(move |(key, _)| key == voter)
+            .map(|(_, vote_account)| *vote_account)
+    }
+
+    pub fn remove(&mut self, vote_account: &Pubkey) {
+        let voter = match self.voters.get(vote_account) {
+            Some(voter) => *voter,
+            None => return,
+        };
+        Arc::make_mut(&mut self.voters).remove(vote_account);
+        if let Ok(idx) = self.vote_accounts.binary_search(&(voter, *vote_account)) {
+            Arc::make_mut(&mut self.vote_accounts).remove(idx);
+        }
+    }
+}
+
+// not part of the serialized state, two generators over the same voters are equal whatever
+// authorized voters they were given
+impl PartialEq for AuthorizedVoters {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+#[cfg(RUSTC_WITH_SPECIALIZATION)]
+impl ::solana_frozen_abi::abi_example::AbiExample for AuthorizedVoters {
+    fn example() -> Self {
+        // AuthorizedVoters is #[serde(skip)]-ed, so just rely on Default
+        Self::default()
+    }
+}
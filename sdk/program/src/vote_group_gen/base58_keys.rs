@@ -0,0 +1,94 @@
+//! Serde helper for the pubkey collections of the vote group state
+//!
+//! Human readable formats (JSON, YAML) get base58 strings, everything else the plain 32 byte
+//! arrays, so bincode layouts, and the snapshots built on them, stay exactly as they were.
+//! Use it with `#[serde(with = "base58_keys")]` on a `Vec` or `BTreeSet` of pubkeys, shared
+//! slices like `Arc<[Pubkey]>` pair `serialize_slice` with `deserialize`.
+
+use crate::pubkey::Pubkey;
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+use std::{iter::FromIterator, str::FromStr};
+
+pub fn serialize<S, T>(keys: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: Serialize,
+    for<'a> &'a T: IntoIterator<Item = &'a Pubkey>,
+{
+    if serializer.is_human_readable() {
+        serializer.collect_seq(keys.into_iter().map(|key| key.to_string()))
+    } else {
+        keys.serialize(serializer)
+    }
+}
+
+/// same as serialize for anything that derefs to a slice of pubkeys
+pub fn serialize_slice<S: Serializer>(keys: &[Pubkey], serializer: S) -> Result<S::Ok, S::Error> {
+    if serializer.is_human_readable() {
+        serializer.collect_seq(keys.iter().map(|key| key.to_string()))
+    } else {
+        serializer.collect_seq(keys)
+    }
+}
+
+pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromIterator<Pubkey>,
+{
+    if deserializer.is_human_readable() {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|key| Pubkey::from_str(key).map_err(D::Error::custom))
+            .collect()
+    } else {
+        Ok(Vec::<Pubkey>::deserialize(deserializer)?
+            .into_iter()
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{collections::BTreeSet, sync::Arc};
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct Keys {
+        #[serde(with = "super")]
+        list: Vec<Pubkey>,
+        #[serde(with = "super")]
+        set: BTreeSet<Pubkey>,
+        #[serde(serialize_with = "serialize_slice", deserialize_with = "deserialize")]
+        shared: Arc<[Pubkey]>,
+    }
+
+    #[test]
+    fn test_base58_keys() {
+        let key = Pubkey::new_unique();
+        let keys = Keys {
+            list: vec![key],
+            set: vec![key].into_iter().collect(),
+            shared: vec![key].into(),
+        };
+
+        let json = serde_json::to_string(&keys).unwrap();
+        assert_eq!(
+            json,
+            format!(
+                r#"{{"list":["{}"],"set":["{}"],"shared":["{}"]}}"#,
+                key, key, key
+            )
+        );
+        assert_eq!(serde_json::from_str::<Keys>(&json).unwrap(), keys);
+        assert!(serde_json::from_str::<Keys>(r#"{"list":["nope"],"set":[],"shared":[]}"#).is_err());
+
+        // the binary layout is the same as without the helper
+        let bytes = bincode::serialize(&keys).unwrap();
+        assert_eq!(
+            bytes,
+            bincode::serialize(&(&keys.list, &keys.set, keys.shared.to_vec())).unwrap()
+        );
+        assert_eq!(bincode::deserialize::<Keys>(&bytes).unwrap(), keys);
+    }
+}
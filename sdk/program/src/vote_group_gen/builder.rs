@@ -0,0 +1,295 @@
+//! Checked construction of a generator
+//!
+//! VoteGroupGenerator::new takes whatever it is given, an empty voter set or a group size of
+//! zero included, and only turns out to be useless once groups are picked. The builder
+//! collects the same inputs and refuses combinations that can't select a sensible group.
+//! Repeated voters and the default pubkey are filtered out first, a voter given twice would
+//! otherwise be as likely to be picked as any other and the default pubkey is never a vote
+//! account.
+
+use super::{ExclusionList, SelectionAlgorithm, VoteGroupError, VoteGroupGenerator};
+use crate::pubkey::Pubkey;
+use std::collections::{HashMap, HashSet};
+
+/// what the builder filtered out of the voters it was given
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SanitizeReport {
+    /// voters given more than once, each listed once in the order first repeated
+    pub duplicates: Vec<Pubkey>,
+    /// how many times the default pubkey was given
+    pub default_keys: usize,
+}
+
+impl SanitizeReport {
+    pub fn is_clean(&self) -> bool {
+        self.duplicates.is_empty() && self.default_keys == 0
+    }
+}
+
+/// `voters` without repeats or the default pubkey, in the order they were first given
+pub fn sanitize_voters<I: IntoIterator<Item = Pubkey>>(voters: I) -> (Vec<Pubkey>, SanitizeReport) {
+    let mut report = SanitizeReport::default();
+    let mut seen = HashSet::new();
+    let mut repeated = HashSet::new();
+    let mut sanitized = Vec::new();
+    for key in voters {
+        if key == Pubkey::default() {
+            report.default_keys += 1;
+        } else if !seen.insert(key) {
+            if repeated.insert(key) {
+                report.duplicates.push(key);
+            }
+        } else {
+            sanitized.push(key);
+        }
+    }
+    (sanitized, report)
+}
+
+#[derive(Clone, Debug)]
+pub struct VoteGroupGeneratorBuilder {
+    voters: Vec<Pubkey>,
+    group_size: usize,
+    exclusions: ExclusionList,
+    algorithm: SelectionAlgorithm,
+    weights: Option<HashMap<Pubkey, u64>>,
+}
+
+impl Default for VoteGroupGeneratorBuilder {
+    fn default() -> Self {
+        Self {
+            voters: Vec::new(),
+            group_size: super::OPTIMAL_VOTE_GROUP_SIZE,
+            exclusions: ExclusionList::legacy(),
+            algorithm: SelectionAlgorithm::default(),
+            weights: None,
+        }
+    }
+}
+
+impl VoteGroupGeneratorBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// the vote accounts groups are picked from, duplicates count once and the default
+    /// pubkey is dropped
+    pub fn with_voters<I: IntoIterator<Item = Pubkey>>(mut self, voters: I) -> Self {
+        self.voters = voters.into_iter().collect();
+        self
+    }
+
+    /// defaults to OPTIMAL_VOTE_GROUP_SIZE
+    pub fn with_group_size(mut self, group_size: usize) -> Self {
+        self.group_size = group_size;
+        self
+    }
+
+    /// voters that are never picked, defaults to ExclusionList::legacy()
+    pub fn with_exclusions(mut self, exclusions: ExclusionList) -> Self {
+        self.exclusions = exclusions;
+        self
+    }
+
+    pub fn with_algorithm(mut self, algorithm: SelectionAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// picks members in proportion to `weights`, voters without a weight are never picked
+    pub fn with_weights(mut self, weights: HashMap<Pubkey, u64>) -> Self {
+        self.weights = Some(weights);
+        self
+    }
+
+    /// the generator, as long as it has at least group_size voters it can pick
+    pub fn build(self) -> Result<VoteGroupGenerator, VoteGroupError> {
+        self.build_with_report().map(|(generator, _)| generator)
+    }
+
+    /// same as build, along with what was filtered out of the voters
+    pub fn build_with_report(self) -> Result<(VoteGroupGenerator, SanitizeReport), VoteGroupError> {
+        let (voters, report) = sanitize_voters(self.voters.iter().copied());
+        let map: HashMap<Pubkey, Pubkey> = voters.iter().map(|key| (*key, *key)).collect();
+        let generator = match &self.weights {
+            Some(weights) => VoteGroupGenerator::new_stake_weighted(
+                &map,
+                weights,
+                self.group_size,
+                &self.exclusions,
+            ),
+            None => {
+                VoteGroupGenerator::new_with_exclusions(&map, self.group_size, &self.exclusions)
+            }
+        }
+        .with_algorithm(self.algorithm);
+        generator.validate()?;
+        if let Some(weights) = &self.weights {
+            let staked = generator
+                .voters()
+                .iter()
+                .filter(|key| weights.get(key).copied().unwrap_or(0) > 0)
+                .count();
+            if staked == 0 {
+                return Err(VoteGroupError::NoStakedVoters);
+            }
+            if self.group_size > staked {
+                return Err(VoteGroupError::GroupSizeTooLarge {
+                    group_size: self.group_size,
+                    voters: staked,
+                });
+            }
+        }
+        Ok((generator, report))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vote_group_gen::SelectionMode;
+
+    #[test]
+    fn test_builder() {
+        let voters: Vec<Pubkey> = (0..5).map(|_| Pubkey::new_unique()).collect();
+        let map: HashMap<Pubkey, Pubkey> = voters.iter().map(|key| (*key, *key)).collect();
+        let vgg = VoteGroupGeneratorBuilder::new()
+            .with_voters(voters.clone())
+            .with_group_size(3)
+            .build()
+            .unwrap();
+        assert_eq!(vgg, VoteGroupGenerator::new(&map, 3));
+
+        let vgg = VoteGroupGeneratorBuilder::new()
+            .with_voters(voters.iter().chain(voters.iter()).copied())
+            .with_group_size(3)
+            .with_exclusions(ExclusionList::new(vec![voters[0]]))
+            .with_algorithm(SelectionAlgorithm::V2)
+            .build()
+            .unwrap();
+        assert_eq!(vgg.voters().len(), 4);
+        assert!(!vgg.voters().contains(&voters[0]));
+        assert_eq!(vgg.algorithm(), SelectionAlgorithm::V2);
+
+        let weights: HashMap<Pubkey, u64> = vec![(voters[1], 10), (voters[2], 0), (voters[3], 5)]
+            .into_iter()
+            .collect();
+        let vgg = VoteGroupGeneratorBuilder::new()
+            .with_voters(voters.clone())
+            .with_group_size(2)
+            .with_weights(weights)
+            .build()
+            .unwrap();
+        assert_eq!(vgg.selection_mode(), SelectionMode::StakeWeighted);
+    }
+
+    #[test]
+    fn test_builder_sanitizes_voters() {
+        let voters: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        let (vgg, report) = VoteGroupGeneratorBuilder::new()
+            .with_voters(voters.clone())
+            .with_group_size(2)
+            .build_with_report()
+            .unwrap();
+        assert!(report.is_clean());
+        assert_eq!(vgg.voters().len(), 4);
+
+        let given = vec![
+            voters[2],
+            Pubkey::default(),
+            voters[0],
+            voters[2],
+            voters[1],
+            voters[0],
+            voters[2],
+            Pubkey::default(),
+        ];
+        let (vgg, report) = VoteGroupGeneratorBuilder::new()
+            .with_voters(given.clone())
+            .with_group_size(2)
+            .build_with_report()
+            .unwrap();
+        assert_eq!(
+            report,
+            SanitizeReport {
+                duplicates: vec![voters[2], voters[0]],
+                default_keys: 2,
+            }
+        );
+        assert!(!report.is_clean());
+        assert_eq!(vgg.voters().len(), 3);
+        assert!(!vgg.voters().contains(&Pubkey::default()));
+        assert_eq!(
+            sanitize_voters(given).0,
+            vec![voters[2], voters[0], voters[1]]
+        );
+
+        // the default pubkey doesn't count towards the group size
+        assert_eq!(
+            VoteGroupGeneratorBuilder::new()
+                .with_voters(vec![voters[0], Pubkey::default()])
+                .with_group_size(2)
+                .build(),
+            Err(VoteGroupError::GroupSizeTooLarge {
+                group_size: 2,
+                voters: 1
+            })
+        );
+        assert_eq!(
+            VoteGroupGeneratorBuilder::new()
+                .with_voters(vec![Pubkey::default()])
+                .with_group_size(1)
+                .build(),
+            Err(VoteGroupError::EmptyVoterSet)
+        );
+    }
+
+    #[test]
+    fn test_builder_rejects_nonsense() {
+        let voters: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        let builder = VoteGroupGeneratorBuilder::new().with_voters(voters.clone());
+        assert_eq!(
+            VoteGroupGeneratorBuilder::new().build(),
+            Err(VoteGroupError::EmptyVoterSet)
+        );
+        assert_eq!(
+            builder.clone().with_group_size(0).build(),
+            Err(VoteGroupError::ZeroGroupSize)
+        );
+        assert_eq!(
+            builder.clone().with_group_size(4).build(),
+            Err(VoteGroupError::GroupSizeTooLarge {
+                group_size: 4,
+                voters: 3
+            })
+        );
+        assert_eq!(
+            builder
+                .clone()
+                .with_group_size(2)
+                .with_exclusions(ExclusionList::new(voters.clone()))
+                .build(),
+            Err(VoteGroupError::EmptyVoterSet)
+        );
+
+        let weights: HashMap<Pubkey, u64> = vec![(voters[0], 7)].into_iter().collect();
+        assert_eq!(
+            builder
+                .clone()
+                .with_group_size(2)
+                .with_weights(weights)
+                .build(),
+            Err(VoteGroupError::GroupSizeTooLarge {
+                group_size: 2,
+                voters: 1
+            })
+        );
+        assert_eq!(
+            builder
+                .with_group_size(1)
+                .with_weights(HashMap::new())
+                .build(),
+            Err(VoteGroupError::NoStakedVoters)
+        );
+    }
+}
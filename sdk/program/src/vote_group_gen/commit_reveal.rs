@@ -0,0 +1,297 @@
+//! Commit-reveal membership for private vote groups
+//!
+//! A voter commits, an epoch ahead, to a merkle root over one secret per rotation window of
+//! the epoch, the number of windows is folded into the root so it needn't be stored. It is
+//! in the group of a slot when the hash of its window's secret, its vote account and the
+//! slot's bank hash lands under the same threshold VRF outputs do, so only the voter knows
+//! it was picked until it votes and reveals the secret with its merkle path. The bank hash
+//! isn't known when the commitment is made, the secrets can't be ground.
+
+use super::MEMBERSHIP_COMMITMENT_DOMAIN;
+use crate::{
+    clock::Epoch,
+    hash::{hashv, Hash},
+    pubkey::Pubkey,
+};
+
+const SECRET_TAG: &[u8] = &[0];
+const LEAF_TAG: &[u8] = &[1];
+const NODE_TAG: &[u8] = &[2];
+const ROOT_TAG: &[u8] = &[3];
+const OUTPUT_TAG: &[u8] = &[4];
+
+/// what a vote account publishes ahead of `epoch`, kept in its vote state
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, AbiExample, PartialEq, Eq)]
+pub struct MembershipCommitment {
+    pub epoch: Epoch,
+    pub root: Hash,
+}
+
+/// the secret of one rotation window with the merkle siblings that tie it to the commitment
+#[derive(Clone, Debug, Default, Serialize, Deserialize, AbiExample, PartialEq, Eq)]
+pub struct MembershipReveal {
+    pub window: u32,
+    pub secret: Hash,
+    /// merkle siblings from the secret's leaf up, same layout as voter set proofs
+    pub path: Vec<Hash>,
+}
+
+impl MembershipReveal {
+    /// whether the secret is the one `commitment` holds for the reveal's window, out of the
+    /// `windows` rotation windows of the commitment's epoch
+    pub fn verify(&self, commitment: &MembershipCommitment, windows: u32) -> bool {
+        root_from_path(
+            &self.secret,
+            self.window as usize,
+            windows as usize,
+            &self.path,
+        )
+        .map_or(false, |tree_root| {
+            fold_root(commitment.epoch, windows, tree_root) == commitment.root
+        })
+    }
+
+    /// what `vote_account` draws for the slot with bank hash `hash`, compared against the
+    /// generator's vrf_threshold
+    pub fn output(&self, vote_account: &Pubkey, hash: &Hash) -> Hash {
+        membership_output(&self.secret, vote_account, hash)
+    }
+}
+
+fn membership_output(secret: &Hash, vote_account: &Pubkey, hash: &Hash) -> Hash {
+    hashv(&[
+        MEMBERSHIP_COMMITMENT_DOMAIN,
+        OUTPUT_TAG,
+        secret.as_ref(),
+        vote_account.as_ref(),
+        hash.as_ref(),
+    ])
+}
+
+/// a voter's secrets for an epoch, derived from one master secret so only that has to be
+/// kept. the tree is built once, `windows` leaves of it
+#[cfg(not(target_arch = "bpf"))]
+pub struct MembershipSecrets {
+    master: Hash,
+    vote_account: Pubkey,
+    epoch: Epoch,
+    levels: Vec<Vec<Hash>>,
+}
+
+#[cfg(not(target_arch = "bpf"))]
+impl MembershipSecrets {
+    pub fn new(master: &[u8], vote_account: &Pubkey, epoch: Epoch, windows: u32) -> Self {
+        let master = hashv(&[MEMBERSHIP_COMMITMENT_DOMAIN, SECRET_TAG, master]);
+        let leaves = (0..windows)
+            .map(|window| leaf_hash(&window_secret(&master, vote_account, epoch, window)))
+            .collect();
+        Self {
+            master,
+            vote_account: *vote_account,
+            epoch,
+            levels: tree_levels(leaves),
+        }
+    }
+
+    pub fn windows(&self) -> u32 {
+        self.levels[0].len() as u32
+    }
+
+    pub fn commitment(&self) -> MembershipCommitment {
+        let tree_root = self
+            .levels
+            .last()
+            .and_then(|level| level.first().copied())
+            .unwrap_or_default();
+        MembershipCommitment {
+            epoch: self.epoch,
+            root: fold_root(self.epoch, self.windows(), tree_root),
+        }
+    }
+
+    /// None past the last window
+    pub fn reveal(&self, window: u32) -> Option<MembershipReveal> {
+        if window >= self.windows() {
+            return None;
+        }
+        Some(MembershipReveal {
+            window,
+            secret: window_secret(&self.master, &self.vote_account, self.epoch, window),
+            path: merkle_path(&self.levels, window as usize),
+        })
+    }
+
+    /// lets the voter check privately what it would draw for `hash` in `window`
+    pub fn output(&self, window: u32, hash: &Hash) -> Option<Hash> {
+        if window >= self.windows() {
+            return None;
+        }
+        let secret = window_secret(&self.master, &self.vote_account, self.epoch, window);
+        Some(membership_output(&secret, &self.vote_account, hash))
+    }
+}
+
+#[cfg(not(target_arch = "bpf"))]
+fn window_secret(master: &Hash, vote_account: &Pubkey, epoch: Epoch, window: u32) -> Hash {
+    hashv(&[
+        MEMBERSHIP_COMMITMENT_DOMAIN,
+        SECRET_TAG,
+        master.as_ref(),
+        vote_account.as_ref(),
+        &epoch.to_le_bytes(),
+        &window.to_le_bytes(),
+    ])
+}
+
+#[cfg(not(target_arch = "bpf"))]
+fn tree_levels(leaves: Vec<Hash>) -> Vec<Vec<Hash>> {
+    let mut levels = vec![leaves];
+    while levels[levels.len() - 1].len() > 1 {
+        let next = levels[levels.len() - 1]
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => node_hash(left, right),
+                _ => pair[0],
+            })
+            .collect();
+        levels.push(next);
+    }
+    levels
+}
+
+#[cfg(not(target_arch = "bpf"))]
+fn merkle_path(levels: &[Vec<Hash>], index: usize) -> Vec<Hash> {
+    let mut path = Vec::new();
+    let mut position = index;
+    for level in levels {
+        let sibling = position ^ 1;
+        if sibling < level.len() {
+            path.push(level[sibling]);
+        }
+        position /= 2;
+    }
+    path
+}
+
+fn root_from_path(secret: &Hash, index: usize, windows: usize, path: &[Hash]) -> Option<Hash> {
+    if index >= windows {
+        return None;
+    }
+    let mut node = leaf_hash(secret);
+    let mut position = index;
+    let mut width = windows;
+    let mut path = path.iter();
+    while width > 1 {
+        let sibling = position ^ 1;
+        if sibling < width {
+            let sibling = path.next()?;
+            node = if position & 1 == 0 {
+                node_hash(&node, sibling)
+            } else {
+                node_hash(sibling, &node)
+            };
+        }
+        position /= 2;
+        width -= width / 2;
+    }
+    if path.next().is_some() {
+        return None;
+    }
+    Some(node)
+}
+
+fn leaf_hash(secret: &Hash) -> Hash {
+    hashv(&[MEMBERSHIP_COMMITMENT_DOMAIN, LEAF_TAG, secret.as_ref()])
+}
+
+fn node_hash(left: &Hash, right: &Hash) -> Hash {
+    hashv(&[
+        MEMBERSHIP_COMMITMENT_DOMAIN,
+        NODE_TAG,
+        left.as_ref(),
+        right.as_ref(),
+    ])
+}
+
+fn fold_root(epoch: Epoch, windows: u32, tree_root: Hash) -> Hash {
+    hashv(&[
+        MEMBERSHIP_COMMITMENT_DOMAIN,
+        ROOT_TAG,
+        &epoch.to_le_bytes(),
+        &windows.to_le_bytes(),
+        tree_root.as_ref(),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_membership_reveal() {
+        let vote_account = Pubkey::new_unique();
+        for windows in 1..12 {
+            let secrets = MembershipSecrets::new(b"master", &vote_account, 3, windows);
+            let commitment = secrets.commitment();
+            assert_eq!(commitment.epoch, 3);
+            assert_eq!(secrets.windows(), windows);
+            for window in 0..windows {
+                let reveal = secrets.reveal(window).unwrap();
+                assert!(reveal.verify(&commitment, windows));
+                assert!(!reveal.verify(&commitment, windows + 1));
+                let bytes = bincode::serialize(&reveal).unwrap();
+                assert_eq!(
+                    bincode::deserialize::<MembershipReveal>(&bytes).unwrap(),
+                    reveal
+                );
+                let hash = Hash::new_unique();
+                assert_eq!(
+                    Some(reveal.output(&vote_account, &hash)),
+                    secrets.output(window, &hash)
+                );
+            }
+            assert_eq!(secrets.reveal(windows), None);
+            assert_eq!(secrets.output(windows, &Hash::default()), None);
+        }
+    }
+
+    #[test]
+    fn test_membership_reveal_mismatch() {
+        let vote_account = Pubkey::new_unique();
+        let secrets = MembershipSecrets::new(b"master", &vote_account, 3, 9);
+        let commitment = secrets.commitment();
+        let reveal = secrets.reveal(4).unwrap();
+
+        // a secret only opens the window it was committed for
+        let mut moved = reveal.clone();
+        moved.window = 5;
+        assert!(!moved.verify(&commitment, 9));
+        let mut forged = reveal.clone();
+        forged.secret = Hash::new_unique();
+        assert!(!forged.verify(&commitment, 9));
+        let mut truncated = reveal.clone();
+        truncated.path.pop();
+        assert!(!truncated.verify(&commitment, 9));
+
+        // nor any other epoch, window count or master secret
+        for other in &[
+            MembershipSecrets::new(b"master", &vote_account, 4, 9).commitment(),
+            MembershipSecrets::new(b"master", &vote_account, 3, 10).commitment(),
+            MembershipSecrets::new(b"other", &vote_account, 3, 9).commitment(),
+        ] {
+            assert_ne!(other.root, commitment.root);
+            assert!(!reveal.verify(other, 9));
+        }
+
+        // the draw is the vote account's own
+        let hash = Hash::new_unique();
+        assert_ne!(
+            reveal.output(&vote_account, &hash),
+            reveal.output(&Pubkey::new_unique(), &hash)
+        );
+        assert_ne!(
+            reveal.output(&vote_account, &hash),
+            reveal.output(&vote_account, &Hash::new_unique())
+        );
+    }
+}
@@ -0,0 +1,168 @@
+//! Compact wire form of a group, for gossip and RPC
+//!
+//! Instead of a list of 32 byte pubkeys a group goes out as the seed it was picked for, the
+//! algorithm that picked it and one bit per voter in the generator's canonical order. The
+//! bitmap is `voters / 8` bytes whatever the group size, so it beats the plain list as long
+//! as there are fewer than 256 voters per member. The receiver needs the same voter set to
+//! decode it, which every node already has for the epoch.
+
+use super::{SelectionAlgorithm, VoteGroupError, VoteGroupGenerator};
+use crate::pubkey::Pubkey;
+
+#[derive(Clone, Debug, Serialize, Deserialize, AbiExample, PartialEq, Eq)]
+pub struct CompactGroup {
+    pub seed: u64,
+    pub algorithm: SelectionAlgorithm,
+    /// how many voters the bitmap covers, checked against the decoder's voter set
+    pub voter_count: u32,
+    /// bit `i % 8` of byte `i / 8` is set when canonical voter `i` is a member
+    pub bitmap: Vec<u8>,
+}
+
+impl CompactGroup {
+    /// encodes the members of the group `generator` picks for `seed`
+    pub fn new(generator: &VoteGroupGenerator, seed: u64) -> Self {
+        let voter_count = generator.voters().len();
+        let mut bitmap = vec![0u8; (voter_count + 7) / 8];
+        for idx in generator.group_indexes(seed) {
+            bitmap[idx / 8] |= 1 << (idx % 8);
+        }
+        Self {
+            seed,
+            algorithm: generator.algorithm(),
+            voter_count: voter_count as u32,
+            bitmap,
+        }
+    }
+
+    /// the members, in canonical order rather than selection order
+    pub fn decode(&self, voters: &[Pubkey]) -> Result<Vec<Pubkey>, VoteGroupError> {
+        self.check_bitmap(voters.len())?;
+        Ok(voters
+            .iter()
+            .enumerate()
+            .filter(|(idx, _)| self.contains_index(*idx))
+            .map(|(_, key)| *key)
+            .collect())
+    }
+
+    /// decodes against the voters of `generator` and checks that it really picks this group
+    /// for the seed, so a peer can't slip in a group of its own choosing
+    pub fn verify(&self, generator: &VoteGroupGenerator) -> Result<bool, VoteGroupError> {
+        self.check_bitmap(generator.voters().len())?;
+        Ok(*self == Self::new(generator, self.seed))
+    }
+
+    pub fn len(&self) -> usize {
+        self.bitmap
+            .iter()
+            .map(|byte| byte.count_ones() as usize)
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bitmap.iter().all(|byte| *byte == 0)
+    }
+
+    fn contains_index(&self, idx: usize) -> bool {
+        self.bitmap[idx / 8] & (1 << (idx % 8)) != 0
+    }
+
+    fn check_bitmap(&self, voters: usize) -> Result<(), VoteGroupError> {
+        let encoded = self.voter_count as usize;
+        if encoded != voters {
+            return Err(VoteGroupError::VoterCountMismatch { encoded, voters });
+        }
+        if self.bitmap.len() != (encoded + 7) / 8 {
+            return Err(VoteGroupError::MalformedBitmap);
+        }
+        let spare = self.bitmap.len() * 8 - encoded;
+        if spare > 0 && self.bitmap[self.bitmap.len() - 1] >> (8 - spare) != 0 {
+            return Err(VoteGroupError::MalformedBitmap);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn generator(voters: usize, size: usize, algorithm: SelectionAlgorithm) -> VoteGroupGenerator {
+        let map: HashMap<Pubkey, Pubkey> = (0..voters)
+            .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+            .collect();
+        VoteGroupGenerator::new(&map, size).with_algorithm(algorithm)
+    }
+
+    #[test]
+    fn test_compact_group_round_trip() {
+        for algorithm in &[SelectionAlgorithm::V1, SelectionAlgorithm::V2] {
+            for voters in &[1, 7, 8, 9, 100] {
+                let vgg = generator(*voters, 5.min(*voters), *algorithm);
+                for seed in 0..50 {
+                    let compact = CompactGroup::new(&vgg, seed);
+                    let mut expected = vgg.group_for_seed(seed);
+                    expected.sort();
+                    expected.dedup();
+                    assert_eq!(compact.decode(vgg.voters()).unwrap(), expected);
+                    assert_eq!(compact.len(), expected.len());
+                    assert!(compact.verify(&vgg).unwrap());
+
+                    let bytes = bincode::serialize(&compact).unwrap();
+                    assert_eq!(
+                        bincode::deserialize::<CompactGroup>(&bytes).unwrap(),
+                        compact
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_compact_group_is_smaller() {
+        let vgg = generator(1_000, 11, SelectionAlgorithm::V2);
+        let compact = bincode::serialize(&CompactGroup::new(&vgg, 3)).unwrap();
+        let full = bincode::serialize(&vgg.group_for_seed(3)).unwrap();
+        assert!(compact.len() < full.len());
+    }
+
+    #[test]
+    fn test_compact_group_rejects_bad_input() {
+        let vgg = generator(10, 3, SelectionAlgorithm::V2);
+        let compact = CompactGroup::new(&vgg, 1);
+        assert_eq!(
+            compact.decode(&vgg.voters()[..9]),
+            Err(VoteGroupError::VoterCountMismatch {
+                encoded: 10,
+                voters: 9
+            })
+        );
+
+        let mut short = compact.clone();
+        short.bitmap.pop();
+        assert_eq!(
+            short.decode(vgg.voters()),
+            Err(VoteGroupError::MalformedBitmap)
+        );
+
+        // voters 10 through 15 don't exist
+        let mut stray = compact.clone();
+        stray.bitmap[1] |= 0b1000_0000;
+        assert_eq!(
+            stray.decode(vgg.voters()),
+            Err(VoteGroupError::MalformedBitmap)
+        );
+
+        // a well formed group that isn't the one the seed picks
+        let mut forged = compact.clone();
+        forged.bitmap = vec![0b0000_0111, 0];
+        if forged != compact {
+            assert!(!forged.verify(&vgg).unwrap());
+        }
+        let mut other_algorithm = compact;
+        other_algorithm.algorithm = SelectionAlgorithm::V1;
+        assert!(!other_algorithm.verify(&vgg).unwrap());
+    }
+}
@@ -0,0 +1,212 @@
+//! The set of vote accounts that must never be picked for a voter group
+//!
+//! The list can be built in code, read back out of an account's data (bincode)
+//! or loaded from a text file holding one base58 pubkey per line.
+
+use super::SAFECOIN_NEVER_VOTER;
+use crate::pubkey::Pubkey;
+use std::{
+    cmp::Reverse,
+    collections::{BTreeSet, HashMap},
+    str::FromStr,
+};
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, AbiExample, PartialEq)]
+pub struct ExclusionList {
+    #[serde(with = "super::base58_keys")]
+    excluded: BTreeSet<Pubkey>,
+}
+
+impl ExclusionList {
+    pub fn new<I: IntoIterator<Item = Pubkey>>(keys: I) -> Self {
+        Self {
+            excluded: keys.into_iter().collect(),
+        }
+    }
+
+    /// the list the generator has always honored, just the SAFECOIN_NEVER_VOTER
+    pub fn legacy() -> Self {
+        let mut list = Self::default();
+        list.add(Pubkey::from_str(SAFECOIN_NEVER_VOTER).unwrap());
+        list
+    }
+
+    /// migrates an operator supplied list so it still honors the legacy constant
+    pub fn with_legacy(mut self) -> Self {
+        self.add(Pubkey::from_str(SAFECOIN_NEVER_VOTER).unwrap());
+        self
+    }
+
+    /// returns true if the key was not already excluded
+    pub fn add(&mut self, key: Pubkey) -> bool {
+        self.excluded.insert(key)
+    }
+
+    /// returns true if the key was excluded
+    pub fn remove(&mut self, key: &Pubkey) -> bool {
+        self.excluded.remove(key)
+    }
+
+    pub fn contains(&self, key: &Pubkey) -> bool {
+        self.excluded.contains(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.excluded.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.excluded.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Pubkey> {
+        self.excluded.iter()
+    }
+
+    /// excludes all but one vote account of every node identity so running many vote accounts
+    /// doesn't buy an operator more seats in the groups. `identities` maps vote accounts to the
+    /// identity running them, accounts already excluded don't compete and the one kept is the
+    /// most staked, the lowest pubkey on a tie. returns how many were added
+    pub fn add_identity_duplicates<F>(
+        &mut self,
+        identities: &HashMap<Pubkey, Pubkey>,
+        stake_of: F,
+    ) -> usize
+    where
+        F: Fn(&Pubkey) -> u64,
+    {
+        let mut by_identity: HashMap<Pubkey, Vec<Pubkey>> = HashMap::new();
+        for (vote_account, identity) in identities {
+            if !self.contains(vote_account) {
+                by_identity
+                    .entry(*identity)
+                    .or_default()
+                    .push(*vote_account);
+            }
+        }
+        let mut added = 0;
+        for vote_accounts in by_identity.values() {
+            let kept = vote_accounts
+                .iter()
+                .max_by_key(|key| (stake_of(key), Reverse(**key)))
+                .copied();
+            for vote_account in vote_accounts {
+                if Some(*vote_account) != kept && self.add(*vote_account) {
+                    added += 1;
+                }
+            }
+        }
+        added
+    }
+
+    /// reads a list stored in an on-chain account
+    pub fn from_account_data(data: &[u8]) -> Result<Self, bincode::Error> {
+        bincode::deserialize(data)
+    }
+
+    pub fn to_account_data(&self) -> Result<Vec<u8>, bincode::Error> {
+        bincode::serialize(self)
+    }
+
+    /// parses one base58 pubkey per line, blank lines and lines starting with '#' are skipped
+    pub fn from_text(text: &str) -> Result<Self, String> {
+        let mut list = Self::default();
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let key = Pubkey::from_str(line).map_err(|err| format!("{}: {:?}", line, err))?;
+            list.add(key);
+        }
+        Ok(list)
+    }
+
+    #[cfg(not(target_arch = "bpf"))]
+    pub fn load(path: &std::path::Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|err| format!("unable to read {}: {}", path.display(), err))?;
+        Self::from_text(&text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_remove() {
+        let key = Pubkey::new_unique();
+        let mut list = ExclusionList::default();
+        assert!(list.add(key));
+        assert!(!list.add(key));
+        assert!(list.contains(&key));
+        assert!(list.remove(&key));
+        assert!(!list.contains(&key));
+        assert!(list.is_empty());
+    }
+
+    #[test]
+    fn test_legacy() {
+        let never = Pubkey::from_str(SAFECOIN_NEVER_VOTER).unwrap();
+        assert!(ExclusionList::legacy().contains(&never));
+
+        let key = Pubkey::new_unique();
+        let list = ExclusionList::new(vec![key]).with_legacy();
+        assert!(list.contains(&never));
+        assert!(list.contains(&key));
+        assert_eq!(list.len(), 2);
+    }
+
+    #[test]
+    fn test_add_identity_duplicates() {
+        let (operator, solo) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut accounts: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        accounts.sort();
+        let solo_account = Pubkey::new_unique();
+        let mut identities: HashMap<Pubkey, Pubkey> =
+            accounts.iter().map(|key| (*key, operator)).collect();
+        identities.insert(solo_account, solo);
+        let stakes: HashMap<Pubkey, u64> = vec![
+            (accounts[0], 10),
+            (accounts[1], 30),
+            (accounts[2], 30),
+            (accounts[3], 20),
+            (solo_account, 1),
+        ]
+        .into_iter()
+        .collect();
+        let stake_of = |key: &Pubkey| stakes.get(key).copied().unwrap_or(0);
+
+        // the lower pubkey wins the tie at 30
+        let mut list = ExclusionList::default();
+        assert_eq!(list.add_identity_duplicates(&identities, stake_of), 3);
+        assert_eq!(
+            list,
+            ExclusionList::new(vec![accounts[0], accounts[2], accounts[3]])
+        );
+
+        // an excluded account doesn't compete, the best of the rest is kept
+        let mut list = ExclusionList::new(vec![accounts[1], accounts[2]]);
+        assert_eq!(list.add_identity_duplicates(&identities, stake_of), 1);
+        assert!(list.contains(&accounts[0]));
+        assert!(!list.contains(&accounts[3]));
+        assert!(!list.contains(&solo_account));
+    }
+
+    #[test]
+    fn test_account_data_round_trip() {
+        let list = ExclusionList::new(vec![Pubkey::new_unique(), Pubkey::new_unique()]);
+        let data = list.to_account_data().unwrap();
+        assert_eq!(ExclusionList::from_account_data(&data).unwrap(), list);
+    }
+
+    #[test]
+    fn test_from_text() {
+        let a = Pubkey::new_unique();
+        let b = Pubkey::new_unique();
+        let text = format!("# excluded voters\n{}\n\n  {}  \n", a, b);
+        let list = ExclusionList::from_text(&text).unwrap();
+        assert_eq!(list, ExclusionList::new(vec![a, b]));
+        assert!(ExclusionList::from_text("not a pubkey").is_err());
+    }
+}
@@ -0,0 +1,175 @@
+//! Recently materialized groups, keyed by seed
+//!
+//! Thousands of votes are checked against the same slot's group, so the group is walked once
+//! and later checks are a map lookup. Every member maps to its first position in selection
+//! order, so looking up where a voter sits never walks the group again. The cache is never
+//! serialized and starts empty on clone.
+
+use crate::pubkey::Pubkey;
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+pub const MAX_CACHED_GROUPS: usize = 64;
+
+/// member to its first position in selection order
+pub type GroupPositions = HashMap<Pubkey, usize>;
+
+/// lookups since the stats were last taken
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct GroupCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl GroupCacheStats {
+    pub fn hit_ratio(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct CachedGroups {
+    groups: HashMap<u64, Arc<GroupPositions>>,
+    // least recently used seed at the front
+    order: VecDeque<u64>,
+    stats: GroupCacheStats,
+}
+
+#[derive(Debug, Default)]
+pub struct GroupCache {
+    cached: Mutex<CachedGroups>,
+}
+
+impl GroupCache {
+    pub fn get_or_insert_with<F>(&self, seed: u64, materialize: F) -> Arc<GroupPositions>
+    where
+        F: FnOnce() -> GroupPositions,
+    {
+        let mut cached = self.cached.lock().unwrap();
+        if let Some(group) = cached.groups.get(&seed).cloned() {
+            cached.stats.hits += 1;
+            if let Some(pos) = cached.order.iter().position(|s| *s == seed) {
+                cached.order.remove(pos);
+            }
+            cached.order.push_back(seed);
+            return group;
+        }
+        cached.stats.misses += 1;
+        let group = Arc::new(materialize());
+        cached.groups.insert(seed, group.clone());
+        cached.order.push_back(seed);
+        while cached.groups.len() > MAX_CACHED_GROUPS {
+            let evict = cached.order.pop_front().unwrap();
+            cached.groups.remove(&evict);
+        }
+        group
+    }
+
+    #[cfg(test)]
+    pub fn len(&self) -> usize {
+        self.cached.lock().unwrap().groups.len()
+    }
+
+    #[cfg(test)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// the stats since the last call, resets them
+    pub fn take_stats(&self) -> GroupCacheStats {
+        std::mem::take(&mut self.cached.lock().unwrap().stats)
+    }
+
+    /// the stats since they were last taken, without resetting them
+    pub fn stats(&self) -> GroupCacheStats {
+        self.cached.lock().unwrap().stats
+    }
+
+    /// the cached groups by seed, least recently used first
+    pub fn groups(&self) -> Vec<(u64, Arc<GroupPositions>)> {
+        let cached = self.cached.lock().unwrap();
+        cached
+            .order
+            .iter()
+            .map(|seed| (*seed, cached.groups[seed].clone()))
+            .collect()
+    }
+
+    /// must be called whenever the voter set changes
+    pub fn clear(&self) {
+        let mut cached = self.cached.lock().unwrap();
+        cached.groups.clear();
+        cached.order.clear();
+    }
+}
+
+impl Clone for GroupCache {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+// the cache is derived state, two generators are equal regardless of what they have cached
+impl PartialEq for GroupCache {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+#[cfg(RUSTC_WITH_SPECIALIZATION)]
+impl ::solana_frozen_abi::abi_example::AbiExample for GroupCache {
+    fn example() -> Self {
+        // GroupCache is #[serde(skip)]-ed, so just rely on Default
+        Self::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_group_cache_hit() {
+        let cache = GroupCache::default();
+        let key = Pubkey::new_unique();
+        let group = cache.get_or_insert_with(1, || vec![(key, 0)].into_iter().collect());
+        assert_eq!(group.get(&key), Some(&0));
+        let group = cache.get_or_insert_with(1, || panic!("should have been cached"));
+        assert_eq!(group.get(&key), Some(&0));
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.groups(), vec![(1, group)]);
+        assert_eq!(cache.stats(), GroupCacheStats { hits: 1, misses: 1 });
+        let stats = cache.take_stats();
+        assert_eq!(stats, GroupCacheStats { hits: 1, misses: 1 });
+        assert_eq!(stats.hit_ratio(), 0.5);
+        assert_eq!(cache.take_stats(), GroupCacheStats::default());
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_group_cache_evicts_least_recently_used() {
+        let cache = GroupCache::default();
+        for seed in 0..MAX_CACHED_GROUPS as u64 {
+            cache.get_or_insert_with(seed, GroupPositions::new);
+        }
+        // touch seed 0 so seed 1 becomes the oldest
+        cache.get_or_insert_with(0, || panic!("should have been cached"));
+        cache.get_or_insert_with(MAX_CACHED_GROUPS as u64, GroupPositions::new);
+        assert_eq!(cache.len(), MAX_CACHED_GROUPS);
+        cache.get_or_insert_with(0, || panic!("should have been cached"));
+        let mut missed = false;
+        cache.get_or_insert_with(1, || {
+            missed = true;
+            GroupPositions::new()
+        });
+        assert!(missed);
+    }
+}
@@ -0,0 +1,235 @@
+//! Proof that a voter belongs to a group, for light clients
+//!
+//! A light client doesn't hold the voter set, only a VoterSetCommitment: the voter set root
+//! (see voter_set) plus the handful of settings that shape the groups. A proof names the seed, the voter's canonical index and the merkle siblings that
+//! tie the voter to that index. The walk only depends on the size of the voter set, so the
+//! client re-walks the group for the seed on indexes alone and checks the index is in it.
+//! Stake weighted groups are drawn out of the stakes and can't be proven this way.
+
+use super::{
+    distance_for, ring_indexes_for,
+    voter_set::{merkle_path, root_from_path, tree_levels},
+    SelectionAlgorithm, SelectionMode, VoteGroupGenerator,
+};
+use crate::{hash::Hash, pubkey::Pubkey};
+
+/// everything a light client needs to hold to check membership proofs for an epoch
+#[derive(Clone, Debug, Serialize, Deserialize, AbiExample, PartialEq, Eq)]
+pub struct VoterSetCommitment {
+    /// the generator's voter_set_root
+    pub root: Hash,
+    pub voter_count: u32,
+    pub group_size: u32,
+    pub algorithm: SelectionAlgorithm,
+    pub distinct_members: bool,
+}
+
+impl VoterSetCommitment {
+    /// None when `generator` has no voters or draws its groups by stake
+    pub fn new(generator: &VoteGroupGenerator) -> Option<Self> {
+        if !is_provable(generator) {
+            return None;
+        }
+        Some(Self {
+            root: generator.voter_set_root(),
+            voter_count: generator.voters().len() as u32,
+            group_size: generator.group_size() as u32,
+            algorithm: generator.algorithm(),
+            distinct_members: generator.distinct_members(),
+        })
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, AbiExample, PartialEq, Eq)]
+pub struct GroupMembershipProof {
+    pub seed: u64,
+    pub algorithm: SelectionAlgorithm,
+    /// the voter's position in canonical order
+    pub index: u32,
+    /// the ring distance the walk for the seed starts with, None for v3
+    pub distance: Option<u32>,
+    /// merkle siblings from the voter's leaf up, a level where the voter's node is the odd
+    /// one out has no sibling and nothing in here
+    pub path: Vec<Hash>,
+}
+
+impl GroupMembershipProof {
+    /// None unless `voter` is in the group `generator` picks for `seed`
+    pub fn new(generator: &VoteGroupGenerator, seed: u64, voter: &Pubkey) -> Option<Self> {
+        if !is_provable(generator) {
+            return None;
+        }
+        let index = generator.index_of(voter)?;
+        if !generator.group_indexes(seed).contains(&index) {
+            return None;
+        }
+        Some(Self {
+            seed,
+            algorithm: generator.algorithm(),
+            index: index as u32,
+            distance: generator.distance_for_seed(seed),
+            path: merkle_path(&tree_levels(generator.voters()), index),
+        })
+    }
+
+    /// whether `voter` is in the group for the proof's seed of the voter set `commitment`
+    /// was made for
+    pub fn verify(&self, voter: &Pubkey, commitment: &VoterSetCommitment) -> bool {
+        let voters_len = commitment.voter_count as usize;
+        let index = self.index as usize;
+        if self.algorithm != commitment.algorithm || index >= voters_len {
+            return false;
+        }
+
+        if root_from_path(voter, index, voters_len, &self.path) != Some(commitment.root) {
+            return false;
+        }
+
+        let distances = VoteGroupGenerator::distances_for(voters_len, self.algorithm);
+        if distance_for(self.seed, self.algorithm, &distances) != self.distance {
+            return false;
+        }
+        ring_indexes_for(
+            self.seed,
+            voters_len,
+            commitment.group_size as usize,
+            self.algorithm,
+            commitment.distinct_members,
+            &distances,
+        )
+        .contains(&index)
+    }
+}
+
+fn is_provable(generator: &VoteGroupGenerator) -> bool {
+    !generator.voters().is_empty()
+        && (!matches!(
+            generator.selection_mode(),
+            SelectionMode::StakeWeighted | SelectionMode::Binomial
+        ) || generator.alias_table.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vote_group_gen::ExclusionList;
+    use std::collections::HashMap;
+
+    fn generator(voters: usize, size: usize, algorithm: SelectionAlgorithm) -> VoteGroupGenerator {
+        let map: HashMap<Pubkey, Pubkey> = (0..voters)
+            .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+            .collect();
+        VoteGroupGenerator::new(&map, size).with_algorithm(algorithm)
+    }
+
+    #[test]
+    fn test_membership_proof_verifies() {
+        for algorithm in &[
+            SelectionAlgorithm::V1,
+            SelectionAlgorithm::V2,
+            SelectionAlgorithm::V3,
+        ] {
+            for distinct_members in &[false, true] {
+                for voters in &[1, 2, 7, 8, 9, 100] {
+                    let vgg =
+                        generator(*voters, 5, *algorithm).with_distinct_members(*distinct_members);
+                    let commitment = VoterSetCommitment::new(&vgg).unwrap();
+                    for seed in 0..20 {
+                        let group = vgg.group_for_seed(seed);
+                        for voter in vgg.voters() {
+                            match GroupMembershipProof::new(&vgg, seed, voter) {
+                                Some(proof) => {
+                                    assert!(group.contains(voter));
+                                    assert!(proof.verify(voter, &commitment));
+                                    let bytes = bincode::serialize(&proof).unwrap();
+                                    assert_eq!(
+                                        bincode::deserialize::<GroupMembershipProof>(&bytes)
+                                            .unwrap(),
+                                        proof
+                                    );
+                                }
+                                None => assert!(!group.contains(voter)),
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_membership_proof_is_small() {
+        let vgg = generator(2_000, 11, SelectionAlgorithm::V2);
+        let voter = vgg.group_for_seed(3)[0];
+        let proof = GroupMembershipProof::new(&vgg, 3, &voter).unwrap();
+        assert_eq!(proof.path.len(), 11);
+        assert!(bincode::serialize(&proof).unwrap().len() < 400);
+    }
+
+    #[test]
+    fn test_membership_proof_rejects_forgeries() {
+        let vgg = generator(100, 5, SelectionAlgorithm::V2);
+        let commitment = VoterSetCommitment::new(&vgg).unwrap();
+        let group = vgg.group_for_seed(7);
+        let member = group[0];
+        let outsider = *vgg
+            .voters()
+            .iter()
+            .find(|voter| !group.contains(voter))
+            .unwrap();
+        assert_eq!(GroupMembershipProof::new(&vgg, 7, &outsider), None);
+        let proof = GroupMembershipProof::new(&vgg, 7, &member).unwrap();
+        assert!(proof.verify(&member, &commitment));
+
+        // the path ties the index to the member and nobody else
+        assert!(!proof.verify(&outsider, &commitment));
+
+        // an index the seed doesn't pick
+        let outsider_index = vgg.voters().iter().position(|v| *v == outsider).unwrap();
+        let mut moved = proof.clone();
+        moved.index = outsider_index as u32;
+        assert!(!moved.verify(&member, &commitment));
+
+        // a seed that doesn't pick the member
+        let mut reseeded = proof.clone();
+        reseeded.seed = (0..)
+            .find(|seed| !vgg.in_group_for_seed(*seed, member))
+            .unwrap();
+        assert!(!reseeded.verify(&member, &commitment));
+
+        let mut short = proof.clone();
+        short.path.pop();
+        assert!(!short.verify(&member, &commitment));
+        let mut long = proof.clone();
+        long.path.push(Hash::default());
+        assert!(!long.verify(&member, &commitment));
+
+        let mut other_algorithm = proof.clone();
+        other_algorithm.algorithm = SelectionAlgorithm::V1;
+        assert!(!other_algorithm.verify(&member, &commitment));
+        let mut other_distance = proof.clone();
+        other_distance.distance = other_distance.distance.map(|distance| distance + 1);
+        assert!(!other_distance.verify(&member, &commitment));
+
+        // a commitment to another voter set
+        let other = VoterSetCommitment::new(&generator(100, 5, SelectionAlgorithm::V2)).unwrap();
+        assert!(!proof.verify(&member, &other));
+    }
+
+    #[test]
+    fn test_membership_proof_stake_weighted() {
+        let map: HashMap<Pubkey, Pubkey> = (0..10)
+            .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+            .collect();
+        let stakes: HashMap<Pubkey, u64> = map.keys().zip(1..).map(|(k, s)| (*k, s)).collect();
+        let vgg =
+            VoteGroupGenerator::new_stake_weighted(&map, &stakes, 3, &ExclusionList::default());
+        assert_eq!(VoterSetCommitment::new(&vgg), None);
+        let member = vgg.group_for_seed(0)[0];
+        assert_eq!(GroupMembershipProof::new(&vgg, 0, &member), None);
+        assert_eq!(
+            VoterSetCommitment::new(&VoteGroupGenerator::new_dummy()),
+            None
+        );
+    }
+}
@@ -0,0 +1,3662 @@
+//! This thing generates random voter groups of a given size
+//! Given the set of all authorized voters (their pubkeys) it selects one randomly
+//! then it picks a shift distance (some prime number less than the voter set size)
+//! and iteratively selects the rest of the group by shifting that distance
+//! its treating the set of voters as a ring
+
+use crate::{pubkey::Pubkey};
+use crate::clock::{Epoch, Slot};
+use crate::epoch_schedule::EpochSchedule;
+use crate::hash::{hashv, Hash};
+use log::*;
+#[cfg(not(target_arch = "bpf"))]
+use crate::vrf::VrfProof;
+#[cfg(not(target_arch = "bpf"))]
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+use thiserror::Error;
+
+use std::convert::{TryFrom, TryInto};
+
+mod alias_table;
+mod authorized_voters;
+mod base58_keys;
+pub mod builder;
+pub mod commit_reveal;
+pub mod compact;
+pub mod exclusion_list;
+mod group_cache;
+pub mod membership_proof;
+mod onchain;
+pub mod schedule;
+pub mod seed_source;
+pub mod selection_core;
+pub mod stats;
+pub mod threshold;
+pub mod voter_set;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+use alias_table::AliasTable;
+use authorized_voters::AuthorizedVoters;
+use group_cache::{GroupCache, GroupPositions};
+pub use group_cache::GroupCacheStats;
+pub use builder::{SanitizeReport, VoteGroupGeneratorBuilder};
+pub use commit_reveal::{MembershipCommitment, MembershipReveal};
+pub use compact::CompactGroup;
+pub use exclusion_list::ExclusionList;
+pub use membership_proof::{GroupMembershipProof, VoterSetCommitment};
+pub use schedule::VoteGroupSchedule;
+pub use seed_source::{
+    BlockhashSeed, EpochAnchoredSeed, FixedSeed, SeedDerivation, SeedSource, WindowedSeed,
+};
+pub use voter_set::{EligibilityProof, VoterInclusion};
+use selection_core::{ring_shift, splitmix64, uniform_below, Taken, MAX_SCANNED_WALK};
+use voter_set::VoterSetRoot;
+
+pub static OPTIMAL_VOTE_GROUP_SIZE: usize = 11;
+/// out of group votes in an epoch that get a vote account left out of later groups
+pub const DEFAULT_OUT_OF_GROUP_VOTE_THRESHOLD: u64 = 1024;
+/// slots without a landed vote before a vote account is left out of later groups, about an
+/// hour of 400ms slots
+pub const DEFAULT_DELINQUENT_SLOT_DISTANCE: Slot = 9_000;
+/// how recent the last vote of a delinquent vote account has to be for it to be picked again
+pub const DEFAULT_REENTRY_SLOT_DISTANCE: Slot = 1_000;
+/// share of its assigned slots a vote account can miss before the next groups leave it out,
+/// skipped slots can't be voted on so it leaves plenty of room for them
+pub const DEFAULT_MAX_ABSENT_PERCENT: u8 = 50;
+/// fewer assigned slots than this say too little about a vote account to leave it out
+pub const DEFAULT_MIN_ASSIGNED_SLOTS: u64 = 32;
+/// slots a group lasts before the next one is selected, every slot gets its own by default
+pub const DEFAULT_ROTATION_WINDOW_SLOTS: u64 = 1;
+
+pub static SAFECOIN_NEVER_VOTER: &str = "83E5RMejo6d98FV1EAXTx5t4bvoDMoxE4DboDee3VJsu";
+
+/// version of the ordering applied to `possible_voters`
+/// 0 - whatever order the HashMap iterated in (not consensus safe)
+/// 1 - sorted by pubkey bytes
+pub const CANONICAL_ORDERING_VERSION: u8 = 1;
+
+/// domain separation for group seeds so they can't collide with any other use of the same hash
+pub const SEED_DOMAIN: &[u8] = b"safecoin-vote-group-seed";
+
+/// domain separation for the seed backup groups are drawn with, keeps them independent of
+/// the primary group drawn from the same seed
+pub const BACKUP_SEED_DOMAIN: &[u8] = b"safecoin-vote-group-backup-seed";
+
+/// domain separation for the seed the members shared by two consecutive groups are drawn with
+pub const OVERLAP_SEED_DOMAIN: &[u8] = b"safecoin-vote-group-overlap-seed";
+/// domain separation for the seed stake weighted standby lists are drawn with
+pub const STANDBY_SEED_DOMAIN: &[u8] = b"safecoin-vote-group-standby-seed";
+/// domain separation for the seed the voters standing in for cooling down members are drawn with
+pub const COOLDOWN_SEED_DOMAIN: &[u8] = b"safecoin-vote-group-cooldown-seed";
+/// domain separation for seeds hashed out of caller supplied material
+pub const MATERIAL_SEED_DOMAIN: &[u8] = b"safecoin-vote-group-material-seed";
+/// domain separation for the message a group's threshold signature covers
+pub const THRESHOLD_SIGNATURE_DOMAIN: &[u8] = b"safecoin-vote-group-threshold-signature";
+/// domain separation for the key of the ChaCha20 stream v3 groups are shuffled with
+pub const CHACHA_SEED_DOMAIN: &[u8] = b"safecoin-vote-group-chacha-seed";
+/// domain separation for the merkle tree a voter set commitment is the root of
+pub const VOTER_SET_COMMITMENT_DOMAIN: &[u8] = b"safecoin-vote-group-voter-set";
+/// domain separation for the secrets, merkle tree and draws of commit-reveal membership
+pub const MEMBERSHIP_COMMITMENT_DOMAIN: &[u8] = b"safecoin-vote-group-membership-commitment";
+/// domain of the per voter draws of binomial groups
+pub const BINOMIAL_SEED_DOMAIN: &[u8] = b"safecoin-vote-group-binomial-seed";
+/// the overlap between consecutive groups can't be more than half a group, the members
+/// shared with the previous and with the next slot both have to fit
+pub const MAX_OVERLAP_PERCENT: u8 = 50;
+
+/// a group reaches quorum once more than this share of it voted, by member count or by
+/// member stake when the group is stake weighted
+pub const GROUP_QUORUM_NUMERATOR: u64 = 2;
+pub const GROUP_QUORUM_DENOMINATOR: u64 = 3;
+
+/// membership checks log under this target so they can be filtered on their own,
+/// e.g. RUST_LOG=solana_vote_group=trace
+pub const VOTE_GROUP_LOG_TARGET: &str = "solana_vote_group";
+
+#[derive(Error, Debug, Serialize, Clone, PartialEq, Eq)]
+pub enum VoteGroupError {
+    /// There are no voters to pick a group from
+    #[error("The voter set is empty")]
+    EmptyVoterSet,
+    /// A group must have at least one member
+    #[error("The group size must be greater than zero")]
+    ZeroGroupSize,
+    /// The group cannot be bigger than the voter set
+    #[error("Group size {group_size} exceeds the {voters} available voters")]
+    GroupSizeTooLarge { group_size: usize, voters: usize },
+    /// Seed material must be a non-empty multiple of 8 bytes
+    #[error("Seed material of length {0} cannot be folded into a seed")]
+    BadSeedMaterial(usize),
+    /// A deserialized generator whose fields contradict each other
+    #[error("The generator state is inconsistent: {0}")]
+    InconsistentState(String),
+    /// A compact group was encoded against a different voter set
+    #[error("The group was encoded against {encoded} voters but {voters} are known")]
+    VoterCountMismatch { encoded: usize, voters: usize },
+    /// The bitmap of a compact group has the wrong length or bits past the last voter
+    #[error("The group bitmap is malformed")]
+    MalformedBitmap,
+    /// A stake weighted group needs at least one voter with stake
+    #[error("None of the voters has any stake")]
+    NoStakedVoters,
+    /// Writing the state in an older layout would lose some of it
+    #[error("The older layout cannot hold {0}")]
+    LegacyLayout(String),
+}
+
+/// how the members of a group are picked from the possible voters
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Serialize,
+    Deserialize,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    AbiExample,
+    AbiEnumVisitor,
+    PartialEq,
+    Eq,
+)]
+pub enum SelectionMode {
+    /// every voter is equally likely, groups are walked out of the ring
+    Uniform,
+    /// voters are picked in proportion to their stake
+    StakeWeighted,
+    /// every voter is equally likely and the groups of consecutive slots share at least
+    /// `min_overlap_percent` of group_size members, so towers don't start over every slot
+    Overlapping { min_overlap_percent: u8 },
+    /// every voter is picked on its own with a chance proportional to its stake, group_size
+    /// members are expected but the count changes from group to group. a voter holding more
+    /// than 1/group_size of the stake is always picked, the expected size falls short then
+    Binomial,
+}
+
+impl Default for SelectionMode {
+    fn default() -> Self {
+        SelectionMode::Uniform
+    }
+}
+
+/// version of the ring walk used to pick groups, switched cluster wide by feature gate
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Serialize,
+    Deserialize,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    AbiExample,
+    AbiEnumVisitor,
+    PartialEq,
+    Eq,
+)]
+pub enum SelectionAlgorithm {
+    /// the seed picks the start and the distance directly, members may repeat
+    V1,
+    /// the seed is mixed before use and members are always distinct
+    V2,
+    /// no ring, a ChaCha20 stream keyed by the seed drives a partial Fisher-Yates shuffle
+    /// of all voters, members are always distinct
+    V3,
+}
+
+impl Default for SelectionAlgorithm {
+    fn default() -> Self {
+        SelectionAlgorithm::V1
+    }
+}
+
+/// parses the lowercased names the algorithms are reported with, "v1", "v2" and "v3"
+impl std::str::FromStr for SelectionAlgorithm {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "v1" => Ok(SelectionAlgorithm::V1),
+            "v2" => Ok(SelectionAlgorithm::V2),
+            "v3" => Ok(SelectionAlgorithm::V3),
+            _ => Err(format!("unknown vote group selection algorithm: {}", s)),
+        }
+    }
+}
+
+/// the least delegated stake a vote account needs to be picked for a group, keeps dust
+/// accounts from diluting the groups and makes every sybil account cost real stake
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MinimumStake {
+    /// every staked vote account is eligible
+    None,
+    /// at least this many lamports
+    Lamports(u64),
+    /// at least `numerator / denominator` of the epoch's total stake
+    FractionOfTotal { numerator: u64, denominator: u64 },
+}
+
+impl Default for MinimumStake {
+    fn default() -> Self {
+        MinimumStake::None
+    }
+}
+
+impl MinimumStake {
+    /// integer only so every node draws the line in the same place, a zero denominator
+    /// disables the threshold
+    pub fn is_met(&self, stake: u64, total_stake: u64) -> bool {
+        match *self {
+            MinimumStake::None => true,
+            MinimumStake::Lamports(lamports) => stake >= lamports,
+            MinimumStake::FractionOfTotal {
+                numerator,
+                denominator,
+            } => {
+                denominator == 0
+                    || stake as u128 * denominator as u128
+                        >= total_stake as u128 * numerator as u128
+            }
+        }
+    }
+}
+
+/// vote accounts whose last landed vote is too old when an epoch's stakes are captured are
+/// left out of that epoch's groups. one that was left out has to vote a lot more recently
+/// before it is picked again, so a voter sitting right on the line doesn't flap in and out
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DelinquencyThreshold {
+    /// an eligible voter whose last vote is further behind than this is left out
+    pub max_slot_distance: Slot,
+    /// a voter that was left out comes back once its last vote is at most this far behind
+    pub reentry_slot_distance: Slot,
+}
+
+impl Default for DelinquencyThreshold {
+    fn default() -> Self {
+        Self {
+            max_slot_distance: DEFAULT_DELINQUENT_SLOT_DISTANCE,
+            reentry_slot_distance: DEFAULT_REENTRY_SLOT_DISTANCE,
+        }
+    }
+}
+
+impl DelinquencyThreshold {
+    /// a vote account that never landed a vote is always delinquent
+    pub fn is_delinquent(
+        &self,
+        last_voted_slot: Option<Slot>,
+        slot: Slot,
+        was_delinquent: bool,
+    ) -> bool {
+        let max_slot_distance = if was_delinquent {
+            self.reentry_slot_distance.min(self.max_slot_distance)
+        } else {
+            self.max_slot_distance
+        };
+        last_voted_slot.map_or(true, |last_voted_slot| {
+            slot.saturating_sub(last_voted_slot) > max_slot_distance
+        })
+    }
+}
+
+/// vote accounts that voted on too few of the slots an epoch's groups assigned them are left
+/// out of the next groups. whoever was left out has no assigned slots to miss in the epoch
+/// it sits out, so it is back on the following one
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AbsenceThreshold {
+    pub max_absent_percent: u8,
+    pub min_assigned_slots: u64,
+}
+
+impl Default for AbsenceThreshold {
+    fn default() -> Self {
+        Self {
+            max_absent_percent: DEFAULT_MAX_ABSENT_PERCENT,
+            min_assigned_slots: DEFAULT_MIN_ASSIGNED_SLOTS,
+        }
+    }
+}
+
+impl AbsenceThreshold {
+    /// `voted` counts the assigned slots the account voted on, anything past `assigned` is
+    /// ignored
+    pub fn is_absent(&self, assigned: u64, voted: u64) -> bool {
+        let absent = assigned.saturating_sub(voted);
+        assigned >= self.min_assigned_slots.max(1)
+            && absent as u128 * 100 > assigned as u128 * self.max_absent_percent as u128
+    }
+}
+
+/// a group size that follows the number of eligible voters, `percent` of them rounded up
+/// and kept within `min..=max`, so the groups grow with the cluster without retuning
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DynamicGroupSize {
+    pub percent: u8,
+    pub min: usize,
+    pub max: usize,
+}
+
+impl DynamicGroupSize {
+    /// the group size for an epoch with `eligible_voters`, never more than there are
+    pub fn size_for(&self, eligible_voters: usize) -> usize {
+        let size = (eligible_voters as u128 * self.percent as u128 + 99) / 100;
+        (size as usize)
+            .max(self.min)
+            .min(self.max)
+            .min(eligible_voters)
+    }
+}
+
+/// the share of a group whose votes make a quorum, more than numerator / denominator of
+/// it, GROUP_QUORUM_NUMERATOR / GROUP_QUORUM_DENOMINATOR unless the cluster set another
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, AbiExample, PartialEq, Eq)]
+pub struct QuorumFraction {
+    pub numerator: u64,
+    pub denominator: u64,
+}
+
+impl Default for QuorumFraction {
+    fn default() -> Self {
+        Self {
+            numerator: GROUP_QUORUM_NUMERATOR,
+            denominator: GROUP_QUORUM_DENOMINATOR,
+        }
+    }
+}
+
+impl QuorumFraction {
+    /// at least half and less than all of the group
+    pub fn is_valid(&self) -> bool {
+        self.numerator < self.denominator && self.numerator.saturating_mul(2) >= self.denominator
+    }
+
+    /// the fraction as a share, how stake thresholds are compared
+    pub fn as_f64(&self) -> f64 {
+        self.numerator as f64 / self.denominator as f64
+    }
+}
+
+/// everything the runtime decides about how an epoch's generator is built
+#[derive(Clone, Debug, PartialEq)]
+pub struct VoteGroupParams {
+    pub selection_mode: SelectionMode,
+    pub algorithm: SelectionAlgorithm,
+    pub distinct_members: bool,
+    /// keep one vote account per node identity, see ExclusionList::add_identity_duplicates
+    pub dedup_identities: bool,
+    /// upper bound on the group size, the generator never selects more voters than it has
+    pub group_size: usize,
+    /// replaces group_size with a share of the eligible voters when set
+    pub dynamic_group_size: Option<DynamicGroupSize>,
+    /// leave out vote accounts whose vote state flags them for this epoch's groups
+    pub exclude_penalized: bool,
+    /// leave out vote accounts with less delegated stake than this
+    pub minimum_stake: MinimumStake,
+    /// leave out vote accounts that stopped voting, needs the vote activity at capture
+    pub delinquency: Option<DelinquencyThreshold>,
+    /// leave out vote accounts that missed their assigned slots, needs the previous epoch's
+    /// assignments at capture
+    pub absence: Option<AbsenceThreshold>,
+    /// slots every group lasts, only groups seeded from slots can last longer than one
+    pub rotation_window_slots: u64,
+    /// vote accounts that are never picked, the cluster's registry or the legacy list
+    pub exclusions: ExclusionList,
+    /// every group is these keys and nothing else, only node local overrides set them
+    pub preset_members: Option<Vec<Pubkey>>,
+}
+
+impl Default for VoteGroupParams {
+    fn default() -> Self {
+        Self {
+            selection_mode: SelectionMode::default(),
+            algorithm: SelectionAlgorithm::default(),
+            distinct_members: false,
+            dedup_identities: false,
+            group_size: OPTIMAL_VOTE_GROUP_SIZE,
+            dynamic_group_size: None,
+            exclude_penalized: false,
+            minimum_stake: MinimumStake::default(),
+            delinquency: None,
+            absence: None,
+            rotation_window_slots: DEFAULT_ROTATION_WINDOW_SLOTS,
+            exclusions: ExclusionList::legacy(),
+            preset_members: None,
+        }
+    }
+}
+
+/// node local replacements for what the cluster decides about the groups, only meant for
+/// development clusters where every node is started with the same flags
+#[derive(Clone, Debug, Default, AbiExample, PartialEq, Eq)]
+pub struct VoteGroupOverrides {
+    pub group_size: Option<usize>,
+    pub algorithm: Option<SelectionAlgorithm>,
+    /// pins the members of every group, group_size is ignored
+    pub members: Option<Vec<Pubkey>>,
+    /// pins the seed of every group, so the members always come in the same order
+    pub seed: Option<u64>,
+}
+
+impl VoteGroupOverrides {
+    pub fn is_empty(&self) -> bool {
+        self.group_size.is_none()
+            && self.algorithm.is_none()
+            && self.members.is_none()
+            && self.seed.is_none()
+    }
+
+    pub fn apply(&self, params: &mut VoteGroupParams) {
+        if let Some(group_size) = self.group_size {
+            params.group_size = group_size;
+            params.dynamic_group_size = None;
+        }
+        if let Some(algorithm) = self.algorithm {
+            params.algorithm = algorithm;
+        }
+        if let Some(members) = &self.members {
+            params.preset_members = Some(members.clone());
+        }
+    }
+}
+
+//#[derive(Clone, Debug, Serialize, Deserialize, AbiExample, PartialEq)]
+//pub struct ArcPubkey(std::sync::Arc<Pubkey>);
+
+#[derive(Clone, Debug, Serialize, Deserialize, AbiExample, PartialEq)]
+pub struct VoteGroupGenerator {
+    #[serde(
+        serialize_with = "base58_keys::serialize_slice",
+        deserialize_with = "base58_keys::deserialize"
+    )]
+    possible_voters: Arc<[Pubkey]>, // shared by clones, replaced rather than mutated
+    all_distance: Vec<u32>, // shift distances coprime with the number of possible voters (v1 keeps the legacy list)
+
+    group_size: usize,
+    ordering_version: u8,
+    selection_mode: SelectionMode,
+    alias_table: AliasTable, // stake weights of possible_voters, empty unless StakeWeighted or Binomial
+    algorithm: SelectionAlgorithm,
+    distinct_members: bool, // v1 re-derives the distance when the walk revisits a voter
+    #[serde(skip)]
+    group_cache: GroupCache, // groups of recently checked seeds, cleared when the voters change
+    #[serde(skip)]
+    voter_set_root: VoterSetRoot, // merkle root of possible_voters, computed on first use and cleared when they change
+    #[serde(skip)]
+    authorized_voters: AuthorizedVoters, // vote account to the key that signs its votes
+}
+
+impl VoteGroupGenerator {
+    /// `map` is vote account to authorized voter, the keys are who gets selected
+    pub fn new(map: &HashMap<Pubkey, Pubkey>, size: usize) -> VoteGroupGenerator {
+        Self::new_with_exclusions(map, size, &ExclusionList::legacy())
+    }
+
+    /// same as new but the voters in `exclusions` will never be selected
+    pub fn new_with_exclusions(
+        map: &HashMap<Pubkey, Pubkey>,
+        size: usize,
+        exclusions: &ExclusionList,
+    ) -> VoteGroupGenerator {
+        let mut temp: Vec<Pubkey> = map
+            .keys()
+            .filter(|key| !exclusions.contains(key))
+            .copied()
+            .collect();
+        // every node must see the voters in the same order or they will disagree on the groups
+        temp.sort();
+        let all_distance = Self::distances_for(temp.len(), SelectionAlgorithm::V1);
+        let authorized_voters = AuthorizedVoters::new(map, &temp);
+        Self {
+            possible_voters: temp.into(),
+            all_distance,
+            group_size: size,
+            ordering_version: CANONICAL_ORDERING_VERSION,
+            selection_mode: SelectionMode::Uniform,
+            alias_table: AliasTable::default(),
+            algorithm: SelectionAlgorithm::V1,
+            distinct_members: false,
+            group_cache: GroupCache::default(),
+            voter_set_root: VoterSetRoot::default(),
+            authorized_voters,
+        }
+    }
+
+    /// same as new_with_exclusions but only the most staked vote account of each node
+    /// identity in `identities`, keyed by vote account, can be selected
+    pub fn new_with_identity_dedup(
+        map: &HashMap<Pubkey, Pubkey>,
+        identities: &HashMap<Pubkey, Pubkey>,
+        stakes: &HashMap<Pubkey, u64>,
+        size: usize,
+        exclusions: &ExclusionList,
+    ) -> VoteGroupGenerator {
+        let mut exclusions = exclusions.clone();
+        exclusions
+            .add_identity_duplicates(identities, |key| stakes.get(key).copied().unwrap_or(0));
+        Self::new_with_exclusions(map, size, &exclusions)
+    }
+
+    /// the shift distances usable on a ring of `len` voters
+    fn distances_for(len: usize, algorithm: SelectionAlgorithm) -> Vec<u32> {
+        match algorithm {
+            SelectionAlgorithm::V1 => Self::legacy_distances(len),
+            SelectionAlgorithm::V2 => coprime_distances(len),
+            SelectionAlgorithm::V3 => Vec::new(),
+        }
+    }
+
+    /// the hardcoded list v1 groups were always built from, it contains the non-primes
+    /// 51, 57 and 87 and stops at 103, kept as is so v1 groups never change
+    fn legacy_distances(len: usize) -> Vec<u32> {
+        let len = len as u32;
+        let mut initial = Vec::new();
+        initial.push(1);
+        for val in [
+            2, 3, 5, 7, 11, 13, 17, 23, 29, 31, 37, 41, 43, 47, 51, 53, 57, 59, 61, 67, 71, 73, 79,
+            83, 87, 89, 97, 101, 103,
+        ]
+        .iter()
+        {
+            if (len > *val) && ((len % *val) != 0) {
+                initial.push(*val);
+            }
+        }
+        initial
+    }
+
+    /// adds a voter in its canonical position, `stake` only matters when stake weighted
+    /// returns false if the voter was already present
+    pub fn add_voter(&mut self, key: Pubkey, stake: u64) -> bool {
+        let idx = match self.possible_voters.binary_search(&key) {
+            Ok(_) => return false,
+            Err(idx) => idx,
+        };
+        let mut voters = self.possible_voters.to_vec();
+        voters.insert(idx, key);
+        self.possible_voters = voters.into();
+        if self.weighs_stake() {
+            let mut weights = self.alias_table.weights().to_vec();
+            weights.resize(self.possible_voters.len() - 1, 0);
+            weights.insert(idx, stake);
+            self.alias_table = AliasTable::new(&weights);
+        }
+        self.all_distance = Self::distances_for(self.possible_voters.len(), self.algorithm);
+        self.group_cache.clear();
+        self.voter_set_root.clear();
+        true
+    }
+
+    /// returns false if the voter wasn't present
+    pub fn remove_voter(&mut self, key: &Pubkey) -> bool {
+        let idx = match self.possible_voters.binary_search(key) {
+            Ok(idx) => idx,
+            Err(_) => return false,
+        };
+        let mut voters = self.possible_voters.to_vec();
+        voters.remove(idx);
+        self.possible_voters = voters.into();
+        self.authorized_voters.remove(key);
+        if self.weighs_stake() {
+            let mut weights = self.alias_table.weights().to_vec();
+            if idx < weights.len() {
+                weights.remove(idx);
+            }
+            self.alias_table = AliasTable::new(&weights);
+        }
+        self.all_distance = Self::distances_for(self.possible_voters.len(), self.algorithm);
+        self.group_cache.clear();
+        self.voter_set_root.clear();
+        true
+    }
+
+    /// swaps in a new voter set keeping the group size, selection mode and algorithm
+    pub fn replace_voters(
+        &mut self,
+        map: &HashMap<Pubkey, Pubkey>,
+        stakes: &HashMap<Pubkey, u64>,
+        exclusions: &ExclusionList,
+    ) {
+        let mut voters: Vec<Pubkey> = map
+            .keys()
+            .filter(|key| !exclusions.contains(key))
+            .copied()
+            .collect();
+        voters.sort();
+        if self.weighs_stake() {
+            let weights: Vec<u64> = voters
+                .iter()
+                .map(|key| stakes.get(key).copied().unwrap_or(0))
+                .collect();
+            self.alias_table = AliasTable::new(&weights);
+        }
+        self.all_distance = Self::distances_for(voters.len(), self.algorithm);
+        self.authorized_voters = AuthorizedVoters::new(map, &voters);
+        self.possible_voters = voters.into();
+        self.group_cache.clear();
+        self.voter_set_root.clear();
+    }
+
+    /// picks group members in proportion to `stakes`, voters missing from `stakes` are never picked
+    pub fn new_stake_weighted(
+        map: &HashMap<Pubkey, Pubkey>,
+        stakes: &HashMap<Pubkey, u64>,
+        size: usize,
+        exclusions: &ExclusionList,
+    ) -> VoteGroupGenerator {
+        let mut generator = Self::new_with_exclusions(map, size, exclusions);
+        let weights: Vec<u64> = generator
+            .possible_voters
+            .iter()
+            .map(|key| stakes.get(key).copied().unwrap_or(0))
+            .collect();
+        generator.selection_mode = SelectionMode::StakeWeighted;
+        generator.alias_table = AliasTable::new(&weights);
+        generator.group_cache.clear();
+        generator
+    }
+
+    /// picks every voter on its own with a chance proportional to `stakes`, `size` is the
+    /// expected group size. voters missing from `stakes` are never picked
+    pub fn new_binomial(
+        map: &HashMap<Pubkey, Pubkey>,
+        stakes: &HashMap<Pubkey, u64>,
+        size: usize,
+        exclusions: &ExclusionList,
+    ) -> VoteGroupGenerator {
+        let mut generator = Self::new_stake_weighted(map, stakes, size, exclusions);
+        generator.selection_mode = SelectionMode::Binomial;
+        generator
+    }
+
+    pub fn selection_mode(&self) -> SelectionMode {
+        self.selection_mode
+    }
+
+    /// whether the stake weights are kept, they follow the voters as they come and go
+    fn weighs_stake(&self) -> bool {
+        matches!(
+            self.selection_mode,
+            SelectionMode::StakeWeighted | SelectionMode::Binomial
+        )
+    }
+
+    pub fn with_algorithm(mut self, algorithm: SelectionAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self.all_distance = Self::distances_for(self.possible_voters.len(), algorithm);
+        self.group_cache.clear();
+        self
+    }
+
+    pub fn algorithm(&self) -> SelectionAlgorithm {
+        self.algorithm
+    }
+
+    pub fn with_distinct_members(mut self, distinct_members: bool) -> Self {
+        self.distinct_members = distinct_members;
+        self.group_cache.clear();
+        self
+    }
+
+    pub fn distinct_members(&self) -> bool {
+        self.distinct_members
+    }
+
+    /// switches to overlapping groups, the overlap is capped at MAX_OVERLAP_PERCENT
+    pub fn with_min_overlap(mut self, min_overlap_percent: u8) -> Self {
+        self.selection_mode = SelectionMode::Overlapping {
+            min_overlap_percent: min_overlap_percent.min(MAX_OVERLAP_PERCENT),
+        };
+        self.alias_table = AliasTable::default();
+        self.group_cache.clear();
+        self
+    }
+
+    /// checked construction, see VoteGroupGeneratorBuilder
+    pub fn builder() -> VoteGroupGeneratorBuilder {
+        VoteGroupGeneratorBuilder::new()
+    }
+
+    /// same as new but refuses to build a generator that can't select a sensible group
+    pub fn try_new(
+        map: &HashMap<Pubkey, Pubkey>,
+        size: usize,
+    ) -> Result<VoteGroupGenerator, VoteGroupError> {
+        Self::builder()
+            .with_voters(map.keys().copied())
+            .with_group_size(size)
+            .build()
+    }
+
+    fn validate(&self) -> Result<(), VoteGroupError> {
+        if self.possible_voters.is_empty() {
+            return Err(VoteGroupError::EmptyVoterSet);
+        }
+        if self.group_size == 0 {
+            return Err(VoteGroupError::ZeroGroupSize);
+        }
+        if self.group_size > self.possible_voters.len() {
+            return Err(VoteGroupError::GroupSizeTooLarge {
+                group_size: self.group_size,
+                voters: self.possible_voters.len(),
+            });
+        }
+        Ok(())
+    }
+
+    /// checks that the fields agree with each other the way the constructors leave them,
+    /// a generator that came off the wire should pass this before any group is walked
+    pub fn check_state(&self) -> Result<(), VoteGroupError> {
+        let inconsistent = |reason: &str| Err(VoteGroupError::InconsistentState(reason.into()));
+        if self.possible_voters.windows(2).any(|w| w[0] >= w[1]) {
+            return inconsistent("voters are not sorted and unique");
+        }
+        if !self.possible_voters.is_empty()
+            && self.all_distance != Self::distances_for(self.possible_voters.len(), self.algorithm)
+        {
+            return inconsistent("distances don't match the voter count");
+        }
+        if !self.alias_table.is_empty()
+            && !self.alias_table.is_consistent(self.possible_voters.len())
+        {
+            return inconsistent("stake weights don't match the voters");
+        }
+        if let SelectionMode::Overlapping {
+            min_overlap_percent,
+        } = self.selection_mode
+        {
+            if min_overlap_percent > MAX_OVERLAP_PERCENT {
+                return inconsistent("the overlap is more than half a group");
+            }
+        }
+        Ok(())
+    }
+
+    /// every voter that can be selected, in canonical order
+    pub fn voters(&self) -> &[Pubkey] {
+        &self.possible_voters
+    }
+
+    /// the canonical position of `key`, a binary search of the sorted voters
+    pub fn index_of(&self, key: &Pubkey) -> Option<usize> {
+        self.possible_voters.binary_search(key).ok()
+    }
+
+    /// merkle root of the voters in canonical order, see voter_set
+    pub fn voter_set_root(&self) -> Hash {
+        self.voter_set_root.get_or_compute(&self.possible_voters)
+    }
+
+    /// hash of `seed` and the members of its group in canonical order, whoever computes
+    /// another group for the seed gets another commitment
+    pub fn group_commitment(&self, seed: u64) -> Result<Hash, VoteGroupError> {
+        let mut members = self.try_group_for_seed(seed)?;
+        members.sort();
+        let seed = seed.to_le_bytes();
+        let mut data: Vec<&[u8]> = Vec::with_capacity(members.len() + 1);
+        data.push(&seed);
+        data.extend(members.iter().map(|member| member.as_ref()));
+        Ok(hashv(&data))
+    }
+
+    /// proves `key` was or wasn't one of the voters against voter_set_root
+    pub fn eligibility_proof(&self, key: &Pubkey) -> EligibilityProof {
+        EligibilityProof::new(&self.possible_voters, key)
+    }
+
+    /// the key that signs the votes of `vote_account`, None for a vote account that can't be
+    /// selected or a generator that was read back without its authorized voters
+    pub fn authorized_voter_for(&self, vote_account: &Pubkey) -> Option<Pubkey> {
+        self.authorized_voters.get(vote_account)
+    }
+
+    /// puts back the authorized voters of a generator that was read back, `map` is keyed by
+    /// vote account like new's
+    pub fn set_authorized_voters(&mut self, map: &HashMap<Pubkey, Pubkey>) {
+        self.authorized_voters = AuthorizedVoters::new(map, &self.possible_voters);
+    }
+
+    pub fn group_size(&self) -> usize {
+        self.group_size
+    }
+
+    /// the number of distinct members a group really has, a group_size beyond the voter
+    /// count is clamped to the whole voter set
+    pub fn effective_group_size(&self) -> usize {
+        self.group_size.min(self.possible_voters.len())
+    }
+
+    /// the number of members a group has on average, effective_group_size unless the group
+    /// is binomial
+    pub fn expected_group_size(&self) -> f64 {
+        match self.binomial_probabilities() {
+            Some(probabilities) => probabilities.sum(),
+            None => self.effective_group_size() as f64,
+        }
+    }
+
+    /// how much the member count spreads around expected_group_size, a binomial group's is
+    /// the sum of p(1 - p) over its voters and every other group always has the same size
+    pub fn group_size_variance(&self) -> f64 {
+        match self.binomial_probabilities() {
+            Some(probabilities) => probabilities.map(|p| p * (1. - p)).sum(),
+            None => 0.,
+        }
+    }
+
+    pub fn group_size_std_dev(&self) -> f64 {
+        self.group_size_variance().sqrt()
+    }
+
+    /// the chance `key` is in a binomial group, None for other groups and unknown keys
+    pub fn selection_probability(&self, key: &Pubkey) -> Option<f64> {
+        let idx = self.index_of(key)?;
+        self.binomial_probabilities()?.nth(idx)
+    }
+
+    fn binomial_probabilities(&self) -> Option<impl Iterator<Item = f64> + '_> {
+        if self.selection_mode != SelectionMode::Binomial || self.alias_table.is_empty() {
+            return None;
+        }
+        Some(
+            self.alias_table
+                .weights()
+                .iter()
+                .map(move |weight| self.binomial_threshold(*weight) as f64 / 2f64.powi(64)),
+        )
+    }
+
+    /// draws below this put a voter with `weight` in the group, out of 2^64. group_size
+    /// times its share of the stake, so the chances add up to group_size
+    fn binomial_threshold(&self, weight: u64) -> u128 {
+        let total = self.alias_table.total() as u128;
+        let scaled = weight as u128 * self.group_size as u128;
+        if scaled >= total {
+            return 1 << 64;
+        }
+        (scaled << 64) / total
+    }
+
+    pub fn ordering_version(&self) -> u8 {
+        self.ordering_version
+    }
+
+    /// the ring distance the walk for `seed` starts with, None when the group isn't walked
+    /// around the ring
+    pub fn distance_for_seed(&self, seed: u64) -> Option<u32> {
+        if self.possible_voters.is_empty() || self.all_distance.is_empty() {
+            return None;
+        }
+        if self.weighs_stake() && !self.alias_table.is_empty() {
+            return None;
+        }
+        distance_for(seed, self.algorithm, &self.all_distance)
+    }
+
+    pub fn new_dummy() -> VoteGroupGenerator {
+        let hm: HashMap<Pubkey, Pubkey> = HashMap::new();
+        Self::new(&hm, 1)
+    }
+
+    /// folds the hash straight into a seed, the groups drawn from bank hashes depend on it
+    /// staying that way. anything else should go through in_group_for_bytes
+    pub fn in_group_for_hash(&self, hash: Hash, test_key: Pubkey) -> bool {
+        self.in_group_for_seed(hash_to_seed(hash), test_key)
+    }
+
+    /// returns the complete group for a given hash, in selection order
+    pub fn group_for_hash(&self, hash: Hash) -> Vec<Pubkey> {
+        self.group_for_seed(hash_to_seed(hash))
+    }
+
+    /// same as in_group_for_seed with the seed hashed out of `material`, any length will do
+    pub fn in_group_for_bytes(&self, material: impl AsRef<[u8]>, test_key: Pubkey) -> bool {
+        self.in_group_for_seed(seed_for_material(material.as_ref()), test_key)
+    }
+
+    /// returns the complete group for the seed hashed out of `material`, in selection order
+    pub fn group_for_bytes(&self, material: impl AsRef<[u8]>) -> Vec<Pubkey> {
+        self.group_for_seed(seed_for_material(material.as_ref()))
+    }
+
+    /// returns the complete group for a given seed, in selection order
+    pub fn group_for_seed(&self, seed: u64) -> Vec<Pubkey> {
+        self.group_indexes(seed)
+            .into_iter()
+            .map(|idx| self.possible_voters[idx])
+            .collect()
+    }
+
+    /// returns the indexes of the selected voters for a seed, in selection order
+    fn group_indexes(&self, seed: u64) -> Vec<usize> {
+        if self.possible_voters.is_empty() {
+            return Vec::new();
+        }
+        match self.selection_mode {
+            SelectionMode::StakeWeighted if !self.alias_table.is_empty() => {
+                self.weighted_indexes(seed)
+            }
+            SelectionMode::Binomial if !self.alias_table.is_empty() => self.binomial_indexes(seed),
+            _ => self.ring_indexes(seed),
+        }
+    }
+
+    /// every voter's own draw for `seed` against its binomial_threshold, the members come
+    /// lowest draw first. hashes once per voter, the cache makes up for it
+    fn binomial_indexes(&self, seed: u64) -> Vec<usize> {
+        let seed = seed.to_le_bytes();
+        let mut draws: Vec<(u64, usize)> = self
+            .possible_voters
+            .iter()
+            .zip(self.alias_table.weights())
+            .enumerate()
+            .filter_map(|(idx, (key, weight))| {
+                let draw = hash_to_seed(hashv(&[BINOMIAL_SEED_DOMAIN, &seed, key.as_ref()]));
+                ((draw as u128) < self.binomial_threshold(*weight)).then(|| (draw, idx))
+            })
+            .collect();
+        draws.sort_unstable();
+        draws.into_iter().map(|(_, idx)| idx).collect()
+    }
+
+    /// draws distinct voters out of the alias table using a stream seeded by `seed`
+    fn weighted_indexes(&self, seed: u64) -> Vec<usize> {
+        self.weighted_indexes_excluding(seed, &[])
+    }
+
+    /// weighted_indexes that never picks a voter in `excluded`, which has to be sorted
+    fn weighted_indexes_excluding(&self, seed: u64, excluded: &[usize]) -> Vec<usize> {
+        self.alias_table
+            .sample_distinct(self.group_size, seed, excluded)
+    }
+
+    fn ring_indexes(&self, seed: u64) -> Vec<usize> {
+        ring_indexes_for(
+            seed,
+            self.possible_voters.len(),
+            self.group_size,
+            self.algorithm,
+            self.distinct_members,
+            &self.all_distance,
+        )
+    }
+
+    /// see selection_core::chacha_indexes
+    #[cfg(test)]
+    fn chacha_indexes(&self, seed: u64) -> Vec<usize> {
+        ring_indexes_for(
+            seed,
+            self.possible_voters.len(),
+            self.group_size,
+            SelectionAlgorithm::V3,
+            self.distinct_members,
+            &[],
+        )
+    }
+
+    /// a second group for `seed` made of voters the primary group left out, at most
+    /// group_size of them and empty when the primary group already holds every voter.
+    /// it only ever stands in for a primary group that doesn't show up to vote
+    pub fn backup_group_for_seed(&self, seed: u64) -> Vec<Pubkey> {
+        self.backup_group_indexes(seed)
+            .into_iter()
+            .map(|idx| self.possible_voters[idx])
+            .collect()
+    }
+
+    fn backup_group_indexes(&self, seed: u64) -> Vec<usize> {
+        let mut primary = self.group_indexes(seed);
+        primary.sort_unstable();
+        let backup_seed = hash_to_seed(hashv(&[BACKUP_SEED_DOMAIN, &seed.to_le_bytes()]));
+        match self.selection_mode {
+            SelectionMode::StakeWeighted | SelectionMode::Binomial
+                if !self.alias_table.is_empty() =>
+            {
+                self.weighted_indexes_excluding(backup_seed, &primary)
+            }
+            _ => {
+                let remaining: Vec<usize> = (0..self.possible_voters.len())
+                    .filter(|idx| primary.binary_search(idx).is_err())
+                    .collect();
+                let target = self.group_size.min(remaining.len());
+                pick_distinct(remaining, target, backup_seed)
+            }
+        }
+    }
+
+    /// the group for `seed` with the slot leader's vote accounts taken out so the block
+    /// producer never confirms its own block. every place a leader account held goes to the
+    /// next member of the backup group, the group only shrinks once the backup runs out
+    pub fn group_for_seed_without_leader(
+        &self,
+        seed: u64,
+        leader_vote_accounts: &[Pubkey],
+    ) -> Vec<Pubkey> {
+        let group = self.group_for_seed(seed);
+        if !group.iter().any(|key| leader_vote_accounts.contains(key)) {
+            return group;
+        }
+        let mut replacements = self
+            .backup_group_for_seed(seed)
+            .into_iter()
+            .filter(|key| !leader_vote_accounts.contains(key));
+        group
+            .into_iter()
+            .filter_map(|key| {
+                if leader_vote_accounts.contains(&key) {
+                    replacements.next()
+                } else {
+                    Some(key)
+                }
+            })
+            .collect()
+    }
+
+    /// same as in_group_for_seed for the group without the leader's vote accounts, only
+    /// walks the backup group when a leader account was picked
+    pub fn in_group_for_seed_without_leader(
+        &self,
+        seed: u64,
+        leader_vote_accounts: &[Pubkey],
+        test_key: Pubkey,
+    ) -> bool {
+        if leader_vote_accounts.contains(&test_key) {
+            return false;
+        }
+        let group = self.cached_group(seed);
+        if group.contains_key(&test_key) {
+            return true;
+        }
+        leader_vote_accounts
+            .iter()
+            .any(|key| group.contains_key(key))
+            && self
+                .group_for_seed_without_leader(seed, leader_vote_accounts)
+                .contains(&test_key)
+    }
+
+    /// the `count` voters next in line after the group for `seed`, in the order they stand in
+    /// for members that are offline. ring walks go on past the group and step to the next
+    /// free voter once the walk only revisits, asking them for fewer only cuts the list
+    /// short. stake weighted groups draw their standby voters by stake
+    pub fn standby_for_seed(&self, seed: u64, count: usize) -> Vec<Pubkey> {
+        self.group_and_standby_for_seed(seed, count).1
+    }
+
+    /// the group for `seed` and its standby list, see standby_for_seed
+    pub fn group_and_standby_for_seed(
+        &self,
+        seed: u64,
+        count: usize,
+    ) -> (Vec<Pubkey>, Vec<Pubkey>) {
+        let group = self.group_indexes(seed);
+        let standby = self.standby_indexes(seed, &group, count);
+        let keys = |indexes: Vec<usize>| -> Vec<Pubkey> {
+            indexes
+                .into_iter()
+                .map(|idx| self.possible_voters[idx])
+                .collect()
+        };
+        (keys(group), keys(standby))
+    }
+
+    /// the group for `seed` with every member in `offline` replaced by the next standby
+    /// voter that isn't offline itself. the group only shrinks once the `count` standby
+    /// voters run out
+    pub fn group_with_standby_promoted(
+        &self,
+        seed: u64,
+        count: usize,
+        offline: &[Pubkey],
+    ) -> Vec<Pubkey> {
+        let (group, standby) = self.group_and_standby_for_seed(seed, count);
+        if !group.iter().any(|key| offline.contains(key)) {
+            return group;
+        }
+        let mut standby = standby.into_iter().filter(|key| !offline.contains(key));
+        group
+            .into_iter()
+            .filter_map(|key| {
+                if offline.contains(&key) {
+                    standby.next()
+                } else {
+                    Some(key)
+                }
+            })
+            .collect()
+    }
+
+    fn standby_indexes(&self, seed: u64, group: &[usize], count: usize) -> Vec<usize> {
+        let voters_len = self.possible_voters.len();
+        let mut taken: HashSet<usize> = group.iter().copied().collect();
+        let target = count.min(voters_len - taken.len());
+        if target == 0 {
+            return Vec::new();
+        }
+        if self.weighs_stake() && !self.alias_table.is_empty() {
+            let mut excluded: Vec<usize> = taken.into_iter().collect();
+            excluded.sort_unstable();
+            let standby_seed = hash_to_seed(hashv(&[STANDBY_SEED_DOMAIN, &seed.to_le_bytes()]));
+            return self
+                .alias_table
+                .sample_distinct(target, standby_seed, &excluded);
+        }
+        let mut standby = Vec::with_capacity(target);
+        let mut last = group.last().copied().unwrap_or(0);
+        if self.algorithm == SelectionAlgorithm::V1 && !self.distinct_members {
+            // a plain v1 walk only gets longer by stepping on from the group's last member,
+            // it revisits the voter it started on once it went all the way round
+            let start = (seed % voters_len as u64) as usize;
+            let dist = self.all_distance[(seed % self.all_distance.len() as u64) as usize];
+            while standby.len() < target {
+                let next = ring_shift(voters_len, last, dist as usize);
+                if next == start {
+                    break;
+                }
+                if taken.insert(next) {
+                    standby.push(next);
+                }
+                last = next;
+            }
+        } else {
+            // the longer walk starts out as the group and every step is a new voter.
+            // ring_indexes_for hands v1 every voter in canonical order once they all fit,
+            // so the distinct v1 walk is taken as is
+            let walk_len = self.group_size.saturating_add(target).min(voters_len);
+            let walk = if self.algorithm == SelectionAlgorithm::V1 {
+                let mut walk = vec![0; walk_len];
+                let mut words = vec![0; Taken::words_for(voters_len, walk_len)];
+                selection_core::ring_indexes_v1_distinct(
+                    seed,
+                    voters_len,
+                    &self.all_distance,
+                    || coprime_distances(voters_len),
+                    &mut Taken::new(&mut words),
+                    &mut walk,
+                );
+                walk
+            } else {
+                ring_indexes_for(
+                    seed,
+                    voters_len,
+                    walk_len,
+                    self.algorithm,
+                    self.distinct_members,
+                    &self.all_distance,
+                )
+            };
+            for idx in walk {
+                if standby.len() == target {
+                    break;
+                }
+                if taken.insert(idx) {
+                    standby.push(idx);
+                }
+                last = idx;
+            }
+        }
+        while standby.len() < target {
+            last = ring_shift(voters_len, last, 1);
+            if taken.insert(last) {
+                standby.push(last);
+            }
+        }
+        standby
+    }
+
+    /// the group for `seed` with the members of the groups for `previous_seeds` cooling down.
+    /// they sit this group out and every place one of them held goes to a voter drawn from
+    /// the rest, everybody else keeps their place. the window is made of the plain groups of
+    /// those seeds so the outcome only depends on seeds, a cooling member only keeps its
+    /// place once nobody else is left
+    pub fn group_with_cooldown(&self, seed: u64, previous_seeds: &[u64]) -> Vec<Pubkey> {
+        let window: Vec<Vec<usize>> = previous_seeds
+            .iter()
+            .map(|seed| self.group_indexes(*seed))
+            .collect();
+        self.cooldown_indexes(self.group_indexes(seed), seed, &window)
+            .into_iter()
+            .map(|idx| self.possible_voters[idx])
+            .collect()
+    }
+
+    /// the group for `slot` with the seeds taken from `source`, the members of the groups of
+    /// the `cooldown` slots before it cool down as in group_with_cooldown. zero is no
+    /// cooldown at all. overlapping groups cool down their shared members too, so the two
+    /// don't go together
+    pub fn group_from_source_with_cooldown<S: SeedSource + ?Sized>(
+        &self,
+        source: &S,
+        slot: Slot,
+        cooldown: usize,
+    ) -> Vec<Pubkey> {
+        let window: Vec<Vec<usize>> = (1..=cooldown as Slot)
+            .take_while(|distance| *distance <= slot)
+            .map(|distance| self.slot_group_indexes(source, slot - distance))
+            .collect();
+        self.cooldown_indexes(
+            self.slot_group_indexes(source, slot),
+            source.seed_for(slot),
+            &window,
+        )
+        .into_iter()
+        .map(|idx| self.possible_voters[idx])
+        .collect()
+    }
+
+    fn cooldown_indexes(&self, group: Vec<usize>, seed: u64, window: &[Vec<usize>]) -> Vec<usize> {
+        let cooling: HashSet<usize> = window.iter().flatten().copied().collect();
+        let cooling_members = group.iter().filter(|idx| cooling.contains(idx)).count();
+        if cooling_members == 0 {
+            return group;
+        }
+        let mut excluded: Vec<usize> = cooling.iter().chain(group.iter()).copied().collect();
+        excluded.sort_unstable();
+        excluded.dedup();
+        let cooldown_seed = hash_to_seed(hashv(&[COOLDOWN_SEED_DOMAIN, &seed.to_le_bytes()]));
+        let replacements = match self.selection_mode {
+            SelectionMode::StakeWeighted | SelectionMode::Binomial
+                if !self.alias_table.is_empty() =>
+            {
+                self.weighted_indexes_excluding(cooldown_seed, &excluded)
+            }
+            _ => {
+                let remaining: Vec<usize> = (0..self.possible_voters.len())
+                    .filter(|idx| excluded.binary_search(idx).is_err())
+                    .collect();
+                pick_distinct(remaining, cooling_members, cooldown_seed)
+            }
+        };
+        let mut replacements = replacements.into_iter();
+        group
+            .into_iter()
+            .map(|idx| {
+                if cooling.contains(&idx) {
+                    replacements.next().unwrap_or(idx)
+                } else {
+                    idx
+                }
+            })
+            .collect()
+    }
+
+    /// the group for `slot` of `epoch`. overlapping groups also mix in the seeds of the
+    /// neighbouring slots, the first and last slot use the seeds the slots just outside the
+    /// epoch would have had so there is no guaranteed overlap across an epoch boundary
+    pub fn group_for_slot(&self, slot: Slot, epoch: Epoch, anchor: &Hash) -> Vec<Pubkey> {
+        self.group_from_source(&EpochAnchoredSeed::new(epoch, *anchor), slot)
+    }
+
+    /// the group for `slot` with the seeds taken from `source`, overlapping groups ask it
+    /// for the seeds of the neighbouring slots too
+    pub fn group_from_source<S: SeedSource + ?Sized>(&self, source: &S, slot: Slot) -> Vec<Pubkey> {
+        self.slot_group_indexes(source, slot)
+            .into_iter()
+            .map(|idx| self.possible_voters[idx])
+            .collect()
+    }
+
+    /// same as in_group_for_seed with the seed of `slot` taken from `source`
+    pub fn in_group_from_source<S: SeedSource + ?Sized>(
+        &self,
+        source: &S,
+        slot: Slot,
+        test_key: Pubkey,
+    ) -> bool {
+        match self.selection_mode {
+            SelectionMode::Overlapping { .. } => {
+                self.group_from_source(source, slot).contains(&test_key)
+            }
+            _ => self.in_group_for_seed(source.seed_for(slot), test_key),
+        }
+    }
+
+    /// how many of `slots` every voter was picked for, with the seeds taken from `source`.
+    /// voters that never were aren't in it
+    pub fn assigned_slots<S: SeedSource + ?Sized>(
+        &self,
+        source: &S,
+        slots: RangeInclusive<Slot>,
+    ) -> HashMap<Pubkey, u64> {
+        let mut counts = vec![0u64; self.possible_voters.len()];
+        for slot in slots {
+            for idx in self.slot_group_indexes(source, slot) {
+                counts[idx] += 1;
+            }
+        }
+        counts
+            .into_iter()
+            .enumerate()
+            .filter(|(_, count)| *count > 0)
+            .map(|(idx, count)| (self.possible_voters[idx], count))
+            .collect()
+    }
+
+    fn slot_group_indexes<S: SeedSource + ?Sized>(&self, source: &S, slot: Slot) -> Vec<usize> {
+        let seed = source.seed_for(slot);
+        match self.selection_mode {
+            SelectionMode::Overlapping { .. } => self.consecutive_group_indexes(
+                source.seed_for(slot.wrapping_sub(1)),
+                seed,
+                source.seed_for(slot.wrapping_add(1)),
+            ),
+            _ => self.group_indexes(seed),
+        }
+    }
+
+    /// the group for `seed` given the seeds of the slots before and after it. outside the
+    /// overlapping mode the neighbours are ignored and this is group_for_seed, in it
+    /// group_for_seed alone can't see the neighbours and walks a plain uniform group
+    pub fn group_for_consecutive_seeds(
+        &self,
+        prev_seed: u64,
+        seed: u64,
+        next_seed: u64,
+    ) -> Vec<Pubkey> {
+        self.consecutive_group_indexes(prev_seed, seed, next_seed)
+            .into_iter()
+            .map(|idx| self.possible_voters[idx])
+            .collect()
+    }
+
+    /// the members shared with the previous slot, the members shared with the next slot and
+    /// the rest drawn from `seed` alone
+    fn consecutive_group_indexes(&self, prev_seed: u64, seed: u64, next_seed: u64) -> Vec<usize> {
+        let min_overlap_percent = match self.selection_mode {
+            SelectionMode::Overlapping {
+                min_overlap_percent,
+            } if !self.possible_voters.is_empty() => min_overlap_percent as usize,
+            _ => return self.group_indexes(seed),
+        };
+        let voters_len = self.possible_voters.len();
+        let target = self.effective_group_size();
+        let link_size = ((self.group_size * min_overlap_percent + 99) / 100).min(target / 2);
+        let mut indexes = self.link_indexes(prev_seed, seed, link_size);
+        for idx in self.link_indexes(seed, next_seed, link_size) {
+            if !indexes.contains(&idx) {
+                indexes.push(idx);
+            }
+        }
+        let remaining: Vec<usize> = (0..voters_len)
+            .filter(|idx| !indexes.contains(idx))
+            .collect();
+        let fill = target - indexes.len();
+        indexes.extend(pick_distinct(remaining, fill, seed));
+        indexes
+    }
+
+    /// `link_size` voters drawn from both seeds, they are in the groups of both slots
+    fn link_indexes(&self, seed: u64, next_seed: u64, link_size: usize) -> Vec<usize> {
+        let link_seed = hash_to_seed(hashv(&[
+            OVERLAP_SEED_DOMAIN,
+            &seed.to_le_bytes(),
+            &next_seed.to_le_bytes(),
+        ]));
+        pick_distinct(
+            (0..self.possible_voters.len()).collect(),
+            link_size,
+            link_seed,
+        )
+    }
+
+    pub fn try_in_group_for_hash(
+        &self,
+        hash: Hash,
+        test_key: Pubkey,
+    ) -> Result<bool, VoteGroupError> {
+        self.try_in_group_for_seed(seed_from_bytes(hash.as_ref())?, test_key)
+    }
+
+    pub fn try_group_for_seed(&self, seed: u64) -> Result<Vec<Pubkey>, VoteGroupError> {
+        if self.possible_voters.is_empty() {
+            return Err(VoteGroupError::EmptyVoterSet);
+        }
+        Ok(self.group_for_seed(seed))
+    }
+
+    pub fn try_in_group_for_seed(
+        &self,
+        seed: u64,
+        test_key: Pubkey,
+    ) -> Result<bool, VoteGroupError> {
+        if self.possible_voters.is_empty() {
+            return Err(VoteGroupError::EmptyVoterSet);
+        }
+        Ok(self.in_group_for_seed(seed, test_key))
+    }
+
+    /// VRF outputs at or below this value put their owner in the group,
+    /// on average group_size voters land under it for any seed
+    pub fn vrf_threshold(&self) -> u64 {
+        let voters = self.possible_voters.len() as u128;
+        if voters == 0 {
+            return 0;
+        }
+        let expected = self.effective_group_size() as u128;
+        ((u64::MAX as u128 + 1) * expected / voters).saturating_sub(1) as u64
+    }
+
+    /// lets a voter check its own VRF output privately
+    pub fn vrf_output_selected(&self, output: &[u8]) -> bool {
+        if output.len() < 8 {
+            return false;
+        }
+        let value = u64::from_le_bytes(output[..8].try_into().unwrap());
+        value <= self.vrf_threshold()
+    }
+
+    /// verifies a voter's sortition proof instead of walking the ring
+    #[cfg(not(target_arch = "bpf"))]
+    pub fn in_vrf_group_for_seed(&self, seed: u64, test_key: Pubkey, proof: &VrfProof) -> bool {
+        self.index_of(&test_key).is_some()
+            && proof
+                .verify(&test_key, &vrf_alpha(seed))
+                .map(|output| self.vrf_output_selected(&output))
+                .unwrap_or(false)
+    }
+
+    #[cfg(not(target_arch = "bpf"))]
+    pub fn in_vrf_group_for_hash(&self, hash: Hash, test_key: Pubkey, proof: &VrfProof) -> bool {
+        self.in_vrf_group_for_seed(hash_to_seed(hash), test_key, proof)
+    }
+
+    /// verifies a voter's revealed secret for `window` of the epoch's `windows` against the
+    /// commitment it published instead of walking the ring, the draw lands under the same
+    /// threshold as VRF outputs
+    pub fn in_committed_group_for_hash(
+        &self,
+        hash: Hash,
+        test_key: Pubkey,
+        commitment: &MembershipCommitment,
+        (window, windows): (u32, u32),
+        reveal: &MembershipReveal,
+    ) -> bool {
+        self.index_of(&test_key).is_some()
+            && reveal.window == window
+            && reveal.verify(commitment, windows)
+            && self.vrf_output_selected(reveal.output(&test_key, &hash).as_ref())
+    }
+
+    /// callers outside the generator go through try_in_group_for_seed, which refuses an
+    /// empty voter set instead of answering for it
+    fn in_group_for_seed(&self, seed: u64, test_key: Pubkey) -> bool {
+        let found = self.cached_group(seed).contains_key(&test_key);
+        // the position means walking the group again, only pay for it when it gets logged
+        if log_enabled!(target: VOTE_GROUP_LOG_TARGET, Level::Trace) {
+            let index = self.position_in_group(seed, &test_key);
+            trace!(
+                target: VOTE_GROUP_LOG_TARGET,
+                "membership check seed={} key={} found={} index={:?}",
+                seed,
+                test_key,
+                found,
+                index
+            );
+        }
+        found
+    }
+
+    /// the member of the group for `seed` that `key` is or signs the votes of, members are
+    /// tried in selection order when `key` is the authorized voter of more than one
+    pub fn group_member_for_seed(&self, seed: u64, key: &Pubkey) -> Option<Pubkey> {
+        let group = self.cached_group(seed);
+        if group.contains_key(key) {
+            return Some(*key);
+        }
+        self.authorized_voters
+            .vote_accounts(key)
+            .filter_map(|member| group.get(&member).map(|position| (*position, member)))
+            .min()
+            .map(|(_, member)| member)
+    }
+
+    /// same as in_group_for_seed but `key` may also be a member's authorized voter, the key
+    /// a vote transaction is actually signed with
+    pub fn in_group_for_seed_or_authorized_voter(&self, seed: u64, key: &Pubkey) -> bool {
+        self.group_member_for_seed(seed, key).is_some()
+    }
+
+    /// see in_group_for_hash and in_group_for_seed_or_authorized_voter
+    pub fn in_group_for_hash_or_authorized_voter(&self, hash: Hash, key: &Pubkey) -> bool {
+        self.in_group_for_seed_or_authorized_voter(hash_to_seed(hash), key)
+    }
+
+    /// the first member selected for `seed`, the one that relays the rest of the group's
+    /// votes to the next leader. None only when there are no voters
+    pub fn group_leader_for_seed(&self, seed: u64) -> Option<Pubkey> {
+        self.group_indexes(seed)
+            .first()
+            .map(|idx| self.possible_voters[*idx])
+    }
+
+    /// where `test_key` sits in the group for `seed`, in selection order. groups that repeat
+    /// a member give its first position, None for keys outside the group
+    pub fn position_in_group(&self, seed: u64, test_key: &Pubkey) -> Option<usize> {
+        self.cached_group(seed).get(test_key).copied()
+    }
+
+    /// the member votes a group of effective_group_size distinct members needs for a
+    /// quorum, the smallest count above GROUP_QUORUM_NUMERATOR / GROUP_QUORUM_DENOMINATOR
+    pub fn quorum(&self) -> usize {
+        Self::count_quorum(self.effective_group_size())
+    }
+
+    fn count_quorum(members: usize) -> usize {
+        Self::count_quorum_with(members, QuorumFraction::default())
+    }
+
+    fn count_quorum_with(members: usize, fraction: QuorumFraction) -> usize {
+        if members == 0 {
+            return 0;
+        }
+        (members as u128 * fraction.numerator as u128 / fraction.denominator as u128) as usize + 1
+    }
+
+    /// whether `votes` hold a quorum of the group for `seed`. votes from outside the group
+    /// and repeated votes don't count, stake weighted groups are measured by member stake
+    pub fn has_quorum(&self, votes: &[Pubkey], seed: u64) -> bool {
+        self.has_quorum_with_fraction(votes, seed, QuorumFraction::default())
+    }
+
+    /// same as has_quorum for a quorum of more than `fraction` of the group
+    pub fn has_quorum_with_fraction(
+        &self,
+        votes: &[Pubkey],
+        seed: u64,
+        fraction: QuorumFraction,
+    ) -> bool {
+        let mut members = self.group_indexes(seed);
+        members.sort_unstable();
+        members.dedup();
+        if members.is_empty() {
+            return false;
+        }
+        let voted: HashSet<Pubkey> = votes.iter().copied().collect();
+        let (voted_members, other_members): (Vec<usize>, Vec<usize>) = members
+            .into_iter()
+            .partition(|idx| voted.contains(&self.possible_voters[*idx]));
+        match self.selection_mode {
+            SelectionMode::StakeWeighted | SelectionMode::Binomial
+                if !self.alias_table.is_empty() =>
+            {
+                let stake_of = |indexes: &[usize]| -> u128 {
+                    indexes
+                        .iter()
+                        .map(|idx| self.alias_table.weights()[*idx] as u128)
+                        .sum()
+                };
+                let voted_stake = stake_of(&voted_members);
+                let group_stake = voted_stake + stake_of(&other_members);
+                voted_stake * fraction.denominator as u128
+                    > group_stake * fraction.numerator as u128
+            }
+            _ => {
+                voted_members.len()
+                    >= Self::count_quorum_with(voted_members.len() + other_members.len(), fraction)
+            }
+        }
+    }
+
+    /// membership of every key in `keys` for one seed, the group is materialized only once
+    pub fn in_group_for_seed_batch(&self, seed: u64, keys: &[Pubkey]) -> Vec<bool> {
+        let group = self.cached_group(seed);
+        keys.iter().map(|key| group.contains_key(key)).collect()
+    }
+
+    /// same as in_group_for_seed_batch but checks the keys on the rayon pool,
+    /// worth it for the large vote batches seen in banking and replay
+    #[cfg(not(target_arch = "bpf"))]
+    pub fn par_in_group_for_seed_batch(&self, seed: u64, keys: &[Pubkey]) -> Vec<bool> {
+        let group = self.cached_group(seed);
+        keys.par_iter().map(|key| group.contains_key(key)).collect()
+    }
+
+    /// hits and misses of the group cache since the last call, for metrics
+    pub fn take_cache_stats(&self) -> GroupCacheStats {
+        self.group_cache.take_stats()
+    }
+
+    /// same as take_cache_stats without resetting them
+    pub fn cache_stats(&self) -> GroupCacheStats {
+        self.group_cache.stats()
+    }
+
+    /// the groups in the cache with their members in selection order, a member picked more
+    /// than once is listed once. least recently used seed first
+    pub fn cached_groups(&self) -> Vec<(u64, Vec<Pubkey>)> {
+        self.group_cache
+            .groups()
+            .into_iter()
+            .map(|(seed, positions)| {
+                let mut members: Vec<_> = positions.iter().collect();
+                members.sort_unstable_by_key(|(_, position)| **position);
+                (seed, members.into_iter().map(|(key, _)| *key).collect())
+            })
+            .collect()
+    }
+
+    /// the members of the group for `seed` and their first positions, walked once and then
+    /// served from the cache
+    fn cached_group(&self, seed: u64) -> Arc<GroupPositions> {
+        self.group_cache.get_or_insert_with(seed, || {
+            let indexes = self.group_indexes(seed);
+            let mut positions = GroupPositions::with_capacity(indexes.len());
+            for (position, idx) in indexes.into_iter().enumerate() {
+                positions
+                    .entry(self.possible_voters[idx])
+                    .or_insert(position);
+            }
+            positions
+        })
+    }
+}
+
+/// the three fields every generator had before the selection settings were added, epoch
+/// generators in snapshots older than 1.3.0 are laid out like this
+#[derive(Clone, Debug, Serialize, Deserialize, AbiExample, PartialEq)]
+pub struct LegacyVoteGroupGenerator {
+    #[serde(with = "base58_keys")]
+    pub possible_voters: Vec<Pubkey>,
+    pub all_distance: Vec<u32>,
+    pub group_size: usize,
+}
+
+impl From<LegacyVoteGroupGenerator> for VoteGroupGenerator {
+    /// old generators were only ever v1 and uniform. the voters are put back in canonical
+    /// order since the oldest ones kept whatever order the HashMap iterated in
+    fn from(legacy: LegacyVoteGroupGenerator) -> Self {
+        let mut possible_voters = legacy.possible_voters;
+        possible_voters.sort();
+        possible_voters.dedup();
+        let all_distance = Self::distances_for(possible_voters.len(), SelectionAlgorithm::V1);
+        Self {
+            possible_voters: possible_voters.into(),
+            all_distance,
+            group_size: legacy.group_size,
+            ordering_version: CANONICAL_ORDERING_VERSION,
+            selection_mode: SelectionMode::Uniform,
+            alias_table: AliasTable::default(),
+            algorithm: SelectionAlgorithm::V1,
+            distinct_members: false,
+            group_cache: GroupCache::default(),
+            voter_set_root: VoterSetRoot::default(),
+            authorized_voters: AuthorizedVoters::default(),
+        }
+    }
+}
+
+impl TryFrom<&VoteGroupGenerator> for LegacyVoteGroupGenerator {
+    type Error = VoteGroupError;
+
+    /// the old layout has no room for the selection settings, whoever reads it back gets a
+    /// v1 uniform generator over canonically ordered voters. any other generator is refused
+    fn try_from(generator: &VoteGroupGenerator) -> Result<Self, Self::Error> {
+        let refuse = |what: String| Err(VoteGroupError::LegacyLayout(what));
+        if generator.selection_mode != SelectionMode::Uniform {
+            return refuse(format!("the {:?} selection mode", generator.selection_mode));
+        }
+        if generator.algorithm != SelectionAlgorithm::V1 {
+            return refuse(format!("the {:?} selection algorithm", generator.algorithm));
+        }
+        if generator.distinct_members {
+            return refuse("distinct members".to_string());
+        }
+        if generator.ordering_version != CANONICAL_ORDERING_VERSION {
+            return refuse(format!("ordering version {}", generator.ordering_version));
+        }
+        let possible_voters = generator.possible_voters.to_vec();
+        let all_distance =
+            VoteGroupGenerator::distances_for(possible_voters.len(), SelectionAlgorithm::V1);
+        Ok(Self {
+            possible_voters,
+            all_distance,
+            group_size: generator.group_size,
+        })
+    }
+}
+
+/// 1 and every prime below `len` that doesn't divide it, so any of them steps through the
+/// whole ring before coming back to where it started
+pub fn coprime_distances(len: usize) -> Vec<u32> {
+    let mut distances = vec![1];
+    if len < 3 {
+        return distances;
+    }
+    // sieve of eratosthenes over [0, len)
+    let mut composite = vec![false; len];
+    let mut p = 2;
+    while p * p < len {
+        if !composite[p] {
+            let mut multiple = p * p;
+            while multiple < len {
+                composite[multiple] = true;
+                multiple += p;
+            }
+        }
+        p += 1;
+    }
+    distances.extend(
+        (2..len)
+            .filter(|p| !composite[*p] && len % *p != 0)
+            .map(|p| p as u32),
+    );
+    distances
+}
+
+/// the entry of `distances` the walk for `seed` starts with, None for v3 which doesn't walk
+pub(crate) fn distance_for(
+    seed: u64,
+    algorithm: SelectionAlgorithm,
+    distances: &[u32],
+) -> Option<u32> {
+    if distances.is_empty() {
+        return None;
+    }
+    let choose_dist = match algorithm {
+        SelectionAlgorithm::V1 => seed % distances.len() as u64,
+        SelectionAlgorithm::V2 => {
+            let mut state = seed;
+            (splitmix64(&mut state) >> 32) % distances.len() as u64
+        }
+        SelectionAlgorithm::V3 => return None,
+    };
+    Some(distances[choose_dist as usize])
+}
+
+/// the indexes a uniform group of `voters_len` voters is made of for `seed`, in selection
+/// order. it needs the size of the voter set and not the voters themselves, so a light
+/// client holding only a voter set commitment walks the same groups the validators do
+pub(crate) fn ring_indexes_for(
+    seed: u64,
+    voters_len: usize,
+    group_size: usize,
+    algorithm: SelectionAlgorithm,
+    distinct_members: bool,
+    distances: &[u32],
+) -> Vec<usize> {
+    let effective_group_size = group_size.min(voters_len);
+    // only the walks that never repeat a voter need the bitset
+    let words = || vec![0; Taken::words_for(voters_len, effective_group_size)];
+    match algorithm {
+        SelectionAlgorithm::V1 => {
+            // a group that can hold every voter is every voter, walking on would only revisit
+            if group_size >= voters_len {
+                return (0..voters_len).collect();
+            }
+            let mut indexes;
+            if distinct_members {
+                // see selection_core::ring_indexes_v1_distinct, groups v1 already built
+                // without a revisit come out exactly the same
+                indexes = vec![0; effective_group_size];
+                selection_core::ring_indexes_v1_distinct(
+                    seed,
+                    voters_len,
+                    distances,
+                    || coprime_distances(voters_len),
+                    &mut Taken::new(&mut words()),
+                    &mut indexes,
+                );
+            } else {
+                // v1 always took the voter the walk starts on, even for an empty group
+                indexes = vec![0; group_size.max(1)];
+                selection_core::ring_indexes_v1(seed, voters_len, distances, &mut indexes);
+            }
+            indexes
+        }
+        SelectionAlgorithm::V2 => {
+            let mut indexes = vec![0; effective_group_size];
+            selection_core::ring_indexes_v2(
+                seed,
+                voters_len,
+                distances,
+                &mut Taken::new(&mut words()),
+                &mut indexes,
+            );
+            indexes
+        }
+        SelectionAlgorithm::V3 => {
+            let mut indexes = vec![0; effective_group_size];
+            if effective_group_size > MAX_SCANNED_WALK {
+                chacha_indexes_sparse(seed, voters_len, &mut indexes);
+            } else {
+                selection_core::chacha_indexes(seed, voters_len, &mut indexes);
+            }
+            indexes
+        }
+    }
+}
+
+/// selection_core::chacha_indexes for long walks, the same shuffle run forwards with the
+/// positions it swapped kept in a map instead of traced back through every earlier swap
+fn chacha_indexes_sparse(seed: u64, voters_len: usize, out: &mut [usize]) -> usize {
+    let target = out.len().min(voters_len);
+    let key = hashv(&[CHACHA_SEED_DOMAIN, &seed.to_le_bytes()]);
+    let mut rng = ChaCha20Rng::from_seed(key.to_bytes());
+    // position to the voter it holds, positions that were never swapped hold their own
+    let mut swapped: HashMap<usize, usize> = HashMap::with_capacity(target * 2);
+    for (step, slot) in out[..target].iter_mut().enumerate() {
+        let pick = step + uniform_below(&mut rng, (voters_len - step) as u64) as usize;
+        let own = swapped.get(&step).copied().unwrap_or(step);
+        *slot = swapped.get(&pick).copied().unwrap_or(pick);
+        swapped.insert(pick, own);
+    }
+    target
+}
+
+/// the seed every consumer must use for the group of `slot`, see EpochAnchoredSeed
+/// the bank hash is the one that closed the epoch before `epoch`, so it is fixed long before the slot
+pub fn seed_for_slot(slot: Slot, epoch: Epoch, last_epoch_bank_hash: &Hash) -> u64 {
+    EpochAnchoredSeed::new(epoch, *last_epoch_bank_hash).seed_for(slot)
+}
+
+/// same as seed_for_slot with the epoch looked up in the schedule
+pub fn seed_for_slot_in_schedule(
+    epoch_schedule: &EpochSchedule,
+    slot: Slot,
+    last_epoch_bank_hash: &Hash,
+) -> u64 {
+    seed_for_slot(slot, epoch_schedule.get_epoch(slot), last_epoch_bank_hash)
+}
+
+/// the message a voter proves over with its VRF key for a seed
+pub fn vrf_alpha(seed: u64) -> [u8; 8] {
+    seed.to_le_bytes()
+}
+
+/// `count` of `items` picked by a partial shuffle driven by `seed`, in pick order
+fn pick_distinct(mut items: Vec<usize>, count: usize, seed: u64) -> Vec<usize> {
+    let count = count.min(items.len());
+    let mut state = seed;
+    for i in 0..count {
+        let pick = i + (splitmix64(&mut state) % (items.len() - i) as u64) as usize;
+        items.swap(i, pick);
+    }
+    items.truncate(count);
+    items
+}
+
+/// folds a hash down to a u64 seed
+fn hash_to_seed(hash_val: Hash) -> u64 {
+    seed_from_bytes(hash_val.as_ref()).expect("hashes are a multiple of 8 bytes")
+}
+
+/// hashes seed material of any length, slot bytes, bank hashes or a mix of them, down to a
+/// u64 seed
+pub fn seed_for_material(material: &[u8]) -> u64 {
+    hash_to_seed(hashv(&[MATERIAL_SEED_DOMAIN, material]))
+}
+
+/// folds seed material down to a u64 seed by xor-ing its 8 byte words together
+pub fn seed_from_bytes(material: &[u8]) -> Result<u64, VoteGroupError> {
+    if material.is_empty() || (material.len() % 8) != 0 {
+        return Err(VoteGroupError::BadSeedMaterial(material.len()));
+    }
+    Ok(material.chunks_exact(8).fold(0, |val, hunk| {
+        let word: [u8; 8] = hunk.try_into().expect("slice with incorrect length");
+        val ^ u64::from_le_bytes(word)
+    }))
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use std::str::FromStr;
+    #[test]
+    fn test_vgg_multi() {
+        let canary = Pubkey::new_unique();
+        let mut hm: HashMap<Pubkey, Pubkey> = HashMap::new();
+        hm.insert(canary, Pubkey::new_unique());
+
+        for it in 0..4 {
+            let val = Pubkey::new_unique();
+            hm.insert(val, Pubkey::new_unique());
+            println!("insert {}", it);
+        }
+        let vgg = VoteGroupGenerator::new(&hm, hm.len());
+        for h in hm.keys() {
+            let found = vgg.in_group_for_seed(0, *h);
+            assert!(found);
+        }
+
+        let not_canary = Pubkey::new_unique();
+        assert_eq!(vgg.in_group_for_seed(0, not_canary), false);
+    }
+
+    #[test]
+    fn test_vgg_single() {
+        let canary = Pubkey::new_unique();
+        let mut hm: HashMap<Pubkey, Pubkey> = HashMap::new();
+        hm.insert(canary, Pubkey::new_unique());
+
+        let vgg = VoteGroupGenerator::new(&hm, hm.len());
+        for h in hm.keys() {
+            let found = vgg.in_group_for_seed(0, *h);
+            assert!(found);
+        }
+
+        let not_canary = Pubkey::new_unique();
+        assert_eq!(vgg.in_group_for_seed(0, not_canary), false);
+    }
+
+    #[test]
+    fn test_vgg_magic() {
+        let magic = Pubkey::from_str(SAFECOIN_NEVER_VOTER).unwrap();
+        let mut hm: HashMap<Pubkey, Pubkey> = HashMap::new();
+        hm.insert(magic, Pubkey::new_unique());
+
+        for it in 0..4 {
+            let val = Pubkey::new_unique();
+            hm.insert(val, Pubkey::new_unique());
+            println!("insert {}", it);
+        }
+        let vgg = VoteGroupGenerator::new(&hm, hm.len());
+        for h in hm.keys() {
+            let found = vgg.in_group_for_seed(0, *h);
+            let result = h.to_string() != SAFECOIN_NEVER_VOTER;
+            assert_eq!(found, result);
+        }
+        assert_eq!(vgg.in_group_for_seed(0, magic), false);
+    }
+
+    #[test]
+    fn test_vgg_tiny_clusters() {
+        for voters in 1..=3usize {
+            let hm: HashMap<Pubkey, Pubkey> = (0..voters)
+                .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+                .collect();
+            let stakes: HashMap<Pubkey, u64> = hm.keys().map(|key| (*key, 10)).collect();
+            for size in 1..=5 {
+                let mut generators = vec![VoteGroupGenerator::new_stake_weighted(
+                    &hm,
+                    &stakes,
+                    size,
+                    &ExclusionList::default(),
+                )];
+                for algorithm in &[
+                    SelectionAlgorithm::V1,
+                    SelectionAlgorithm::V2,
+                    SelectionAlgorithm::V3,
+                ] {
+                    for distinct in &[false, true] {
+                        generators.push(
+                            VoteGroupGenerator::new(&hm, size)
+                                .with_algorithm(*algorithm)
+                                .with_distinct_members(*distinct),
+                        );
+                    }
+                }
+                for vgg in generators {
+                    assert_eq!(vgg.group_size(), size);
+                    assert_eq!(vgg.effective_group_size(), size.min(voters));
+                    for seed in 0..50 {
+                        let group = vgg.group_for_seed(seed);
+                        assert_eq!(group.len(), vgg.effective_group_size());
+                        let members: HashSet<Pubkey> = group.iter().copied().collect();
+                        assert_eq!(members.len(), group.len());
+                        if size >= voters {
+                            // a group too big for the cluster is the whole cluster
+                            assert!(hm.keys().all(|key| vgg.in_group_for_seed(seed, *key)));
+                        }
+                    }
+                }
+            }
+        }
+        assert_eq!(VoteGroupGenerator::new_dummy().effective_group_size(), 0);
+    }
+
+    #[test]
+    fn test_vgg_group_for_seed() {
+        let mut hm: HashMap<Pubkey, Pubkey> = HashMap::new();
+        for _ in 0..20 {
+            hm.insert(Pubkey::new_unique(), Pubkey::new_unique());
+        }
+        let vgg = VoteGroupGenerator::new(&hm, 5);
+        for seed in 0..50 {
+            let group = vgg.group_for_seed(seed);
+            assert_eq!(group.len(), 5);
+            for key in hm.keys() {
+                assert_eq!(group.contains(key), vgg.in_group_for_seed(seed, *key));
+            }
+        }
+
+        let hash = Hash::new_unique();
+        assert_eq!(vgg.group_for_hash(hash), vgg.group_for_seed(hash_to_seed(hash)));
+    }
+
+    #[test]
+    fn test_vgg_position_in_group() {
+        let hm: HashMap<Pubkey, Pubkey> = (0..20)
+            .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+            .collect();
+        let outsider = Pubkey::new_unique();
+        for algorithm in &[
+            SelectionAlgorithm::V1,
+            SelectionAlgorithm::V2,
+            SelectionAlgorithm::V3,
+        ] {
+            let vgg = VoteGroupGenerator::new(&hm, 5).with_algorithm(*algorithm);
+            for seed in 0..20 {
+                let group = vgg.group_for_seed(seed);
+                for (position, key) in group.iter().enumerate() {
+                    let found = vgg.position_in_group(seed, key).unwrap();
+                    assert_eq!(group[found], *key);
+                    assert!(found <= position);
+                }
+                for key in hm.keys().filter(|key| !group.contains(key)) {
+                    assert_eq!(vgg.position_in_group(seed, key), None);
+                }
+                assert_eq!(vgg.position_in_group(seed, &outsider), None);
+            }
+        }
+        assert_eq!(
+            VoteGroupGenerator::new_dummy().position_in_group(0, &outsider),
+            None
+        );
+    }
+
+    #[test]
+    fn test_vgg_group_leader_for_seed() {
+        let keys: Vec<Pubkey> = (0..20).map(|_| Pubkey::new_unique()).collect();
+        let hm: HashMap<Pubkey, Pubkey> = keys.iter().map(|key| (*key, *key)).collect();
+        let mut reversed = keys.clone();
+        reversed.reverse();
+        let stakes: HashMap<Pubkey, u64> = keys.iter().map(|key| (*key, 10)).collect();
+        for vgg in &[
+            VoteGroupGenerator::new(&hm, 5),
+            VoteGroupGenerator::new(&hm, 5).with_algorithm(SelectionAlgorithm::V3),
+            VoteGroupGenerator::new_stake_weighted(&hm, &stakes, 5, &ExclusionList::default()),
+        ] {
+            // every node builds its generator on its own, from its own HashMap, or loads it
+            // from a snapshot, and has to come up with the same leader
+            let rebuilt = VoteGroupGeneratorBuilder::new()
+                .with_voters(reversed.iter().copied())
+                .with_group_size(5)
+                .with_exclusions(ExclusionList::default())
+                .with_algorithm(vgg.algorithm())
+                .build()
+                .unwrap();
+            let decoded: VoteGroupGenerator =
+                bincode::deserialize(&bincode::serialize(vgg).unwrap()).unwrap();
+            for seed in 0..50 {
+                let leader = vgg.group_leader_for_seed(seed).unwrap();
+                assert_eq!(Some(leader), vgg.group_for_seed(seed).first().copied());
+                assert_eq!(vgg.position_in_group(seed, &leader), Some(0));
+                assert_eq!(decoded.group_leader_for_seed(seed), Some(leader));
+                if vgg.selection_mode() == SelectionMode::Uniform {
+                    assert_eq!(rebuilt.group_leader_for_seed(seed), Some(leader));
+                }
+            }
+        }
+        assert_eq!(VoteGroupGenerator::new_dummy().group_leader_for_seed(0), None);
+    }
+
+    #[test]
+    fn test_vgg_canonical_order() {
+        let keys: Vec<Pubkey> = (0..20).map(|_| Pubkey::new_unique()).collect();
+        let forward: HashMap<Pubkey, Pubkey> =
+            keys.iter().map(|k| (*k, Pubkey::new_unique())).collect();
+        let mut backward: HashMap<Pubkey, Pubkey> = HashMap::with_capacity(1);
+        for k in keys.iter().rev() {
+            backward.insert(*k, Pubkey::new_unique());
+        }
+
+        let vgg_a = VoteGroupGenerator::new(&forward, 5);
+        let vgg_b = VoteGroupGenerator::new(&backward, 5);
+        assert_eq!(vgg_a, vgg_b);
+        assert_eq!(vgg_a.ordering_version(), CANONICAL_ORDERING_VERSION);
+        for seed in 0..50 {
+            assert_eq!(vgg_a.group_for_seed(seed), vgg_b.group_for_seed(seed));
+        }
+    }
+
+    #[test]
+    fn test_vgg_exclusions() {
+        let excluded_a = Pubkey::new_unique();
+        let excluded_b = Pubkey::new_unique();
+        let mut hm: HashMap<Pubkey, Pubkey> = HashMap::new();
+        hm.insert(excluded_a, Pubkey::new_unique());
+        hm.insert(excluded_b, Pubkey::new_unique());
+        for _ in 0..4 {
+            hm.insert(Pubkey::new_unique(), Pubkey::new_unique());
+        }
+        let exclusions = ExclusionList::new(vec![excluded_a, excluded_b]);
+        let vgg = VoteGroupGenerator::new_with_exclusions(&hm, 4, &exclusions);
+        for seed in 0..20 {
+            let group = vgg.group_for_seed(seed);
+            assert!(!group.contains(&excluded_a));
+            assert!(!group.contains(&excluded_b));
+        }
+    }
+
+    #[test]
+    fn test_vgg_identity_dedup() {
+        let operator = Pubkey::new_unique();
+        let mut hm: HashMap<Pubkey, Pubkey> = HashMap::new();
+        let mut stakes: HashMap<Pubkey, u64> = HashMap::new();
+        for stake in 1..=5 {
+            let vote_account = Pubkey::new_unique();
+            hm.insert(vote_account, operator);
+            stakes.insert(vote_account, stake);
+        }
+        let best = *stakes.iter().max_by_key(|(_, stake)| **stake).unwrap().0;
+        let solo = Pubkey::new_unique();
+        hm.insert(solo, Pubkey::new_unique());
+        let vgg = VoteGroupGenerator::new_with_identity_dedup(
+            &hm,
+            &hm,
+            &stakes,
+            2,
+            &ExclusionList::default(),
+        );
+        let mut expected = vec![best, solo];
+        expected.sort();
+        assert_eq!(vgg.voters(), &expected[..]);
+    }
+
+    #[test]
+    fn test_vgg_authorized_voters() {
+        let mut hm: HashMap<Pubkey, Pubkey> = HashMap::new();
+        for _ in 0..10 {
+            hm.insert(Pubkey::new_unique(), Pubkey::new_unique());
+        }
+        // one key authorized for two vote accounts
+        let shared_voter = Pubkey::new_unique();
+        let shared: Vec<Pubkey> = (0..2).map(|_| Pubkey::new_unique()).collect();
+        for vote_account in &shared {
+            hm.insert(*vote_account, shared_voter);
+        }
+        let excluded = Pubkey::new_unique();
+        hm.insert(excluded, Pubkey::new_unique());
+        let mut vgg =
+            VoteGroupGenerator::new_with_exclusions(&hm, 4, &ExclusionList::new(vec![excluded]));
+        assert_eq!(vgg.authorized_voter_for(&excluded), None);
+        for (vote_account, voter) in hm.iter().filter(|(key, _)| **key != excluded) {
+            assert_eq!(vgg.authorized_voter_for(vote_account), Some(*voter));
+        }
+
+        for seed in 0..30 {
+            let group = vgg.group_for_seed(seed);
+            for (vote_account, voter) in hm.iter().filter(|(key, _)| **key != excluded) {
+                let expected = group.contains(vote_account)
+                    || (*voter == shared_voter && shared.iter().any(|key| group.contains(key)));
+                assert_eq!(
+                    vgg.in_group_for_seed_or_authorized_voter(seed, voter),
+                    expected
+                );
+                assert_eq!(
+                    vgg.in_group_for_seed_or_authorized_voter(seed, vote_account),
+                    group.contains(vote_account)
+                );
+            }
+            if let Some(member) = group
+                .iter()
+                .find(|member| hm.get(member) == Some(&shared_voter))
+            {
+                assert_eq!(
+                    vgg.group_member_for_seed(seed, &shared_voter),
+                    Some(*member)
+                );
+            }
+        }
+
+        // not serialized, put back by whoever reads the generator
+        let mut read_back: VoteGroupGenerator =
+            bincode::deserialize(&bincode::serialize(&vgg).unwrap()).unwrap();
+        let (vote_account, voter) = hm.iter().find(|(key, _)| **key != excluded).unwrap();
+        assert_eq!(read_back.authorized_voter_for(vote_account), None);
+        read_back.set_authorized_voters(&hm);
+        assert_eq!(read_back.authorized_voter_for(vote_account), Some(*voter));
+        assert_eq!(read_back.authorized_voter_for(&excluded), None);
+
+        assert!(vgg.remove_voter(vote_account));
+        assert_eq!(vgg.authorized_voter_for(vote_account), None);
+    }
+
+    #[test]
+    fn test_vgg_try_new() {
+        let mut hm: HashMap<Pubkey, Pubkey> = HashMap::new();
+        assert_eq!(
+            VoteGroupGenerator::try_new(&hm, 1),
+            Err(VoteGroupError::EmptyVoterSet)
+        );
+        for _ in 0..3 {
+            hm.insert(Pubkey::new_unique(), Pubkey::new_unique());
+        }
+        assert_eq!(
+            VoteGroupGenerator::try_new(&hm, 0),
+            Err(VoteGroupError::ZeroGroupSize)
+        );
+        assert_eq!(
+            VoteGroupGenerator::try_new(&hm, 4),
+            Err(VoteGroupError::GroupSizeTooLarge {
+                group_size: 4,
+                voters: 3
+            })
+        );
+        assert!(VoteGroupGenerator::try_new(&hm, 3).is_ok());
+    }
+
+    #[test]
+    fn test_vgg_empty_voter_set() {
+        let vgg = VoteGroupGenerator::new_dummy();
+        let key = Pubkey::new_unique();
+        assert_eq!(
+            vgg.try_in_group_for_seed(7, key),
+            Err(VoteGroupError::EmptyVoterSet)
+        );
+        assert_eq!(
+            vgg.try_in_group_for_hash(Hash::new_unique(), key),
+            Err(VoteGroupError::EmptyVoterSet)
+        );
+        assert_eq!(
+            vgg.try_group_for_seed(7),
+            Err(VoteGroupError::EmptyVoterSet)
+        );
+    }
+
+    #[test]
+    fn test_seed_from_bytes() {
+        assert_eq!(seed_from_bytes(&[]), Err(VoteGroupError::BadSeedMaterial(0)));
+        assert_eq!(
+            seed_from_bytes(&[1, 2, 3]),
+            Err(VoteGroupError::BadSeedMaterial(3))
+        );
+        let mut material = [0u8; 16];
+        material[0] = 3;
+        material[8] = 5;
+        assert_eq!(seed_from_bytes(&material), Ok(6));
+    }
+
+    #[test]
+    fn test_vgg_group_for_bytes() {
+        let hm: HashMap<Pubkey, Pubkey> = (0..20)
+            .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+            .collect();
+        let vgg = VoteGroupGenerator::new(&hm, 5);
+        let hash = Hash::new_unique();
+        let slot: Slot = 42;
+        let composite = [&slot.to_le_bytes()[..], hash.as_ref()].concat();
+        // lengths seed_from_bytes rejects hash just as well
+        let materials: Vec<&[u8]> = vec![&[], &[1, 2, 3], &composite[..33], &composite];
+        for material in materials {
+            let group = vgg.group_for_bytes(material);
+            assert_eq!(group.len(), 5);
+            assert_eq!(group, vgg.group_for_seed(seed_for_material(material)));
+            for key in hm.keys() {
+                assert_eq!(group.contains(key), vgg.in_group_for_bytes(material, *key));
+            }
+        }
+        assert_eq!(
+            vgg.group_for_bytes(hash),
+            vgg.group_for_bytes(hash.to_bytes())
+        );
+        assert_eq!(
+            vgg.group_for_bytes(slot.to_le_bytes()),
+            vgg.group_for_seed(seed_for_material(&slot.to_le_bytes()))
+        );
+        assert_ne!(seed_for_material(&[]), seed_for_material(&[0]));
+        assert_ne!(seed_for_material(hash.as_ref()), hash_to_seed(hash));
+    }
+
+    #[test]
+    fn test_vgg_stake_weighted() {
+        let whale = Pubkey::new_unique();
+        let unstaked = Pubkey::new_unique();
+        let mut hm: HashMap<Pubkey, Pubkey> = HashMap::new();
+        let mut stakes: HashMap<Pubkey, u64> = HashMap::new();
+        hm.insert(whale, Pubkey::new_unique());
+        stakes.insert(whale, 1_000_000);
+        hm.insert(unstaked, Pubkey::new_unique());
+        for _ in 0..20 {
+            let key = Pubkey::new_unique();
+            hm.insert(key, Pubkey::new_unique());
+            stakes.insert(key, 1_000);
+        }
+        let vgg = VoteGroupGenerator::new_stake_weighted(&hm, &stakes, 3, &ExclusionList::default());
+        assert_eq!(vgg.selection_mode(), SelectionMode::StakeWeighted);
+        let mut whale_hits = 0;
+        for seed in 0..100 {
+            let group = vgg.group_for_seed(seed);
+            assert_eq!(group.len(), 3);
+            assert!(!group.contains(&unstaked));
+            let mut dedup = group.clone();
+            dedup.sort();
+            dedup.dedup();
+            assert_eq!(dedup.len(), 3);
+            if group.contains(&whale) {
+                whale_hits += 1;
+            }
+        }
+        assert!(whale_hits > 90);
+        assert_eq!(
+            vgg.group_for_seed(42),
+            VoteGroupGenerator::new_stake_weighted(&hm, &stakes, 3, &ExclusionList::default())
+                .group_for_seed(42)
+        );
+    }
+
+    #[test]
+    fn test_vgg_binomial() {
+        let whale = Pubkey::new_unique();
+        let unstaked = Pubkey::new_unique();
+        let mut hm: HashMap<Pubkey, Pubkey> = HashMap::new();
+        let mut stakes: HashMap<Pubkey, u64> = HashMap::new();
+        hm.insert(whale, Pubkey::new_unique());
+        stakes.insert(whale, 100_000);
+        hm.insert(unstaked, Pubkey::new_unique());
+        for _ in 0..20 {
+            let key = Pubkey::new_unique();
+            hm.insert(key, Pubkey::new_unique());
+            stakes.insert(key, 10_000);
+        }
+        let vgg = VoteGroupGenerator::new_binomial(&hm, &stakes, 3, &ExclusionList::default());
+        assert_eq!(vgg.selection_mode(), SelectionMode::Binomial);
+        assert!(vgg.check_state().is_ok());
+        assert_eq!(vgg.distance_for_seed(7), None);
+
+        // the whale holds a third of the stake and is always in, the rest share 2 places
+        assert_eq!(vgg.selection_probability(&whale), Some(1.));
+        assert_eq!(vgg.selection_probability(&unstaked), Some(0.));
+        let staked = stakes.keys().find(|key| **key != whale).unwrap();
+        assert!((vgg.selection_probability(staked).unwrap() - 0.1).abs() < 1e-9);
+        assert_eq!(vgg.selection_probability(&Pubkey::new_unique()), None);
+        assert!((vgg.expected_group_size() - 3.).abs() < 1e-9);
+        assert!((vgg.group_size_variance() - 20. * 0.1 * 0.9).abs() < 1e-9);
+
+        let seeds = 0..4_000;
+        for seed in seeds.clone() {
+            let group = vgg.group_for_seed(seed);
+            assert!(group.contains(&whale));
+            assert!(!group.contains(&unstaked));
+            let mut dedup = group.clone();
+            dedup.sort();
+            dedup.dedup();
+            assert_eq!(dedup.len(), group.len());
+            for key in vgg.voters() {
+                assert_eq!(vgg.in_group_for_seed(seed, *key), group.contains(key));
+            }
+        }
+        let sizes = stats::GroupSizes::new(&vgg, seeds);
+        assert!(sizes.min < 3 && sizes.max > 3);
+        assert!((sizes.mean - vgg.expected_group_size()).abs() < 0.1);
+        assert!((sizes.variance - vgg.group_size_variance()).abs() < 0.3);
+        assert_eq!(
+            vgg.group_for_seed(42),
+            VoteGroupGenerator::new_binomial(&hm, &stakes, 3, &ExclusionList::default())
+                .group_for_seed(42)
+        );
+
+        // fixed size groups don't spread
+        let fixed =
+            VoteGroupGenerator::new_stake_weighted(&hm, &stakes, 3, &ExclusionList::default());
+        assert!((fixed.expected_group_size() - 3.).abs() < f64::EPSILON);
+        assert!(fixed.group_size_variance().abs() < f64::EPSILON);
+        assert_eq!(fixed.selection_probability(&whale), None);
+
+        // the weights follow the voters
+        let mut vgg = vgg;
+        assert!(vgg.remove_voter(&whale));
+        assert!((vgg.expected_group_size() - 3.).abs() < 1e-9);
+        assert!(vgg.add_voter(whale, 0));
+        assert!(!vgg.group_for_seed(0).contains(&whale));
+        assert_eq!(vgg.selection_probability(&whale), Some(0.));
+    }
+
+    #[test]
+    fn test_vgg_vrf_sortition() {
+        use crate::vrf::VrfKeypair;
+
+        let keypairs: Vec<VrfKeypair> = (0..20u8)
+            .map(|i| VrfKeypair::from_secret(&[i; 32]))
+            .collect();
+        let hm: HashMap<Pubkey, Pubkey> = keypairs
+            .iter()
+            .map(|keypair| (keypair.pubkey(), Pubkey::new_unique()))
+            .collect();
+        let vgg = VoteGroupGenerator::new(&hm, 5);
+        let outsider = VrfKeypair::from_secret(&[99u8; 32]);
+
+        let mut selected = 0;
+        for seed in 0..20 {
+            for keypair in &keypairs {
+                let (proof, output) = keypair.prove(&vrf_alpha(seed));
+                let expected = vgg.vrf_output_selected(&output);
+                assert_eq!(
+                    vgg.in_vrf_group_for_seed(seed, keypair.pubkey(), &proof),
+                    expected
+                );
+                if expected {
+                    selected += 1;
+                    // a proof for one seed doesn't carry over to another
+                    assert!(!vgg.in_vrf_group_for_seed(seed + 1000, keypair.pubkey(), &proof));
+                }
+            }
+            let (proof, output) = outsider.prove(&vrf_alpha(seed));
+            if vgg.vrf_output_selected(&output) {
+                assert!(!vgg.in_vrf_group_for_seed(seed, outsider.pubkey(), &proof));
+            }
+        }
+        // 5 of 20 expected per seed, 100 expected over 20 seeds
+        assert!(selected > 40 && selected < 160);
+    }
+
+    #[test]
+    fn test_vgg_committed_group() {
+        use commit_reveal::MembershipSecrets;
+
+        let hm: HashMap<Pubkey, Pubkey> = (0..20)
+            .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+            .collect();
+        let vgg = VoteGroupGenerator::new(&hm, 5);
+        let outsider = Pubkey::new_unique();
+
+        let mut selected = 0;
+        for voter in hm.keys().chain(std::iter::once(&outsider)) {
+            let secrets = MembershipSecrets::new(voter.as_ref(), voter, 1, 4);
+            let commitment = secrets.commitment();
+            for window in 0..4 {
+                let reveal = secrets.reveal(window).unwrap();
+                let hash = Hash::new_unique();
+                let expected = *voter != outsider
+                    && vgg.vrf_output_selected(secrets.output(window, &hash).unwrap().as_ref());
+                assert_eq!(
+                    vgg.in_committed_group_for_hash(
+                        hash,
+                        *voter,
+                        &commitment,
+                        (window, 4),
+                        &reveal
+                    ),
+                    expected
+                );
+                if expected {
+                    selected += 1;
+                    // the secret of one window doesn't open another
+                    assert!(!vgg.in_committed_group_for_hash(
+                        hash,
+                        *voter,
+                        &commitment,
+                        (window + 1, 4),
+                        &reveal
+                    ));
+                }
+            }
+        }
+        // 5 of 20 expected per window, 20 expected over 4 windows
+        assert!(selected > 4 && selected < 50);
+    }
+
+    #[test]
+    fn test_vgg_algorithm_v2_distinct() {
+        // 76 voters lets the legacy distance 57 share a factor with the ring size
+        for voters in &[1usize, 2, 3, 10, 76] {
+            let hm: HashMap<Pubkey, Pubkey> = (0..*voters)
+                .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+                .collect();
+            for size in 1..=12 {
+                let vgg = VoteGroupGenerator::new(&hm, size).with_algorithm(SelectionAlgorithm::V2);
+                assert_eq!(vgg.algorithm(), SelectionAlgorithm::V2);
+                for seed in 0..200 {
+                    let mut group = vgg.group_for_seed(seed);
+                    assert_eq!(group.len(), size.min(*voters));
+                    group.sort();
+                    group.dedup();
+                    assert_eq!(group.len(), size.min(*voters));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_vgg_algorithm_v3() {
+        for voters in &[1usize, 2, 3, 10, 76] {
+            let hm: HashMap<Pubkey, Pubkey> = (0..*voters)
+                .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+                .collect();
+            for size in 1..=12 {
+                let vgg = VoteGroupGenerator::new(&hm, size).with_algorithm(SelectionAlgorithm::V3);
+                assert!(vgg.check_state().is_ok());
+                assert_eq!(vgg.distance_for_seed(7), None);
+                for seed in 0..200 {
+                    let mut group = vgg.group_for_seed(seed);
+                    assert_eq!(group.len(), size.min(*voters));
+                    group.sort();
+                    group.dedup();
+                    assert_eq!(group.len(), size.min(*voters));
+                }
+            }
+        }
+
+        // every voter is equally likely, 3000 groups of 3 out of 10 pick each about 900 times
+        let hm: HashMap<Pubkey, Pubkey> = (0..10)
+            .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+            .collect();
+        let vgg = VoteGroupGenerator::new(&hm, 3).with_algorithm(SelectionAlgorithm::V3);
+        let mut counts: HashMap<Pubkey, usize> = HashMap::new();
+        for seed in 0..3000 {
+            for key in vgg.group_for_seed(seed) {
+                *counts.entry(key).or_default() += 1;
+            }
+        }
+        assert_eq!(counts.len(), 10);
+        assert!(counts.values().all(|count| *count > 750 && *count < 1050));
+
+        // the indexes only depend on the seed and the voter count, they must never change
+        let hm: HashMap<Pubkey, Pubkey> = (0..100)
+            .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+            .collect();
+        let vgg = VoteGroupGenerator::new(&hm, 5).with_algorithm(SelectionAlgorithm::V3);
+        assert_eq!(vgg.chacha_indexes(0), vec![58, 52, 36, 99, 0]);
+        assert_eq!(vgg.chacha_indexes(u64::MAX), vec![13, 96, 53, 95, 41]);
+        assert_eq!(
+            "v3".parse::<SelectionAlgorithm>(),
+            Ok(SelectionAlgorithm::V3)
+        );
+    }
+
+    #[test]
+    fn test_vgg_large_voter_set() {
+        // the sparse shuffle long v3 walks take picks what tracing the swaps back does
+        for seed in 0..8 {
+            for len in [1, 2, 65, 500, 2_000].iter().copied() {
+                let mut traced = vec![0; len];
+                let mut sparse = vec![0; len];
+                selection_core::chacha_indexes(seed, 2_000, &mut traced);
+                chacha_indexes_sparse(seed, 2_000, &mut sparse);
+                assert_eq!(traced, sparse);
+            }
+        }
+
+        let hm: HashMap<Pubkey, Pubkey> = (0..20_000)
+            .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+            .collect();
+        for algorithm in &[
+            SelectionAlgorithm::V1,
+            SelectionAlgorithm::V2,
+            SelectionAlgorithm::V3,
+        ] {
+            let vgg = VoteGroupGenerator::new(&hm, 6_000)
+                .with_algorithm(*algorithm)
+                .with_distinct_members(true);
+            for seed in 0..2 {
+                let group = vgg.group_for_seed(seed);
+                let members: HashSet<Pubkey> = group.iter().copied().collect();
+                assert_eq!(members.len(), 6_000);
+                for (position, key) in group.iter().enumerate().step_by(97) {
+                    assert_eq!(vgg.position_in_group(seed, key), Some(position));
+                    assert_eq!(vgg.voters()[vgg.index_of(key).unwrap()], *key);
+                }
+                let backup = vgg.backup_group_for_seed(seed);
+                assert_eq!(backup.len(), 6_000);
+                assert!(backup.iter().all(|key| !members.contains(key)));
+            }
+        }
+        assert_eq!(
+            VoteGroupGenerator::new(&hm, 5).index_of(&Pubkey::new_unique()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_vgg_algorithm_v1_unchanged() {
+        let hm: HashMap<Pubkey, Pubkey> = (0..30)
+            .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+            .collect();
+        let vgg = VoteGroupGenerator::new(&hm, 11);
+        assert_eq!(vgg.algorithm(), SelectionAlgorithm::V1);
+        // seed 0 always starts at the first voter with a distance of 1
+        let group = vgg.group_for_seed(0);
+        assert_eq!(group[..], vgg.possible_voters[..11]);
+    }
+
+    #[test]
+    fn test_seed_for_slot_vectors() {
+        // these must never change, every node has to map slots to the same groups
+        assert_eq!(seed_for_slot(0, 0, &Hash::default()), 4923527347116642102);
+        assert_eq!(
+            seed_for_slot(432_000, 1, &Hash::new_from_array([1u8; 32])),
+            14593035086946958478
+        );
+        let mut bytes = [0u8; 32];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        assert_eq!(
+            seed_for_slot(12_345_678, 28, &Hash::new_from_array(bytes)),
+            4151813346602358154
+        );
+    }
+
+    #[test]
+    fn test_seed_for_slot_in_schedule() {
+        let epoch_schedule = EpochSchedule::custom(32, 32, false);
+        let hash = Hash::new_unique();
+        assert_eq!(
+            seed_for_slot_in_schedule(&epoch_schedule, 70, &hash),
+            seed_for_slot(70, 2, &hash)
+        );
+        assert_ne!(seed_for_slot(70, 2, &hash), seed_for_slot(71, 2, &hash));
+        assert_ne!(seed_for_slot(70, 2, &hash), seed_for_slot(70, 3, &hash));
+    }
+
+    #[test]
+    fn test_vgg_add_remove_voter() {
+        let hm: HashMap<Pubkey, Pubkey> = (0..10)
+            .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+            .collect();
+        let mut vgg = VoteGroupGenerator::new(&hm, 4);
+
+        let key = Pubkey::new_unique();
+        assert!(vgg.add_voter(key, 0));
+        assert!(!vgg.add_voter(key, 0));
+        let mut grown = hm.clone();
+        grown.insert(key, Pubkey::new_unique());
+        assert_eq!(vgg, VoteGroupGenerator::new(&grown, 4));
+
+        assert!(vgg.remove_voter(&key));
+        assert!(!vgg.remove_voter(&key));
+        assert_eq!(vgg, VoteGroupGenerator::new(&hm, 4));
+    }
+
+    #[test]
+    fn test_vgg_add_remove_weighted_voter() {
+        let hm: HashMap<Pubkey, Pubkey> = (0..10)
+            .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+            .collect();
+        let mut stakes: HashMap<Pubkey, u64> = hm.keys().map(|key| (*key, 100)).collect();
+        let exclusions = ExclusionList::default();
+        let mut vgg = VoteGroupGenerator::new_stake_weighted(&hm, &stakes, 4, &exclusions);
+
+        let key = Pubkey::new_unique();
+        assert!(vgg.add_voter(key, 500));
+        let mut grown = hm.clone();
+        grown.insert(key, Pubkey::new_unique());
+        stakes.insert(key, 500);
+        assert_eq!(
+            vgg,
+            VoteGroupGenerator::new_stake_weighted(&grown, &stakes, 4, &exclusions)
+        );
+
+        assert!(vgg.remove_voter(&key));
+        assert_eq!(
+            vgg,
+            VoteGroupGenerator::new_stake_weighted(&hm, &stakes, 4, &exclusions)
+        );
+    }
+
+    #[test]
+    fn test_vgg_replace_voters() {
+        let hm: HashMap<Pubkey, Pubkey> = (0..10)
+            .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+            .collect();
+        let other: HashMap<Pubkey, Pubkey> = (0..7)
+            .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+            .collect();
+        let mut vgg = VoteGroupGenerator::new(&hm, 4).with_algorithm(SelectionAlgorithm::V2);
+        vgg.replace_voters(&other, &HashMap::new(), &ExclusionList::legacy());
+        assert_eq!(
+            vgg,
+            VoteGroupGenerator::new(&other, 4).with_algorithm(SelectionAlgorithm::V2)
+        );
+    }
+
+    #[test]
+    fn test_vgg_group_cache() {
+        let hm: HashMap<Pubkey, Pubkey> = (0..20)
+            .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+            .collect();
+        let mut vgg = VoteGroupGenerator::new(&hm, 5);
+        for seed in 0..100 {
+            let group = vgg.group_for_seed(seed);
+            for key in hm.keys() {
+                // second pass is served from the cache
+                assert_eq!(vgg.in_group_for_seed(seed, *key), group.contains(key));
+                assert_eq!(vgg.in_group_for_seed(seed, *key), group.contains(key));
+            }
+        }
+        assert!(!vgg.group_cache.is_empty());
+        assert!(vgg.clone().group_cache.is_empty());
+
+        // the most recently checked group comes last, a member picked twice listed once
+        let (seed, members) = vgg.cached_groups().pop().unwrap();
+        assert_eq!(seed, 99);
+        let mut expected: Vec<Pubkey> = vec![];
+        for member in vgg.group_for_seed(seed) {
+            if !expected.contains(&member) {
+                expected.push(member);
+            }
+        }
+        assert_eq!(members, expected);
+
+        // a stale group must never survive a voter set change
+        let key = Pubkey::new_unique();
+        vgg.add_voter(key, 0);
+        assert!(vgg.group_cache.is_empty());
+        for seed in 0..100 {
+            assert_eq!(
+                vgg.in_group_for_seed(seed, key),
+                vgg.group_for_seed(seed).contains(&key)
+            );
+        }
+        vgg.remove_voter(&key);
+        assert!(vgg.group_cache.is_empty());
+    }
+
+    #[test]
+    fn test_vgg_group_commitment() {
+        let hm: HashMap<Pubkey, Pubkey> = (0..20)
+            .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+            .collect();
+        let vgg = VoteGroupGenerator::new(&hm, 5).with_algorithm(SelectionAlgorithm::V2);
+        let mut members = vgg.group_for_seed(7);
+        members.sort();
+        let mut data: Vec<&[u8]> = vec![&[7, 0, 0, 0, 0, 0, 0, 0]];
+        data.extend(members.iter().map(|member| member.as_ref()));
+        assert_eq!(vgg.group_commitment(7).unwrap(), hashv(&data));
+
+        // another seed or another voter set commits to another group
+        assert_ne!(vgg.group_commitment(7), vgg.group_commitment(8));
+        let mut other = vgg.clone();
+        other.remove_voter(&members[0]);
+        assert_ne!(vgg.group_commitment(7), other.group_commitment(7));
+        assert!(VoteGroupGenerator::new(&HashMap::new(), 5)
+            .group_commitment(7)
+            .is_err());
+    }
+
+    #[test]
+    fn test_vgg_in_group_batch() {
+        let hm: HashMap<Pubkey, Pubkey> = (0..40)
+            .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+            .collect();
+        let vgg = VoteGroupGenerator::new(&hm, 7);
+        let mut keys: Vec<Pubkey> = hm.keys().copied().collect();
+        keys.push(Pubkey::new_unique());
+        for seed in 0..50 {
+            let expected: Vec<bool> = keys
+                .iter()
+                .map(|key| vgg.group_for_seed(seed).contains(key))
+                .collect();
+            assert_eq!(vgg.in_group_for_seed_batch(seed, &keys), expected);
+            assert_eq!(vgg.par_in_group_for_seed_batch(seed, &keys), expected);
+        }
+        assert!(vgg.in_group_for_seed_batch(0, &[]).is_empty());
+    }
+
+    fn gcd(mut a: usize, mut b: usize) -> usize {
+        while b != 0 {
+            let t = a % b;
+            a = b;
+            b = t;
+        }
+        a
+    }
+
+    #[test]
+    fn test_coprime_distances() {
+        assert_eq!(coprime_distances(0), vec![1]);
+        assert_eq!(coprime_distances(2), vec![1]);
+        assert_eq!(coprime_distances(10), vec![1, 3, 7]);
+        assert_eq!(coprime_distances(13), vec![1, 2, 3, 5, 7, 11]);
+        for len in 1..1000 {
+            for dist in coprime_distances(len) {
+                let dist = dist as usize;
+                assert!(
+                    dist == 1 || (2..).take_while(|d| d * d <= dist).all(|d| dist % d != 0),
+                    "{} isn't prime",
+                    dist
+                );
+                assert!(dist == 1 || dist < len);
+                assert_eq!(gcd(len, dist), 1);
+            }
+        }
+        // well past the end of the old hardcoded list
+        assert!(coprime_distances(100_000).contains(&99_991));
+    }
+
+    #[test]
+    fn test_vgg_v2_groups_distinct_exhaustive() {
+        let voters: Vec<Pubkey> = (0..64).map(|_| Pubkey::new_unique()).collect();
+        for len in 1..=voters.len() {
+            let hm: HashMap<Pubkey, Pubkey> = voters[..len].iter().map(|key| (*key, *key)).collect();
+            let vgg = VoteGroupGenerator::new(&hm, OPTIMAL_VOTE_GROUP_SIZE)
+                .with_algorithm(SelectionAlgorithm::V2);
+            for seed in 0..256 {
+                let mut group = vgg.group_indexes(seed);
+                assert_eq!(group.len(), OPTIMAL_VOTE_GROUP_SIZE.min(len));
+                group.sort_unstable();
+                group.dedup();
+                assert_eq!(group.len(), OPTIMAL_VOTE_GROUP_SIZE.min(len));
+            }
+        }
+    }
+
+    #[test]
+    fn test_vgg_v1_keeps_legacy_distances() {
+        let hm: HashMap<Pubkey, Pubkey> = (0..200)
+            .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+            .collect();
+        let mut vgg = VoteGroupGenerator::new(&hm, 11);
+        assert!(vgg.all_distance.contains(&51));
+        vgg = vgg.with_algorithm(SelectionAlgorithm::V2);
+        assert!(!vgg.all_distance.contains(&51));
+        assert!(vgg.all_distance.contains(&199));
+        vgg = vgg.with_algorithm(SelectionAlgorithm::V1);
+        assert_eq!(vgg, VoteGroupGenerator::new(&hm, 11));
+    }
+
+    #[test]
+    fn test_vgg_v1_distinct_members() {
+        use rand::{thread_rng, Rng};
+        let mut rng = thread_rng();
+        let voters: Vec<Pubkey> = (0..128).map(|_| Pubkey::new_unique()).collect();
+        for _ in 0..200 {
+            let len = rng.gen_range(1, voters.len() + 1);
+            let size = rng.gen_range(1, 2 * OPTIMAL_VOTE_GROUP_SIZE);
+            let hm: HashMap<Pubkey, Pubkey> = voters[..len].iter().map(|key| (*key, *key)).collect();
+            let legacy = VoteGroupGenerator::new(&hm, size);
+            let vgg = legacy.clone().with_distinct_members(true);
+            for _ in 0..50 {
+                let seed = rng.gen::<u64>();
+                let mut group = vgg.group_indexes(seed);
+                assert_eq!(group.len(), size.min(len));
+                // walks that never revisited a voter are untouched
+                let legacy_group = legacy.group_indexes(seed);
+                let mut deduped = legacy_group.clone();
+                deduped.sort_unstable();
+                deduped.dedup();
+                if deduped.len() == legacy_group.len() {
+                    assert_eq!(group, legacy_group);
+                }
+                group.sort_unstable();
+                group.dedup();
+                assert_eq!(group.len(), size.min(len));
+            }
+        }
+    }
+
+    #[test]
+    fn test_vgg_v1_revisit_detected() {
+        // 68 voters and a distance of 51 share the factor 17, so the legacy walk
+        // comes back to its start after 4 steps
+        let hm: HashMap<Pubkey, Pubkey> = (0..68)
+            .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+            .collect();
+        let legacy = VoteGroupGenerator::new(&hm, OPTIMAL_VOTE_GROUP_SIZE);
+        // the seed picks both the start and the distance
+        let seed = legacy.all_distance.iter().position(|d| *d == 51).unwrap() as u64;
+        let mut repeated = legacy.group_indexes(seed);
+        repeated.sort_unstable();
+        repeated.dedup();
+        assert!(repeated.len() < OPTIMAL_VOTE_GROUP_SIZE);
+
+        let vgg = legacy.with_distinct_members(true);
+        let mut group = vgg.group_indexes(seed);
+        group.sort_unstable();
+        group.dedup();
+        assert_eq!(group.len(), OPTIMAL_VOTE_GROUP_SIZE);
+        // and it's deterministic
+        assert_eq!(vgg.group_indexes(seed), vgg.group_indexes(seed));
+    }
+
+    #[test]
+    fn test_vgg_distance_for_seed() {
+        let hm: HashMap<Pubkey, Pubkey> = (0..40)
+            .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+            .collect();
+        for algorithm in vec![SelectionAlgorithm::V1, SelectionAlgorithm::V2] {
+            let vgg = VoteGroupGenerator::new(&hm, 5).with_algorithm(algorithm);
+            for seed in 0..200u64 {
+                let indexes = vgg.group_indexes(seed);
+                let dist = vgg.distance_for_seed(seed).unwrap() as usize;
+                assert_eq!((indexes[0] + dist) % hm.len(), indexes[1]);
+            }
+        }
+        let stakes: HashMap<Pubkey, u64> = hm.keys().map(|key| (*key, 1)).collect();
+        let vgg =
+            VoteGroupGenerator::new_stake_weighted(&hm, &stakes, 5, &ExclusionList::default());
+        assert_eq!(vgg.distance_for_seed(0), None);
+        assert_eq!(VoteGroupGenerator::new_dummy().distance_for_seed(0), None);
+    }
+
+    #[test]
+    fn test_vote_group_overrides() {
+        let mut params = VoteGroupParams::default();
+        VoteGroupOverrides::default().apply(&mut params);
+        assert_eq!(params, VoteGroupParams::default());
+
+        let overrides = VoteGroupOverrides {
+            group_size: Some(3),
+            algorithm: Some("v2".parse().unwrap()),
+            ..VoteGroupOverrides::default()
+        };
+        assert!(!overrides.is_empty());
+        overrides.apply(&mut params);
+        assert_eq!(params.group_size, 3);
+        assert_eq!(params.algorithm, SelectionAlgorithm::V2);
+        assert_eq!(params.selection_mode, SelectionMode::Uniform);
+        assert!("v4".parse::<SelectionAlgorithm>().is_err());
+
+        // a fixed override wins over the cluster's dynamic size
+        params.dynamic_group_size = Some(DynamicGroupSize {
+            percent: 5,
+            min: 11,
+            max: 100,
+        });
+        overrides.apply(&mut params);
+        assert_eq!(params.dynamic_group_size, None);
+
+        // only the members reach the params, the bank answers for a pinned seed
+        let members = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+        let overrides = VoteGroupOverrides {
+            members: Some(members.clone()),
+            seed: Some(7),
+            ..VoteGroupOverrides::default()
+        };
+        assert!(!overrides.is_empty());
+        overrides.apply(&mut params);
+        assert_eq!(params.preset_members, Some(members));
+        assert_eq!(params.group_size, 3);
+    }
+
+    #[test]
+    fn test_dynamic_group_size() {
+        let sizing = DynamicGroupSize {
+            percent: 5,
+            min: 11,
+            max: 100,
+        };
+        assert_eq!(sizing.size_for(0), 0);
+        assert_eq!(sizing.size_for(7), 7);
+        assert_eq!(sizing.size_for(100), 11);
+        assert_eq!(sizing.size_for(220), 11);
+        assert_eq!(sizing.size_for(221), 12);
+        assert_eq!(sizing.size_for(1_000), 50);
+        assert_eq!(sizing.size_for(5_000), 100);
+        assert_eq!(sizing.size_for(usize::MAX), 100);
+    }
+
+    #[test]
+    fn test_minimum_stake() {
+        assert!(MinimumStake::default().is_met(0, 100));
+        assert!(MinimumStake::Lamports(10).is_met(10, 100));
+        assert!(!MinimumStake::Lamports(10).is_met(9, 100));
+
+        let one_percent = MinimumStake::FractionOfTotal {
+            numerator: 1,
+            denominator: 100,
+        };
+        assert!(one_percent.is_met(10, 1_000));
+        assert!(!one_percent.is_met(9, 1_000));
+        assert!(one_percent.is_met(u64::MAX / 100 + 1, u64::MAX));
+        assert!(!one_percent.is_met(u64::MAX / 100, u64::MAX));
+        let disabled = MinimumStake::FractionOfTotal {
+            numerator: 1,
+            denominator: 0,
+        };
+        assert!(disabled.is_met(0, 1_000));
+    }
+
+    #[test]
+    fn test_delinquency_threshold() {
+        let threshold = DelinquencyThreshold {
+            max_slot_distance: 100,
+            reentry_slot_distance: 10,
+        };
+        assert!(!threshold.is_delinquent(Some(900), 1_000, false));
+        assert!(threshold.is_delinquent(Some(899), 1_000, false));
+        assert!(threshold.is_delinquent(None, 1_000, false));
+        // a voter that was left out needs a much more recent vote to come back
+        assert!(threshold.is_delinquent(Some(900), 1_000, true));
+        assert!(!threshold.is_delinquent(Some(990), 1_000, true));
+        assert!(!threshold.is_delinquent(Some(5), 0, false));
+    }
+
+    #[test]
+    fn test_vgg_backup_group() {
+        let hm: HashMap<Pubkey, Pubkey> = (0..40)
+            .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+            .collect();
+        let stakes: HashMap<Pubkey, u64> = hm.keys().zip(1..).map(|(key, i)| (*key, i)).collect();
+        let generators = vec![
+            VoteGroupGenerator::new(&hm, 11),
+            VoteGroupGenerator::new(&hm, 11).with_algorithm(SelectionAlgorithm::V2),
+            VoteGroupGenerator::new_stake_weighted(&hm, &stakes, 11, &ExclusionList::default()),
+        ];
+        for vgg in generators {
+            for seed in 0..200 {
+                let primary = vgg.group_for_seed(seed);
+                let backup = vgg.backup_group_for_seed(seed);
+                assert_eq!(backup.len(), 11);
+                assert!(backup.iter().all(|key| !primary.contains(key)));
+                let distinct: HashSet<_> = backup.iter().collect();
+                assert_eq!(distinct.len(), backup.len());
+                assert_eq!(backup, vgg.backup_group_for_seed(seed));
+            }
+        }
+
+        // only the voters the primary group left out are available
+        let hm: HashMap<Pubkey, Pubkey> = (0..15)
+            .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+            .collect();
+        let vgg = VoteGroupGenerator::new(&hm, 11).with_algorithm(SelectionAlgorithm::V2);
+        assert_eq!(vgg.backup_group_for_seed(7).len(), 4);
+        let vgg = VoteGroupGenerator::new(&hm, 15);
+        assert!(vgg.backup_group_for_seed(7).is_empty());
+        assert!(VoteGroupGenerator::new_dummy()
+            .backup_group_for_seed(7)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_vgg_quorum() {
+        let hm: HashMap<Pubkey, Pubkey> = (0..30)
+            .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+            .collect();
+        for (size, quorum) in &[(1, 1), (2, 2), (3, 3), (4, 3), (6, 5), (11, 8), (30, 21)] {
+            let vgg = VoteGroupGenerator::new(&hm, *size).with_algorithm(SelectionAlgorithm::V2);
+            assert_eq!(vgg.quorum(), *quorum);
+            let group = vgg.group_for_seed(5);
+            assert!(vgg.has_quorum(&group[..*quorum], 5));
+            assert!(!vgg.has_quorum(&group[..*quorum - 1], 5));
+            // repeats and outsiders add nothing
+            let mut votes = group[..*quorum - 1].to_vec();
+            votes.extend_from_slice(&group[..*quorum - 1]);
+            votes.extend(hm.keys().filter(|key| !group.contains(key)));
+            assert!(!vgg.has_quorum(&votes, 5));
+        }
+        assert_eq!(VoteGroupGenerator::new_dummy().quorum(), 0);
+        assert!(!VoteGroupGenerator::new_dummy().has_quorum(&[], 5));
+
+        // stake weighted groups need more than two thirds of the group's stake
+        let keys: Vec<Pubkey> = hm.keys().copied().collect();
+        let stakes: HashMap<Pubkey, u64> = keys
+            .iter()
+            .enumerate()
+            .map(|(i, key)| (*key, if i == 0 { 1_000 } else { 1 }))
+            .collect();
+        let vgg =
+            VoteGroupGenerator::new_stake_weighted(&hm, &stakes, 4, &ExclusionList::default());
+        for seed in 0..20 {
+            let group = vgg.group_for_seed(seed);
+            let (whale, others): (Vec<Pubkey>, Vec<Pubkey>) =
+                group.iter().partition(|key| **key == keys[0]);
+            if whale.is_empty() {
+                continue;
+            }
+            assert!(vgg.has_quorum(&whale, seed));
+            assert!(!vgg.has_quorum(&others, seed));
+        }
+    }
+
+    #[test]
+    fn test_vgg_quorum_fraction() {
+        let hm: HashMap<Pubkey, Pubkey> = (0..30)
+            .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+            .collect();
+        let vgg = VoteGroupGenerator::new(&hm, 11).with_algorithm(SelectionAlgorithm::V2);
+        let group = vgg.group_for_seed(5);
+        let three_quarters = QuorumFraction {
+            numerator: 3,
+            denominator: 4,
+        };
+        assert!(three_quarters.is_valid());
+        // more than 8.25 of 11
+        assert!(vgg.has_quorum_with_fraction(&group[..9], 5, three_quarters));
+        assert!(!vgg.has_quorum_with_fraction(&group[..8], 5, three_quarters));
+        assert!(vgg.has_quorum(&group[..8], 5));
+        assert_eq!(
+            vgg.has_quorum(&group[..7], 5),
+            vgg.has_quorum_with_fraction(&group[..7], 5, QuorumFraction::default())
+        );
+
+        assert!(QuorumFraction::default().is_valid());
+        for (numerator, denominator) in &[(1, 3), (1, 1), (2, 1), (0, 0)] {
+            assert!(!QuorumFraction {
+                numerator: *numerator,
+                denominator: *denominator,
+            }
+            .is_valid());
+        }
+    }
+
+    #[test]
+    fn test_vgg_group_without_leader() {
+        let hm: HashMap<Pubkey, Pubkey> = (0..30)
+            .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+            .collect();
+        let vgg = VoteGroupGenerator::new(&hm, 11).with_algorithm(SelectionAlgorithm::V2);
+        for seed in 0..100 {
+            let group = vgg.group_for_seed(seed);
+            let leader = [group[3]];
+            let without = vgg.group_for_seed_without_leader(seed, &leader);
+            assert_eq!(without, vgg.group_for_seed_without_leader(seed, &leader));
+            assert_eq!(without.len(), group.len());
+            assert!(!without.contains(&leader[0]));
+            // everybody else keeps their place, the replacement comes from the backup group
+            for (i, key) in without.iter().enumerate() {
+                if i == 3 {
+                    assert_eq!(*key, vgg.backup_group_for_seed(seed)[0]);
+                } else {
+                    assert_eq!(*key, group[i]);
+                }
+            }
+            for key in hm.keys() {
+                assert_eq!(
+                    vgg.in_group_for_seed_without_leader(seed, &leader, *key),
+                    without.contains(key)
+                );
+            }
+
+            // a leader outside the group changes nothing
+            let outsider = *hm.keys().find(|key| !group.contains(key)).unwrap();
+            assert_eq!(vgg.group_for_seed_without_leader(seed, &[outsider]), group);
+        }
+
+        // without a backup the group just loses the leader
+        let vgg = VoteGroupGenerator::new(&hm, 30).with_algorithm(SelectionAlgorithm::V2);
+        let group = vgg.group_for_seed(7);
+        let without = vgg.group_for_seed_without_leader(7, &[group[0]]);
+        assert_eq!(without, group[1..].to_vec());
+        assert!(!vgg.in_group_for_seed_without_leader(7, &[group[0]], group[0]));
+    }
+
+    #[test]
+    fn test_vgg_standby_for_seed() {
+        let hm: HashMap<Pubkey, Pubkey> = (0..30)
+            .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+            .collect();
+        for algorithm in &[
+            SelectionAlgorithm::V1,
+            SelectionAlgorithm::V2,
+            SelectionAlgorithm::V3,
+        ] {
+            for distinct_members in &[false, true] {
+                let vgg = VoteGroupGenerator::new(&hm, 7)
+                    .with_algorithm(*algorithm)
+                    .with_distinct_members(*distinct_members);
+                for seed in 0..50 {
+                    let group = vgg.group_for_seed(seed);
+                    let members: HashSet<Pubkey> = group.iter().copied().collect();
+                    let all = vgg.standby_for_seed(seed, 100);
+                    // every voter outside the group is somewhere on the list
+                    assert_eq!(all.len(), 30 - members.len());
+                    assert!(all.iter().all(|key| !members.contains(key)));
+                    assert_eq!(all.iter().collect::<HashSet<_>>().len(), all.len());
+                    for count in 0..all.len() {
+                        assert_eq!(vgg.standby_for_seed(seed, count), all[..count].to_vec());
+                    }
+                    assert_eq!(
+                        vgg.group_and_standby_for_seed(seed, 3),
+                        (group, all[..3].to_vec())
+                    );
+                }
+            }
+        }
+
+        // v2 walks on from where the group stopped
+        let vgg = VoteGroupGenerator::new(&hm, 7).with_algorithm(SelectionAlgorithm::V2);
+        let longer = VoteGroupGenerator::new(&hm, 10).with_algorithm(SelectionAlgorithm::V2);
+        assert_eq!(
+            vgg.standby_for_seed(5, 3),
+            longer.group_for_seed(5)[7..].to_vec()
+        );
+
+        let stakes: HashMap<Pubkey, u64> = hm.keys().zip(1..).map(|(k, s)| (*k, s)).collect();
+        let weighted =
+            VoteGroupGenerator::new_stake_weighted(&hm, &stakes, 7, &ExclusionList::default());
+        let group = weighted.group_for_seed(5);
+        let standby = weighted.standby_for_seed(5, 4);
+        assert_eq!(standby.len(), 4);
+        assert!(standby.iter().all(|key| !group.contains(key)));
+        assert!(VoteGroupGenerator::new_dummy()
+            .standby_for_seed(5, 4)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_vgg_group_with_standby_promoted() {
+        let hm: HashMap<Pubkey, Pubkey> = (0..30)
+            .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+            .collect();
+        let vgg = VoteGroupGenerator::new(&hm, 7).with_algorithm(SelectionAlgorithm::V2);
+        let (group, standby) = vgg.group_and_standby_for_seed(9, 3);
+        assert_eq!(vgg.group_with_standby_promoted(9, 3, &[]), group);
+
+        // offline standby voters are passed over
+        let offline = [group[1], group[4], standby[0]];
+        let mut expected = group.clone();
+        expected[1] = standby[1];
+        expected[4] = standby[2];
+        assert_eq!(vgg.group_with_standby_promoted(9, 3, &offline), expected);
+
+        // once the standby voters run out the group shrinks
+        let offline = [group[0], group[1], group[2], standby[0]];
+        let promoted = vgg.group_with_standby_promoted(9, 3, &offline);
+        assert_eq!(promoted.len(), 6);
+        assert_eq!(promoted[..2], standby[1..]);
+        assert_eq!(promoted[2..], group[3..]);
+    }
+
+    #[test]
+    fn test_vgg_group_with_cooldown() {
+        let hm: HashMap<Pubkey, Pubkey> = (0..40)
+            .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+            .collect();
+        let vgg = VoteGroupGenerator::new(&hm, 5).with_algorithm(SelectionAlgorithm::V2);
+        for seed in 10..60 {
+            let previous: Vec<u64> = (seed - 3..seed).collect();
+            let cooled = vgg.group_with_cooldown(seed, &previous);
+            assert_eq!(cooled, vgg.group_with_cooldown(seed, &previous));
+            assert_eq!(cooled.len(), 5);
+            let group = vgg.group_for_seed(seed);
+            for (key, original) in cooled.iter().zip(group.iter()) {
+                let cooling = previous
+                    .iter()
+                    .any(|seed| vgg.group_for_seed(*seed).contains(key));
+                assert!(!cooling);
+                // members that aren't cooling down keep their place
+                if !previous
+                    .iter()
+                    .any(|seed| vgg.group_for_seed(*seed).contains(original))
+                {
+                    assert_eq!(key, original);
+                }
+            }
+            assert_eq!(vgg.group_with_cooldown(seed, &[]), group);
+        }
+
+        // slots cool down the groups of the slots right before them
+        let source = FixedSeed(7);
+        let anchored = EpochAnchoredSeed::new(2, Hash::new_unique());
+        for slot in 0..50 {
+            assert_eq!(
+                vgg.group_from_source_with_cooldown(&anchored, slot, 0),
+                vgg.group_from_source(&anchored, slot)
+            );
+            let cooled = vgg.group_from_source_with_cooldown(&anchored, slot, 2);
+            for previous in slot.saturating_sub(2)..slot {
+                let group = vgg.group_from_source(&anchored, previous);
+                assert!(cooled.iter().all(|key| !group.contains(key)));
+            }
+        }
+        // a fixed seed picks the same group every slot, only the cooldown moves it
+        assert_ne!(
+            vgg.group_from_source_with_cooldown(&source, 9, 1),
+            vgg.group_from_source(&source, 9)
+        );
+
+        // once nobody else is left the cooling members keep their places
+        let vgg = VoteGroupGenerator::new(&hm, 30).with_algorithm(SelectionAlgorithm::V2);
+        let cooled = vgg.group_with_cooldown(3, &[1, 2]);
+        assert_eq!(cooled.len(), 30);
+        assert_eq!(
+            cooled.iter().collect::<HashSet<_>>().len(),
+            30,
+            "replacements are never picked twice"
+        );
+
+        let stakes: HashMap<Pubkey, u64> = hm.keys().zip(1..).map(|(k, s)| (*k, s)).collect();
+        let vgg =
+            VoteGroupGenerator::new_stake_weighted(&hm, &stakes, 5, &ExclusionList::default());
+        let previous = vgg.group_for_seed(1);
+        let cooled = vgg.group_with_cooldown(2, &[1]);
+        assert_eq!(cooled.len(), 5);
+        assert!(cooled.iter().all(|key| !previous.contains(key)));
+    }
+
+    #[test]
+    fn test_vgg_assigned_slots() {
+        let hm: HashMap<Pubkey, Pubkey> = (0..30)
+            .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+            .collect();
+        let vgg = VoteGroupGenerator::new(&hm, 5).with_algorithm(SelectionAlgorithm::V2);
+        let source = EpochAnchoredSeed::new(3, Hash::new_unique());
+        let assigned = vgg.assigned_slots(&source, 10..=109);
+        assert_eq!(assigned.values().sum::<u64>(), 500);
+        for key in hm.keys() {
+            let picked = (10..=109)
+                .filter(|slot| vgg.group_from_source(&source, *slot).contains(key))
+                .count() as u64;
+            assert_eq!(assigned.get(key).copied().unwrap_or(0), picked);
+        }
+        assert!(VoteGroupGenerator::new_dummy()
+            .assigned_slots(&source, 0..=9)
+            .is_empty());
+
+        let threshold = AbsenceThreshold::default();
+        assert!(!threshold.is_absent(100, 50));
+        assert!(threshold.is_absent(100, 49));
+        assert!(!threshold.is_absent(100, 200));
+        assert!(!threshold.is_absent(31, 0));
+        assert!(!AbsenceThreshold {
+            max_absent_percent: 0,
+            min_assigned_slots: 0,
+        }
+        .is_absent(0, 0));
+    }
+
+    #[test]
+    fn test_vgg_overlapping_groups() {
+        let hm: HashMap<Pubkey, Pubkey> = (0..40)
+            .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+            .collect();
+        let anchor = Hash::new_unique();
+        let vgg = VoteGroupGenerator::new(&hm, 10).with_min_overlap(30);
+        assert_eq!(
+            vgg.selection_mode(),
+            SelectionMode::Overlapping {
+                min_overlap_percent: 30
+            }
+        );
+        assert!(vgg.check_state().is_ok());
+        let mut previous: Option<HashSet<Pubkey>> = None;
+        for slot in 0..200 {
+            let group = vgg.group_for_slot(slot, 2, &anchor);
+            assert_eq!(group, vgg.group_for_slot(slot, 2, &anchor));
+            let members: HashSet<Pubkey> = group.iter().copied().collect();
+            assert_eq!(members.len(), 10);
+            if let Some(previous) = previous {
+                assert!(previous.intersection(&members).count() >= 3);
+            }
+            previous = Some(members);
+        }
+
+        // the overlap is capped so both links fit in a group
+        let vgg = VoteGroupGenerator::new(&hm, 10).with_min_overlap(90);
+        assert_eq!(
+            vgg.selection_mode(),
+            SelectionMode::Overlapping {
+                min_overlap_percent: MAX_OVERLAP_PERCENT
+            }
+        );
+        assert!(vgg.check_state().is_ok());
+        let first: HashSet<Pubkey> = vgg.group_for_slot(5, 2, &anchor).into_iter().collect();
+        let second: HashSet<Pubkey> = vgg.group_for_slot(6, 2, &anchor).into_iter().collect();
+        assert_eq!(first.len(), 10);
+        assert!(first.intersection(&second).count() >= 5);
+
+        // other modes ignore the neighbouring seeds
+        let vgg = VoteGroupGenerator::new(&hm, 10);
+        assert_eq!(
+            vgg.group_for_consecutive_seeds(1, 2, 3),
+            vgg.group_for_seed(2)
+        );
+        assert_eq!(
+            vgg.group_for_slot(9, 2, &anchor),
+            vgg.group_for_seed(seed_for_slot(9, 2, &anchor))
+        );
+        assert!(VoteGroupGenerator::new_dummy()
+            .with_min_overlap(30)
+            .group_for_slot(9, 2, &anchor)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_vgg_group_from_source() {
+        let hm: HashMap<Pubkey, Pubkey> = (0..30)
+            .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+            .collect();
+        let anchor = Hash::new_unique();
+        let sources: Vec<Box<dyn SeedSource>> = vec![
+            Box::new(EpochAnchoredSeed::new(2, anchor)),
+            Box::new(BlockhashSeed::new(anchor)),
+            Box::new(FixedSeed(7)),
+        ];
+        for vgg in &[
+            VoteGroupGenerator::new(&hm, 10),
+            VoteGroupGenerator::new(&hm, 10).with_min_overlap(30),
+        ] {
+            for source in &sources {
+                let group = vgg.group_from_source(source.as_ref(), 9);
+                assert_eq!(group.len(), 10);
+                for key in hm.keys() {
+                    assert_eq!(
+                        group.contains(key),
+                        vgg.in_group_from_source(source.as_ref(), 9, *key)
+                    );
+                }
+            }
+            assert_eq!(
+                vgg.group_from_source(&EpochAnchoredSeed::new(2, anchor), 9),
+                vgg.group_for_slot(9, 2, &anchor)
+            );
+        }
+
+        let vgg = VoteGroupGenerator::new(&hm, 10);
+        assert_eq!(
+            vgg.group_from_source(&FixedSeed(7), 9),
+            vgg.group_for_seed(7)
+        );
+        assert_eq!(
+            vgg.group_from_source(&BlockhashSeed::new(anchor), 9),
+            vgg.group_for_hash(anchor)
+        );
+    }
+
+    #[test]
+    fn test_vgg_empty_voter_set_does_not_panic() {
+        let vgg = VoteGroupGenerator::new_dummy();
+        for algorithm in &[
+            SelectionAlgorithm::V1,
+            SelectionAlgorithm::V2,
+            SelectionAlgorithm::V3,
+        ] {
+            let vgg = vgg.clone().with_algorithm(*algorithm);
+            assert!(vgg.group_for_seed(u64::MAX).is_empty());
+            assert!(!vgg.in_group_for_seed(3, Pubkey::new_unique()));
+            assert!(!vgg.in_group_for_hash(Hash::new_unique(), Pubkey::default()));
+        }
+        assert!(vgg.check_state().is_ok());
+    }
+
+    #[test]
+    fn test_vgg_check_state() {
+        let hm: HashMap<Pubkey, Pubkey> = (0..10)
+            .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+            .collect();
+        let stakes: HashMap<Pubkey, u64> = hm.keys().map(|key| (*key, 10)).collect();
+        let vgg = VoteGroupGenerator::new(&hm, 3);
+        assert!(vgg.check_state().is_ok());
+        let weighted =
+            VoteGroupGenerator::new_stake_weighted(&hm, &stakes, 3, &ExclusionList::default());
+        assert!(weighted.check_state().is_ok());
+
+        let mut unsorted = vgg.clone();
+        let mut swapped = vgg.voters().to_vec();
+        swapped.swap(0, 1);
+        unsorted.possible_voters = swapped.into();
+        assert!(matches!(
+            unsorted.check_state(),
+            Err(VoteGroupError::InconsistentState(_))
+        ));
+
+        let mut no_distances = vgg.clone();
+        no_distances.all_distance.clear();
+        assert!(no_distances.check_state().is_err());
+
+        let mut short_table = weighted.clone();
+        short_table.possible_voters = weighted.voters()[..9].into();
+        short_table.all_distance = VoteGroupGenerator::distances_for(9, short_table.algorithm);
+        assert!(short_table.check_state().is_err());
+
+        // the same checks hold for a generator that went through bincode
+        let bytes = bincode::serialize(&weighted).unwrap();
+        let decoded: VoteGroupGenerator = bincode::deserialize(&bytes).unwrap();
+        assert!(decoded.check_state().is_ok());
+    }
+
+    #[test]
+    fn test_vgg_clone_shares_voters() {
+        let hm: HashMap<Pubkey, Pubkey> = (0..10)
+            .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+            .collect();
+        let vgg = VoteGroupGenerator::new(&hm, 3);
+        let mut cloned = vgg.clone();
+        assert!(Arc::ptr_eq(&vgg.possible_voters, &cloned.possible_voters));
+
+        // changing the voters of one copy leaves the other alone
+        assert!(cloned.add_voter(Pubkey::new_unique(), 0));
+        assert!(!Arc::ptr_eq(&vgg.possible_voters, &cloned.possible_voters));
+        assert_eq!(vgg.voters().len(), 10);
+        assert_eq!(cloned.voters().len(), 11);
+        assert!(vgg.check_state().is_ok());
+        assert!(cloned.check_state().is_ok());
+    }
+
+    #[test]
+    fn test_vgg_human_readable() {
+        let hm: HashMap<Pubkey, Pubkey> = (0..9)
+            .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+            .collect();
+        let vgg = VoteGroupGenerator::new(&hm, 4);
+        let json = serde_json::to_value(&vgg).unwrap();
+        let voters: Vec<String> = vgg.voters().iter().map(|key| key.to_string()).collect();
+        assert_eq!(json["possible_voters"], serde_json::json!(voters));
+        assert_eq!(json["group_size"], 4);
+        let decoded: VoteGroupGenerator = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded, vgg);
+
+        // bincode keeps the raw key bytes right after the length
+        let bytes = bincode::serialize(&vgg).unwrap();
+        assert_eq!(&bytes[8..40], vgg.voters()[0].as_ref());
+    }
+
+    #[test]
+    fn test_legacy_vote_group_generator() {
+        let mut keys: Vec<Pubkey> = (0..9).map(|_| Pubkey::new_unique()).collect();
+        let hm: HashMap<Pubkey, Pubkey> = keys.iter().map(|key| (*key, *key)).collect();
+        let vgg = VoteGroupGenerator::new(&hm, 4);
+
+        // the oldest generators kept the voters in HashMap order
+        keys.reverse();
+        let legacy = LegacyVoteGroupGenerator {
+            possible_voters: keys,
+            all_distance: VoteGroupGenerator::legacy_distances(9),
+            group_size: 4,
+        };
+        let restored = VoteGroupGenerator::from(legacy);
+        assert!(restored.check_state().is_ok());
+        assert_eq!(restored, vgg);
+
+        let downgraded = LegacyVoteGroupGenerator::try_from(&vgg).unwrap();
+        assert_eq!(downgraded.possible_voters, vgg.voters());
+        assert_eq!(VoteGroupGenerator::from(downgraded), vgg);
+
+        // settings the old layout can't hold are refused rather than dropped
+        let v2 = vgg.clone().with_algorithm(SelectionAlgorithm::V2);
+        assert!(matches!(
+            LegacyVoteGroupGenerator::try_from(&v2),
+            Err(VoteGroupError::LegacyLayout(_))
+        ));
+        let distinct = vgg.clone().with_distinct_members(true);
+        assert!(matches!(
+            LegacyVoteGroupGenerator::try_from(&distinct),
+            Err(VoteGroupError::LegacyLayout(_))
+        ));
+    }
+
+    /// generated voter sets, sizes, seeds and configurations, checked against the
+    /// invariants every selection has to keep whatever algorithm picked it. the cases come
+    /// from a fixed seed so a failure names a case that can be replayed
+    #[test]
+    fn test_vgg_selection_properties() {
+        use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+        let mut rng = StdRng::seed_from_u64(0x5afe_c014);
+        let never_voter = Pubkey::from_str(SAFECOIN_NEVER_VOTER).unwrap();
+        let algorithms = [
+            SelectionAlgorithm::V1,
+            SelectionAlgorithm::V2,
+            SelectionAlgorithm::V3,
+        ];
+        for case in 0..300 {
+            let len = rng.gen_range(1, 64);
+            let size = rng.gen_range(1, 2 * OPTIMAL_VOTE_GROUP_SIZE);
+            let mut voters: Vec<Pubkey> = (0..len).map(|_| Pubkey::new_unique()).collect();
+            voters.push(never_voter);
+            let stakes: HashMap<Pubkey, u64> = voters
+                .iter()
+                .map(|key| (*key, rng.gen_range(0, 4) * rng.gen_range(1, 1_000_000)))
+                .collect();
+            let algorithm = *algorithms.choose(&mut rng).unwrap();
+            let distinct = rng.gen::<bool>();
+            let mode = rng.gen_range(0, 3);
+
+            let build = |voters: &[Pubkey]| {
+                let hm: HashMap<Pubkey, Pubkey> = voters.iter().map(|key| (*key, *key)).collect();
+                let vgg = match mode {
+                    0 => VoteGroupGenerator::new(&hm, size),
+                    1 => VoteGroupGenerator::new_stake_weighted(
+                        &hm,
+                        &stakes,
+                        size,
+                        &ExclusionList::legacy(),
+                    ),
+                    _ => VoteGroupGenerator::new(&hm, size).with_min_overlap(30),
+                };
+                vgg.with_algorithm(algorithm)
+                    .with_distinct_members(distinct)
+            };
+            let vgg = build(&voters);
+            // the same input in another order builds the same generator
+            let mut shuffled = voters.clone();
+            shuffled.shuffle(&mut rng);
+            let other = build(&shuffled);
+            assert_eq!(vgg, other, "case {}", case);
+
+            // a weighted generator without any stake falls back to uniform groups
+            let weighted = mode == 1
+                && voters
+                    .iter()
+                    .any(|key| *key != never_voter && stakes[key] > 0);
+            let eligible: HashSet<Pubkey> = voters
+                .iter()
+                .filter(|key| **key != never_voter)
+                .filter(|key| !weighted || stakes[key] > 0)
+                .copied()
+                .collect();
+            let always_distinct =
+                algorithm != SelectionAlgorithm::V1 || distinct || weighted || mode == 2;
+            for _ in 0..20 {
+                let seed = rng.gen::<u64>();
+                let group = if mode == 2 {
+                    let (prev_seed, next_seed) = (rng.gen::<u64>(), rng.gen::<u64>());
+                    vgg.group_for_consecutive_seeds(prev_seed, seed, next_seed)
+                } else {
+                    vgg.group_for_seed(seed)
+                };
+                assert!(!group.contains(&never_voter), "case {}", case);
+                assert!(
+                    group.iter().all(|key| eligible.contains(key)),
+                    "case {}",
+                    case
+                );
+                assert_eq!(group.len(), size.min(eligible.len()), "case {}", case);
+                let members: HashSet<Pubkey> = group.iter().copied().collect();
+                if always_distinct {
+                    assert_eq!(members.len(), group.len(), "case {}", case);
+                }
+                assert_eq!(
+                    vgg.group_for_seed(seed),
+                    other.group_for_seed(seed),
+                    "case {}",
+                    case
+                );
+                let plain: HashSet<Pubkey> = vgg.group_for_seed(seed).into_iter().collect();
+                for key in &voters {
+                    assert_eq!(
+                        vgg.in_group_for_seed(seed, *key),
+                        plain.contains(key),
+                        "case {}",
+                        case
+                    );
+                }
+            }
+        }
+    }
+}
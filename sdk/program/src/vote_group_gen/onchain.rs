@@ -0,0 +1,158 @@
+//! Borsh encoding of a generator, the canonical format on-chain programs and their clients
+//! read generator state in
+//!
+//! The group cache, voter set root and authorized voters are never encoded and the group
+//! size always goes out as a u64 so the layout doesn't depend on the pointer width of
+//! whoever wrote it. Decoding runs the same consistency check as any other generator that
+//! came off the wire.
+
+use super::{
+    alias_table::AliasTable, authorized_voters::AuthorizedVoters, group_cache::GroupCache,
+    voter_set::VoterSetRoot, SelectionAlgorithm, SelectionMode, VoteGroupGenerator,
+};
+use crate::pubkey::Pubkey;
+use borsh::{
+    maybestd::io::{Error, ErrorKind, Result, Write},
+    schema::{Declaration, Definition, Fields},
+    BorshDeserialize, BorshSchema, BorshSerialize,
+};
+use std::{collections::HashMap, convert::TryFrom};
+
+impl BorshSerialize for VoteGroupGenerator {
+    fn serialize<W: Write>(&self, writer: &mut W) -> Result<()> {
+        self.possible_voters.serialize(writer)?;
+        self.all_distance.serialize(writer)?;
+        (self.group_size as u64).serialize(writer)?;
+        self.ordering_version.serialize(writer)?;
+        self.selection_mode.serialize(writer)?;
+        self.alias_table.serialize(writer)?;
+        self.algorithm.serialize(writer)?;
+        self.distinct_members.serialize(writer)
+    }
+}
+
+impl BorshDeserialize for VoteGroupGenerator {
+    fn deserialize(buf: &mut &[u8]) -> Result<Self> {
+        let possible_voters = Vec::<Pubkey>::deserialize(buf)?;
+        let voter_set_root = VoterSetRoot::new(&possible_voters);
+        let all_distance = Vec::<u32>::deserialize(buf)?;
+        let group_size = usize::try_from(u64::deserialize(buf)?)
+            .map_err(|_| Error::new(ErrorKind::InvalidData, "group size overflows usize"))?;
+        let generator = Self {
+            possible_voters: possible_voters.into(),
+            all_distance,
+            group_size,
+            ordering_version: u8::deserialize(buf)?,
+            selection_mode: SelectionMode::deserialize(buf)?,
+            alias_table: AliasTable::deserialize(buf)?,
+            algorithm: SelectionAlgorithm::deserialize(buf)?,
+            distinct_members: bool::deserialize(buf)?,
+            group_cache: GroupCache::default(),
+            voter_set_root,
+            authorized_voters: AuthorizedVoters::default(),
+        };
+        generator
+            .check_state()
+            .map_err(|err| Error::new(ErrorKind::InvalidData, err.to_string()))?;
+        Ok(generator)
+    }
+}
+
+impl BorshSchema for VoteGroupGenerator {
+    fn add_definitions_recursively(definitions: &mut HashMap<Declaration, Definition>) {
+        let fields = Fields::NamedFields(vec![
+            ("possible_voters".to_string(), Vec::<Pubkey>::declaration()),
+            ("all_distance".to_string(), Vec::<u32>::declaration()),
+            ("group_size".to_string(), u64::declaration()),
+            ("ordering_version".to_string(), u8::declaration()),
+            ("selection_mode".to_string(), SelectionMode::declaration()),
+            ("alias_table".to_string(), AliasTable::declaration()),
+            ("algorithm".to_string(), SelectionAlgorithm::declaration()),
+            ("distinct_members".to_string(), bool::declaration()),
+        ]);
+        Self::add_definition(
+            Self::declaration(),
+            Definition::Struct { fields },
+            definitions,
+        );
+        Vec::<Pubkey>::add_definitions_recursively(definitions);
+        Vec::<u32>::add_definitions_recursively(definitions);
+        SelectionMode::add_definitions_recursively(definitions);
+        AliasTable::add_definitions_recursively(definitions);
+        SelectionAlgorithm::add_definitions_recursively(definitions);
+    }
+
+    fn declaration() -> Declaration {
+        "VoteGroupGenerator".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vote_group_gen::{ExclusionList, VoteGroupError};
+
+    fn voters(n: usize) -> HashMap<Pubkey, Pubkey> {
+        (0..n)
+            .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+            .collect()
+    }
+
+    #[test]
+    fn test_borsh_round_trip() {
+        let map = voters(12);
+        let stakes: HashMap<Pubkey, u64> = map.keys().map(|key| (*key, 42)).collect();
+        for vgg in &[
+            VoteGroupGenerator::new(&map, 5),
+            VoteGroupGenerator::new(&map, 5)
+                .with_algorithm(SelectionAlgorithm::V2)
+                .with_distinct_members(true),
+            VoteGroupGenerator::new_stake_weighted(&map, &stakes, 4, &ExclusionList::default()),
+            VoteGroupGenerator::new_dummy(),
+        ] {
+            // fill the cache first, it must not end up in the encoding
+            let _ = vgg.in_group_for_seed(7, Pubkey::new_unique());
+            let bytes = vgg.try_to_vec().unwrap();
+            let decoded = VoteGroupGenerator::try_from_slice(&bytes).unwrap();
+            assert_eq!(&decoded, vgg);
+            assert_eq!(decoded.group_for_seed(7), vgg.group_for_seed(7));
+            assert_eq!(
+                crate::borsh::get_instance_packed_len(vgg).unwrap(),
+                bytes.len()
+            );
+        }
+    }
+
+    #[test]
+    fn test_borsh_layout() {
+        let vgg = VoteGroupGenerator::new(&voters(3), 2);
+        let bytes = vgg.try_to_vec().unwrap();
+        // voters, then the v1 distances for 3 voters
+        let distances = 4 + 4 * vgg.all_distance.len();
+        let size_at = 4 + 3 * 32 + distances;
+        assert_eq!(&bytes[size_at..size_at + 8], &2u64.to_le_bytes());
+
+        let mut definitions = HashMap::new();
+        VoteGroupGenerator::add_definitions_recursively(&mut definitions);
+        assert!(definitions.contains_key("VoteGroupGenerator"));
+        assert!(definitions.contains_key("AliasTable"));
+        assert!(definitions.contains_key("SelectionAlgorithm"));
+    }
+
+    #[test]
+    fn test_borsh_rejects_inconsistent_state() {
+        let mut vgg = VoteGroupGenerator::new(&voters(4), 2);
+        let mut reversed = vgg.possible_voters.to_vec();
+        reversed.reverse();
+        vgg.possible_voters = reversed.into();
+        let bytes = vgg.try_to_vec().unwrap();
+        let err = VoteGroupGenerator::try_from_slice(&bytes).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        assert!(err.to_string().contains(
+            &VoteGroupError::InconsistentState("voters are not sorted and unique".into())
+                .to_string()
+        ));
+
+        assert!(VoteGroupGenerator::try_from_slice(&bytes[..bytes.len() - 1]).is_err());
+    }
+}
@@ -0,0 +1,147 @@
+//! The vote group of every slot in an epoch, computed once up front
+//!
+//! Groups are seeded with `seed_for_slot` so the whole epoch can be laid out as soon as
+//! the epoch's generator and anchor hash are known, the same way a leader schedule is.
+
+use super::{EpochAnchoredSeed, VoteGroupGenerator};
+use crate::{
+    clock::{Epoch, Slot},
+    hash::Hash,
+    pubkey::Pubkey,
+};
+
+#[derive(Debug, Default, PartialEq)]
+pub struct VoteGroupSchedule {
+    epoch: Epoch,
+    first_slot: Slot,
+    voters: Vec<Pubkey>,
+    // members of slot i are voters[members[offsets[i]..offsets[i + 1]]]
+    members: Vec<u32>,
+    offsets: Vec<u32>,
+}
+
+impl VoteGroupSchedule {
+    pub fn new(
+        generator: &VoteGroupGenerator,
+        epoch: Epoch,
+        first_slot: Slot,
+        num_slots: u64,
+        anchor: &Hash,
+    ) -> Self {
+        let mut members = Vec::with_capacity(num_slots as usize * generator.group_size());
+        let mut offsets = Vec::with_capacity(num_slots as usize + 1);
+        offsets.push(0);
+        if !generator.voters().is_empty() {
+            let source = EpochAnchoredSeed::new(epoch, *anchor);
+            for slot in first_slot..first_slot + num_slots {
+                members.extend(
+                    generator
+                        .slot_group_indexes(&source, slot)
+                        .into_iter()
+                        .map(|i| i as u32),
+                );
+                offsets.push(members.len() as u32);
+            }
+        }
+        Self {
+            epoch,
+            first_slot,
+            voters: generator.voters().to_vec(),
+            members,
+            offsets,
+        }
+    }
+
+    pub fn epoch(&self) -> Epoch {
+        self.epoch
+    }
+
+    pub fn first_slot(&self) -> Slot {
+        self.first_slot
+    }
+
+    pub fn num_slots(&self) -> usize {
+        self.offsets.len().saturating_sub(1)
+    }
+
+    fn slot_range(&self, slot: Slot) -> Option<(usize, usize)> {
+        let index = slot.checked_sub(self.first_slot)? as usize;
+        if index >= self.num_slots() {
+            return None;
+        }
+        Some((
+            self.offsets[index] as usize,
+            self.offsets[index + 1] as usize,
+        ))
+    }
+
+    /// the group for `slot` in selection order, None if the slot isn't in this epoch
+    pub fn group_at(&self, slot: Slot) -> Option<Vec<Pubkey>> {
+        let (start, end) = self.slot_range(slot)?;
+        Some(
+            self.members[start..end]
+                .iter()
+                .map(|i| self.voters[*i as usize])
+                .collect(),
+        )
+    }
+
+    pub fn in_group_at(&self, slot: Slot, key: &Pubkey) -> Option<bool> {
+        let (start, end) = self.slot_range(slot)?;
+        Some(
+            self.members[start..end]
+                .iter()
+                .any(|i| self.voters[*i as usize] == *key),
+        )
+    }
+
+    /// slots of this epoch where `key` is a group member
+    pub fn slots_for(&self, key: &Pubkey) -> Vec<Slot> {
+        (0..self.num_slots())
+            .filter(|index| {
+                let (start, end) = (self.offsets[*index], self.offsets[*index + 1]);
+                self.members[start as usize..end as usize]
+                    .iter()
+                    .any(|i| self.voters[*i as usize] == *key)
+            })
+            .map(|index| self.first_slot + index as Slot)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_vote_group_schedule() {
+        let hm: HashMap<Pubkey, Pubkey> = (0..30)
+            .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+            .collect();
+        let vgg = VoteGroupGenerator::new(&hm, 5);
+        let anchor = Hash::new_unique();
+        let schedule = VoteGroupSchedule::new(&vgg, 3, 96, 32, &anchor);
+        assert_eq!(schedule.epoch(), 3);
+        assert_eq!(schedule.num_slots(), 32);
+        assert_eq!(schedule.group_at(95), None);
+        assert_eq!(schedule.group_at(128), None);
+        assert_eq!(schedule.in_group_at(128, &Pubkey::new_unique()), None);
+        for slot in 96..128 {
+            let group = vgg.group_for_slot(slot, 3, &anchor);
+            assert_eq!(schedule.group_at(slot), Some(group.clone()));
+            for key in hm.keys() {
+                assert_eq!(schedule.in_group_at(slot, key), Some(group.contains(key)));
+                assert_eq!(schedule.slots_for(key).contains(&slot), group.contains(key));
+            }
+        }
+    }
+
+    #[test]
+    fn test_vote_group_schedule_no_voters() {
+        let schedule =
+            VoteGroupSchedule::new(&VoteGroupGenerator::new_dummy(), 0, 0, 32, &Hash::default());
+        assert_eq!(schedule.num_slots(), 0);
+        assert_eq!(schedule.group_at(0), None);
+    }
+}
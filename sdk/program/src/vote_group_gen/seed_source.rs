@@ -0,0 +1,277 @@
+//! Where the seed of every slot's vote group comes from
+//!
+//! The generator only needs a seed per slot, a SeedSource decides how that seed is derived.
+//! EpochAnchoredSeed only mixes the slot and epoch with a hash that was fixed long before
+//! the slot, the anchor captured along with the epoch's stakes. The blockhash a leader
+//! produces for the slot plays no part, so grinding it can't steer the slot's group.
+//! BlockhashSeed is what groups were seeded with before that, and FixedSeed gives every
+//! slot the same seed for tests. WindowedSeed wraps another source so a group lasts for a
+//! window of slots, every slot of the window takes the seed of its first slot.
+//!
+//! SeedDerivation versions how a hash becomes a seed. V1 xor-folds the hash's 8 byte words,
+//! which keeps nothing of its structure and lets anyone who controls part of the hash cancel
+//! out the rest. V2 hashes the hash again behind its own context string and keeps the
+//! first 8 bytes, with the same sha256 every target has so BPF programs derive the same seeds.
+
+use super::{hash_to_seed, SEED_DOMAIN};
+use crate::{
+    clock::{Epoch, Slot},
+    epoch_schedule::EpochSchedule,
+    hash::{hashv, Hash},
+};
+
+/// context string SeedDerivation::V2 prefixes the hash with, keeps its seeds apart from
+/// every other derivation out of the same hash
+pub const SEED_DERIVATION_V2_CONTEXT: &str = "safecoin vote group seed derivation v2";
+
+/// version of the hash to seed derivation, switched cluster wide by feature gate
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SeedDerivation {
+    /// the hash's 8 byte words xor-ed together
+    V1,
+    /// the first 8 bytes of the hash hashed again under SEED_DERIVATION_V2_CONTEXT
+    V2,
+}
+
+impl Default for SeedDerivation {
+    fn default() -> Self {
+        SeedDerivation::V1
+    }
+}
+
+impl SeedDerivation {
+    pub fn seed_from_hash(&self, hash: &Hash) -> u64 {
+        match self {
+            SeedDerivation::V1 => hash_to_seed(*hash),
+            SeedDerivation::V2 => {
+                let mixed = hashv(&[SEED_DERIVATION_V2_CONTEXT.as_bytes(), hash.as_ref()]);
+                let mut word = [0u8; 8];
+                word.copy_from_slice(&mixed.as_ref()[..8]);
+                u64::from_le_bytes(word)
+            }
+        }
+    }
+}
+
+pub trait SeedSource {
+    /// the seed every consumer must use for the group of `slot`
+    fn seed_for(&self, slot: Slot) -> u64;
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EpochAnchoredSeed {
+    epoch: Epoch,
+    anchor: Hash,
+}
+
+impl EpochAnchoredSeed {
+    /// `anchor` is the bank hash captured when `epoch`'s stakes were, an epoch or more
+    /// before `epoch` starts
+    pub fn new(epoch: Epoch, anchor: Hash) -> Self {
+        Self { epoch, anchor }
+    }
+
+    pub fn epoch(&self) -> Epoch {
+        self.epoch
+    }
+
+    pub fn anchor(&self) -> &Hash {
+        &self.anchor
+    }
+
+    /// whether `slot` belongs to this source's epoch, seeds for slots of other epochs have
+    /// to come from the source of their own epoch
+    pub fn covers(&self, epoch_schedule: &EpochSchedule, slot: Slot) -> bool {
+        epoch_schedule.get_epoch(slot) == self.epoch
+    }
+}
+
+impl SeedSource for EpochAnchoredSeed {
+    fn seed_for(&self, slot: Slot) -> u64 {
+        let hash = hashv(&[
+            SEED_DOMAIN,
+            &slot.to_le_bytes(),
+            &self.epoch.to_le_bytes(),
+            self.anchor.as_ref(),
+        ]);
+        let mut word = [0u8; 8];
+        word.copy_from_slice(&hash.as_ref()[..8]);
+        u64::from_le_bytes(word)
+    }
+}
+
+/// the hash the slot's bank was voted on turned into a seed, the slot itself plays no part
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BlockhashSeed {
+    hash: Hash,
+    derivation: SeedDerivation,
+}
+
+impl BlockhashSeed {
+    /// derives the seed with SeedDerivation::V1
+    pub fn new(hash: Hash) -> Self {
+        Self {
+            hash,
+            derivation: SeedDerivation::V1,
+        }
+    }
+
+    pub fn with_derivation(self, derivation: SeedDerivation) -> Self {
+        Self { derivation, ..self }
+    }
+}
+
+impl SeedSource for BlockhashSeed {
+    fn seed_for(&self, _slot: Slot) -> u64 {
+        self.derivation.seed_from_hash(&self.hash)
+    }
+}
+
+/// the same seed for every slot
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FixedSeed(pub u64);
+
+impl SeedSource for FixedSeed {
+    fn seed_for(&self, _slot: Slot) -> u64 {
+        self.0
+    }
+}
+
+/// the group of a window's first slot for every slot of the window
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WindowedSeed<S> {
+    source: S,
+    first_slot: Slot,
+    window_slots: u64,
+}
+
+impl<S> WindowedSeed<S> {
+    /// windows of `window_slots` slots are counted from `first_slot`, the first slot of the
+    /// epoch, so none of them straddles two epochs. 0 and 1 select a new group every slot
+    pub fn new(source: S, first_slot: Slot, window_slots: u64) -> Self {
+        Self {
+            source,
+            first_slot,
+            window_slots: window_slots.max(1),
+        }
+    }
+
+    pub fn source(&self) -> &S {
+        &self.source
+    }
+
+    pub fn window_slots(&self) -> u64 {
+        self.window_slots
+    }
+
+    /// the slot whose seed `slot` takes, slots before first_slot aren't windowed
+    pub fn window_start(&self, slot: Slot) -> Slot {
+        if slot < self.first_slot {
+            return slot;
+        }
+        slot - (slot - self.first_slot) % self.window_slots
+    }
+}
+
+impl<S: SeedSource> SeedSource for WindowedSeed<S> {
+    fn seed_for(&self, slot: Slot) -> u64 {
+        self.source.seed_for(self.window_start(slot))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{hash::hash, vote_group_gen::seed_from_bytes};
+
+    #[test]
+    fn test_seed_source() {
+        let anchor = Hash::new_unique();
+        let source = EpochAnchoredSeed::new(2, anchor);
+        assert_eq!(source.epoch(), 2);
+        assert_eq!(source.anchor(), &anchor);
+        assert_ne!(source.seed_for(70), source.seed_for(71));
+        assert_ne!(
+            source.seed_for(70),
+            EpochAnchoredSeed::new(3, anchor).seed_for(70)
+        );
+        assert_ne!(
+            source.seed_for(70),
+            EpochAnchoredSeed::new(2, Hash::new_unique()).seed_for(70)
+        );
+
+        let epoch_schedule = EpochSchedule::custom(32, 32, false);
+        assert!(source.covers(&epoch_schedule, 64));
+        assert!(source.covers(&epoch_schedule, 95));
+        assert!(!source.covers(&epoch_schedule, 96));
+        assert!(!source.covers(&epoch_schedule, 63));
+    }
+
+    #[test]
+    fn test_other_seed_sources() {
+        let hash = Hash::new_unique();
+        let blockhash = BlockhashSeed::new(hash);
+        assert_eq!(blockhash.seed_for(70), blockhash.seed_for(71));
+        assert_eq!(Ok(blockhash.seed_for(70)), seed_from_bytes(hash.as_ref()));
+        assert_eq!(FixedSeed(7).seed_for(70), 7);
+
+        let mixed = blockhash.with_derivation(SeedDerivation::V2);
+        assert_eq!(mixed.seed_for(70), SeedDerivation::V2.seed_from_hash(&hash));
+        assert_ne!(mixed.seed_for(70), blockhash.seed_for(70));
+    }
+
+    #[test]
+    fn test_windowed_seed() {
+        let source = EpochAnchoredSeed::new(2, Hash::new_unique());
+        let windowed = WindowedSeed::new(source, 64, 4);
+        assert_eq!(windowed.window_slots(), 4);
+        assert_eq!(windowed.window_start(64), 64);
+        assert_eq!(windowed.window_start(67), 64);
+        assert_eq!(windowed.window_start(68), 68);
+        assert_eq!(windowed.window_start(63), 63);
+        for slot in 64..68 {
+            assert_eq!(windowed.seed_for(slot), source.seed_for(64));
+        }
+        assert_eq!(windowed.seed_for(69), source.seed_for(68));
+        assert_ne!(windowed.seed_for(67), windowed.seed_for(68));
+
+        // windows are counted from the first slot, not from slot 0
+        assert_eq!(WindowedSeed::new(source, 10, 4).window_start(17), 14);
+
+        for window_slots in 0..=1 {
+            let windowed = WindowedSeed::new(source, 64, window_slots);
+            assert_eq!(windowed.window_slots(), 1);
+            assert_eq!(windowed.seed_for(70), source.seed_for(70));
+        }
+    }
+
+    #[test]
+    fn test_seed_derivation_vectors() {
+        // changing any of these changes every group seeded from a hash
+        let mut squares = [0u8; 32];
+        for (i, byte) in squares.iter_mut().enumerate() {
+            *byte = (i * i) as u8;
+        }
+        let vectors: [(Hash, u64, u64); 3] = [
+            (Hash::default(), 0, 11384611917053941443),
+            (
+                Hash::new_from_array(squares),
+                36099168992755712,
+                4544388029827768766,
+            ),
+            (hash(&[]), 2994429884826677498, 15717781808179221500),
+        ];
+        for (hash, v1, v2) in vectors.iter() {
+            assert_eq!(SeedDerivation::V1.seed_from_hash(hash), *v1);
+            assert_eq!(SeedDerivation::V2.seed_from_hash(hash), *v2);
+        }
+
+        // v1 cancels out a word repeated in the hash, v2 doesn't
+        let mut bytes = [0u8; 32];
+        bytes[..8].copy_from_slice(&[7u8; 8]);
+        bytes[8..16].copy_from_slice(&[7u8; 8]);
+        let hash = Hash::new_from_array(bytes);
+        assert_eq!(SeedDerivation::V1.seed_from_hash(&hash), 0);
+        assert_ne!(SeedDerivation::V2.seed_from_hash(&hash), 0);
+    }
+}
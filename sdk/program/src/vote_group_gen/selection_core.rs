@@ -0,0 +1,433 @@
+//! The selection math of the generator, without std collections
+//!
+//! Everything here works on slices the caller owns and never allocates, so it builds for the
+//! BPF target and an on-chain program can check group membership with the exact code the
+//! validators pick groups with. The walks write the selected voter indexes, in selection
+//! order, to the front of `out` and return how many they wrote, `out.len()` is the group
+//! size asked for. VoteGroupGenerator keeps the voters, distances and alias table and hands
+//! them to these functions, it never selects anything on its own.
+//!
+//! The walks that never pick a voter twice check every step against the voters they took.
+//! That is a scan of `out` unless the caller lends them a Taken bitset, which keeps groups
+//! of thousands of members out of quadratic time without changing what gets picked.
+
+use super::CHACHA_SEED_DOMAIN;
+use crate::hash::hashv;
+use rand_chacha::{rand_core::RngCore, rand_core::SeedableRng, ChaCha20Rng};
+
+/// distance re-derivations tried before a distinct v1 walk falls back to the next free voter
+pub const MAX_DISTANCE_RETRIES: usize = 8;
+
+/// walks up to this long scan `out`, that beats clearing a bitset over every voter
+pub const MAX_SCANNED_WALK: usize = 64;
+
+/// deterministic stream of pseudo random values, the same on every platform
+pub fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// a value in 0..bound without modulo bias, draws that land in the incomplete last
+/// stretch of the u64 range are thrown away
+pub fn uniform_below(rng: &mut ChaCha20Rng, bound: u64) -> u64 {
+    let zone = u64::MAX - u64::MAX % bound;
+    loop {
+        let value = rng.next_u64();
+        if value < zone {
+            return value % bound;
+        }
+    }
+}
+
+/// the voter indexes a walk already took, a bitset the caller owns with a bit for every
+/// voter. one that is too short, like an empty one, leaves the walk scanning `out`
+pub struct Taken<'a> {
+    words: &'a mut [u64],
+}
+
+impl<'a> Taken<'a> {
+    /// clears `words` so the walk starts with nothing taken
+    pub fn new(words: &'a mut [u64]) -> Self {
+        for word in words.iter_mut() {
+            *word = 0;
+        }
+        Self { words }
+    }
+
+    /// no bitset, every check scans `out`
+    pub fn scan() -> Taken<'static> {
+        Taken { words: &mut [] }
+    }
+
+    /// the words a walk of `walk_len` over `voters_len` voters should be lent, none for
+    /// walks short enough to scan
+    pub fn words_for(voters_len: usize, walk_len: usize) -> usize {
+        if walk_len <= MAX_SCANNED_WALK {
+            0
+        } else {
+            (voters_len + 63) / 64
+        }
+    }
+
+    /// whether `idx` is one of `taken`, the indexes written so far
+    fn contains(&self, taken: &[usize], idx: usize) -> bool {
+        match self.words.get(idx / 64) {
+            Some(word) => word & (1 << (idx % 64)) != 0,
+            None => taken.contains(&idx),
+        }
+    }
+
+    fn insert(&mut self, idx: usize) {
+        if let Some(word) = self.words.get_mut(idx / 64) {
+            *word |= 1 << (idx % 64);
+        }
+    }
+}
+
+pub fn ring_shift(voters_len: usize, a: usize, b: usize) -> usize {
+    (a + b) % voters_len
+}
+
+/// v1, the seed picks the start and the distance directly and members may repeat. an
+/// `out` that can hold every voter gets every voter
+pub fn ring_indexes_v1(
+    seed: u64,
+    voters_len: usize,
+    distances: &[u32],
+    out: &mut [usize],
+) -> usize {
+    if voters_len == 0 || out.is_empty() {
+        return 0;
+    }
+    if out.len() >= voters_len {
+        for (idx, slot) in out[..voters_len].iter_mut().enumerate() {
+            *slot = idx;
+        }
+        return voters_len;
+    }
+    let mut loc = (seed % voters_len as u64) as usize;
+    out[0] = loc;
+    if out.len() > 1 {
+        let choose_dist = seed % distances.len() as u64;
+        let dist = distances[choose_dist as usize] as usize;
+        for slot in out[1..].iter_mut() {
+            loc = ring_shift(voters_len, loc, dist);
+            *slot = loc;
+        }
+    }
+    out.len()
+}
+
+/// v1 with revisits detected, a taken position re-derives the distance from the seed
+/// (and after MAX_DISTANCE_RETRIES steps to the next free voter) so the group always has
+/// min(out.len(), voters) distinct members. `fallback_distances` is only called once the
+/// walk runs into itself and has to return coprime_distances(voters_len)
+pub fn ring_indexes_v1_distinct<F, D>(
+    seed: u64,
+    voters_len: usize,
+    distances: &[u32],
+    mut fallback_distances: F,
+    taken: &mut Taken,
+    out: &mut [usize],
+) -> usize
+where
+    F: FnMut() -> D,
+    D: AsRef<[u32]>,
+{
+    let target = out.len().min(voters_len);
+    if target == 0 {
+        return 0;
+    }
+    let mut loc = (seed % voters_len as u64) as usize;
+    out[0] = loc;
+    taken.insert(loc);
+    let choose_dist = seed % distances.len() as u64;
+    let mut dist = distances[choose_dist as usize] as usize;
+    let mut fallback: Option<(D, u64)> = None;
+    for len in 1..target {
+        let mut next = ring_shift(voters_len, loc, dist);
+        let mut retries = 0;
+        while taken.contains(&out[..len], next) {
+            if retries < MAX_DISTANCE_RETRIES {
+                let (distances, state) =
+                    fallback.get_or_insert_with(|| (fallback_distances(), seed));
+                let distances = distances.as_ref();
+                let pick = splitmix64(state) % distances.len() as u64;
+                dist = distances[pick as usize] as usize;
+                next = ring_shift(voters_len, loc, dist);
+            } else {
+                next = ring_shift(voters_len, next, 1);
+            }
+            retries += 1;
+        }
+        loc = next;
+        out[len] = loc;
+        taken.insert(loc);
+    }
+    target
+}
+
+/// v2, the start and distance come from independent halves of the mixed seed and a
+/// position that was already taken moves on to the next free voter
+pub fn ring_indexes_v2(
+    seed: u64,
+    voters_len: usize,
+    distances: &[u32],
+    taken: &mut Taken,
+    out: &mut [usize],
+) -> usize {
+    let target = out.len().min(voters_len);
+    if target == 0 {
+        return 0;
+    }
+    let mut state = seed;
+    let mixed = splitmix64(&mut state);
+    let mut loc = ((mixed & 0xFFFF_FFFF) % voters_len as u64) as usize;
+    let choose_dist = (mixed >> 32) % distances.len() as u64;
+    let dist = distances[choose_dist as usize] as usize;
+    for len in 0..target {
+        while taken.contains(&out[..len], loc) {
+            loc = ring_shift(voters_len, loc, 1);
+        }
+        out[len] = loc;
+        taken.insert(loc);
+        loc = ring_shift(voters_len, loc, dist);
+    }
+    target
+}
+
+/// v3, a partial Fisher-Yates shuffle of all voters driven by a ChaCha20 stream keyed with
+/// the hashed seed. the positions each step swaps into place are drawn first, then the
+/// voter every one of them held is traced back through the earlier swaps
+pub fn chacha_indexes(seed: u64, voters_len: usize, out: &mut [usize]) -> usize {
+    let target = out.len().min(voters_len);
+    let key = hashv(&[CHACHA_SEED_DOMAIN, &seed.to_le_bytes()]);
+    let mut rng = ChaCha20Rng::from_seed(key.to_bytes());
+    for (step, slot) in out[..target].iter_mut().enumerate() {
+        *slot = step + uniform_below(&mut rng, (voters_len - step) as u64) as usize;
+    }
+    for step in (0..target).rev() {
+        out[step] = held_at(&out[..step], out[step]);
+    }
+    target
+}
+
+/// the voter at `position` once the swaps of `picks` were made, a step swaps the voter its
+/// own position held into the position it picked
+fn held_at(picks: &[usize], mut position: usize) -> usize {
+    let mut steps = picks.len();
+    while let Some(step) = picks[..steps].iter().rposition(|pick| *pick == position) {
+        position = step;
+        steps = step;
+    }
+    position
+}
+
+/// the columns of a Walker/Vose alias table, see AliasTable
+#[derive(Clone, Copy, Debug)]
+pub struct AliasColumns<'a> {
+    pub weights: &'a [u64],
+    /// how much of each column belongs to its own index, out of `total`
+    pub prob: &'a [u64],
+    /// the index that owns the rest of the column
+    pub alias: &'a [u32],
+    pub total: u64,
+}
+
+impl<'a> AliasColumns<'a> {
+    pub fn len(&self) -> usize {
+        self.prob.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.prob.is_empty()
+    }
+
+    /// picks an index from two independent random values
+    pub fn sample(&self, column: u64, coin: u64) -> usize {
+        let idx = (column % self.prob.len() as u64) as usize;
+        if coin % self.total < self.prob[idx] {
+            idx
+        } else {
+            self.alias[idx] as usize
+        }
+    }
+
+    /// up to `out.len()` distinct indexes drawn from a stream seeded by `seed`, never one in
+    /// `excluded`, which has to be sorted. every draw is O(1), the weights are only scanned when fewer indexes can
+    /// be picked than asked for or a few huge weights starve the draws
+    pub fn sample_distinct(
+        &self,
+        seed: u64,
+        excluded: &[usize],
+        taken: &mut Taken,
+        out: &mut [usize],
+    ) -> usize {
+        if self.is_empty() {
+            return 0;
+        }
+        let count = out.len();
+        let target = if count + excluded.len() >= self.len() {
+            count.min(self.pickable(excluded).count())
+        } else {
+            count
+        };
+        let mut len = 0;
+        let mut state = seed;
+        let mut attempts = target * 32;
+        while len < target && attempts > 0 {
+            let column = splitmix64(&mut state);
+            let coin = splitmix64(&mut state);
+            let idx = self.sample(column, coin);
+            if !taken.contains(&out[..len], idx) && excluded.binary_search(&idx).is_err() {
+                out[len] = idx;
+                taken.insert(idx);
+                len += 1;
+            }
+            attempts -= 1;
+        }
+        // top up deterministically in index order
+        for idx in self.pickable(excluded) {
+            if len >= target {
+                break;
+            }
+            if !taken.contains(&out[..len], idx) {
+                out[len] = idx;
+                taken.insert(idx);
+                len += 1;
+            }
+        }
+        len
+    }
+
+    fn pickable(&self, excluded: &'a [usize]) -> impl Iterator<Item = usize> + 'a {
+        let weights = self.weights;
+        (0..weights.len())
+            .filter(move |idx| weights[*idx] > 0 && excluded.binary_search(idx).is_err())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_core_walks_fit_out() {
+        let distances = [1, 3, 7];
+        for seed in 0..64 {
+            let mut out = [usize::MAX; 4];
+            assert_eq!(ring_indexes_v1(seed, 10, &distances, &mut out), 4);
+            assert!(out.iter().all(|idx| *idx < 10));
+
+            let mut v2 = [usize::MAX; 4];
+            let mut v3 = [usize::MAX; 4];
+            assert_eq!(
+                ring_indexes_v2(seed, 10, &distances, &mut Taken::scan(), &mut v2),
+                4
+            );
+            assert_eq!(chacha_indexes(seed, 10, &mut v3), 4);
+            for mut group in [v2, v3].iter().copied() {
+                group.sort_unstable();
+                assert!(group.windows(2).all(|pair| pair[0] < pair[1]));
+                assert!(group[3] < 10);
+            }
+
+            // more room than voters
+            let mut out = [usize::MAX; 6];
+            assert_eq!(chacha_indexes(seed, 3, &mut out), 3);
+            let mut sorted = [out[0], out[1], out[2]];
+            sorted.sort_unstable();
+            assert_eq!(sorted, [0, 1, 2]);
+            assert_eq!(out[3..], [usize::MAX; 3]);
+        }
+        assert_eq!(
+            ring_indexes_v2(5, 0, &distances, &mut Taken::scan(), &mut [0; 4]),
+            0
+        );
+    }
+
+    #[test]
+    fn test_taken_walks_match_scans() {
+        let voters_len = 1_000;
+        let distances = [1, 3, 7, 11, 13];
+        let weights: Vec<u64> = (0..voters_len as u64).map(|w| w % 7).collect();
+        let table = crate::vote_group_gen::AliasTable::new(&weights);
+        let mut words = [0; 16];
+        for seed in 0..16 {
+            for len in [1, 65, 400, voters_len].iter().copied() {
+                let mut scanned = vec![0; len];
+                let mut bits = vec![0; len];
+                ring_indexes_v1_distinct(
+                    seed,
+                    voters_len,
+                    &distances[..1],
+                    || distances,
+                    &mut Taken::scan(),
+                    &mut scanned,
+                );
+                ring_indexes_v1_distinct(
+                    seed,
+                    voters_len,
+                    &distances[..1],
+                    || distances,
+                    &mut Taken::new(&mut words),
+                    &mut bits,
+                );
+                assert_eq!(scanned, bits);
+
+                ring_indexes_v2(
+                    seed,
+                    voters_len,
+                    &distances,
+                    &mut Taken::scan(),
+                    &mut scanned,
+                );
+                ring_indexes_v2(
+                    seed,
+                    voters_len,
+                    &distances,
+                    &mut Taken::new(&mut words),
+                    &mut bits,
+                );
+                assert_eq!(scanned, bits);
+
+                let excluded = [3, 10, 500];
+                let columns = table.columns();
+                let scanned_len =
+                    columns.sample_distinct(seed, &excluded, &mut Taken::scan(), &mut scanned);
+                let bits_len = columns.sample_distinct(
+                    seed,
+                    &excluded,
+                    &mut Taken::new(&mut words),
+                    &mut bits,
+                );
+                assert_eq!(scanned[..scanned_len], bits[..bits_len]);
+            }
+        }
+        // a bitset too short for the voters falls back to the scan
+        let mut scanned = vec![0; 300];
+        let mut short = vec![0; 300];
+        ring_indexes_v2(7, voters_len, &distances, &mut Taken::scan(), &mut scanned);
+        ring_indexes_v2(
+            7,
+            voters_len,
+            &distances,
+            &mut Taken::new(&mut words[..4]),
+            &mut short,
+        );
+        assert_eq!(scanned, short);
+    }
+
+    #[test]
+    fn test_held_at() {
+        // step 0 swaps 0 with 2, step 1 swaps 1 with 2 which now holds voter 0
+        let picks = [2, 2];
+        assert_eq!(held_at(&picks[..0], 2), 2);
+        assert_eq!(held_at(&picks[..1], 2), 0);
+        assert_eq!(held_at(&picks, 2), 1);
+        assert_eq!(held_at(&picks, 3), 3);
+    }
+}
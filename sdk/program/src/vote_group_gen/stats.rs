@@ -0,0 +1,288 @@
+//! Measures how evenly a generator spreads group membership over its voters
+//!
+//! Everything here walks the groups of a range of seeds, so it is meant for tests and
+//! offline analysis rather than anything on the vote path. Voters are always indexed in
+//! the generator's canonical order, the same order `VoteGroupGenerator::voters` returns.
+
+use super::VoteGroupGenerator;
+
+/// z-scores of the one sided significance levels the uniformity test is usually run at
+pub const Z_SCORE_95: f64 = 1.645;
+pub const Z_SCORE_99: f64 = 2.326;
+pub const Z_SCORE_999: f64 = 3.090;
+
+/// canonical indexes of the members of one group, a voter the walk visited twice is
+/// only counted once since it only gets to vote once
+fn member_indexes(generator: &VoteGroupGenerator, seed: u64) -> Vec<usize> {
+    let voters = generator.voters();
+    let mut indexes: Vec<usize> = generator
+        .group_for_seed(seed)
+        .iter()
+        .filter_map(|member| voters.binary_search(member).ok())
+        .collect();
+    indexes.sort_unstable();
+    indexes.dedup();
+    indexes
+}
+
+/// how many of the groups for `seeds` each voter was a member of
+pub fn hit_counts<I>(generator: &VoteGroupGenerator, seeds: I) -> Vec<u64>
+where
+    I: IntoIterator<Item = u64>,
+{
+    let mut counts = vec![0; generator.voters().len()];
+    for seed in seeds {
+        for i in member_indexes(generator, seed) {
+            counts[i] += 1;
+        }
+    }
+    counts
+}
+
+/// result of Pearson's chi-square test of the hit counts against every voter being
+/// selected equally often
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ChiSquare {
+    pub statistic: f64,
+    pub degrees_of_freedom: u64,
+}
+
+impl ChiSquare {
+    pub fn new(counts: &[u64]) -> Self {
+        let total: u64 = counts.iter().sum();
+        if counts.len() < 2 || total == 0 {
+            return Self {
+                statistic: 0.,
+                degrees_of_freedom: 0,
+            };
+        }
+        let expected = total as f64 / counts.len() as f64;
+        let statistic = counts
+            .iter()
+            .map(|count| (*count as f64 - expected).powi(2) / expected)
+            .sum();
+        Self {
+            statistic,
+            degrees_of_freedom: counts.len() as u64 - 1,
+        }
+    }
+
+    /// the statistic a uniform selection stays under with the confidence `z_score` stands
+    /// for, from the Wilson-Hilferty approximation of the chi-square distribution
+    pub fn critical_value(&self, z_score: f64) -> f64 {
+        if self.degrees_of_freedom == 0 {
+            return 0.;
+        }
+        let k = self.degrees_of_freedom as f64;
+        let a = 2. / (9. * k);
+        k * (1. - a + z_score * a.sqrt()).powi(3)
+    }
+
+    pub fn is_uniform(&self, z_score: f64) -> bool {
+        self.statistic <= self.critical_value(z_score)
+    }
+}
+
+/// runs the chi-square test over the groups for `seeds`
+pub fn chi_square<I>(generator: &VoteGroupGenerator, seeds: I) -> ChiSquare
+where
+    I: IntoIterator<Item = u64>,
+{
+    ChiSquare::new(&hit_counts(generator, seeds))
+}
+
+/// how many distinct members the groups for a range of seeds had, to hold binomial groups
+/// up against expected_group_size and group_size_variance
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GroupSizes {
+    pub groups: u64,
+    pub mean: f64,
+    pub variance: f64,
+    pub min: usize,
+    pub max: usize,
+}
+
+impl GroupSizes {
+    pub fn new<I>(generator: &VoteGroupGenerator, seeds: I) -> Self
+    where
+        I: IntoIterator<Item = u64>,
+    {
+        let sizes: Vec<usize> = seeds
+            .into_iter()
+            .map(|seed| member_indexes(generator, seed).len())
+            .collect();
+        if sizes.is_empty() {
+            return Self {
+                groups: 0,
+                mean: 0.,
+                variance: 0.,
+                min: 0,
+                max: 0,
+            };
+        }
+        let groups = sizes.len() as f64;
+        let mean = sizes.iter().sum::<usize>() as f64 / groups;
+        let variance = sizes
+            .iter()
+            .map(|size| (*size as f64 - mean).powi(2))
+            .sum::<f64>()
+            / groups;
+        Self {
+            groups: sizes.len() as u64,
+            mean,
+            variance,
+            min: sizes.iter().copied().min().unwrap_or(0),
+            max: sizes.iter().copied().max().unwrap_or(0),
+        }
+    }
+
+    pub fn std_dev(&self) -> f64 {
+        self.variance.sqrt()
+    }
+}
+
+/// how often every pair of voters ended up in the same group, the diagonal holds the
+/// plain hit counts
+#[derive(Clone, Debug, PartialEq)]
+pub struct CoOccurrence {
+    voters: usize,
+    counts: Vec<u64>,
+}
+
+impl CoOccurrence {
+    pub fn new<I>(generator: &VoteGroupGenerator, seeds: I) -> Self
+    where
+        I: IntoIterator<Item = u64>,
+    {
+        let voters = generator.voters().len();
+        let mut counts = vec![0; voters * voters];
+        for seed in seeds {
+            let members = member_indexes(generator, seed);
+            for i in &members {
+                for j in &members {
+                    counts[i * voters + j] += 1;
+                }
+            }
+        }
+        Self { voters, counts }
+    }
+
+    pub fn voters(&self) -> usize {
+        self.voters
+    }
+
+    pub fn get(&self, i: usize, j: usize) -> u64 {
+        self.counts[i * self.voters + j]
+    }
+
+    /// the pair seen together most often and how often, None with fewer than two voters
+    pub fn max_pair(&self) -> Option<((usize, usize), u64)> {
+        (0..self.voters)
+            .flat_map(|i| (i + 1..self.voters).map(move |j| (i, j)))
+            .map(|(i, j)| ((i, j), self.get(i, j)))
+            .max_by_key(|(_, count)| *count)
+    }
+
+    /// the pair seen together least often and how often, None with fewer than two voters
+    pub fn min_pair(&self) -> Option<((usize, usize), u64)> {
+        (0..self.voters)
+            .flat_map(|i| (i + 1..self.voters).map(move |j| (i, j)))
+            .map(|(i, j)| ((i, j), self.get(i, j)))
+            .min_by_key(|(_, count)| *count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{pubkey::Pubkey, vote_group_gen::SelectionAlgorithm};
+    use std::collections::HashMap;
+
+    fn generator(voters: usize, size: usize) -> VoteGroupGenerator {
+        let map: HashMap<Pubkey, Pubkey> = (0..voters)
+            .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+            .collect();
+        VoteGroupGenerator::new_with_exclusions(&map, size, &Default::default())
+            .with_algorithm(SelectionAlgorithm::V2)
+    }
+
+    #[test]
+    fn test_hit_counts() {
+        let vgg = generator(20, 5);
+        let counts = hit_counts(&vgg, 0..1_000);
+        assert_eq!(counts.len(), 20);
+        assert_eq!(counts.iter().sum::<u64>(), 5 * 1_000);
+        assert_eq!(hit_counts(&vgg, 0..0), vec![0; 20]);
+    }
+
+    #[test]
+    fn test_chi_square() {
+        let even = ChiSquare::new(&[10, 10, 10, 10]);
+        assert!(even.statistic.abs() < f64::EPSILON);
+        assert_eq!(even.degrees_of_freedom, 3);
+        assert!(even.is_uniform(Z_SCORE_95));
+
+        let skewed = ChiSquare::new(&[1_000, 0, 0, 0]);
+        assert!(!skewed.is_uniform(Z_SCORE_999));
+
+        // the approximation is close to the tabulated 95% value for 10 degrees of freedom
+        let ten = ChiSquare {
+            statistic: 0.,
+            degrees_of_freedom: 10,
+        };
+        assert!((ten.critical_value(Z_SCORE_95) - 18.307).abs() < 0.1);
+
+        assert_eq!(ChiSquare::new(&[]).degrees_of_freedom, 0);
+        assert!(ChiSquare::new(&[0, 0]).is_uniform(Z_SCORE_95));
+    }
+
+    #[test]
+    fn test_v2_selection_is_uniform() {
+        let vgg = generator(50, 11);
+        assert!(chi_square(&vgg, 0..20_000).is_uniform(Z_SCORE_999));
+    }
+
+    #[test]
+    fn test_group_sizes() {
+        let vgg = generator(20, 5);
+        let sizes = GroupSizes::new(&vgg, 0..100);
+        assert_eq!(sizes.groups, 100);
+        assert!((sizes.mean - 5.).abs() < f64::EPSILON);
+        assert!(sizes.variance.abs() < f64::EPSILON);
+        assert_eq!((sizes.min, sizes.max), (5, 5));
+        assert_eq!(GroupSizes::new(&vgg, 0..0).groups, 0);
+
+        let map: HashMap<Pubkey, Pubkey> = vgg.voters().iter().map(|key| (*key, *key)).collect();
+        let stakes = vgg.voters().iter().map(|key| (*key, 100)).collect();
+        let binomial = VoteGroupGenerator::new_binomial(&map, &stakes, 5, &Default::default());
+        let sizes = GroupSizes::new(&binomial, 0..5_000);
+        assert!(sizes.min < 5 && sizes.max > 5);
+        assert!((sizes.mean - binomial.expected_group_size()).abs() < 0.1);
+        assert!((sizes.variance - binomial.group_size_variance()).abs() < 0.3);
+    }
+
+    #[test]
+    fn test_co_occurrence() {
+        let vgg = generator(10, 3);
+        let counts = hit_counts(&vgg, 0..500);
+        let pairs = CoOccurrence::new(&vgg, 0..500);
+        assert_eq!(pairs.voters(), 10);
+        for (i, count) in counts.iter().enumerate() {
+            assert_eq!(pairs.get(i, i), *count);
+            for j in 0..10 {
+                assert_eq!(pairs.get(i, j), pairs.get(j, i));
+            }
+        }
+        let (_, max) = pairs.max_pair().unwrap();
+        let (_, min) = pairs.min_pair().unwrap();
+        assert!(min <= max);
+        // every group of 3 puts 3 pairs together
+        let together: u64 = (0..10)
+            .flat_map(|i| (i + 1..10).map(move |j| (i, j)))
+            .map(|(i, j)| pairs.get(i, j))
+            .sum();
+        assert_eq!(together, 3 * 500);
+
+        assert_eq!(CoOccurrence::new(&generator(1, 1), 0..10).max_pair(), None);
+    }
+}
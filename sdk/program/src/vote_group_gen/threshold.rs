@@ -0,0 +1,274 @@
+//! Threshold signing by a slot's group
+//!
+//! In this mode the members of a slot's group hold shares of a group key and any t of the n
+//! members can produce one signature over the slot and the hash they confirm. A light
+//! client that knows the group key checks that single signature instead of a vote per
+//! member. Shares are numbered by the member's position in the group, 1 for the first
+//! member selected, so every node that agrees on the group agrees on who holds which share.
+//! The signature arithmetic is left to a ThresholdScheme.
+
+use super::{VoteGroupGenerator, THRESHOLD_SIGNATURE_DOMAIN};
+use crate::{
+    clock::Slot,
+    hash::{hashv, Hash},
+    pubkey::Pubkey,
+};
+use std::collections::{BTreeMap, HashSet};
+
+/// the message the group's shares sign for a confirmation of `hash` at `slot`
+pub fn threshold_message(slot: Slot, hash: &Hash) -> Hash {
+    hashv(&[
+        THRESHOLD_SIGNATURE_DOMAIN,
+        &slot.to_le_bytes(),
+        hash.as_ref(),
+    ])
+}
+
+/// combination and verification of threshold signatures
+pub trait ThresholdScheme {
+    /// the group signature out of partial signatures keyed by share index, None unless
+    /// there are enough valid shares
+    fn combine(&self, threshold: usize, partials: &[(u32, Vec<u8>)]) -> Option<Vec<u8>>;
+
+    fn verify(&self, group_pubkey: &[u8], message: &[u8], signature: &[u8]) -> bool;
+}
+
+/// who holds which share of the group for one seed and how many shares it takes
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ThresholdGroup {
+    members: Vec<Pubkey>,
+    threshold: usize,
+}
+
+impl ThresholdGroup {
+    /// the group `generator` picks for `seed`, a member picked twice holds one share. the
+    /// threshold defaults to the group's quorum
+    pub fn new(generator: &VoteGroupGenerator, seed: u64) -> Self {
+        let mut seen = HashSet::new();
+        let members: Vec<Pubkey> = generator
+            .group_for_seed(seed)
+            .into_iter()
+            .filter(|member| seen.insert(*member))
+            .collect();
+        let threshold = VoteGroupGenerator::count_quorum(members.len());
+        Self { members, threshold }
+    }
+
+    /// `threshold` clamped to the number of shares
+    pub fn with_threshold(mut self, threshold: usize) -> Self {
+        self.threshold = threshold.max(1).min(self.members.len());
+        self
+    }
+
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    /// the share `key` holds, None for keys outside the group
+    pub fn share_index(&self, key: &Pubkey) -> Option<u32> {
+        self.members
+            .iter()
+            .position(|member| member == key)
+            .map(|position| position as u32 + 1)
+    }
+
+    pub fn member_for_share(&self, share_index: u32) -> Option<Pubkey> {
+        let position = (share_index as usize).checked_sub(1)?;
+        self.members.get(position).copied()
+    }
+}
+
+/// collects the members' partial signatures until the threshold is met
+#[derive(Debug)]
+pub struct ShareCollector<'a> {
+    group: &'a ThresholdGroup,
+    partials: BTreeMap<u32, Vec<u8>>,
+}
+
+impl<'a> ShareCollector<'a> {
+    pub fn new(group: &'a ThresholdGroup) -> Self {
+        Self {
+            group,
+            partials: BTreeMap::new(),
+        }
+    }
+
+    /// false for keys outside the group and members that already handed in a share
+    pub fn add(&mut self, member: &Pubkey, partial: Vec<u8>) -> bool {
+        match self.group.share_index(member) {
+            Some(share_index) if !self.partials.contains_key(&share_index) => {
+                self.partials.insert(share_index, partial);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.partials.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.partials.is_empty()
+    }
+
+    pub fn has_threshold(&self) -> bool {
+        !self.group.is_empty() && self.partials.len() >= self.group.threshold()
+    }
+
+    /// the slot's confirmation once enough shares are in
+    pub fn confirmation<S: ThresholdScheme + ?Sized>(
+        &self,
+        slot: Slot,
+        hash: Hash,
+        scheme: &S,
+    ) -> Option<ThresholdConfirmation> {
+        if !self.has_threshold() {
+            return None;
+        }
+        let partials: Vec<(u32, Vec<u8>)> = self
+            .partials
+            .iter()
+            .map(|(share_index, partial)| (*share_index, partial.clone()))
+            .collect();
+        let signature = scheme.combine(self.group.threshold(), &partials)?;
+        Some(ThresholdConfirmation {
+            slot,
+            hash,
+            signature,
+        })
+    }
+}
+
+/// the single signature that confirms `hash` at `slot` for the slot's group
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ThresholdConfirmation {
+    pub slot: Slot,
+    pub hash: Hash,
+    pub signature: Vec<u8>,
+}
+
+impl ThresholdConfirmation {
+    /// what a light client checks, the signature against the group key it trusts
+    pub fn verify<S: ThresholdScheme + ?Sized>(&self, group_pubkey: &[u8], scheme: &S) -> bool {
+        scheme.verify(
+            group_pubkey,
+            threshold_message(self.slot, &self.hash).as_ref(),
+            &self.signature,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    /// a partial is the share index and the message, the group signature is the message
+    /// and the group key, combined from any `threshold` partials over the same message
+    struct MockScheme;
+
+    impl ThresholdScheme for MockScheme {
+        fn combine(&self, threshold: usize, partials: &[(u32, Vec<u8>)]) -> Option<Vec<u8>> {
+            let message = &partials.first()?.1[4..];
+            let valid = partials
+                .iter()
+                .filter(|(share_index, partial)| {
+                    partial[..4] == share_index.to_le_bytes() && &partial[4..] == message
+                })
+                .count();
+            if valid < threshold {
+                return None;
+            }
+            Some([b"group".as_ref(), message].concat())
+        }
+
+        fn verify(&self, group_pubkey: &[u8], message: &[u8], signature: &[u8]) -> bool {
+            group_pubkey == b"group" && signature == [group_pubkey, message].concat().as_slice()
+        }
+    }
+
+    fn partial(share_index: u32, slot: Slot, hash: &Hash) -> Vec<u8> {
+        [
+            &share_index.to_le_bytes()[..],
+            threshold_message(slot, hash).as_ref(),
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn test_threshold_group() {
+        let hm: HashMap<Pubkey, Pubkey> = (0..20)
+            .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+            .collect();
+        let generator = VoteGroupGenerator::new(&hm, 7);
+        let group = ThresholdGroup::new(&generator, 4);
+        let members = generator.group_for_seed(4);
+        assert_eq!(group.len(), 7);
+        assert_eq!(group.threshold(), generator.quorum());
+        for (position, member) in members.iter().enumerate() {
+            assert_eq!(group.share_index(member), Some(position as u32 + 1));
+            assert_eq!(group.member_for_share(position as u32 + 1), Some(*member));
+        }
+        assert_eq!(group.member_for_share(0), None);
+        assert_eq!(group.member_for_share(8), None);
+        assert_eq!(group.share_index(&Pubkey::new_unique()), None);
+        assert_eq!(group.clone().with_threshold(0).threshold(), 1);
+        assert_eq!(group.clone().with_threshold(100).threshold(), 7);
+
+        // every node numbers the shares the same way
+        let decoded: VoteGroupGenerator =
+            bincode::deserialize(&bincode::serialize(&generator).unwrap()).unwrap();
+        assert_eq!(ThresholdGroup::new(&decoded, 4), group);
+
+        let empty = ThresholdGroup::new(&VoteGroupGenerator::new_dummy(), 4);
+        assert!(empty.is_empty());
+        assert!(!ShareCollector::new(&empty).has_threshold());
+    }
+
+    #[test]
+    fn test_threshold_confirmation() {
+        let hm: HashMap<Pubkey, Pubkey> = (0..20)
+            .map(|_| (Pubkey::new_unique(), Pubkey::new_unique()))
+            .collect();
+        let generator = VoteGroupGenerator::new(&hm, 7);
+        let group = ThresholdGroup::new(&generator, 4).with_threshold(4);
+        let members = generator.group_for_seed(4);
+        let (slot, hash) = (11, Hash::new_unique());
+
+        let mut collector = ShareCollector::new(&group);
+        assert!(!collector.add(&Pubkey::new_unique(), partial(1, slot, &hash)));
+        for (position, member) in members.iter().take(3).enumerate() {
+            assert!(collector.add(member, partial(position as u32 + 1, slot, &hash)));
+            assert!(!collector.add(member, partial(position as u32 + 1, slot, &hash)));
+        }
+        assert_eq!(collector.len(), 3);
+        assert!(collector.confirmation(slot, hash, &MockScheme).is_none());
+
+        assert!(collector.add(&members[6], partial(7, slot, &hash)));
+        let confirmation = collector.confirmation(slot, hash, &MockScheme).unwrap();
+        assert!(confirmation.verify(b"group", &MockScheme));
+        assert!(!confirmation.verify(b"other", &MockScheme));
+
+        let mut wrong_slot = confirmation;
+        wrong_slot.slot += 1;
+        assert!(!wrong_slot.verify(b"group", &MockScheme));
+
+        // a share signed over another hash doesn't count toward this one
+        let mut collector = ShareCollector::new(&group);
+        for (position, member) in members.iter().take(3).enumerate() {
+            collector.add(member, partial(position as u32 + 1, slot, &hash));
+        }
+        collector.add(&members[3], partial(4, slot, &Hash::new_unique()));
+        assert!(collector.has_threshold());
+        assert!(collector.confirmation(slot, hash, &MockScheme).is_none());
+    }
+}
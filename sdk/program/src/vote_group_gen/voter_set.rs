@@ -0,0 +1,316 @@
+//! Merkle commitment over the eligible voter set
+//!
+//! The leaves are the voters in canonical order and the root is folded with their count, so
+//! one hash pins down exactly who could be picked in an epoch. The bank hash carries it once
+//! the feature is on. A proof for a key is either the key's own leaf or, when it wasn't
+//! eligible, the leaves of its neighbours in canonical order with nothing between them.
+
+use super::VOTER_SET_COMMITMENT_DOMAIN;
+use crate::{
+    hash::{hashv, Hash},
+    pubkey::Pubkey,
+};
+use std::sync::Mutex;
+
+const LEAF_TAG: &[u8] = &[0];
+const NODE_TAG: &[u8] = &[1];
+const ROOT_TAG: &[u8] = &[2];
+
+/// a voter and the merkle siblings that put it at `index`
+#[derive(Clone, Debug, Serialize, Deserialize, AbiExample, PartialEq, Eq)]
+pub struct VoterInclusion {
+    pub voter: Pubkey,
+    pub index: u32,
+    pub path: Vec<Hash>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, AbiExample, PartialEq, Eq)]
+pub struct EligibilityProof {
+    pub voter_count: u32,
+    /// the key itself when it was eligible, otherwise the voters right before and after it.
+    /// only one of them for a key past either end, none for an empty voter set
+    pub voters: Vec<VoterInclusion>,
+}
+
+impl EligibilityProof {
+    /// every key sorting between the first and last voter is covered by its neighbours
+    pub fn new(voters: &[Pubkey], key: &Pubkey) -> Self {
+        let levels = tree_levels(voters);
+        let inclusion = |index: usize| VoterInclusion {
+            voter: voters[index],
+            index: index as u32,
+            path: merkle_path(&levels, index),
+        };
+        let voters_proof = match voters.binary_search(key) {
+            Ok(index) => vec![inclusion(index)],
+            Err(index) => {
+                let below = index.checked_sub(1).map(inclusion);
+                let above = Some(index)
+                    .filter(|index| *index < voters.len())
+                    .map(inclusion);
+                below.into_iter().chain(above).collect()
+            }
+        };
+        Self {
+            voter_count: voters.len() as u32,
+            voters: voters_proof,
+        }
+    }
+
+    /// Some(eligible) when the proof holds up against `voter_set_root`, None when it doesn't
+    pub fn verify(&self, key: &Pubkey, voter_set_root: &Hash) -> Option<bool> {
+        for inclusion in &self.voters {
+            let root = root_from_path(
+                &inclusion.voter,
+                inclusion.index as usize,
+                self.voter_count as usize,
+                &inclusion.path,
+            )?;
+            if root != *voter_set_root {
+                return None;
+            }
+        }
+        let last = self.voter_count.checked_sub(1);
+        match self.voters.as_slice() {
+            [] if self.voter_count == 0 && fold_root(0, Hash::default()) == *voter_set_root => {
+                Some(false)
+            }
+            [voter] if voter.voter == *key => Some(true),
+            [above] if above.index == 0 && *key < above.voter => Some(false),
+            [below] if Some(below.index) == last && below.voter < *key => Some(false),
+            [below, above]
+                if below.index + 1 == above.index && below.voter < *key && *key < above.voter =>
+            {
+                Some(false)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// the root `voters` commit to, they have to be in canonical order
+pub fn voter_set_root(voters: &[Pubkey]) -> Hash {
+    let tree_root = tree_levels(voters)
+        .last()
+        .and_then(|level| level.first().copied())
+        .unwrap_or_default();
+    fold_root(voters.len() as u32, tree_root)
+}
+
+/// the generator's root, computed along with it. serde skips it so a generator read back
+/// from a snapshot computes it again the first time it's asked for
+#[derive(Debug, Default)]
+pub(crate) struct VoterSetRoot {
+    root: Mutex<Option<Hash>>,
+}
+
+impl VoterSetRoot {
+    pub fn new(voters: &[Pubkey]) -> Self {
+        Self {
+            root: Mutex::new(Some(voter_set_root(voters))),
+        }
+    }
+
+    pub fn get_or_compute(&self, voters: &[Pubkey]) -> Hash {
+        *self
+            .root
+            .lock()
+            .unwrap()
+            .get_or_insert_with(|| voter_set_root(voters))
+    }
+
+    /// must be called whenever the voter set changes
+    pub fn clear(&self) {
+        *self.root.lock().unwrap() = None;
+    }
+}
+
+impl Clone for VoterSetRoot {
+    fn clone(&self) -> Self {
+        Self {
+            root: Mutex::new(*self.root.lock().unwrap()),
+        }
+    }
+}
+
+// the root is derived from the voters, two generators are equal regardless of it
+impl PartialEq for VoterSetRoot {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+#[cfg(RUSTC_WITH_SPECIALIZATION)]
+impl ::solana_frozen_abi::abi_example::AbiExample for VoterSetRoot {
+    fn example() -> Self {
+        // VoterSetRoot is #[serde(skip)]-ed, so just rely on Default
+        Self::default()
+    }
+}
+
+/// the siblings of `index` from the leaves up, a level where its node is the odd one out
+/// has no sibling and adds nothing
+pub(crate) fn merkle_path(levels: &[Vec<Hash>], index: usize) -> Vec<Hash> {
+    let mut path = Vec::new();
+    let mut position = index;
+    for level in levels {
+        let sibling = position ^ 1;
+        if sibling < level.len() {
+            path.push(level[sibling]);
+        }
+        position /= 2;
+    }
+    path
+}
+
+/// the root a voter at `index` of `voter_count` voters leads to, None when `path` is the
+/// wrong length for the position
+pub(crate) fn root_from_path(
+    voter: &Pubkey,
+    index: usize,
+    voter_count: usize,
+    path: &[Hash],
+) -> Option<Hash> {
+    if index >= voter_count {
+        return None;
+    }
+    let mut node = leaf_hash(voter);
+    let mut position = index;
+    let mut width = voter_count;
+    let mut path = path.iter();
+    while width > 1 {
+        let sibling = position ^ 1;
+        if sibling < width {
+            let sibling = path.next()?;
+            node = if position & 1 == 0 {
+                node_hash(&node, sibling)
+            } else {
+                node_hash(sibling, &node)
+            };
+        }
+        position /= 2;
+        width -= width / 2;
+    }
+    if path.next().is_some() {
+        return None;
+    }
+    Some(fold_root(voter_count as u32, node))
+}
+
+/// every level of the tree from the leaves up to the root, an odd node out moves up as is
+pub(crate) fn tree_levels(voters: &[Pubkey]) -> Vec<Vec<Hash>> {
+    let mut levels = vec![voters.iter().map(leaf_hash).collect::<Vec<_>>()];
+    while levels[levels.len() - 1].len() > 1 {
+        let next = levels[levels.len() - 1]
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => node_hash(left, right),
+                _ => pair[0],
+            })
+            .collect();
+        levels.push(next);
+    }
+    levels
+}
+
+fn leaf_hash(voter: &Pubkey) -> Hash {
+    hashv(&[VOTER_SET_COMMITMENT_DOMAIN, LEAF_TAG, voter.as_ref()])
+}
+
+fn node_hash(left: &Hash, right: &Hash) -> Hash {
+    hashv(&[
+        VOTER_SET_COMMITMENT_DOMAIN,
+        NODE_TAG,
+        left.as_ref(),
+        right.as_ref(),
+    ])
+}
+
+fn fold_root(voter_count: u32, tree_root: Hash) -> Hash {
+    hashv(&[
+        VOTER_SET_COMMITMENT_DOMAIN,
+        ROOT_TAG,
+        &voter_count.to_le_bytes(),
+        tree_root.as_ref(),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn voters(count: usize) -> Vec<Pubkey> {
+        let mut voters: Vec<Pubkey> = (0..count).map(|_| Pubkey::new_unique()).collect();
+        voters.sort();
+        voters
+    }
+
+    #[test]
+    fn test_eligibility_proof() {
+        for count in 0..12 {
+            let voters = voters(count);
+            let root = voter_set_root(&voters);
+            for voter in &voters {
+                let proof = EligibilityProof::new(&voters, voter);
+                assert_eq!(proof.verify(voter, &root), Some(true));
+                let bytes = bincode::serialize(&proof).unwrap();
+                assert_eq!(
+                    bincode::deserialize::<EligibilityProof>(&bytes).unwrap(),
+                    proof
+                );
+            }
+            // new_unique keys sort after every voter made before them
+            let outsider = Pubkey::new_unique();
+            let proof = EligibilityProof::new(&voters, &outsider);
+            assert_eq!(proof.verify(&outsider, &root), Some(false));
+            let first = Pubkey::default();
+            let proof = EligibilityProof::new(&voters, &first);
+            assert_eq!(proof.verify(&first, &root), Some(false));
+        }
+    }
+
+    #[test]
+    fn test_eligibility_proof_between_voters() {
+        let mut voters = voters(9);
+        let removed = voters.remove(4);
+        let root = voter_set_root(&voters);
+        let proof = EligibilityProof::new(&voters, &removed);
+        assert_eq!(proof.voters.len(), 2);
+        assert_eq!(proof.verify(&removed, &root), Some(false));
+
+        // leaving out a neighbour hides nothing
+        let mut below_only = proof.clone();
+        below_only.voters.pop();
+        assert_eq!(below_only.verify(&removed, &root), None);
+
+        // neighbours that aren't adjacent leave room for the key
+        let mut gap = EligibilityProof::new(&voters, &voters[5]);
+        gap.voters.insert(
+            0,
+            EligibilityProof::new(&voters, &voters[2]).voters[0].clone(),
+        );
+        assert_eq!(gap.verify(&removed, &root), None);
+
+        // the proof of one voter set says nothing about another
+        let other = voter_set_root(&voters[..7]);
+        assert_eq!(proof.verify(&removed, &other), None);
+        let mut recounted = proof;
+        recounted.voter_count += 1;
+        assert_eq!(recounted.verify(&removed, &root), None);
+
+        // claiming an outsider's eligibility with a voter's proof
+        let voter_proof = EligibilityProof::new(&voters, &voters[0]);
+        assert_eq!(voter_proof.verify(&removed, &root), None);
+    }
+
+    #[test]
+    fn test_voter_set_root_cache() {
+        let voters = voters(5);
+        let cached = VoterSetRoot::new(&voters);
+        assert_eq!(cached.get_or_compute(&[]), voter_set_root(&voters));
+        assert_eq!(cached.clone().get_or_compute(&[]), voter_set_root(&voters));
+        cached.clear();
+        assert_eq!(cached.get_or_compute(&[]), voter_set_root(&[]));
+        assert_ne!(voter_set_root(&voters), voter_set_root(&voters[..4]));
+    }
+}
@@ -0,0 +1,58 @@
+//! JavaScript bindings for checking vote group membership in the browser
+//!
+//! Wallets and explorers load the epoch's generator, in the borsh encoding on-chain programs
+//! read or the JSON the RPC serves, and then pick groups locally with the same Rust code the
+//! validators run, no RPC round trip per seed. Seeds are u64 so they cross over as BigInt,
+//! pubkeys cross over as base58 strings.
+
+use super::VoteGroupGenerator;
+use crate::pubkey::Pubkey;
+use borsh::BorshDeserialize;
+use std::str::FromStr;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen(js_name = VoteGroupGenerator)]
+pub struct WasmVoteGroupGenerator {
+    inner: VoteGroupGenerator,
+}
+
+#[wasm_bindgen(js_class = VoteGroupGenerator)]
+impl WasmVoteGroupGenerator {
+    /// decodes the borsh encoding of a generator, state checks included
+    #[wasm_bindgen(constructor)]
+    pub fn new(borsh_bytes: &[u8]) -> Result<WasmVoteGroupGenerator, JsValue> {
+        VoteGroupGenerator::try_from_slice(borsh_bytes)
+            .map(|inner| Self { inner })
+            .map_err(|err| JsValue::from_str(&err.to_string()))
+    }
+
+    /// decodes the human readable JSON form of a generator, state checks included
+    #[wasm_bindgen(js_name = fromJson)]
+    pub fn from_json(json: &str) -> Result<WasmVoteGroupGenerator, JsValue> {
+        let inner: VoteGroupGenerator =
+            serde_json::from_str(json).map_err(|err| JsValue::from_str(&err.to_string()))?;
+        inner
+            .check_state()
+            .map_err(|err| JsValue::from_str(&err.to_string()))?;
+        Ok(Self { inner })
+    }
+
+    /// base58 vote account pubkeys of the group for `seed`, in selection order
+    #[wasm_bindgen(js_name = groupForSeed)]
+    pub fn group_for_seed(&self, seed: u64) -> Box<[JsValue]> {
+        self.inner
+            .group_for_seed(seed)
+            .iter()
+            .map(|key| JsValue::from_str(&key.to_string()))
+            .collect()
+    }
+
+    /// whether the base58 vote account `key` is in the group for `seed`
+    #[wasm_bindgen(js_name = inGroupForSeed)]
+    pub fn in_group_for_seed(&self, seed: u64, key: &str) -> Result<bool, JsValue> {
+        let key = Pubkey::from_str(key).map_err(|err| JsValue::from_str(&err.to_string()))?;
+        self.inner
+            .try_in_group_for_seed(seed, key)
+            .map_err(|err| JsValue::from_str(&err.to_string()))
+    }
+}
@@ -0,0 +1,233 @@
+//! A verifiable random function over edwards25519 used for secret vote group sortition
+//!
+//! This follows the shape of ECVRF-EDWARDS25519-SHA512-TAI: the proof is (Gamma, c, s)
+//! and the output is a hash of the cofactor cleared Gamma. The secret scalar is expanded
+//! exactly like an ed25519 secret key, so a voter's VRF public key is its ed25519 pubkey.
+
+use crate::pubkey::Pubkey;
+#[cfg(not(target_arch = "bpf"))]
+use curve25519_dalek::{
+    constants::ED25519_BASEPOINT_TABLE,
+    edwards::{CompressedEdwardsY, EdwardsPoint},
+    scalar::Scalar,
+};
+#[cfg(not(target_arch = "bpf"))]
+use sha2::{Digest, Sha512};
+
+#[cfg(not(target_arch = "bpf"))]
+const SUITE: u8 = 0x03;
+#[cfg(not(target_arch = "bpf"))]
+const HASH_TO_CURVE_TAG: u8 = 0x01;
+#[cfg(not(target_arch = "bpf"))]
+const CHALLENGE_TAG: u8 = 0x02;
+#[cfg(not(target_arch = "bpf"))]
+const OUTPUT_TAG: u8 = 0x03;
+
+/// Number of bytes in a serialized proof
+pub const VRF_PROOF_BYTES: usize = 80;
+/// Number of bytes of VRF output
+pub const VRF_OUTPUT_BYTES: usize = 64;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, AbiExample)]
+pub struct VrfProof {
+    gamma: [u8; 32],
+    c: [u8; 16],
+    s: [u8; 32],
+}
+
+impl VrfProof {
+    pub fn to_bytes(&self) -> [u8; VRF_PROOF_BYTES] {
+        let mut bytes = [0u8; VRF_PROOF_BYTES];
+        bytes[..32].copy_from_slice(&self.gamma);
+        bytes[32..48].copy_from_slice(&self.c);
+        bytes[48..].copy_from_slice(&self.s);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != VRF_PROOF_BYTES {
+            return None;
+        }
+        let mut proof = Self::default();
+        proof.gamma.copy_from_slice(&bytes[..32]);
+        proof.c.copy_from_slice(&bytes[32..48]);
+        proof.s.copy_from_slice(&bytes[48..]);
+        Some(proof)
+    }
+
+    #[cfg(not(target_arch = "bpf"))]
+    /// checks the proof was produced by `pubkey` for `alpha`, returning the VRF output if so
+    pub fn verify(&self, pubkey: &Pubkey, alpha: &[u8]) -> Option<[u8; VRF_OUTPUT_BYTES]> {
+        let y = CompressedEdwardsY::from_slice(pubkey.as_ref()).decompress()?;
+        if y.is_small_order() {
+            return None;
+        }
+        let gamma = CompressedEdwardsY(self.gamma).decompress()?;
+        let s = Scalar::from_canonical_bytes(self.s)?;
+        let c = challenge_scalar(&self.c);
+        let h = hash_to_curve(pubkey, alpha)?;
+
+        // U = s*B - c*Y, V = s*H - c*Gamma
+        let u = EdwardsPoint::vartime_double_scalar_mul_basepoint(&-c, &y, &s);
+        let v = s * h - c * gamma;
+        if hash_points(&h, &gamma, &u, &v) != self.c {
+            return None;
+        }
+        Some(proof_to_output(&gamma))
+    }
+}
+
+#[cfg(not(target_arch = "bpf"))]
+pub struct VrfKeypair {
+    secret: Scalar,
+    nonce_prefix: [u8; 32],
+    public: Pubkey,
+}
+
+#[cfg(not(target_arch = "bpf"))]
+impl VrfKeypair {
+    /// expands a 32 byte ed25519 secret key
+    pub fn from_secret(secret_key: &[u8; 32]) -> Self {
+        let expanded = Sha512::digest(secret_key);
+        let mut scalar_bytes = [0u8; 32];
+        scalar_bytes.copy_from_slice(&expanded[..32]);
+        scalar_bytes[0] &= 248;
+        scalar_bytes[31] &= 127;
+        scalar_bytes[31] |= 64;
+        let secret = Scalar::from_bits(scalar_bytes);
+        let mut nonce_prefix = [0u8; 32];
+        nonce_prefix.copy_from_slice(&expanded[32..]);
+        let public = Pubkey::new_from_array((&secret * &ED25519_BASEPOINT_TABLE).compress().0);
+        Self {
+            secret,
+            nonce_prefix,
+            public,
+        }
+    }
+
+    pub fn pubkey(&self) -> Pubkey {
+        self.public
+    }
+
+    /// produces the proof for `alpha` along with the VRF output it commits to
+    pub fn prove(&self, alpha: &[u8]) -> (VrfProof, [u8; VRF_OUTPUT_BYTES]) {
+        // the public key is always a valid point so this only fails with negligible probability
+        let h = hash_to_curve(&self.public, alpha).expect("hash to curve");
+        let gamma = self.secret * h;
+        let k = {
+            let mut hasher = Sha512::new();
+            hasher.update(&self.nonce_prefix);
+            hasher.update(h.compress().as_bytes());
+            let mut wide = [0u8; 64];
+            wide.copy_from_slice(&hasher.finalize());
+            Scalar::from_bytes_mod_order_wide(&wide)
+        };
+        let c_bytes = hash_points(&h, &gamma, &(&k * &ED25519_BASEPOINT_TABLE), &(k * h));
+        let s = k + challenge_scalar(&c_bytes) * self.secret;
+        let proof = VrfProof {
+            gamma: gamma.compress().0,
+            c: c_bytes,
+            s: s.to_bytes(),
+        };
+        (proof, proof_to_output(&gamma))
+    }
+}
+
+#[cfg(not(target_arch = "bpf"))]
+/// try and increment until the hash lands on the curve, then clear the cofactor
+fn hash_to_curve(pubkey: &Pubkey, alpha: &[u8]) -> Option<EdwardsPoint> {
+    for ctr in 0..=u8::MAX {
+        let mut hasher = Sha512::new();
+        hasher.update(&[SUITE, HASH_TO_CURVE_TAG]);
+        hasher.update(pubkey.as_ref());
+        hasher.update(alpha);
+        hasher.update(&[ctr, 0x00]);
+        let digest = hasher.finalize();
+        if let Some(point) = CompressedEdwardsY::from_slice(&digest[..32]).decompress() {
+            let point = point.mul_by_cofactor();
+            if !point.is_small_order() {
+                return Some(point);
+            }
+        }
+    }
+    None
+}
+
+#[cfg(not(target_arch = "bpf"))]
+fn hash_points(
+    h: &EdwardsPoint,
+    gamma: &EdwardsPoint,
+    u: &EdwardsPoint,
+    v: &EdwardsPoint,
+) -> [u8; 16] {
+    let mut hasher = Sha512::new();
+    hasher.update(&[SUITE, CHALLENGE_TAG]);
+    for point in &[h, gamma, u, v] {
+        hasher.update(point.compress().as_bytes());
+    }
+    hasher.update(&[0x00]);
+    let mut c = [0u8; 16];
+    c.copy_from_slice(&hasher.finalize()[..16]);
+    c
+}
+
+#[cfg(not(target_arch = "bpf"))]
+fn challenge_scalar(c: &[u8; 16]) -> Scalar {
+    let mut bytes = [0u8; 32];
+    bytes[..16].copy_from_slice(c);
+    Scalar::from_bits(bytes)
+}
+
+#[cfg(not(target_arch = "bpf"))]
+fn proof_to_output(gamma: &EdwardsPoint) -> [u8; VRF_OUTPUT_BYTES] {
+    let mut hasher = Sha512::new();
+    hasher.update(&[SUITE, OUTPUT_TAG]);
+    hasher.update(gamma.mul_by_cofactor().compress().as_bytes());
+    hasher.update(&[0x00]);
+    let mut output = [0u8; VRF_OUTPUT_BYTES];
+    output.copy_from_slice(&hasher.finalize());
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vrf_prove_verify() {
+        let keypair = VrfKeypair::from_secret(&[7u8; 32]);
+        let (proof, output) = keypair.prove(b"slot 42");
+        assert_eq!(proof.verify(&keypair.pubkey(), b"slot 42"), Some(output));
+
+        // deterministic
+        assert_eq!(keypair.prove(b"slot 42"), (proof, output));
+
+        // wrong message
+        assert_eq!(proof.verify(&keypair.pubkey(), b"slot 43"), None);
+
+        // wrong key
+        let other = VrfKeypair::from_secret(&[8u8; 32]);
+        assert_eq!(proof.verify(&other.pubkey(), b"slot 42"), None);
+    }
+
+    #[test]
+    fn test_vrf_tampered_proof() {
+        let keypair = VrfKeypair::from_secret(&[1u8; 32]);
+        let (proof, _) = keypair.prove(b"alpha");
+        let mut bytes = proof.to_bytes();
+        bytes[40] ^= 1;
+        let tampered = VrfProof::from_bytes(&bytes).unwrap();
+        assert_eq!(tampered.verify(&keypair.pubkey(), b"alpha"), None);
+    }
+
+    #[test]
+    fn test_vrf_proof_bytes_round_trip() {
+        let keypair = VrfKeypair::from_secret(&[2u8; 32]);
+        let (proof, _) = keypair.prove(b"alpha");
+        assert_eq!(VrfProof::from_bytes(&proof.to_bytes()), Some(proof));
+        assert_eq!(VrfProof::from_bytes(&[0u8; 79]), None);
+        let data = bincode::serialize(&proof).unwrap();
+        assert_eq!(data.len(), VRF_PROOF_BYTES);
+        assert_eq!(bincode::deserialize::<VrfProof>(&data).unwrap(), proof);
+    }
+}
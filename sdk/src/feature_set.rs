@@ -166,6 +166,98 @@ pub mod merge_nonce_error_into_system_error {
     solana_sdk::declare_id!("21AWDosvp3pBamFW91KB35pNoaoZVTM7ess8nr2nt53B");
 }
 
+pub mod stake_weighted_vote_groups {
+    solana_sdk::declare_id!("35rnQ8wJPgQgBCrLJifL124eV5EVWWNrd7MwMvLU3BEL");
+}
+
+pub mod vote_group_selection_v2 {
+    solana_sdk::declare_id!("5HjNsA9M8h9wXLihxUbS4RBgVfVSr8aJTrvU6mKokQ3H");
+}
+
+pub mod vote_group_slot_seeds {
+    solana_sdk::declare_id!("GyBzmSNdbvKUf8ksF6wfjdGXQSX2pCwosdz4rwP4mt2b");
+}
+
+pub mod vote_group_sysvar {
+    solana_sdk::declare_id!("DWBR6H85jGYcCuPa5AmmCWnNpKqhg3ZPs193wHssDHDd");
+}
+
+pub mod enforce_vote_group_membership {
+    solana_sdk::declare_id!("37RdL8wP1nXtyjqmzWeT6ytn8jHNDjSKzeEnT7sF2Dfy");
+}
+
+pub mod vote_group_credits {
+    solana_sdk::declare_id!("77o2xVNe9WPScJwdfgEb9ZZPu2Fmu6NUkVe4GiwRcJx7");
+}
+
+pub mod vote_group_penalties {
+    solana_sdk::declare_id!("9g2Zciq136rF6SjawCqAZEePnkSnaKvQteLeig6vEt3e");
+}
+
+pub mod vote_group_distinct_members {
+    solana_sdk::declare_id!("36DbHaxip4s95LTsKcTxbWKVt29TVNs7zv7XTeQhNfec");
+}
+
+pub mod vote_group_delinquency {
+    solana_sdk::declare_id!("J6gZWmM9pN7AN6YpszJSUFjvyEdFmiujfNPqxFwSSHNQ");
+}
+
+pub mod vote_group_selection_v3 {
+    solana_sdk::declare_id!("27FimfVPjMw3769H5FdVcmBBMezubEvKtQVdUNKdjjXh");
+}
+
+pub mod vote_group_seed_derivation_v2 {
+    solana_sdk::declare_id!("Art4sJuwxpVcPeHWeT57LTNwWsj253hmbbCeVx3KccdJ");
+}
+
+pub mod vote_group_admin_program {
+    solana_sdk::declare_id!("Cew8UFpBJHaZd844jw6XxCthuiUoReHhxsC5W1NmNY2v");
+}
+
+pub mod limited_voting {
+    solana_sdk::declare_id!("8Aa6RmzpnhPLbhiiuqwFBDoEmos6Xdvn39VR1fHW1eoB");
+}
+
+pub mod group_weighted_fork_choice {
+    solana_sdk::declare_id!("EZitUpzcp5otStPVW2zYNwcdyjb4yh2ruttaSU7pSK25");
+}
+
+pub mod voter_set_root_in_bank_hash {
+    solana_sdk::declare_id!("5bZCXeEN3ecQDcL3Z6oaXzriUe6fGo4iwTyTq7bV1zHf");
+}
+
+pub mod vote_group_identity_dedup {
+    solana_sdk::declare_id!("39S97AtPrBhmZK9zXB8zzsMY7EWfS6L9HLX3tYxbMUTx");
+}
+
+pub mod vote_group_absence {
+    solana_sdk::declare_id!("BmdCgiCfMv4K1K5yPE3z8ZCV2sLDiY9sLHFCXLnzpuQr");
+}
+
+pub mod vote_group_rotation_window {
+    solana_sdk::declare_id!("PxyyRUhabXiggGDUpJUPqByDHde8qJyRDDPMjdaGhAv");
+}
+
+pub mod vote_group_fee_exemption {
+    solana_sdk::declare_id!("9y9hviY25GCkz6tDN1RgUHn7jDzSo1YcECzMF2K4QR58");
+}
+
+pub mod vote_group_participation_rewards {
+    solana_sdk::declare_id!("6FqArdiFHNNvw5wSV3PUYFs3SxsPHi1hqGqCBQ4A17W1");
+}
+
+pub mod vote_group_in_bank_hash {
+    solana_sdk::declare_id!("2E8QmT7x8DpNZzAKJoRKUWojAuKNJmfBL7ZxtcKChFut");
+}
+
+pub mod commit_reveal_vote_groups {
+    solana_sdk::declare_id!("5R9B9HoQCC57qSMB39RejtcyMxc6WiKG2MmcUtzEpqrX");
+}
+
+pub mod vote_group_gossip_claims {
+    solana_sdk::declare_id!("6CcFv5da2niduAzkZd4vXe5AWFxz2UsFp89cUFwWZDFW");
+}
+
 lazy_static! {
     /// Map of feature identifiers to user-visible description
     pub static ref FEATURE_NAMES: HashMap<Pubkey, &'static str> = [
@@ -206,6 +298,29 @@ lazy_static! {
         (updated_verify_policy::id(), "Update verify policy"),
         (libsecp256k1_0_5_upgrade_enabled::id(), "upgrade libsecp256k1 to v0.5.0"),
         (merge_nonce_error_into_system_error::id(), "merge NonceError into SystemError"),
+        (stake_weighted_vote_groups::id(), "pick vote group members in proportion to stake"),
+        (vote_group_selection_v2::id(), "mixed seeds and distinct members in vote groups"),
+        (vote_group_slot_seeds::id(), "seed vote groups from the slot and epoch anchor hash"),
+        (vote_group_distinct_members::id(), "re-derive the v1 vote group distance instead of repeating members"),
+        (vote_group_sysvar::id(), "publish the current vote group in a sysvar"),
+        (enforce_vote_group_membership::id(), "check the vote account against the voted slot's group"),
+        (vote_group_credits::id(), "only credit rooted votes for slots the voter was in the group for"),
+        (vote_group_penalties::id(), "count out of group votes and leave persistent offenders out of later groups"),
+        (vote_group_delinquency::id(), "leave vote accounts that stopped voting out of later vote groups"),
+        (vote_group_selection_v3::id(), "shuffle vote groups out of a ChaCha20 stream instead of walking the ring"),
+        (vote_group_seed_derivation_v2::id(), "derive vote group seeds by rehashing hashes under a context string instead of xor-folding them"),
+        (vote_group_admin_program::id(), "vote group admin program"),
+        (limited_voting::id(), "only accept votes from the voted slot's vote group"),
+        (group_weighted_fork_choice::id(), "weigh forks by the stake of each slot's vote group"),
+        (voter_set_root_in_bank_hash::id(), "hash the epoch's eligible voter set root into the bank hash"),
+        (vote_group_identity_dedup::id(), "pick at most one vote account per node identity for vote groups"),
+        (vote_group_absence::id(), "leave vote accounts that missed their assigned slots out of the next vote groups"),
+        (vote_group_rotation_window::id(), "keep every vote group for the rotation window of the vote group config"),
+        (vote_group_fee_exemption::id(), "vote transactions from the members of the voted slot's group pay no fee"),
+        (vote_group_participation_rewards::id(), "staking rewards follow the share of its assigned slots a vote account voted on"),
+        (vote_group_in_bank_hash::id(), "hash a commitment to the slot's vote group into the bank hash"),
+        (commit_reveal_vote_groups::id(), "vote accounts prove group membership by revealing a secret committed an epoch ahead"),
+        (vote_group_gossip_claims::id(), "announce vote group membership claims in gossip"),
         /*************** ADD NEW FEATURES HERE ***************/
     ]
     .iter()
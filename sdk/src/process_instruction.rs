@@ -8,6 +8,8 @@ use solana_sdk::{
     instruction::VoterGroup,
     hash::Hash,
     clock::Slot,
+    vrf::VrfProof,
+    vote_group_gen::{MembershipCommitment, MembershipReveal},
 };
 use std::{cell::RefCell, fmt::Debug, rc::Rc, sync::Arc};
 
@@ -414,4 +416,19 @@ impl VoterGroup for MockInvokeContext {
     fn in_group(&self,_: Slot,_ : Hash, _: Pubkey) -> bool {
         true
     }
+
+    fn in_group_with_proof(&self, _: Slot, _: Hash, _: Pubkey, _: &VrfProof) -> bool {
+        true
+    }
+
+    fn in_group_with_reveal(
+        &self,
+        _: Slot,
+        _: Hash,
+        _: Pubkey,
+        _: &[MembershipCommitment],
+        _: &MembershipReveal,
+    ) -> bool {
+        true
+    }
 }
@@ -121,6 +121,59 @@ pub fn parse_vote(
                 }),
             })
         }
+        VoteInstruction::VoteWithVrfProof(vote, proof) => {
+            check_num_vote_accounts(&instruction.accounts, 4)?;
+            let vote = json!({
+                "slots": vote.slots,
+                "hash": vote.hash.to_string(),
+                "timestamp": vote.timestamp,
+            });
+            Ok(ParsedInstructionEnum {
+                instruction_type: "voteWithVrfProof".to_string(),
+                info: json!({
+                    "voteAccount": account_keys[instruction.accounts[0] as usize].to_string(),
+                    "slotHashesSysvar": account_keys[instruction.accounts[1] as usize].to_string(),
+                    "clockSysvar": account_keys[instruction.accounts[2] as usize].to_string(),
+                    "voteAuthority": account_keys[instruction.accounts[3] as usize].to_string(),
+                    "vote": vote,
+                    "vrfProof": bs58::encode(proof.to_bytes()).into_string(),
+                }),
+            })
+        }
+        VoteInstruction::CommitGroupMembership(commitment) => {
+            check_num_vote_accounts(&instruction.accounts, 3)?;
+            Ok(ParsedInstructionEnum {
+                instruction_type: "commitGroupMembership".to_string(),
+                info: json!({
+                    "voteAccount": account_keys[instruction.accounts[0] as usize].to_string(),
+                    "clockSysvar": account_keys[instruction.accounts[1] as usize].to_string(),
+                    "voteAuthority": account_keys[instruction.accounts[2] as usize].to_string(),
+                    "epoch": commitment.epoch,
+                    "root": commitment.root.to_string(),
+                }),
+            })
+        }
+        VoteInstruction::VoteWithMembershipReveal(vote, reveal) => {
+            check_num_vote_accounts(&instruction.accounts, 4)?;
+            let vote = json!({
+                "slots": vote.slots,
+                "hash": vote.hash.to_string(),
+                "timestamp": vote.timestamp,
+            });
+            Ok(ParsedInstructionEnum {
+                instruction_type: "voteWithMembershipReveal".to_string(),
+                info: json!({
+                    "voteAccount": account_keys[instruction.accounts[0] as usize].to_string(),
+                    "slotHashesSysvar": account_keys[instruction.accounts[1] as usize].to_string(),
+                    "clockSysvar": account_keys[instruction.accounts[2] as usize].to_string(),
+                    "voteAuthority": account_keys[instruction.accounts[3] as usize].to_string(),
+                    "vote": vote,
+                    "window": reveal.window,
+                    "secret": reveal.secret.to_string(),
+                    "path": reveal.path.iter().map(|hash| hash.to_string()).collect::<Vec<_>>(),
+                }),
+            })
+        }
         VoteInstruction::AuthorizeChecked(authority_type) => {
             check_num_vote_accounts(&instruction.accounts, 4)?;
             Ok(ParsedInstructionEnum {
@@ -5,9 +5,16 @@ use {
     jsonrpc_ipc_server::{RequestContext, ServerBuilder},
     jsonrpc_server_utils::tokio,
     log::*,
+    serde_derive::{Deserialize, Serialize},
     solana_core::validator::{ValidatorExit, ValidatorStartProgress},
-    solana_sdk::signature::{read_keypair_file, Keypair, Signer},
+    solana_runtime::shared_vote_group_generator::SharedVoteGroupGenerator,
+    solana_sdk::{
+        clock::{Epoch, Slot},
+        signature::{read_keypair_file, Keypair, Signer},
+        vote_group_gen::VOTE_GROUP_LOG_TARGET,
+    },
     std::{
+        env,
         net::SocketAddr,
         path::Path,
         sync::{Arc, RwLock},
@@ -23,9 +30,50 @@ pub struct AdminRpcRequestMetadata {
     pub start_progress: Arc<RwLock<ValidatorStartProgress>>,
     pub validator_exit: Arc<RwLock<ValidatorExit>>,
     pub authorized_voter_keypairs: Arc<RwLock<Vec<Arc<Keypair>>>>,
+    pub vote_group_generator: Arc<RwLock<Option<Arc<SharedVoteGroupGenerator>>>>,
 }
 impl Metadata for AdminRpcRequestMetadata {}
 
+impl AdminRpcRequestMetadata {
+    fn vote_group_generator(&self) -> Result<Arc<SharedVoteGroupGenerator>> {
+        self.vote_group_generator
+            .read()
+            .unwrap()
+            .clone()
+            .ok_or_else(|| {
+                jsonrpc_core::error::Error::invalid_params("Validator is still starting")
+            })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminVoteGroupStatus {
+    pub epoch: Epoch,
+    pub first_slot: Slot,
+    pub last_slot: Slot,
+    /// the epoch before, while its groups are still answered after the boundary
+    pub previous_epoch: Option<Epoch>,
+    pub voters: usize,
+    pub group_size: usize,
+    pub algorithm: String,
+    /// false while groups are seeded from voted hashes
+    pub slot_seeds: bool,
+    pub cached_groups: usize,
+    /// group cache lookups since the metrics last reported them
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdminCachedVoteGroup {
+    pub seed: u64,
+    /// base58 vote account pubkeys in selection order, a member picked more than once is
+    /// listed once
+    pub members: Vec<String>,
+}
+
 #[rpc]
 pub trait AdminRpc {
     type Metadata;
@@ -50,6 +98,15 @@ pub trait AdminRpc {
 
     #[rpc(meta, name = "removeAllAuthorizedVoters")]
     fn remove_all_authorized_voters(&self, meta: Self::Metadata) -> Result<()>;
+
+    #[rpc(meta, name = "voteGroupStatus")]
+    fn vote_group_status(&self, meta: Self::Metadata) -> Result<AdminVoteGroupStatus>;
+
+    #[rpc(name = "setVoteGroupLogLevel")]
+    fn set_vote_group_log_level(&self, level: String) -> Result<()>;
+
+    #[rpc(meta, name = "dumpVoteGroupCache")]
+    fn dump_vote_group_cache(&self, meta: Self::Metadata) -> Result<Vec<AdminCachedVoteGroup>>;
 }
 
 pub struct AdminRpcImpl;
@@ -131,6 +188,57 @@ impl AdminRpc for AdminRpcImpl {
         //meta.authorized_voter_keypairs.write().unwrap().clear();
         Ok(())
     }
+
+    fn vote_group_status(&self, meta: Self::Metadata) -> Result<AdminVoteGroupStatus> {
+        debug!("vote_group_status admin rpc request received");
+        let epochs = meta.vote_group_generator()?.load_epochs();
+        let current = &epochs.current;
+        let generator = &current.generator;
+        let cache_stats = generator.cache_stats();
+        Ok(AdminVoteGroupStatus {
+            epoch: current.epoch,
+            first_slot: *current.slots.start(),
+            last_slot: *current.slots.end(),
+            previous_epoch: epochs.previous.as_ref().map(|previous| previous.epoch),
+            voters: generator.voters().len(),
+            group_size: generator.group_size(),
+            algorithm: format!("{:?}", generator.algorithm()).to_lowercase(),
+            slot_seeds: current.seed_source.is_some(),
+            cached_groups: generator.cached_groups().len(),
+            cache_hits: cache_stats.hits,
+            cache_misses: cache_stats.misses,
+        })
+    }
+
+    fn set_vote_group_log_level(&self, level: String) -> Result<()> {
+        debug!("set_vote_group_log_level admin rpc request received");
+        let level = level
+            .parse::<LevelFilter>()
+            .map_err(|err| jsonrpc_core::error::Error::invalid_params(format!("{}", err)))?;
+        // the rest of the filter is whatever the validator was started with
+        let filter = env::var("RUST_LOG").unwrap_or_else(|_| "solana=info".to_string());
+        solana_logger::setup_with(&format!(
+            "{},{}={}",
+            filter,
+            VOTE_GROUP_LOG_TARGET,
+            level.to_string().to_lowercase()
+        ));
+        Ok(())
+    }
+
+    fn dump_vote_group_cache(&self, meta: Self::Metadata) -> Result<Vec<AdminCachedVoteGroup>> {
+        debug!("dump_vote_group_cache admin rpc request received");
+        Ok(meta
+            .vote_group_generator()?
+            .generator()
+            .cached_groups()
+            .into_iter()
+            .map(|(seed, members)| AdminCachedVoteGroup {
+                seed,
+                members: members.iter().map(|key| key.to_string()).collect(),
+            })
+            .collect())
+    }
 }
 
 // Start the Admin RPC interface
@@ -193,3 +301,101 @@ pub async fn connect(ledger_path: &Path) -> std::result::Result<gen_client::Clie
 pub fn runtime() -> jsonrpc_server_utils::tokio::runtime::Runtime {
     jsonrpc_server_utils::tokio::runtime::Runtime::new().expect("new tokio runtime")
 }
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        serde_json::Value,
+        solana_runtime::{
+            bank::Bank,
+            genesis_utils::{
+                create_genesis_config_with_vote_accounts, GenesisConfigInfo, ValidatorVoteKeypairs,
+            },
+        },
+    };
+
+    fn meta() -> AdminRpcRequestMetadata {
+        AdminRpcRequestMetadata {
+            rpc_addr: None,
+            start_time: SystemTime::now(),
+            start_progress: Arc::new(RwLock::new(ValidatorStartProgress::default())),
+            validator_exit: Arc::new(RwLock::new(ValidatorExit::default())),
+            authorized_voter_keypairs: Arc::new(RwLock::new(vec![])),
+            vote_group_generator: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    fn request(
+        io: &MetaIoHandler<AdminRpcRequestMetadata>,
+        meta: &AdminRpcRequestMetadata,
+        method: &str,
+        params: &str,
+    ) -> Value {
+        let request = format!(
+            r#"{{"jsonrpc":"2.0","id":1,"method":"{}","params":{}}}"#,
+            method, params
+        );
+        let response = io.handle_request_sync(&request, meta.clone()).unwrap();
+        serde_json::from_str(&response).unwrap()
+    }
+
+    #[test]
+    fn test_vote_group_admin_rpc() {
+        let mut io = MetaIoHandler::default();
+        io.extend_with(AdminRpcImpl.to_delegate());
+        let meta = meta();
+
+        // nothing to inspect until the validator publishes its generator
+        let response = request(&io, &meta, "voteGroupStatus", "[]");
+        assert_eq!(response["error"]["message"], "Validator is still starting");
+
+        let validator_keypairs: Vec<_> =
+            (0..10).map(|_| ValidatorVoteKeypairs::new_rand()).collect();
+        let GenesisConfigInfo { genesis_config, .. } = create_genesis_config_with_vote_accounts(
+            1_000_000_000,
+            &validator_keypairs,
+            vec![10_000; 10],
+        );
+        let bank = Bank::new(&genesis_config);
+        let shared = Arc::new(SharedVoteGroupGenerator::new_from_bank(&bank));
+        *meta.vote_group_generator.write().unwrap() = Some(shared.clone());
+
+        // checking a member caches the slot's group
+        let group = shared.group_at(0).unwrap();
+        assert_eq!(shared.in_group_at(0, &group[0]), Some(true));
+        let seed = shared.load().seed_at(0).unwrap();
+        let status: AdminVoteGroupStatus =
+            serde_json::from_value(request(&io, &meta, "voteGroupStatus", "[]")["result"].clone())
+                .unwrap();
+        assert_eq!(status.epoch, 0);
+        assert_eq!(status.first_slot, 0);
+        assert_eq!(
+            status.last_slot,
+            bank.epoch_schedule().get_last_slot_in_epoch(0)
+        );
+        assert_eq!(status.previous_epoch, None);
+        assert_eq!(status.voters, validator_keypairs.len());
+        assert_eq!(status.group_size, shared.generator().group_size());
+        assert!(status.cached_groups > 0);
+
+        let cached: Vec<AdminCachedVoteGroup> = serde_json::from_value(
+            request(&io, &meta, "dumpVoteGroupCache", "[]")["result"].clone(),
+        )
+        .unwrap();
+        let cached = cached.iter().find(|cached| cached.seed == seed).unwrap();
+        let mut expected: Vec<String> = vec![];
+        for member in group.iter().map(|member| member.to_string()) {
+            if !expected.contains(&member) {
+                expected.push(member);
+            }
+        }
+        assert_eq!(cached.members, expected);
+
+        let response = request(&io, &meta, "setVoteGroupLogLevel", r#"["trace"]"#);
+        assert_eq!(response["result"], Value::Null);
+        assert!(response.get("error").is_none());
+        let response = request(&io, &meta, "setVoteGroupLogLevel", r#"["loud"]"#);
+        assert!(response.get("error").is_some());
+    }
+}
@@ -4,7 +4,7 @@ use {
     solana_clap_utils::{
         input_parsers::{pubkey_of, pubkeys_of, value_of},
         input_validators::{
-            is_pubkey, is_pubkey_or_keypair, is_slot, is_url_or_moniker,
+            is_parsable, is_pubkey, is_pubkey_or_keypair, is_slot, is_url_or_moniker,
             normalize_to_url_if_moniker,
         },
     },
@@ -21,6 +21,7 @@ use {
         rpc_port,
         signature::{read_keypair_file, write_keypair_file, Keypair, Signer},
         system_program,
+        vote_group_gen::VoteGroupOverrides,
     },
     solana_validator::{
         admin_rpc_service, dashboard::Dashboard, println_name_value, redirect_stderr_to_file,
@@ -279,6 +280,42 @@ fn main() {
                      If the ledger already exists then this parameter is silently ignored",
                 ),
         )
+        .arg(
+            Arg::with_name("vote_group_size")
+                .long("vote-group-size")
+                .takes_value(true)
+                .value_name("SIZE")
+                .validator(is_parsable::<usize>)
+                .help("Override the vote group size"),
+        )
+        .arg(
+            Arg::with_name("vote_group_algorithm")
+                .long("vote-group-algorithm")
+                .takes_value(true)
+                .value_name("ALGORITHM")
+                .possible_values(&["v1", "v2", "v3"])
+                .help("Override the vote group selection algorithm"),
+        )
+        .arg(
+            Arg::with_name("vote_group_members")
+                .long("vote-group-members")
+                .takes_value(true)
+                .value_name("VOTE_ACCOUNT")
+                .validator(is_pubkey)
+                .multiple(true)
+                .help(
+                    "Pin every vote group to these vote accounts, --vote-group-size is ignored. \
+                     Votes of a vote account left out are rejected once groups are enforced",
+                ),
+        )
+        .arg(
+            Arg::with_name("vote_group_seed")
+                .long("vote-group-seed")
+                .takes_value(true)
+                .value_name("SEED")
+                .validator(is_parsable::<u64>)
+                .help("Pin the seed of every vote group, so the groups never rotate"),
+        )
         .get_matches();
 
     let cli_config = if let Some(config_file) = matches.value_of("config_file") {
@@ -512,6 +549,7 @@ fn main() {
             start_time: std::time::SystemTime::now(),
             validator_exit: genesis.validator_exit.clone(),
             authorized_voter_keypairs: genesis.authorized_voter_keypairs.clone(),
+            vote_group_generator: genesis.vote_group_generator.clone(),
         },
     );
     let dashboard = if output == Output::Dashboard {
@@ -556,6 +594,16 @@ fn main() {
         genesis.warp_slot(warp_slot);
     }
 
+    let vote_group_overrides = VoteGroupOverrides {
+        group_size: value_of(&matches, "vote_group_size"),
+        algorithm: value_of(&matches, "vote_group_algorithm"),
+        members: pubkeys_of(&matches, "vote_group_members"),
+        seed: value_of(&matches, "vote_group_seed"),
+    };
+    if !vote_group_overrides.is_empty() {
+        genesis.vote_group_overrides(vote_group_overrides);
+    }
+
     if let Some(slots_per_epoch) = slots_per_epoch {
         genesis.epoch_schedule(EpochSchedule::custom(
             slots_per_epoch,
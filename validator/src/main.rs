@@ -53,6 +53,7 @@ use {
         hash::Hash,
         pubkey::Pubkey,
         signature::{Keypair, Signer},
+        vote_group_gen::VoteGroupOverrides,
     },
     solana_validator::{
         admin_rpc_service, dashboard::Dashboard, new_spinner_progress_bar, println_name_value,
@@ -1716,6 +1717,25 @@ pub fn main() {
                 .value_name("NUM")
                 .help("Specify hashes per batch in PoH service"),
         )
+        .arg(
+            Arg::with_name("vote_group_size")
+                .long("vote-group-size")
+                .takes_value(true)
+                .value_name("SIZE")
+                .validator(is_parsable::<usize>)
+                .help("Override the vote group size. Only honoured on development clusters, \
+                       every validator of the cluster must use the same value"),
+        )
+        .arg(
+            Arg::with_name("vote_group_algorithm")
+                .long("vote-group-algorithm")
+                .takes_value(true)
+                .value_name("ALGORITHM")
+                .possible_values(&["v1", "v2", "v3"])
+                .help("Override the vote group selection algorithm. Only honoured on \
+                       development clusters, every validator of the cluster must use the \
+                       same value"),
+        )
         .arg(
             Arg::with_name("account_indexes")
                 .long("account-index")
@@ -1859,6 +1879,36 @@ pub fn main() {
             )
             .after_help("Note: the new filter only applies to the currently running validator instance")
         )
+        .subcommand(
+            SubCommand::with_name("vote-group")
+            .about("Inspect the vote group generator of the running validator")
+            .setting(AppSettings::SubcommandRequiredElseHelp)
+            .setting(AppSettings::InferSubcommands)
+            .subcommand(
+                SubCommand::with_name("status")
+                .about("Show the epoch, voters and group cache of the current generator")
+            )
+            .subcommand(
+                SubCommand::with_name("set-log-level")
+                .about("Adjust the log level of vote group decisions")
+                .arg(
+                    Arg::with_name("level")
+                        .index(1)
+                        .value_name("LEVEL")
+                        .takes_value(true)
+                        .required(true)
+                        .possible_values(&["off", "error", "warn", "info", "debug", "trace"])
+                        .help("Log level for the solana_vote_group target, trace logs every \
+                               membership check")
+                )
+                .after_help("Note: the rest of the log filter is reset to the one the \
+                             validator was started with")
+            )
+            .subcommand(
+                SubCommand::with_name("dump-cache")
+                .about("Print the recently selected groups held in the group cache")
+            )
+        )
         .subcommand(
             SubCommand::with_name("wait-for-restart-window")
             .about("Monitor the validator for a good time to restart")
@@ -1973,6 +2023,34 @@ pub fn main() {
                 });
             return;
         }
+        ("vote-group", Some(vote_group_subcommand_matches)) => {
+            let admin_client = admin_rpc_service::connect(&ledger_path);
+            let output = match vote_group_subcommand_matches.subcommand() {
+                ("status", _) => admin_rpc_service::runtime()
+                    .block_on(async move { admin_client.await?.vote_group_status().await })
+                    .map(|status| serde_json::to_string_pretty(&status).unwrap()),
+                ("set-log-level", Some(subcommand_matches)) => {
+                    let level = value_t_or_exit!(subcommand_matches, "level", String);
+                    admin_rpc_service::runtime()
+                        .block_on(async move {
+                            admin_client.await?.set_vote_group_log_level(level).await
+                        })
+                        .map(|()| "Vote group log level set".to_string())
+                }
+                ("dump-cache", _) => admin_rpc_service::runtime()
+                    .block_on(async move { admin_client.await?.dump_vote_group_cache().await })
+                    .map(|groups| serde_json::to_string_pretty(&groups).unwrap()),
+                _ => unreachable!(),
+            };
+            match output {
+                Ok(output) => println!("{}", output),
+                Err(err) => {
+                    println!("vote group request failed: {}", err);
+                    exit(1);
+                }
+            }
+            return;
+        }
         ("wait-for-restart-window", Some(subcommand_matches)) => {
             let min_idle_time = value_t_or_exit!(subcommand_matches, "min_idle_time", usize);
             wait_for_restart_window(&ledger_path, min_idle_time).unwrap_or_else(|err| {
@@ -2174,6 +2252,12 @@ pub fn main() {
         accounts_db_use_index_hash_calculation: matches.is_present("accounts_db_index_hashing"),
         tpu_coalesce_ms,
         no_wait_for_vote_to_start_leader: matches.is_present("no_wait_for_vote_to_start_leader"),
+        vote_group_overrides: Some(VoteGroupOverrides {
+            group_size: value_of(&matches, "vote_group_size"),
+            algorithm: value_of(&matches, "vote_group_algorithm"),
+            ..VoteGroupOverrides::default()
+        })
+        .filter(|overrides| !overrides.is_empty()),
         ..ValidatorConfig::default()
     };
 
@@ -2382,6 +2466,7 @@ pub fn main() {
             validator_exit: validator_config.validator_exit.clone(),
             start_progress: start_progress.clone(),
             authorized_voter_keypairs: authorized_voter_keypairs.clone(),
+            vote_group_generator: validator_config.vote_group_generator.clone(),
         },
     );
 
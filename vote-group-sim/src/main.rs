@@ -0,0 +1,604 @@
+//! Replays vote group selection over a voter set and reports how the groups behave
+//!
+//! The voter set comes either from a JSON file or from the newest snapshot in a ledger
+//! directory. Every slot of the simulated epochs is seeded the way the runtime seeds it,
+//! with a made up anchor hash per epoch so runs are repeatable.
+
+use {
+    clap::{crate_description, crate_name, value_t, value_t_or_exit, App, Arg, ArgGroup},
+    log::*,
+    serde::{Deserialize, Serialize},
+    solana_clap_utils::input_validators::{is_parsable, is_valid_percentage},
+    solana_runtime::{accounts_index::AccountSecondaryIndexes, snapshot_utils},
+    solana_sdk::{
+        clock::Epoch,
+        epoch_schedule::EpochSchedule,
+        genesis_config::GenesisConfig,
+        hash::hashv,
+        pubkey::Pubkey,
+        vote_group_gen::{
+            ExclusionList, SelectionAlgorithm, SelectionMode, VoteGroupGenerator,
+            OPTIMAL_VOTE_GROUP_SIZE,
+        },
+    },
+    std::{
+        collections::{HashMap, HashSet},
+        fs::File,
+        io::{self, Write},
+        path::Path,
+        process::exit,
+        str::FromStr,
+    },
+};
+
+/// one voter of a JSON voter set, a missing stake counts as zero and any other field is
+/// ignored
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct VoterEntry {
+    vote_pubkey: String,
+    #[serde(default)]
+    stake: u64,
+}
+
+#[derive(Debug, Default)]
+struct VoterSet {
+    // vote account -> authorized voter, the generator only looks at the keys
+    voters: HashMap<Pubkey, Pubkey>,
+    stakes: HashMap<Pubkey, u64>,
+    epoch_schedule: Option<EpochSchedule>,
+    epoch: Option<Epoch>,
+}
+
+fn parse_voters_json(json: &str) -> Result<VoterSet, String> {
+    let entries: Vec<VoterEntry> =
+        serde_json::from_str(json).map_err(|err| format!("invalid voter set: {}", err))?;
+    let mut voter_set = VoterSet::default();
+    for entry in entries {
+        let vote_pubkey = Pubkey::from_str(&entry.vote_pubkey)
+            .map_err(|err| format!("invalid vote pubkey {}: {}", entry.vote_pubkey, err))?;
+        voter_set.voters.insert(vote_pubkey, vote_pubkey);
+        voter_set.stakes.insert(vote_pubkey, entry.stake);
+    }
+    Ok(voter_set)
+}
+
+fn load_voters_json(path: &Path) -> Result<VoterSet, String> {
+    let json = std::fs::read_to_string(path)
+        .map_err(|err| format!("unable to read {}: {}", path.display(), err))?;
+    parse_voters_json(&json)
+}
+
+/// the voters of the current epoch of the newest snapshot in `ledger_path`
+fn load_voters_from_snapshot(ledger_path: &Path) -> Result<VoterSet, String> {
+    let genesis_config = GenesisConfig::load(ledger_path).map_err(|err| {
+        format!(
+            "unable to load genesis from {}: {}",
+            ledger_path.display(),
+            err
+        )
+    })?;
+    let (archive_path, (slot, _, archive_format)) =
+        snapshot_utils::get_highest_snapshot_archive_path(ledger_path)
+            .ok_or_else(|| format!("no snapshot found in {}", ledger_path.display()))?;
+    info!("loading snapshot for slot {}", slot);
+
+    let unpack_dir = tempfile::TempDir::new().map_err(|err| err.to_string())?;
+    let account_paths = vec![unpack_dir.path().join("accounts")];
+    let bank = snapshot_utils::bank_from_archive(
+        &account_paths,
+        &[],
+        unpack_dir.path(),
+        &archive_path,
+        archive_format,
+        &genesis_config,
+        None,
+        None,
+        AccountSecondaryIndexes::default(),
+        false,
+    )
+    .map_err(|err| {
+        format!(
+            "unable to load snapshot {}: {}",
+            archive_path.display(),
+            err
+        )
+    })?;
+
+    let epoch_stakes = bank
+        .epoch_stakes(bank.epoch())
+        .ok_or_else(|| format!("snapshot has no stakes for epoch {}", bank.epoch()))?;
+    let voters: HashMap<Pubkey, Pubkey> = epoch_stakes
+        .epoch_authorized_voters()
+        .iter()
+        .map(|(vote_pubkey, authorized_voter)| (*vote_pubkey, *authorized_voter))
+        .collect();
+    let stakes = voters
+        .keys()
+        .map(|vote_pubkey| (*vote_pubkey, epoch_stakes.vote_account_stake(vote_pubkey)))
+        .collect();
+    Ok(VoterSet {
+        voters,
+        stakes,
+        epoch_schedule: Some(*bank.epoch_schedule()),
+        epoch: Some(bank.epoch()),
+    })
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct VoterReport {
+    vote_pubkey: String,
+    stake: u64,
+    selections: u64,
+    frequency: f64,
+    /// most consecutive slots the voter was left out of the group
+    longest_gap: u64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Report {
+    first_epoch: Epoch,
+    epochs: u64,
+    slots: u64,
+    voters: usize,
+    group_size: usize,
+    algorithm: String,
+    selection_mode: String,
+    /// members a group has on average and how much that spreads, only binomial groups
+    /// change in size
+    expected_group_size: f64,
+    group_size_variance: f64,
+    average_group_size: f64,
+    min_group_size: usize,
+    max_group_size: usize,
+    /// share of the voters that were in at least one group
+    coverage: f64,
+    never_selected: usize,
+    /// members the groups of consecutive slots have in common
+    average_consecutive_overlap: f64,
+    max_consecutive_overlap: usize,
+    /// share of the total stake a group has to hold to confirm a slot on its own
+    quorum_threshold: f64,
+    quorum_feasible_slots: u64,
+    quorum_feasibility: f64,
+    min_group_stake_fraction: f64,
+    average_group_stake_fraction: f64,
+    voter_reports: Vec<VoterReport>,
+}
+
+fn simulate(
+    generator: &VoteGroupGenerator,
+    stakes: &HashMap<Pubkey, u64>,
+    epoch_schedule: &EpochSchedule,
+    first_epoch: Epoch,
+    epochs: u64,
+    quorum_threshold: f64,
+) -> Report {
+    let voters = generator.voters();
+    let mut selections = vec![0u64; voters.len()];
+    let mut longest_gaps = vec![0u64; voters.len()];
+    let mut last_selected: Vec<Option<u64>> = vec![None; voters.len()];
+    // a voter set without stakes counts every voter the same for the quorum
+    let total_stake: u64 = stakes.values().sum();
+    let stake_of = |vote_pubkey: &Pubkey| {
+        if total_stake == 0 {
+            1
+        } else {
+            stakes.get(vote_pubkey).copied().unwrap_or(0)
+        }
+    };
+    let total_stake = if total_stake == 0 {
+        voters.len() as u64
+    } else {
+        total_stake
+    };
+
+    let mut slots = 0;
+    let mut previous_group = HashSet::new();
+    let mut total_overlap = 0;
+    let mut max_consecutive_overlap = 0;
+    let mut quorum_feasible_slots = 0;
+    let mut min_group_stake_fraction = f64::MAX;
+    let mut total_group_stake_fraction = 0.;
+    let mut total_group_size = 0;
+    let mut min_group_size = usize::MAX;
+    let mut max_group_size = 0;
+    for epoch in first_epoch..first_epoch + epochs {
+        let anchor = hashv(&[b"vote-group-sim", &epoch.to_le_bytes()]);
+        let first_slot = epoch_schedule.get_first_slot_in_epoch(epoch);
+        for slot in first_slot..first_slot + epoch_schedule.get_slots_in_epoch(epoch) {
+            let group: HashSet<usize> = generator
+                .group_for_slot(slot, epoch, &anchor)
+                .iter()
+                .filter_map(|member| voters.binary_search(member).ok())
+                .collect();
+
+            let mut group_stake = 0;
+            for i in &group {
+                let gap = last_selected[*i].map_or(slots, |last| slots - last - 1);
+                longest_gaps[*i] = longest_gaps[*i].max(gap);
+                last_selected[*i] = Some(slots);
+                selections[*i] += 1;
+                group_stake += stake_of(&voters[*i]);
+            }
+            let group_stake_fraction = group_stake as f64 / total_stake as f64;
+            if group_stake_fraction >= quorum_threshold {
+                quorum_feasible_slots += 1;
+            }
+            min_group_stake_fraction = min_group_stake_fraction.min(group_stake_fraction);
+            total_group_stake_fraction += group_stake_fraction;
+            total_group_size += group.len();
+            min_group_size = min_group_size.min(group.len());
+            max_group_size = max_group_size.max(group.len());
+
+            if slots > 0 {
+                let overlap = group.intersection(&previous_group).count();
+                total_overlap += overlap;
+                max_consecutive_overlap = max_consecutive_overlap.max(overlap);
+            }
+            previous_group = group;
+            slots += 1;
+        }
+    }
+    for (longest_gap, last) in longest_gaps.iter_mut().zip(last_selected.iter()) {
+        let gap = last.map_or(slots, |last| slots - last - 1);
+        *longest_gap = (*longest_gap).max(gap);
+    }
+
+    let never_selected = selections.iter().filter(|count| **count == 0).count();
+    let ratio = |numerator: f64, denominator: f64| {
+        if denominator > 0. {
+            numerator / denominator
+        } else {
+            0.
+        }
+    };
+    let voter_reports = voters
+        .iter()
+        .zip(selections.iter().zip(longest_gaps.iter()))
+        .map(|(vote_pubkey, (selections, longest_gap))| VoterReport {
+            vote_pubkey: vote_pubkey.to_string(),
+            stake: stakes.get(vote_pubkey).copied().unwrap_or(0),
+            selections: *selections,
+            frequency: ratio(*selections as f64, slots as f64),
+            longest_gap: *longest_gap,
+        })
+        .collect();
+
+    Report {
+        first_epoch,
+        epochs,
+        slots,
+        voters: voters.len(),
+        group_size: generator.group_size(),
+        algorithm: format!("{:?}", generator.algorithm()).to_lowercase(),
+        selection_mode: format!("{:?}", generator.selection_mode()),
+        expected_group_size: generator.expected_group_size(),
+        group_size_variance: generator.group_size_variance(),
+        average_group_size: ratio(total_group_size as f64, slots as f64),
+        min_group_size: if slots > 0 { min_group_size } else { 0 },
+        max_group_size,
+        coverage: ratio((voters.len() - never_selected) as f64, voters.len() as f64),
+        never_selected,
+        average_consecutive_overlap: ratio(total_overlap as f64, slots.saturating_sub(1) as f64),
+        max_consecutive_overlap,
+        quorum_threshold,
+        quorum_feasible_slots,
+        quorum_feasibility: ratio(quorum_feasible_slots as f64, slots as f64),
+        min_group_stake_fraction: if slots > 0 {
+            min_group_stake_fraction
+        } else {
+            0.
+        },
+        average_group_stake_fraction: ratio(total_group_stake_fraction, slots as f64),
+        voter_reports,
+    }
+}
+
+/// the per voter rows, the summary goes to stderr since it doesn't fit the columns
+fn write_csv<W: Write>(report: &Report, w: &mut W) -> io::Result<()> {
+    writeln!(w, "vote_pubkey,stake,selections,frequency,longest_gap")?;
+    for voter in &report.voter_reports {
+        writeln!(
+            w,
+            "{},{},{},{},{}",
+            voter.vote_pubkey, voter.stake, voter.selections, voter.frequency, voter.longest_gap
+        )?;
+    }
+    Ok(())
+}
+
+fn main() {
+    solana_logger::setup_with_default("solana=info");
+
+    let matches = App::new(crate_name!())
+        .about(crate_description!())
+        .version(solana_version::version!())
+        .arg(
+            Arg::with_name("voters")
+                .long("voters")
+                .value_name("FILE")
+                .takes_value(true)
+                .help(
+                    "JSON array of voters to simulate, \
+                     e.g. [{\"votePubkey\": \"...\", \"stake\": 100}]",
+                ),
+        )
+        .arg(
+            Arg::with_name("ledger_path")
+                .long("ledger")
+                .value_name("DIR")
+                .takes_value(true)
+                .help("Simulate the voters of the newest snapshot in this ledger"),
+        )
+        .group(
+            ArgGroup::with_name("voter_set")
+                .args(&["voters", "ledger_path"])
+                .required(true),
+        )
+        .arg(
+            Arg::with_name("epochs")
+                .long("epochs")
+                .value_name("EPOCHS")
+                .takes_value(true)
+                .default_value("1")
+                .validator(is_parsable::<u64>)
+                .help("Number of epochs to replay"),
+        )
+        .arg(
+            Arg::with_name("slots_per_epoch")
+                .long("slots-per-epoch")
+                .value_name("SLOTS")
+                .takes_value(true)
+                .validator(is_parsable::<u64>)
+                .help("Slots per simulated epoch [default: the snapshot's epoch schedule]"),
+        )
+        .arg(
+            Arg::with_name("group_size")
+                .long("group-size")
+                .value_name("SIZE")
+                .takes_value(true)
+                .validator(is_parsable::<usize>)
+                .help("Vote group size [default: 11]"),
+        )
+        .arg(
+            Arg::with_name("algorithm")
+                .long("algorithm")
+                .value_name("ALGORITHM")
+                .takes_value(true)
+                .possible_values(&["v1", "v2", "v3"])
+                .default_value("v1")
+                .help("Selection algorithm"),
+        )
+        .arg(
+            Arg::with_name("stake_weighted")
+                .long("stake-weighted")
+                .takes_value(false)
+                .help("Pick members in proportion to their stake"),
+        )
+        .arg(
+            Arg::with_name("binomial")
+                .long("binomial")
+                .takes_value(false)
+                .conflicts_with("stake_weighted")
+                .help(
+                    "Pick every voter on its own with a chance proportional to its stake, \
+                     the group size is only the expected size",
+                ),
+        )
+        .arg(
+            Arg::with_name("min_overlap")
+                .long("min-overlap")
+                .value_name("PERCENT")
+                .takes_value(true)
+                .conflicts_with_all(&["stake_weighted", "binomial"])
+                .validator(is_valid_percentage)
+                .help("Share of a group the groups of consecutive slots have to have in common"),
+        )
+        .arg(
+            Arg::with_name("distinct_members")
+                .long("distinct-members")
+                .takes_value(false)
+                .help("Never put the same voter in a v1 group twice"),
+        )
+        .arg(
+            Arg::with_name("quorum")
+                .long("quorum")
+                .value_name("PERCENT")
+                .takes_value(true)
+                .default_value("67")
+                .validator(is_valid_percentage)
+                .help("Share of the total stake a group has to hold to confirm a slot"),
+        )
+        .arg(
+            Arg::with_name("output_format")
+                .long("output")
+                .value_name("FORMAT")
+                .takes_value(true)
+                .possible_values(&["json", "csv"])
+                .default_value("json")
+                .help("Report format"),
+        )
+        .arg(
+            Arg::with_name("output_file")
+                .long("output-file")
+                .value_name("FILE")
+                .takes_value(true)
+                .help("Write the report here instead of stdout"),
+        )
+        .get_matches();
+
+    let voter_set = if let Some(voters) = matches.value_of("voters") {
+        load_voters_json(Path::new(voters))
+    } else {
+        load_voters_from_snapshot(Path::new(matches.value_of("ledger_path").unwrap()))
+    }
+    .unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        exit(1);
+    });
+    if voter_set.voters.is_empty() {
+        eprintln!("the voter set is empty");
+        exit(1);
+    }
+
+    let epoch_schedule = match value_t!(matches, "slots_per_epoch", u64) {
+        Ok(slots_per_epoch) => EpochSchedule::custom(slots_per_epoch, slots_per_epoch, false),
+        Err(_) => voter_set.epoch_schedule.unwrap_or_default(),
+    };
+    let group_size = value_t!(matches, "group_size", usize)
+        .unwrap_or(OPTIMAL_VOTE_GROUP_SIZE)
+        .min(voter_set.voters.len());
+    let algorithm = value_t_or_exit!(matches, "algorithm", SelectionAlgorithm);
+    let selection_mode = if matches.is_present("stake_weighted") {
+        SelectionMode::StakeWeighted
+    } else if matches.is_present("binomial") {
+        SelectionMode::Binomial
+    } else if let Ok(min_overlap_percent) = value_t!(matches, "min_overlap", u8) {
+        SelectionMode::Overlapping {
+            min_overlap_percent,
+        }
+    } else {
+        SelectionMode::Uniform
+    };
+    let exclusions = ExclusionList::legacy();
+    let generator = match selection_mode {
+        SelectionMode::Uniform => {
+            VoteGroupGenerator::new_with_exclusions(&voter_set.voters, group_size, &exclusions)
+        }
+        SelectionMode::StakeWeighted => VoteGroupGenerator::new_stake_weighted(
+            &voter_set.voters,
+            &voter_set.stakes,
+            group_size,
+            &exclusions,
+        ),
+        SelectionMode::Binomial => VoteGroupGenerator::new_binomial(
+            &voter_set.voters,
+            &voter_set.stakes,
+            group_size,
+            &exclusions,
+        ),
+        SelectionMode::Overlapping {
+            min_overlap_percent,
+        } => VoteGroupGenerator::new_with_exclusions(&voter_set.voters, group_size, &exclusions)
+            .with_min_overlap(min_overlap_percent),
+    }
+    .with_algorithm(algorithm)
+    .with_distinct_members(matches.is_present("distinct_members"));
+
+    let epochs = value_t_or_exit!(matches, "epochs", u64);
+    let quorum_threshold = value_t_or_exit!(matches, "quorum", u8) as f64 / 100.;
+    let report = simulate(
+        &generator,
+        &voter_set.stakes,
+        &epoch_schedule,
+        voter_set.epoch.unwrap_or(0),
+        epochs,
+        quorum_threshold,
+    );
+
+    let mut output: Box<dyn Write> = match matches.value_of("output_file") {
+        Some(path) => Box::new(File::create(path).unwrap_or_else(|err| {
+            eprintln!("unable to create {}: {}", path, err);
+            exit(1);
+        })),
+        None => Box::new(io::stdout()),
+    };
+    let result = match matches.value_of("output_format").unwrap() {
+        "csv" => {
+            eprintln!(
+                "{} slots, coverage {:.4}, average overlap {:.4}, quorum feasibility {:.4}",
+                report.slots,
+                report.coverage,
+                report.average_consecutive_overlap,
+                report.quorum_feasibility
+            );
+            write_csv(&report, &mut output)
+        }
+        _ => serde_json::to_writer_pretty(&mut output, &report)
+            .map_err(io::Error::from)
+            .and_then(|_| writeln!(output)),
+    };
+    if let Err(err) = result {
+        eprintln!("unable to write the report: {}", err);
+        exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn voter_set(stakes: &[u64]) -> VoterSet {
+        let json = serde_json::to_string(
+            &stakes
+                .iter()
+                .map(|stake| {
+                    serde_json::json!({
+                        "votePubkey": Pubkey::new_unique().to_string(),
+                        "stake": stake,
+                    })
+                })
+                .collect::<Vec<_>>(),
+        )
+        .unwrap();
+        parse_voters_json(&json).unwrap()
+    }
+
+    #[test]
+    fn test_parse_voters_json() {
+        let voter_set = voter_set(&[1, 2, 3]);
+        assert_eq!(voter_set.voters.len(), 3);
+        assert_eq!(voter_set.stakes.values().sum::<u64>(), 6);
+        assert!(parse_voters_json("[{\"votePubkey\": \"nope\"}]").is_err());
+        assert!(parse_voters_json("{}").is_err());
+    }
+
+    #[test]
+    fn test_simulate() {
+        let voter_set = voter_set(&[10; 20]);
+        let generator = VoteGroupGenerator::new_with_exclusions(
+            &voter_set.voters,
+            5,
+            &ExclusionList::default(),
+        )
+        .with_algorithm(SelectionAlgorithm::V2);
+        let epoch_schedule = EpochSchedule::custom(64, 64, false);
+        let report = simulate(&generator, &voter_set.stakes, &epoch_schedule, 0, 2, 0.67);
+        assert_eq!(report.slots, 128);
+        assert_eq!(report.voters, 20);
+        assert_eq!(
+            report
+                .voter_reports
+                .iter()
+                .map(|voter| voter.selections)
+                .sum::<u64>(),
+            128 * 5
+        );
+        // every group holds a quarter of the stake, never enough for the quorum
+        assert_eq!(report.quorum_feasible_slots, 0);
+        assert!((report.average_group_stake_fraction - 0.25).abs() < 1e-9);
+        assert!(report.max_consecutive_overlap <= 5);
+        assert!((report.average_group_size - 5.).abs() < 1e-9);
+        assert_eq!((report.min_group_size, report.max_group_size), (5, 5));
+        assert!(report.group_size_variance.abs() < f64::EPSILON);
+
+        let mut csv = vec![];
+        write_csv(&report, &mut csv).unwrap();
+        assert_eq!(String::from_utf8(csv).unwrap().lines().count(), 21);
+
+        let binomial = VoteGroupGenerator::new_binomial(
+            &voter_set.voters,
+            &voter_set.stakes,
+            5,
+            &ExclusionList::default(),
+        );
+        let report = simulate(&binomial, &voter_set.stakes, &epoch_schedule, 0, 2, 0.67);
+        assert_eq!(report.selection_mode, "Binomial");
+        assert!((report.expected_group_size - 5.).abs() < 1e-9);
+        assert!((report.group_size_variance - 20. * 0.25 * 0.75).abs() < 1e-9);
+        assert!(report.min_group_size < report.max_group_size);
+    }
+}